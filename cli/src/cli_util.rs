@@ -35,6 +35,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::SystemTime;
 
+use bstr::ByteSlice as _;
 use bstr::ByteVec as _;
 use chrono::TimeZone;
 use clap::builder::MapValueParser;
@@ -55,16 +56,24 @@ use jj_lib::backend::CommitId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::copies;
 use jj_lib::dag_walk;
 use jj_lib::file_util;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetExpression;
+#[cfg(target_os = "linux")]
+use jj_lib::fsmonitor::inotify;
+#[cfg(target_os = "linux")]
+use jj_lib::fsmonitor::FsmonitorSettings;
 use jj_lib::git;
 use jj_lib::git_backend::GitBackend;
 use jj_lib::gitignore::GitIgnoreError;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::hex_util::to_reverse_hex;
 use jj_lib::id_prefix::IdPrefixContext;
+#[cfg(target_os = "linux")]
+use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
@@ -108,6 +117,7 @@ use jj_lib::signing::SignInitError;
 use jj_lib::str_util::StringPattern;
 use jj_lib::transaction::Transaction;
 use jj_lib::view::View;
+use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
 use jj_lib::working_copy::LockedWorkingCopy;
 use jj_lib::working_copy::SnapshotOptions;
@@ -127,6 +137,7 @@ use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
 
 use crate::command_error::cli_error;
+use crate::command_error::config_error;
 use crate::command_error::config_error_with_message;
 use crate::command_error::handle_command_result;
 use crate::command_error::internal_error;
@@ -153,6 +164,7 @@ use crate::formatter::PlainTextFormatter;
 use crate::git_util::is_colocated_git_workspace;
 use crate::git_util::print_failed_git_export;
 use crate::git_util::print_git_import_stats;
+use crate::git_util::update_initialized_submodules;
 use crate::merge_tools::DiffEditor;
 use crate::merge_tools::MergeEditor;
 use crate::merge_tools::MergeToolConfigError;
@@ -166,6 +178,7 @@ use crate::template_parser::TemplateParseResult;
 use crate::templater::PropertyPlaceholder;
 use crate::templater::TemplateRenderer;
 use crate::text_util;
+use crate::ui::format_error_with_sources;
 use crate::ui::ColorChoice;
 use crate::ui::Ui;
 
@@ -353,6 +366,10 @@ impl CommandHelper {
             .map_err(Clone::clone)
     }
 
+    pub fn store_factories(&self) -> &StoreFactories {
+        &self.data.store_factories
+    }
+
     /// Loads workspace and repo, then snapshots the working copy if allowed.
     #[instrument(skip(self, ui))]
     pub fn workspace_helper(&self, ui: &Ui) -> Result<WorkspaceCommandHelper, CommandError> {
@@ -593,6 +610,7 @@ pub struct WorkspaceCommandEnvironment {
     path_converter: RepoPathUiConverter,
     workspace_id: WorkspaceId,
     short_prefixes_expression: Option<Rc<RevsetExpression>>,
+    conflict_marker_style: ConflictMarkerStyle,
 }
 
 impl WorkspaceCommandEnvironment {
@@ -612,6 +630,7 @@ impl WorkspaceCommandEnvironment {
             path_converter,
             workspace_id: workspace.workspace_id().to_owned(),
             short_prefixes_expression: None,
+            conflict_marker_style: command.settings().conflict_marker_style()?,
         };
         env.short_prefixes_expression = env.load_short_prefixes_expression()?;
         Ok(env)
@@ -629,6 +648,11 @@ impl WorkspaceCommandEnvironment {
         &self.workspace_id
     }
 
+    /// Style of the conflict markers written to files and shown in diffs.
+    pub fn conflict_marker_style(&self) -> ConflictMarkerStyle {
+        self.conflict_marker_style
+    }
+
     pub(crate) fn revset_parse_context(&self) -> RevsetParseContext {
         let workspace_context = RevsetWorkspaceContext {
             path_converter: &self.path_converter,
@@ -705,6 +729,7 @@ impl WorkspaceCommandEnvironment {
             &self.workspace_id,
             self.revset_parse_context(),
             id_prefix_context,
+            self.conflict_marker_style,
             &self.command.data.commit_template_extensions,
         )
     }
@@ -740,6 +765,9 @@ impl WorkspaceCommandHelper {
         let may_update_working_copy =
             loaded_at_head && !env.command.global_args().ignore_working_copy;
         let working_copy_shared_with_git = is_colocated_git_workspace(&workspace, &repo);
+        // Check the merge settings early to report errors before starting
+        // mutable operation.
+        repo.settings().merge_detect_renames()?;
         let helper = Self {
             workspace,
             user_repo: ReadonlyUserRepo::new(repo),
@@ -758,6 +786,17 @@ impl WorkspaceCommandHelper {
         self.env.settings()
     }
 
+    pub fn conflict_marker_style(&self) -> ConflictMarkerStyle {
+        self.env.conflict_marker_style()
+    }
+
+    /// Options for updating files in the working copy.
+    pub fn checkout_options(&self) -> CheckoutOptions {
+        CheckoutOptions {
+            conflict_marker_style: self.conflict_marker_style(),
+        }
+    }
+
     pub fn git_backend(&self) -> Option<&GitBackend> {
         self.user_repo.git_backend()
     }
@@ -1004,7 +1043,9 @@ impl WorkspaceCommandHelper {
                 base: "".into(),
             },
         )?;
-        Ok(expression.to_matcher())
+        expression
+            .to_matcher()
+            .map_err(|err| config_error_with_message("Invalid `snapshot.auto-track`", err))
     }
 
     pub(crate) fn path_converter(&self) -> &RepoPathUiConverter {
@@ -1055,7 +1096,12 @@ impl WorkspaceCommandHelper {
 
     /// Creates textual diff renderer of the specified `formats`.
     pub fn diff_renderer(&self, formats: Vec<DiffFormat>) -> DiffRenderer<'_> {
-        DiffRenderer::new(self.repo().as_ref(), self.path_converter(), formats)
+        DiffRenderer::new(
+            self.repo().as_ref(),
+            self.path_converter(),
+            self.conflict_marker_style(),
+            formats,
+        )
     }
 
     /// Loads textual diff renderer from the settings and command arguments.
@@ -1076,6 +1122,15 @@ impl WorkspaceCommandHelper {
         patch: bool,
     ) -> Result<Option<DiffRenderer<'_>>, CommandError> {
         let formats = diff_util::diff_formats_for_log(self.settings(), args, patch)?;
+        if formats.len() > 1
+            && formats
+                .iter()
+                .any(|format| matches!(format, DiffFormat::Json(_)))
+        {
+            return Err(user_error(
+                "--json can't be combined with other diff formats in log-like commands",
+            ));
+        }
         Ok((!formats.is_empty()).then(|| self.diff_renderer(formats)))
     }
 
@@ -1402,6 +1457,11 @@ impl WorkspaceCommandHelper {
         // Compare working-copy tree and operation with repo's, and reload as needed.
         let fsmonitor_settings = self.settings().fsmonitor_settings()?;
         let max_new_file_size = self.settings().max_new_file_size()?;
+        let conflict_marker_style = self.conflict_marker_style();
+        #[cfg(target_os = "linux")]
+        if fsmonitor_settings == FsmonitorSettings::Inotify {
+            self.ensure_inotify_daemon(ui)?;
+        }
         let command = self.env.command.clone();
         let mut locked_ws = self.workspace.start_working_copy_mutation()?;
         let old_op_id = locked_ws.locked_wc().old_operation_id().clone();
@@ -1455,6 +1515,7 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
             progress: progress.as_ref().map(|x| x as _),
             start_tracking_matcher: &auto_tracking_matcher,
             max_new_file_size,
+            conflict_marker_style,
         })?;
         drop(progress);
         if new_tree_id != *wc_commit.tree_id() {
@@ -1465,9 +1526,16 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
             );
             tx.set_is_snapshot(true);
             let mut_repo = tx.repo_mut();
+            // The working copy reports copies relative to the tree it had
+            // when it was locked, which is the working-copy commit's tree.
+            let copies = copies::compose_copies(
+                &wc_commit.store_commit().copies,
+                &locked_ws.locked_wc().snapshot_copies(),
+            );
             let commit = mut_repo
                 .rewrite_commit(command.settings(), &wc_commit)
                 .set_tree_id(new_tree_id)
+                .set_copies(copies)
                 .write()?;
             mut_repo.set_wc_commit(workspace_id, commit.id().clone())?;
 
@@ -1491,6 +1559,50 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
         Ok(())
     }
 
+    /// Starts the inotify daemon in the background unless it's already
+    /// watching the working copy. Failures are reported as warnings, in which
+    /// case the snapshot falls back to scanning the whole working copy.
+    #[cfg(target_os = "linux")]
+    fn ensure_inotify_daemon(&self, ui: &Ui) -> Result<(), CommandError> {
+        use std::os::unix::process::CommandExt as _;
+        use std::process::Stdio;
+        use std::time::Duration;
+
+        let Some(wc) = self
+            .working_copy()
+            .as_any()
+            .downcast_ref::<LocalWorkingCopy>()
+        else {
+            return Ok(());
+        };
+        let fsmonitor = wc.inotify_fsmonitor();
+        let start_daemon = || -> Result<(), inotify::Error> {
+            if fsmonitor.is_daemon_running()? {
+                return Ok(());
+            }
+            let current_exe = env::current_exe().map_err(inotify::Error::Spawn)?;
+            let mut daemon = std::process::Command::new(current_exe);
+            daemon
+                .args([
+                    "debug",
+                    "fsmonitor",
+                    "daemon",
+                    "--ignore-working-copy",
+                    "-R",
+                ])
+                .arg(self.workspace_root())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .process_group(0);
+            fsmonitor.start_daemon(daemon, Duration::from_secs(1))
+        };
+        if let Err(err) = start_daemon() {
+            writeln!(ui.warning_default(), "{}", format_error_with_sources(&err))?;
+        }
+        Ok(())
+    }
+
     fn update_working_copy(
         &mut self,
         ui: &Ui,
@@ -1498,11 +1610,13 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
         new_commit: &Commit,
     ) -> Result<(), CommandError> {
         assert!(self.may_update_working_copy);
+        let checkout_options = self.checkout_options();
         let stats = update_working_copy(
             &self.user_repo.repo,
             &mut self.workspace,
             maybe_old_commit,
             new_commit,
+            &checkout_options,
         )?;
         if Some(new_commit) != maybe_old_commit {
             if let Some(mut formatter) = ui.status_formatter() {
@@ -1521,6 +1635,26 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
         }
         if let Some(stats) = stats {
             print_checkout_stats(ui, stats, new_commit)?;
+            // The checkout has already succeeded, so failing to update the
+            // submodules shouldn't fail the command.
+            let update_submodules = || {
+                let old_tree = maybe_old_commit.map(|commit| commit.tree()).transpose()?;
+                update_initialized_submodules(
+                    ui,
+                    self.repo(),
+                    self.workspace_id(),
+                    self.workspace_root(),
+                    old_tree.as_ref(),
+                    &new_commit.tree()?,
+                )
+            };
+            if let Err(err) = update_submodules() {
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to update submodules: {}",
+                    format_error_with_sources(err.error.as_ref())
+                )?;
+            }
         }
         if Some(new_commit) != maybe_old_commit {
             if let Some(mut formatter) = ui.status_formatter() {
@@ -1559,6 +1693,20 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
         if num_rebased > 0 {
             writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
         }
+        if let Some(resolution_store) = tx.repo().store().resolution_store() {
+            for path in resolution_store
+                .take_applied_paths()
+                .iter()
+                .sorted()
+                .dedup()
+            {
+                writeln!(
+                    ui.status(),
+                    "Reused recorded conflict resolutions in {}",
+                    self.format_file_path(path)
+                )?;
+            }
+        }
 
         for (workspace_id, wc_commit_id) in tx.repo().view().wc_commit_ids().clone().iter().sorted()
         //sorting otherwise non deterministic order (bad for tests)
@@ -1989,6 +2137,7 @@ jj git init --colocate",
         WorkspaceLoadError::StoreLoadError(StoreLoadError::Signing(
             err @ SignInitError::UnknownBackend(_),
         )) => user_error(err),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::Config(err)) => config_error(err),
         WorkspaceLoadError::StoreLoadError(err) => internal_error(err),
         WorkspaceLoadError::WorkingCopyState(err) => internal_error(err),
         WorkspaceLoadError::NonUnicodePath | WorkspaceLoadError::Path(_) => user_error(err),
@@ -2275,13 +2424,19 @@ pub fn update_working_copy(
     workspace: &mut Workspace,
     old_commit: Option<&Commit>,
     new_commit: &Commit,
+    options: &CheckoutOptions,
 ) -> Result<Option<CheckoutStats>, CommandError> {
     let old_tree_id = old_commit.map(|commit| commit.tree_id().clone());
     let stats = if Some(new_commit.tree_id()) != old_tree_id.as_ref() {
         // TODO: CheckoutError::ConcurrentCheckout should probably just result in a
         // warning for most commands (but be an error for the checkout command)
         let stats = workspace
-            .check_out(repo.op_id().clone(), old_tree_id.as_ref(), new_commit)
+            .check_out(
+                repo.op_id().clone(),
+                old_tree_id.as_ref(),
+                new_commit,
+                options,
+            )
             .map_err(|err| {
                 internal_error_with_message(
                     format!("Failed to check out commit {}", new_commit.id().hex()),
@@ -2375,6 +2530,31 @@ impl LogContentFormat {
     }
 }
 
+/// Writes the template output of a log-like command whose diffs are printed as
+/// JSON lines. The template output is checked to be JSON lines as well so the
+/// whole output can be parsed.
+pub fn write_json_lines_template(
+    formatter: &mut dyn Formatter,
+    content_fn: impl FnOnce(&mut dyn Formatter) -> std::io::Result<()>,
+) -> Result<(), CommandError> {
+    let mut buffer = vec![];
+    content_fn(&mut PlainTextFormatter::new(&mut buffer))?;
+    if !buffer.is_empty() && !buffer.ends_with(b"\n") {
+        buffer.push(b'\n');
+    }
+    if buffer
+        .lines()
+        .any(|line| serde_json::from_slice::<serde_json::Value>(line).is_err())
+    {
+        return Err(user_error_with_hint(
+            "The template output isn't JSON, which is required with --json",
+            r#"Use a template which prints one JSON value per line, e.g. `-T 'json(self) ++ "\n"'`"#,
+        ));
+    }
+    formatter.write_all(&buffer)?;
+    Ok(())
+}
+
 pub fn get_new_config_file_path(
     config_source: &ConfigSource,
     command: &CommandHelper,
@@ -21,6 +21,7 @@ use std::str;
 use std::sync::Arc;
 
 use itertools::Itertools as _;
+use jj_lib::absorb::AbsorbError;
 use jj_lib::backend::BackendError;
 use jj_lib::fileset::FilePatternParseError;
 use jj_lib::fileset::FilesetParseError;
@@ -29,11 +30,16 @@ use jj_lib::git::GitConfigParseError;
 use jj_lib::git::GitExportError;
 use jj_lib::git::GitImportError;
 use jj_lib::git::GitRemoteManagementError;
+use jj_lib::git::GitSubmoduleError;
+use jj_lib::git::GitTagError;
 use jj_lib::gitignore::GitIgnoreError;
+use jj_lib::native_sync::NativeSyncError;
 use jj_lib::op_heads_store::OpHeadResolutionError;
 use jj_lib::op_store::OpStoreError;
 use jj_lib::op_walk::OpsetEvaluationError;
 use jj_lib::op_walk::OpsetResolutionError;
+use jj_lib::patch::PatchApplyError;
+use jj_lib::patch::PatchParseError;
 use jj_lib::repo::CheckOutCommitError;
 use jj_lib::repo::EditCommitError;
 use jj_lib::repo::RepoLoaderError;
@@ -296,6 +302,23 @@ impl From<WorkspaceInitError> for CommandError {
             }
             WorkspaceInitError::SignInit(err @ SignInitError::UnknownBackend(_)) => user_error(err),
             WorkspaceInitError::SignInit(err) => internal_error(err),
+            WorkspaceInitError::Config(err) => config_error(err),
+        }
+    }
+}
+
+impl From<NativeSyncError> for CommandError {
+    fn from(err: NativeSyncError) -> Self {
+        match err {
+            NativeSyncError::IncompatibleBackend { .. }
+            | NativeSyncError::ResultCountMismatch { .. }
+            | NativeSyncError::Transport(_) => user_error(err),
+            NativeSyncError::RemoteBookmarkMoved(_) => user_error_with_hint(
+                err,
+                "Run `jj sync fetch` to update the remote bookmarks, then try again.",
+            ),
+            NativeSyncError::ObjectIdMismatch { .. } => internal_error(err),
+            NativeSyncError::Backend(err) => err.into(),
         }
     }
 }
@@ -475,6 +498,44 @@ impl From<GitRemoteManagementError> for CommandError {
     }
 }
 
+impl From<GitTagError> for CommandError {
+    fn from(err: GitTagError) -> Self {
+        match err {
+            GitTagError::UnexpectedBackend => user_error(err),
+            GitTagError::Backend(err) => err.into(),
+            GitTagError::UpdateRef(..) => internal_error(err),
+        }
+    }
+}
+
+impl From<AbsorbError> for CommandError {
+    fn from(err: AbsorbError) -> Self {
+        match err {
+            AbsorbError::Backend(err) => err.into(),
+            AbsorbError::RevsetEvaluation(err) => err.into(),
+        }
+    }
+}
+
+impl From<PatchParseError> for CommandError {
+    fn from(err: PatchParseError) -> Self {
+        user_error_with_message("Failed to parse patch", err)
+    }
+}
+
+impl From<PatchApplyError> for CommandError {
+    fn from(err: PatchApplyError) -> Self {
+        match err {
+            PatchApplyError::Backend(err) => err.into(),
+            PatchApplyError::MissingFile(_)
+            | PatchApplyError::NotAFile(_)
+            | PatchApplyError::InvalidSubmoduleCommit(_) => {
+                user_error_with_message("Failed to apply patch", err)
+            }
+        }
+    }
+}
+
 impl From<RevsetEvaluationError> for CommandError {
     fn from(err: RevsetEvaluationError) -> Self {
         user_error(err)
@@ -550,6 +611,20 @@ impl From<GitConfigParseError> for CommandError {
     }
 }
 
+impl From<GitSubmoduleError> for CommandError {
+    fn from(err: GitSubmoduleError) -> Self {
+        match err {
+            GitSubmoduleError::SubmoduleStore(_)
+            | GitSubmoduleError::NotInitialized(_)
+            | GitSubmoduleError::CommitNotFound { .. }
+            | GitSubmoduleError::UnmanagedGitDir { .. } => user_error(err),
+            GitSubmoduleError::Io { .. } | GitSubmoduleError::InternalGitError(_) => {
+                user_error_with_message("Failed to update submodule", err)
+            }
+        }
+    }
+}
+
 impl From<WorkingCopyStateError> for CommandError {
     fn from(err: WorkingCopyStateError) -> Self {
         internal_error_with_message("Failed to access working copy state", err)
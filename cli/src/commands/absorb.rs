@@ -0,0 +1,122 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+
+use jj_lib::absorb::absorb_hunks;
+use jj_lib::absorb::split_hunks_to_trees;
+use jj_lib::absorb::AbsorbSource;
+use jj_lib::object_id::ObjectId;
+use jj_lib::revset::RevsetExpression;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Move changes from a revision into the stack of mutable revisions
+///
+/// This command splits the changes in the source revision and moves each change
+/// into the closest mutable ancestor where the surrounding lines were last
+/// modified. If the destination revision can't be determined unambiguously,
+/// the change is left in the source revision. Only changes to files that exist
+/// on both sides are considered.
+///
+/// The descendants of the destination revisions, including the source
+/// revision, are rebased. The changes moved out are removed from the source
+/// revision as it's rebased, but the source revision itself is kept even if it
+/// becomes empty.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct AbsorbArgs {
+    /// Source revision to absorb from
+    #[arg(long, short, default_value = "@")]
+    from: RevisionArg,
+    /// Destination revisions to absorb into
+    ///
+    /// Only ancestors of the source revision are considered.
+    #[arg(
+        long,
+        short = 't',
+        visible_alias = "to",
+        default_value = "mutable()",
+        value_name = "REVISIONS"
+    )]
+    into: Vec<RevisionArg>,
+    /// Move only changes to these paths (instead of all paths)
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_absorb(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &AbsorbArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let source_commit = workspace_command.resolve_single_rev(&args.from)?;
+    let destination_ids = workspace_command
+        .parse_union_revsets(&args.into)?
+        .evaluate_to_commit_ids()?
+        .collect();
+    let destinations = RevsetExpression::commits(destination_ids)
+        .intersection(&RevsetExpression::commit(source_commit.id().clone()).ancestors())
+        .minus(&RevsetExpression::commit(source_commit.id().clone()));
+    let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
+
+    let repo = workspace_command.repo().as_ref();
+    let source = AbsorbSource::from_commit(repo, source_commit)?;
+    let matcher =
+        fileset_expression.to_diff_matcher(source.parent_tree(), &source.commit().tree()?)?;
+    let selected_trees = split_hunks_to_trees(repo, &source, &destinations, matcher.as_ref())?;
+    for (path, reason) in &selected_trees.skipped_paths {
+        let ui_path = workspace_command.format_file_path(path);
+        writeln!(ui.warning_default(), "{ui_path}: {reason}")?;
+    }
+    if selected_trees.target_commits.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(selected_trees.target_commits.keys())?;
+
+    let mut tx = workspace_command.start_transaction();
+    let stats = absorb_hunks(
+        tx.repo_mut(),
+        &source,
+        selected_trees.target_commits,
+        command.settings(),
+    )?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Absorbed changes into these revisions:")?;
+        let template = tx.commit_summary_template();
+        for commit in stats.rewritten_destinations.iter().rev() {
+            write!(formatter, "  ")?;
+            template.format(commit, formatter.as_mut())?;
+            writeln!(formatter)?;
+        }
+        if stats.num_rebased > 0 {
+            writeln!(
+                formatter,
+                "Rebased {} descendant commits.",
+                stats.num_rebased
+            )?;
+        }
+    }
+    tx.finish(
+        ui,
+        format!("absorb changes from commit {}", source.commit().id().hex()),
+    )?;
+    Ok(())
+}
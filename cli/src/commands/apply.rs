@@ -0,0 +1,107 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use jj_lib::patch::apply_patch;
+use jj_lib::patch::parse_patches;
+use jj_lib::repo::Repo;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Apply patch files as new revisions
+///
+/// Each patch is applied in a new revision on top of the previous one,
+/// starting at the destination revision. Mailbox files, as written by `jj
+/// export-patch` or `git format-patch`, can contain several patches, and the
+/// author and the description of each revision are taken from the mail. Other
+/// files are applied as a single patch in the unified or the Git diff format.
+///
+/// Hunks that don't apply to the destination are recorded as conflicts in the
+/// new revision instead of being rejected.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ApplyArgs {
+    /// The patch files to apply
+    #[arg(required = true, value_hint = clap::ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+    /// The revision to apply the patches on top of
+    #[arg(long, short, default_value = "@")]
+    destination: RevisionArg,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_apply(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ApplyArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let destination = workspace_command.resolve_single_rev(&args.destination)?;
+    let mut patches = vec![];
+    for path in &args.files {
+        let data = fs::read(command.cwd().join(path)).map_err(|err| {
+            user_error_with_message(format!("Failed to read {}", path.display()), err)
+        })?;
+        let file_patches = parse_patches(&data).map_err(|err| {
+            user_error_with_message(format!("Failed to parse {}", path.display()), err)
+        })?;
+        patches.extend(file_patches);
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let store = tx.repo().store().clone();
+    let mut parent = destination;
+    let mut new_commits = vec![];
+    for patch in &patches {
+        let tree_id = apply_patch(&store, &parent.tree()?, patch)?;
+        let mut commit_builder = tx
+            .repo_mut()
+            .new_commit(command.settings(), vec![parent.id().clone()], tree_id)
+            .set_description(&patch.description);
+        if let Some(author) = &patch.author {
+            commit_builder = commit_builder.set_author(author.clone());
+        }
+        let new_commit = commit_builder.write()?;
+        new_commits.push(new_commit.clone());
+        parent = new_commit;
+    }
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Applied {} patches:", new_commits.len())?;
+        for commit in &new_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    let mut has_conflict = false;
+    for commit in &new_commits {
+        has_conflict |= commit.has_conflict()?;
+    }
+    if has_conflict {
+        writeln!(
+            ui.warning_default(),
+            "Some hunks couldn't be applied and were recorded as conflicts"
+        )?;
+    }
+    tx.finish(ui, format!("apply {} patches", new_commits.len()))?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId as _;
+
+use super::bisect_state;
+use super::check_out_next_step;
+use super::mark_commits;
+use super::BisectMark;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Mark revisions as good
+#[derive(clap::Args, Clone, Debug)]
+pub struct BisectGoodArgs {
+    /// The revisions to mark (default: the revision being tested)
+    #[arg(long, short, default_value = "@-")]
+    revisions: Vec<RevisionArg>,
+}
+
+/// Mark revisions as bad
+#[derive(clap::Args, Clone, Debug)]
+pub struct BisectBadArgs {
+    /// The revisions to mark (default: the revision being tested)
+    #[arg(long, short, default_value = "@-")]
+    revisions: Vec<RevisionArg>,
+}
+
+/// Skip revisions that can't be tested
+#[derive(clap::Args, Clone, Debug)]
+pub struct BisectSkipArgs {
+    /// The revisions to skip (default: the revision being tested)
+    #[arg(long, short, default_value = "@-")]
+    revisions: Vec<RevisionArg>,
+}
+
+pub fn cmd_bisect_good(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectGoodArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, &args.revisions, BisectMark::Good)
+}
+
+pub fn cmd_bisect_bad(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectBadArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, &args.revisions, BisectMark::Bad)
+}
+
+pub fn cmd_bisect_skip(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectSkipArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, &args.revisions, BisectMark::Skip)
+}
+
+fn mark_revisions(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    revisions: &[RevisionArg],
+    mark: BisectMark,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut state = bisect_state(workspace_command.repo().as_ref())?;
+    let commit_ids: Vec<CommitId> = workspace_command
+        .parse_union_revsets(revisions)?
+        .evaluate_to_commit_ids()?
+        .collect();
+
+    let mut tx = workspace_command.start_transaction();
+    mark_commits(&mut state, &commit_ids, mark);
+    tx.repo_mut().set_bisect_state(Some(state.clone()));
+    check_out_next_step(ui, &mut tx, &state)?;
+    let transaction_description = match &commit_ids[..] {
+        [commit_id] => format!("mark commit {} as {}", commit_id.hex(), mark.as_str()),
+        _ => format!("mark {} commits as {}", commit_ids.len(), mark.as_str()),
+    };
+    tx.finish(ui, transaction_description)?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod mark;
+mod reset;
+mod run;
+mod start;
+
+use std::io;
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectionStep;
+use jj_lib::commit::Commit;
+use jj_lib::op_store::BisectState;
+use jj_lib::repo::Repo;
+
+use self::mark::cmd_bisect_bad;
+use self::mark::cmd_bisect_good;
+use self::mark::cmd_bisect_skip;
+use self::mark::BisectBadArgs;
+use self::mark::BisectGoodArgs;
+use self::mark::BisectSkipArgs;
+use self::reset::cmd_bisect_reset;
+use self::reset::BisectResetArgs;
+use self::run::cmd_bisect_run;
+use self::run::BisectRunArgs;
+use self::start::cmd_bisect_start;
+use self::start::BisectStartArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandTransaction;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::formatter::Formatter;
+use crate::ui::Ui;
+
+/// Find the revision that introduced a bug by bisection
+///
+/// Start with `jj bisect start --good <revisions> --bad <revisions>`. At each
+/// step, a new working-copy commit is created on top of a revision halfway
+/// between the good and bad revisions, as `jj new` would do. Test it, and mark
+/// it with `jj bisect good`, `jj bisect bad`, or `jj bisect skip` until the
+/// first bad revision is found. `jj bisect run` automates the testing with a
+/// command.
+///
+/// The bisection state is recorded in the operation log, so `jj undo` undoes
+/// the last marking. Use `jj bisect reset` to end the bisection.
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum BisectCommand {
+    Bad(BisectBadArgs),
+    Good(BisectGoodArgs),
+    Reset(BisectResetArgs),
+    Run(BisectRunArgs),
+    Skip(BisectSkipArgs),
+    Start(BisectStartArgs),
+}
+
+pub fn cmd_bisect(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &BisectCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        BisectCommand::Bad(args) => cmd_bisect_bad(ui, command, args),
+        BisectCommand::Good(args) => cmd_bisect_good(ui, command, args),
+        BisectCommand::Reset(args) => cmd_bisect_reset(ui, command, args),
+        BisectCommand::Run(args) => cmd_bisect_run(ui, command, args),
+        BisectCommand::Skip(args) => cmd_bisect_skip(ui, command, args),
+        BisectCommand::Start(args) => cmd_bisect_start(ui, command, args),
+    }
+}
+
+/// How a revision behaved when it was tested.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum BisectMark {
+    Good,
+    Bad,
+    Skip,
+}
+
+impl BisectMark {
+    fn as_str(self) -> &'static str {
+        match self {
+            BisectMark::Good => "good",
+            BisectMark::Bad => "bad",
+            BisectMark::Skip => "skipped",
+        }
+    }
+}
+
+fn bisect_state(repo: &dyn Repo) -> Result<BisectState, CommandError> {
+    repo.view().bisect_state().cloned().ok_or_else(|| {
+        user_error_with_hint(
+            "No bisection in progress",
+            "Use `jj bisect start` to start one.",
+        )
+    })
+}
+
+/// Records the commits as good, bad, or skipped. A commit that was previously
+/// marked differently is only kept with the new mark.
+fn mark_commits(state: &mut BisectState, commit_ids: &[CommitId], mark: BisectMark) {
+    for list in [&mut state.good, &mut state.bad, &mut state.skipped] {
+        list.retain(|id| !commit_ids.contains(id));
+    }
+    let list = match mark {
+        BisectMark::Good => &mut state.good,
+        BisectMark::Bad => &mut state.bad,
+        BisectMark::Skip => &mut state.skipped,
+    };
+    list.extend(commit_ids.iter().cloned());
+}
+
+/// Prints the next step of the bisection, and checks out the revision to test
+/// if there is one.
+fn check_out_next_step(
+    ui: &mut Ui,
+    tx: &mut WorkspaceCommandTransaction,
+    state: &BisectState,
+) -> Result<(), CommandError> {
+    match state.next_step(tx.repo())? {
+        BisectionStep::Test {
+            commit_id,
+            num_untested,
+        } => {
+            let commit = tx.repo().store().get_commit(&commit_id)?;
+            if let Some(mut formatter) = ui.status_formatter() {
+                writeln!(
+                    formatter,
+                    "Bisecting: {num_untested} revisions left to test"
+                )?;
+                write!(formatter, "Now testing: ")?;
+                tx.write_commit_summary(formatter.as_mut(), &commit)?;
+                writeln!(formatter)?;
+            }
+            tx.check_out(&commit)?;
+        }
+        BisectionStep::Done(candidate_ids) => {
+            let candidates: Vec<_> = candidate_ids
+                .iter()
+                .map(|id| tx.repo().store().get_commit(id))
+                .try_collect()?;
+            write_bisection_result(ui, &candidates, |formatter, commit| {
+                tx.write_commit_summary(formatter, commit)
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn write_bisection_result(
+    ui: &Ui,
+    candidates: &[Commit],
+    write_commit_summary: impl Fn(&mut dyn Formatter, &Commit) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut formatter = ui.stdout_formatter();
+    match candidates {
+        [] => writeln!(formatter, "No bad revision found")?,
+        [commit] => {
+            write!(formatter, "The first bad revision is: ")?;
+            write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+        _ => {
+            writeln!(formatter, "The first bad revision could be any of:")?;
+            for commit in candidates {
+                write!(formatter, "  ")?;
+                write_commit_summary(formatter.as_mut(), commit)?;
+                writeln!(formatter)?;
+            }
+        }
+    }
+    Ok(())
+}
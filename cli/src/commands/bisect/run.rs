@@ -0,0 +1,139 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::bisect::BisectionStep;
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::working_copy::SnapshotOptions;
+
+use super::bisect_state;
+use super::mark_commits;
+use super::write_bisection_result;
+use super::BisectMark;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::commands::run::run_in_revision;
+use crate::ui::Ui;
+
+/// Test revisions with a command until the first bad revision is found
+///
+/// The command is run through the shell in a temporary working copy of each
+/// revision to test, as with `jj run`, so the working copy is left untouched.
+/// The exit status of the command decides how the revision is marked:
+///  - 0: good
+///  - 125: skipped
+///  - 1-127 (except 125): bad
+///
+/// Any other exit status, or termination by a signal, stops the bisection.
+#[derive(clap::Args, Clone, Debug)]
+#[command(verbatim_doc_comment)]
+pub struct BisectRunArgs {
+    /// The command to run in each revision
+    shell_command: String,
+}
+
+pub fn cmd_bisect_run(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectRunArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut state = bisect_state(workspace_command.repo().as_ref())?;
+
+    let snapshot_options = SnapshotOptions {
+        base_ignores: workspace_command.base_ignores()?,
+        fsmonitor_settings: FsmonitorSettings::None,
+        progress: None,
+        start_tracking_matcher: &workspace_command.auto_tracking_matcher()?,
+        max_new_file_size: command.settings().max_new_file_size()?,
+        conflict_marker_style: workspace_command.conflict_marker_style(),
+    };
+    let run_dir = workspace_command.repo_path().join("run");
+    std::fs::create_dir_all(&run_dir).map_err(|err| {
+        user_error_with_message(
+            format!("Failed to create directory {}", run_dir.display()),
+            err,
+        )
+    })?;
+
+    loop {
+        let repo = workspace_command.repo().clone();
+        let commit_id = match state.next_step(repo.as_ref())? {
+            BisectionStep::Test {
+                commit_id,
+                num_untested,
+            } => {
+                writeln!(
+                    ui.status(),
+                    "Bisecting: {num_untested} revisions left to test"
+                )?;
+                commit_id
+            }
+            BisectionStep::Done(candidate_ids) => {
+                let candidates: Vec<_> = candidate_ids
+                    .iter()
+                    .map(|id| repo.store().get_commit(id))
+                    .try_collect()?;
+                write_bisection_result(ui, &candidates, |formatter, commit| {
+                    workspace_command.write_commit_summary(formatter, commit)
+                })?;
+                return Ok(());
+            }
+        };
+        let commit = repo.store().get_commit(&commit_id)?;
+        let result = run_in_revision(
+            repo.store(),
+            &run_dir,
+            &commit,
+            &args.shell_command,
+            &snapshot_options,
+        )?;
+        ui.stdout().write_all(&result.output.stdout)?;
+        ui.stderr().write_all(&result.output.stderr)?;
+        let mark = match result.output.status.code() {
+            Some(0) => BisectMark::Good,
+            Some(125) => BisectMark::Skip,
+            Some(1..=127) => BisectMark::Bad,
+            Some(code) => {
+                return Err(user_error(format!(
+                    "The command exited with status {code}, stopping the bisection"
+                )));
+            }
+            None => {
+                return Err(user_error(
+                    "The command was terminated by a signal, stopping the bisection",
+                ));
+            }
+        };
+        if let Some(mut formatter) = ui.status_formatter() {
+            write!(formatter, "Marked as {}: ", mark.as_str())?;
+            workspace_command.write_commit_summary(formatter.as_mut(), &commit)?;
+            writeln!(formatter)?;
+        }
+
+        let mut tx = workspace_command.start_transaction();
+        mark_commits(&mut state, std::slice::from_ref(&commit_id), mark);
+        tx.repo_mut().set_bisect_state(Some(state.clone()));
+        tx.finish(
+            ui,
+            format!("mark commit {} as {}", commit_id.hex(), mark.as_str()),
+        )?;
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::op_store::BisectState;
+
+use super::check_out_next_step;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Start a bisection between good and bad revisions
+///
+/// The first bad revision is searched among the ancestors of the bad revisions
+/// that aren't ancestors of the good revisions.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BisectStartArgs {
+    /// Revisions known to be good
+    #[arg(long, required = true)]
+    good: Vec<RevisionArg>,
+    /// Revisions known to be bad
+    #[arg(long, required = true)]
+    bad: Vec<RevisionArg>,
+}
+
+pub fn cmd_bisect_start(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectStartArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    if workspace_command.repo().view().bisect_state().is_some() {
+        return Err(user_error_with_hint(
+            "A bisection is already in progress",
+            "Use `jj bisect reset` to end it first.",
+        ));
+    }
+    let good_ids = workspace_command
+        .parse_union_revsets(&args.good)?
+        .evaluate_to_commit_ids()?
+        .collect();
+    let bad_ids: Vec<_> = workspace_command
+        .parse_union_revsets(&args.bad)?
+        .evaluate_to_commit_ids()?
+        .collect();
+    if bad_ids.is_empty() {
+        return Err(user_error("No bad revisions specified"));
+    }
+    let state = BisectState::new(good_ids, bad_ids);
+
+    let mut tx = workspace_command.start_transaction();
+    tx.repo_mut().set_bisect_state(Some(state.clone()));
+    check_out_next_step(ui, &mut tx, &state)?;
+    tx.finish(ui, "start bisection")?;
+    Ok(())
+}
@@ -64,14 +64,14 @@ pub(crate) fn cmd_commit(
         .get_wc_commit_id()
         .ok_or_else(|| user_error("This command requires a working copy"))?;
     let commit = workspace_command.repo().store().get_commit(commit_id)?;
-    let matcher = workspace_command
-        .parse_file_patterns(&args.paths)?
-        .to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
     let advanceable_branches = workspace_command.get_advanceable_bookmarks(commit.parent_ids())?;
     let diff_selector =
         workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
     let mut tx = workspace_command.start_transaction();
     let base_tree = commit.parent_tree(tx.repo())?;
+    let tree = commit.tree()?;
+    let matcher = fileset_expression.to_diff_matcher(&base_tree, &tree)?;
     let format_instructions = || {
         format!(
             "\
@@ -84,12 +84,7 @@ new working-copy commit.
             tx.format_commit_summary(&commit)
         )
     };
-    let tree_id = diff_selector.select(
-        &base_tree,
-        &commit.tree()?,
-        matcher.as_ref(),
-        format_instructions,
-    )?;
+    let tree_id = diff_selector.select(&base_tree, &tree, matcher.as_ref(), format_instructions)?;
     if !args.paths.is_empty() && tree_id == base_tree.id() {
         writeln!(
             ui.warning_default(),
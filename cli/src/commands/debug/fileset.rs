@@ -41,8 +41,11 @@ pub fn cmd_debug_fileset(
     writeln!(ui.stdout(), "{expression:#?}")?;
     writeln!(ui.stdout())?;
 
-    let matcher = expression.to_matcher();
     writeln!(ui.stdout(), "-- Matcher:")?;
-    writeln!(ui.stdout(), "{matcher:#?}")?;
+    match expression.to_matcher() {
+        Ok(matcher) => writeln!(ui.stdout(), "{matcher:#?}")?,
+        // File predicates need trees to be evaluated
+        Err(err) => writeln!(ui.stdout(), "{err}")?,
+    }
     Ok(())
 }
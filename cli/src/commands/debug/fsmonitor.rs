@@ -0,0 +1,143 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+#[cfg(target_os = "linux")]
+use std::io::Write as _;
+
+use clap::Subcommand;
+#[cfg(target_os = "linux")]
+use jj_lib::fsmonitor::FsmonitorSettings;
+
+#[cfg(target_os = "linux")]
+use super::check_local_disk_wc;
+use crate::cli_util::CommandHelper;
+#[cfg(target_os = "linux")]
+use crate::command_error::internal_error_with_message;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum DebugFsmonitorCommand {
+    /// Check whether the inotify filesystem monitor is enabled and running,
+    /// and list the paths changed since the last snapshot
+    Status,
+    /// Stop the inotify daemon watching the working copy
+    Stop,
+    /// Watch the working copy in the foreground
+    ///
+    /// This is started in the background when the working copy is snapshotted
+    /// with `core.fsmonitor = "inotify"`.
+    #[command(hide = true)]
+    Daemon,
+}
+
+#[cfg(target_os = "linux")]
+pub fn cmd_debug_fsmonitor(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &DebugFsmonitorCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        DebugFsmonitorCommand::Status => {
+            match command.settings().fsmonitor_settings()? {
+                FsmonitorSettings::Inotify => {
+                    writeln!(
+                        ui.stdout(),
+                        "The inotify filesystem monitor is enabled via `core.fsmonitor`."
+                    )?;
+                }
+                FsmonitorSettings::None => {
+                    writeln!(
+                        ui.stdout(),
+                        r#"The inotify filesystem monitor is disabled. Set `core.fsmonitor="inotify"` to enable."#
+                    )?;
+                }
+                other_fsmonitor => {
+                    return Err(user_error(format!(
+                        r"This command does not support the currently enabled filesystem monitor: {other_fsmonitor:?}."
+                    )))
+                }
+            }
+            // Don't snapshot, which would start the daemon and consume the
+            // changed paths.
+            let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+            let wc = check_local_disk_wc(workspace_command.working_copy().as_any())?;
+            let fsmonitor = wc.inotify_fsmonitor();
+            if !fsmonitor.is_daemon_running().map_err(|err| {
+                internal_error_with_message("Failed to query the inotify daemon", err)
+            })? {
+                writeln!(ui.stdout(), "The inotify daemon is not running.")?;
+                return Ok(());
+            }
+            writeln!(ui.stdout(), "The inotify daemon is running.")?;
+            let (_clock, changed_files) = wc.query_inotify()?;
+            match changed_files {
+                Some(mut changed_files) if !changed_files.is_empty() => {
+                    changed_files.sort();
+                    changed_files.dedup();
+                    writeln!(ui.stdout(), "Paths changed since the last snapshot:")?;
+                    for path in changed_files {
+                        writeln!(ui.stdout(), "  {}", path.display())?;
+                    }
+                }
+                Some(_) => {
+                    writeln!(ui.stdout(), "No paths changed since the last snapshot.")?;
+                }
+                None => {
+                    writeln!(
+                        ui.stdout(),
+                        "The next snapshot will scan the whole working copy."
+                    )?;
+                }
+            }
+        }
+        DebugFsmonitorCommand::Stop => {
+            let workspace = command.load_workspace()?;
+            let wc = check_local_disk_wc(workspace.working_copy().as_any())?;
+            let fsmonitor = wc.inotify_fsmonitor();
+            let map_err =
+                |err| internal_error_with_message("Failed to stop the inotify daemon", err);
+            if fsmonitor.is_daemon_running().map_err(map_err)? {
+                fsmonitor.stop_daemon().map_err(map_err)?;
+                writeln!(ui.status(), "Stopped the inotify daemon")?;
+            } else {
+                writeln!(ui.status(), "The inotify daemon is not running")?;
+            }
+        }
+        DebugFsmonitorCommand::Daemon => {
+            let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+            let base_ignores = workspace_command.base_ignores()?;
+            let wc = check_local_disk_wc(workspace_command.working_copy().as_any())?;
+            wc.inotify_fsmonitor()
+                .run_daemon(base_ignores)
+                .map_err(|err| {
+                    internal_error_with_message("Failed to run the inotify daemon", err)
+                })?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cmd_debug_fsmonitor(
+    _ui: &mut Ui,
+    _command: &CommandHelper,
+    _subcommand: &DebugFsmonitorCommand,
+) -> Result<(), CommandError> {
+    Err(user_error(
+        "The inotify filesystem monitor is only supported on Linux",
+    ))
+}
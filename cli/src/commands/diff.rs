@@ -14,7 +14,6 @@
 
 use itertools::Itertools;
 use jj_lib::copies::CopyRecords;
-use jj_lib::repo::Repo;
 use jj_lib::rewrite::merge_commit_trees;
 use tracing::instrument;
 
@@ -68,30 +67,32 @@ pub(crate) fn cmd_diff(
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
     let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
-    let matcher = fileset_expression.to_matcher();
     let resolve_revision = |r: &Option<RevisionArg>| {
         workspace_command.resolve_single_rev(r.as_ref().unwrap_or(&RevisionArg::AT))
     };
 
     let from_tree;
     let to_tree;
+    let matcher;
     let mut copy_records = CopyRecords::default();
     if args.from.is_some() || args.to.is_some() {
         let from = resolve_revision(&args.from)?;
         let to = resolve_revision(&args.to)?;
         from_tree = from.tree()?;
         to_tree = to.tree()?;
+        matcher = fileset_expression.to_diff_matcher(&from_tree, &to_tree)?;
 
-        let records = get_copy_records(repo.store(), from.id(), to.id(), &matcher)?;
+        let records = get_copy_records(repo.as_ref(), from.id(), to.id(), &matcher)?;
         copy_records.add_records(records)?;
     } else {
         let to = resolve_revision(&args.revision)?;
         let parents: Vec<_> = to.parents().try_collect()?;
         from_tree = merge_commit_trees(repo.as_ref(), &parents)?;
         to_tree = to.tree()?;
+        matcher = fileset_expression.to_diff_matcher(&from_tree, &to_tree)?;
 
         for p in &parents {
-            let records = get_copy_records(repo.store(), p.id(), to.id(), &matcher)?;
+            let records = get_copy_records(repo.as_ref(), p.id(), to.id(), &matcher)?;
             copy_records.add_records(records)?;
         }
     }
@@ -20,6 +20,7 @@ use tracing::instrument;
 
 use super::log::get_node_template;
 use crate::cli_util::format_template;
+use crate::cli_util::write_json_lines_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::RevisionArg;
@@ -80,6 +81,10 @@ pub(crate) fn cmd_evolog(
     let start_commit = workspace_command.resolve_single_rev(&args.revision)?;
 
     let diff_renderer = workspace_command.diff_renderer_for_log(&args.diff_format, args.patch)?;
+    let json_format = diff_renderer
+        .as_ref()
+        .is_some_and(|renderer| renderer.has_json_format());
+    let no_graph = args.no_graph || json_format;
     let graph_style = GraphStyle::from_settings(command.settings())?;
     let with_content_format = LogContentFormat::new(ui, command.settings())?;
 
@@ -139,7 +144,7 @@ pub(crate) fn cmd_evolog(
     if let Some(n) = args.limit.or(args.deprecated_limit) {
         commits.truncate(n);
     }
-    if !args.no_graph {
+    if !no_graph {
         let mut graph = get_graphlog(graph_style, formatter.raw());
         for commit in commits {
             let edges = commit
@@ -180,8 +185,14 @@ pub(crate) fn cmd_evolog(
         }
     } else {
         for commit in commits {
-            with_content_format
-                .write(formatter, |formatter| template.format(&commit, formatter))?;
+            if json_format {
+                write_json_lines_template(formatter, |formatter| {
+                    template.format(&commit, formatter)
+                })?;
+            } else {
+                with_content_format
+                    .write(formatter, |formatter| template.format(&commit, formatter))?;
+            }
             if let Some(renderer) = &diff_renderer {
                 let predecessors: Vec<_> = commit.predecessors().try_collect()?;
                 let width = ui.term_width();
@@ -0,0 +1,204 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::copies::CopyRecords;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo;
+use tracing::instrument;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::diff_util::get_copy_records;
+use crate::diff_util::show_git_patch;
+use crate::formatter::PlainTextFormatter;
+use crate::time_util::format_rfc2822_timestamp;
+use crate::ui::Ui;
+
+/// Export revisions as patch files in the mailbox format
+///
+/// Each revision is written to a numbered file in the output directory. The
+/// author, the date and the description of the revision are written as the
+/// headers and the body of a mail, followed by the changes in the Git diff
+/// format. The patches can be applied with `jj apply` or `git am`.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ExportPatchArgs {
+    /// The revisions to export
+    #[arg(long, short, default_value = "@", value_name = "REVISIONS")]
+    revisions: Vec<RevisionArg>,
+    /// The directory to write the patch files to [default: the current
+    /// directory]
+    #[arg(long, short, value_hint = clap::ValueHint::DirPath)]
+    output: Option<PathBuf>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_export_patch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ExportPatchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let mut commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(&args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    commits.reverse();
+    for commit in &commits {
+        if commit.id() == repo.store().root_commit_id() {
+            return Err(user_error("Cannot export the root commit"));
+        }
+        if commit.parent_ids().len() > 1 {
+            return Err(user_error(format!(
+                "Cannot export merge commit {} as a patch",
+                short_commit_hash(commit.id())
+            )));
+        }
+    }
+
+    let output_dir = match &args.output {
+        Some(output) => {
+            let output_dir = command.cwd().join(output);
+            fs::create_dir_all(&output_dir).map_err(|err| {
+                user_error_with_message(
+                    format!("Failed to create directory {}", output.display()),
+                    err,
+                )
+            })?;
+            output_dir
+        }
+        None => command.cwd().to_owned(),
+    };
+    let mut formatter = ui.stdout_formatter();
+    for (index, commit) in commits.iter().enumerate() {
+        let mut patch = vec![];
+        write_patch(
+            repo.as_ref(),
+            &mut patch,
+            commit,
+            index + 1,
+            commits.len(),
+            workspace_command.conflict_marker_style(),
+        )?;
+        let file_name = patch_file_name(index + 1, commit.description());
+        let path = match &args.output {
+            Some(output) => output.join(&file_name),
+            None => PathBuf::from(&file_name),
+        };
+        fs::write(output_dir.join(&file_name), patch).map_err(|err| {
+            user_error_with_message(format!("Failed to write {}", path.display()), err)
+        })?;
+        writeln!(formatter, "{}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Writes `commit` as a mail in the format of `git format-patch`.
+fn write_patch(
+    repo: &dyn Repo,
+    out: &mut Vec<u8>,
+    commit: &Commit,
+    number: usize,
+    total: usize,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), CommandError> {
+    let author = commit.author();
+    let date = format_rfc2822_timestamp(&author.timestamp).map_err(user_error)?;
+    let (subject, body) = commit
+        .description()
+        .split_once('\n')
+        .unwrap_or((commit.description(), ""));
+    let mut subject_line = if total > 1 {
+        format!("[PATCH {number}/{total}]")
+    } else {
+        "[PATCH]".to_owned()
+    };
+    if !subject.is_empty() {
+        subject_line.push(' ');
+        subject_line.push_str(subject);
+    }
+    writeln!(out, "From {} Mon Sep 17 00:00:00 2001", commit.id().hex())?;
+    writeln!(out, "From: {} <{}>", author.name, author.email)?;
+    writeln!(out, "Date: {date}")?;
+    writeln!(out, "Subject: {subject_line}")?;
+    writeln!(out, "MIME-Version: 1.0")?;
+    writeln!(out, "Content-Type: text/plain; charset=UTF-8")?;
+    writeln!(out, "Content-Transfer-Encoding: 8bit")?;
+    writeln!(out)?;
+    for line in body.trim().lines() {
+        // Quote lines that would otherwise end the description, as mboxrd
+        // does for "From " lines. They're unquoted by `jj apply`.
+        let unquoted = line.trim_start_matches('>');
+        if unquoted.starts_with("From ") || unquoted.starts_with("---") {
+            write!(out, ">")?;
+        }
+        writeln!(out, "{line}")?;
+    }
+    writeln!(out, "---")?;
+
+    let from_tree = commit.parent_tree(repo)?;
+    let to_tree = commit.tree()?;
+    let matcher = EverythingMatcher;
+    let mut copy_records = CopyRecords::default();
+    for parent_id in commit.parent_ids() {
+        let records = get_copy_records(repo, parent_id, commit.id(), &matcher)?;
+        copy_records.add_records(records)?;
+    }
+    let tree_diff = from_tree.diff_stream_with_copies(&to_tree, &matcher, &copy_records);
+    let mut formatter = PlainTextFormatter::new(out);
+    show_git_patch(
+        &mut formatter,
+        repo.store(),
+        tree_diff,
+        3,
+        conflict_marker_style,
+    )?;
+    Ok(())
+}
+
+/// Returns a file name like `0001-fix-the-thing.patch`, as `git format-patch`
+/// does.
+fn patch_file_name(number: usize, description: &str) -> String {
+    const MAX_SLUG_LEN: usize = 52;
+    let subject = description.lines().next().unwrap_or_default();
+    let mut slug = String::new();
+    for c in subject.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= MAX_SLUG_LEN {
+            break;
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        format!("{number:04}.patch")
+    } else {
+        format!("{number:04}-{slug}.patch")
+    }
+}
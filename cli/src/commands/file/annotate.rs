@@ -0,0 +1,92 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::hash_map;
+use std::collections::HashMap;
+use std::io::Write;
+
+use jj_lib::annotate::get_annotation_for_file;
+use jj_lib::repo::Repo;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Show the source change for each line of the target file
+///
+/// Annotates a revision line by line. Each line includes the source change
+/// that introduced the associated line. The prefix of each line can be
+/// customized with the `templates.annotate_commit_summary` config variable.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileAnnotateArgs {
+    /// The file to annotate
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    path: String,
+    /// The revision to start at
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// Follow the file across renames recorded by the backend
+    #[arg(long)]
+    follow_renames: bool,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_annotate(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileAnnotateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let starting_commit = workspace_command.resolve_single_rev(&args.revision)?;
+    let file_path = workspace_command.parse_file_path(&args.path)?;
+    let template_text = command
+        .settings()
+        .config()
+        .get_string("templates.annotate_commit_summary")?;
+    let template = workspace_command.parse_commit_template(&template_text)?;
+
+    let Some(annotations) = get_annotation_for_file(
+        repo.as_ref(),
+        &starting_commit,
+        &file_path,
+        args.follow_renames,
+    )?
+    else {
+        let ui_path = workspace_command.format_file_path(&file_path);
+        return Err(user_error(format!(
+            "Path '{ui_path}' is not a file in the revision"
+        )));
+    };
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let mut commits = HashMap::new();
+    for (line_number, (commit_id, line)) in annotations.file_annotations.iter().enumerate() {
+        let commit = match commits.entry(commit_id) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => entry.insert(repo.store().get_commit(commit_id)?),
+        };
+        template.format(commit, formatter.as_mut())?;
+        write!(formatter, " {:>4}: ", line_number + 1)?;
+        formatter.write_all(line)?;
+        if !line.ends_with(b"\n") {
+            writeln!(formatter)?;
+        }
+    }
+    Ok(())
+}
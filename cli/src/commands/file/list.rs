@@ -43,7 +43,7 @@ pub(crate) fn cmd_file_list(
     let tree = commit.tree()?;
     let matcher = workspace_command
         .parse_file_patterns(&args.paths)?
-        .to_matcher();
+        .to_tree_matcher(&tree)?;
     ui.request_pager();
     for (name, _value) in tree.entries_matching(matcher.as_ref()) {
         writeln!(
@@ -76,7 +76,7 @@ pub(crate) fn cmd_file_show(
         }
     }
 
-    let matcher = fileset_expression.to_matcher();
+    let matcher = fileset_expression.to_tree_matcher(&tree)?;
     ui.request_pager();
     write_tree_entries(
         ui,
@@ -121,7 +121,11 @@ fn write_tree_entries<P: AsRef<RepoPath>>(
                 io::copy(&mut reader, &mut ui.stdout_formatter().as_mut())?;
             }
             MaterializedTreeValue::FileConflict { contents, .. } => {
-                materialize_merge_result(&contents, &mut ui.stdout_formatter())?;
+                materialize_merge_result(
+                    &contents,
+                    workspace_command.conflict_marker_style(),
+                    &mut ui.stdout_formatter(),
+                )?;
             }
             MaterializedTreeValue::OtherConflict { id } => {
                 ui.stdout_formatter().write_all(id.describe().as_bytes())?;
@@ -18,6 +18,7 @@ use jj_lib::working_copy::SnapshotOptions;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
@@ -46,10 +47,14 @@ pub(crate) fn cmd_file_track(
     let mut workspace_command = command.workspace_helper(ui)?;
     let matcher = workspace_command
         .parse_file_patterns(&args.paths)?
-        .to_matcher();
+        .to_matcher()
+        .map_err(|err| {
+            user_error_with_message("Untracked files can't be matched by predicates", err)
+        })?;
 
     let mut tx = workspace_command.start_transaction().into_inner();
     let base_ignores = workspace_command.base_ignores()?;
+    let conflict_marker_style = workspace_command.conflict_marker_style();
     let (mut locked_ws, _wc_commit) = workspace_command.start_working_copy_mutation()?;
     locked_ws.locked_wc().snapshot(&SnapshotOptions {
         base_ignores,
@@ -57,6 +62,7 @@ pub(crate) fn cmd_file_track(
         progress: None,
         start_tracking_matcher: &matcher,
         max_new_file_size: command.settings().max_new_file_size()?,
+        conflict_marker_style,
     })?;
     let num_rebased = tx.repo_mut().rebase_descendants(command.settings())?;
     if num_rebased > 0 {
@@ -46,6 +46,7 @@ use tracing::instrument;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::config_error;
+use crate::command_error::config_error_with_message;
 use crate::command_error::CommandError;
 use crate::config::to_toml_value;
 use crate::config::CommandNameAndArgs;
@@ -151,9 +152,9 @@ pub(crate) fn cmd_fix(
     .evaluate_to_commit_ids()?
     .collect();
     workspace_command.check_rewritable(root_commits.iter())?;
-    let matcher = workspace_command
+    let value_matcher = workspace_command
         .parse_file_patterns(&args.paths)?
-        .to_matcher();
+        .to_value_matcher();
 
     let mut tx = workspace_command.start_transaction();
 
@@ -198,7 +199,7 @@ pub(crate) fn cmd_fix(
             commit.parent_tree(tx.repo())?
         };
         // TODO: handle copy tracking
-        let mut diff_stream = parent_tree.diff_stream(&commit.tree()?, &matcher);
+        let mut diff_stream = parent_tree.diff_stream(&commit.tree()?, value_matcher.candidates());
         async {
             while let Some(TreeDiffEntry {
                 path: repo_path,
@@ -206,6 +207,9 @@ pub(crate) fn cmd_fix(
             }) = diff_stream.next().await
             {
                 let (_before, after) = values?;
+                if !value_matcher.matches_values(tx.repo().store(), &repo_path, &[&after])? {
+                    continue;
+                }
                 // Deleted files have no file content to fix, and they have no terms in `after`,
                 // so we don't add any tool inputs for them. Conflicted files produce one tool
                 // input for each side of the conflict.
@@ -492,7 +496,10 @@ fn get_tools_config(ui: &mut Ui, config: &config::Config) -> Result<ToolsConfig,
                             })
                             .try_collect()?,
                     )
-                    .to_matcher(),
+                    .to_matcher()
+                    .map_err(|err| {
+                        config_error_with_message("Invalid `fix.tools` patterns", err)
+                    })?,
                 })
             })
             .try_collect()?;
@@ -24,6 +24,7 @@ use jj_lib::backend::CommitId;
 use jj_lib::git;
 use jj_lib::git::GitBranchPushTargets;
 use jj_lib::git::GitPushError;
+use jj_lib::git::RefName;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::RefTarget;
 use jj_lib::refs::classify_bookmark_push_action;
@@ -48,6 +49,7 @@ use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
 use crate::commands::git::map_git_error;
+use crate::commands::tag::find_tags;
 use crate::git_util::get_git_repo;
 use crate::git_util::with_remote_git_callbacks;
 use crate::git_util::GitSidebandProgressMessageWriter;
@@ -59,7 +61,8 @@ use crate::ui::Ui;
 /// By default, pushes any bookmarks pointing to
 /// `remote_bookmarks(remote=<remote>)..@`. Use `--bookmark` to push specific
 /// bookmarks. Use `--all` to push all bookmarks. Use `--change` to generate
-/// bookmark names based on the change IDs of specific commits.
+/// bookmark names based on the change IDs of specific commits. Use `--tag` to
+/// push tags along with, or instead of, bookmarks.
 ///
 /// Before the command actually moves, creates, or deletes a remote bookmark, it
 /// makes several [safety checks]. If there is a problem, you may need to run
@@ -73,7 +76,7 @@ use crate::ui::Ui;
 ///     https://martinvonz.github.io/jj/latest/bookmarks/#conflicts
 
 #[derive(clap::Args, Clone, Debug)]
-#[command(group(ArgGroup::new("specific").args(&["bookmark", "change", "revisions"]).multiple(true)))]
+#[command(group(ArgGroup::new("specific").args(&["bookmark", "change", "revisions", "tag"]).multiple(true)))]
 #[command(group(ArgGroup::new("what").args(&["all", "deleted", "tracked"]).conflicts_with("specific")))]
 pub struct GitPushArgs {
     /// The remote to push to (only named remotes are supported)
@@ -117,6 +120,17 @@ pub struct GitPushArgs {
     /// repeated)
     #[arg(long, short)]
     change: Vec<RevisionArg>,
+    /// Push this tag, or tags matching a pattern (can be repeated)
+    ///
+    /// The tags are exported to the backing Git repo before pushing. Tags are
+    /// expected not to exist on the remote yet, so the push is rejected if a
+    /// tag of the same name points elsewhere on the remote.
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select tags by wildcard pattern. For details, see
+    /// https://martinvonz.github.io/jj/latest/revsets#string-patterns.
+    #[arg(long, value_parser = StringPattern::parse)]
+    tag: Vec<StringPattern>,
     /// Only display what will change on the remote
     #[arg(long)]
     dry_run: bool,
@@ -129,6 +143,13 @@ fn make_bookmark_term(bookmark_names: &[impl fmt::Display]) -> String {
     }
 }
 
+fn make_tag_term(tag_names: &[impl fmt::Display]) -> String {
+    match tag_names {
+        [tag_name] => format!("tag {}", tag_name),
+        tag_names => format!("tags {}", tag_names.iter().join(", ")),
+    }
+}
+
 const DEFAULT_REMOTE: &str = "origin";
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -156,6 +177,7 @@ pub fn cmd_git_push(
     let mut tx = workspace_command.start_transaction();
     let tx_description;
     let mut branch_updates = vec![];
+    let mut tag_updates = vec![];
     if args.all {
         for (bookmark_name, targets) in repo.view().local_remote_bookmarks(&remote) {
             match classify_bookmark_update(bookmark_name, &remote, targets) {
@@ -221,8 +243,12 @@ pub fn cmd_git_push(
             }
         }
 
-        let use_default_revset =
-            args.bookmark.is_empty() && args.change.is_empty() && args.revisions.is_empty();
+        tag_updates = find_tags_to_push(repo.view(), &args.tag)?;
+
+        let use_default_revset = args.bookmark.is_empty()
+            && args.change.is_empty()
+            && args.revisions.is_empty()
+            && args.tag.is_empty();
         let bookmarks_targeted = find_bookmarks_targeted_by_revisions(
             ui,
             tx.base_workspace_helper(),
@@ -241,18 +267,26 @@ pub fn cmd_git_push(
             }
         }
 
-        tx_description = format!(
-            "push {} to git remote {}",
-            make_bookmark_term(
+        let mut terms = vec![];
+        if !branch_updates.is_empty() || tag_updates.is_empty() {
+            terms.push(make_bookmark_term(
                 &branch_updates
                     .iter()
                     .map(|(bookmark, _)| bookmark.as_str())
-                    .collect_vec()
-            ),
-            &remote
-        );
+                    .collect_vec(),
+            ));
+        }
+        if !tag_updates.is_empty() {
+            terms.push(make_tag_term(
+                &tag_updates
+                    .iter()
+                    .map(|(tag, _)| tag.as_str())
+                    .collect_vec(),
+            ));
+        }
+        tx_description = format!("push {} to git remote {}", terms.join(" and "), &remote);
     }
-    if branch_updates.is_empty() {
+    if branch_updates.is_empty() && tag_updates.is_empty() {
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
     }
@@ -279,9 +313,16 @@ pub fn cmd_git_push(
         );
     }
 
-    validate_commits_ready_to_push(&branch_updates, &remote, &tx, command, args)?;
+    let new_heads = branch_updates
+        .iter()
+        .filter_map(|(_, update)| update.new_target.clone())
+        .chain(tag_updates.iter().map(|(_, target)| target.clone()))
+        .collect_vec();
+    validate_commits_ready_to_push(new_heads, &remote, &tx, command, args)?;
 
-    writeln!(ui.status(), "Branch changes to push to {}:", &remote)?;
+    if !branch_updates.is_empty() {
+        writeln!(ui.status(), "Branch changes to push to {}:", &remote)?;
+    }
     for (bookmark_name, update) in &branch_updates {
         match (&update.old_target, &update.new_target) {
             (Some(old_target), Some(new_target)) => {
@@ -326,21 +367,63 @@ pub fn cmd_git_push(
         }
     }
 
+    if !tag_updates.is_empty() {
+        writeln!(ui.status(), "Tags to push to {}:", &remote)?;
+    }
+    for (tag_name, target) in &tag_updates {
+        writeln!(
+            ui.status(),
+            "  Add tag {tag_name} to {}",
+            short_commit_hash(target)
+        )?;
+    }
+
     if args.dry_run {
         writeln!(ui.status(), "Dry-run requested, not pushing.")?;
         return Ok(());
     }
 
+    if !tag_updates.is_empty() {
+        // Tags are pushed from the backing Git repo, so make sure they're up to
+        // date there.
+        let failed_tags = git::export_some_refs(tx.repo_mut(), |ref_name| {
+            matches!(ref_name, RefName::Tag(name)
+                if tag_updates.iter().any(|(tag_name, _)| tag_name == name))
+        })?;
+        if !failed_tags.is_empty() {
+            return Err(user_error(format!(
+                "Failed to export tags to the underlying Git repo: {}",
+                failed_tags.iter().map(|failed| &failed.name).join(", ")
+            )));
+        }
+    }
+
     let targets = GitBranchPushTargets { branch_updates };
+    let tag_names = tag_updates.into_iter().map(|(name, _)| name).collect_vec();
     let mut writer = GitSidebandProgressMessageWriter::new(ui);
     let mut sideband_progress_callback = |progress_message: &[u8]| {
         _ = writer.write(ui, progress_message);
     };
+    // Bookmarks and tags are pushed at once, so the remote-tracking bookmarks
+    // recorded in the transaction can't get out of sync with the remote.
     with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
-        git::push_branches(tx.repo_mut(), &git_repo, &remote, &targets, cb)
+        git::push_branches_and_tags(tx.repo_mut(), &git_repo, &remote, &targets, &tag_names, cb)
     })
     .map_err(|err| match err {
         GitPushError::InternalGitError(err) => map_git_error(err),
+        GitPushError::RefInUnexpectedLocation(refs)
+            if refs.iter().all(|name| name.starts_with("refs/tags/")) =>
+        {
+            user_error_with_hint(
+                format!(
+                    "Refusing to overwrite tags which already exist on the remote. Affected \
+                     refs: {}",
+                    refs.join(", ")
+                ),
+                "Tags are not moved on the remote. Delete the tags there first if you want to \
+                 replace them.",
+            )
+        }
         GitPushError::RefInUnexpectedLocation(refs) => user_error_with_hint(
             format!(
                 "Refusing to push a bookmark that unexpectedly moved on the remote. Affected \
@@ -360,7 +443,7 @@ pub fn cmd_git_push(
 /// Validates that the commits that will be pushed are ready (have authorship
 /// information, are not conflicted, etc.)
 fn validate_commits_ready_to_push(
-    bookmark_updates: &[(String, BranchPushUpdate)],
+    new_heads: Vec<CommitId>,
     remote: &str,
     tx: &WorkspaceCommandTransaction,
     command: &CommandHelper,
@@ -368,11 +451,10 @@ fn validate_commits_ready_to_push(
 ) -> Result<(), CommandError> {
     let workspace_helper = tx.base_workspace_helper();
     let repo = workspace_helper.repo();
+    let git_backend = workspace_helper
+        .git_backend()
+        .ok_or_else(|| user_error("The repo is not backed by a git repo"))?;
 
-    let new_heads = bookmark_updates
-        .iter()
-        .filter_map(|(_, update)| update.new_target.clone())
-        .collect_vec();
     let old_heads = repo
         .view()
         .remote_bookmarks(remote)
@@ -415,7 +497,9 @@ fn validate_commits_ready_to_push(
         {
             reasons.push("it has no author and/or committer set");
         }
-        if commit.has_conflict()? {
+        // Conflicts can be read back by jj if the change id is preserved too,
+        // which is only the case for commits written with jj headers.
+        if commit.has_conflict()? && !git_backend.has_change_id_header(commit.id())? {
             reasons.push("it has conflicts");
         }
         if !args.allow_private && is_private(commit.id()) {
@@ -585,6 +669,22 @@ fn find_bookmarks_to_push<'a>(
     }
 }
 
+fn find_tags_to_push(
+    view: &View,
+    tag_patterns: &[StringPattern],
+) -> Result<Vec<(String, CommitId)>, CommandError> {
+    find_tags(view, tag_patterns)?
+        .into_iter()
+        .map(|(name, target)| match target.as_normal() {
+            Some(id) => Ok((name.to_owned(), id.clone())),
+            None => Err(user_error_with_hint(
+                format!("Tag {name} is conflicted"),
+                "Run `jj tag list` to inspect, and use `jj tag set` to fix it up.",
+            )),
+        })
+        .try_collect()
+}
+
 fn find_bookmarks_targeted_by_revisions<'a>(
     ui: &Ui,
     workspace_command: &'a WorkspaceCommandHelper,
@@ -16,22 +16,33 @@ use std::io::Write;
 
 use clap::Subcommand;
 use jj_lib::backend::TreeValue;
-use jj_lib::git::parse_gitmodules;
+use jj_lib::git;
+use jj_lib::git::GitSubmoduleError;
 use jj_lib::repo::Repo;
-use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 
+use crate::cli_util::short_commit_hash;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
+use crate::git_util::read_gitmodules;
+use crate::git_util::with_remote_git_callbacks;
 use crate::ui::Ui;
 
-/// FOR INTERNAL USE ONLY Interact with git submodules
+/// Interact with git submodules
+///
+/// Submodule repositories are stored inside the jj repo. `jj git submodule
+/// update` initializes them and populates the submodule working copies.
+/// Initialized submodules are then updated automatically when the working
+/// copy is checked out.
 #[derive(Subcommand, Clone, Debug)]
 pub enum GitSubmoduleCommand {
     /// Print the relevant contents from .gitmodules. For debugging purposes
     /// only.
     PrintGitmodules(PrintArgs),
+    Sync(GitSubmoduleSyncArgs),
+    Update(GitSubmoduleUpdateArgs),
 }
 
 pub fn cmd_git_submodule(
@@ -41,12 +52,11 @@ pub fn cmd_git_submodule(
 ) -> Result<(), CommandError> {
     match subcommand {
         GitSubmoduleCommand::PrintGitmodules(args) => cmd_submodule_print(ui, command, args),
+        GitSubmoduleCommand::Sync(args) => cmd_submodule_sync(ui, command, args),
+        GitSubmoduleCommand::Update(args) => cmd_submodule_update(ui, command, args),
     }
 }
 
-// TODO: break everything below into a separate file as soon as there is more
-// than one subcommand here.
-
 /// Print debugging info about Git submodules
 #[derive(clap::Args, Clone, Debug)]
 #[command(hide = true)]
@@ -64,20 +74,10 @@ fn cmd_submodule_print(
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
     let commit = workspace_command.resolve_single_rev(&args.revisions)?;
-    let tree = commit.tree()?;
-    let gitmodules_path = RepoPath::from_internal_string(".gitmodules");
-    let mut gitmodules_file = match tree.path_value(gitmodules_path)?.into_resolved() {
-        Ok(None) => {
-            writeln!(ui.status(), "No submodules!")?;
-            return Ok(());
-        }
-        Ok(Some(TreeValue::File { id, .. })) => repo.store().read_file(gitmodules_path, &id)?,
-        _ => {
-            return Err(user_error(".gitmodules is not a file."));
-        }
+    let Some(submodules) = read_gitmodules(repo.store(), &commit.tree()?)? else {
+        writeln!(ui.status(), "No submodules!")?;
+        return Ok(());
     };
-
-    let submodules = parse_gitmodules(&mut gitmodules_file)?;
     for (name, submodule) in submodules {
         writeln!(
             ui.stdout(),
@@ -89,3 +89,127 @@ fn cmd_submodule_print(
     }
     Ok(())
 }
+
+/// Update the working copies of submodules
+///
+/// Checks out the submodule commits recorded in the working-copy commit.
+/// Submodules are initialized from the URLs in .gitmodules, and fetched if
+/// the recorded commits are missing.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitSubmoduleUpdateArgs {
+    /// Only update the submodules matching these paths
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+fn cmd_submodule_update(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitSubmoduleUpdateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let submodule_store = repo.submodule_store().as_ref();
+    let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
+    let wc_commit_id = workspace_command
+        .get_wc_commit_id()
+        .ok_or_else(|| user_error("This command requires a working copy"))?;
+    let tree = repo.store().get_commit(wc_commit_id)?.tree()?;
+    let matcher = fileset_expression.to_tree_matcher(&tree)?;
+    let Some(submodules) = read_gitmodules(repo.store(), &tree)? else {
+        writeln!(ui.status(), "No submodules!")?;
+        return Ok(());
+    };
+    for config in submodules.values() {
+        let Ok(path) = RepoPathBuf::from_relative_path(&config.path) else {
+            writeln!(
+                ui.warning_default(),
+                "Skipping submodule {} with invalid path {}",
+                config.name,
+                config.path
+            )?;
+            continue;
+        };
+        if !matcher.matches(&path) {
+            continue;
+        }
+        let Ok(Some(TreeValue::GitSubmodule(commit_id))) = tree.path_value(&path)?.into_resolved()
+        else {
+            writeln!(
+                ui.warning_default(),
+                "Skipping submodule {}: not a submodule in the working-copy commit",
+                config.path
+            )?;
+            continue;
+        };
+        let work_dir = path.to_fs_path(workspace_command.workspace_root());
+        let checkout = || {
+            git::checkout_submodule(
+                submodule_store,
+                &config.name,
+                workspace_command.workspace_id(),
+                &commit_id,
+                &work_dir,
+            )
+        };
+        let mut result = checkout();
+        if let Err(
+            GitSubmoduleError::NotInitialized(_) | GitSubmoduleError::CommitNotFound { .. },
+        ) = result
+        {
+            writeln!(ui.status(), "Fetching submodule {}", config.path)?;
+            with_remote_git_callbacks(ui, None, |callbacks| {
+                git::fetch_submodule(submodule_store, config, callbacks)
+            })?;
+            result = checkout();
+        }
+        if let Err(err @ GitSubmoduleError::UnmanagedGitDir { .. }) = result {
+            writeln!(
+                ui.warning_default(),
+                "Skipping submodule {}: {err}",
+                config.path
+            )?;
+            continue;
+        }
+        result?;
+        writeln!(
+            ui.status(),
+            "Updated submodule {} to {}",
+            config.path,
+            short_commit_hash(&commit_id)
+        )?;
+    }
+    Ok(())
+}
+
+/// Update the remote URLs of initialized submodules from .gitmodules
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitSubmoduleSyncArgs {}
+
+fn cmd_submodule_sync(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &GitSubmoduleSyncArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let wc_commit_id = workspace_command
+        .get_wc_commit_id()
+        .ok_or_else(|| user_error("This command requires a working copy"))?;
+    let tree = repo.store().get_commit(wc_commit_id)?.tree()?;
+    let Some(submodules) = read_gitmodules(repo.store(), &tree)? else {
+        writeln!(ui.status(), "No submodules!")?;
+        return Ok(());
+    };
+    for config in submodules.values() {
+        if git::sync_submodule(repo.submodule_store().as_ref(), config)? {
+            writeln!(
+                ui.status(),
+                "Synchronized URL of submodule {}: {}",
+                config.path,
+                config.url
+            )?;
+        }
+    }
+    Ok(())
+}
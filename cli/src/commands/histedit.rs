@@ -0,0 +1,414 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+
+use indoc::indoc;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::hex_util::to_reverse_hex;
+use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo;
+use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::rewrite::CommitRewriter;
+use tracing::instrument;
+
+use crate::cli_util::edit_temp_file;
+use crate::cli_util::short_change_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::description_util::combine_messages;
+use crate::description_util::description_template;
+use crate::description_util::edit_description;
+use crate::ui::Ui;
+
+/// Edit the history of a set of revisions with a todo list in the editor
+///
+/// The revisions are listed in the editor oldest first, one per line, each
+/// with the action to apply to it. If the lines are kept in order, each
+/// revision keeps its parents, so revisions on different branches of the set
+/// and merges within it are preserved. If the lines are reordered, they are
+/// applied from top to bottom, each revision on top of the previous one, so the
+/// revisions end up in a single linear sequence in the new order. Parents of
+/// merge revisions that aren't in the set are kept either way.
+///
+/// The available actions are:
+///
+/// * `pick`: keep the revision
+/// * `reword`: keep the revision, but edit its description
+/// * `edit`: keep the revision, and make it the working-copy revision
+/// * `squash`: fold the revision into the previous one, combining the
+///   descriptions
+/// * `fixup`: fold the revision into the previous one, keeping the description
+///   of the previous revision
+/// * `drop`: abandon the revision and discard its changes
+///
+/// Descendants of the revisions that aren't in the set are rebased onto the
+/// rewritten revisions. Everything is done in a single operation, so it can be
+/// undone with `jj undo`.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct HisteditArgs {
+    /// The revisions to edit
+    ///
+    /// The revisions must have a single root.
+    #[arg(
+        long,
+        short,
+        default_value = "mutable() & ::@",
+        value_name = "REVISIONS"
+    )]
+    revisions: Vec<RevisionArg>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Action {
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl Action {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "p" | "pick" => Some(Action::Pick),
+            "r" | "reword" => Some(Action::Reword),
+            "e" | "edit" => Some(Action::Edit),
+            "s" | "squash" => Some(Action::Squash),
+            "f" | "fixup" => Some(Action::Fixup),
+            "d" | "drop" => Some(Action::Drop),
+            _ => None,
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_histedit(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &HisteditArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(&args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to edit.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(commits.iter().map(|commit| commit.id()))?;
+    commits.reverse();
+    let commit_ids: HashSet<_> = commits.iter().map(|commit| commit.id().clone()).collect();
+    let roots = commits
+        .iter()
+        .filter(|commit| {
+            commit
+                .parent_ids()
+                .iter()
+                .all(|parent_id| !commit_ids.contains(parent_id))
+        })
+        .collect_vec();
+    if roots.len() > 1 {
+        return Err(user_error_with_hint(
+            "The revisions to edit must have a single root",
+            "Edit the history of each root separately",
+        ));
+    }
+    let base_parent_ids = roots[0].parent_ids().to_vec();
+
+    let todo_text = format_todo_list(&commits);
+    let edited_text = edit_temp_file(
+        "todo list",
+        ".jjhistedit",
+        workspace_command.repo_path(),
+        &todo_text,
+        command.settings(),
+    )?;
+    let actions = parse_todo_list(&edited_text, &commits)?;
+    if actions.is_empty() {
+        writeln!(ui.status(), "The todo list is empty, nothing changed.")?;
+        return Ok(());
+    }
+
+    // Unless the revisions are reordered, they keep their parents, or the
+    // rewritten parents if those are being edited.
+    let reordered = !actions
+        .iter()
+        .map(|(_, commit)| commit.id())
+        .eq(commits.iter().map(|commit| commit.id()));
+
+    let mut tx = workspace_command.start_transaction();
+    let mut parent_ids = base_parent_ids.clone();
+    // Maps the edited revisions to the ids their children should be rebased
+    // onto
+    let mut new_ids: HashMap<CommitId, Vec<CommitId>> = HashMap::new();
+    let mut last_commit: Option<Commit> = None;
+    let mut edit_target: Option<CommitId> = None;
+    for (action, old_commit) in actions {
+        match action {
+            Action::Pick | Action::Reword | Action::Edit => {
+                let description = if action == Action::Reword {
+                    let template = description_template(&tx, "", &old_commit)?;
+                    Some(edit_description(
+                        tx.base_workspace_helper(),
+                        &template,
+                        command.settings(),
+                    )?)
+                } else {
+                    None
+                };
+                let new_parent_ids = if reordered {
+                    let mut new_parent_ids = parent_ids.clone();
+                    for parent_id in external_parent_ids(&old_commit, &commit_ids, &base_parent_ids)
+                    {
+                        if !new_parent_ids.contains(parent_id) {
+                            new_parent_ids.push(parent_id.clone());
+                        }
+                    }
+                    new_parent_ids
+                } else {
+                    map_parent_ids(&old_commit, &new_ids)
+                };
+                let rewriter =
+                    CommitRewriter::new(tx.repo_mut(), old_commit.clone(), new_parent_ids);
+                let new_commit = if description.is_some() || rewriter.parents_changed() {
+                    let mut commit_builder = rewriter.rebase(command.settings())?;
+                    if let Some(description) = description {
+                        commit_builder = commit_builder.set_description(description);
+                    }
+                    commit_builder.write()?
+                } else {
+                    old_commit.clone()
+                };
+                if action == Action::Edit {
+                    edit_target = Some(new_commit.id().clone());
+                }
+                new_ids.insert(old_commit.id().clone(), vec![new_commit.id().clone()]);
+                parent_ids = vec![new_commit.id().clone()];
+                last_commit = Some(new_commit);
+            }
+            Action::Squash | Action::Fixup => {
+                let destination = last_commit.take().expect("checked by parse_todo_list()");
+                // Parents of a folded merge revision that aren't being edited
+                // become parents of the destination, and so do their changes
+                let folded_parent_ids = if reordered {
+                    external_parent_ids(&old_commit, &commit_ids, &base_parent_ids)
+                        .cloned()
+                        .collect()
+                } else {
+                    map_parent_ids(&old_commit, &new_ids)
+                };
+                let mut new_parent_ids = destination.parent_ids().to_vec();
+                let mut tree_parents = vec![destination.clone()];
+                for parent_id in folded_parent_ids {
+                    if !new_parent_ids.contains(&parent_id)
+                        && !tx.repo().index().is_ancestor(&parent_id, destination.id())
+                    {
+                        tree_parents.push(tx.repo().store().get_commit(&parent_id)?);
+                        new_parent_ids.push(parent_id);
+                    }
+                }
+                let new_tree = merge_commit_trees(tx.repo(), &tree_parents)?
+                    .merge(&old_commit.parent_tree(tx.repo())?, &old_commit.tree()?)?;
+                let description = if action == Action::Squash {
+                    combine_messages(
+                        tx.base_workspace_helper(),
+                        &[&old_commit],
+                        &destination,
+                        command.settings(),
+                    )?
+                } else {
+                    destination.description().to_owned()
+                };
+                let new_commit = tx
+                    .repo_mut()
+                    .rewrite_commit(command.settings(), &destination)
+                    .set_parents(new_parent_ids)
+                    .set_tree_id(new_tree.id())
+                    .set_description(description)
+                    .set_predecessors(vec![destination.id().clone(), old_commit.id().clone()])
+                    .write()?;
+                tx.repo_mut()
+                    .set_rewritten_commit(old_commit.id().clone(), new_commit.id().clone());
+                if edit_target.as_ref() == Some(destination.id()) {
+                    edit_target = Some(new_commit.id().clone());
+                }
+                for ids in new_ids.values_mut() {
+                    for id in ids.iter_mut() {
+                        if id == destination.id() {
+                            *id = new_commit.id().clone();
+                        }
+                    }
+                }
+                new_ids.insert(old_commit.id().clone(), vec![new_commit.id().clone()]);
+                parent_ids = vec![new_commit.id().clone()];
+                last_commit = Some(new_commit);
+            }
+            Action::Drop => {
+                new_ids.insert(
+                    old_commit.id().clone(),
+                    map_parent_ids(&old_commit, &new_ids),
+                );
+                tx.repo_mut()
+                    .record_abandoned_commit(old_commit.id().clone());
+            }
+        }
+    }
+    let num_rebased = tx.repo_mut().rebase_descendants(command.settings())?;
+    if let Some(commit_id) = edit_target {
+        let commit = tx.repo().store().get_commit(&commit_id)?;
+        tx.edit(&commit)?;
+    }
+    if num_rebased > 0 {
+        writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
+    }
+    tx.finish(
+        ui,
+        format!(
+            "edit history of {} commits starting at {}",
+            commits.len(),
+            commits[0].id().hex()
+        ),
+    )?;
+    Ok(())
+}
+
+/// Returns the parents of `commit` that are neither being edited nor parents
+/// of the root of the edited revisions.
+fn external_parent_ids<'a>(
+    commit: &'a Commit,
+    commit_ids: &'a HashSet<CommitId>,
+    base_parent_ids: &'a [CommitId],
+) -> impl Iterator<Item = &'a CommitId> {
+    commit
+        .parent_ids()
+        .iter()
+        .filter(|id| !commit_ids.contains(id) && !base_parent_ids.contains(id))
+}
+
+/// Returns the parents of `commit`, with the edited ones replaced by their
+/// entries in `new_ids`.
+fn map_parent_ids(commit: &Commit, new_ids: &HashMap<CommitId, Vec<CommitId>>) -> Vec<CommitId> {
+    let mut parent_ids = vec![];
+    for parent_id in commit.parent_ids() {
+        let ids = new_ids
+            .get(parent_id)
+            .map_or(std::slice::from_ref(parent_id), Vec::as_slice);
+        for id in ids {
+            if !parent_ids.contains(id) {
+                parent_ids.push(id.clone());
+            }
+        }
+    }
+    parent_ids
+}
+
+fn format_todo_list(commits: &[Commit]) -> String {
+    let mut text = String::new();
+    for commit in commits {
+        let first_line = commit
+            .description()
+            .lines()
+            .next()
+            .unwrap_or("(no description set)");
+        let change_hash = short_change_hash(commit.change_id());
+        text.push_str(&format!("pick {change_hash} {first_line}\n"));
+    }
+    text.push_str(indoc! {r#"
+
+        JJ: Edit the history of these revisions by editing the lines above. If the
+        JJ: lines are reordered, they are applied from top to bottom, each on top of
+        JJ: the previous one. Otherwise, the revisions keep their parents.
+        JJ:
+        JJ: Actions:
+        JJ:   p, pick <change id>   = keep the revision
+        JJ:   r, reword <change id> = keep the revision, but edit its description
+        JJ:   e, edit <change id>   = keep the revision, and make it the working copy
+        JJ:   s, squash <change id> = fold into the previous revision, combining the
+        JJ:                           descriptions
+        JJ:   f, fixup <change id>  = fold into the previous revision, keeping its
+        JJ:                           description
+        JJ:   d, drop <change id>   = abandon the revision and discard its changes
+        JJ:
+        JJ: Lines can be reordered. Every revision must be listed.
+        JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "#});
+    text
+}
+
+fn parse_todo_list(text: &str, commits: &[Commit]) -> Result<Vec<(Action, Commit)>, CommandError> {
+    let mut actions: Vec<(Action, Commit)> = vec![];
+    let mut seen = HashSet::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("JJ:") {
+            continue;
+        }
+        let line_error =
+            |message: &str| user_error(format!("Line {}: {message}: {line}", line_number + 1));
+        let mut words = line.split_whitespace();
+        let (Some(action_name), Some(change_hash)) = (words.next(), words.next()) else {
+            return Err(line_error("Expected an action and a change id"));
+        };
+        let Some(action) = Action::parse(action_name) else {
+            return Err(line_error("Unknown action"));
+        };
+        // Any unique prefix of the change id is accepted
+        let candidates = commits
+            .iter()
+            .filter(|commit| {
+                to_reverse_hex(&commit.change_id().hex())
+                    .is_some_and(|hex| hex.starts_with(change_hash))
+            })
+            .collect_vec();
+        let commit = match candidates[..] {
+            [commit] => commit,
+            [] => return Err(line_error("Not one of the revisions being edited")),
+            _ => return Err(line_error("Change id prefix is ambiguous")),
+        };
+        if !seen.insert(commit.id()) {
+            return Err(line_error("Revision is listed more than once"));
+        }
+        if matches!(action, Action::Squash | Action::Fixup)
+            && !actions.iter().any(|(action, _)| *action != Action::Drop)
+        {
+            return Err(line_error("There is no previous revision to fold into"));
+        }
+        if action == Action::Edit && actions.iter().any(|(action, _)| *action == Action::Edit) {
+            return Err(line_error("Only one revision can be edited"));
+        }
+        actions.push((action, commit.clone()));
+    }
+    if !actions.is_empty() {
+        if let Some(missing) = commits.iter().find(|commit| !seen.contains(commit.id())) {
+            return Err(user_error_with_hint(
+                format!(
+                    "Revision {} is missing from the todo list",
+                    short_change_hash(missing.change_id())
+                ),
+                "Use `drop` to abandon a revision",
+            ));
+        }
+    }
+    Ok(actions)
+}
@@ -56,7 +56,7 @@ pub(crate) fn cmd_interdiff(
     let to = workspace_command.resolve_single_rev(args.to.as_ref().unwrap_or(&RevisionArg::AT))?;
     let matcher = workspace_command
         .parse_file_patterns(&args.paths)?
-        .to_matcher();
+        .to_diff_matcher(&from.tree()?, &to.tree()?)?;
     let diff_renderer = workspace_command.diff_renderer_for(&args.format)?;
     ui.request_pager();
     diff_renderer.show_inter_diff(
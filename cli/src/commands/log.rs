@@ -12,10 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
+use jj_lib::backend::BackendResult;
 use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::fileset::FilesetExpression;
 use jj_lib::graph::GraphEdgeType;
 use jj_lib::graph::ReverseGraphIterator;
 use jj_lib::graph::TopoGroupedGraphIterator;
+use jj_lib::matchers::Matcher;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterPredicate;
@@ -25,6 +31,7 @@ use jj_lib::settings::UserSettings;
 use tracing::instrument;
 
 use crate::cli_util::format_template;
+use crate::cli_util::write_json_lines_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::RevisionArg;
@@ -115,11 +122,16 @@ pub(crate) fn cmd_log(
     };
 
     let repo = workspace_command.repo();
-    let matcher = fileset_expression.to_matcher();
+    let paths_matcher: Option<Arc<dyn Matcher>> =
+        fileset_expression.to_matcher().ok().map(Arc::from);
     let revset = revset_expression.evaluate()?;
 
     let store = repo.store();
     let diff_renderer = workspace_command.diff_renderer_for_log(&args.diff_format, args.patch)?;
+    let json_format = diff_renderer
+        .as_ref()
+        .is_some_and(|renderer| renderer.has_json_format());
+    let no_graph = args.no_graph || json_format;
     let graph_style = GraphStyle::from_settings(command.settings())?;
 
     let use_elided_nodes = command
@@ -165,7 +177,7 @@ pub(crate) fn cmd_log(
         }
         let limit = args.limit.or(args.deprecated_limit).unwrap_or(usize::MAX);
 
-        if !args.no_graph {
+        if !no_graph {
             let mut graph = get_graphlog(graph_style, formatter.raw());
             let forward_iter = TopoGroupedGraphIterator::new(revset.iter_graph());
             let iter: Box<dyn Iterator<Item = _>> = if args.reversed {
@@ -217,6 +229,12 @@ pub(crate) fn cmd_log(
                 if let Some(renderer) = &diff_renderer {
                     let mut formatter = ui.new_formatter(&mut buffer);
                     let width = usize::saturating_sub(ui.term_width(), graph_width());
+                    let matcher = commit_matcher(
+                        repo.as_ref(),
+                        &commit,
+                        &fileset_expression,
+                        paths_matcher.as_ref(),
+                    )?;
                     renderer.show_patch(
                         ui,
                         formatter.as_mut(),
@@ -260,10 +278,22 @@ pub(crate) fn cmd_log(
             };
             for commit_or_error in iter.commits(store).take(limit) {
                 let commit = commit_or_error?;
-                with_content_format
-                    .write(formatter, |formatter| template.format(&commit, formatter))?;
+                if json_format {
+                    write_json_lines_template(formatter, |formatter| {
+                        template.format(&commit, formatter)
+                    })?;
+                } else {
+                    with_content_format
+                        .write(formatter, |formatter| template.format(&commit, formatter))?;
+                }
                 if let Some(renderer) = &diff_renderer {
                     let width = ui.term_width();
+                    let matcher = commit_matcher(
+                        repo.as_ref(),
+                        &commit,
+                        &fileset_expression,
+                        paths_matcher.as_ref(),
+                    )?;
                     renderer.show_patch(ui, formatter, &commit, matcher.as_ref(), width)?;
                 }
             }
@@ -297,6 +327,23 @@ pub(crate) fn cmd_log(
     Ok(())
 }
 
+/// Returns a matcher for the diff of the `commit`. The `paths_matcher` is used
+/// if available, otherwise file predicates are evaluated against the changes in
+/// the `commit`.
+fn commit_matcher(
+    repo: &dyn Repo,
+    commit: &Commit,
+    fileset_expression: &FilesetExpression,
+    paths_matcher: Option<&Arc<dyn Matcher>>,
+) -> BackendResult<Arc<dyn Matcher>> {
+    if let Some(matcher) = paths_matcher {
+        return Ok(matcher.clone());
+    }
+    let matcher =
+        fileset_expression.to_diff_matcher(&commit.parent_tree(repo)?, &commit.tree()?)?;
+    Ok(matcher.into())
+}
+
 pub fn get_node_template(
     style: GraphStyle,
     settings: &UserSettings,
@@ -13,9 +13,12 @@
 // limitations under the License.
 
 mod abandon;
+mod absorb;
+mod apply;
 mod backout;
 #[cfg(feature = "bench")]
 mod bench;
+mod bisect;
 mod bookmark;
 mod checkout;
 mod commit;
@@ -27,9 +30,11 @@ mod diffedit;
 mod duplicate;
 mod edit;
 mod evolog;
+mod export_patch;
 mod file;
 mod fix;
 mod git;
+mod histedit;
 mod init;
 mod interdiff;
 mod log;
@@ -40,9 +45,11 @@ mod next;
 mod operation;
 mod parallelize;
 mod prev;
+mod range_diff;
 mod rebase;
 mod resolve;
 mod restore;
+mod revert;
 mod root;
 mod run;
 mod show;
@@ -50,6 +57,7 @@ mod sparse;
 mod split;
 mod squash;
 mod status;
+mod sync;
 mod tag;
 mod unsquash;
 mod util;
@@ -65,18 +73,21 @@ use tracing::instrument;
 
 use crate::cli_util::Args;
 use crate::cli_util::CommandHelper;
-use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 #[derive(clap::Parser, Clone, Debug)]
 enum Command {
     Abandon(abandon::AbandonArgs),
+    Absorb(absorb::AbsorbArgs),
+    Apply(apply::ApplyArgs),
     Backout(backout::BackoutArgs),
     #[cfg(feature = "bench")]
     #[command(subcommand)]
     Bench(bench::BenchCommand),
     #[command(subcommand)]
+    Bisect(bisect::BisectCommand),
+    #[command(subcommand)]
     Bookmark(bookmark::BookmarkCommand),
     // TODO: Remove in jj 0.28+
     #[command(subcommand, hide = true)]
@@ -100,6 +111,7 @@ enum Command {
     Edit(edit::EditArgs),
     #[command(alias = "obslog", visible_alias = "evolution-log")]
     Evolog(evolog::EvologArgs),
+    ExportPatch(export_patch::ExportPatchArgs),
     #[command(subcommand)]
     File(file::FileCommand),
     /// List files in a revision (DEPRECATED use `jj file list`)
@@ -109,6 +121,7 @@ enum Command {
     Fix(fix::FixArgs),
     #[command(subcommand)]
     Git(git::GitCommand),
+    Histedit(histedit::HisteditArgs),
     Init(init::InitArgs),
     Interdiff(interdiff::InterdiffArgs),
     Log(log::LogArgs),
@@ -132,17 +145,12 @@ enum Command {
     Operation(operation::OperationCommand),
     Parallelize(parallelize::ParallelizeArgs),
     Prev(prev::PrevArgs),
+    RangeDiff(range_diff::RangeDiffArgs),
     Rebase(rebase::RebaseArgs),
     Resolve(resolve::ResolveArgs),
     Restore(restore::RestoreArgs),
-    #[command(
-        hide = true,
-        help_template = "Not a real subcommand; consider `jj backout` or `jj restore`"
-    )]
-    Revert(DummyCommandArgs),
+    Revert(revert::RevertArgs),
     Root(root::RootArgs),
-    #[command(hide = true)]
-    // TODO: Flesh out.
     Run(run::RunArgs),
     Show(show::ShowArgs),
     #[command(subcommand)]
@@ -151,6 +159,8 @@ enum Command {
     Squash(squash::SquashArgs),
     Status(status::StatusArgs),
     #[command(subcommand)]
+    Sync(sync::SyncCommand),
+    #[command(subcommand)]
     Tag(tag::TagCommand),
     #[command(subcommand)]
     Util(util::UtilCommand),
@@ -165,13 +175,6 @@ enum Command {
     Workspace(workspace::WorkspaceCommand),
 }
 
-/// A dummy command that accepts any arguments
-#[derive(clap::Args, Clone, Debug)]
-struct DummyCommandArgs {
-    #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
-    _args: Vec<String>,
-}
-
 pub fn default_app() -> clap::Command {
     Command::augment_subcommands(Args::command())
 }
@@ -181,9 +184,12 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
     let subcommand = Command::from_arg_matches(command_helper.matches()).unwrap();
     match &subcommand {
         Command::Abandon(args) => abandon::cmd_abandon(ui, command_helper, args),
+        Command::Absorb(args) => absorb::cmd_absorb(ui, command_helper, args),
+        Command::Apply(args) => apply::cmd_apply(ui, command_helper, args),
         Command::Backout(args) => backout::cmd_backout(ui, command_helper, args),
         #[cfg(feature = "bench")]
         Command::Bench(args) => bench::cmd_bench(ui, command_helper, args),
+        Command::Bisect(args) => bisect::cmd_bisect(ui, command_helper, args),
         Command::Bookmark(args) => bookmark::cmd_bookmark(ui, command_helper, args),
         Command::Branch(args) => {
             let cmd = renamed_cmd("branch", "bookmark", bookmark::cmd_bookmark);
@@ -213,6 +219,7 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         }
         Command::Fix(args) => fix::cmd_fix(ui, command_helper, args),
         Command::Git(args) => git::cmd_git(ui, command_helper, args),
+        Command::Histedit(args) => histedit::cmd_histedit(ui, command_helper, args),
         Command::Init(args) => init::cmd_init(ui, command_helper, args),
         Command::Interdiff(args) => interdiff::cmd_interdiff(ui, command_helper, args),
         Command::Log(args) => log::cmd_log(ui, command_helper, args),
@@ -221,13 +228,15 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::New(args) => new::cmd_new(ui, command_helper, args),
         Command::Next(args) => next::cmd_next(ui, command_helper, args),
         Command::Evolog(args) => evolog::cmd_evolog(ui, command_helper, args),
+        Command::ExportPatch(args) => export_patch::cmd_export_patch(ui, command_helper, args),
         Command::Operation(args) => operation::cmd_operation(ui, command_helper, args),
         Command::Parallelize(args) => parallelize::cmd_parallelize(ui, command_helper, args),
         Command::Prev(args) => prev::cmd_prev(ui, command_helper, args),
+        Command::RangeDiff(args) => range_diff::cmd_range_diff(ui, command_helper, args),
         Command::Rebase(args) => rebase::cmd_rebase(ui, command_helper, args),
         Command::Resolve(args) => resolve::cmd_resolve(ui, command_helper, args),
         Command::Restore(args) => restore::cmd_restore(ui, command_helper, args),
-        Command::Revert(_args) => revert(),
+        Command::Revert(args) => revert::cmd_revert(ui, command_helper, args),
         Command::Root(args) => root::cmd_root(ui, command_helper, args),
         Command::Run(args) => run::cmd_run(ui, command_helper, args),
         Command::Show(args) => show::cmd_show(ui, command_helper, args),
@@ -235,6 +244,7 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::Split(args) => split::cmd_split(ui, command_helper, args),
         Command::Squash(args) => squash::cmd_squash(ui, command_helper, args),
         Command::Status(args) => status::cmd_status(ui, command_helper, args),
+        Command::Sync(args) => sync::cmd_sync(ui, command_helper, args),
         Command::Tag(args) => tag::cmd_tag(ui, command_helper, args),
         Command::Undo(args) => operation::undo::cmd_op_undo(ui, command_helper, args),
         Command::Unsquash(args) => unsquash::cmd_unsquash(ui, command_helper, args),
@@ -267,13 +277,6 @@ pub(crate) fn renamed_cmd<Args>(
     }
 }
 
-fn revert() -> Result<(), CommandError> {
-    Err(user_error_with_hint(
-        "No such subcommand: revert",
-        "Consider `jj backout` or `jj restore`",
-    ))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,9 +81,7 @@ pub(crate) fn cmd_move(
     if source.id() == destination.id() {
         return Err(user_error("Source and destination cannot be the same."));
     }
-    let matcher = workspace_command
-        .parse_file_patterns(&args.paths)?
-        .to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
     let diff_selector =
         workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
     let mut tx = workspace_command.start_transaction();
@@ -98,7 +96,7 @@ pub(crate) fn cmd_move(
         command.settings(),
         &[source],
         &destination,
-        matcher.as_ref(),
+        &fileset_expression,
         &diff_selector,
         SquashedDescription::Combine,
         false,
@@ -77,7 +77,15 @@ pub fn cmd_op_show(
     let diff_renderer = {
         let formats = diff_formats_for_log(command.settings(), &args.diff_format, args.patch)?;
         let path_converter = workspace_env.path_converter();
-        (!formats.is_empty()).then(|| DiffRenderer::new(repo.as_ref(), path_converter, formats))
+        let conflict_marker_style = workspace_env.conflict_marker_style();
+        (!formats.is_empty()).then(|| {
+            DiffRenderer::new(
+                repo.as_ref(),
+                path_converter,
+                conflict_marker_style,
+                formats,
+            )
+        })
     };
 
     // TODO: Should we make this customizable via clap arg?
@@ -0,0 +1,309 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io::Read as _;
+use std::slice;
+
+use futures::executor::block_on_stream;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::conflicts::materialize_merge_result;
+use jj_lib::conflicts::materialize_tree_value;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::conflicts::MaterializedTreeValue;
+use jj_lib::diff::find_line_ranges;
+use jj_lib::diff::Diff;
+use jj_lib::diff::DiffHunk;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merged_tree::TreeDiffEntry;
+use jj_lib::repo::Repo;
+use jj_lib::rewrite::rebase_to_dest_parent;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::diff_util::DiffFormatArgs;
+use crate::formatter::Formatter;
+use crate::ui::Ui;
+
+/// Commits whose changes are at least this similar are considered to be
+/// versions of each other, if they can't be matched by change id.
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// Compare two versions of a series of revisions
+///
+/// The revisions of the old series are matched with the revisions of the new
+/// series, first by change id, and then by the similarity of their changes.
+/// Each pair is listed with the position of the revisions in their series, and
+/// the interdiff of the pair is shown if their changes differ:
+///
+/// * `=`: the changes are the same
+/// * `!`: the changes differ
+/// * `<`: the revision was removed from the series
+/// * `>`: the revision was added to the series
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct RangeDiffArgs {
+    /// The old version of the series, e.g. `main@origin..feature@origin`
+    old: RevisionArg,
+    /// The new version of the series, e.g. `main..feature`
+    new: RevisionArg,
+    #[command(flatten)]
+    format: DiffFormatArgs,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_range_diff(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &RangeDiffArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo().as_ref();
+    let mut old_commits: Vec<Commit> = workspace_command
+        .parse_revset(&args.old)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    old_commits.reverse();
+    let mut new_commits: Vec<Commit> = workspace_command
+        .parse_revset(&args.new)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    new_commits.reverse();
+    let matches = match_commits(repo, &old_commits, &new_commits)?;
+
+    let diff_renderer = workspace_command.diff_renderer_for(&args.format)?;
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+    let mut old_index_to_new: HashMap<usize, usize> = HashMap::new();
+    for (new_index, old_index) in matches.iter().enumerate() {
+        if let Some(old_index) = old_index {
+            old_index_to_new.insert(*old_index, new_index);
+        }
+    }
+    // Removed commits are listed before the first new commit that matches a
+    // later old commit
+    let mut next_old_index = 0;
+    let mut write_removed_until =
+        |formatter: &mut dyn Formatter, end: usize| -> Result<(), CommandError> {
+            while next_old_index < end {
+                if !old_index_to_new.contains_key(&next_old_index) {
+                    write_pair_line(
+                        formatter,
+                        Some((next_old_index, &old_commits[next_old_index])),
+                        None,
+                        "<",
+                    )?;
+                }
+                next_old_index += 1;
+            }
+            Ok(())
+        };
+    for (new_index, new_commit) in new_commits.iter().enumerate() {
+        let Some(old_index) = matches[new_index] else {
+            write_pair_line(formatter, None, Some((new_index, new_commit)), ">")?;
+            continue;
+        };
+        write_removed_until(formatter, old_index)?;
+        let old_commit = &old_commits[old_index];
+        let old_tree = rebase_to_dest_parent(repo, slice::from_ref(old_commit), new_commit)?;
+        if old_tree.id() == *new_commit.tree_id() {
+            write_pair_line(
+                formatter,
+                Some((old_index, old_commit)),
+                Some((new_index, new_commit)),
+                "=",
+            )?;
+        } else {
+            write_pair_line(
+                formatter,
+                Some((old_index, old_commit)),
+                Some((new_index, new_commit)),
+                "!",
+            )?;
+            diff_renderer.show_inter_diff(
+                ui,
+                formatter,
+                slice::from_ref(old_commit),
+                new_commit,
+                &EverythingMatcher,
+                ui.term_width(),
+            )?;
+        }
+    }
+    write_removed_until(formatter, old_commits.len())?;
+    Ok(())
+}
+
+/// Writes a line like `1: 0123456789ab ! 1: 3456789abcde description`.
+fn write_pair_line(
+    formatter: &mut dyn Formatter,
+    old: Option<(usize, &Commit)>,
+    new: Option<(usize, &Commit)>,
+    marker: &str,
+) -> Result<(), CommandError> {
+    let write_side = |formatter: &mut dyn Formatter,
+                      side: Option<(usize, &Commit)>|
+     -> Result<(), CommandError> {
+        match side {
+            Some((index, commit)) => {
+                write!(formatter, "{}: ", index + 1)?;
+                write!(
+                    formatter.labeled("commit_id"),
+                    "{}",
+                    short_commit_hash(commit.id())
+                )?;
+            }
+            None => write!(formatter, "-: {}", "-".repeat(12))?,
+        }
+        Ok(())
+    };
+    write_side(formatter, old)?;
+    write!(formatter, " {marker} ")?;
+    write_side(formatter, new)?;
+    let (_, commit) = new.or(old).unwrap();
+    let description = commit.description().lines().next().unwrap_or_default();
+    if description.is_empty() {
+        writeln!(
+            formatter.labeled("description placeholder"),
+            " (no description set)"
+        )?;
+    } else {
+        writeln!(formatter, " {description}")?;
+    }
+    Ok(())
+}
+
+/// Matches each new commit with an old commit. Commits with the same change id
+/// are matched first. The remaining commits are matched by the similarity of
+/// the lines they change, most similar first.
+fn match_commits(
+    repo: &dyn Repo,
+    old_commits: &[Commit],
+    new_commits: &[Commit],
+) -> Result<Vec<Option<usize>>, CommandError> {
+    let mut matches: Vec<Option<usize>> = vec![None; new_commits.len()];
+    let mut old_matched = vec![false; old_commits.len()];
+    let old_by_change_id: HashMap<_, _> = old_commits
+        .iter()
+        .enumerate()
+        .map(|(index, commit)| (commit.change_id(), index))
+        .collect();
+    for (new_index, new_commit) in new_commits.iter().enumerate() {
+        if let Some(&old_index) = old_by_change_id.get(new_commit.change_id()) {
+            if !old_matched[old_index] {
+                matches[new_index] = Some(old_index);
+                old_matched[old_index] = true;
+            }
+        }
+    }
+
+    let unmatched_old: Vec<_> = (0..old_commits.len())
+        .filter(|&index| !old_matched[index])
+        .map(|index| Ok((index, changed_lines(repo, &old_commits[index])?)))
+        .try_collect::<_, _, CommandError>()?;
+    let unmatched_new: Vec<_> = (0..new_commits.len())
+        .filter(|&index| matches[index].is_none())
+        .map(|index| Ok((index, changed_lines(repo, &new_commits[index])?)))
+        .try_collect::<_, _, CommandError>()?;
+    let mut candidates = vec![];
+    for (old_index, old_lines) in &unmatched_old {
+        for (new_index, new_lines) in &unmatched_new {
+            let similarity = similarity(old_lines, new_lines);
+            if similarity >= MIN_SIMILARITY {
+                candidates.push((similarity, *old_index, *new_index));
+            }
+        }
+    }
+    candidates.sort_by(|(a, ..), (b, ..)| b.total_cmp(a));
+    for (_, old_index, new_index) in candidates {
+        if !old_matched[old_index] && matches[new_index].is_none() {
+            matches[new_index] = Some(old_index);
+            old_matched[old_index] = true;
+        }
+    }
+    Ok(matches)
+}
+
+/// Counts of the lines added and removed by a commit, keyed by the path, the
+/// kind of change, and the line.
+type ChangedLines = HashMap<Vec<u8>, usize>;
+
+fn changed_lines(repo: &dyn Repo, commit: &Commit) -> Result<ChangedLines, CommandError> {
+    let store = repo.store();
+    let from_tree = commit.parent_tree(repo)?;
+    let to_tree = commit.tree()?;
+    let mut lines = ChangedLines::new();
+    let diff_stream = from_tree.diff_stream(&to_tree, &EverythingMatcher);
+    for TreeDiffEntry { path, values } in block_on_stream(diff_stream) {
+        let (before, after) = values?;
+        let before = materialize_tree_value(store, &path, before).block_on()?;
+        let after = materialize_tree_value(store, &path, after).block_on()?;
+        let before = file_contents(before)?;
+        let after = file_contents(after)?;
+        for hunk in Diff::by_line([&before, &after]).hunks() {
+            let DiffHunk::Different(contents) = hunk else {
+                continue;
+            };
+            for (kind, text) in [(b'-', contents[0]), (b'+', contents[1])] {
+                for range in find_line_ranges(text) {
+                    let key = [
+                        path.as_internal_file_string().as_bytes(),
+                        &[0, kind],
+                        &text[range],
+                    ]
+                    .concat();
+                    *lines.entry(key).or_default() += 1;
+                }
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// Reads the contents of a file for comparison. Other kinds of values don't
+/// have lines to compare.
+fn file_contents(value: MaterializedTreeValue) -> Result<Vec<u8>, CommandError> {
+    let mut contents = vec![];
+    match value {
+        MaterializedTreeValue::File { mut reader, .. } => {
+            reader.read_to_end(&mut contents)?;
+        }
+        MaterializedTreeValue::FileConflict {
+            contents: merge, ..
+        } => {
+            materialize_merge_result(&merge, ConflictMarkerStyle::default(), &mut contents)?;
+        }
+        MaterializedTreeValue::Symlink { target, .. } => contents = target.into_bytes(),
+        _ => {}
+    }
+    Ok(contents)
+}
+
+/// Returns the fraction of changed lines that the commits have in common.
+fn similarity(a: &ChangedLines, b: &ChangedLines) -> f64 {
+    let total = a.values().sum::<usize>() + b.values().sum::<usize>();
+    if total == 0 {
+        return 0.0;
+    }
+    let common: usize = a
+        .iter()
+        .map(|(line, count)| (*count).min(b.get(line).copied().unwrap_or(0)))
+        .sum();
+    (2 * common) as f64 / total as f64
+}
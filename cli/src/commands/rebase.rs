@@ -249,52 +249,15 @@ pub(crate) fn cmd_rebase(
             .parse_union_revsets(&args.revisions)?
             .evaluate_to_commits()?
             .try_collect()?; // in reverse topological order
-        if !args.insert_after.is_empty() && !args.insert_before.is_empty() {
-            let after_commits =
-                workspace_command.resolve_some_revsets_default_single(&args.insert_after)?;
-            let before_commits =
-                workspace_command.resolve_some_revsets_default_single(&args.insert_before)?;
-            rebase_revisions_after_before(
-                ui,
-                command.settings(),
-                &mut workspace_command,
-                &after_commits,
-                &before_commits,
-                &target_commits,
-            )?;
-        } else if !args.insert_after.is_empty() {
-            let after_commits =
-                workspace_command.resolve_some_revsets_default_single(&args.insert_after)?;
-            rebase_revisions_after(
-                ui,
-                command.settings(),
-                &mut workspace_command,
-                &after_commits,
-                &target_commits,
-            )?;
-        } else if !args.insert_before.is_empty() {
-            let before_commits =
-                workspace_command.resolve_some_revsets_default_single(&args.insert_before)?;
-            rebase_revisions_before(
-                ui,
-                command.settings(),
-                &mut workspace_command,
-                &before_commits,
-                &target_commits,
-            )?;
-        } else {
-            let new_parents = workspace_command
-                .resolve_some_revsets_default_single(&args.destination)?
-                .into_iter()
-                .collect_vec();
-            rebase_revisions(
-                ui,
-                command.settings(),
-                &mut workspace_command,
-                &new_parents,
-                &target_commits,
-            )?;
-        }
+        rebase_revisions(
+            ui,
+            command.settings(),
+            &mut workspace_command,
+            &args.destination,
+            &args.insert_after,
+            &args.insert_before,
+            &target_commits,
+        )?;
     } else if !args.source.is_empty() {
         let new_parents = workspace_command
             .resolve_some_revsets_default_single(&args.destination)?
@@ -436,7 +399,9 @@ fn rebase_revisions(
     ui: &mut Ui,
     settings: &UserSettings,
     workspace_command: &mut WorkspaceCommandHelper,
-    new_parents: &[Commit],
+    destination: &[RevisionArg],
+    insert_after: &[RevisionArg],
+    insert_before: &[RevisionArg],
     target_commits: &[Commit],
 ) -> Result<(), CommandError> {
     if target_commits.is_empty() {
@@ -444,90 +409,23 @@ fn rebase_revisions(
     }
 
     workspace_command.check_rewritable(target_commits.iter().ids())?;
-    for commit in target_commits.iter() {
-        if new_parents.contains(commit) {
-            return Err(user_error(format!(
-                "Cannot rebase {} onto itself",
-                short_commit_hash(commit.id()),
-            )));
-        }
-    }
-
-    move_commits_transaction(
-        ui,
-        settings,
+    let (new_parent_ids, new_children) = compute_commit_location(
         workspace_command,
-        &new_parents.iter().ids().cloned().collect_vec(),
-        &[],
-        target_commits,
-    )
-}
-
-fn rebase_revisions_after(
-    ui: &mut Ui,
-    settings: &UserSettings,
-    workspace_command: &mut WorkspaceCommandHelper,
-    after_commits: &IndexSet<Commit>,
-    target_commits: &[Commit],
-) -> Result<(), CommandError> {
-    workspace_command.check_rewritable(target_commits.iter().ids())?;
-
-    let after_commit_ids = after_commits.iter().ids().cloned().collect_vec();
-    let new_parents_expression = RevsetExpression::commits(after_commit_ids.clone());
-    let new_children_expression = new_parents_expression.children();
-
-    ensure_no_commit_loop(
-        workspace_command.repo().as_ref(),
-        &new_children_expression,
-        &new_parents_expression,
+        destination,
+        insert_after,
+        insert_before,
+        "rebased commits",
     )?;
-
-    let new_parent_ids = after_commit_ids;
-    let new_children: Vec<_> = new_children_expression
-        .evaluate_programmatic(workspace_command.repo().as_ref())?
-        .iter()
-        .commits(workspace_command.repo().store())
-        .try_collect()?;
-    workspace_command.check_rewritable(new_children.iter().ids())?;
-
-    move_commits_transaction(
-        ui,
-        settings,
-        workspace_command,
-        &new_parent_ids,
-        &new_children,
-        target_commits,
-    )
-}
-
-fn rebase_revisions_before(
-    ui: &mut Ui,
-    settings: &UserSettings,
-    workspace_command: &mut WorkspaceCommandHelper,
-    before_commits: &IndexSet<Commit>,
-    target_commits: &[Commit],
-) -> Result<(), CommandError> {
-    workspace_command.check_rewritable(target_commits.iter().ids())?;
-    let before_commit_ids = before_commits.iter().ids().cloned().collect_vec();
-    workspace_command.check_rewritable(&before_commit_ids)?;
-
-    let new_children_expression = RevsetExpression::commits(before_commit_ids);
-    let new_parents_expression = new_children_expression.parents();
-
-    ensure_no_commit_loop(
-        workspace_command.repo().as_ref(),
-        &new_children_expression,
-        &new_parents_expression,
-    )?;
-
-    // Not using `new_parents_expression` here to persist the order of parents
-    // specified in `before_commits`.
-    let new_parent_ids: IndexSet<_> = before_commits
-        .iter()
-        .flat_map(|commit| commit.parent_ids().iter().cloned().collect_vec())
-        .collect();
-    let new_parent_ids = new_parent_ids.into_iter().collect_vec();
-    let new_children = before_commits.iter().cloned().collect_vec();
+    if insert_after.is_empty() && insert_before.is_empty() {
+        for commit in target_commits.iter() {
+            if new_parent_ids.contains(commit.id()) {
+                return Err(user_error(format!(
+                    "Cannot rebase {} onto itself",
+                    short_commit_hash(commit.id()),
+                )));
+            }
+        }
+    }
 
     move_commits_transaction(
         ui,
@@ -539,39 +437,94 @@ fn rebase_revisions_before(
     )
 }
 
-fn rebase_revisions_after_before(
-    ui: &mut Ui,
-    settings: &UserSettings,
-    workspace_command: &mut WorkspaceCommandHelper,
-    after_commits: &IndexSet<Commit>,
-    before_commits: &IndexSet<Commit>,
-    target_commits: &[Commit],
-) -> Result<(), CommandError> {
-    workspace_command.check_rewritable(target_commits.iter().ids())?;
-    let before_commit_ids = before_commits.iter().ids().cloned().collect_vec();
-    workspace_command.check_rewritable(&before_commit_ids)?;
-
-    let after_commit_ids = after_commits.iter().ids().cloned().collect_vec();
-    let new_children_expression = RevsetExpression::commits(before_commit_ids);
-    let new_parents_expression = RevsetExpression::commits(after_commit_ids.clone());
+/// Computes the new parents and new children of commits to be placed at the
+/// location given by `--destination`, `--insert-after` and `--insert-before`.
+///
+/// If `--insert-after` or `--insert-before` is given, `destination` is
+/// ignored. The new children are checked to be rewritable, and the location is
+/// checked not to create a loop. `commit_type` describes the placed commits
+/// in error messages.
+pub(crate) fn compute_commit_location(
+    workspace_command: &WorkspaceCommandHelper,
+    destination: &[RevisionArg],
+    insert_after: &[RevisionArg],
+    insert_before: &[RevisionArg],
+    commit_type: &str,
+) -> Result<(Vec<CommitId>, Vec<Commit>), CommandError> {
+    if !insert_after.is_empty() && !insert_before.is_empty() {
+        let after_commits = workspace_command.resolve_some_revsets_default_single(insert_after)?;
+        let before_commits =
+            workspace_command.resolve_some_revsets_default_single(insert_before)?;
+        let before_commit_ids = before_commits.iter().ids().cloned().collect_vec();
+        workspace_command.check_rewritable(&before_commit_ids)?;
+
+        let after_commit_ids = after_commits.iter().ids().cloned().collect_vec();
+        let new_children_expression = RevsetExpression::commits(before_commit_ids);
+        let new_parents_expression = RevsetExpression::commits(after_commit_ids.clone());
+
+        ensure_no_commit_loop(
+            workspace_command.repo().as_ref(),
+            &new_children_expression,
+            &new_parents_expression,
+            commit_type,
+        )?;
 
-    ensure_no_commit_loop(
-        workspace_command.repo().as_ref(),
-        &new_children_expression,
-        &new_parents_expression,
-    )?;
+        let new_children = before_commits.into_iter().collect_vec();
+        Ok((after_commit_ids, new_children))
+    } else if !insert_after.is_empty() {
+        let after_commits = workspace_command.resolve_some_revsets_default_single(insert_after)?;
+        let after_commit_ids = after_commits.iter().ids().cloned().collect_vec();
+        let new_parents_expression = RevsetExpression::commits(after_commit_ids.clone());
+        let new_children_expression = new_parents_expression.children();
+
+        ensure_no_commit_loop(
+            workspace_command.repo().as_ref(),
+            &new_children_expression,
+            &new_parents_expression,
+            commit_type,
+        )?;
 
-    let new_parent_ids = after_commit_ids;
-    let new_children = before_commits.iter().cloned().collect_vec();
+        let new_children: Vec<_> = new_children_expression
+            .evaluate_programmatic(workspace_command.repo().as_ref())?
+            .iter()
+            .commits(workspace_command.repo().store())
+            .try_collect()?;
+        workspace_command.check_rewritable(new_children.iter().ids())?;
+        Ok((after_commit_ids, new_children))
+    } else if !insert_before.is_empty() {
+        let before_commits =
+            workspace_command.resolve_some_revsets_default_single(insert_before)?;
+        let before_commit_ids = before_commits.iter().ids().cloned().collect_vec();
+        workspace_command.check_rewritable(&before_commit_ids)?;
+
+        let new_children_expression = RevsetExpression::commits(before_commit_ids);
+        let new_parents_expression = new_children_expression.parents();
+
+        ensure_no_commit_loop(
+            workspace_command.repo().as_ref(),
+            &new_children_expression,
+            &new_parents_expression,
+            commit_type,
+        )?;
 
-    move_commits_transaction(
-        ui,
-        settings,
-        workspace_command,
-        &new_parent_ids,
-        &new_children,
-        target_commits,
-    )
+        // Not using `new_parents_expression` here to persist the order of parents
+        // specified in `before_commits`.
+        let new_parent_ids: IndexSet<_> = before_commits
+            .iter()
+            .flat_map(|commit| commit.parent_ids().iter().cloned().collect_vec())
+            .collect();
+        let new_parent_ids = new_parent_ids.into_iter().collect_vec();
+        let new_children = before_commits.into_iter().collect_vec();
+        Ok((new_parent_ids, new_children))
+    } else {
+        let new_parent_ids = workspace_command
+            .resolve_some_revsets_default_single(destination)?
+            .iter()
+            .ids()
+            .cloned()
+            .collect_vec();
+        Ok((new_parent_ids, vec![]))
+    }
 }
 
 /// Wraps `move_commits` in a transaction.
@@ -637,6 +590,7 @@ fn ensure_no_commit_loop(
     repo: &ReadonlyRepo,
     children_expression: &Rc<RevsetExpression>,
     parents_expression: &Rc<RevsetExpression>,
+    commit_type: &str,
 ) -> Result<(), CommandError> {
     if let Some(commit_id) = children_expression
         .dag_range_to(parents_expression)
@@ -646,7 +600,7 @@ fn ensure_no_commit_loop(
     {
         return Err(user_error(format!(
             "Refusing to create a loop: commit {} would be both an ancestor and a descendant of \
-             the rebased commits",
+             the {commit_type}",
             short_commit_hash(&commit_id),
         )));
     }
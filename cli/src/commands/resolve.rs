@@ -15,13 +15,19 @@
 use std::io::Write;
 
 use itertools::Itertools;
+use jj_lib::commit::Commit;
+use jj_lib::dag_walk;
+use jj_lib::matchers::Matcher;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
+use jj_lib::rerere::forget_resolutions;
 use tracing::instrument;
 
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::cli_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
@@ -33,6 +39,11 @@ use crate::ui::Ui;
 /// Note that conflicts can also be resolved without using this command. You may
 /// edit the conflict markers in the conflicted file directly with a text
 /// editor.
+///
+/// If `merge.reuse-resolutions` is enabled, however a conflict is resolved, the
+/// resolution is recorded and reused when the same conflict appears again, e.g.
+/// when the commit is rebased again. Use `--forget` to forget the recorded
+/// resolutions.
 //  TODOs:
 //   - `jj resolve --editor` to resolve a conflict in the default text editor. Should work for
 //     conflicts with 3+ adds. Useful to resolve conflicts in a commit other than the current one.
@@ -52,6 +63,14 @@ pub(crate) struct ResolveArgs {
     /// Specify 3-way merge tool to be used
     #[arg(long, conflicts_with = "list", value_name = "NAME")]
     tool: Option<String>,
+    /// Forget the recorded resolutions of the conflicts instead of resolving
+    /// them
+    ///
+    /// The revision may also be one where the conflicts were resolved. The
+    /// conflicts are then looked up in the previous versions of the revision
+    /// from `jj evolog` and in its parents.
+    #[arg(long, conflicts_with_all = ["list", "tool"])]
+    forget: bool,
     /// Restrict to these paths when searching for a conflict to resolve. We
     /// will attempt to resolve the first conflict we can find. You can use
     /// the `--list` argument to find paths to use here.
@@ -67,15 +86,18 @@ pub(crate) fn cmd_resolve(
     args: &ResolveArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
-    let matcher = workspace_command
-        .parse_file_patterns(&args.paths)?
-        .to_matcher();
     let commit = workspace_command.resolve_single_rev(&args.revision)?;
     let tree = commit.tree()?;
+    let matcher = workspace_command
+        .parse_file_patterns(&args.paths)?
+        .to_tree_matcher(&tree)?;
     let conflicts = tree
         .conflicts()
         .filter(|path| matcher.matches(&path.0))
         .collect_vec();
+    if args.forget {
+        return forget_recorded_resolutions(ui, &commit, matcher.as_ref(), !args.paths.is_empty());
+    }
     if conflicts.is_empty() {
         return Err(cli_error(if args.paths.is_empty() {
             "No conflicts found at this revision"
@@ -90,7 +112,6 @@ pub(crate) fn cmd_resolve(
             &workspace_command,
         );
     };
-
     let (repo_path, _) = conflicts.first().unwrap();
     workspace_command.check_rewritable([commit.id()])?;
     let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
@@ -129,3 +150,63 @@ pub(crate) fn cmd_resolve(
     }
     Ok(())
 }
+
+/// Forgets the recorded resolutions of the conflicts at the matched paths.
+///
+/// If the commit doesn't have any such conflicts, e.g. because they were
+/// resolved in it, the conflicts are looked up in the commits it evolved from
+/// and in its parents instead.
+fn forget_recorded_resolutions(
+    ui: &mut Ui,
+    commit: &Commit,
+    matcher: &dyn Matcher,
+    has_paths: bool,
+) -> Result<(), CommandError> {
+    let store = commit.store();
+    let Some(resolution_store) = store.resolution_store() else {
+        return Err(user_error_with_hint(
+            "Conflict resolutions are not being recorded",
+            "Set `merge.reuse-resolutions = true` to record and reuse them.",
+        ));
+    };
+    let matching_conflicts = |tree: &MergedTree| {
+        tree.conflicts()
+            .map(|(path, _)| path)
+            .filter(|path| matcher.matches(path))
+            .collect_vec()
+    };
+    let tree = commit.tree()?;
+    let mut conflicted_trees = vec![];
+    let paths = matching_conflicts(&tree);
+    if paths.is_empty() {
+        let predecessors = dag_walk::dfs_ok(
+            commit.predecessors(),
+            |commit: &Commit| commit.id().clone(),
+            |commit: &Commit| commit.predecessors().collect_vec(),
+        );
+        for other in itertools::chain(predecessors, commit.parents()) {
+            let other_tree = other?.tree()?;
+            let paths = matching_conflicts(&other_tree);
+            if !paths.is_empty() {
+                conflicted_trees.push((other_tree, paths));
+            }
+        }
+    } else {
+        conflicted_trees.push((tree, paths));
+    }
+    if conflicted_trees.is_empty() {
+        return Err(cli_error(if has_paths {
+            "No conflicts found at the given path(s) in this revision or the revisions it was \
+             resolved from"
+        } else {
+            "No conflicts found in this revision or the revisions it was resolved from"
+        }));
+    }
+    let mut num_forgotten = 0;
+    for (tree, paths) in &conflicted_trees {
+        let paths = paths.iter().map(AsRef::as_ref).collect_vec();
+        num_forgotten += forget_resolutions(store, resolution_store, tree, &paths)?;
+    }
+    writeln!(ui.status(), "Forgot {num_forgotten} recorded resolutions")?;
+    Ok(())
+}
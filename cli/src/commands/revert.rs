@@ -0,0 +1,192 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::io::Write;
+
+use bstr::ByteVec as _;
+use clap::ArgGroup;
+use indexmap::IndexSet;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::CommitIteratorExt;
+use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo;
+use jj_lib::rewrite::merge_commit_trees;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::commands::rebase::compute_commit_location;
+use crate::formatter::PlainTextFormatter;
+use crate::ui::Ui;
+
+/// Apply the reverse of the given revision(s)
+///
+/// The reverse of each of the given revisions is applied sequentially in
+/// reverse topological order at the given location, creating one new revision
+/// per reverted revision. The new revisions can be placed on top of
+/// `--destination`, or inserted into the graph with `--insert-after` and/or
+/// `--insert-before`, in which case the descendants are rebased onto the last
+/// new revision.
+///
+/// The description of the new revisions can be customized with the
+/// `templates.revert_description` config variable, which is rendered against
+/// the revision being reverted.
+#[derive(clap::Args, Clone, Debug)]
+#[command(group(ArgGroup::new("location").args(&["destination", "insert_after", "insert_before"]).multiple(true).required(true)))]
+pub(crate) struct RevertArgs {
+    /// The revision(s) to apply the reverse of
+    #[arg(long, short, required = true)]
+    revisions: Vec<RevisionArg>,
+    /// The revision(s) to apply the reverse changes on top of (can be repeated
+    /// to create a merge commit)
+    #[arg(long, short)]
+    destination: Vec<RevisionArg>,
+    /// The revision(s) to insert the reverse changes after (can be repeated to
+    /// create a merge commit)
+    #[arg(
+        long,
+        short = 'A',
+        visible_alias = "after",
+        conflicts_with = "destination"
+    )]
+    insert_after: Vec<RevisionArg>,
+    /// The revision(s) to insert the reverse changes before (can be repeated
+    /// to create a merge commit)
+    #[arg(
+        long,
+        short = 'B',
+        visible_alias = "before",
+        conflicts_with = "destination"
+    )]
+    insert_before: Vec<RevisionArg>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_revert(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &RevertArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let to_revert: Vec<_> = workspace_command
+        .parse_union_revsets(&args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?; // in reverse topological order
+    if to_revert.is_empty() {
+        writeln!(ui.status(), "No revisions to revert.")?;
+        return Ok(());
+    }
+    let (new_parent_ids, new_children) = compute_commit_location(
+        &workspace_command,
+        &args.destination,
+        &args.insert_after,
+        &args.insert_before,
+        "reverted commits",
+    )?;
+    let template_text = command
+        .settings()
+        .config()
+        .get_string("templates.revert_description")?;
+    let new_descriptions = {
+        let template = workspace_command.parse_commit_template(&template_text)?;
+        to_revert
+            .iter()
+            .map(|commit| {
+                let mut output = Vec::new();
+                template
+                    .format(commit, &mut PlainTextFormatter::new(&mut output))
+                    .expect("write() to vec backed formatter should never fail");
+                output.into_string_lossy()
+            })
+            .collect_vec()
+    };
+
+    let mut tx = workspace_command.start_transaction();
+    let transaction_description = if to_revert.len() == 1 {
+        format!("revert commit {}", to_revert[0].id().hex())
+    } else {
+        format!(
+            "revert commit {} and {} more",
+            to_revert[0].id().hex(),
+            to_revert.len() - 1
+        )
+    };
+    let new_parents: Vec<_> = new_parent_ids
+        .iter()
+        .map(|id| tx.repo().store().get_commit(id))
+        .try_collect()?;
+    let mut new_base_tree = merge_commit_trees(tx.repo(), &new_parents)?;
+    let mut parent_ids = new_parent_ids.clone();
+    let mut reverted_commits = vec![];
+    for (commit_to_revert, new_description) in to_revert.iter().zip(new_descriptions) {
+        let old_base_tree = commit_to_revert.parent_tree(tx.repo())?;
+        let old_tree = commit_to_revert.tree()?;
+        let new_tree = new_base_tree.merge(&old_tree, &old_base_tree)?;
+        let new_commit = tx
+            .repo_mut()
+            .new_commit(command.settings(), parent_ids, new_tree.id())
+            .set_description(new_description)
+            .write()?;
+        parent_ids = vec![new_commit.id().clone()];
+        reverted_commits.push(new_commit);
+        new_base_tree = new_tree;
+    }
+
+    // Rebase the new children onto the last reverted commit, replacing any of
+    // the new parents they had.
+    let last_commit_id = reverted_commits.last().unwrap().id().clone();
+    let new_parent_ids_set: HashSet<&CommitId> = new_parent_ids.iter().collect();
+    let new_child_ids: HashSet<CommitId> = new_children.iter().ids().cloned().collect();
+    let mut num_rebased = 0;
+    tx.repo_mut().transform_descendants(
+        command.settings(),
+        new_child_ids.iter().cloned().collect(),
+        |mut rewriter| {
+            if new_child_ids.contains(rewriter.old_commit().id()) {
+                let mut child_new_parent_ids: IndexSet<CommitId> = rewriter
+                    .new_parents()
+                    .iter()
+                    .filter(|id| !new_parent_ids_set.contains(id))
+                    .cloned()
+                    .collect();
+                child_new_parent_ids.insert(last_commit_id.clone());
+                rewriter.set_new_parents(child_new_parent_ids.into_iter().collect());
+            }
+            num_rebased += 1;
+            rewriter.rebase(command.settings())?.write()?;
+            Ok(())
+        },
+    )?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(
+            formatter,
+            "Reverted {} commits as follows:",
+            reverted_commits.len()
+        )?;
+        for commit in &reverted_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+    }
+    tx.finish(ui, transaction_description)?;
+    Ok(())
+}
@@ -14,19 +14,62 @@
 
 //! This file contains the internal implementation of `run`.
 
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::Command;
+use std::process::Output;
+use std::sync::Arc;
+
+use futures::StreamExt as _;
 use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::commit::Commit;
+use jj_lib::commit::CommitIteratorExt as _;
+use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::hex_util::to_reverse_hex;
+use jj_lib::local_working_copy::TreeState;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::merged_tree::TreeDiffEntry;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::store::Store;
+use jj_lib::working_copy::CheckoutOptions;
+use jj_lib::working_copy::SnapshotOptions;
+use pollster::FutureExt as _;
+use rayon::iter::IntoParallelRefIterator as _;
+use rayon::iter::ParallelIterator as _;
+use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::internal_error;
+use crate::command_error::internal_error_with_message;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
-/// (**Stub**, does not work yet) Run a command across a set of revisions.
+/// Run a command across a set of revisions.
+///
+/// Each revision is checked out into a temporary working copy under the `.jj`
+/// directory, and the command is run there through the shell. The user's
+/// working copy is never touched. Any changes the command makes to the files
+/// are recorded by rewriting the revision, and descendants are rebased on top
+/// of the rewritten revisions. If the command changes the same file in a
+/// revision and in one of its ancestors, the version from the descendant is
+/// kept instead of creating a conflict.
 ///
+/// The command's output is printed once it has finished, along with its exit
+/// status. If the command fails in any of the revisions, `jj run` will exit
+/// with an error after recording the changes from all revisions.
 ///
-/// All recorded state will be persisted in the `.jj` directory, so occasionally
-/// a `jj run --clean` is needed to clean up disk space.
+/// The following environment variables are set for the command:
+///  - `JJ_CHANGE_ID`: The full change id of the revision.
+///  - `JJ_COMMIT_ID`: The full commit id of the revision.
 ///
 /// # Example
 ///
@@ -50,21 +93,214 @@ pub struct RunArgs {
     jobs: Option<usize>,
 }
 
+/// The result of running the command in a single revision.
+pub(crate) struct RunResult {
+    /// The tree of the temporary working copy after the command finished.
+    pub tree_id: MergedTreeId,
+    /// The exit status and captured output of the command.
+    pub output: Output,
+}
+
+#[instrument(skip_all)]
 pub fn cmd_run(ui: &mut Ui, command: &CommandHelper, args: &RunArgs) -> Result<(), CommandError> {
-    let workspace_command = command.workspace_helper(ui)?;
-    let _resolved_commits: Vec<_> = workspace_command
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let resolved_commits: Vec<_> = workspace_command
         .parse_union_revsets(&args.revisions)?
         .evaluate_to_commits()?
         .try_collect()?;
+    if resolved_commits.is_empty() {
+        writeln!(ui.status(), "No revisions to run the command in.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(resolved_commits.iter().ids())?;
     // Jobs are resolved in this order:
     // 1. Commandline argument iff > 0.
     // 2. the amount of cores available.
     // 3. a single job, if all of the above fails.
-    let _jobs = match args.jobs {
+    let jobs = match args.jobs {
         Some(0) | None => std::thread::available_parallelism().map(|t| t.into()).ok(),
         Some(jobs) => Some(jobs),
     }
     // Fallback to a single user-visible job.
     .unwrap_or(1usize);
-    Err(user_error("This is a stub, do not use"))
+
+    let base_ignores = workspace_command.base_ignores()?;
+    let auto_tracking_matcher = workspace_command.auto_tracking_matcher()?;
+    let max_new_file_size = command.settings().max_new_file_size()?;
+    let conflict_marker_style = workspace_command.conflict_marker_style();
+    let run_dir = workspace_command.repo_path().join("run");
+    std::fs::create_dir_all(&run_dir).map_err(|err| {
+        user_error_with_message(
+            format!("Failed to create directory {}", run_dir.display()),
+            err,
+        )
+    })?;
+    let store = workspace_command.repo().store().clone();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(internal_error)?;
+    let results: Vec<RunResult> = pool.install(|| {
+        resolved_commits
+            .par_iter()
+            .map(|commit| {
+                let snapshot_options = SnapshotOptions {
+                    base_ignores: base_ignores.clone(),
+                    fsmonitor_settings: FsmonitorSettings::None,
+                    progress: None,
+                    start_tracking_matcher: auto_tracking_matcher.as_ref(),
+                    max_new_file_size,
+                    conflict_marker_style,
+                };
+                run_in_revision(
+                    &store,
+                    &run_dir,
+                    commit,
+                    &args.shell_command,
+                    &snapshot_options,
+                )
+            })
+            .collect::<Result<_, CommandError>>()
+    })?;
+
+    let mut new_tree_ids: HashMap<CommitId, MergedTreeId> = HashMap::new();
+    let mut num_failed = 0;
+    for (commit, result) in resolved_commits.iter().zip(&results) {
+        if let Some(mut formatter) = ui.status_formatter() {
+            write!(formatter, "Ran command in ")?;
+            workspace_command.write_commit_summary(formatter.as_mut(), commit)?;
+            match result.output.status.code() {
+                Some(code) => writeln!(formatter, ": exit status {code}")?,
+                None => writeln!(formatter, ": terminated by signal")?,
+            }
+        }
+        ui.stdout().write_all(&result.output.stdout)?;
+        ui.stderr().write_all(&result.output.stderr)?;
+        if !result.output.status.success() {
+            num_failed += 1;
+        }
+        if result.tree_id != *commit.tree_id() {
+            new_tree_ids.insert(commit.id().clone(), result.tree_id.clone());
+        }
+    }
+
+    if !new_tree_ids.is_empty() {
+        let mut tx = workspace_command.start_transaction();
+        let store = tx.repo().store().clone();
+        let mut num_rewritten = 0;
+        let mut num_rebased = 0;
+        tx.repo_mut().transform_descendants(
+            command.settings(),
+            new_tree_ids.keys().cloned().collect_vec(),
+            |rewriter| {
+                let old_commit = rewriter.old_commit().clone();
+                if let Some(new_tree_id) = new_tree_ids.get(old_commit.id()) {
+                    // The command ran against the old tree, so we apply the files it changed
+                    // on top of the rebased tree. Like `jj fix`, we overwrite these files
+                    // instead of merging them, so changes made by the command in ancestors
+                    // don't result in conflicts.
+                    let old_tree = old_commit.tree()?;
+                    let new_tree = store.get_root_tree(new_tree_id)?;
+                    let builder = rewriter.rebase(command.settings())?;
+                    let mut tree_builder = MergedTreeBuilder::new(builder.tree_id().clone());
+                    let mut diff_stream = old_tree.diff_stream(&new_tree, &EverythingMatcher);
+                    async {
+                        while let Some(TreeDiffEntry { path, values }) = diff_stream.next().await {
+                            let (_before, after) = values?;
+                            tree_builder.set_or_remove(path, after);
+                        }
+                        Ok::<(), BackendError>(())
+                    }
+                    .block_on()?;
+                    let new_tree_id = tree_builder.write_tree(&store)?;
+                    builder.set_tree_id(new_tree_id).write()?;
+                    num_rewritten += 1;
+                } else if rewriter.parents_changed() {
+                    rewriter.rebase(command.settings())?.write()?;
+                    num_rebased += 1;
+                }
+                Ok(())
+            },
+        )?;
+        writeln!(
+            ui.status(),
+            "Rewrote {num_rewritten} commits and rebased {num_rebased} descendant commits"
+        )?;
+        tx.finish(
+            ui,
+            format!(
+                "run command '{}' in {} commits",
+                args.shell_command,
+                resolved_commits.len()
+            ),
+        )?;
+    }
+
+    if num_failed > 0 {
+        return Err(user_error(format!(
+            "The command failed in {num_failed} of {} revisions",
+            resolved_commits.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Checks out `commit` into a temporary directory under `run_dir`, runs the
+/// shell command there and snapshots the result.
+pub(crate) fn run_in_revision(
+    store: &Arc<Store>,
+    run_dir: &Path,
+    commit: &Commit,
+    shell_command: &str,
+    snapshot_options: &SnapshotOptions,
+) -> Result<RunResult, CommandError> {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("jj-run-")
+        .tempdir_in(run_dir)?;
+    let working_copy_path = temp_dir.path().join("working_copy");
+    let state_path = temp_dir.path().join("state");
+    std::fs::create_dir(&working_copy_path)?;
+    std::fs::create_dir(&state_path)?;
+    let mut tree_state = TreeState::init(store.clone(), working_copy_path.clone(), state_path)
+        .map_err(|err| {
+            internal_error_with_message("Failed to create temporary working copy", err)
+        })?;
+    // Conflicts are written the same way as in the working copy so that they
+    // are parsed back when snapshotting
+    let checkout_options = CheckoutOptions {
+        conflict_marker_style: snapshot_options.conflict_marker_style,
+    };
+    tree_state
+        .check_out(&commit.tree()?, &checkout_options)
+        .map_err(|err| internal_error_with_message("Failed to check out revision", err))?;
+
+    let output = shell(shell_command)
+        .current_dir(&working_copy_path)
+        .env(
+            "JJ_CHANGE_ID",
+            to_reverse_hex(&commit.change_id().hex()).unwrap(),
+        )
+        .env("JJ_COMMIT_ID", commit.id().hex())
+        .output()
+        .map_err(|err| user_error_with_message(format!("Failed to run '{shell_command}'"), err))?;
+
+    tree_state.snapshot(snapshot_options)?;
+    Ok(RunResult {
+        tree_id: tree_state.current_tree_id().clone(),
+        output,
+    })
+}
+
+/// Builds a command that runs `shell_command` through the platform's shell.
+fn shell(shell_command: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(shell_command);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(shell_command);
+        command
+    }
 }
@@ -80,9 +80,7 @@ pub(crate) fn cmd_split(
     }
 
     workspace_command.check_rewritable([commit.id()])?;
-    let matcher = workspace_command
-        .parse_file_patterns(&args.paths)?
-        .to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
     let diff_selector = workspace_command.diff_selector(
         ui,
         args.tool.as_deref(),
@@ -91,6 +89,7 @@ pub(crate) fn cmd_split(
     let mut tx = workspace_command.start_transaction();
     let end_tree = commit.tree()?;
     let base_tree = commit.parent_tree(tx.repo())?;
+    let matcher = fileset_expression.to_diff_matcher(&base_tree, &end_tree)?;
     let format_instructions = || {
         format!(
             "\
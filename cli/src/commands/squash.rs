@@ -15,10 +15,11 @@
 use itertools::Itertools as _;
 use jj_lib::commit::Commit;
 use jj_lib::commit::CommitIteratorExt;
-use jj_lib::matchers::Matcher;
+use jj_lib::fileset::FilesetExpression;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
+use jj_lib::rewrite::merge_trees_across_renames;
 use jj_lib::settings::UserSettings;
 use tracing::instrument;
 
@@ -127,9 +128,7 @@ pub(crate) fn cmd_squash(
         destination = parents.pop().unwrap();
     }
 
-    let matcher = workspace_command
-        .parse_file_patterns(&args.paths)?
-        .to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
     let diff_selector =
         workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
     let mut tx = workspace_command.start_transaction();
@@ -140,7 +139,7 @@ pub(crate) fn cmd_squash(
         command.settings(),
         &sources,
         &destination,
-        matcher.as_ref(),
+        &fileset_expression,
         &diff_selector,
         SquashedDescription::from_args(args),
         args.revision.is_none() && args.from.is_empty() && args.into.is_none(),
@@ -186,7 +185,7 @@ pub fn move_diff(
     settings: &UserSettings,
     sources: &[Commit],
     destination: &Commit,
-    matcher: &dyn Matcher,
+    fileset_expression: &FilesetExpression,
     diff_selector: &DiffSelector,
     description: SquashedDescription,
     no_rev_arg: bool,
@@ -206,6 +205,7 @@ pub fn move_diff(
     for source in sources {
         let parent_tree = source.parent_tree(tx.repo())?;
         let source_tree = source.tree()?;
+        let matcher = fileset_expression.to_diff_matcher(&parent_tree, &source_tree)?;
         let format_instructions = || {
             format!(
                 "\
@@ -224,8 +224,12 @@ from the source will be moved into the destination.
                 tx.format_commit_summary(destination)
             )
         };
-        let selected_tree_id =
-            diff_selector.select(&parent_tree, &source_tree, matcher, format_instructions)?;
+        let selected_tree_id = diff_selector.select(
+            &parent_tree,
+            &source_tree,
+            matcher.as_ref(),
+            format_instructions,
+        )?;
         let selected_tree = tx.repo().store().get_root_tree(&selected_tree_id)?;
         let abandon = !keep_emptied && selected_tree.id() == source_tree.id();
         if !abandon && selected_tree_id == parent_tree.id() {
@@ -297,7 +301,15 @@ from the source will be moved into the destination.
     // Apply the selected changes onto the destination
     let mut destination_tree = rewritten_destination.tree()?;
     for source in &source_commits {
-        destination_tree = destination_tree.merge(&source.parent_tree, &source.selected_tree)?;
+        let source_parents: Vec<_> = source.commit.parents().try_collect()?;
+        destination_tree = merge_trees_across_renames(
+            tx.repo(),
+            &destination_tree,
+            &source.parent_tree,
+            &source.selected_tree,
+            &source_parents,
+            &[source.commit, &rewritten_destination],
+        )?;
     }
     let description = match description {
         SquashedDescription::Exact(description) => description,
@@ -54,9 +54,7 @@ pub(crate) fn cmd_status(
         .get_wc_commit_id()
         .map(|id| repo.store().get_commit(id))
         .transpose()?;
-    let matcher = workspace_command
-        .parse_file_patterns(&args.paths)?
-        .to_matcher();
+    let fileset_expression = workspace_command.parse_file_patterns(&args.paths)?;
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
@@ -68,9 +66,10 @@ pub(crate) fn cmd_status(
             writeln!(formatter, "The working copy is clean")?;
         } else {
             writeln!(formatter, "Working copy changes:")?;
+            let matcher = fileset_expression.to_diff_matcher(&parent_tree, &tree)?;
             let mut copy_records = CopyRecords::default();
             for parent in wc_commit.parent_ids() {
-                let records = get_copy_records(repo.store(), parent, wc_commit.id(), &matcher)?;
+                let records = get_copy_records(repo.as_ref(), parent, wc_commit.id(), &matcher)?;
                 copy_records.add_records(records)?;
             }
             let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Summary]);
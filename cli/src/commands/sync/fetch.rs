@@ -0,0 +1,70 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools as _;
+use jj_lib::native_sync;
+
+use super::load_remote;
+use super::DEFAULT_REMOTE;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Fetch commits and bookmarks from a jj remote
+///
+/// The bookmarks of the remote repo are recorded as remote bookmarks. Tracked
+/// local bookmarks are updated accordingly.
+#[derive(clap::Args, Clone, Debug)]
+pub struct SyncFetchArgs {
+    /// The remote to fetch from
+    #[arg(long, default_value = DEFAULT_REMOTE)]
+    remote: String,
+}
+
+#[tracing::instrument(skip(ui, command))]
+pub fn cmd_sync_fetch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SyncFetchArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let transport = load_remote(command, &workspace_command, &args.remote)?;
+    let mut tx = workspace_command.start_transaction();
+    let stats = native_sync::fetch(tx.repo_mut(), &transport, &args.remote)?;
+    if stats.copied_commit_count == 0 && stats.changed_bookmarks.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    if stats.copied_commit_count > 0 {
+        writeln!(
+            ui.status(),
+            "Fetched {} commits from {}",
+            stats.copied_commit_count,
+            args.remote
+        )?;
+    }
+    if !stats.changed_bookmarks.is_empty() {
+        writeln!(
+            ui.status(),
+            "Updated remote bookmarks: {}",
+            stats
+                .changed_bookmarks
+                .iter()
+                .map(|name| format!("{name}@{}", args.remote))
+                .join(", ")
+        )?;
+    }
+    tx.finish(ui, format!("fetch from sync remote {}", args.remote))?;
+    Ok(())
+}
@@ -0,0 +1,90 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod fetch;
+pub mod push;
+
+use clap::Subcommand;
+use jj_lib::file_util;
+use jj_lib::native_sync::LocalRepoTransport;
+use jj_lib::settings::ConfigResultExt as _;
+use jj_lib::workspace::DefaultWorkspaceLoaderFactory;
+use jj_lib::workspace::WorkspaceLoaderFactory as _;
+
+use self::fetch::cmd_sync_fetch;
+use self::fetch::SyncFetchArgs;
+use self::push::cmd_sync_push;
+use self::push::SyncPushArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Exchange commits and bookmarks with other jj repos
+///
+/// Unlike the `jj git` commands, these commands copy commits as is, so change
+/// ids, predecessors and conflicts are preserved. Both repos must use the same
+/// commit backend.
+///
+/// Remotes are configured by the path to their workspace, for example with
+/// `jj config set --repo sync.remotes.origin.path ../other-repo`. A relative
+/// path is resolved from the workspace root.
+#[derive(Subcommand, Clone, Debug)]
+pub enum SyncCommand {
+    Fetch(SyncFetchArgs),
+    Push(SyncPushArgs),
+}
+
+pub fn cmd_sync(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &SyncCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        SyncCommand::Fetch(args) => cmd_sync_fetch(ui, command, args),
+        SyncCommand::Push(args) => cmd_sync_push(ui, command, args),
+    }
+}
+
+const DEFAULT_REMOTE: &str = "origin";
+
+/// Loads the repo of the remote configured as `sync.remotes.<remote_name>`.
+fn load_remote(
+    command: &CommandHelper,
+    workspace_command: &WorkspaceCommandHelper,
+    remote_name: &str,
+) -> Result<LocalRepoTransport, CommandError> {
+    let key = format!("sync.remotes.{remote_name}.path");
+    let Some(path) = command.settings().config().get_string(&key).optional()? else {
+        return Err(user_error_with_hint(
+            format!("No sync remote named '{remote_name}'"),
+            format!("Configure its path with `jj config set --repo {key} <path>`."),
+        ));
+    };
+    let workspace_root = workspace_command.workspace_root();
+    let path = file_util::expand_home_path(&path);
+    let loader = DefaultWorkspaceLoaderFactory
+        .create(&workspace_root.join(path))
+        .map_err(|err| {
+            user_error_with_message(format!("Failed to load sync remote '{remote_name}'"), err)
+        })?;
+    let transport = LocalRepoTransport::load(
+        command.settings(),
+        loader.repo_path(),
+        command.store_factories(),
+    )?;
+    Ok(transport)
+}
@@ -0,0 +1,181 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools as _;
+use jj_lib::native_sync;
+use jj_lib::refs::classify_bookmark_push_action;
+use jj_lib::refs::BranchPushAction;
+use jj_lib::refs::BranchPushUpdate;
+use jj_lib::refs::LocalAndRemoteRef;
+use jj_lib::str_util::StringPattern;
+use jj_lib::view::View;
+
+use super::load_remote;
+use super::DEFAULT_REMOTE;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Push bookmarks to a jj remote
+///
+/// Missing commits are copied to the remote repo, and the bookmarks are
+/// updated there in a new operation. The push is rejected if a bookmark was
+/// moved on the remote since it was last fetched.
+#[derive(clap::Args, Clone, Debug)]
+pub struct SyncPushArgs {
+    /// The remote to push to
+    #[arg(long, default_value = DEFAULT_REMOTE)]
+    remote: String,
+    /// Push only this bookmark, or bookmarks matching a pattern (can be
+    /// repeated)
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select bookmarks by wildcard pattern.
+    #[arg(
+        long,
+        short,
+        value_parser = StringPattern::parse,
+        required_unless_present = "all",
+    )]
+    bookmark: Vec<StringPattern>,
+    /// Push all local bookmarks
+    #[arg(long, conflicts_with = "bookmark")]
+    all: bool,
+}
+
+#[tracing::instrument(skip(ui, command))]
+pub fn cmd_sync_push(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SyncPushArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let remote = &args.remote;
+    let repo = workspace_command.repo().clone();
+    let bookmarks = if args.all {
+        repo.view()
+            .local_remote_bookmarks(remote)
+            .filter(|(_, targets)| targets.local_target.is_present())
+            .collect_vec()
+    } else {
+        find_bookmarks_to_push(repo.view(), &args.bookmark, remote)?
+    };
+
+    let mut updates = vec![];
+    for (name, targets) in bookmarks {
+        match classify_bookmark_push_action(targets) {
+            BranchPushAction::Update(update) => updates.push((name.to_owned(), update)),
+            BranchPushAction::AlreadyMatches => {
+                if !args.all {
+                    writeln!(
+                        ui.status(),
+                        "Bookmark {name}@{remote} already matches {name}"
+                    )?;
+                }
+            }
+            BranchPushAction::LocalConflicted => {
+                return Err(user_error_with_hint(
+                    format!("Bookmark {name} is conflicted"),
+                    "Run `jj bookmark list` to inspect, and use `jj bookmark set` to fix it up.",
+                ));
+            }
+            BranchPushAction::RemoteConflicted => {
+                return Err(user_error_with_hint(
+                    format!("Bookmark {name}@{remote} is conflicted"),
+                    "Run `jj sync fetch` to update the conflicted remote bookmark.",
+                ));
+            }
+            BranchPushAction::RemoteUntracked => {
+                return Err(user_error_with_hint(
+                    format!("Non-tracking remote bookmark {name}@{remote} exists"),
+                    format!(
+                        "Run `jj bookmark track {name}@{remote}` to import the remote bookmark."
+                    ),
+                ));
+            }
+        }
+    }
+    if updates.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let transport = load_remote(command, &workspace_command, remote)?;
+    writeln!(ui.status(), "Bookmark changes to push to {remote}:")?;
+    for (name, update) in &updates {
+        print_update(ui, name, update)?;
+    }
+    let mut tx = workspace_command.start_transaction();
+    native_sync::push_bookmarks(tx.repo_mut(), &transport, remote, &updates)?;
+    tx.finish(
+        ui,
+        format!(
+            "push bookmarks {} to sync remote {remote}",
+            updates.iter().map(|(name, _)| name).join(", ")
+        ),
+    )?;
+    Ok(())
+}
+
+fn find_bookmarks_to_push<'a>(
+    view: &'a View,
+    bookmark_patterns: &[StringPattern],
+    remote_name: &str,
+) -> Result<Vec<(&'a str, LocalAndRemoteRef<'a>)>, CommandError> {
+    let mut matching_bookmarks = vec![];
+    for pattern in bookmark_patterns {
+        let mut matches = view
+            .local_remote_bookmarks_matching(pattern, remote_name)
+            .filter(|(_, targets)| {
+                // An absent local bookmark is only a deleted bookmark if the
+                // remote bookmark is tracked.
+                targets.local_target.is_present() || targets.remote_ref.is_tracking()
+            })
+            .peekable();
+        if matches.peek().is_none() {
+            return Err(user_error(format!("No such bookmark: {pattern}")));
+        }
+        matching_bookmarks.extend(matches);
+    }
+    Ok(matching_bookmarks
+        .into_iter()
+        .unique_by(|(name, _)| *name)
+        .collect())
+}
+
+fn print_update(ui: &Ui, name: &str, update: &BranchPushUpdate) -> Result<(), CommandError> {
+    match (&update.old_target, &update.new_target) {
+        (Some(old_target), Some(new_target)) => writeln!(
+            ui.status(),
+            "  Move bookmark {name} from {} to {}",
+            short_commit_hash(old_target),
+            short_commit_hash(new_target)
+        )?,
+        (Some(old_target), None) => writeln!(
+            ui.status(),
+            "  Delete bookmark {name} from {}",
+            short_commit_hash(old_target)
+        )?,
+        (None, Some(new_target)) => writeln!(
+            ui.status(),
+            "  Add bookmark {name} to {}",
+            short_commit_hash(new_target)
+        )?,
+        (None, None) => panic!("Not pushing any change to bookmark {name}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::object_id::ObjectId as _;
+
+use super::check_tag_message_args;
+use super::parse_tag_name;
+use super::set_tag_targets;
+use super::TagMessageArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Create a new tag
+///
+/// The tags are exported to the backing Git repo along with bookmarks, and can
+/// be pushed with `jj git push --tag`.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagCreateArgs {
+    /// The tag's target revision
+    #[arg(long, short, visible_alias = "to")]
+    revision: Option<RevisionArg>,
+
+    #[command(flatten)]
+    message_args: TagMessageArgs,
+
+    /// The tags to create
+    #[arg(required = true, value_parser = parse_tag_name)]
+    names: Vec<String>,
+}
+
+pub fn cmd_tag_create(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagCreateArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit =
+        workspace_command.resolve_single_rev(args.revision.as_ref().unwrap_or(&RevisionArg::AT))?;
+    let view = workspace_command.repo().view();
+    let tag_names = &args.names;
+    for name in tag_names {
+        if view.get_tag(name).is_present() {
+            return Err(user_error_with_hint(
+                format!("Tag already exists: {name}"),
+                "Use `jj tag set --allow-move` to update it.",
+            ));
+        }
+    }
+    check_tag_message_args(&target_commit, &args.message_args)?;
+
+    let mut tx = workspace_command.start_transaction();
+    set_tag_targets(
+        &mut tx,
+        command,
+        tag_names,
+        &target_commit,
+        &args.message_args,
+    )?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Created {} tags pointing to ", tag_names.len())?;
+        tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+        writeln!(formatter)?;
+    }
+    if tag_names.len() > 1 && args.revision.is_none() {
+        writeln!(ui.hint_default(), "Use -r to specify the target revision.")?;
+    }
+
+    tx.finish(
+        ui,
+        format!(
+            "create tag {names} pointing to commit {id}",
+            names = tag_names.join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
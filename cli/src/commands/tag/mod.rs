@@ -0,0 +1,175 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod create;
+mod delete;
+mod list;
+mod set;
+
+use itertools::Itertools as _;
+use jj_lib::backend::SigningFn;
+use jj_lib::commit::Commit;
+use jj_lib::git;
+use jj_lib::op_store::RefTarget;
+use jj_lib::repo::Repo as _;
+use jj_lib::str_util::StringPattern;
+use jj_lib::view::View;
+
+use self::create::cmd_tag_create;
+use self::create::TagCreateArgs;
+use self::delete::cmd_tag_delete;
+use self::delete::TagDeleteArgs;
+use self::list::cmd_tag_list;
+use self::list::TagListArgs;
+use self::set::cmd_tag_set;
+use self::set::TagSetArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandTransaction;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage tags.
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum TagCommand {
+    #[command(visible_alias("c"))]
+    Create(TagCreateArgs),
+    #[command(visible_alias("d"))]
+    Delete(TagDeleteArgs),
+    #[command(visible_alias("l"))]
+    List(TagListArgs),
+    #[command(visible_alias("s"))]
+    Set(TagSetArgs),
+}
+
+pub fn cmd_tag(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &TagCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        TagCommand::Create(args) => cmd_tag_create(ui, command, args),
+        TagCommand::Delete(args) => cmd_tag_delete(ui, command, args),
+        TagCommand::List(args) => cmd_tag_list(ui, command, args),
+        TagCommand::Set(args) => cmd_tag_set(ui, command, args),
+    }
+}
+
+/// Options shared by the commands creating tags.
+#[derive(clap::Args, Clone, Debug)]
+struct TagMessageArgs {
+    /// Create annotated tags with the given message
+    ///
+    /// Annotated tags are only supported by the Git backend. Without a
+    /// message, lightweight tags are created.
+    #[arg(long, short)]
+    message: Option<String>,
+
+    /// Sign the annotated tags with the configured signing backend
+    #[arg(long, requires = "message")]
+    sign: bool,
+}
+
+/// Parses a tag name, rejecting names which can't be exported to Git.
+fn parse_tag_name(name: &str) -> Result<String, String> {
+    if git2::Reference::is_valid_name(&format!("refs/tags/{name}")) {
+        Ok(name.to_owned())
+    } else {
+        Err(format!("Invalid tag name: {name:?}"))
+    }
+}
+
+fn check_tag_message_args(
+    target_commit: &Commit,
+    args: &TagMessageArgs,
+) -> Result<(), CommandError> {
+    if args.message.is_none() {
+        return Ok(());
+    }
+    let store = target_commit.store();
+    if target_commit.id() == store.root_commit_id() {
+        return Err(user_error(
+            "Cannot create annotated tags on the root commit",
+        ));
+    }
+    if args.sign && !store.signer().can_sign() {
+        return Err(user_error_with_hint(
+            "No signing backend is configured",
+            "Set `signing.backend` to sign the tags.",
+        ));
+    }
+    Ok(())
+}
+
+/// Points the tags to the target commit. If a message is specified, annotated
+/// tags are written to the backing Git repo right away.
+fn set_tag_targets(
+    tx: &mut WorkspaceCommandTransaction,
+    command: &CommandHelper,
+    tag_names: &[String],
+    target_commit: &Commit,
+    args: &TagMessageArgs,
+) -> Result<(), CommandError> {
+    let Some(message) = &args.message else {
+        for name in tag_names {
+            tx.repo_mut()
+                .set_tag_target(name, RefTarget::normal(target_commit.id().clone()));
+        }
+        return Ok(());
+    };
+    let settings = command.settings();
+    let tagger = settings.signature();
+    let sign_key = settings.sign_settings().key;
+    let store = tx.repo().store().clone();
+    for name in tag_names {
+        let mut sign_fn = |data: &[u8]| store.signer().sign(data, sign_key.as_deref());
+        git::set_annotated_tag(
+            tx.repo_mut(),
+            name,
+            target_commit.id(),
+            &tagger,
+            message,
+            args.sign.then_some(&mut sign_fn as &mut SigningFn),
+        )?;
+    }
+    Ok(())
+}
+
+pub(crate) fn find_tags<'a>(
+    view: &'a View,
+    name_patterns: &[StringPattern],
+) -> Result<Vec<(&'a str, &'a RefTarget)>, CommandError> {
+    let mut matching_tags = vec![];
+    let mut unmatched_patterns = vec![];
+    for pattern in name_patterns {
+        let mut matches = view.tags_matching(pattern).peekable();
+        if matches.peek().is_none() {
+            unmatched_patterns.push(pattern);
+        }
+        matching_tags.extend(matches);
+    }
+    match &unmatched_patterns[..] {
+        [] => {
+            matching_tags.sort_unstable_by_key(|(name, _)| *name);
+            matching_tags.dedup_by_key(|(name, _)| *name);
+            Ok(matching_tags)
+        }
+        [pattern] if pattern.is_exact() => Err(user_error(format!("No such tag: {pattern}"))),
+        patterns => Err(user_error(format!(
+            "No matching tags for patterns: {}",
+            patterns.iter().join(", ")
+        ))),
+    }
+}
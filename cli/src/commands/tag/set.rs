@@ -0,0 +1,111 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::object_id::ObjectId as _;
+
+use super::check_tag_message_args;
+use super::parse_tag_name;
+use super::set_tag_targets;
+use super::TagMessageArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Create or update a tag to point to a certain commit
+///
+/// Since tags are usually expected to stay where they are once published,
+/// existing tags are only moved if `--allow-move` is specified.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagSetArgs {
+    /// The tag's target revision
+    #[arg(long, short, visible_alias = "to")]
+    revision: Option<RevisionArg>,
+
+    /// Allow moving existing tags
+    #[arg(long)]
+    allow_move: bool,
+
+    #[command(flatten)]
+    message_args: TagMessageArgs,
+
+    /// The tags to update
+    #[arg(required = true, value_parser = parse_tag_name)]
+    names: Vec<String>,
+}
+
+pub fn cmd_tag_set(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagSetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit =
+        workspace_command.resolve_single_rev(args.revision.as_ref().unwrap_or(&RevisionArg::AT))?;
+    let view = workspace_command.repo().view();
+    let tag_names = &args.names;
+    let mut new_tag_count = 0;
+    let mut moved_tag_count = 0;
+    for name in tag_names {
+        let old_target = view.get_tag(name);
+        if old_target.is_absent() {
+            new_tag_count += 1;
+        } else if old_target.as_normal() != Some(target_commit.id()) {
+            if !args.allow_move {
+                return Err(user_error_with_hint(
+                    format!("Refusing to move tag: {name}"),
+                    "Use --allow-move to allow it.",
+                ));
+            }
+            moved_tag_count += 1;
+        }
+    }
+    check_tag_message_args(&target_commit, &args.message_args)?;
+
+    let mut tx = workspace_command.start_transaction();
+    set_tag_targets(
+        &mut tx,
+        command,
+        tag_names,
+        &target_commit,
+        &args.message_args,
+    )?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        if new_tag_count > 0 {
+            write!(formatter, "Created {new_tag_count} tags pointing to ")?;
+            tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+            writeln!(formatter)?;
+        }
+        if moved_tag_count > 0 {
+            write!(formatter, "Moved {moved_tag_count} tags to ")?;
+            tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    if tag_names.len() > 1 && args.revision.is_none() {
+        writeln!(ui.hint_default(), "Use -r to specify the target revision.")?;
+    }
+
+    tx.finish(
+        ui,
+        format!(
+            "point tag {names} to commit {id}",
+            names = tag_names.join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
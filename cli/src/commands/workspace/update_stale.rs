@@ -66,6 +66,7 @@ pub fn cmd_workspace_update_stale(
     let mut workspace_command = command.workspace_helper_no_snapshot(ui)?;
 
     let repo = workspace_command.repo().clone();
+    let checkout_options = workspace_command.checkout_options();
     let (mut locked_ws, desired_wc_commit) =
         workspace_command.unchecked_start_working_copy_mutation()?;
     match check_stale_working_copy(locked_ws.locked_wc(), &desired_wc_commit, &repo)? {
@@ -83,7 +84,7 @@ pub fn cmd_workspace_update_stale(
             }
             let stats = locked_ws
                 .locked_wc()
-                .check_out(&desired_wc_commit)
+                .check_out(&desired_wc_commit, &checkout_options)
                 .map_err(|err| {
                     internal_error_with_message(
                         format!(
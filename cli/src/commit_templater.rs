@@ -24,6 +24,7 @@ use jj_lib::backend::BackendResult;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
+use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopyRecords;
 use jj_lib::extensions_map::ExtensionsMap;
@@ -45,10 +46,15 @@ use jj_lib::revset::Revset;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetModifier;
 use jj_lib::revset::RevsetParseContext;
+use jj_lib::signing::SigStatus;
+use jj_lib::signing::SignError;
+use jj_lib::signing::SignResult;
+use jj_lib::signing::Verification;
 use jj_lib::store::Store;
 use once_cell::unsync::OnceCell;
 
 use crate::diff_util;
+use crate::diff_util::LineDiffOptions;
 use crate::formatter::Formatter;
 use crate::revset_util;
 use crate::template_builder;
@@ -72,6 +78,7 @@ use crate::templater::TemplateFormatter;
 use crate::templater::TemplateProperty;
 use crate::templater::TemplatePropertyError;
 use crate::templater::TemplatePropertyExt as _;
+use crate::templater::ToJson;
 use crate::text_util;
 
 pub trait CommitTemplateLanguageExtension {
@@ -91,6 +98,7 @@ pub struct CommitTemplateLanguage<'repo> {
     // are contained in RevsetParseContext for example.
     revset_parse_context: RevsetParseContext<'repo>,
     id_prefix_context: &'repo IdPrefixContext,
+    conflict_marker_style: ConflictMarkerStyle,
     build_fn_table: CommitTemplateBuildFnTable<'repo>,
     keyword_cache: CommitKeywordCache<'repo>,
     cache_extensions: ExtensionsMap,
@@ -105,6 +113,7 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         workspace_id: &WorkspaceId,
         revset_parse_context: RevsetParseContext<'repo>,
         id_prefix_context: &'repo IdPrefixContext,
+        conflict_marker_style: ConflictMarkerStyle,
         extensions: &[impl AsRef<dyn CommitTemplateLanguageExtension>],
     ) -> Self {
         let mut build_fn_table = CommitTemplateBuildFnTable::builtin();
@@ -123,6 +132,7 @@ impl<'repo> CommitTemplateLanguage<'repo> {
             workspace_id: workspace_id.clone(),
             revset_parse_context,
             id_prefix_context,
+            conflict_marker_style,
             build_fn_table,
             keyword_cache: CommitKeywordCache::default(),
             cache_extensions,
@@ -215,6 +225,13 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
+                let type_name = "CryptographicSignature";
+                let table = &self.build_fn_table.cryptographic_signature_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                let inner_property = property.try_unwrap(type_name);
+                build(self, build_ctx, Box::new(inner_property), function)
+            }
         }
     }
 }
@@ -291,6 +308,12 @@ impl<'repo> CommitTemplateLanguage<'repo> {
     ) -> CommitTemplatePropertyKind<'repo> {
         CommitTemplatePropertyKind::TreeDiff(Box::new(property))
     }
+
+    pub fn wrap_cryptographic_signature_opt(
+        property: impl TemplateProperty<Output = Option<CryptographicSignature>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::CryptographicSignatureOpt(Box::new(property))
+    }
 }
 
 pub enum CommitTemplatePropertyKind<'repo> {
@@ -304,6 +327,9 @@ pub enum CommitTemplatePropertyKind<'repo> {
     CommitOrChangeId(Box<dyn TemplateProperty<Output = CommitOrChangeId> + 'repo>),
     ShortestIdPrefix(Box<dyn TemplateProperty<Output = ShortestIdPrefix> + 'repo>),
     TreeDiff(Box<dyn TemplateProperty<Output = TreeDiff> + 'repo>),
+    CryptographicSignatureOpt(
+        Box<dyn TemplateProperty<Output = Option<CryptographicSignature>> + 'repo>,
+    ),
 }
 
 impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
@@ -319,6 +345,9 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::CommitOrChangeId(_) => "CommitOrChangeId",
             CommitTemplatePropertyKind::ShortestIdPrefix(_) => "ShortestIdPrefix",
             CommitTemplatePropertyKind::TreeDiff(_) => "TreeDiff",
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => {
+                "Option<CryptographicSignature>"
+            }
         }
     }
 
@@ -344,6 +373,9 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             // TODO: boolean cast could be implemented, but explicit
             // diff.empty() method might be better.
             CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
+                Some(Box::new(property.map(|sig| sig.is_some())))
+            }
         }
     }
 
@@ -380,6 +412,48 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
                 Some(property.into_template())
             }
             CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
+        }
+    }
+
+    fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'repo>> {
+        fn to_json<T: ToJson>(value: T) -> Result<serde_json::Value, TemplatePropertyError> {
+            value.to_json()
+        }
+        match self {
+            CommitTemplatePropertyKind::Core(property) => property.try_into_json(),
+            CommitTemplatePropertyKind::Commit(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::CommitOpt(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::CommitList(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::RefName(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::RefNameOpt(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::RefNameList(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::CommitOrChangeId(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::ShortestIdPrefix(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::TreeDiff(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
         }
     }
 }
@@ -396,6 +470,8 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub commit_or_change_id_methods: CommitTemplateBuildMethodFnMap<'repo, CommitOrChangeId>,
     pub shortest_id_prefix_methods: CommitTemplateBuildMethodFnMap<'repo, ShortestIdPrefix>,
     pub tree_diff_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiff>,
+    pub cryptographic_signature_methods:
+        CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature>,
 }
 
 impl<'repo> CommitTemplateBuildFnTable<'repo> {
@@ -408,6 +484,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             commit_or_change_id_methods: builtin_commit_or_change_id_methods(),
             shortest_id_prefix_methods: builtin_shortest_id_prefix_methods(),
             tree_diff_methods: builtin_tree_diff_methods(),
+            cryptographic_signature_methods: builtin_cryptographic_signature_methods(),
         }
     }
 
@@ -419,6 +496,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             commit_or_change_id_methods: HashMap::new(),
             shortest_id_prefix_methods: HashMap::new(),
             tree_diff_methods: HashMap::new(),
+            cryptographic_signature_methods: HashMap::new(),
         }
     }
 
@@ -430,6 +508,7 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             commit_or_change_id_methods,
             shortest_id_prefix_methods,
             tree_diff_methods,
+            cryptographic_signature_methods,
         } = extension;
 
         self.core.merge(core);
@@ -444,6 +523,10 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             shortest_id_prefix_methods,
         );
         merge_fn_map(&mut self.tree_diff_methods, tree_diff_methods);
+        merge_fn_map(
+            &mut self.cryptographic_signature_methods,
+            cryptographic_signature_methods,
+        );
     }
 }
 
@@ -484,6 +567,20 @@ impl<'repo> CommitKeywordCache<'repo> {
     }
 }
 
+impl ToJson for Commit {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let parents = self.parent_ids().iter().map(|id| id.hex()).collect_vec();
+        Ok(serde_json::json!({
+            "commit_id": self.id().hex(),
+            "change_id": to_reverse_hex(&self.change_id().hex()).unwrap(),
+            "parents": parents,
+            "description": self.description(),
+            "author": self.author().to_json()?,
+            "committer": self.committer().to_json()?,
+        }))
+    }
+}
+
 fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Commit> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
     // Not using maplit::hashmap!{} or custom declarative macro here because
@@ -717,7 +814,7 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         Ok(L::wrap_boolean(out_property))
     });
     map.insert("diff", |language, _build_ctx, self_property, function| {
-        let ([], [files_node]) = function.expect_arguments()?;
+        let ([], [files_node, options_node]) = function.expect_arguments()?;
         let files = if let Some(node) = files_node {
             expect_fileset_literal(node, language.path_converter)?
         } else {
@@ -725,12 +822,32 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             // https://github.com/martinvonz/jj/issues/2933#issuecomment-1925870731
             FilesetExpression::all()
         };
+        let line_diff = if let Some(node) = options_node {
+            expect_line_diff_options_literal(node)?
+        } else {
+            LineDiffOptions::default()
+        };
         let repo = language.repo;
-        let matcher: Rc<dyn Matcher> = files.to_matcher().into();
-        let out_property = self_property
-            .and_then(move |commit| Ok(TreeDiff::from_commit(repo, &commit, matcher.clone())?));
+        let conflict_marker_style = language.conflict_marker_style;
+        let out_property = self_property.and_then(move |commit| {
+            Ok(TreeDiff::from_commit(
+                repo,
+                &commit,
+                &files,
+                line_diff,
+                conflict_marker_style,
+            )?)
+        });
         Ok(L::wrap_tree_diff(out_property))
     });
+    map.insert(
+        "signature",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(CryptographicSignature::new);
+            Ok(L::wrap_cryptographic_signature_opt(out_property))
+        },
+    );
     map.insert("root", |language, _build_ctx, self_property, function| {
         function.expect_no_arguments()?;
         let repo = language.repo;
@@ -766,6 +883,15 @@ fn expect_fileset_literal(
     })
 }
 
+fn expect_line_diff_options_literal(
+    node: &ExpressionNode,
+) -> Result<LineDiffOptions, TemplateParseError> {
+    template_parser::expect_string_literal_with(node, |text, span| {
+        LineDiffOptions::parse(text)
+            .map_err(|message| TemplateParseError::expression(message, span))
+    })
+}
+
 type RevsetContainingFn<'repo> = dyn Fn(&CommitId) -> bool + 'repo;
 
 fn evaluate_revset_expression<'repo>(
@@ -975,6 +1101,21 @@ impl RefName {
     }
 }
 
+impl ToJson for Rc<RefName> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let targets = self.target.added_ids().map(|id| id.hex()).collect_vec();
+        Ok(serde_json::json!({
+            "name": self.name,
+            "remote": self.remote,
+            "targets": targets,
+            "present": self.is_present(),
+            "conflict": self.has_conflict(),
+            "tracked": self.is_tracked(),
+            "synced": self.synced,
+        }))
+    }
+}
+
 // If wrapping with Rc<T> becomes common, add generic impl for Rc<T>.
 impl Template for Rc<RefName> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
@@ -1213,6 +1354,12 @@ impl CommitOrChangeId {
     }
 }
 
+impl ToJson for CommitOrChangeId {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.hex().into())
+    }
+}
+
 impl Template for CommitOrChangeId {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         write!(formatter, "{}", self.hex())
@@ -1255,6 +1402,12 @@ pub struct ShortestIdPrefix {
     pub rest: String,
 }
 
+impl ToJson for ShortestIdPrefix {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({ "prefix": self.prefix, "rest": self.rest }))
+    }
+}
+
 impl Template for ShortestIdPrefix {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         write!(formatter.labeled("prefix"), "{}", self.prefix)?;
@@ -1317,25 +1470,33 @@ pub struct TreeDiff {
     to_tree: MergedTree,
     matcher: Rc<dyn Matcher>,
     copy_records: CopyRecords,
+    line_diff: LineDiffOptions,
+    conflict_marker_style: ConflictMarkerStyle,
 }
 
 impl TreeDiff {
     fn from_commit(
         repo: &dyn Repo,
         commit: &Commit,
-        matcher: Rc<dyn Matcher>,
+        files: &FilesetExpression,
+        line_diff: LineDiffOptions,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> BackendResult<Self> {
+        let from_tree = commit.parent_tree(repo)?;
+        let to_tree = commit.tree()?;
+        let matcher: Rc<dyn Matcher> = files.to_diff_matcher(&from_tree, &to_tree)?.into();
         let mut copy_records = CopyRecords::default();
         for parent in commit.parent_ids() {
-            let records =
-                diff_util::get_copy_records(repo.store(), parent, commit.id(), &*matcher)?;
+            let records = diff_util::get_copy_records(repo, parent, commit.id(), &*matcher)?;
             copy_records.add_records(records)?;
         }
         Ok(TreeDiff {
-            from_tree: commit.parent_tree(repo)?,
-            to_tree: commit.tree()?,
+            from_tree,
+            to_tree,
             matcher,
             copy_records,
+            line_diff,
+            conflict_marker_style,
         })
     }
 
@@ -1353,6 +1514,20 @@ impl TreeDiff {
     }
 }
 
+/// Serialized as the per-file statistics shown by `diff.stat()`.
+impl ToJson for TreeDiff {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let store = self.from_tree.store();
+        let stats = diff_util::get_diff_stats(
+            store,
+            self.diff_stream(),
+            &self.line_diff,
+            self.conflict_marker_style,
+        )?;
+        Ok(stats.iter().map(|stat| stat.to_json_value()).collect())
+    }
+}
+
 /// Tree diff to be rendered by predefined function `F`.
 struct TreeDiffFormatted<F> {
     diff: TreeDiff,
@@ -1390,8 +1565,10 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     // TODO: load defaults from UserSettings?
                     let options = diff_util::ColorWordsOptions {
                         context: context.unwrap_or(diff_util::DEFAULT_CONTEXT_LINES),
+                        line_diff: diff.line_diff,
                         max_inline_alternation: Some(3),
                     };
+                    let conflict_marker_style = diff.conflict_marker_style;
                     diff.into_formatted(move |formatter, store, tree_diff| {
                         diff_util::show_color_words_diff(
                             formatter,
@@ -1399,6 +1576,7 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                             tree_diff,
                             path_converter,
                             &options,
+                            conflict_marker_style,
                         )
                     })
                 })
@@ -1413,9 +1591,19 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
             .transpose()?;
         let template = (self_property, context_property)
             .map(|(diff, context)| {
-                let context = context.unwrap_or(diff_util::DEFAULT_CONTEXT_LINES);
+                let options = diff_util::UnifiedDiffOptions {
+                    context: context.unwrap_or(diff_util::DEFAULT_CONTEXT_LINES),
+                    line_diff: diff.line_diff,
+                };
+                let conflict_marker_style = diff.conflict_marker_style;
                 diff.into_formatted(move |formatter, store, tree_diff| {
-                    diff_util::show_git_diff(formatter, store, tree_diff, context)
+                    diff_util::show_git_diff(
+                        formatter,
+                        store,
+                        tree_diff,
+                        &options,
+                        conflict_marker_style,
+                    )
                 })
             })
             .into_template();
@@ -1428,8 +1616,18 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
         let path_converter = language.path_converter;
         let template = (self_property, width_property)
             .map(move |(diff, width)| {
+                let line_diff = diff.line_diff;
+                let conflict_marker_style = diff.conflict_marker_style;
                 diff.into_formatted(move |formatter, store, tree_diff| {
-                    diff_util::show_diff_stat(formatter, store, tree_diff, path_converter, width)
+                    diff_util::show_diff_stat(
+                        formatter,
+                        store,
+                        tree_diff,
+                        path_converter,
+                        &line_diff,
+                        width,
+                        conflict_marker_style,
+                    )
                 })
             })
             .into_template();
@@ -1455,3 +1653,87 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
     // TODO: add files() or map() to support custom summary-like formatting?
     map
 }
+
+/// Cryptographic signature of a commit, verified lazily.
+#[derive(Debug)]
+pub struct CryptographicSignature {
+    commit: Commit,
+}
+
+impl CryptographicSignature {
+    fn new(commit: Commit) -> Option<Self> {
+        commit.is_signed().then_some(Self { commit })
+    }
+
+    fn verify(&self) -> SignResult<Verification> {
+        self.commit
+            .verification()
+            .transpose()
+            .expect("must have signature")
+    }
+
+    fn status(&self) -> SignResult<String> {
+        match self.verify() {
+            Ok(verification) => Ok(match verification.status {
+                SigStatus::Good => "good",
+                SigStatus::Unknown => "unknown",
+                SigStatus::Bad => "bad",
+            }
+            .to_owned()),
+            Err(SignError::InvalidSignatureFormat) => Ok("invalid".to_owned()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Defaults to empty string if key is not present.
+    fn key(&self) -> SignResult<String> {
+        self.verify()
+            .map(|verification| verification.key.unwrap_or_default())
+    }
+
+    /// Defaults to empty string if display is not present.
+    fn display(&self) -> SignResult<String> {
+        self.verify()
+            .map(|verification| verification.display.unwrap_or_default())
+    }
+}
+
+impl ToJson for CryptographicSignature {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "status": self.status()?,
+            "key": self.key()?,
+            "display": self.display()?,
+        }))
+    }
+}
+
+fn builtin_cryptographic_signature_methods<'repo>(
+) -> CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<CryptographicSignature>::new();
+    map.insert(
+        "status",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.and_then(|sig| Ok(sig.status()?));
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert("key", |_language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let out_property = self_property.and_then(|sig| Ok(sig.key()?));
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "display",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.and_then(|sig| Ok(sig.display()?));
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map
+}
@@ -16,11 +16,14 @@ use std::borrow::Borrow;
 use std::cmp::max;
 use std::collections::HashSet;
 use std::io;
+use std::iter;
 use std::mem;
 use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 
+use bstr::BStr;
+use clap::builder::TypedValueParser as _;
 use futures::executor::block_on_stream;
 use futures::stream::BoxStream;
 use futures::StreamExt;
@@ -33,13 +36,18 @@ use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
 use jj_lib::conflicts::materialize_merge_result;
 use jj_lib::conflicts::materialized_diff_stream;
+use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MaterializedTreeDiffEntry;
 use jj_lib::conflicts::MaterializedTreeValue;
 use jj_lib::copies::CopiesTreeDiffEntry;
+use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
 use jj_lib::diff::Diff;
+use jj_lib::diff::DiffAlgorithm;
 use jj_lib::diff::DiffHunk;
+use jj_lib::diff::DiffOptions;
+use jj_lib::diff::WhitespaceMode;
 use jj_lib::files::DiffLine;
 use jj_lib::files::DiffLineHunkSide;
 use jj_lib::files::DiffLineIterator;
@@ -48,6 +56,7 @@ use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
+use jj_lib::patch::format_binary_patch;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathUiConverter;
@@ -58,6 +67,7 @@ use jj_lib::store::Store;
 use pollster::FutureExt;
 use thiserror::Error;
 use tracing::instrument;
+use unicode_width::UnicodeWidthChar as _;
 use unicode_width::UnicodeWidthStr as _;
 
 use crate::config::CommandNameAndArgs;
@@ -77,7 +87,7 @@ pub const DEFAULT_CONTEXT_LINES: usize = 3;
 #[derive(clap::Args, Clone, Debug)]
 #[command(next_help_heading = "Diff Formatting Options")]
 #[command(group(clap::ArgGroup::new("short-format").args(&["summary", "stat", "types", "name_only"])))]
-#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "tool"])))]
+#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "side_by_side", "tool"])))]
 pub struct DiffFormatArgs {
     /// For each path, show only whether it was modified, added, or deleted
     #[arg(long, short)]
@@ -85,6 +95,14 @@ pub struct DiffFormatArgs {
     /// Show a histogram of the changes
     #[arg(long)]
     pub stat: bool,
+    /// Show the statistics of `--stat` as JSON, one object per line
+    ///
+    /// Each object has the `path`, `source`, `status`, `added` and `removed`
+    /// fields. Paths are relative to the workspace root. In `jj log` and `jj
+    /// evolog`, this implies `--no-graph`, and the template has to print one
+    /// JSON value per line as well, e.g. `-T 'json(self) ++ "\n"'`.
+    #[arg(long)]
+    pub json: bool,
     /// For each path, show only its type before and after
     ///
     /// The diff is shown as two letters. The first letter indicates the type
@@ -106,23 +124,45 @@ pub struct DiffFormatArgs {
     /// Show a word-level diff with changes indicated only by color
     #[arg(long)]
     pub color_words: bool,
+    /// Show the old and new contents side by side
+    #[arg(long)]
+    pub side_by_side: bool,
     /// Generate diff by external command
     #[arg(long)]
     pub tool: Option<String>,
     /// Number of lines of context to show
     #[arg(long)]
     context: Option<usize>,
+    /// Algorithm used to match up lines
+    #[arg(
+        long,
+        value_name = "ALGORITHM",
+        value_parser = clap::builder::PossibleValuesParser::new(["histogram", "patience", "myers"])
+            .map(|s| s.parse::<DiffAlgorithm>().unwrap()),
+    )]
+    diff_algorithm: Option<DiffAlgorithm>,
+    /// Ignore whitespace when comparing lines
+    #[arg(long, short = 'w')]
+    ignore_all_space: bool,
+    /// Ignore changes in amount of whitespace when comparing lines
+    #[arg(long, short = 'b', conflicts_with = "ignore_all_space")]
+    ignore_space_change: bool,
+    /// Ignore changes whose lines are all blank
+    #[arg(long)]
+    ignore_blank_lines: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DiffFormat {
     // Non-trivial parameters are boxed in order to keep the variants small
     Summary,
-    Stat,
+    Stat(LineDiffOptions),
+    Json(LineDiffOptions),
     Types,
     NameOnly,
-    Git { context: usize },
+    Git(Box<UnifiedDiffOptions>),
     ColorWords(Box<ColorWordsOptions>),
+    SideBySide(Box<SideBySideOptions>),
     Tool(Box<ExternalMergeTool>),
 }
 
@@ -170,15 +210,22 @@ fn diff_formats_from_args(
         formats.push(DiffFormat::NameOnly);
     }
     if args.git {
-        let context = args.context.unwrap_or(DEFAULT_CONTEXT_LINES);
-        formats.push(DiffFormat::Git { context });
+        let options = UnifiedDiffOptions::from_args(args);
+        formats.push(DiffFormat::Git(Box::new(options)));
     }
     if args.color_words {
         let options = ColorWordsOptions::from_settings_and_args(settings, args)?;
         formats.push(DiffFormat::ColorWords(Box::new(options)));
     }
+    if args.side_by_side {
+        let options = SideBySideOptions::from_args(args);
+        formats.push(DiffFormat::SideBySide(Box::new(options)));
+    }
     if args.stat {
-        formats.push(DiffFormat::Stat);
+        formats.push(DiffFormat::Stat(LineDiffOptions::from_args(args)));
+    }
+    if args.json {
+        formats.push(DiffFormat::Json(LineDiffOptions::from_args(args)));
     }
     if let Some(name) = &args.tool {
         let tool = merge_tools::get_external_tool_config(settings, name)?
@@ -214,14 +261,20 @@ fn default_diff_format(
         "summary" => Ok(DiffFormat::Summary),
         "types" => Ok(DiffFormat::Types),
         "name-only" => Ok(DiffFormat::NameOnly),
-        "git" => Ok(DiffFormat::Git {
-            context: args.context.unwrap_or(DEFAULT_CONTEXT_LINES),
-        }),
+        "git" => {
+            let options = UnifiedDiffOptions::from_args(args);
+            Ok(DiffFormat::Git(Box::new(options)))
+        }
         "color-words" => {
             let options = ColorWordsOptions::from_settings_and_args(settings, args)?;
             Ok(DiffFormat::ColorWords(Box::new(options)))
         }
-        "stat" => Ok(DiffFormat::Stat),
+        "side-by-side" => {
+            let options = SideBySideOptions::from_args(args);
+            Ok(DiffFormat::SideBySide(Box::new(options)))
+        }
+        "stat" => Ok(DiffFormat::Stat(LineDiffOptions::from_args(args))),
+        "json" => Ok(DiffFormat::Json(LineDiffOptions::from_args(args))),
         _ => Err(config::ConfigError::Message(format!(
             "invalid diff format: {name}"
         ))),
@@ -247,6 +300,7 @@ pub enum DiffRenderError {
 pub struct DiffRenderer<'a> {
     repo: &'a dyn Repo,
     path_converter: &'a RepoPathUiConverter,
+    conflict_marker_style: ConflictMarkerStyle,
     formats: Vec<DiffFormat>,
 }
 
@@ -254,15 +308,25 @@ impl<'a> DiffRenderer<'a> {
     pub fn new(
         repo: &'a dyn Repo,
         path_converter: &'a RepoPathUiConverter,
+        conflict_marker_style: ConflictMarkerStyle,
         formats: Vec<DiffFormat>,
     ) -> Self {
         DiffRenderer {
             repo,
             formats,
             path_converter,
+            conflict_marker_style,
         }
     }
 
+    /// Whether the diff is printed as JSON, which can't be interleaved with
+    /// a graph.
+    pub fn has_json_format(&self) -> bool {
+        self.formats
+            .iter()
+            .any(|format| matches!(format, DiffFormat::Json(_)))
+    }
+
     /// Generates diff between `from_tree` and `to_tree`.
     #[allow(clippy::too_many_arguments)]
     pub fn show_diff(
@@ -301,6 +365,7 @@ impl<'a> DiffRenderer<'a> {
     ) -> Result<(), DiffRenderError> {
         let store = self.repo.store();
         let path_converter = self.path_converter;
+        let conflict_marker_style = self.conflict_marker_style;
         for format in &self.formats {
             match format {
                 DiffFormat::Summary => {
@@ -308,10 +373,29 @@ impl<'a> DiffRenderer<'a> {
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
                     show_diff_summary(formatter, tree_diff, path_converter)?;
                 }
-                DiffFormat::Stat => {
+                DiffFormat::Stat(options) => {
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    show_diff_stat(
+                        formatter,
+                        store,
+                        tree_diff,
+                        path_converter,
+                        options,
+                        width,
+                        conflict_marker_style,
+                    )?;
+                }
+                DiffFormat::Json(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
-                    show_diff_stat(formatter, store, tree_diff, path_converter, width)?;
+                    show_diff_stat_json(
+                        formatter,
+                        store,
+                        tree_diff,
+                        options,
+                        conflict_marker_style,
+                    )?;
                 }
                 DiffFormat::Types => {
                     let tree_diff =
@@ -323,15 +407,35 @@ impl<'a> DiffRenderer<'a> {
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
                     show_names(formatter, tree_diff, path_converter)?;
                 }
-                DiffFormat::Git { context } => {
+                DiffFormat::Git(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
-                    show_git_diff(formatter, store, tree_diff, *context)?;
+                    show_git_diff(formatter, store, tree_diff, options, conflict_marker_style)?;
                 }
                 DiffFormat::ColorWords(options) => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
-                    show_color_words_diff(formatter, store, tree_diff, path_converter, options)?;
+                    show_color_words_diff(
+                        formatter,
+                        store,
+                        tree_diff,
+                        path_converter,
+                        options,
+                        conflict_marker_style,
+                    )?;
+                }
+                DiffFormat::SideBySide(options) => {
+                    let tree_diff =
+                        from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
+                    show_side_by_side_diff(
+                        formatter,
+                        store,
+                        tree_diff,
+                        path_converter,
+                        options,
+                        width,
+                        conflict_marker_style,
+                    )?;
                 }
                 DiffFormat::Tool(tool) => {
                     match tool.diff_invocation_mode {
@@ -345,12 +449,19 @@ impl<'a> DiffRenderer<'a> {
                                 tree_diff,
                                 path_converter,
                                 tool,
+                                conflict_marker_style,
                             )
                         }
-                        DiffToolMode::Dir => {
-                            generate_diff(ui, formatter.raw(), from_tree, to_tree, matcher, tool)
-                                .map_err(DiffRenderError::DiffGenerate)
-                        }
+                        DiffToolMode::Dir => generate_diff(
+                            ui,
+                            formatter.raw(),
+                            from_tree,
+                            to_tree,
+                            matcher,
+                            tool,
+                            conflict_marker_style,
+                        )
+                        .map_err(DiffRenderError::DiffGenerate),
                     }?;
                 }
             }
@@ -397,7 +508,7 @@ impl<'a> DiffRenderer<'a> {
         let to_tree = commit.tree()?;
         let mut copy_records = CopyRecords::default();
         for parent_id in commit.parent_ids() {
-            let records = get_copy_records(self.repo.store(), parent_id, commit.id(), matcher)?;
+            let records = get_copy_records(self.repo, parent_id, commit.id(), matcher)?;
             copy_records.add_records(records)?;
         }
         self.show_diff(
@@ -413,21 +524,154 @@ impl<'a> DiffRenderer<'a> {
 }
 
 pub fn get_copy_records<'a>(
-    store: &'a Store,
+    repo: &'a dyn Repo,
     root: &CommitId,
     head: &CommitId,
     matcher: &'a dyn Matcher,
 ) -> BackendResult<impl Iterator<Item = BackendResult<CopyRecord>> + 'a> {
     // TODO: teach backend about matching path prefixes?
-    let stream = store.get_copy_records(None, root, head)?;
+    let stream = repo
+        .store()
+        .get_copy_records(None, root, head, repo.index())?;
     // TODO: test record.source as well? should be AND-ed or OR-ed?
     Ok(block_on_stream(stream).filter_ok(|record| matcher.matches(&record.target)))
 }
 
+/// Options for comparing files line by line.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LineDiffOptions {
+    /// Algorithm used to match up lines.
+    pub algorithm: DiffAlgorithm,
+    /// How whitespace is taken into account when comparing lines.
+    pub whitespace: WhitespaceMode,
+    /// Whether to hide changes whose lines are all blank, unless they're shown
+    /// along with other changes.
+    pub ignore_blank_lines: bool,
+}
+
+impl LineDiffOptions {
+    fn from_args(args: &DiffFormatArgs) -> Self {
+        let whitespace = if args.ignore_all_space {
+            WhitespaceMode::IgnoreAllSpace
+        } else if args.ignore_space_change {
+            WhitespaceMode::IgnoreSpaceChange
+        } else {
+            WhitespaceMode::Exact
+        };
+        LineDiffOptions {
+            algorithm: args.diff_algorithm.unwrap_or_default(),
+            whitespace,
+            ignore_blank_lines: args.ignore_blank_lines,
+        }
+    }
+
+    /// Parses space-separated option names such as `"ignore-all-space"` or
+    /// `"patience"`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut options = LineDiffOptions::default();
+        for name in text.split_whitespace() {
+            if name == "ignore-blank-lines" {
+                options.ignore_blank_lines = true;
+            } else if let Ok(whitespace) = name.parse() {
+                options.whitespace = whitespace;
+            } else if let Ok(algorithm) = name.parse() {
+                options.algorithm = algorithm;
+            } else {
+                return Err(format!("Invalid diff option: {name}"));
+            }
+        }
+        Ok(options)
+    }
+
+    fn diff_options(&self) -> DiffOptions {
+        DiffOptions {
+            algorithm: self.algorithm,
+            whitespace: self.whitespace,
+        }
+    }
+
+    fn diff_lines<'input>(&self, left: &'input [u8], right: &'input [u8]) -> Diff<'input> {
+        Diff::by_line_with_options([left, right], &self.diff_options())
+    }
+
+    fn diff_words<'input>(&self, contents: &[&'input BStr]) -> Diff<'input> {
+        Diff::by_word_with_options(contents.iter().copied(), &self.diff_options())
+    }
+}
+
+/// Returns true if all sides of the changed hunk consist of blank lines, and
+/// the hunk should be hidden as per `ignore_blank_lines`.
+fn is_blank_lines_hunk(options: &LineDiffOptions, hunk: &DiffHunk) -> bool {
+    match hunk {
+        DiffHunk::Matching(_) => false,
+        DiffHunk::Different(contents) => {
+            options.ignore_blank_lines
+                && contents
+                    .iter()
+                    .all(|content| content.iter().all(u8::is_ascii_whitespace))
+        }
+    }
+}
+
+/// Returns flags indicating which of the `hunks` should be hidden as per
+/// `ignore_blank_lines`.
+///
+/// Changes consisting only of blank lines are still shown if they're close
+/// enough to other changes to share context lines with them.
+fn find_hidden_hunks(
+    hunks: &[DiffHunk],
+    options: &LineDiffOptions,
+    num_context_lines: usize,
+) -> Vec<bool> {
+    let mut hidden = hunks
+        .iter()
+        .map(|hunk| is_blank_lines_hunk(options, hunk))
+        .collect_vec();
+    let is_separator = |hunk: &DiffHunk| match hunk {
+        DiffHunk::Matching(content) => {
+            content.split_inclusive(|b| *b == b'\n').count() > 2 * num_context_lines
+        }
+        DiffHunk::Different(_) => false,
+    };
+    let mut group_start = 0;
+    for i in 0..=hunks.len() {
+        if i == hunks.len() || is_separator(&hunks[i]) {
+            let group = group_start..i;
+            let has_other_changes = group
+                .clone()
+                .any(|j| matches!(hunks[j], DiffHunk::Different(_)) && !hidden[j]);
+            if has_other_changes {
+                hidden[group].fill(false);
+            }
+            group_start = i + 1;
+        }
+    }
+    hidden
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnifiedDiffOptions {
+    /// Number of context lines to show.
+    pub context: usize,
+    /// How lines are compared.
+    pub line_diff: LineDiffOptions,
+}
+
+impl UnifiedDiffOptions {
+    fn from_args(args: &DiffFormatArgs) -> Self {
+        UnifiedDiffOptions {
+            context: args.context.unwrap_or(DEFAULT_CONTEXT_LINES),
+            line_diff: LineDiffOptions::from_args(args),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ColorWordsOptions {
     /// Number of context lines to show.
     pub context: usize,
+    /// How lines are compared.
+    pub line_diff: LineDiffOptions,
     /// Maximum number of removed/added word alternation to inline.
     pub max_inline_alternation: Option<usize>,
 }
@@ -449,59 +693,84 @@ impl ColorWordsOptions {
         };
         Ok(ColorWordsOptions {
             context: args.context.unwrap_or(DEFAULT_CONTEXT_LINES),
+            line_diff: LineDiffOptions::from_args(args),
             max_inline_alternation,
         })
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SideBySideOptions {
+    /// Number of context lines to show.
+    pub context: usize,
+    /// How lines are compared.
+    pub line_diff: LineDiffOptions,
+}
+
+impl SideBySideOptions {
+    fn from_args(args: &DiffFormatArgs) -> Self {
+        SideBySideOptions {
+            context: args.context.unwrap_or(DEFAULT_CONTEXT_LINES),
+            line_diff: LineDiffOptions::from_args(args),
+        }
+    }
+}
+
 fn show_color_words_diff_hunks(
     formatter: &mut dyn Formatter,
     left: &[u8],
     right: &[u8],
     options: &ColorWordsOptions,
 ) -> io::Result<()> {
-    let line_diff = Diff::by_line([left, right]);
-    let mut line_diff_hunks = line_diff.hunks().peekable();
+    let line_diff = options.line_diff.diff_lines(left, right);
+    let line_diff_hunks = line_diff.hunks().collect_vec();
+    let hidden = find_hidden_hunks(&line_diff_hunks, &options.line_diff, options.context);
+    let is_shown_change =
+        |i: usize| matches!(line_diff_hunks.get(i), Some(DiffHunk::Different(_))) && !hidden[i];
+    if !(0..line_diff_hunks.len()).any(is_shown_change) {
+        return Ok(());
+    }
     let mut line_number = DiffLineNumber { left: 1, right: 1 };
+    // Whether the last printed line is an ellipsis
+    let mut skipped_context = false;
 
-    // First "before" context
-    if let Some(DiffHunk::Matching(content)) =
-        line_diff_hunks.next_if(|hunk| matches!(hunk, DiffHunk::Matching(_)))
-    {
-        if line_diff_hunks.peek().is_some() {
-            line_number = show_color_words_context_lines(
-                formatter,
-                content,
-                line_number,
-                0,
-                options.context,
-            )?;
-        }
-    }
-    while let Some(hunk) = line_diff_hunks.next() {
+    for (i, hunk) in line_diff_hunks.iter().enumerate() {
         match hunk {
-            // Middle "after"/"before" context
-            DiffHunk::Matching(content) if line_diff_hunks.peek().is_some() => {
-                line_number = show_color_words_context_lines(
-                    formatter,
-                    content,
-                    line_number,
-                    options.context,
-                    options.context,
-                )?;
-            }
-            // Last "after" context
+            // "after" context of the previous change and "before" context of
+            // the next change
             DiffHunk::Matching(content) => {
-                line_number = show_color_words_context_lines(
+                let num_after = if i > 0 && is_shown_change(i - 1) {
+                    options.context
+                } else {
+                    0
+                };
+                let num_before = if is_shown_change(i + 1) {
+                    options.context
+                } else {
+                    0
+                };
+                line_number = show_context_lines(
                     formatter,
                     content,
                     line_number,
-                    options.context,
-                    0,
+                    num_after,
+                    num_before,
+                    &mut skipped_context,
+                    |formatter, line_number, line| {
+                        let diff_line = DiffLine {
+                            line_number,
+                            hunks: vec![(DiffLineHunkSide::Both, line.as_ref())],
+                        };
+                        show_color_words_diff_line(formatter, &diff_line)
+                    },
                 )?;
             }
+            DiffHunk::Different(contents) if hidden[i] => {
+                skip_hidden_hunk_lines(&mut line_number, contents);
+            }
             DiffHunk::Different(contents) => {
-                let word_diff_hunks = Diff::by_word(&contents).hunks().collect_vec();
+                skipped_context = false;
+                let word_diff_hunks = Diff::by_word(contents).hunks().collect_vec();
                 let can_inline = match options.max_inline_alternation {
                     None => true,     // unlimited
                     Some(0) => false, // no need to count alternation
@@ -537,41 +806,57 @@ fn show_color_words_diff_hunks(
     Ok(())
 }
 
-/// Prints `num_after` lines, ellipsis, and `num_before` lines.
-fn show_color_words_context_lines(
+/// Advances `line_number` past the lines of the hidden hunk `contents`.
+fn skip_hidden_hunk_lines(line_number: &mut DiffLineNumber, contents: &[&BStr]) {
+    let [left, right] = contents[..]
+        .try_into()
+        .expect("hunk should have exactly two inputs");
+    let count_lines = |content: &[u8]| -> u32 {
+        let num_lines = content.split_inclusive(|b| *b == b'\n').count();
+        num_lines.try_into().unwrap()
+    };
+    line_number.left += count_lines(left);
+    line_number.right += count_lines(right);
+}
+
+/// Prints `num_after` lines, ellipsis, and `num_before` lines by using
+/// `show_line`.
+///
+/// The ellipsis is omitted if `skipped_context` indicates that the last
+/// printed line is an ellipsis.
+fn show_context_lines(
     formatter: &mut dyn Formatter,
     content: &[u8],
     mut line_number: DiffLineNumber,
     num_after: usize,
     num_before: usize,
+    skipped_context: &mut bool,
+    mut show_line: impl FnMut(&mut dyn Formatter, DiffLineNumber, &[u8]) -> io::Result<()>,
 ) -> io::Result<DiffLineNumber> {
     const SKIPPED_CONTEXT_LINE: &str = "    ...\n";
     let mut lines = content.split_inclusive(|b| *b == b'\n').fuse();
     for line in lines.by_ref().take(num_after) {
-        let diff_line = DiffLine {
-            line_number,
-            hunks: vec![(DiffLineHunkSide::Both, line.as_ref())],
-        };
-        show_color_words_diff_line(formatter, &diff_line)?;
+        show_line(formatter, line_number, line)?;
         line_number.left += 1;
         line_number.right += 1;
+        *skipped_context = false;
     }
     let mut before_lines = lines.by_ref().rev().take(num_before + 1).collect_vec();
     let num_skipped: u32 = lines.count().try_into().unwrap();
     if num_skipped > 0 {
-        write!(formatter, "{SKIPPED_CONTEXT_LINE}")?;
+        if !*skipped_context {
+            write!(formatter, "{SKIPPED_CONTEXT_LINE}")?;
+            *skipped_context = true;
+        }
         before_lines.pop();
         line_number.left += num_skipped + 1;
         line_number.right += num_skipped + 1;
     }
     for line in before_lines.into_iter().rev() {
-        let diff_line = DiffLine {
-            line_number,
-            hunks: vec![(DiffLineHunkSide::Both, line.as_ref())],
-        };
-        show_color_words_diff_line(formatter, &diff_line)?;
+        show_line(formatter, line_number, line)?;
         line_number.left += 1;
         line_number.right += 1;
+        *skipped_context = false;
     }
     Ok(line_number)
 }
@@ -685,6 +970,270 @@ fn split_diff_hunks_by_matching_newline<'a, 'b>(
     })
 }
 
+fn show_side_by_side_diff_hunks(
+    formatter: &mut dyn Formatter,
+    left: &[u8],
+    right: &[u8],
+    options: &SideBySideOptions,
+    width: usize,
+) -> io::Result<()> {
+    let max_line_number = max(count_lines(left), count_lines(right));
+    let layout = SideBySideLayout::new(width, max_line_number);
+    let line_diff = options.line_diff.diff_lines(left, right);
+    let line_diff_hunks = line_diff.hunks().collect_vec();
+    let hidden = find_hidden_hunks(&line_diff_hunks, &options.line_diff, options.context);
+    let is_shown_change =
+        |i: usize| matches!(line_diff_hunks.get(i), Some(DiffHunk::Different(_))) && !hidden[i];
+    if !(0..line_diff_hunks.len()).any(is_shown_change) {
+        return Ok(());
+    }
+    let mut line_number = DiffLineNumber { left: 1, right: 1 };
+    // Whether the last printed line is an ellipsis
+    let mut skipped_context = false;
+
+    for (i, hunk) in line_diff_hunks.iter().enumerate() {
+        match hunk {
+            DiffHunk::Matching(content) => {
+                let num_after = if i > 0 && is_shown_change(i - 1) {
+                    options.context
+                } else {
+                    0
+                };
+                let num_before = if is_shown_change(i + 1) {
+                    options.context
+                } else {
+                    0
+                };
+                line_number = show_context_lines(
+                    formatter,
+                    content,
+                    line_number,
+                    num_after,
+                    num_before,
+                    &mut skipped_context,
+                    |formatter, line_number, line| {
+                        let tokens = [(DiffTokenType::Matching, line)];
+                        let left = SideBySideLine {
+                            line_number: line_number.left,
+                            tokens: &tokens,
+                            label: None,
+                        };
+                        let right = SideBySideLine {
+                            line_number: line_number.right,
+                            tokens: &tokens,
+                            label: None,
+                        };
+                        show_side_by_side_line(formatter, Some(left), Some(right), &layout)
+                    },
+                )?;
+            }
+            DiffHunk::Different(contents) if hidden[i] => {
+                skip_hidden_hunk_lines(&mut line_number, contents);
+            }
+            DiffHunk::Different(contents) => {
+                skipped_context = false;
+                let word_diff_hunks = options.line_diff.diff_words(contents).hunks().collect_vec();
+                let (left_lines, right_lines) = unzip_diff_hunks_to_lines(&word_diff_hunks);
+                for pair in left_lines.iter().zip_longest(&right_lines) {
+                    let (left_tokens, right_tokens) = pair.left_and_right();
+                    let left = left_tokens.map(|tokens| SideBySideLine {
+                        line_number: line_number.left,
+                        tokens,
+                        label: Some("removed"),
+                    });
+                    let right = right_tokens.map(|tokens| SideBySideLine {
+                        line_number: line_number.right,
+                        tokens,
+                        label: Some("added"),
+                    });
+                    show_side_by_side_line(formatter, left, right, &layout)?;
+                    line_number.left += u32::from(left_tokens.is_some());
+                    line_number.right += u32::from(right_tokens.is_some());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Minimum number of digits in the line number column of side-by-side diff.
+const SIDE_BY_SIDE_MIN_LINE_NUMBER_DIGITS: usize = 4;
+const SIDE_BY_SIDE_SEPARATOR: &str = " |";
+const SIDE_BY_SIDE_MIN_CONTENT_WIDTH: usize = 10;
+const SIDE_BY_SIDE_TAB_WIDTH: usize = 4;
+
+fn count_lines(content: &[u8]) -> usize {
+    content.split_inclusive(|b| *b == b'\n').count()
+}
+
+/// Column widths of side-by-side diff.
+#[derive(Clone, Copy, Debug)]
+struct SideBySideLayout {
+    /// Display width of each line number, excluding the space after it.
+    line_number_width: usize,
+    /// Display width of each content column.
+    content_width: usize,
+}
+
+impl SideBySideLayout {
+    /// Fits the columns in the terminal `width`, leaving enough room for
+    /// line numbers up to `max_line_number`.
+    fn new(width: usize, max_line_number: usize) -> Self {
+        let line_number_width = max(
+            max_line_number.to_string().len(),
+            SIDE_BY_SIDE_MIN_LINE_NUMBER_DIGITS,
+        );
+        // left/right line numbers followed by a space, separator, and space
+        // after the separator
+        let fixed_width = 2 * (line_number_width + 1) + SIDE_BY_SIDE_SEPARATOR.len() + 1;
+        let content_width = max(
+            width.saturating_sub(fixed_width) / 2,
+            SIDE_BY_SIDE_MIN_CONTENT_WIDTH,
+        );
+        SideBySideLayout {
+            line_number_width,
+            content_width,
+        }
+    }
+}
+
+/// One side of side-by-side diff line.
+#[derive(Clone, Copy, Debug)]
+struct SideBySideLine<'a, 'content> {
+    line_number: u32,
+    tokens: &'a [(DiffTokenType, &'content [u8])],
+    /// Label of the changed line, or `None` for a context line.
+    label: Option<&'static str>,
+}
+
+/// Row of wrapped line tokens and its display width.
+type SideBySideRow = (Vec<(DiffTokenType, String)>, usize);
+
+/// Prints `left` and `right` lines in columns, wrapping long lines.
+fn show_side_by_side_line(
+    formatter: &mut dyn Formatter,
+    left: Option<SideBySideLine>,
+    right: Option<SideBySideLine>,
+    layout: &SideBySideLayout,
+) -> io::Result<()> {
+    let content_width = layout.content_width;
+    let wrap = |line: &Option<SideBySideLine>| {
+        line.as_ref()
+            .map(|line| wrap_diff_line_tokens(line.tokens, content_width))
+            .unwrap_or_default()
+    };
+    let left_rows = wrap(&left);
+    let right_rows = wrap(&right);
+    for i in 0..max(left_rows.len(), right_rows.len()) {
+        let left_line_number = left.filter(|_| i == 0).map(|line| line.line_number);
+        show_side_by_side_line_number(formatter, left_line_number, "removed", layout)?;
+        write!(formatter, " ")?;
+        let left_row_width = if let Some((tokens, row_width)) = left_rows.get(i) {
+            let label = left.and_then(|line| line.label);
+            show_side_by_side_row_tokens(formatter, tokens, label)?;
+            *row_width
+        } else {
+            0
+        };
+        let padding = content_width.saturating_sub(left_row_width);
+        write!(formatter, "{:padding$}{SIDE_BY_SIDE_SEPARATOR}", "")?;
+        if let Some((tokens, _)) = right_rows.get(i) {
+            write!(formatter, " ")?;
+            let right_line_number = right.filter(|_| i == 0).map(|line| line.line_number);
+            show_side_by_side_line_number(formatter, right_line_number, "added", layout)?;
+            if !tokens.is_empty() {
+                write!(formatter, " ")?;
+                let label = right.and_then(|line| line.label);
+                show_side_by_side_row_tokens(formatter, tokens, label)?;
+            }
+        }
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
+
+fn show_side_by_side_line_number(
+    formatter: &mut dyn Formatter,
+    line_number: Option<u32>,
+    label: &str,
+    layout: &SideBySideLayout,
+) -> io::Result<()> {
+    let width = layout.line_number_width;
+    if let Some(line_number) = line_number {
+        formatter.with_label(label, |formatter| {
+            write!(formatter.labeled("line_number"), "{line_number:>width$}")
+        })
+    } else {
+        write!(formatter, "{:width$}", "")
+    }
+}
+
+fn show_side_by_side_row_tokens(
+    formatter: &mut dyn Formatter,
+    tokens: &[(DiffTokenType, String)],
+    label: Option<&str>,
+) -> io::Result<()> {
+    let show_tokens = |formatter: &mut dyn Formatter| {
+        for (token_type, text) in tokens {
+            match token_type {
+                DiffTokenType::Matching => write!(formatter, "{text}")?,
+                DiffTokenType::Different => {
+                    write!(formatter.labeled("token"), "{text}")?;
+                }
+            }
+        }
+        Ok(())
+    };
+    if let Some(label) = label {
+        formatter.with_label(label, show_tokens)
+    } else {
+        show_tokens(formatter)
+    }
+}
+
+/// Splits line `tokens` into rows of at most `width` display columns.
+///
+/// The line terminator is removed, and tabs are expanded to spaces. There's
+/// always at least one row.
+fn wrap_diff_line_tokens(tokens: &[(DiffTokenType, &[u8])], width: usize) -> Vec<SideBySideRow> {
+    let mut rows = vec![];
+    let mut row_tokens = vec![];
+    let mut row_width = 0;
+    let mut column = 0;
+    for (token_type, content) in tokens {
+        let content = content.strip_suffix(b"\n").unwrap_or(content);
+        let content = content.strip_suffix(b"\r").unwrap_or(content);
+        let mut text = String::new();
+        for c in String::from_utf8_lossy(content).chars() {
+            let char_width = if c == '\t' {
+                SIDE_BY_SIDE_TAB_WIDTH - column % SIDE_BY_SIDE_TAB_WIDTH
+            } else {
+                c.width().unwrap_or(0)
+            };
+            if row_width > 0 && row_width + char_width > width {
+                if !text.is_empty() {
+                    row_tokens.push((*token_type, mem::take(&mut text)));
+                }
+                rows.push((mem::take(&mut row_tokens), row_width));
+                row_width = 0;
+            }
+            if c == '\t' {
+                text.extend(iter::repeat(' ').take(char_width));
+            } else {
+                text.push(c);
+            }
+            row_width += char_width;
+            column += char_width;
+        }
+        if !text.is_empty() {
+            row_tokens.push((*token_type, text));
+        }
+    }
+    rows.push((row_tokens, row_width));
+    rows
+}
+
 struct FileContent {
     /// false if this file is likely text; true if it is likely binary.
     is_binary: bool,
@@ -722,7 +1271,11 @@ fn file_content_for_diff(reader: &mut dyn io::Read) -> io::Result<FileContent> {
     })
 }
 
-fn diff_content(path: &RepoPath, value: MaterializedTreeValue) -> io::Result<FileContent> {
+fn diff_content(
+    path: &RepoPath,
+    value: MaterializedTreeValue,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> io::Result<FileContent> {
     match value {
         MaterializedTreeValue::Absent => Ok(FileContent::empty()),
         MaterializedTreeValue::AccessDenied(err) => Ok(FileContent {
@@ -748,7 +1301,7 @@ fn diff_content(path: &RepoPath, value: MaterializedTreeValue) -> io::Result<Fil
             executable: _,
         } => {
             let mut data = vec![];
-            materialize_merge_result(&contents, &mut data)
+            materialize_merge_result(&contents, conflict_marker_style, &mut data)
                 .expect("Failed to materialize conflict to in-memory buffer");
             Ok(FileContent {
                 is_binary: false,
@@ -792,6 +1345,49 @@ pub fn show_color_words_diff(
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
     path_converter: &RepoPathUiConverter,
     options: &ColorWordsOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    show_diff_with_file_headers(
+        formatter,
+        store,
+        tree_diff,
+        path_converter,
+        conflict_marker_style,
+        |formatter, left, right| show_color_words_diff_hunks(formatter, left, right, options),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn show_side_by_side_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    path_converter: &RepoPathUiConverter,
+    options: &SideBySideOptions,
+    width: usize,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    show_diff_with_file_headers(
+        formatter,
+        store,
+        tree_diff,
+        path_converter,
+        conflict_marker_style,
+        |formatter, left, right| {
+            show_side_by_side_diff_hunks(formatter, left, right, options, width)
+        },
+    )
+}
+
+/// Prints a descriptive header for each file, followed by the content diff
+/// rendered by `show_hunks`.
+fn show_diff_with_file_headers(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    path_converter: &RepoPathUiConverter,
+    conflict_marker_style: ConflictMarkerStyle,
+    mut show_hunks: impl FnMut(&mut dyn Formatter, &[u8], &[u8]) -> io::Result<()>,
 ) -> Result<(), DiffRenderError> {
     let mut diff_stream = materialized_diff_stream(store, tree_diff);
     async {
@@ -827,13 +1423,13 @@ pub fn show_color_words_diff(
                     formatter.labeled("header"),
                     "Added {description} {right_ui_path}:"
                 )?;
-                let right_content = diff_content(right_path, right_value)?;
+                let right_content = diff_content(right_path, right_value, conflict_marker_style)?;
                 if right_content.is_empty() {
                     writeln!(formatter.labeled("empty"), "    (empty)")?;
                 } else if right_content.is_binary {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
-                    show_color_words_diff_hunks(formatter, &[], &right_content.contents, options)?;
+                    show_hunks(formatter, &[], &right_content.contents)?;
                 }
             } else if right_value.is_present() {
                 let description = match (&left_value, &right_value) {
@@ -889,8 +1485,8 @@ pub fn show_color_words_diff(
                         )
                     }
                 };
-                let left_content = diff_content(left_path, left_value)?;
-                let right_content = diff_content(right_path, right_value)?;
+                let left_content = diff_content(left_path, left_value, conflict_marker_style)?;
+                let right_content = diff_content(right_path, right_value, conflict_marker_style)?;
                 if left_path == right_path {
                     writeln!(
                         formatter.labeled("header"),
@@ -905,12 +1501,7 @@ pub fn show_color_words_diff(
                 if left_content.is_binary || right_content.is_binary {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
-                    show_color_words_diff_hunks(
-                        formatter,
-                        &left_content.contents,
-                        &right_content.contents,
-                        options,
-                    )?;
+                    show_hunks(formatter, &left_content.contents, &right_content.contents)?;
                 }
             } else {
                 let description = basic_diff_file_type(&left_value);
@@ -918,13 +1509,13 @@ pub fn show_color_words_diff(
                     formatter.labeled("header"),
                     "Removed {description} {right_ui_path}:"
                 )?;
-                let left_content = diff_content(left_path, left_value)?;
+                let left_content = diff_content(left_path, left_value, conflict_marker_style)?;
                 if left_content.is_empty() {
                     writeln!(formatter.labeled("empty"), "    (empty)")?;
                 } else if left_content.is_binary {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
-                    show_color_words_diff_hunks(formatter, &left_content.contents, &[], options)?;
+                    show_hunks(formatter, &left_content.contents, &[])?;
                 }
             }
         }
@@ -940,18 +1531,18 @@ pub fn show_file_by_file_diff(
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
     path_converter: &RepoPathUiConverter,
     tool: &ExternalMergeTool,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
-    fn create_file(
-        path: &RepoPath,
-        wc_dir: &Path,
-        value: MaterializedTreeValue,
-    ) -> Result<PathBuf, DiffRenderError> {
+    let create_file = |path: &RepoPath,
+                       wc_dir: &Path,
+                       value: MaterializedTreeValue|
+     -> Result<PathBuf, DiffRenderError> {
         let fs_path = path.to_fs_path(wc_dir);
         std::fs::create_dir_all(fs_path.parent().unwrap())?;
-        let content = diff_content(path, value)?;
+        let content = diff_content(path, value, conflict_marker_style)?;
         std::fs::write(&fs_path, content.contents)?;
         Ok(fs_path)
-    }
+    };
 
     let temp_dir = new_utf8_temp_dir("jj-diff-")?;
     let left_wc_dir = temp_dir.path().join("left");
@@ -1013,6 +1604,7 @@ struct GitDiffPart {
 fn git_diff_part(
     path: &RepoPath,
     value: MaterializedTreeValue,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<GitDiffPart, DiffRenderError> {
     const DUMMY_HASH: &str = "0000000000";
     let mode;
@@ -1051,10 +1643,13 @@ fn git_diff_part(
             };
         }
         MaterializedTreeValue::GitSubmodule(id) => {
-            // TODO: What should we actually do here?
-            mode = "040000";
+            // Describe the commit pointer as Git does
+            mode = "160000";
             hash = id.hex();
-            content = FileContent::empty();
+            content = FileContent {
+                is_binary: false,
+                contents: format!("Subproject commit {}\n", id.hex()).into_bytes(),
+            };
         }
         MaterializedTreeValue::FileConflict {
             id: _,
@@ -1064,7 +1659,7 @@ fn git_diff_part(
             mode = if executable { "100755" } else { "100644" };
             hash = DUMMY_HASH.to_owned();
             let mut data = vec![];
-            materialize_merge_result(&contents, &mut data)
+            materialize_merge_result(&contents, conflict_marker_style, &mut data)
                 .expect("Failed to materialize conflict to in-memory buffer");
             content = FileContent {
                 is_binary: false, // TODO: are we sure this is never binary?
@@ -1113,6 +1708,15 @@ struct UnifiedDiffHunk<'content> {
 }
 
 impl<'content> UnifiedDiffHunk<'content> {
+    /// Returns true if all removed and added lines are blank.
+    fn is_blank_lines_only(&self) -> bool {
+        self.lines
+            .iter()
+            .filter(|(line_type, _)| *line_type != DiffLineType::Context)
+            .flat_map(|(_, tokens)| tokens)
+            .all(|(_, content)| content.iter().all(u8::is_ascii_whitespace))
+    }
+
     fn extend_context_lines(&mut self, lines: impl IntoIterator<Item = &'content [u8]>) {
         let old_len = self.lines.len();
         self.lines.extend(lines.into_iter().map(|line| {
@@ -1141,15 +1745,16 @@ impl<'content> UnifiedDiffHunk<'content> {
 fn unified_diff_hunks<'content>(
     left_content: &'content [u8],
     right_content: &'content [u8],
-    num_context_lines: usize,
+    options: &UnifiedDiffOptions,
 ) -> Vec<UnifiedDiffHunk<'content>> {
+    let num_context_lines = options.context;
     let mut hunks = vec![];
     let mut current_hunk = UnifiedDiffHunk {
         left_line_range: 1..1,
         right_line_range: 1..1,
         lines: vec![],
     };
-    let diff = Diff::by_line([left_content, right_content]);
+    let diff = options.line_diff.diff_lines(left_content, right_content);
     let mut diff_hunks = diff.hunks().peekable();
     while let Some(hunk) = diff_hunks.next() {
         match hunk {
@@ -1191,6 +1796,10 @@ fn unified_diff_hunks<'content>(
     if !current_hunk.lines.is_empty() {
         hunks.push(current_hunk);
     }
+    if options.line_diff.ignore_blank_lines {
+        // Each hunk is a group of changes sharing context lines.
+        hunks.retain(|hunk| !hunk.is_blank_lines_only());
+    }
     hunks
 }
 
@@ -1252,9 +1861,9 @@ fn show_unified_diff_hunks(
     formatter: &mut dyn Formatter,
     left_content: &[u8],
     right_content: &[u8],
-    num_context_lines: usize,
+    options: &UnifiedDiffOptions,
 ) -> io::Result<()> {
-    for hunk in unified_diff_hunks(left_content, right_content, num_context_lines) {
+    for hunk in unified_diff_hunks(left_content, right_content, options) {
         writeln!(
             formatter.labeled("hunk_header"),
             "@@ -{},{} +{},{} @@",
@@ -1298,10 +1907,53 @@ fn show_diff_line_tokens(
 }
 
 pub fn show_git_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    options: &UnifiedDiffOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    write_git_diff(
+        formatter,
+        store,
+        tree_diff,
+        options,
+        false,
+        conflict_marker_style,
+    )
+}
+
+/// Like [`show_git_diff()`], but includes the contents of binary files as git
+/// binary patches so that the diff can be applied.
+pub fn show_git_patch(
     formatter: &mut dyn Formatter,
     store: &Store,
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
     num_context_lines: usize,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    // Whitespace changes can't be omitted from a patch to be applied.
+    let options = UnifiedDiffOptions {
+        context: num_context_lines,
+        line_diff: LineDiffOptions::default(),
+    };
+    write_git_diff(
+        formatter,
+        store,
+        tree_diff,
+        &options,
+        true,
+        conflict_marker_style,
+    )
+}
+
+fn write_git_diff(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    options: &UnifiedDiffOptions,
+    include_binary: bool,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
     let mut diff_stream = materialized_diff_stream(store, tree_diff);
     async {
@@ -1312,8 +1964,8 @@ pub fn show_git_diff(
             let right_path_string = right_path.as_internal_file_string();
             let (left_value, right_value) = values?;
 
-            let left_part = git_diff_part(left_path, left_value)?;
-            let right_part = git_diff_part(right_path, right_value)?;
+            let left_part = git_diff_part(left_path, left_value, conflict_marker_style)?;
+            let right_part = git_diff_part(right_path, right_value, conflict_marker_style)?;
 
             formatter.with_label("file_header", |formatter| {
                 writeln!(
@@ -1368,8 +2020,11 @@ pub fn show_git_diff(
                 Some(_) => format!("b/{right_path_string}"),
                 None => "/dev/null".to_owned(),
             };
-            if left_part.content.is_binary || right_part.content.is_binary {
-                // TODO: add option to emit Git binary diff
+            if (left_part.content.is_binary || right_part.content.is_binary) && include_binary {
+                let binary_patch =
+                    format_binary_patch(&left_part.content.contents, &right_part.content.contents);
+                formatter.write_all(binary_patch.as_bytes())?;
+            } else if left_part.content.is_binary || right_part.content.is_binary {
                 writeln!(
                     formatter,
                     "Binary files {left_path} and {right_path} differ"
@@ -1384,7 +2039,7 @@ pub fn show_git_diff(
                     formatter,
                     &left_part.content.contents,
                     &right_part.content.contents,
-                    num_context_lines,
+                    options,
                 )?;
             }
         }
@@ -1426,27 +2081,63 @@ pub fn show_diff_summary(
     .block_on()
 }
 
-struct DiffStat {
-    path: String,
-    added: usize,
-    removed: usize,
-    is_deletion: bool,
+/// Line-based statistics of a file in a diff.
+pub struct DiffStat {
+    pub path: CopiesTreeDiffEntryPath,
+    pub added: usize,
+    pub removed: usize,
+    pub is_addition: bool,
+    pub is_deletion: bool,
+}
+
+impl DiffStat {
+    /// Serializes the statistics as a JSON object with the `path`, `source`,
+    /// `status`, `added` and `removed` fields.
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let status = match self.path.copy_operation() {
+            Some(CopyOperation::Copy) => "copied",
+            Some(CopyOperation::Rename) => "renamed",
+            None if self.is_addition => "added",
+            None if self.is_deletion => "removed",
+            None => "modified",
+        };
+        let source = self
+            .path
+            .copy_operation()
+            .map(|_| self.path.source().as_internal_file_string());
+        serde_json::json!({
+            "path": self.path.target().as_internal_file_string(),
+            "source": source,
+            "status": status,
+            "added": self.added,
+            "removed": self.removed,
+        })
+    }
 }
 
 fn get_diff_stat(
-    path: String,
-    left_content: &FileContent,
-    right_content: &FileContent,
-) -> DiffStat {
+    path: CopiesTreeDiffEntryPath,
+    left: MaterializedTreeValue,
+    right: MaterializedTreeValue,
+    options: &LineDiffOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> io::Result<DiffStat> {
+    let is_addition = left.is_absent();
+    let is_deletion = right.is_absent();
+    let left_content = diff_content(path.source(), left, conflict_marker_style)?;
+    let right_content = diff_content(path.target(), right, conflict_marker_style)?;
     // TODO: this matches git's behavior, which is to count the number of newlines
     // in the file. but that behavior seems unhelpful; no one really cares how
     // many `0x0a` characters are in an image.
-    let diff = Diff::by_line([&left_content.contents, &right_content.contents]);
+    let diff = options.diff_lines(&left_content.contents, &right_content.contents);
+    let hunks = diff.hunks().collect_vec();
+    let hidden = find_hidden_hunks(&hunks, options, DEFAULT_CONTEXT_LINES);
     let mut added = 0;
     let mut removed = 0;
-    for hunk in diff.hunks() {
+    for (hunk, hidden) in iter::zip(hunks, hidden) {
         match hunk {
             DiffHunk::Matching(_) => {}
+            DiffHunk::Different(_) if hidden => {}
             DiffHunk::Different(contents) => {
                 let [left, right] = contents.try_into().unwrap();
                 removed += left.split_inclusive(|b| *b == b'\n').count();
@@ -1454,50 +2145,67 @@ fn get_diff_stat(
             }
         }
     }
-    DiffStat {
+    Ok(DiffStat {
         path,
         added,
         removed,
-        is_deletion: right_content.contents.is_empty(),
-    }
+        is_addition,
+        is_deletion,
+    })
 }
 
-pub fn show_diff_stat(
-    formatter: &mut dyn Formatter,
+/// Computes line-based statistics of the files in the diff. Deletions of the
+/// source paths of renames are omitted since they are covered by the renames.
+pub fn get_diff_stats(
     store: &Store,
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
-    path_converter: &RepoPathUiConverter,
-    display_width: usize,
-) -> Result<(), DiffRenderError> {
+    options: &LineDiffOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<Vec<DiffStat>, DiffRenderError> {
     let mut stats: Vec<DiffStat> = vec![];
     let mut unresolved_renames = HashSet::new();
-    let mut max_path_width = 0;
-    let mut max_diffs = 0;
-
     let mut diff_stream = materialized_diff_stream(store, tree_diff);
     async {
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
             let (left, right) = values?;
-            let left_path = path.source();
-            let right_path = path.target();
-            let left_content = diff_content(left_path, left)?;
-            let right_content = diff_content(right_path, right)?;
-
-            let left_ui_path = path_converter.format_file_path(left_path);
-            let path = if left_path == right_path {
-                left_ui_path
-            } else {
-                unresolved_renames.insert(left_ui_path);
-                path_converter.format_copied_path(left_path, right_path)
-            };
-            max_path_width = max(max_path_width, path.width());
-            let stat = get_diff_stat(path, &left_content, &right_content);
-            max_diffs = max(max_diffs, stat.added + stat.removed);
-            stats.push(stat);
+            if path.source() != path.target() {
+                unresolved_renames.insert(path.source().to_owned());
+            }
+            stats.push(get_diff_stat(
+                path,
+                left,
+                right,
+                options,
+                conflict_marker_style,
+            )?);
         }
         Ok::<(), DiffRenderError>(())
     }
     .block_on()?;
+    stats.retain(|stat| !(stat.is_deletion && unresolved_renames.contains(stat.path.target())));
+    Ok(stats)
+}
+
+pub fn show_diff_stat(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    path_converter: &RepoPathUiConverter,
+    options: &LineDiffOptions,
+    display_width: usize,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    let stats = get_diff_stats(store, tree_diff, options, conflict_marker_style)?;
+    let ui_paths = stats
+        .iter()
+        .map(|stat| path_converter.format_copied_path(stat.path.source(), stat.path.target()))
+        .collect_vec();
+    let max_path_width = ui_paths.iter().map(|path| path.width()).max().unwrap_or(0);
+    let max_diffs = stats
+        .iter()
+        .map(|stat| stat.added + stat.removed)
+        .max()
+        .unwrap_or(0);
 
     let number_padding = max_diffs.to_string().len();
     // 4 characters padding for the graph
@@ -1515,18 +2223,14 @@ pub fn show_diff_stat(
     let mut total_added = 0;
     let mut total_removed = 0;
     let mut total_files = 0;
-    for stat in &stats {
-        if stat.is_deletion && unresolved_renames.contains(&stat.path) {
-            continue;
-        }
-
+    for (stat, path) in stats.iter().zip(&ui_paths) {
         total_added += stat.added;
         total_removed += stat.removed;
         total_files += 1;
         let bar_added = (stat.added as f64 * factor).ceil() as usize;
         let bar_removed = (stat.removed as f64 * factor).ceil() as usize;
         // replace start of path with ellipsis if the path is too long
-        let (path, path_width) = text_util::elide_start(&stat.path, "...", max_path_width);
+        let (path, path_width) = text_util::elide_start(path, "...", max_path_width);
         let path_pad_width = max_path_width - path_width;
         write!(
             formatter,
@@ -1551,6 +2255,21 @@ pub fn show_diff_stat(
     Ok(())
 }
 
+/// Prints the statistics of each file in the diff as a line of JSON.
+pub fn show_diff_stat_json(
+    formatter: &mut dyn Formatter,
+    store: &Store,
+    tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    options: &LineDiffOptions,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(), DiffRenderError> {
+    let stats = get_diff_stats(store, tree_diff, options, conflict_marker_style)?;
+    for stat in &stats {
+        writeln!(formatter, "{}", stat.to_json_value())?;
+    }
+    Ok(())
+}
+
 pub fn show_types(
     formatter: &mut dyn Formatter,
     mut tree_diff: BoxStream<CopiesTreeDiffEntry>,
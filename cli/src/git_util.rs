@@ -14,6 +14,7 @@
 
 //! Git utilities shared by various commands.
 
+use std::collections::BTreeMap;
 use std::error;
 use std::io::Read;
 use std::io::Write;
@@ -24,16 +25,23 @@ use std::process::Stdio;
 use std::time::Instant;
 
 use itertools::Itertools;
+use jj_lib::backend::TreeValue;
 use jj_lib::git;
 use jj_lib::git::FailedRefExport;
 use jj_lib::git::FailedRefExportReason;
 use jj_lib::git::GitImportStats;
+use jj_lib::git::GitSubmoduleError;
 use jj_lib::git::RefName;
+use jj_lib::git::SubmoduleConfig;
 use jj_lib::git_backend::GitBackend;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
+use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::store::Store;
 use jj_lib::workspace::Workspace;
 use unicode_width::UnicodeWidthStr;
@@ -230,6 +238,83 @@ impl GitSidebandProgressMessageWriter {
     }
 }
 
+/// Reads the submodule configurations from `.gitmodules` in the `tree`.
+/// Returns `None` if there's no `.gitmodules` file.
+pub fn read_gitmodules(
+    store: &Store,
+    tree: &MergedTree,
+) -> Result<Option<BTreeMap<String, SubmoduleConfig>>, CommandError> {
+    let gitmodules_path = RepoPath::from_internal_string(".gitmodules");
+    let mut gitmodules_file = match tree.path_value(gitmodules_path)?.into_resolved() {
+        Ok(None) => return Ok(None),
+        Ok(Some(TreeValue::File { id, .. })) => store.read_file(gitmodules_path, &id)?,
+        _ => {
+            return Err(user_error(".gitmodules is not a file."));
+        }
+    };
+    Ok(Some(git::parse_gitmodules(&mut gitmodules_file)?))
+}
+
+/// Checks out the new commits of the initialized submodules which changed
+/// between `old_tree` and `new_tree`. Missing commits aren't fetched.
+pub fn update_initialized_submodules(
+    ui: &Ui,
+    repo: &ReadonlyRepo,
+    workspace_id: &WorkspaceId,
+    workspace_root: &Path,
+    old_tree: Option<&MergedTree>,
+    new_tree: &MergedTree,
+) -> Result<(), CommandError> {
+    let Some(submodules) = read_gitmodules(repo.store(), new_tree)? else {
+        return Ok(());
+    };
+    for config in submodules.values() {
+        let Ok(path) = RepoPathBuf::from_relative_path(&config.path) else {
+            continue;
+        };
+        let new_value = new_tree.path_value(&path)?;
+        let Some(TreeValue::GitSubmodule(commit_id)) = new_value.as_normal() else {
+            continue;
+        };
+        if let Some(old_tree) = old_tree {
+            if old_tree.path_value(&path)? == new_value {
+                continue;
+            }
+        }
+        let work_dir = path.to_fs_path(workspace_root);
+        match git::checkout_submodule(
+            repo.submodule_store().as_ref(),
+            &config.name,
+            workspace_id,
+            commit_id,
+            &work_dir,
+        ) {
+            Ok(()) | Err(GitSubmoduleError::NotInitialized(_)) => {}
+            Err(err @ GitSubmoduleError::CommitNotFound { .. }) => {
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to update submodule {}: {err}",
+                    config.path
+                )?;
+                writeln!(
+                    ui.hint_default(),
+                    "Run `jj git submodule update` to fetch the submodule."
+                )?;
+            }
+            Err(err) => {
+                writeln!(
+                    ui.warning_default(),
+                    "Failed to update submodule {}: {}",
+                    config.path,
+                    iter::successors(Some(&err as &dyn error::Error), |err| err.source())
+                        .format(": ")
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
 type SidebandProgressCallback<'a> = &'a mut dyn FnMut(&[u8]);
 
 pub fn with_remote_git_callbacks<T>(
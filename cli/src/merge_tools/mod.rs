@@ -21,6 +21,7 @@ use std::sync::Arc;
 use config::ConfigError;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merged_tree::MergedTree;
@@ -178,6 +179,7 @@ pub struct DiffEditor {
     tool: MergeTool,
     base_ignores: Arc<GitIgnoreFile>,
     use_instructions: bool,
+    conflict_marker_style: ConflictMarkerStyle,
 }
 
 impl DiffEditor {
@@ -218,6 +220,7 @@ impl DiffEditor {
             tool,
             base_ignores,
             use_instructions: settings.config().get_bool("ui.diff-instructions")?,
+            conflict_marker_style: settings.conflict_marker_style()?,
         })
     }
 
@@ -231,7 +234,10 @@ impl DiffEditor {
     ) -> Result<MergedTreeId, DiffEditError> {
         match &self.tool {
             MergeTool::Builtin => {
-                Ok(edit_diff_builtin(left_tree, right_tree, matcher).map_err(Box::new)?)
+                Ok(
+                    edit_diff_builtin(left_tree, right_tree, matcher, self.conflict_marker_style)
+                        .map_err(Box::new)?,
+                )
             }
             MergeTool::External(editor) => {
                 let instructions = self.use_instructions.then(format_instructions);
@@ -242,6 +248,7 @@ impl DiffEditor {
                     matcher,
                     instructions.as_deref(),
                     self.base_ignores.clone(),
+                    self.conflict_marker_style,
                 )
             }
         }
@@ -252,6 +259,7 @@ impl DiffEditor {
 #[derive(Clone, Debug)]
 pub struct MergeEditor {
     tool: MergeTool,
+    conflict_marker_style: ConflictMarkerStyle,
 }
 
 impl MergeEditor {
@@ -260,7 +268,7 @@ impl MergeEditor {
     pub fn with_name(name: &str, settings: &UserSettings) -> Result<Self, MergeToolConfigError> {
         let tool = get_tool_config(settings, name)?
             .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_program(name)));
-        Self::new_inner(name, tool)
+        Self::new_inner(name, tool, settings)
     }
 
     /// Loads the default 3-way merge editor from the settings.
@@ -272,16 +280,23 @@ impl MergeEditor {
             None
         }
         .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_merge_args(&args)));
-        Self::new_inner(&args, tool)
+        Self::new_inner(&args, tool, settings)
     }
 
-    fn new_inner(name: impl ToString, tool: MergeTool) -> Result<Self, MergeToolConfigError> {
+    fn new_inner(
+        name: impl ToString,
+        tool: MergeTool,
+        settings: &UserSettings,
+    ) -> Result<Self, MergeToolConfigError> {
         if matches!(&tool, MergeTool::External(mergetool) if mergetool.merge_args.is_empty()) {
             return Err(MergeToolConfigError::MergeArgsNotConfigured {
                 tool_name: name.to_string(),
             });
         }
-        Ok(MergeEditor { tool })
+        Ok(MergeEditor {
+            tool,
+            conflict_marker_style: settings.conflict_marker_style()?,
+        })
     }
 
     /// Starts a merge editor for the specified file.
@@ -316,7 +331,13 @@ impl MergeEditor {
                 Ok(tree_id)
             }
             MergeTool::External(editor) => external::run_mergetool_external(
-                editor, file_merge, content, repo_path, conflict, tree,
+                editor,
+                file_merge,
+                content,
+                repo_path,
+                conflict,
+                tree,
+                self.conflict_marker_style,
             ),
         }
     }
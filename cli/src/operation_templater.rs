@@ -37,8 +37,10 @@ use crate::templater::PlainTextFormattedProperty;
 use crate::templater::Template;
 use crate::templater::TemplateFormatter;
 use crate::templater::TemplateProperty;
+use crate::templater::TemplatePropertyError;
 use crate::templater::TemplatePropertyExt as _;
 use crate::templater::TimestampRange;
+use crate::templater::ToJson;
 
 pub trait OperationTemplateLanguageExtension {
     fn build_fn_table(&self) -> OperationTemplateBuildFnTable;
@@ -185,6 +187,18 @@ impl IntoTemplateProperty<'static> for OperationTemplatePropertyKind {
             OperationTemplatePropertyKind::OperationId(property) => Some(property.into_template()),
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value>>> {
+        match self {
+            OperationTemplatePropertyKind::Core(property) => property.try_into_json(),
+            OperationTemplatePropertyKind::Operation(property) => {
+                Some(Box::new(property.and_then(|op| op.to_json())))
+            }
+            OperationTemplatePropertyKind::OperationId(property) => {
+                Some(Box::new(property.and_then(|id| id.to_json())))
+            }
+        }
+    }
 }
 
 /// Table of functions that translate method call node of self type `T`.
@@ -307,6 +321,32 @@ impl Template for OperationId {
     }
 }
 
+impl ToJson for Operation {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let metadata = self.metadata();
+        let time = TimestampRange {
+            start: metadata.start_time.clone(),
+            end: metadata.end_time.clone(),
+        };
+        Ok(serde_json::json!({
+            "id": self.id().to_json()?,
+            "parents": self.parent_ids().iter().map(|id| id.to_json()).try_collect::<_, Vec<_>, _>()?,
+            "time": time.to_json()?,
+            "description": metadata.description,
+            "hostname": metadata.hostname,
+            "username": metadata.username,
+            "is_snapshot": metadata.is_snapshot,
+            "tags": metadata.tags,
+        }))
+    }
+}
+
+impl ToJson for OperationId {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.hex().into())
+    }
+}
+
 fn builtin_operation_id_methods() -> OperationTemplateBuildMethodFnMap<OperationId> {
     type L = OperationTemplateLanguage;
     // Not using maplit::hashmap!{} or custom declarative macro here because
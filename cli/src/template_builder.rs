@@ -47,6 +47,7 @@ use crate::templater::TemplatePropertyError;
 use crate::templater::TemplatePropertyExt as _;
 use crate::templater::TemplateRenderer;
 use crate::templater::TimestampRange;
+use crate::templater::ToJson;
 use crate::text_util;
 use crate::time_util;
 
@@ -154,6 +155,9 @@ pub trait IntoTemplateProperty<'a> {
 
     fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<Output = String> + 'a>>;
     fn try_into_template(self) -> Option<Box<dyn Template + 'a>>;
+
+    /// Converts to a property of JSON value to be serialized by `json()`.
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>>;
 }
 
 pub enum CoreTemplatePropertyKind<'a> {
@@ -258,6 +262,48 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::ListTemplate(template) => Some(template.into_template()),
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        fn to_json<T: ToJson>(value: T) -> Result<serde_json::Value, TemplatePropertyError> {
+            value.to_json()
+        }
+        match self {
+            CoreTemplatePropertyKind::String(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::StringList(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::Boolean(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::Integer(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::IntegerOpt(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::Signature(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::SizeHint(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::Timestamp(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::TimestampRange(property) => {
+                Some(Box::new(property.and_then(to_json)))
+            }
+            // Formatted templates are serialized as plain text, and lists of
+            // them as arrays of plain text.
+            CoreTemplatePropertyKind::Template(_) => {
+                let property = self.try_into_plain_text()?;
+                Some(Box::new(property.and_then(to_json)))
+            }
+            CoreTemplatePropertyKind::ListTemplate(template) => Some(template.into_json()),
+        }
+    }
 }
 
 /// Function that translates global function call node.
@@ -477,6 +523,12 @@ impl<'a, P: IntoTemplateProperty<'a>> Expression<P> {
         self.property.try_into_plain_text()
     }
 
+    pub fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        self.property.try_into_json()
+    }
+
     pub fn try_into_template(self) -> Option<Box<dyn Template + 'a>> {
         let template = self.property.try_into_template()?;
         if self.labels.is_empty() {
@@ -1007,6 +1059,12 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
         });
         Ok(L::wrap_template(Box::new(template)))
     });
+    map.insert("json", |language, build_ctx, function| {
+        let [content_node] = function.expect_exact_arguments()?;
+        let content = expect_json_expression(language, build_ctx, content_node)?;
+        let out_property = content.map(|value| value.to_string());
+        Ok(L::wrap_string(out_property))
+    });
     map.insert("label", |language, build_ctx, function| {
         let [label_node, content_node] = function.expect_exact_arguments()?;
         let label_property = expect_plain_text_expression(language, build_ctx, label_node)?;
@@ -1232,6 +1290,18 @@ pub fn expect_plain_text_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
     })
 }
 
+/// If the given expression `node` can be serialized, converts it to a JSON
+/// value property.
+pub fn expect_json_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
+    language: &L,
+    build_ctx: &BuildContext<L::Property>,
+    node: &ExpressionNode,
+) -> TemplateParseResult<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+    expect_expression_of_type(language, build_ctx, node, "Serialize", |expression| {
+        expression.try_into_json()
+    })
+}
+
 pub fn expect_template_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
     language: &L,
     build_ctx: &BuildContext<L::Property>,
@@ -2147,6 +2217,44 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_json_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("author", || {
+            L::wrap_signature(Literal(new_signature("Test User", "test.user@example.com")))
+        });
+        env.add_keyword("t0", || L::wrap_timestamp(Literal(new_timestamp(0, 540))));
+        env.add_keyword("none_i64", || L::wrap_integer_opt(Literal(None)));
+        env.add_keyword("words", || {
+            L::wrap_string_list(Literal(vec!["foo".to_owned(), "bar".to_owned()]))
+        });
+
+        insta::assert_snapshot!(env.render_ok(r#"json("a \"quoted\"\nline")"#), @r#""a \"quoted\"\nline""#);
+        insta::assert_snapshot!(env.render_ok(r#"json(-1)"#), @"-1");
+        insta::assert_snapshot!(env.render_ok(r#"json(none_i64)"#), @"null");
+        insta::assert_snapshot!(env.render_ok(r#"json(true)"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"json(words)"#), @r#"["foo","bar"]"#);
+        insta::assert_snapshot!(env.render_ok(r#"json(t0)"#), @r#""1970-01-01T09:00:00.000+09:00""#);
+        insta::assert_snapshot!(
+            env.render_ok(r#"json(author)"#),
+            @r#"{"email":"test.user@example.com","name":"Test User","timestamp":"1970-01-01T00:00:00.000+00:00"}"#);
+        insta::assert_snapshot!(
+            env.render_ok(r#"json(words.map(|w| w.upper()))"#),
+            @r#"["FOO","BAR"]"#);
+        insta::assert_snapshot!(
+            env.render_ok(r#"json(words.map(|w| w.upper()).join(","))"#),
+            @r#""FOO,BAR""#);
+
+        insta::assert_snapshot!(env.parse_err(r#"json()"#), @r###"
+         --> 1:6
+          |
+        1 | json()
+          |      ^
+          |
+          = Function "json": Expected 1 arguments
+        "###);
+    }
+
     #[test]
     fn test_label_function() {
         let mut env = TestTemplateEnv::new();
@@ -33,6 +33,11 @@ pub trait Template {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()>;
 }
 
+/// Value that can be serialized by the `json()` function.
+pub trait ToJson {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError>;
+}
+
 /// Template that supports list-like behavior.
 pub trait ListTemplate: Template {
     /// Concatenates items with the given separator.
@@ -44,6 +49,12 @@ pub trait ListTemplate: Template {
     fn into_template<'a>(self: Box<Self>) -> Box<dyn Template + 'a>
     where
         Self: 'a;
+
+    /// Converts to a property of JSON array, each item of which is formatted
+    /// as plain text.
+    fn into_json<'a>(self: Box<Self>) -> Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>
+    where
+        Self: 'a;
 }
 
 impl<T: Template + ?Sized> Template for &T {
@@ -156,6 +167,69 @@ impl Template for i64 {
     }
 }
 
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        self.as_ref()
+            .map_or(Ok(serde_json::Value::Null), |t| t.to_json())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        self.iter().map(|t| t.to_json()).collect()
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok((*self).into())
+    }
+}
+
+impl ToJson for i64 {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok((*self).into())
+    }
+}
+
+impl ToJson for Signature {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "name": self.name,
+            "email": self.email,
+            "timestamp": self.timestamp.to_json()?,
+        }))
+    }
+}
+
+impl ToJson for SizeHint {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let (lower, upper) = *self;
+        Ok(serde_json::json!({ "lower": lower, "upper": upper }))
+    }
+}
+
+impl ToJson for Timestamp {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(time_util::format_rfc3339_timestamp(self)?.into())
+    }
+}
+
+impl ToJson for TimestampRange {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "start": self.start.to_json()?,
+            "end": self.end.to_json()?,
+        }))
+    }
+}
+
 pub struct LabelTemplate<T, L> {
     content: T,
     labels: L,
@@ -519,6 +593,29 @@ where
     {
         self
     }
+
+    fn into_json<'a>(self: Box<Self>) -> Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>
+    where
+        Self: 'a,
+    {
+        let ListPropertyTemplate {
+            property,
+            separator: _,
+            format_item,
+        } = *self;
+        Box::new(property.and_then(move |contents| {
+            let mut items = vec![];
+            for item in contents {
+                let mut output = vec![];
+                let mut formatter = PlainTextFormatter::new(&mut output);
+                let mut wrapper = TemplateFormatter::new(&mut formatter, propagate_property_error);
+                format_item(&mut wrapper, item)?;
+                let text = String::from_utf8(output).map_err(|err| err.utf8_error())?;
+                items.push(serde_json::Value::String(text));
+            }
+            Ok(serde_json::Value::Array(items))
+        }))
+    }
 }
 
 pub struct ConditionalTemplate<P, T, U> {
@@ -9,10 +9,13 @@ fn test_no_forgotten_test_files() {
 }
 
 mod test_abandon_command;
+mod test_absorb_command;
 mod test_acls;
 mod test_advance_bookmarks;
 mod test_alias;
+mod test_apply_command;
 mod test_backout_command;
+mod test_bisect_command;
 mod test_bookmark_command;
 mod test_builtin_aliases;
 mod test_checkout;
@@ -28,7 +31,10 @@ mod test_diffedit_command;
 mod test_duplicate_command;
 mod test_edit_command;
 mod test_evolog_command;
+mod test_export_patch_command;
+mod test_file_annotate_command;
 mod test_file_chmod_command;
+mod test_file_list_command;
 mod test_file_print_command;
 mod test_file_track_untrack_commands;
 mod test_fix_command;
@@ -44,6 +50,7 @@ mod test_git_remotes;
 mod test_git_submodule;
 mod test_gitignores;
 mod test_global_opts;
+mod test_histedit_command;
 mod test_immutable_commits;
 mod test_init_command;
 mod test_interdiff_command;
@@ -53,18 +60,22 @@ mod test_new_command;
 mod test_next_prev_commands;
 mod test_operations;
 mod test_parallelize_command;
+mod test_range_diff_command;
 mod test_rebase_command;
 mod test_repo_change_report;
 mod test_resolve_command;
 mod test_restore_command;
+mod test_revert_command;
 mod test_revset_output;
 mod test_root;
+mod test_run_command;
 mod test_shell_completion;
 mod test_show_command;
 mod test_sparse_command;
 mod test_split_command;
 mod test_squash_command;
 mod test_status_command;
+mod test_sync_command;
 mod test_tag_command;
 mod test_templater;
 mod test_undo;
@@ -0,0 +1,176 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn get_diffs(test_env: &TestEnvironment, repo_path: &Path, revision: &str) -> String {
+    let template = r#"description.first_line() ++ "\n""#;
+    test_env.jj_cmd_success(
+        repo_path,
+        &["log", "-r", revision, "-T", template, "--no-graph", "--git"],
+    )
+}
+
+#[test]
+fn test_absorb_simple() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=1"]);
+    std::fs::write(repo_path.join("file1"), "1a\n1b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=2"]);
+    std::fs::write(repo_path.join("file1"), "1a\n1b\n2a\n2b\n").unwrap();
+
+    // Nothing to absorb
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Nothing changed.");
+
+    // Each hunk goes to the revision that last modified its lines. The
+    // insertion between lines from different revisions is ambiguous, and the
+    // added file isn't absorbed.
+    std::fs::write(repo_path.join("file1"), "1A\n1b\nmiddle\n2a\n2B\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "new\n").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Warning: file1: 1 hunk(s) couldn't be attributed to a single revision and were left in place
+    Warning: file2: Added files can't be absorbed
+    Absorbed changes into these revisions:
+      kkmpptxz 931a8ceb 2
+      qpvuntsm 7f75cc50 1
+    Rebased 1 descendant commits.
+    Working copy now at: zsuskuln cc9e9dd9 (no description set)
+    Parent commit      : kkmpptxz 931a8ceb 2
+    "###);
+    insta::assert_snapshot!(get_diffs(&test_env, &repo_path, "::@ ~ root()"), @r###"
+
+    diff --git a/file1 b/file1
+    index 428796ca20..a8b55035a4 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,4 +1,5 @@
+     1A
+     1b
+    +middle
+     2a
+     2B
+    diff --git a/file2 b/file2
+    new file mode 100644
+    index 0000000000..3e757656cf
+    --- /dev/null
+    +++ b/file2
+    @@ -1,0 +1,1 @@
+    +new
+    2
+    diff --git a/file1 b/file1
+    index 7860a02b65..428796ca20 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,2 +1,4 @@
+     1A
+     1b
+    +2a
+    +2B
+    1
+    diff --git a/file1 b/file1
+    new file mode 100644
+    index 0000000000..7860a02b65
+    --- /dev/null
+    +++ b/file1
+    @@ -1,0 +1,2 @@
+    +1A
+    +1b
+    "###);
+}
+
+#[test]
+fn test_absorb_from_into_paths() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=1"]);
+    std::fs::write(repo_path.join("file1"), "1a\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "1a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=2"]);
+    std::fs::write(repo_path.join("file1"), "1A\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "1A\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=3"]);
+
+    // Only the selected paths are absorbed, and descendants of the
+    // destination are rebased
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["absorb", "--from=description(2)", "file1"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Absorbed changes into these revisions:
+      qpvuntsm ae044adb 1
+    Rebased 2 descendant commits.
+    Working copy now at: zsuskuln 7468cbd7 (empty) 3
+    Parent commit      : kkmpptxz 76459277 2
+    "###);
+    insta::assert_snapshot!(get_diffs(&test_env, &repo_path, "::@ ~ root()"), @r###"
+    3
+    2
+    diff --git a/file2 b/file2
+    index a8994dc188..268de3f3ec 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,1 +1,1 @@
+    -1a
+    +1A
+    1
+    diff --git a/file1 b/file1
+    new file mode 100644
+    index 0000000000..268de3f3ec
+    --- /dev/null
+    +++ b/file1
+    @@ -1,0 +1,1 @@
+    +1A
+    diff --git a/file2 b/file2
+    new file mode 100644
+    index 0000000000..a8994dc188
+    --- /dev/null
+    +++ b/file2
+    @@ -1,0 +1,1 @@
+    +1a
+    "###);
+
+    // Revisions outside of the destination set aren't modified
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["absorb", "--from=description(2)", "--into=none()"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Warning: file2: 1 hunk(s) couldn't be attributed to a single revision and were left in place
+    Nothing changed.
+    "###);
+
+    // Immutable revisions can't be rewritten
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "description(1)""#);
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["absorb", "--from=description(2)", "--into=all()"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Commit ae044adb3893 is immutable
+    Hint: Pass `--ignore-immutable` or configure the set of immutable commits via `revset-aliases.immutable_heads()`.
+    "###);
+}
@@ -0,0 +1,198 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path, revisions: &str) -> String {
+    let template = r#"separate(" ", change_id.short(), author, description) ++ "\n""#;
+    test_env.jj_cmd_success(
+        repo_path,
+        &["log", "-r", revisions, "-T", template, "--summary"],
+    )
+}
+
+#[test]
+fn test_apply_exported_patches() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+    std::fs::write(repo_path.join("binary"), b"\0\x01\x02").unwrap();
+    test_env
+        .jj_cmd(
+            &repo_path,
+            &[
+                "describe",
+                "--reset-author",
+                "-m",
+                "Add some files\n\nWith a body.\n---\nFrom a line that looks like a separator.",
+            ],
+        )
+        .env("JJ_USER", "Some One")
+        .env("JJ_EMAIL", "some.one@example.com")
+        .assert()
+        .success();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::rename(repo_path.join("file"), repo_path.join("renamed")).unwrap();
+    std::fs::write(repo_path.join("renamed"), "a\nc\n").unwrap();
+    std::fs::write(repo_path.join("binary"), b"\0\x01\x03").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "renamed"]);
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "Change the files"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["export-patch", "-r", "::@- ~ root()", "-o", "../patches"],
+    );
+
+    // The patches recreate the same changes with the same metadata
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "apply",
+            "-d",
+            "root()",
+            "../patches/0001-Add-some-files.patch",
+            "../patches/0002-Change-the-files.patch",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Applied 2 patches:
+      yqosqzyt 5a4d19a5 Add some files
+      spxsnpux 5fa8371c Change the files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path, "all()"), @r###"
+    ○  spxsnpuxtvxq Test User <test.user@example.com> Change the files
+    │
+    │  M binary
+    │  R {file => renamed}
+    ○  yqosqzytrlsw Some One <some.one@example.com> Add some files
+    │
+    │  With a body.
+    │  ---
+    │  From a line that looks like a separator.
+    │
+    │  A binary
+    │  A file
+    │ @  mzvwutvlkqwt Test User <test.user@example.com>
+    │ ○  kkmpptxzrspx Test User <test.user@example.com> Change the files
+    │ │
+    │ │  M binary
+    │ │  R {file => renamed}
+    │ ○  qpvuntsmwlqt Some One <some.one@example.com> Add some files
+    ├─╯
+    │    With a body.
+    │    ---
+    │    From a line that looks like a separator.
+    │
+    │    A binary
+    │    A file
+    ◆  zzzzzzzzzzzz
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--from", "description(Change) & ~@-", "--to", "@-"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+}
+
+#[test]
+fn test_apply_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\nb\nc\nd\ne\nf\ng\nchanged\ni\n").unwrap();
+
+    // A plain unified diff. The first hunk applies after lines were added
+    // above it, and the second hunk is recorded as a conflict.
+    std::fs::write(
+        test_env.env_root().join("diff"),
+        "--- file\n+++ file\n@@ -1,2 +1,2 @@\n-b\n+B\n c\n@@ -6,3 +6,3 @@\n g\n-h\n+H\n i\n",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["apply", "../diff"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Applied 1 patches:
+      rlvkpnrz dd2fe6d3 (conflict) (no description set)
+    Warning: Some hunks couldn't be applied and were recorded as conflicts
+    New conflicts appeared in these commits:
+      rlvkpnrz dd2fe6d3 (conflict) (no description set)
+    To resolve the conflicts, start by updating to it:
+      jj new rlvkpnrzqnoo
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path, "@+"), @r###"
+    ×  rlvkpnrzqnoo Test User <test.user@example.com>
+    │  M file
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "@+", "file"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    B
+    c
+    d
+    e
+    f
+    g
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    changed
+    %%%%%%% Changes from base to side #2
+    -h
+    +H
+    >>>>>>> Conflict 1 of 1 ends
+    i
+    "###);
+}
+
+#[test]
+fn test_apply_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["apply", "missing"]);
+    insta::assert_snapshot!(stderr.replace("(os error 2)", "").trim_end(), @r###"
+    Error: Failed to read missing
+    Caused by: No such file or directory
+    "###);
+
+    std::fs::write(
+        test_env.env_root().join("diff"),
+        "--- a/file\n+++ b/file\n@@ -1 +1 @@\n-a\n+b\n",
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["apply", "../diff"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to apply patch
+    Caused by: Path 'file' doesn't exist
+    "###);
+
+    std::fs::write(
+        test_env.env_root().join("diff"),
+        "--- a/file\n+++ b/file\n@@ -1 +1 @@\nbad\n",
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["apply", "../diff"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse ../diff
+    Caused by: Line 4: Unexpected line in hunk
+    "###);
+}
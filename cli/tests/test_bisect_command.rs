@@ -0,0 +1,210 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+/// Creates a linear history `a` to `f`, where each commit adds a file named
+/// after it.
+fn init_repo(test_env: &TestEnvironment) -> std::path::PathBuf {
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    for name in ["a", "b", "c", "d", "e", "f"] {
+        std::fs::write(repo_path.join(name), format!("{name}\n")).unwrap();
+        test_env.jj_cmd_ok(&repo_path, &["commit", "-m", name]);
+    }
+    repo_path
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    test_env.jj_cmd_success(repo_path, &["log", "-T", "description.first_line()"])
+}
+
+#[test]
+fn test_bisect() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+
+    // "d" introduced the bug
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--good",
+            "description(a)",
+            "--bad",
+            "description(f)",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Bisecting: 4 revisions left to test
+    Now testing: zsuskuln 3af9db12 d
+    Working copy now at: vruxwmqv 0be57d26 (empty) (no description set)
+    Parent commit      : zsuskuln 3af9db12 d
+    Added 0 files, modified 0 files, removed 2 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @
+    │ ○  f
+    │ ○  e
+    ├─╯
+    ○  d
+    ○  c
+    ○  b
+    ○  a
+    ◆
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "bad"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Bisecting: 2 revisions left to test
+    Now testing: kkmpptxz 4f7d8e73 c
+    Working copy now at: znkkpsqq 732f3133 (empty) (no description set)
+    Parent commit      : kkmpptxz 4f7d8e73 c
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+
+    // The bisection state is restored by undo
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "bad", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Bisecting: 2 revisions left to test
+    Now testing: kkmpptxz 4f7d8e73 c
+    Working copy now at: kmkuslsw d4d9d5a6 (empty) (no description set)
+    Parent commit      : kkmpptxz 4f7d8e73 c
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "good"]);
+    insta::assert_snapshot!(stdout, @"The first bad revision is: zsuskuln 3af9db12 d");
+    insta::assert_snapshot!(stderr, @"");
+
+    // Can't start another bisection before ending the current one
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["bisect", "start", "--good", "root()", "--bad", "@"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: A bisection is already in progress
+    Hint: Use `jj bisect reset` to end it first.
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "reset"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["bisect", "good"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    "###);
+}
+
+#[test]
+fn test_bisect_skip() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--good",
+            "description(b)",
+            "--bad",
+            "description(e)",
+        ],
+    );
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "skip"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Bisecting: 1 revisions left to test
+    Now testing: kkmpptxz 4f7d8e73 c
+    Working copy now at: yostqsxw e7e3b604 (empty) (no description set)
+    Parent commit      : kkmpptxz 4f7d8e73 c
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "skip"]);
+    insta::assert_snapshot!(stdout, @r###"
+    The first bad revision could be any of:
+      mzvwutvl eed9e9c6 e
+      zsuskuln 3af9db12 d
+      kkmpptxz 4f7d8e73 c
+    "###);
+    insta::assert_snapshot!(stderr, @"");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_bisect_run() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["bisect", "run", "true"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    "###);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--good",
+            "description(a)",
+            "--bad",
+            "description(f)",
+        ],
+    );
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["bisect", "run", "echo testing; test ! -e d"]);
+    insta::assert_snapshot!(stdout, @r###"
+    testing
+    testing
+    The first bad revision is: zsuskuln 3af9db12 d
+    "###);
+    insta::assert_snapshot!(stderr, @r###"
+    Bisecting: 4 revisions left to test
+    Marked as bad: zsuskuln 3af9db12 d
+    Bisecting: 2 revisions left to test
+    Marked as good: kkmpptxz 4f7d8e73 c
+    "###);
+
+    // The working copy isn't touched by the command
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @
+    │ ○  f
+    │ ○  e
+    ├─╯
+    ○  d
+    ○  c
+    ○  b
+    ○  a
+    ◆
+    "###);
+
+    // Unexpected exit statuses stop the bisection
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["bisect", "run", "exit 200"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Bisecting: 2 revisions left to test
+    Error: The command exited with status 200, stopping the bisection
+    "###);
+}
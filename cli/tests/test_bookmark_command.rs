@@ -1825,6 +1825,48 @@ fn test_bookmark_list_conflicted() {
     "###);
 }
 
+#[test]
+fn test_bookmark_list_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "b"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "bar"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["bookmark", "create", "foo", "-r", "description(a)"],
+    );
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bookmark",
+            "create",
+            "foo",
+            "-r",
+            "description(b)",
+            "--at-op=@-",
+        ],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "bookmark",
+            "list",
+            "--all-remotes",
+            "-T",
+            r#"json(self) ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    {"conflict":false,"name":"bar","present":true,"remote":null,"synced":true,"targets":["06a973bcb57fc824c8214f55c07436ec9197dd33"],"tracked":false}
+    {"conflict":false,"name":"bar","present":true,"remote":"git","synced":true,"targets":["06a973bcb57fc824c8214f55c07436ec9197dd33"],"tracked":true}
+    {"conflict":true,"name":"foo","present":true,"remote":null,"synced":true,"targets":["d8d5f980a897bec1a085986377897c00e531ebce","06a973bcb57fc824c8214f55c07436ec9197dd33"],"tracked":false}
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
     let template = r#"bookmarks ++ " " ++ commit_id.short()"#;
     test_env.jj_cmd_success(cwd, &["log", "-T", template])
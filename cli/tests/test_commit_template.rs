@@ -1136,4 +1136,105 @@ fn test_log_diff_predefined_formats() {
      b
     +c
     "###);
+
+    // diff() with line diff options
+    let template = "self.diff('file1', 'myers ignore-all-space').git(0)";
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file1 b/file1
+    index 422c2b7ab3..de980441c3 100644
+    --- a/file1
+    +++ b/file1
+    @@ -3,0 +3,1 @@
+    +c
+    "###);
+
+    let template = "self.diff('file1', 'ignore-everything')";
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template: Invalid diff option: ignore-everything
+    Caused by:  --> 1:20
+      |
+    1 | self.diff('file1', 'ignore-everything')
+      |                    ^-----------------^
+      |
+      = Invalid diff option: ignore-everything
+    "###);
+}
+
+#[test]
+fn test_log_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\nb\n").unwrap();
+    std::fs::write(repo_path.join("rename-source"), "rename").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first \"quoted\"\n\nbody"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@-", "main"]);
+    std::fs::write(repo_path.join("file1"), "a\nc\nd\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "new\n").unwrap();
+    std::fs::rename(
+        repo_path.join("rename-source"),
+        repo_path.join("rename-target"),
+    )
+    .unwrap();
+
+    let render =
+        |template| test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(render(r#"json(self) ++ "\n""#), @r###"
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:10.000+07:00"},"change_id":"rlvkpnrzqnoowoytxnquwvuryrwnrmlp","commit_id":"78b276945ec738df161447bcb3c31e3390f7b810","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:10.000+07:00"},"description":"","parents":["40819334217efc09021a9ed9208c0bd8260a3164"]}
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08.000+07:00"},"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"40819334217efc09021a9ed9208c0bd8260a3164","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08.000+07:00"},"description":"first \"quoted\"\n\nbody\n","parents":["0000000000000000000000000000000000000000"]}
+    {"author":{"email":"","name":"","timestamp":"1970-01-01T00:00:00.000+00:00"},"change_id":"zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz","commit_id":"0000000000000000000000000000000000000000","committer":{"email":"","name":"","timestamp":"1970-01-01T00:00:00.000+00:00"},"description":"","parents":[]}
+    "###);
+    insta::assert_snapshot!(render(r#"json(diff) ++ "\n""#), @r###"
+    [{"added":2,"path":"file1","removed":1,"source":null,"status":"modified"},{"added":1,"path":"file2","removed":0,"source":null,"status":"added"},{"added":0,"path":"rename-target","removed":0,"source":"rename-source","status":"renamed"}]
+    [{"added":2,"path":"file1","removed":0,"source":null,"status":"added"},{"added":1,"path":"rename-source","removed":0,"source":null,"status":"added"}]
+    []
+    "###);
+    insta::assert_snapshot!(
+        render(r#"json(bookmarks) ++ " " ++ json(change_id.shortest(4)) ++ "\n""#), @r###"
+    [] {"prefix":"r","rest":"lvk"}
+    [{"conflict":false,"name":"main","present":true,"remote":null,"synced":true,"targets":["40819334217efc09021a9ed9208c0bd8260a3164"],"tracked":false}] {"prefix":"q","rest":"pvu"}
+    [] {"prefix":"z","rest":"zzz"}
+    "###);
+    insta::assert_snapshot!(render(r#"json(parents.map(|c| c.commit_id().short())) ++ "\n""#), @r###"
+    ["40819334217e"]
+    ["000000000000"]
+    []
+    "###);
+}
+
+#[test]
+fn test_log_signature() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "unsigned"]);
+
+    let render =
+        |template| test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(
+        render(r#"if(signature, signature.status(), "no signature") ++ "\n""#), @r###"
+    no signature
+    no signature
+    no signature
+    "###);
+    insta::assert_snapshot!(render(r#"json(signature) ++ "\n""#), @r###"
+    null
+    null
+    null
+    "###);
+
+    // Unsigned commits don't match any signature predicate.
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-r", "signed() | good_signature()"]),
+        @"");
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-r", "all() ~ signed_by(test)", "-T", "description"]),
+        @r###"
+    @
+    ○  unsigned
+    ◆
+    "###);
 }
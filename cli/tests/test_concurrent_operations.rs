@@ -34,18 +34,18 @@ fn test_concurrent_operation_divergence() {
     let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "log", "--at-op=@"]);
     insta::assert_snapshot!(stderr, @r###"
     Error: The "@" expression resolved to more than one operation
-    Hint: Try specifying one of the operations by ID: e31015019d90, 48f4a48f3f70
+    Hint: Try specifying one of the operations by ID: b203c8839f8f, 232bfbdfe9ec
     "###);
 
     // "op log --at-op" should work without merging the head operations
-    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--at-op=48f4a48f3f70"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--at-op=232bfbdfe9ec"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  48f4a48f3f70 test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
+    @  232bfbdfe9ec test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
     │  describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
     │  args: jj describe -m 'message 2' --at-op @-
-    ○  b51416386f26 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  401227d3f4b4 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
-    ○  9a7d829846af test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  cf7914c575b6 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  initialize repo
     ○  000000000000 root()
     "###);
@@ -73,15 +73,15 @@ fn test_concurrent_operations_auto_rebase() {
     test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "initial"]);
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  66d1dd775c54 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    @  0db25de648cd test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  describe commit 4e8f9d2be039994f589b4e57ac5e9488703e604d
     │  args: jj describe -m initial
-    ○  130d67859810 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    ○  850d6166e261 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  snapshot working copy
     │  args: jj describe -m initial
-    ○  b51416386f26 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  401227d3f4b4 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
-    ○  9a7d829846af test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  cf7914c575b6 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  initialize repo
     ○  000000000000 root()
     "###);
@@ -188,24 +188,23 @@ fn test_concurrent_snapshot_wc_reloadable() {
     let template = r#"id ++ "\n" ++ description ++ "\n" ++ tags"#;
     let op_log_stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", template]);
     insta::assert_snapshot!(op_log_stdout, @r###"
-    @  9f11958bcf79340028eeabf9b0381cd8d2ae2258d0097b8ce8bd24fe7138eca08d9eb113bb4722ebacd9b7a6fa017e3888f72907be7487f275823c8d21359eed
+    @  702196d62a2937db3e72ed6d466892a753d54ea5535046192b48c2789b47d68b04e89411a722f06d2171775ebac82412090950895998cd59097da0a2433eff68
     │  commit 554d22b2c43c1c47e279430197363e8daabe2fd6
     │  args: jj commit -m 'new child1'
-    ○  f5460e8f43a04fbc61553d12fa5ba8d3b12e4fdcfda1999db6b67cc8e1e473b7e62cc0536196a53b84f34e18c1c6d608f427bb64bd5f834f845a9859e39cb320
+    ○  6877c6102c114245b1d71033b12399ba02dd7e59e2bd46775a374672ecb9e8aba36266422995c27fef1077d83d67be1df0269a0ae1172005a19a4907477ee46d
     │  snapshot working copy
     │  args: jj commit -m 'new child1'
-    ○  49359b6597ead3fbb66802a6bbd8761c0ad4646a2b089090d6fd72fb6e2568aa99c4a92f9f1f252a83cce56ec84961c36e85f731f19fc5a4c24d6a3f7282b774
+    ○  06a0515118284bf4c2fadfa63926d5d843d2e525a9f4cddfa2d028735d932206b9b5a66202de341b7eeb7b58694f0c0501deea5879119e86f13dcbbceb0d682a
     │  commit de71e09289762a65f80bb1c3dae2a949df6bcde7
     │  args: jj commit -m initial
-    ○  86dbba2b96a4a801abef7f77f8fdf338b6e36f81ea4a531aacf06acbd06f4037731fffef42503c2225fdb206488971c1601ca8b2b4a83a3fe2dce64ee4db085e
+    ○  c7fd1f4af4cc8d8036a426cf1afeab57d04f319b1edb9dae1c518ef25cd0f936057949f3a00618a91e3573aef1179dd58745a71af73ae43b575051472369db8f
     │  snapshot working copy
     │  args: jj commit -m initial
-    ○  b51416386f2685fd5493f2b20e8eec3c24a1776d9e1a7cb5ed7e30d2d9c88c0c1e1fe71b0b7358cba60de42533d1228ed9878f2f89817d892c803395ccf9fe92
+    ○  401227d3f4b407917c8a3eed5196e61a32059e9819f024b81f8c9d7dd2d5170a902dcaa0c5af43beb466f503e5d7b7b9d112d2cc1d17c7cea28f09dd0bbeea04
     │  add workspace 'default'
-    ○  9a7d829846af88a2f7a1e348fb46ff58729e49632bc9c6a052aec8501563cb0d10f4a4e6010ffde529f84a2b9b5b3a4c211a889106a41f6c076dfdacc79f6af7
+    ○  cf7914c575b6ad055ff631ee8a248c9bf2143a2812e78ddeae8e0203a85adcedbd99a81cac9d8650f291391875b9d138d719e8f5f240c9f52f564f12c07f965a
     │  initialize repo
     ○  00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000
-
     "###);
     let op_log_lines = op_log_stdout.lines().collect_vec();
     let current_op_id = op_log_lines[0].split_once("  ").unwrap().1;
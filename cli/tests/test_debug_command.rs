@@ -32,6 +32,17 @@ fn test_debug_fileset() {
     EverythingMatcher
     "###);
 
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "fileset", "binary()"]);
+    assert_snapshot!(stdout, @r###"
+    -- Parsed:
+    Predicate(
+        Binary,
+    )
+
+    -- Matcher:
+    File predicates such as `binary()` can't be evaluated without trees
+    "###);
+
     let stderr = test_env.jj_cmd_failure(&workspace_path, &["debug", "fileset", "cwd:.."]);
     assert_snapshot!(stderr.replace('\\', "/"), @r###"
     Error: Failed to parse fileset: Invalid file pattern
@@ -235,12 +246,61 @@ fn test_debug_operation_id() {
     let workspace_path = test_env.env_root().join("repo");
     let stdout =
         test_env.jj_cmd_success(&workspace_path, &["debug", "operation", "--display", "id"]);
-    assert_snapshot!(filter_index_stats(&stdout), @r###"
-    b51416386f2685fd5493f2b20e8eec3c24a1776d9e1a7cb5ed7e30d2d9c88c0c1e1fe71b0b7358cba60de42533d1228ed9878f2f89817d892c803395ccf9fe92
-    "###
+    assert_snapshot!(filter_index_stats(&stdout), @"401227d3f4b407917c8a3eed5196e61a32059e9819f024b81f8c9d7dd2d5170a902dcaa0c5af43beb466f503e5d7b7b9d112d2cc1d17c7cea28f09dd0bbeea04"
     );
 }
 
+#[cfg(target_os = "linux")]
+#[test]
+fn test_debug_fsmonitor_inotify() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"core.fsmonitor = "inotify""#);
+
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "fsmonitor", "status"]);
+    assert_snapshot!(stdout, @r###"
+    The inotify filesystem monitor is enabled via `core.fsmonitor`.
+    The inotify daemon is not running.
+    "###);
+
+    // Snapshotting starts the daemon
+    test_env.jj_cmd_ok(&workspace_path, &["status"]);
+    std::fs::write(workspace_path.join("file"), "contents").unwrap();
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "fsmonitor", "status"]);
+    assert_snapshot!(stdout, @r###"
+    The inotify filesystem monitor is enabled via `core.fsmonitor`.
+    The inotify daemon is running.
+    Paths changed since the last snapshot:
+      file
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["status"]);
+    assert_snapshot!(stdout, @r###"
+    Working copy changes:
+    A file
+    Working copy : qpvuntsm dd6d0afd (no description set)
+    Parent commit: zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "fsmonitor", "status"]);
+    assert_snapshot!(stdout, @r###"
+    The inotify filesystem monitor is enabled via `core.fsmonitor`.
+    The inotify daemon is running.
+    No paths changed since the last snapshot.
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_path, &["debug", "fsmonitor", "stop"]);
+    assert_snapshot!(stdout, @"");
+    assert_snapshot!(stderr, @r###"
+    Stopped the inotify daemon
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "fsmonitor", "status"]);
+    assert_snapshot!(stdout, @r###"
+    The inotify filesystem monitor is enabled via `core.fsmonitor`.
+    The inotify daemon is not running.
+    "###);
+}
+
 fn filter_index_stats(text: &str) -> String {
     let regex = Regex::new(r"    Name: [0-9a-z]+").unwrap();
     regex.replace_all(text, "    Name: [hash]").to_string()
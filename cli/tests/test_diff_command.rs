@@ -222,6 +222,40 @@ fn test_diff_basic() {
     insta::assert_snapshot!(stderr, @"");
 }
 
+#[test]
+fn test_diff_copies_native_backend() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"ui.allow-init-native = true"#);
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "1\n2\n3\n4\n").unwrap();
+    std::fs::write(repo_path.join("file3"), "a\nb\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::rename(repo_path.join("file1"), repo_path.join("file4")).unwrap();
+    std::fs::remove_file(repo_path.join("file2")).unwrap();
+    std::fs::write(repo_path.join("file5"), "1\n2\n3\n5\n").unwrap();
+    // Copies of unchanged files aren't detected
+    std::fs::write(repo_path.join("file6"), "a\nb\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    R {file1 => file4}
+    R {file2 => file5}
+    A file6
+    "###);
+
+    // The copies are recorded with the commit
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @r###"
+    R {file1 => file4}
+    R {file2 => file5}
+    A file6
+    "###);
+}
+
 #[test]
 fn test_diff_empty() {
     let test_env = TestEnvironment::default();
@@ -1832,6 +1866,381 @@ fn test_diff_leading_trailing_context() {
     "###);
 }
 
+#[test]
+fn test_diff_ignore_whitespace() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(
+        repo_path.join("file1"),
+        indoc! {"
+            foo {
+                bar;
+            }
+            baz {  }
+        "},
+    )
+    .unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(
+        repo_path.join("file1"),
+        indoc! {"
+            foo {
+                if cond {
+                    bar;
+                }
+            }
+            baz  {}  
+        "},
+    )
+    .unwrap();
+
+    // Git diff as reference
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file1 b/file1
+    index d33445991b..6b64008a1d 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,4 +1,6 @@
+     foo {
+    -    bar;
+    +    if cond {
+    +        bar;
+    +    }
+     }
+    -baz {  }
+    +baz  {}  
+    "###);
+
+    // Ignore all whitespace
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "-w"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file1 b/file1
+    index d33445991b..6b64008a1d 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,4 +1,6 @@
+     foo {
+    +    if cond {
+         bar;
+     }
+    +}
+     baz {  }
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--ignore-all-space"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Modified regular file file1:
+       1    1: foo {
+            2:     if cond {
+       2    3:     bar;
+       3    4: }
+            5: }
+       4    6: baz {  }
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--stat", "-w"]);
+    insta::assert_snapshot!(stdout, @r###"
+    file1 | 2 ++
+    1 file changed, 2 insertions(+), 0 deletions(-)
+    "###);
+
+    // Ignore whitespace changes
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "-b"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file1 b/file1
+    index d33445991b..6b64008a1d 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,4 +1,6 @@
+     foo {
+    +    if cond {
+         bar;
+    +    }
+     }
+    -baz {  }
+    +baz  {}  
+    "###);
+
+    // The two whitespace modes are exclusive
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["diff", "-w", "-b"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the argument '--ignore-all-space' cannot be used with '--ignore-space-change'
+
+    Usage: jj diff --ignore-all-space [PATHS]...
+
+    For more information, try '--help'.
+    "###);
+}
+
+#[test]
+fn test_diff_ignore_blank_lines() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "a\nb\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    // Blank line added far from other changes, and one next to a change
+    std::fs::write(
+        repo_path.join("file1"),
+        "a\n\nb\nc\nd\ne\nf\ng\nh\n\nI\nj\n",
+    )
+    .unwrap();
+    // Only blank lines changed
+    std::fs::write(repo_path.join("file2"), "a\n  \n\nb\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--ignore-blank-lines"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file1 b/file1
+    index 92dfa21641..96cd126655 100644
+    --- a/file1
+    +++ b/file1
+    @@ -6,5 +7,6 @@
+     f
+     g
+     h
+    -i
+    +
+    +I
+     j
+    diff --git a/file2 b/file2
+    index 422c2b7ab3..445931fe3d 100644
+    --- a/file2
+    +++ b/file2
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--ignore-blank-lines"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Modified regular file file1:
+       1    1: a
+        ...
+       6    7: f
+       7    8: g
+       8    9: h
+       9   10: i
+           11: I
+      10   12: j
+    Modified regular file file2:
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--stat", "--ignore-blank-lines"]);
+    insta::assert_snapshot!(stdout, @r###"
+    file1 | 3 ++-
+    file2 | 0
+    2 files changed, 2 insertions(+), 1 deletion(-)
+    "###);
+}
+
+#[test]
+fn test_diff_algorithm() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\nb\nc\na\nb\nb\na\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file1"), "c\nb\na\nb\na\nc\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file1 b/file1
+    index 4eca417796..5640cf335e 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,6 @@
+    -a
+    -b
+    -c
+    -a
+    -b
+    -b
+    -a
+    +c
+    +b
+    +a
+    +b
+    +a
+    +c
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--diff-algorithm=myers"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file1 b/file1
+    index 4eca417796..5640cf335e 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,6 @@
+    -a
+    +c
+     b
+    -c
+     a
+     b
+    -b
+     a
+    +c
+    "###);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--diff-algorithm=patience"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file1 b/file1
+    index 4eca417796..5640cf335e 100644
+    --- a/file1
+    +++ b/file1
+    @@ -1,7 +1,6 @@
+    -a
+    +c
+     b
+    -c
+     a
+     b
+    -b
+     a
+    +c
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["diff", "--diff-algorithm=foo"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value 'foo' for '--diff-algorithm <ALGORITHM>'
+      [possible values: histogram, patience, myers]
+
+    For more information, try '--help'.
+    "###);
+}
+
+#[test]
+fn test_diff_side_by_side() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_env_var("COLUMNS", "50");
+
+    std::fs::write(
+        repo_path.join("file1"),
+        "1\n2\n3\n4\n5\n6\n7\nfoo bar\nkeep\nremoved\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("file2"), "deleted\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(
+        repo_path.join("file1"),
+        "1\n2\n3\n4\n5\n6\n7\nfoo baz\nkeep\nadded\nthis line is too long to fit\n",
+    )
+    .unwrap();
+    std::fs::remove_file(repo_path.join("file2")).unwrap();
+    std::fs::write(repo_path.join("file3"), "\tindented\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--side-by-side"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Modified regular file file1:
+        ...
+       5 5                  |    5 5
+       6 6                  |    6 6
+       7 7                  |    7 7
+       8 foo bar            |    8 foo baz
+       9 keep               |    9 keep
+      10 removed            |   10 added
+                            |   11 this line is too l
+                            |      ong to fit
+    Removed regular file file2:
+       1 deleted            |
+    Added regular file file3:
+                            |    1     indented
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "diff",
+            "--side-by-side",
+            "--context=0",
+            "--color=always",
+            "file1",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    [38;5;3mModified regular file file1:[39m
+        ...
+    [38;5;1m   8[39m [38;5;1mfoo [4mbar[24m[39m            | [38;5;2m   8[39m [38;5;2mfoo [4mbaz[24m[39m
+    [38;5;1m   9[39m keep               | [38;5;2m   9[39m keep
+    [38;5;1m  10[39m [4m[38;5;1mremoved[24m[39m            | [38;5;2m  10[39m [4m[38;5;2madded[24m[39m
+                            | [38;5;2m  11[39m [4m[38;5;2mthis line is too l[24m[39m
+                            |      [4m[38;5;2mong to fit[24m[39m
+    "###);
+
+    // Narrow terminal
+    test_env.add_env_var("COLUMNS", "30");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["diff", "--side-by-side", "--context=1", "file1"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Modified regular file file1:
+        ...
+       7 7          |    7 7
+       8 foo bar    |    8 foo baz
+       9 keep       |    9 keep
+      10 removed    |   10 added
+                    |   11 this line 
+                    |      is too lon
+                    |      g to fit
+    "###);
+
+    // Default format
+    test_env.add_config(r#"ui.diff.format = "side-by-side""#);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description", "-p", "file3"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    │  Added regular file file3:
+    ~                  |    1     indent
+                       |      ed
+    "###);
+
+    let stderr =
+        test_env.jj_cmd_cli_error(&repo_path, &["diff", "--side-by-side", "--color-words"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the argument '--side-by-side' cannot be used with '--color-words'
+
+    Usage: jj diff --side-by-side [PATHS]...
+
+    For more information, try '--help'.
+    "###);
+}
+
+#[test]
+fn test_diff_side_by_side_options() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_env_var("COLUMNS", "50");
+
+    let mut content = (1..=10000).map(|i| format!("{i}\n")).collect::<String>();
+    content.push_str("foo  bar baz\n");
+    std::fs::write(repo_path.join("file1"), &content).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    content.truncate(content.len() - "foo  bar baz\n".len());
+    content.push_str("foo bar qux\n");
+    std::fs::write(repo_path.join("file1"), &content).unwrap();
+
+    // Whitespace changes aren't highlighted, and the line number column is
+    // wide enough for the largest line number
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "diff",
+            "--side-by-side",
+            "--context=1",
+            "--color=always",
+            "-b",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    [38;5;3mModified regular file file1:[39m
+        ...
+    [38;5;1m10000[39m 10000             | [38;5;2m10000[39m 10000
+    [38;5;1m10001[39m [38;5;1mfoo  bar [4mbaz[24m[39m      | [38;5;2m10001[39m [38;5;2mfoo  bar [4mqux[24m[39m
+    "###);
+}
+
 #[test]
 fn test_diff_external_tool() {
     let mut test_env = TestEnvironment::default();
@@ -2134,6 +2543,88 @@ fn test_diff_stat() {
     "###);
 }
 
+#[test]
+fn test_diff_stat_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(repo_path.join("dir/file1"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "foo\nbar\n").unwrap();
+    std::fs::write(repo_path.join("file3"), "baz\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("dir/file1"), "foo\nqux\n").unwrap();
+    std::fs::rename(repo_path.join("file2"), repo_path.join("file4")).unwrap();
+    std::fs::remove_file(repo_path.join("file3")).unwrap();
+
+    // Paths are relative to the workspace root regardless of the cwd
+    let stdout = test_env.jj_cmd_success(&repo_path.join("dir"), &["diff", "--json"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"added":1,"path":"dir/file1","removed":0,"source":null,"status":"modified"}
+    {"added":0,"path":"file3","removed":1,"source":null,"status":"removed"}
+    {"added":0,"path":"file4","removed":0,"source":"file2","status":"renamed"}
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--json", "file3"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"added":0,"path":"file3","removed":1,"source":null,"status":"removed"}
+    "###);
+
+    // The JSON lines aren't prefixed by a graph, and the template output is
+    // JSON too
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-r@",
+            "-T",
+            r#"json(commit_id.short()) ++ "\n""#,
+            "--json",
+            "file3",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    "21ca17b0f71c"
+    {"added":0,"path":"file3","removed":1,"source":null,"status":"removed"}
+    "###);
+
+    // Every line of the output of log and evolog can be parsed
+    for args in [
+        &["log", "-p", "--json", "-T", r#"json(self) ++ "\n""#][..],
+        &["log", "--json", "-T", "json(commit_id)"],
+        &["log", "--json", "-T", r#""""#],
+        &["evolog", "-p", "--json", "-T", r#"json(self) ++ "\n""#],
+    ] {
+        let stdout = test_env.jj_cmd_success(&repo_path, args);
+        assert!(!stdout.is_empty());
+        for line in stdout.lines() {
+            assert!(
+                serde_json::from_str::<serde_json::Value>(line).is_ok(),
+                "{line:?} isn't JSON"
+            );
+        }
+    }
+
+    // The template output has to be JSON
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-p", "--json"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The template output isn't JSON, which is required with --json
+    Hint: Use a template which prints one JSON value per line, e.g. `-T 'json(self) ++ "\n"'`
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["evolog", "--json"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The template output isn't JSON, which is required with --json
+    Hint: Use a template which prints one JSON value per line, e.g. `-T 'json(self) ++ "\n"'`
+    "###);
+
+    // Other diff formats can't be interleaved with the JSON lines
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["log", "--json", "--git", "-T", r#"json(self) ++ "\n""#],
+    );
+    insta::assert_snapshot!(stderr, @"Error: --json can't be combined with other diff formats in log-like commands");
+}
+
 #[test]
 fn test_diff_stat_long_name_or_stat() {
     let mut test_env = TestEnvironment::default();
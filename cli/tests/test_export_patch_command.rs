@@ -0,0 +1,139 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_export_patch() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+    std::fs::write(repo_path.join("binary"), b"\0\x01\x02").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["commit", "-m", "Add some files\n\nWith a body."],
+    );
+    std::fs::rename(repo_path.join("file"), repo_path.join("renamed")).unwrap();
+    std::fs::write(repo_path.join("renamed"), "a\nc\n").unwrap();
+    std::fs::write(repo_path.join("binary"), b"\0\x01\x03").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "Change: the files!"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["export-patch", "-r", "::@- ~ root()", "-o", "../patches"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    ../patches/0001-Add-some-files.patch
+    ../patches/0002-Change-the-files.patch
+    "###);
+    insta::assert_snapshot!(stderr, @"");
+    let read_patch = |name: &str| {
+        std::fs::read_to_string(test_env.env_root().join("patches").join(name)).unwrap()
+    };
+    insta::assert_snapshot!(read_patch("0001-Add-some-files.patch"), @r###"
+    From fd1f48ed74a1831cf74950d55204821ca8d73c79 Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:08 +0700
+    Subject: [PATCH 1/2] Add some files
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    With a body.
+    ---
+    diff --git a/binary b/binary
+    new file mode 100644
+    index 0000000000..8352675d67
+    GIT binary patch
+    literal 3
+    KcmZQzWC8#H2LJ>B
+
+    literal 0
+    HcmV?d00001
+
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..422c2b7ab3
+    --- /dev/null
+    +++ b/file
+    @@ -1,0 +1,2 @@
+    +a
+    +b
+    "###);
+    insta::assert_snapshot!(read_patch("0002-Change-the-files.patch"), @r###"
+    From ad024215e5f75e9ebeec620e260ecabacfd8d50b Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:09 +0700
+    Subject: [PATCH 2/2] Change: the files!
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    ---
+    diff --git a/binary b/binary
+    index 8352675d67..1592e5c60f 100644
+    GIT binary patch
+    literal 3
+    KcmZQzWCj2L2ml2D
+
+    literal 3
+    KcmZQzWC8#H2LJ>B
+
+    diff --git a/file b/renamed
+    rename from file
+    rename to renamed
+    index 422c2b7ab3..0f7bc76605 100644
+    --- a/file
+    +++ b/renamed
+    @@ -1,2 +1,2 @@
+     a
+    -b
+    +c
+    "###);
+
+    // A single patch without a description, in the current directory
+    let (stdout, _stderr) =
+        test_env.jj_cmd_ok(test_env.env_root(), &["-R", "repo", "export-patch"]);
+    insta::assert_snapshot!(stdout, @"0001.patch");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("0001.patch")).unwrap(), @r###"
+    From f4acef771e470242c744d080149f99dedf1c07af Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:09 +0700
+    Subject: [PATCH]
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    ---
+    "###);
+}
+
+#[test]
+fn test_export_patch_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["export-patch", "-r", "root()"]);
+    insta::assert_snapshot!(stderr, @"Error: Cannot export the root commit");
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "b"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(a)", "description(b)"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["export-patch"]);
+    insta::assert_snapshot!(stderr, @"Error: Cannot export merge commit f2c15e963528 as a patch");
+}
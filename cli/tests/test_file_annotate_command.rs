@@ -0,0 +1,142 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn append_to_file(file_path: &Path, contents: &str) {
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(file_path)
+        .unwrap();
+    writeln!(file, "{contents}").unwrap();
+}
+
+#[test]
+fn test_annotate_linear() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file.txt"), "line1\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=initial"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=next"]);
+    append_to_file(&repo_path.join("file.txt"), "new text from new commit");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "annotate", "file.txt"]);
+    insta::assert_snapshot!(stdout, @r###"
+    qpvuntsm test.user 2001-02-03 08:05:08    1: line1
+    kkmpptxz test.user 2001-02-03 08:05:09    2: new text from new commit
+    "###);
+
+    // The prefix can be customized
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "file",
+            "annotate",
+            "file.txt",
+            "-r=@-",
+            "--config-toml=templates.annotate_commit_summary='description.first_line()'",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"initial    1: line1");
+}
+
+#[test]
+fn test_annotate_merge() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file.txt"), "line1\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=initial"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "initial"]);
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=commit1"]);
+    append_to_file(&repo_path.join("file.txt"), "new text from new commit 1");
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "commit1"]);
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=commit2", "initial"]);
+    append_to_file(&repo_path.join("file.txt"), "new text from new commit 2");
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "commit2"]);
+
+    // create a (conflicted) merge
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=merged", "commit1", "commit2"]);
+    // resolve conflicts
+    std::fs::write(
+        repo_path.join("file.txt"),
+        "line1\nnew text from new commit 1\nnew text from new commit 2\n",
+    )
+    .unwrap();
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "file",
+            "annotate",
+            "file.txt",
+            "--config-toml=templates.annotate_commit_summary='description.first_line()'",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    initial    1: line1
+    commit1    2: new text from new commit 1
+    commit2    3: new text from new commit 2
+    "###);
+}
+
+#[test]
+fn test_annotate_renamed() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("old.txt"), "line1\nline2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=rename"]);
+    std::fs::rename(repo_path.join("old.txt"), repo_path.join("new.txt")).unwrap();
+    append_to_file(&repo_path.join("new.txt"), "line3");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "annotate", "new.txt", "--follow-renames"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    qpvuntsm test.user 2001-02-03 08:05:08    1: line1
+    qpvuntsm test.user 2001-02-03 08:05:08    2: line2
+    rlvkpnrz test.user 2001-02-03 08:05:08    3: line3
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "annotate", "new.txt"]);
+    insta::assert_snapshot!(stdout, @r###"
+    rlvkpnrz test.user 2001-02-03 08:05:08    1: line1
+    rlvkpnrz test.user 2001-02-03 08:05:08    2: line2
+    rlvkpnrz test.user 2001-02-03 08:05:08    3: line3
+    "###);
+}
+
+#[test]
+fn test_annotate_not_a_file() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(repo_path.join("dir").join("file"), "").unwrap();
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["file", "annotate", "dir"]);
+    insta::assert_snapshot!(stderr, @"Error: Path 'dir' is not a file in the revision");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["file", "annotate", "missing"]);
+    insta::assert_snapshot!(stderr, @"Error: Path 'missing' is not a file in the revision");
+}
@@ -0,0 +1,87 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_file_list_predicates() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("text"), "text\n").unwrap();
+    std::fs::write(repo_path.join("script"), "#!/bin/sh\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "script"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=text"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=binary"]);
+    std::fs::write(repo_path.join("image.png"), b"\x89PNG\r\n\x1a\n\0").unwrap();
+    std::fs::write(repo_path.join("large"), "x".repeat(2000)).unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "executable()"]);
+    insta::assert_snapshot!(stdout, @"script");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "binary()"]);
+    insta::assert_snapshot!(stdout, @"image.png");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", r#"size(">1KiB")"#]);
+    insta::assert_snapshot!(stdout, @"large");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "size(5)"]);
+    insta::assert_snapshot!(stdout, @"text");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", r#"size("<=10")"#]);
+    insta::assert_snapshot!(stdout, @r###"
+    image.png
+    script
+    text
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", r#"size(">=2000")"#]);
+    insta::assert_snapshot!(stdout, @"large");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "~binary() & ~size(0)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    large
+    script
+    text
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "list", "-r@-", "binary() | executable()"],
+    );
+    insta::assert_snapshot!(stdout, @"script");
+
+    // Predicates in diff and revsets
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary", "~binary()"]);
+    insta::assert_snapshot!(stdout, @"A large");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "file(binary())", "-Tdescription"],
+    );
+    insta::assert_snapshot!(stdout, @"binary");
+
+    // Conflicted files
+    test_env.jj_cmd_ok(&repo_path, &["new", "@-", "-m=side"]);
+    std::fs::write(repo_path.join("text"), "side\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "@-", "-m=other"]);
+    std::fs::write(repo_path.join("text"), "other\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(side)", "@", "-m=merge"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "conflicted()"]);
+    insta::assert_snapshot!(stdout, @"text");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["file", "list", "size(big)"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse fileset: Invalid size range: must start with a number
+    Caused by:  --> 1:6
+      |
+    1 | size(big)
+      |      ^-^
+      |
+      = Invalid size range: must start with a number
+    "###);
+}
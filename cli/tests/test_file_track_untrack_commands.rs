@@ -190,6 +190,26 @@ fn test_auto_track() {
     ../file1.rs
     file1.rs
     "###);
+
+    // File predicates can't be evaluated against untracked files
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["file", "track", "binary()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Untracked files can't be matched by predicates
+    Caused by: File predicates such as `binary()` can't be evaluated without trees
+    "###);
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "file",
+            "list",
+            "--config-toml=snapshot.auto-track='glob:*.rs | binary()'",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: Invalid `snapshot.auto-track`
+    Caused by: File predicates such as `binary()` can't be evaluated without trees
+    For help, see https://martinvonz.github.io/jj/latest/config/.
+    "###);
 }
 
 #[test]
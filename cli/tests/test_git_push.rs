@@ -785,6 +785,100 @@ fn test_git_push_mixed() {
     "###);
 }
 
+#[test]
+fn test_git_push_tags() {
+    let (test_env, workspace_root) = set_up();
+    let origin_git_repo = git2::Repository::open(
+        test_env
+            .env_root()
+            .join("origin")
+            .join(".jj")
+            .join("repo")
+            .join("store")
+            .join("git"),
+    )
+    .unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["describe", "-m", "foo"]);
+    test_env.jj_cmd_ok(&workspace_root, &["new"]);
+    test_env.jj_cmd_ok(&workspace_root, &["tag", "create", "v1", "-r@-"]);
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["bookmark", "create", "my-bookmark", "-r@-"],
+    );
+
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--tag", "v3"]);
+    insta::assert_snapshot!(stderr, @"Error: No such tag: v3");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &["git", "push", "--tag", "v1", "--dry-run"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Tags to push to origin:
+      Add tag v1 to 7283b790a895
+    Dry-run requested, not pushing.
+    "###);
+    assert!(origin_git_repo.find_reference("refs/tags/v1").is_err());
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &["git", "push", "--tag", "glob:v1*", "-b", "my-bookmark"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Branch changes to push to origin:
+      Add bookmark my-bookmark to 7283b790a895
+    Tags to push to origin:
+      Add tag v1 to 7283b790a895
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &["op", "log", "-n1", "--no-graph", "-T", "description"],
+    );
+    insta::assert_snapshot!(stdout, @"push bookmark my-bookmark and tag v1 to git remote origin");
+    let local_git_repo = git2::Repository::open(
+        workspace_root
+            .join(".jj")
+            .join("repo")
+            .join("store")
+            .join("git"),
+    )
+    .unwrap();
+    assert_eq!(
+        origin_git_repo
+            .find_reference("refs/tags/v1")
+            .unwrap()
+            .target(),
+        local_git_repo
+            .find_reference("refs/tags/v1")
+            .unwrap()
+            .target(),
+    );
+
+    // Pushing the same tag again is a no-op
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--tag", "v1"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Tags to push to origin:
+      Add tag v1 to 7283b790a895
+    Nothing changed.
+    "###);
+
+    // Tags which point elsewhere on the remote are not overwritten
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["tag", "set", "--allow-move", "v1", "-r", "bookmark2"],
+    );
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--tag", "v1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Tags to push to origin:
+      Add tag v1 to 8476341eb395
+    Error: Refusing to overwrite tags which already exist on the remote. Affected refs: refs/tags/v1
+    Hint: Tags are not moved on the remote. Delete the tags there first if you want to replace them.
+    "###);
+}
+
 #[test]
 fn test_git_push_existing_long_bookmark() {
     let (test_env, workspace_root) = set_up();
@@ -832,6 +926,93 @@ fn test_git_push_conflict() {
     insta::assert_snapshot!(stderr, @r###"
     Error: Won't push commit 73c265a92cfd since it has conflicts
     "###);
+
+    // Enabling jj headers doesn't help commits written without them
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            "--all",
+            "--config-toml=git.write-jj-headers=true",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Won't push commit 73c265a92cfd since it has conflicts
+    "###);
+}
+
+#[test]
+fn test_git_push_conflict_with_jj_headers() {
+    let (test_env, workspace_root) = set_up();
+    test_env.add_config("git.write-jj-headers = true");
+    std::fs::write(workspace_root.join("file"), "first").unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["commit", "-m", "first"]);
+    std::fs::write(workspace_root.join("file"), "second").unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["commit", "-m", "second"]);
+    std::fs::write(workspace_root.join("file"), "third").unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["rebase", "-r", "@", "-d", "@--"]);
+    test_env.jj_cmd_ok(&workspace_root, &["bookmark", "create", "my-bookmark"]);
+    test_env.jj_cmd_ok(&workspace_root, &["describe", "-m", "third"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &["git", "push", "--bookmark", "my-bookmark"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Branch changes to push to origin:
+      Add bookmark my-bookmark to 4917e36182af
+    "###);
+
+    // Another repo fetching the commits sees the same change ids and the
+    // conflict
+    let template = r#"separate(" ", change_id.short(), description.first_line(), bookmarks, if(conflict, "conflict")) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &[
+            "log",
+            "--no-graph",
+            "-T",
+            template,
+            "-r",
+            "::my-bookmark ~ root()",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    yostqsxwqrlt third my-bookmark conflict
+    yqosqzytrlsw first
+    "###);
+    let origin_git_repo_path = test_env
+        .env_root()
+        .join("origin")
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git");
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &[
+            "git",
+            "clone",
+            origin_git_repo_path.to_str().unwrap(),
+            "other",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &test_env.env_root().join("other"),
+        &[
+            "log",
+            "--no-graph",
+            "-T",
+            template,
+            "-r",
+            "::my-bookmark@origin ~ root()",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    yostqsxwqrlt third my-bookmark@origin conflict
+    yqosqzytrlsw first
+    "###);
 }
 
 #[test]
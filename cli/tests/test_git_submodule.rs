@@ -12,8 +12,87 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
+use std::path::PathBuf;
+
 use crate::common::TestEnvironment;
 
+fn git_signature() -> git2::Signature<'static> {
+    git2::Signature::new("Some One", "some.one@example.com", &git2::Time::new(0, 0)).unwrap()
+}
+
+/// Creates a commit with a single `file` on top of the `HEAD`.
+fn create_submodule_commit(git_repo: &git2::Repository, content: &str) -> git2::Oid {
+    let mut tree_builder = git_repo.treebuilder(None).unwrap();
+    let blob_oid = git_repo.blob(content.as_bytes()).unwrap();
+    tree_builder.insert("file", blob_oid, 0o100644).unwrap();
+    let tree = git_repo.find_tree(tree_builder.write().unwrap()).unwrap();
+    let parents = git_repo
+        .head()
+        .ok()
+        .map(|head| head.peel_to_commit().unwrap());
+    let signature = git_signature();
+    git_repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            content,
+            &tree,
+            &parents.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+}
+
+/// Creates a root commit on the `branch` which has the submodule `sub`
+/// checked out at `oid`.
+fn create_superproject_commit(
+    git_repo: &git2::Repository,
+    branch: &str,
+    submodule_url: &Path,
+    oid: git2::Oid,
+) {
+    let gitmodules = format!(
+        "[submodule \"sub\"]\n\tpath = sub\n\turl = {}\n",
+        submodule_url.display()
+    );
+    let mut tree_builder = git_repo.treebuilder(None).unwrap();
+    let blob_oid = git_repo.blob(gitmodules.as_bytes()).unwrap();
+    tree_builder
+        .insert(".gitmodules", blob_oid, 0o100644)
+        .unwrap();
+    tree_builder.insert("sub", oid, 0o160000).unwrap();
+    let tree = git_repo.find_tree(tree_builder.write().unwrap()).unwrap();
+    let signature = git_signature();
+    git_repo
+        .commit(
+            Some(&format!("refs/heads/{branch}")),
+            &signature,
+            &signature,
+            "add submodule",
+            &tree,
+            &[],
+        )
+        .unwrap();
+}
+
+/// Initializes a colocated repo with the submodule `sub` checked out at `oid`.
+fn init_superproject(
+    test_env: &TestEnvironment,
+    submodule_url: &Path,
+    oid: git2::Oid,
+) -> (PathBuf, git2::Repository) {
+    let workspace_root = test_env.env_root().join("repo");
+    let git_repo = git2::Repository::init(&workspace_root).unwrap();
+    create_superproject_commit(&git_repo, "main", submodule_url, oid);
+    git_repo.set_head("refs/heads/main").unwrap();
+    git_repo
+        .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["git", "init", "--git-repo", "."]);
+    (workspace_root, git_repo)
+}
+
 #[test]
 fn test_gitsubmodule_print_gitmodules() {
     let test_env = TestEnvironment::default();
@@ -63,3 +142,249 @@ fn test_gitsubmodule_print_gitmodules() {
 	path:new
     "###);
 }
+
+#[test]
+fn test_gitsubmodule_update() {
+    let test_env = TestEnvironment::default();
+    let submodule_origin_path = test_env.env_root().join("sub-origin");
+    let submodule_origin = git2::Repository::init(&submodule_origin_path).unwrap();
+    let oid1 = create_submodule_commit(&submodule_origin, "v1\n");
+    let oid2 = create_submodule_commit(&submodule_origin, "v2\n");
+
+    let (workspace_root, _git_repo) = init_superproject(&test_env, &submodule_origin_path, oid1);
+
+    // The submodule isn't populated on checkout until it's initialized
+    let submodule_path = workspace_root.join("sub");
+    assert!(submodule_path.is_dir());
+    assert!(!submodule_path.join("file").exists());
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Fetching submodule sub
+    Updated submodule sub to bce6fb0ba035
+    "###);
+    assert_eq!(
+        std::fs::read_to_string(submodule_path.join("file")).unwrap(),
+        "v1\n"
+    );
+
+    // Already up to date
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    insta::assert_snapshot!(stderr, @"Updated submodule sub to bce6fb0ba035");
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Moving the submodule HEAD is recorded in the working-copy commit
+    let submodule_repo = git2::Repository::open(&submodule_path).unwrap();
+    submodule_repo.set_head_detached(oid2).unwrap();
+    submodule_repo
+        .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"M sub");
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/sub b/sub
+    index bce6fb0ba0..893bd5adb8 160000
+    --- a/sub
+    +++ b/sub
+    @@ -1,1 +1,1 @@
+    -Subproject commit bce6fb0ba03537243e0130de8525390136e620b3
+    +Subproject commit 893bd5adb841bfb40eefb2d5741df6764c84ee03
+    "###);
+
+    // Initialized submodules are updated on checkout
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["new", "@-", "--quiet"]);
+    insta::assert_snapshot!(stderr, @"");
+    assert_eq!(
+        std::fs::read_to_string(submodule_path.join("file")).unwrap(),
+        "v1\n"
+    );
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+}
+
+#[test]
+fn test_gitsubmodule_checkout_missing_commit() {
+    let test_env = TestEnvironment::default();
+    let submodule_origin_path = test_env.env_root().join("sub-origin");
+    let submodule_origin = git2::Repository::init(&submodule_origin_path).unwrap();
+    let oid1 = create_submodule_commit(&submodule_origin, "v1\n");
+
+    let (workspace_root, git_repo) = init_superproject(&test_env, &submodule_origin_path, oid1);
+    test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+
+    // The new submodule commit hasn't been fetched yet
+    let oid2 = create_submodule_commit(&submodule_origin, "v2\n");
+    create_superproject_commit(&git_repo, "update", &submodule_origin_path, oid2);
+    test_env.jj_cmd_ok(&workspace_root, &["git", "import"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["new", "update", "--quiet"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Warning: Failed to update submodule sub: Commit 893bd5adb841bfb40eefb2d5741df6764c84ee03 of submodule sub not found
+    "###);
+    let submodule_path = workspace_root.join("sub");
+    assert_eq!(
+        std::fs::read_to_string(submodule_path.join("file")).unwrap(),
+        "v1\n"
+    );
+
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update", "sub"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Fetching submodule sub
+    Updated submodule sub to 893bd5adb841
+    "###);
+    assert_eq!(
+        std::fs::read_to_string(submodule_path.join("file")).unwrap(),
+        "v2\n"
+    );
+
+    // Paths not matching any submodules are ignored
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update", "unknown"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+}
+
+#[test]
+fn test_gitsubmodule_checkout_conflicted_gitmodules() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_root = test_env.env_root().join("repo");
+    let write_gitmodules = |url: &str| {
+        let gitmodules = format!("[submodule \"sub\"]\n\tpath = sub\n\turl = {url}\n");
+        std::fs::write(workspace_root.join(".gitmodules"), gitmodules).unwrap();
+    };
+    write_gitmodules("https://example.com/base.git");
+    test_env.jj_cmd_ok(&workspace_root, &["describe", "-mbase"]);
+    test_env.jj_cmd_ok(&workspace_root, &["new", "-mleft"]);
+    write_gitmodules("https://example.com/left.git");
+    test_env.jj_cmd_ok(&workspace_root, &["new", "description(base)", "-mright"]);
+    write_gitmodules("https://example.com/right.git");
+
+    // The checkout succeeds even though .gitmodules can't be read
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &["new", "description(left)", "description(right)", "--quiet"],
+    );
+    insta::assert_snapshot!(stderr, @"Warning: Failed to update submodules: .gitmodules is not a file.");
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &["log", "-r@", "--no-graph", "-Tparents.len()"],
+    );
+    insta::assert_snapshot!(stdout, @"2");
+}
+
+#[test]
+fn test_gitsubmodule_update_unmanaged_git_dir() {
+    let test_env = TestEnvironment::default();
+    let submodule_origin_path = test_env.env_root().join("sub-origin");
+    let submodule_origin = git2::Repository::init(&submodule_origin_path).unwrap();
+    let oid1 = create_submodule_commit(&submodule_origin, "v1\n");
+    let (workspace_root, _git_repo) = init_superproject(&test_env, &submodule_origin_path, oid1);
+
+    // The submodule was checked out by another tool
+    let submodule_path = workspace_root.join("sub");
+    std::fs::remove_dir(&submodule_path).unwrap();
+    let submodule_repo =
+        git2::Repository::clone(submodule_origin_path.to_str().unwrap(), &submodule_path).unwrap();
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Fetching submodule sub
+    Warning: Skipping submodule sub: $TEST_ENV/repo/sub/.git is not managed by jj, leaving it as is
+    "###);
+    assert!(submodule_path.join(".git").is_dir());
+    assert_eq!(submodule_repo.head().unwrap().target(), Some(oid1));
+}
+
+#[test]
+fn test_gitsubmodule_update_workspaces() {
+    let test_env = TestEnvironment::default();
+    let submodule_origin_path = test_env.env_root().join("sub-origin");
+    let submodule_origin = git2::Repository::init(&submodule_origin_path).unwrap();
+    let oid1 = create_submodule_commit(&submodule_origin, "v1\n");
+    let oid2 = create_submodule_commit(&submodule_origin, "v2\n");
+
+    let (workspace_root, git_repo) = init_superproject(&test_env, &submodule_origin_path, oid1);
+    create_superproject_commit(&git_repo, "update", &submodule_origin_path, oid2);
+    test_env.jj_cmd_ok(&workspace_root, &["git", "import"]);
+    test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    let secondary_path = test_env.env_root().join("secondary");
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["workspace", "add", "-r", "update", "../secondary"],
+    );
+
+    // Each workspace checks out the submodule with its own HEAD
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&secondary_path, &["git", "submodule", "update"]);
+    insta::assert_snapshot!(stderr, @"Updated submodule sub to 893bd5adb841");
+    assert_eq!(
+        std::fs::read_to_string(secondary_path.join("sub").join("file")).unwrap(),
+        "v2\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(workspace_root.join("sub").join("file")).unwrap(),
+        "v1\n"
+    );
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Checking out in one workspace leaves the other one alone
+    test_env.jj_cmd_ok(&workspace_root, &["new", "update", "--quiet"]);
+    test_env.jj_cmd_ok(&secondary_path, &["new", "main", "--quiet"]);
+    assert_eq!(
+        std::fs::read_to_string(workspace_root.join("sub").join("file")).unwrap(),
+        "v2\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(secondary_path.join("sub").join("file")).unwrap(),
+        "v1\n"
+    );
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+}
+
+#[test]
+fn test_gitsubmodule_sync() {
+    let test_env = TestEnvironment::default();
+    let submodule_origin_path = test_env.env_root().join("sub-origin");
+    let submodule_origin = git2::Repository::init(&submodule_origin_path).unwrap();
+    let oid1 = create_submodule_commit(&submodule_origin, "v1\n");
+
+    let (workspace_root, _git_repo) = init_superproject(&test_env, &submodule_origin_path, oid1);
+
+    // Uninitialized submodules are left alone
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "sync"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+
+    test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    std::fs::write(
+        workspace_root.join(".gitmodules"),
+        "[submodule \"sub\"]\n\tpath = sub\n\turl = https://example.com/sub.git\n",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "sync"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Synchronized URL of submodule sub: https://example.com/sub.git
+    "###);
+    let submodule_repo = git2::Repository::open(workspace_root.join("sub")).unwrap();
+    assert_eq!(
+        submodule_repo.find_remote("origin").unwrap().url(),
+        Some("https://example.com/sub.git")
+    );
+
+    // Nothing to synchronize
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "sync"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+}
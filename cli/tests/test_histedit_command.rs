@@ -0,0 +1,363 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::common::TestEnvironment;
+
+/// Creates commits `a` to `d` on top of each other, where each commit adds a
+/// file named after it.
+fn init_repo(test_env: &TestEnvironment) -> PathBuf {
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    for name in ["a", "b", "c", "d"] {
+        std::fs::write(repo_path.join(name), format!("{name}\n")).unwrap();
+        test_env.jj_cmd_ok(&repo_path, &["commit", "-m", name]);
+    }
+    repo_path
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template =
+        r#"separate(" ", change_id.short(), description.first_line()) ++ "\n" ++ diff.summary()"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template])
+}
+
+#[test]
+fn test_histedit() {
+    let mut test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  mzvwutvlkqwt
+    ○  zsuskulnrvyr d
+    │  A d
+    ○  kkmpptxzrspx c
+    │  A c
+    ○  rlvkpnrzqnoo b
+    │  A b
+    ○  qpvuntsmwlqt a
+    │  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(&edit_script, "dump todo").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["histedit"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Nothing changed.");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("todo")).unwrap(), @r###"
+    pick qpvuntsmwlqt a
+    pick rlvkpnrzqnoo b
+    pick kkmpptxzrspx c
+    pick zsuskulnrvyr d
+    pick mzvwutvlkqwt (no description set)
+
+    JJ: Edit the history of these revisions by editing the lines above. If the
+    JJ: lines are reordered, they are applied from top to bottom, each on top of
+    JJ: the previous one. Otherwise, the revisions keep their parents.
+    JJ:
+    JJ: Actions:
+    JJ:   p, pick <change id>   = keep the revision
+    JJ:   r, reword <change id> = keep the revision, but edit its description
+    JJ:   e, edit <change id>   = keep the revision, and make it the working copy
+    JJ:   s, squash <change id> = fold into the previous revision, combining the
+    JJ:                           descriptions
+    JJ:   f, fixup <change id>  = fold into the previous revision, keeping its
+    JJ:                           description
+    JJ:   d, drop <change id>   = abandon the revision and discard its changes
+    JJ:
+    JJ: Lines can be reordered. Every revision must be listed.
+    JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "###);
+
+    // Reorder, drop, squash and reword in one go
+    std::fs::write(
+        &edit_script,
+        [
+            "write\nreword zsuskuln\npick qpvuntsm\nsquash kkmpptxz\ndrop rlvkpnrz\npick mzvwutvl\n",
+            "next invocation\n",
+            "write\nc reworded\n",
+            "next invocation\n",
+            "write\na and c\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["histedit"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Working copy now at: mzvwutvl 517ae7af (empty) (no description set)
+    Parent commit      : qpvuntsm 65167554 a and c
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  mzvwutvlkqwt
+    ○  qpvuntsmwlqt a and c
+    │  A a
+    │  A c
+    ○  zsuskulnrvyr c reworded
+    │  A d
+    ◆  zzzzzzzzzzzz
+    "###);
+
+    // It's a single operation
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  mzvwutvlkqwt
+    ○  zsuskulnrvyr d
+    │  A d
+    ○  kkmpptxzrspx c
+    │  A c
+    ○  rlvkpnrzqnoo b
+    │  A b
+    ○  qpvuntsmwlqt a
+    │  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+}
+
+#[test]
+fn test_histedit_fixup_and_edit() {
+    let mut test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+
+    // Descendants outside of the revisions are rebased
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        &edit_script,
+        "write\npick rlvkpnrz\nfixup kkmpptxz\nedit zsuskuln\n",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["histedit", "-r", "description(b)::description(d)"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 1 descendant commits
+    Working copy now at: zsuskuln 9da4bca8 d
+    Parent commit      : rlvkpnrz 001066fa b
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  zsuskulnrvyr d
+    │  A d
+    ○  rlvkpnrzqnoo b
+    │  A b
+    │  A c
+    ○  qpvuntsmwlqt a
+    │  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+}
+
+#[test]
+fn test_histedit_merge() {
+    let mut test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "side"]);
+    std::fs::write(repo_path.join("side"), "side\n").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "rebase",
+            "-s",
+            "description(c)",
+            "-d",
+            "description(b)",
+            "-d",
+            "description(side)",
+        ],
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  zsuskulnrvyr d
+    │  A d
+    ○    kkmpptxzrspx c
+    ├─╮  A c
+    │ @  royxmykxtrkr side
+    │ │  A side
+    ○ │  rlvkpnrzqnoo b
+    │ │  A b
+    ○ │  qpvuntsmwlqt a
+    ├─╯  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+
+    // Parents of a merge revision that aren't being edited are kept
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        &edit_script,
+        "write\npick qpvuntsm\ndrop rlvkpnrz\npick kkmpptxz\npick zsuskuln\n",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["histedit", "-r", "description(a)::description(d)"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  zsuskulnrvyr d
+    │  A d
+    ○    kkmpptxzrspx c
+    ├─╮  A c
+    │ @  royxmykxtrkr side
+    │ │  A side
+    ○ │  qpvuntsmwlqt a
+    ├─╯  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+
+    // Folding a merge revision into the previous one moves its other parents
+    // and their changes along with it
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    std::fs::write(
+        &edit_script,
+        "write\npick qpvuntsm\npick rlvkpnrz\nfixup kkmpptxz\npick zsuskuln\n",
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["histedit", "-r", "description(a)::description(d)"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  zsuskulnrvyr d
+    │  A d
+    ○    rlvkpnrzqnoo b
+    ├─╮  A b
+    │ │  A c
+    │ @  royxmykxtrkr side
+    │ │  A side
+    ○ │  qpvuntsmwlqt a
+    ├─╯  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+}
+
+#[test]
+fn test_histedit_forked() {
+    let mut test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(b)", "-m", "e"]);
+    std::fs::write(repo_path.join("e"), "e\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  yqosqzytrlsw
+    ○  royxmykxtrkr e
+    │  A e
+    │ ○  zsuskulnrvyr d
+    │ │  A d
+    │ ○  kkmpptxzrspx c
+    ├─╯  A c
+    ○  rlvkpnrzqnoo b
+    │  A b
+    ○  qpvuntsmwlqt a
+    │  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+
+    // The unchanged todo list doesn't rewrite anything
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(&edit_script, "dump todo").unwrap();
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["histedit", "-r", "description(a):: ~ @"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Nothing changed.");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  yqosqzytrlsw
+    ○  royxmykxtrkr e
+    │  A e
+    │ ○  zsuskulnrvyr d
+    │ │  A d
+    │ ○  kkmpptxzrspx c
+    ├─╯  A c
+    ○  rlvkpnrzqnoo b
+    │  A b
+    ○  qpvuntsmwlqt a
+    │  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+
+    // Without reordering, the revisions keep their parents
+    let todo = std::fs::read_to_string(test_env.env_root().join("todo")).unwrap();
+    let todo = todo.replacen("pick rlvkpnrz", "drop rlvkpnrz", 1);
+    std::fs::write(&edit_script, format!("write\n{todo}")).unwrap();
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["histedit", "-r", "description(a):: ~ @"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 1 descendant commits
+    Working copy now at: yqosqzyt dbc222dd (empty) (no description set)
+    Parent commit      : royxmykx 62c00aed e
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  yqosqzytrlsw
+    ○  royxmykxtrkr e
+    │  A e
+    │ ○  zsuskulnrvyr d
+    │ │  A d
+    │ ○  kkmpptxzrspx c
+    ├─╯  A c
+    ○  qpvuntsmwlqt a
+    │  A a
+    ◆  zzzzzzzzzzzz
+    "###);
+}
+
+#[test]
+fn test_histedit_errors() {
+    let mut test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    let edit_script = test_env.set_up_fake_editor();
+
+    std::fs::write(&edit_script, "write\nmove qpvuntsm\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit"]);
+    insta::assert_snapshot!(stderr, @"Error: Line 1: Unknown action: move qpvuntsm");
+
+    std::fs::write(&edit_script, "write\npick qpvuntsm\npick zzzzzzzz\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit"]);
+    insta::assert_snapshot!(stderr, @"Error: Line 2: Not one of the revisions being edited: pick zzzzzzzz");
+
+    std::fs::write(&edit_script, "write\npick qpvuntsm\npick qpvuntsm\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit"]);
+    insta::assert_snapshot!(stderr, @"Error: Line 2: Revision is listed more than once: pick qpvuntsm");
+
+    std::fs::write(&edit_script, "write\nsquash qpvuntsm\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit"]);
+    insta::assert_snapshot!(stderr, @"Error: Line 1: There is no previous revision to fold into: squash qpvuntsm");
+
+    std::fs::write(&edit_script, "write\npick qpvuntsm\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Revision rlvkpnrzqnoo is missing from the todo list
+    Hint: Use `drop` to abandon a revision
+    "###);
+
+    // An empty todo list doesn't change anything
+    std::fs::write(&edit_script, "write\n").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["histedit"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"The todo list is empty, nothing changed.");
+
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "-r", "all() ~ root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The revisions to edit must have a single root
+    Hint: Edit the history of each root separately
+    "###);
+}
@@ -0,0 +1,96 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_range_diff() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "A"]);
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "B"]);
+    std::fs::write(repo_path.join("file3"), "c1\nc2\nc3\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "C"]);
+    std::fs::write(repo_path.join("file4"), "x\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "X"]);
+    let old_head = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "@-", "-T", "commit_id"],
+    );
+
+    // B is changed, C is recreated with a new change id and a small change, X
+    // is dropped, and D is added
+    test_env.jj_cmd_ok(&repo_path, &["edit", "description(B)"]);
+    std::fs::write(repo_path.join("file2"), "b changed\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "description(C) | description(X)"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(B)", "-m", "D"]);
+    std::fs::write(repo_path.join("file5"), "d\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "C"]);
+    std::fs::write(repo_path.join("file3"), "c1\nc2\nc3 changed\n").unwrap();
+
+    let old = format!("::{old_head} ~ root()");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["range-diff", &old, "::@ ~ root()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    1: 276cee3719d8 = 1: 276cee3719d8 A
+    2: 21b427df9604 ! 2: 009a64c48c16 B
+    Modified regular file file2:
+       1    1: b changed
+    -: ------------ > 3: 8273d0fc103f D
+    3: 237ca783250e ! 4: 83b97840f767 C
+    Modified regular file file3:
+       1    1: c1
+       2    2: c2
+       3    3: c3 changed
+    4: e2d1cb5ab204 < -: ------------ X
+    "###);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["range-diff", &old, "::@ ~ root()", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    1: 276cee3719d8 = 1: 276cee3719d8 A
+    2: 21b427df9604 ! 2: 009a64c48c16 B
+    diff --git a/file2 b/file2
+    index 6178079822..7b1aa3db05 100644
+    --- a/file2
+    +++ b/file2
+    @@ -1,1 +1,1 @@
+    -b
+    +b changed
+    -: ------------ > 3: 8273d0fc103f D
+    3: 237ca783250e ! 4: 83b97840f767 C
+    diff --git a/file3 b/file3
+    index fd585bc75e..e83dd6a34d 100644
+    --- a/file3
+    +++ b/file3
+    @@ -1,3 +1,3 @@
+     c1
+     c2
+    -c3
+    +c3 changed
+    4: e2d1cb5ab204 < -: ------------ X
+    "###);
+
+    // Identical series
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["range-diff", "::@ ~ root()", "::@ ~ root()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    1: 276cee3719d8 = 1: 276cee3719d8 A
+    2: 009a64c48c16 = 2: 009a64c48c16 B
+    3: 8273d0fc103f = 3: 8273d0fc103f D
+    4: 83b97840f767 = 4: 83b97840f767 C
+    "###);
+}
@@ -2464,3 +2464,73 @@ fn get_long_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
                     ++ surround(':  ', '', parents.map(|c| c.bookmarks()))";
     test_env.jj_cmd_success(repo_path, &["log", "-T", template])
 }
+
+#[test]
+fn test_rebase_across_rename() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "1\n2\n3\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "base"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "base"]);
+    std::fs::rename(repo_path.join("file"), repo_path.join("renamed")).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "rename"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "base"]);
+    std::fs::write(repo_path.join("file"), "1\n2\n3\n4\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "edit"]);
+
+    // The edit is carried over to the renamed file
+    test_env.jj_cmd_ok(&repo_path, &["rebase", "-r", "edit", "-d", "rename"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-r", "edit", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/renamed b/renamed
+    index 01e79c32a8..94ebaf9001 100644
+    --- a/renamed
+    +++ b/renamed
+    @@ -1,3 +1,4 @@
+     1
+     2
+     3
+    +4
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "-r", "edit"]);
+    insta::assert_snapshot!(stdout, @"renamed");
+
+    // Without rename detection, the old path has a modify/delete conflict
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "rebase",
+            "-r",
+            "edit",
+            "-d",
+            "rename",
+            "--config-toml=merge.detect-renames=false",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "-r", "edit"]);
+    insta::assert_snapshot!(stdout, @r###"
+    file
+    renamed
+    "###);
+
+    // An invalid setting isn't silently ignored
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "rebase",
+            "-r",
+            "edit",
+            "-d",
+            "rename",
+            "--config-toml=merge.detect-renames='maybe'",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: invalid type: string "maybe", expected a boolean
+    For help, see https://martinvonz.github.io/jj/latest/config/.
+    "###);
+}
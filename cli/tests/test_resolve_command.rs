@@ -981,3 +981,98 @@ fn test_multiple_conflicts() {
     Error: No conflicts found at this revision
     "###);
 }
+
+#[test]
+fn test_reuse_recorded_resolution() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("merge.reuse-resolutions = true");
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "base", &[], &[("file", "base\n")]);
+    create_commit(&test_env, &repo_path, "a", &["base"], &[("file", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["base"], &[("file", "b\n")]);
+    create_commit(&test_env, &repo_path, "c", &["base"], &[("file", "b\n")]);
+    create_commit(&test_env, &repo_path, "d", &["base"], &[("file", "b\n")]);
+
+    // Resolve the conflict in b
+    test_env.jj_cmd_ok(&repo_path, &["rebase", "-r", "b", "-d", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["edit", "b"]);
+    std::fs::write(repo_path.join("file"), "a and b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+
+    // The same conflict in c is resolved the same way
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["rebase", "-r", "c", "-d", "a"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 1 commits onto destination
+    Reused recorded conflict resolutions in file
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "c", "file"]);
+    insta::assert_snapshot!(stdout, @"a and b");
+
+    // The resolution can be forgotten from the revision where the conflict was
+    // resolved
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["resolve", "--forget", "-r", "b"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Forgot 1 recorded resolutions");
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["resolve", "--forget", "-r", "a"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No conflicts found in this revision or the revisions it was resolved from
+    "###);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["rebase", "-r", "d", "-d", "a"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 1 commits onto destination
+    New conflicts appeared in these commits:
+      znkkpsqq 4cec4b47 d | (conflict) d
+    To resolve the conflicts, start by updating to it:
+      jj new znkkpsqqskkl
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ×  d
+    │ ○  c
+    ├─╯
+    │ @  b
+    ├─╯
+    ○  a
+    ○  base
+    ◆
+    "###);
+}
+
+#[test]
+fn test_forget_resolutions_disabled() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "base", &[], &[("file", "base\n")]);
+    create_commit(&test_env, &repo_path, "a", &["base"], &[("file", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["base"], &[("file", "b\n")]);
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["resolve", "--forget"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Conflict resolutions are not being recorded
+    Hint: Set `merge.reuse-resolutions = true` to record and reuse them.
+    "###);
+}
+
+#[test]
+fn test_reuse_resolutions_invalid_config() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["log", "--config-toml=merge.reuse-resolutions='maybe'"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: invalid type: string "maybe", expected a boolean
+    For help, see https://martinvonz.github.io/jj/latest/config/.
+    "###);
+}
@@ -0,0 +1,224 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn create_commit(
+    test_env: &TestEnvironment,
+    repo_path: &Path,
+    name: &str,
+    parents: &[&str],
+    files: &[(&str, &str)],
+) {
+    if parents.is_empty() {
+        test_env.jj_cmd_ok(repo_path, &["new", "root()", "-m", name]);
+    } else {
+        let mut args = vec!["new", "-m", name];
+        args.extend(parents);
+        test_env.jj_cmd_ok(repo_path, &args);
+    }
+    for (name, contents) in files {
+        std::fs::write(repo_path.join(name), contents).unwrap();
+    }
+    test_env.jj_cmd_ok(repo_path, &["bookmark", "create", name]);
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"commit_id.short() ++ " " ++ description"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template])
+}
+
+#[test]
+fn test_revert() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[], &[("a", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["a"], &[("a", "a\nb\n")]);
+
+    // A location is required
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["revert", "-r", "b"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the following required arguments were not provided:
+      <--destination <DESTINATION>|--insert-after <INSERT_AFTER>|--insert-before <INSERT_BEFORE>>
+
+    Usage: jj revert --revisions <REVISIONS> <--destination <DESTINATION>|--insert-after <INSERT_AFTER>|--insert-before <INSERT_BEFORE>>
+
+    For more information, try '--help'.
+    "###);
+
+    // Revert a single commit onto the working copy
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "b", "-d", "@"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted 1 commits as follows:
+      yqosqzyt b2b98b01 Revert "b"
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  b2b98b01c099 Revert "b"
+    │
+    │  This reverts commit 46cc97af6802301d8db381386e8485ff3ff24ae6.
+    @  46cc97af6802 b
+    ○  2443ea76b0b1 a
+    ◆  000000000000
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-r", "@+"]);
+    insta::assert_snapshot!(stdout, @"M a");
+}
+
+#[test]
+fn test_revert_multiple() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[], &[("a", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["a"], &[("a", "a\nb\n")]);
+    create_commit(&test_env, &repo_path, "c", &["b"], &[("a", "a\nb\nc\n")]);
+    create_commit(&test_env, &repo_path, "d", &["c"], &[]);
+
+    // Revert multiple commits in reverse topological order
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "b|c", "-d", "d"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted 2 commits as follows:
+      znkkpsqq 1e092a3a Revert "c"
+      uuzqqzqu 3dfae3c6 Revert "b"
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  3dfae3c60b1f Revert "b"
+    │
+    │  This reverts commit 46cc97af6802301d8db381386e8485ff3ff24ae6.
+    ○  1e092a3aee38 Revert "c"
+    │
+    │  This reverts commit d06a2382d586d9b08078853bb2a5ac1697ba36b6.
+    @  a7e1c4895ccb d
+    ○  d06a2382d586 c
+    ○  46cc97af6802 b
+    ○  2443ea76b0b1 a
+    ◆  000000000000
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "heads(d::)", "a"]);
+    insta::assert_snapshot!(stdout, @"a");
+}
+
+#[test]
+fn test_revert_insert() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[], &[("a", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["a"], &[("b", "b\n")]);
+    create_commit(&test_env, &repo_path, "c", &["b"], &[("c", "c\n")]);
+    let setup_opid = test_env.current_operation_id(&repo_path);
+
+    // Insert after a commit, rebasing its children
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "a", "-A", "b"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted 1 commits as follows:
+      yostqsxw 853a17a2 Revert "a"
+    Rebased 1 descendant commits
+    Working copy now at: royxmykx 1c7d1e67 c | c
+    Parent commit      : yostqsxw 853a17a2 Revert "a"
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  1c7d1e67c96e c
+    ○  853a17a290c2 Revert "a"
+    │
+    │  This reverts commit 2443ea76b0b1c531326908326aab7020abab8e6c.
+    ○  1394f625cbbd b
+    ○  2443ea76b0b1 a
+    ◆  000000000000
+    "###);
+
+    // Insert before a commit
+    test_env.jj_cmd_ok(&repo_path, &["op", "restore", &setup_opid]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "b", "-B", "c"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted 1 commits as follows:
+      kmkuslsw 5b53b137 Revert "b"
+    Rebased 1 descendant commits
+    Working copy now at: royxmykx ecd13184 c | c
+    Parent commit      : kmkuslsw 5b53b137 Revert "b"
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  ecd13184f449 c
+    ○  5b53b13757ad Revert "b"
+    │
+    │  This reverts commit 1394f625cbbddc4245af6505f4ef56b77dc27ba9.
+    ○  1394f625cbbd b
+    ○  2443ea76b0b1 a
+    ◆  000000000000
+    "###);
+
+    // Insert between two commits, creating a merge
+    test_env.jj_cmd_ok(&repo_path, &["op", "restore", &setup_opid]);
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "b", "-A", "a", "-B", "c"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted 1 commits as follows:
+      kxryzmor 5193162d (empty) Revert "b"
+    Rebased 1 descendant commits
+    Working copy now at: royxmykx eb7c26ed c | c
+    Parent commit      : zsuskuln 1394f625 b | b
+    Parent commit      : kxryzmor 5193162d (empty) Revert "b"
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @    eb7c26ed3a0c c
+    ├─╮
+    │ ○  5193162d04c1 Revert "b"
+    │ │
+    │ │  This reverts commit 1394f625cbbddc4245af6505f4ef56b77dc27ba9.
+    ○ │  1394f625cbbd b
+    ├─╯
+    ○  2443ea76b0b1 a
+    ◆  000000000000
+    "###);
+
+    // Refuse to create a loop
+    test_env.jj_cmd_ok(&repo_path, &["op", "restore", &setup_opid]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["revert", "-r", "b", "-A", "c", "-B", "a"]);
+    insta::assert_snapshot!(stderr, @"Error: Refusing to create a loop: commit 7e4fbf4f2759 would be both an ancestor and a descendant of the reverted commits");
+}
+
+#[test]
+fn test_revert_description_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"templates.revert_description = '"Revert " ++ change_id.short() ++ ": " ++ description'"#,
+    );
+    create_commit(&test_env, &repo_path, "a", &[], &[("a", "a\n")]);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "a", "-d", "a"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted 1 commits as follows:
+      zsuskuln 673e9c9b Revert rlvkpnrzqnoo: a
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  673e9c9b1725 Revert rlvkpnrzqnoo: a
+    @  2443ea76b0b1 a
+    ◆  000000000000
+    "###);
+}
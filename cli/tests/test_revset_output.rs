@@ -630,6 +630,65 @@ fn test_all_modifier() {
     "###);
 }
 
+#[test]
+fn test_at_operation() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "-r@", "foo"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-mfirst"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-mnew"]);
+
+    // The working-copy commit and bookmarks are resolved at the operation
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "at_operation(@-, @)",
+            "-Tdescription",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"first");
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "-r", "at_operation(@--, foo) | foo"]);
+    insta::assert_snapshot!(stdout, @r###"
+    ○  qpvuntsm test.user@example.com 2001-02-03 08:05:09 foo ef6b9b66
+    │  (empty) first
+    ~
+
+    ○  qpvuntsm hidden test.user@example.com 2001-02-03 08:05:07 230dd059
+    │  (empty) (no description set)
+    ~
+    "###);
+
+    // Commits that became visible since the operation
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r", "all() ~ at_operation(@-, all())"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  zsuskuln test.user@example.com 2001-02-03 08:05:10 ba0d82b1
+    │  (empty) new
+    ~
+    "###);
+
+    let stderr =
+        test_env.jj_cmd_failure(&repo_path, &["log", "-r", "at_operation(@----------, @)"]);
+    insta::assert_snapshot!(stderr, @r#"Error: The "@----------" expression resolved to no operations"#);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-r", "at_operation(@-)"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse revset: Function "at_operation": Expected 2 arguments
+    Caused by:  --> 1:14
+      |
+    1 | at_operation(@-)
+      |              ^^
+      |
+      = Function "at_operation": Expected 2 arguments
+    "###);
+}
+
 /// Verifies that the committer_date revset honors the local time zone.
 /// This test cannot run on Windows because The TZ env var does not control
 /// chrono::Local on that platform.
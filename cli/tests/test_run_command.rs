@@ -0,0 +1,147 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(unix)]
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn create_commit(test_env: &TestEnvironment, repo_path: &Path, name: &str, parents: &[&str]) {
+    if parents.is_empty() {
+        test_env.jj_cmd_ok(repo_path, &["new", "root()", "-m", name]);
+    } else {
+        let mut args = vec!["new", "-m", name];
+        args.extend(parents);
+        test_env.jj_cmd_ok(repo_path, &args);
+    }
+    std::fs::write(repo_path.join(name), format!("{name}\n")).unwrap();
+    test_env.jj_cmd_ok(repo_path, &["bookmark", "create", name]);
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    test_env.jj_cmd_success(
+        repo_path,
+        &[
+            "log",
+            "-T",
+            r#"description.first_line() ++ " " ++ bookmarks"#,
+        ],
+    )
+}
+
+#[test]
+fn test_run_no_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    let op_id_before = test_env.current_operation_id(&repo_path);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["run", "-j1", "cat *", "-r", "a::"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    b
+    a
+    "###);
+    insta::assert_snapshot!(stderr, @r###"
+    Ran command in zsuskuln 1394f625 b | b: exit status 0
+    Ran command in rlvkpnrz 2443ea76 a | a: exit status 0
+    "###);
+    // Nothing was recorded
+    assert_eq!(test_env.current_operation_id(&repo_path), op_id_before);
+}
+
+#[test]
+fn test_run_rewrites_and_rebases() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["run", "echo $JJ_CHANGE_ID > id", "-r", "a|b"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Ran command in zsuskuln 1394f625 b | b: exit status 0
+    Ran command in rlvkpnrz 2443ea76 a | a: exit status 0
+    Rewrote 2 commits and rebased 1 descendant commits
+    Working copy now at: royxmykx e2f53b05 c | c
+    Parent commit      : zsuskuln 487eb002 b | b
+    Added 1 files, modified 0 files, removed 0 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  c c
+    ○  b b
+    ○  a a
+    ◆
+    "###);
+    // The change made in "b" wins over the change made in "a"
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "a", "id"]);
+    insta::assert_snapshot!(stdout, @"rlvkpnrzqnoowoytxnquwvuryrwnrmlp");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "c", "id"]);
+    insta::assert_snapshot!(stdout, @"zsuskulnrvyrovkzqrwmxqlsskqntxvp");
+    // The working copy was updated
+    let content = std::fs::read_to_string(repo_path.join("id")).unwrap();
+    insta::assert_snapshot!(content, @"zsuskulnrvyrovkzqrwmxqlsskqntxvp");
+}
+
+#[test]
+fn test_run_failure() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+
+    let assert = test_env
+        .jj_cmd(
+            &repo_path,
+            &["run", "test -f b && echo ok > b || exit 3", "-r", "a::"],
+        )
+        .assert()
+        .code(1);
+    let stderr = test_env.normalize_output(&crate::common::get_stderr_string(&assert));
+    insta::assert_snapshot!(stderr, @r###"
+    Ran command in zsuskuln 1394f625 b | b: exit status 0
+    Ran command in rlvkpnrz 2443ea76 a | a: exit status 3
+    Rewrote 1 commits and rebased 0 descendant commits
+    Working copy now at: zsuskuln 24080c24 b | b
+    Parent commit      : rlvkpnrz 2443ea76 a | a
+    Added 0 files, modified 1 files, removed 0 files
+    Error: The command failed in 1 of 2 revisions
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "b", "b"]);
+    insta::assert_snapshot!(stdout, @"ok");
+}
+
+#[test]
+fn test_run_immutable() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "a""#);
+
+    // Nothing is run in immutable commits, even if the command wouldn't modify
+    // any files
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["run", "cat a", "-r", "a"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Commit 2443ea76b0b1 is immutable
+    Hint: Pass `--ignore-immutable` or configure the set of immutable commits via `revset-aliases.immutable_heads()`.
+    "###);
+}
@@ -0,0 +1,216 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn init_native_repo(test_env: &TestEnvironment, name: &str) {
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["init", name, "--config-toml=ui.allow-init-native=true"],
+    );
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"separate(" ", change_id.short(), description.first_line(), bookmarks, if(conflict, "conflict"))"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template, "-r", "all()"])
+}
+
+fn get_bookmark_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    test_env.jj_cmd_success(repo_path, &["bookmark", "list", "--all-remotes", "--quiet"])
+}
+
+#[test]
+fn test_sync_fetch_and_push() {
+    let test_env = TestEnvironment::default();
+    init_native_repo(&test_env, "remote");
+    init_native_repo(&test_env, "local");
+    let remote_path = test_env.env_root().join("remote");
+    let local_path = test_env.env_root().join("local");
+    test_env.jj_cmd_ok(
+        &local_path,
+        &[
+            "config",
+            "set",
+            "--repo",
+            "sync.remotes.origin.path",
+            "../remote",
+        ],
+    );
+
+    // Create a conflicted commit in the remote repo
+    std::fs::write(remote_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&remote_path, &["describe", "-m", "a"]);
+    test_env.jj_cmd_ok(&remote_path, &["new", "root()", "-m", "b"]);
+    std::fs::write(remote_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&remote_path, &["new", "description(a)", "@", "-m", "merge"]);
+    test_env.jj_cmd_ok(&remote_path, &["bookmark", "create", "main"]);
+    let remote_log = get_log_output(&test_env, &remote_path);
+    insta::assert_snapshot!(remote_log, @r###"
+    @    royxmykxtrkr merge main conflict
+    ├─╮
+    │ ○  mzvwutvlkqwt b
+    ○ │  qpvuntsmwlqt a
+    ├─╯
+    ◆  zzzzzzzzzzzz
+    "###);
+
+    // Commits are copied with their change ids and conflicts
+    let (stdout, stderr) = test_env.jj_cmd_ok(&local_path, &["sync", "fetch"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Fetched 6 commits from origin
+    Updated remote bookmarks: main@origin
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &local_path), @r###"
+    ◆    royxmykxtrkr merge main@origin conflict
+    ├─╮
+    │ ◆  mzvwutvlkqwt b
+    ◆ │  qpvuntsmwlqt a
+    ├─╯
+    │ @  rlvkpnrzqnoo
+    ├─╯
+    ◆  zzzzzzzzzzzz
+    "###);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&local_path, &["sync", "fetch"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    // Predecessors are copied too
+    let stdout = test_env.jj_cmd_success(
+        &local_path,
+        &["evolog", "-r", "description(a)", "-T", "commit_id.short()"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    ◆  207572c4352a
+    ○  499635208b6e
+    ○  5b81ed628eef
+    "###);
+
+    // Push a new commit on top
+    test_env.jj_cmd_ok(&local_path, &["bookmark", "track", "main@origin"]);
+    test_env.jj_cmd_ok(&local_path, &["new", "main", "-m", "resolved"]);
+    std::fs::write(local_path.join("file"), "resolved\n").unwrap();
+    test_env.jj_cmd_ok(&local_path, &["bookmark", "set", "main", "-r", "@"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&local_path, &["sync", "push", "-b", "main"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Bookmark changes to push to origin:
+      Move bookmark main from 24236295005d to 9a61b8f94a7b
+    Warning: The working-copy commit in workspace 'default' became immutable, so a new commit has been created on top of it.
+    Working copy now at: nkmrtpmo 09d9479e (empty) (no description set)
+    Parent commit      : lylxulpl 9a61b8f9 main | resolved
+    "###);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &remote_path), @"main: lylxulpl 9a61b8f9 resolved");
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &local_path), @r###"
+    main: lylxulpl 9a61b8f9 resolved
+      @origin: lylxulpl 9a61b8f9 resolved
+    "###);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&local_path, &["sync", "push", "--all"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    // The push is rejected if the bookmark was moved on the remote
+    test_env.jj_cmd_ok(&remote_path, &["new", "main", "-m", "remote"]);
+    test_env.jj_cmd_ok(&remote_path, &["bookmark", "set", "main", "-r", "@"]);
+    test_env.jj_cmd_ok(&local_path, &["new", "main", "-m", "local"]);
+    test_env.jj_cmd_ok(&local_path, &["bookmark", "set", "main", "-r", "@"]);
+    let stderr = test_env.jj_cmd_failure(&local_path, &["sync", "push", "-b", "main"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Bookmark changes to push to origin:
+      Move bookmark main from 9a61b8f94a7b to ff98981afb39
+    Error: Bookmark main on the remote has been moved since it was last fetched
+    Hint: Run `jj sync fetch` to update the remote bookmarks, then try again.
+    "###);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&local_path, &["sync", "fetch"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Fetched 1 commits from origin
+    Updated remote bookmarks: main@origin
+    "###);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &local_path), @r###"
+    main (conflicted):
+      - lylxulpl 9a61b8f9 resolved
+      + wvuyspvk ff98981a (empty) local
+      + nmzmmopx ec137fbd (empty) remote
+      @origin (behind by 1 commits): nmzmmopx ec137fbd (empty) remote
+    "###);
+
+    // Deleting a bookmark deletes it on the remote
+    test_env.jj_cmd_ok(&local_path, &["bookmark", "delete", "main"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&local_path, &["sync", "push", "-b", "main"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Bookmark changes to push to origin:
+      Delete bookmark main from ec137fbd3bb2
+    "###);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &remote_path), @"");
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &local_path), @"");
+}
+
+#[test]
+fn test_sync_errors() {
+    let test_env = TestEnvironment::default();
+    init_native_repo(&test_env, "local");
+    let local_path = test_env.env_root().join("local");
+    test_env.jj_cmd_ok(&local_path, &["bookmark", "create", "main"]);
+
+    let stderr = test_env.jj_cmd_failure(&local_path, &["sync", "fetch"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No sync remote named 'origin'
+    Hint: Configure its path with `jj config set --repo sync.remotes.origin.path <path>`.
+    "###);
+
+    test_env.jj_cmd_ok(
+        &local_path,
+        &[
+            "config",
+            "set",
+            "--repo",
+            "sync.remotes.origin.path",
+            "../missing",
+        ],
+    );
+    let stderr = test_env.jj_cmd_failure(&local_path, &["sync", "fetch"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to load sync remote 'origin'
+    Caused by: There is no Jujutsu repo in $TEST_ENV/local/../missing
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&local_path, &["sync", "push", "-b", "unknown"]);
+    insta::assert_snapshot!(stderr, @"Error: No such bookmark: unknown");
+
+    // Git-backed repos can't be mixed with native repos
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "git-repo"]);
+    test_env.jj_cmd_ok(
+        &local_path,
+        &[
+            "config",
+            "set",
+            "--repo",
+            "sync.remotes.origin.path",
+            "../git-repo",
+        ],
+    );
+    let stderr = test_env.jj_cmd_failure(&local_path, &["sync", "push", "-b", "main"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Bookmark changes to push to origin:
+      Add bookmark main to 5b81ed628eef
+    Error: The remote repo uses the git backend, but this repo uses the local backend
+    "###);
+}
@@ -117,3 +117,150 @@ fn test_tag_list() {
     added_targets: commit2
     "###);
 }
+
+#[test]
+fn test_tag_create_set_delete() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = {
+        let mut git_repo_path = repo_path.clone();
+        git_repo_path.extend([".jj", "repo", "store", "git"]);
+        git2::Repository::open(git_repo_path).unwrap()
+    };
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-mcommit1"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-mcommit2"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "create", "v1", "-r@--"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Created 1 tags pointing to qpvuntsm caf975d0 (empty) commit1");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "create", "v1", "-r@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Tag already exists: v1
+    Hint: Use `jj tag set --allow-move` to update it.
+    "###);
+
+    // Names which aren't valid Git ref names are rejected
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["tag", "create", "v1..v2"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value 'v1..v2' for '<NAMES>...': Invalid tag name: "v1..v2"
+
+    For more information, try '--help'.
+    "###);
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["tag", "set", "v1 "]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value 'v1 ' for '<NAMES>...': Invalid tag name: "v1 "
+
+    For more information, try '--help'.
+    "###);
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["tag", "create", ""]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value '' for '<NAMES>...': Invalid tag name: ""
+
+    For more information, try '--help'.
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "set", "v1", "-r@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Refusing to move tag: v1
+    Hint: Use --allow-move to allow it.
+    "###);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["tag", "set", "--allow-move", "v1", "v2", "-r@-"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Created 1 tags pointing to kkmpptxz d0a19ea4 (empty) commit2
+    Moved 1 tags to kkmpptxz d0a19ea4 (empty) commit2
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["tag", "list"]), @r###"
+    v1: kkmpptxz d0a19ea4 (empty) commit2
+    v2: kkmpptxz d0a19ea4 (empty) commit2
+    "###);
+
+    // Tags are exported to the backing Git repo
+    test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+    let v1_oid = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .target()
+        .unwrap();
+    insta::assert_snapshot!(v1_oid.to_string(), @"d0a19ea4c52e69b88a71739481c05abfbcb2e768");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "delete", "v3"]);
+    insta::assert_snapshot!(stderr, @"Error: No such tag: v3");
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "delete", "glob:v*"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Deleted 2 tags.");
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["tag", "list"]), @"");
+    test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+    assert!(git_repo.find_reference("refs/tags/v1").is_err());
+}
+
+#[test]
+fn test_tag_create_annotated() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = {
+        let mut git_repo_path = repo_path.clone();
+        git_repo_path.extend([".jj", "repo", "store", "git"]);
+        git2::Repository::open(git_repo_path).unwrap()
+    };
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-mcommit1"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["tag", "create", "v1", "-r@-", "-m", "Release 1.0"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @"Created 1 tags pointing to qpvuntsm caf975d0 (empty) commit1");
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["tag", "list"]), @"v1: qpvuntsm caf975d0 (empty) commit1");
+
+    // The annotated tag is written to the backing Git repo immediately
+    let git_tag = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    assert_eq!(git_tag.message(), Some("Release 1.0\n"));
+    assert_eq!(git_tag.tagger().unwrap().name(), Some("Test User"));
+    insta::assert_snapshot!(git_tag.target_id().to_string(), @"caf975d0989a4e84d6d2fd1047ea03663244969c");
+
+    // Export and import don't replace the annotated tag
+    test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "import"]);
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v1").unwrap().target(),
+        Some(git_tag.id())
+    );
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["tag", "create", "root", "-r", "root()", "-m", "Root"],
+    );
+    insta::assert_snapshot!(stderr, @"Error: Cannot create annotated tags on the root commit");
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["tag", "create", "v2", "-r@-", "-m", "Release 2.0", "--sign"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No signing backend is configured
+    Hint: Set `signing.backend` to sign the tags.
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["tag", "create", "v2", "--sign"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the following required arguments were not provided:
+      --message <MESSAGE>
+
+    Usage: jj tag create --message <MESSAGE> --sign <NAMES>...
+
+    For more information, try '--help'.
+    "###);
+}
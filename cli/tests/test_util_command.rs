@@ -91,7 +91,7 @@ fn test_gc_operation_log() {
     // Now this doesn't work.
     let stderr = test_env.jj_cmd_failure(&repo_path, &["debug", "operation", &op_to_remove]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: No operation ID matching "54c5f04ab3dbb32ac86f805c819cb1ef78a3c9758ee560680a465e843fa0b05f42706d0641805905ef4bab550ca805effd1ba73d1980f6cdcc42126f008e51f4"
+    Error: No operation ID matching "9d5667a08dbc886350d9b09037ad229473c9daad97d9d0335b75d500044f32d3e6860c76a5a10be163928687f9138075ae8da3e840ad306d158321e38594870d"
     "###);
 }
 
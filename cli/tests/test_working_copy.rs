@@ -57,3 +57,65 @@ fn test_snapshot_large_file() {
     let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list"]);
     insta::assert_snapshot!(stdout, @"");
 }
+
+#[test]
+fn test_conflict_marker_style() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"ui.conflict-marker-style = "git""#);
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "base"]);
+    std::fs::write(repo_path.join("file"), "line 1\nline 2\nline 3\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "side-a"]);
+    std::fs::write(repo_path.join("file"), "line 1\nside a\nline 3\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(base)", "-m", "side-b"]);
+    std::fs::write(repo_path.join("file"), "line 1\nside b\nline 3\n").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["new", "description(side-a)", "description(side-b)"],
+    );
+
+    // The conflict is materialized with Git-style markers
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(), @r###"
+    line 1
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    side a
+    ||||||| Base
+    line 2
+    =======
+    side b
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    line 3
+    "###);
+    // Unchanged markers don't change the conflict
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Editing around the markers keeps the conflict
+    std::fs::write(
+        repo_path.join("file"),
+        "line 1 edited\n<<<<<<<\nside a\n|||||||\nline 2\n=======\nside b\n>>>>>>>\nline 3\n",
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    --- a/file
+    +++ b/file
+    @@ -1,4 +1,4 @@
+    -line 1
+    +line 1 edited
+     <<<<<<< Side #1 (Conflict 1 of 1)
+     side a
+     ||||||| Base
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]);
+    insta::assert_snapshot!(stdout, @"file    2-sided conflict");
+
+    // Removing the markers resolves the conflict
+    std::fs::write(repo_path.join("file"), "line 1\nside a and b\nline 3\n").unwrap();
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["resolve", "--list"]);
+    insta::assert_snapshot!(stderr, @"Error: No conflicts found at this revision");
+}
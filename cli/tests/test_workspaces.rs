@@ -501,14 +501,14 @@ fn test_workspaces_conflicting_edits() {
     "###);
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["st"]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: The working copy is stale (not updated since operation 0da24da631e3).
+    Error: The working copy is stale (not updated since operation 7a256bb44a83).
     Hint: Run `jj workspace update-stale` to update it.
     See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     "###);
     // Same error on second run, and from another command
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["log"]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: The working copy is stale (not updated since operation 0da24da631e3).
+    Error: The working copy is stale (not updated since operation 7a256bb44a83).
     Hint: Run `jj workspace update-stale` to update it.
     See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     "###);
@@ -588,7 +588,7 @@ fn test_workspaces_updated_by_other() {
     "###);
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["st"]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: The working copy is stale (not updated since operation 0da24da631e3).
+    Error: The working copy is stale (not updated since operation 7a256bb44a83).
     Hint: Run `jj workspace update-stale` to update it.
     See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     "###);
@@ -659,15 +659,15 @@ fn test_workspaces_current_op_discarded_by_other() {
         ],
     );
     insta::assert_snapshot!(stdout, @r###"
-    @  7337338f0b abandon commit 20dd439c4bd12c6ad56c187ac490bd0141804618f638dc5c4dc92ff9aecba20f152b23160db9dcf61beb31a5cb14091d9def5a36d11c9599cc4d2e5689236af1
-    ○  f4bd4d046b create initial working-copy commit in workspace secondary
-    ○  0f99641958 add workspace 'secondary'
-    ○  5641361f60 new empty commit
-    ○  3a6c319c59 snapshot working copy
-    ○  42c6005842 new empty commit
-    ○  6a45045541 snapshot working copy
-    ○  a9e6630bf0 add workspace 'default'
-    ○  cecfee9647 initialize repo
+    @  c735ca72ae abandon commit 14a8afec70514eeffbc0a49c41ab7a246f190b90db0b1e35f2b8267f106a5343c765ba0864a6076c62bfc4427da75c4b8d66b5343db543157500616137bcd5a7
+    ○  014071cdab create initial working-copy commit in workspace secondary
+    ○  1f559d6965 add workspace 'secondary'
+    ○  8275f9a1ad new empty commit
+    ○  355b830ba3 snapshot working copy
+    ○  7589b1ba56 new empty commit
+    ○  01699220d4 snapshot working copy
+    ○  01979317a1 add workspace 'default'
+    ○  268723bbff initialize repo
     ○  0000000000
     "###);
 
@@ -676,10 +676,10 @@ fn test_workspaces_current_op_discarded_by_other() {
     test_env.jj_cmd_ok(&main_path, &["util", "gc", "--expire=now"]);
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r###"
-    ○  96b31dafdc41 secondary@
-    │ @  6c051bd1ccd5 default@
+    ○  f344920b1511 secondary@
+    │ @  0e27f999f99f default@
     ├─╯
-    ○  7c5b25a4fc8f
+    ○  3eba60336e91
     ◆  000000000000
     "###);
 
@@ -692,17 +692,17 @@ fn test_workspaces_current_op_discarded_by_other() {
 
     let (stdout, stderr) = test_env.jj_cmd_ok(&secondary_path, &["workspace", "update-stale"]);
     insta::assert_snapshot!(stderr, @r###"
-    Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object f4bd4d046b3cdf61b0fda7738a0b1414c0aedc6c8229d39a35ee26facc358cad8b588b04d7eba1302a82409c529f69dbb1ff9ea28789d935b74f123f377aa30b of type operation not found
-    Created and checked out recovery commit 62f70695e3b0
+    Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object 014071cdab7166e99162c91a9dde0f04ab0698e0b8ee890a81905ab70d93057c734f7f25b20163b3c2a7da8ad3361ef3e27debb3ed0062adaf3d1e6f417507d3 of type operation not found
+    Created and checked out recovery commit d135e2f9c461
     "###);
     insta::assert_snapshot!(stdout, @"");
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r###"
-    ○  b0b400439a82 secondary@
-    ○  96b31dafdc41
-    │ @  6c051bd1ccd5 default@
+    ○  53739e2afe4e secondary@
+    ○  f344920b1511
+    │ @  0e27f999f99f default@
     ├─╯
-    ○  7c5b25a4fc8f
+    ○  3eba60336e91
     ◆  000000000000
     "###);
 
@@ -720,8 +720,8 @@ fn test_workspaces_current_op_discarded_by_other() {
     A added
     D deleted
     M modified
-    Working copy : kmkuslsw b0b40043 (no description set)
-    Parent commit: rzvqmyuk 96b31daf (empty) (no description set)
+    Working copy : kmkuslsw 53739e2a (no description set)
+    Parent commit: rzvqmyuk f344920b (empty) (no description set)
     "###);
     // The modified file should have the same contents it had before (not reset to
     // the base contents)
@@ -732,9 +732,9 @@ fn test_workspaces_current_op_discarded_by_other() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&secondary_path, &["evolog"]);
     insta::assert_snapshot!(stderr, @"");
     insta::assert_snapshot!(stdout, @r###"
-    @  kmkuslsw test.user@example.com 2001-02-03 08:05:18 secondary@ b0b40043
+    @  kmkuslsw test.user@example.com 2001-02-03 08:05:18 secondary@ 53739e2a
     │  (no description set)
-    ○  kmkuslsw hidden test.user@example.com 2001-02-03 08:05:18 62f70695
+    ○  kmkuslsw hidden test.user@example.com 2001-02-03 08:05:18 d135e2f9
        (empty) (no description set)
     "###);
 }
@@ -896,7 +896,7 @@ fn test_workspaces_forget_multi_transaction() {
     // the op log should have multiple workspaces forgotten in a single tx
     let stdout = test_env.jj_cmd_success(&main_path, &["op", "log", "--limit", "1"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  b7ab9f1c16cc test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
+    @  f224e65e2a0b test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
     │  forget workspaces second, third
     │  args: jj workspace forget second third
     "###);
@@ -1052,27 +1052,27 @@ fn test_debug_snapshot() {
     test_env.jj_cmd_ok(&repo_path, &["debug", "snapshot"]);
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  e1e762d39b39 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    @  77e514661aac test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  snapshot working copy
     │  args: jj debug snapshot
-    ○  b51416386f26 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  401227d3f4b4 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
-    ○  9a7d829846af test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  cf7914c575b6 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  initialize repo
     ○  000000000000 root()
     "###);
     test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "initial"]);
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  9ac6e7144e8a test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
+    @  3b0d86726a0e test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
     │  describe commit 4e8f9d2be039994f589b4e57ac5e9488703e604d
     │  args: jj describe -m initial
-    ○  e1e762d39b39 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    ○  77e514661aac test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  snapshot working copy
     │  args: jj debug snapshot
-    ○  b51416386f26 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  401227d3f4b4 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
-    ○  9a7d829846af test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  cf7914c575b6 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  initialize repo
     ○  000000000000 root()
     "###);
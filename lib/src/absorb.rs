@@ -0,0 +1,306 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Splitting of the changes in a commit into the ancestors that last modified
+//! the same lines, "absorbing" them.
+
+use std::collections::HashMap;
+use std::io::Read as _;
+use std::ops::Range;
+use std::rc::Rc;
+
+use bstr::BString;
+use futures::executor::block_on_stream;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::annotate::get_annotation_with_file_content;
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::CommitId;
+use crate::backend::FileId;
+use crate::backend::TreeValue;
+use crate::commit::Commit;
+use crate::diff::find_line_ranges;
+use crate::diff::Diff;
+use crate::diff::DiffHunk;
+use crate::matchers::Matcher;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
+use crate::merged_tree::TreeDiffEntry;
+use crate::repo::MutableRepo;
+use crate::repo::Repo;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::revset::RevsetEvaluationError;
+use crate::revset::RevsetExpression;
+use crate::settings::UserSettings;
+use crate::store::Store;
+
+/// The commit whose changes are absorbed into its ancestors.
+#[derive(Clone, Debug)]
+pub struct AbsorbSource {
+    commit: Commit,
+    parent_tree: MergedTree,
+}
+
+impl AbsorbSource {
+    /// Creates an absorb source from the changes in `commit`.
+    pub fn from_commit(repo: &dyn Repo, commit: Commit) -> BackendResult<Self> {
+        let parent_tree = commit.parent_tree(repo)?;
+        Ok(AbsorbSource {
+            commit,
+            parent_tree,
+        })
+    }
+
+    /// The commit the changes are absorbed from.
+    pub fn commit(&self) -> &Commit {
+        &self.commit
+    }
+
+    /// The tree the changes in the commit are relative to.
+    pub fn parent_tree(&self) -> &MergedTree {
+        &self.parent_tree
+    }
+}
+
+/// Error from splitting the changes of an absorb source.
+#[derive(Debug, Error)]
+pub enum AbsorbError {
+    /// Error from the backend.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    /// Error from evaluating the destination revisions.
+    #[error(transparent)]
+    RevsetEvaluation(#[from] RevsetEvaluationError),
+}
+
+/// The changes of an absorb source, split up by the commit they should be
+/// moved into.
+#[derive(Default)]
+pub struct SelectedTrees {
+    /// The changes to move into each commit. The trees are based on the parent
+    /// tree of the source.
+    pub target_commits: HashMap<CommitId, MergedTreeBuilder>,
+    /// Paths with changes that were left in the source, and why.
+    pub skipped_paths: Vec<(RepoPathBuf, String)>,
+}
+
+/// Splits the changes in `source` by the commit in `destinations` that last
+/// modified the lines around them.
+///
+/// Only modified files are considered. A change is left in the source if the
+/// lines it replaces, or the lines around an insertion, weren't all last
+/// modified by the same commit in `destinations`.
+pub fn split_hunks_to_trees(
+    repo: &dyn Repo,
+    source: &AbsorbSource,
+    destinations: &Rc<RevsetExpression>,
+    matcher: &dyn Matcher,
+) -> Result<SelectedTrees, AbsorbError> {
+    let store = repo.store();
+    let mut selected_trees = SelectedTrees::default();
+    let left_tree = &source.parent_tree;
+    let right_tree = source.commit.tree()?;
+    let diff_stream = left_tree.diff_stream(&right_tree, matcher);
+    for TreeDiffEntry { path, values } in block_on_stream(diff_stream) {
+        let (left_value, right_value) = values?;
+        let (left_id, right_id, executable) =
+            match (left_value.as_resolved(), right_value.as_resolved()) {
+                (
+                    Some(Some(TreeValue::File { id: left_id, .. })),
+                    Some(Some(TreeValue::File {
+                        id: right_id,
+                        executable,
+                    })),
+                ) => (left_id, right_id, *executable),
+                (Some(None), _) => {
+                    selected_trees
+                        .skipped_paths
+                        .push((path, "Added files can't be absorbed".to_owned()));
+                    continue;
+                }
+                (_, Some(None)) => {
+                    selected_trees
+                        .skipped_paths
+                        .push((path, "Deleted files can't be absorbed".to_owned()));
+                    continue;
+                }
+                _ => {
+                    selected_trees.skipped_paths.push((
+                        path,
+                        "Only resolved regular files can be absorbed".to_owned(),
+                    ));
+                    continue;
+                }
+            };
+        let left_text = read_file_contents(store, &path, left_id)?;
+        let right_text = read_file_contents(store, &path, right_id)?;
+        let annotations = get_annotation_with_file_content(
+            repo,
+            &source.commit,
+            destinations,
+            &path,
+            &left_text,
+        )?;
+        let (selected_ranges, num_skipped_hunks) =
+            split_file_hunks(&left_text, &right_text, &annotations, source.commit.id());
+        if num_skipped_hunks > 0 {
+            selected_trees.skipped_paths.push((
+                path.clone(),
+                format!(
+                    "{num_skipped_hunks} hunk(s) couldn't be attributed to a single revision and \
+                     were left in place"
+                ),
+            ));
+        }
+        for (commit_id, ranges) in selected_ranges {
+            let mut new_text = Vec::new();
+            let mut last_end = 0;
+            for (left_range, right_content) in ranges {
+                new_text.extend_from_slice(&left_text[last_end..left_range.start]);
+                new_text.extend_from_slice(right_content);
+                last_end = left_range.end;
+            }
+            new_text.extend_from_slice(&left_text[last_end..]);
+            let id = store
+                .write_file(&path, &mut new_text.as_slice())
+                .block_on()?;
+            let value = TreeValue::File { id, executable };
+            selected_trees
+                .target_commits
+                .entry(commit_id)
+                .or_insert_with(|| MergedTreeBuilder::new(left_tree.id()))
+                .set_or_remove(path.clone(), Merge::normal(value));
+        }
+    }
+    Ok(selected_trees)
+}
+
+type SelectedRanges<'a> = HashMap<CommitId, Vec<(Range<usize>, &'a [u8])>>;
+
+/// Assigns the changed hunks between `left_text` and `right_text` to the
+/// commits that last modified the surrounding lines. Returns the byte ranges in
+/// `left_text` to replace for each commit, and the number of hunks that
+/// couldn't be assigned.
+fn split_file_hunks<'a>(
+    left_text: &[u8],
+    right_text: &'a [u8],
+    annotations: &[Option<CommitId>],
+    source_id: &CommitId,
+) -> (SelectedRanges<'a>, usize) {
+    let mut selected_ranges = SelectedRanges::new();
+    let mut num_skipped_hunks = 0;
+    let mut left_offset = 0;
+    let mut right_offset = 0;
+    let mut left_line = 0;
+    for hunk in Diff::by_line([left_text, right_text]).hunks() {
+        match hunk {
+            DiffHunk::Matching(text) => {
+                left_offset += text.len();
+                right_offset += text.len();
+                left_line += find_line_ranges(text).len();
+            }
+            DiffHunk::Different(contents) => {
+                let num_left_lines = find_line_ranges(contents[0]).len();
+                let neighbors = if num_left_lines == 0 {
+                    // An insertion belongs with the lines around it
+                    left_line.saturating_sub(1)..(left_line + 1).min(annotations.len())
+                } else {
+                    left_line..left_line + num_left_lines
+                };
+                let destination = annotations[neighbors]
+                    .iter()
+                    .map(|commit_id| commit_id.as_ref())
+                    .reduce(|a, b| if a == b { a } else { None })
+                    .flatten()
+                    .filter(|&commit_id| commit_id != source_id);
+                let left_range = left_offset..left_offset + contents[0].len();
+                let right_range = right_offset..right_offset + contents[1].len();
+                match destination {
+                    Some(commit_id) => {
+                        selected_ranges
+                            .entry(commit_id.clone())
+                            .or_default()
+                            .push((left_range, &right_text[right_range]));
+                    }
+                    None => num_skipped_hunks += 1,
+                }
+                left_offset += contents[0].len();
+                right_offset += contents[1].len();
+                left_line += num_left_lines;
+            }
+        }
+    }
+    (selected_ranges, num_skipped_hunks)
+}
+
+fn read_file_contents(store: &Store, path: &RepoPath, id: &FileId) -> BackendResult<BString> {
+    let mut contents = vec![];
+    store
+        .read_file(path, id)?
+        .read_to_end(&mut contents)
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    Ok(contents.into())
+}
+
+/// The result of [`absorb_hunks()`].
+#[derive(Debug)]
+pub struct AbsorbStats {
+    /// The rewritten destination commits.
+    pub rewritten_destinations: Vec<Commit>,
+    /// The number of other descendants that were rebased.
+    pub num_rebased: usize,
+}
+
+/// Moves the changes in `selected_trees` from `source` into the destination
+/// commits, and rebases their descendants. The changes are removed from the
+/// source when it's rebased onto the rewritten destinations.
+pub fn absorb_hunks(
+    repo: &mut MutableRepo,
+    source: &AbsorbSource,
+    mut selected_trees: HashMap<CommitId, MergedTreeBuilder>,
+    settings: &UserSettings,
+) -> BackendResult<AbsorbStats> {
+    let store = repo.store().clone();
+    let mut rewritten_destinations = Vec::new();
+    let mut num_rebased = 0;
+    let roots = selected_trees.keys().cloned().collect();
+    repo.transform_descendants(settings, roots, |rewriter| {
+        let Some(tree_builder) = selected_trees.remove(rewriter.old_commit().id()) else {
+            if rewriter.parents_changed() {
+                rewriter.rebase(settings)?.write()?;
+                num_rebased += 1;
+            }
+            return Ok(());
+        };
+        let selected_tree = store.get_root_tree(&tree_builder.write_tree(&store)?)?;
+        let commit_builder = rewriter.rebase(settings)?;
+        let destination_tree = store.get_root_tree(commit_builder.tree_id())?;
+        let new_tree = destination_tree.merge(&source.parent_tree, &selected_tree)?;
+        let new_commit = commit_builder.set_tree_id(new_tree.id()).write()?;
+        rewritten_destinations.push(new_commit);
+        Ok(())
+    })?;
+    Ok(AbsorbStats {
+        rewritten_destinations,
+        num_rebased,
+    })
+}
@@ -0,0 +1,308 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Attribution of the lines of a file to the commits that introduced them.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Read as _;
+use std::rc::Rc;
+
+use bstr::BString;
+use futures::executor::block_on_stream;
+use pollster::FutureExt as _;
+
+use crate::backend::BackendError;
+use crate::backend::CommitId;
+use crate::commit::Commit;
+use crate::conflicts::materialize_merge_result;
+use crate::conflicts::materialize_tree_value;
+use crate::conflicts::ConflictMarkerStyle;
+use crate::conflicts::MaterializedTreeValue;
+use crate::diff::find_line_ranges;
+use crate::diff::Diff;
+use crate::diff::DiffHunk;
+use crate::merged_tree::MergedTree;
+use crate::repo::Repo;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::revset::RevsetEvaluationError;
+use crate::revset::RevsetExpression;
+use crate::store::Store;
+
+/// The lines of a file, each attributed to the commit that introduced it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AnnotateResults {
+    /// The lines of the file in order, including their line terminators. Each
+    /// line comes with the id of the commit that last changed it.
+    pub file_annotations: Vec<(CommitId, BString)>,
+}
+
+/// The lines of the starting file that are yet to be attributed, as found in
+/// the file of one of its ancestors.
+struct PendingFile {
+    path: RepoPathBuf,
+    text: BString,
+    /// Maps line numbers in `text` to line numbers in the starting file.
+    line_map: Vec<(usize, usize)>,
+}
+
+/// Attributes each line of the file at `file_path` in `starting_commit` to the
+/// commit that introduced it.
+///
+/// The ancestors of `starting_commit` are visited in reverse topological order.
+/// A line is attributed to a commit if none of its parents contain it
+/// according to a line diff. If the file was renamed and `follow_renames` is
+/// set, the copy records from the backend are used to find the file in the
+/// parents. Returns `None` if the path isn't a file in `starting_commit`.
+pub fn get_annotation_for_file(
+    repo: &dyn Repo,
+    starting_commit: &Commit,
+    file_path: &RepoPath,
+    follow_renames: bool,
+) -> Result<Option<AnnotateResults>, RevsetEvaluationError> {
+    let store = repo.store();
+    let tree = starting_commit
+        .tree()
+        .map_err(RevsetEvaluationError::StoreError)?;
+    let Some(text) =
+        get_file_contents(store, file_path, &tree).map_err(RevsetEvaluationError::StoreError)?
+    else {
+        return Ok(None);
+    };
+    let ancestors = RevsetExpression::commit(starting_commit.id().clone())
+        .ancestors()
+        .evaluate_programmatic(repo)?;
+    let annotations = annotate_lines(
+        repo,
+        starting_commit,
+        file_path,
+        &text,
+        ancestors.iter(),
+        |_| true,
+        follow_renames,
+    )
+    .map_err(RevsetEvaluationError::StoreError)?;
+    let file_annotations = annotations
+        .into_iter()
+        .zip(split_lines(&text))
+        .map(|(commit_id, line)| {
+            let commit_id = commit_id.expect("the root commit has no files");
+            (commit_id, line)
+        })
+        .collect();
+    Ok(Some(AnnotateResults { file_annotations }))
+}
+
+/// Attributes each line of `starting_text` to the commit that introduced it,
+/// as if `starting_text` were the contents of the file at `file_path` in
+/// `starting_commit`.
+///
+/// Only the commits in `domain` are visited. Lines that were introduced outside
+/// of the domain aren't attributed to any commit. Lines that aren't in any of
+/// the parents of `starting_commit` are attributed to `starting_commit`.
+pub fn get_annotation_with_file_content(
+    repo: &dyn Repo,
+    starting_commit: &Commit,
+    domain: &Rc<RevsetExpression>,
+    file_path: &RepoPath,
+    starting_text: &[u8],
+) -> Result<Vec<Option<CommitId>>, RevsetEvaluationError> {
+    let ancestors = RevsetExpression::commit(starting_commit.id().clone()).ancestors();
+    let domain: HashSet<_> = domain
+        .intersection(&ancestors)
+        .evaluate_programmatic(repo)?
+        .iter()
+        .collect();
+    annotate_lines(
+        repo,
+        starting_commit,
+        file_path,
+        starting_text,
+        ancestors.evaluate_programmatic(repo)?.iter(),
+        |commit_id| domain.contains(commit_id),
+        false,
+    )
+    .map_err(RevsetEvaluationError::StoreError)
+}
+
+/// Splits `text` into lines, including their line terminators.
+fn split_lines(text: &[u8]) -> impl Iterator<Item = BString> + '_ {
+    find_line_ranges(text)
+        .into_iter()
+        .map(|range| BString::from(&text[range]))
+}
+
+/// Annotates the lines of `text` by visiting `ancestors`, which must be in
+/// reverse topological order. Lines aren't followed into parents outside of the
+/// domain.
+fn annotate_lines(
+    repo: &dyn Repo,
+    starting_commit: &Commit,
+    file_path: &RepoPath,
+    text: &[u8],
+    ancestors: impl Iterator<Item = CommitId>,
+    is_in_domain: impl Fn(&CommitId) -> bool,
+    follow_renames: bool,
+) -> Result<Vec<Option<CommitId>>, BackendError> {
+    let store = repo.store();
+    let num_lines = find_line_ranges(text).len();
+    let mut annotations: Vec<Option<CommitId>> = vec![None; num_lines];
+    let mut pending: HashMap<CommitId, PendingFile> = HashMap::new();
+    pending.insert(
+        starting_commit.id().clone(),
+        PendingFile {
+            path: file_path.to_owned(),
+            text: text.into(),
+            line_map: (0..num_lines).map(|line| (line, line)).collect(),
+        },
+    );
+
+    for commit_id in ancestors {
+        let Some(file) = pending.remove(&commit_id) else {
+            continue;
+        };
+        let commit = store.get_commit(&commit_id)?;
+        let mut unattributed = file.line_map;
+        for parent in commit.parents() {
+            let parent = parent?;
+            if unattributed.is_empty() {
+                break;
+            }
+            let parent_tree = parent.tree()?;
+            let Some(parent_path) = find_parent_path(
+                repo,
+                &file.path,
+                &parent_tree,
+                parent.id(),
+                commit.id(),
+                follow_renames,
+            )?
+            else {
+                continue;
+            };
+            let Some(parent_text) = get_file_contents(store, &parent_path, &parent_tree)? else {
+                continue;
+            };
+            let same_lines = get_same_line_map(&parent_text, &file.text);
+            let (moved, rest): (Vec<_>, Vec<_>) = unattributed
+                .into_iter()
+                .partition(|(line, _)| same_lines.contains_key(line));
+            unattributed = rest;
+            if moved.is_empty() || !is_in_domain(parent.id()) {
+                continue;
+            }
+            let parent_file = pending
+                .entry(parent.id().clone())
+                .or_insert_with(|| PendingFile {
+                    path: parent_path,
+                    text: parent_text,
+                    line_map: vec![],
+                });
+            parent_file.line_map.extend(
+                moved
+                    .into_iter()
+                    .map(|(line, original_line)| (same_lines[&line], original_line)),
+            );
+        }
+        for (_, original_line) in unattributed {
+            annotations[original_line] = Some(commit_id.clone());
+        }
+        if pending.is_empty() {
+            break;
+        }
+    }
+    Ok(annotations)
+}
+
+/// Finds the path of the file in the parent tree. If the file doesn't exist at
+/// the same path, it may have been renamed in the child.
+fn find_parent_path(
+    repo: &dyn Repo,
+    path: &RepoPath,
+    parent_tree: &MergedTree,
+    parent_id: &CommitId,
+    commit_id: &CommitId,
+    follow_renames: bool,
+) -> Result<Option<RepoPathBuf>, BackendError> {
+    if !parent_tree.path_value(path)?.is_absent() || !follow_renames {
+        return Ok(Some(path.to_owned()));
+    }
+    let paths = [path.to_owned()];
+    let records =
+        repo.store()
+            .get_copy_records(Some(&paths), parent_id, commit_id, repo.index())?;
+    for record in block_on_stream(records) {
+        let record = record?;
+        if record.target.as_ref() == path && !parent_tree.path_value(&record.source)?.is_absent() {
+            return Ok(Some(record.source));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads the contents of the file at `path`. Conflicts are materialized with
+/// conflict markers. Returns `None` if the path isn't a file.
+fn get_file_contents(
+    store: &Store,
+    path: &RepoPath,
+    tree: &MergedTree,
+) -> Result<Option<BString>, BackendError> {
+    let value = tree.path_value(path)?;
+    let materialized = materialize_tree_value(store, path, value).block_on()?;
+    match materialized {
+        MaterializedTreeValue::File { id, mut reader, .. } => {
+            let mut contents = vec![];
+            reader
+                .read_to_end(&mut contents)
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.to_owned(),
+                    id,
+                    source: err.into(),
+                })?;
+            Ok(Some(contents.into()))
+        }
+        MaterializedTreeValue::FileConflict { contents, .. } => {
+            let mut materialized = vec![];
+            materialize_merge_result(&contents, ConflictMarkerStyle::default(), &mut materialized)
+                .expect("writing to an in-memory buffer should never fail");
+            Ok(Some(materialized.into()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Maps the line numbers in `current_text` to the line numbers in
+/// `parent_text` of the lines that are unchanged between them.
+fn get_same_line_map(parent_text: &[u8], current_text: &[u8]) -> HashMap<usize, usize> {
+    let mut same_lines = HashMap::new();
+    let mut parent_line = 0;
+    let mut current_line = 0;
+    for hunk in Diff::by_line([parent_text, current_text]).hunks() {
+        match hunk {
+            DiffHunk::Matching(text) => {
+                for _ in find_line_ranges(text) {
+                    same_lines.insert(current_line, parent_line);
+                    parent_line += 1;
+                    current_line += 1;
+                }
+            }
+            DiffHunk::Different(contents) => {
+                parent_line += find_line_ranges(contents[0]).len();
+                current_line += find_line_ranges(contents[1]).len();
+            }
+        }
+    }
+    same_lines
+}
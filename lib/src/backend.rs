@@ -140,6 +140,19 @@ pub struct Commit {
     pub author: Signature,
     pub committer: Signature,
     pub secure_sig: Option<SecureSig>,
+    /// Copies and renames from the parents' trees to `root_tree`. Not all
+    /// backends store these; the Git backend detects copies when they're
+    /// requested instead.
+    pub copies: Vec<CommitCopy>,
+}
+
+/// A copy or rename recorded with a commit.
+#[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
+pub struct CommitCopy {
+    /// The path in a parent tree the file was copied from.
+    pub source: RepoPathBuf,
+    /// The path in the commit's tree the file was copied to.
+    pub target: RepoPathBuf,
 }
 
 #[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
@@ -376,6 +389,7 @@ pub fn make_root_commit(root_change_id: ChangeId, empty_tree_id: TreeId) -> Comm
         author: signature.clone(),
         committer: signature,
         secure_sig: None,
+        copies: vec![],
     }
 }
 
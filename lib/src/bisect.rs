@@ -0,0 +1,92 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bisection of the commit history to find the commit that introduced a bug.
+
+use std::rc::Rc;
+
+use crate::backend::CommitId;
+use crate::op_store::BisectState;
+use crate::repo::Repo;
+use crate::revset::RevsetEvaluationError;
+use crate::revset::RevsetExpression;
+
+/// What to do next in a bisection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BisectionStep {
+    /// The commit should be tested next.
+    Test {
+        /// The commit to test.
+        commit_id: CommitId,
+        /// Number of commits left to test, including `commit_id`.
+        num_untested: usize,
+    },
+    /// There are no commits left to test. Contains the commits that may be the
+    /// first bad commit, which is a single commit unless some of the commits
+    /// were skipped.
+    Done(Vec<CommitId>),
+}
+
+impl BisectState {
+    /// Creates a new bisection state with the given good and bad commits.
+    pub fn new(good: Vec<CommitId>, bad: Vec<CommitId>) -> Self {
+        BisectState {
+            good,
+            bad,
+            skipped: vec![],
+        }
+    }
+
+    /// Commits that may be the first bad commit: the common ancestors of the
+    /// bad commits which aren't ancestors of any of the good commits.
+    pub fn range(&self) -> Rc<RevsetExpression> {
+        let bad_ancestors = self
+            .bad
+            .iter()
+            .map(|id| RevsetExpression::commit(id.clone()).ancestors())
+            .reduce(|acc, ancestors| acc.intersection(&ancestors))
+            .unwrap_or_else(|| RevsetExpression::visible_heads().ancestors());
+        bad_ancestors.minus(&RevsetExpression::commits(self.good.clone()).ancestors())
+    }
+
+    /// Commits in the range that haven't been marked as bad or skipped.
+    pub fn untested(&self) -> Rc<RevsetExpression> {
+        let marked = RevsetExpression::commits(
+            itertools::chain(&self.bad, &self.skipped)
+                .cloned()
+                .collect(),
+        );
+        self.range().minus(&marked)
+    }
+
+    /// Picks the commit to test next, or the first bad commit if there are no
+    /// commits left to test.
+    pub fn next_step(&self, repo: &dyn Repo) -> Result<BisectionStep, RevsetEvaluationError> {
+        let untested = self.untested();
+        let num_untested = untested.clone().evaluate_programmatic(repo)?.iter().count();
+        if let Some(commit_id) = untested.bisect().evaluate_programmatic(repo)?.iter().next() {
+            return Ok(BisectionStep::Test {
+                commit_id,
+                num_untested,
+            });
+        }
+        // Every commit in the range is now known to be bad or was skipped. Bad
+        // commits can't be the first bad one if they have bad ancestors.
+        let range = self.range();
+        let bad_in_range = range.intersection(&RevsetExpression::commits(self.bad.clone()));
+        let candidates = range.minus(&bad_in_range.children().descendants());
+        let candidate_ids = candidates.evaluate_programmatic(repo)?.iter().collect();
+        Ok(BisectionStep::Done(candidate_ids))
+    }
+}
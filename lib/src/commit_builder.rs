@@ -14,17 +14,21 @@
 
 #![allow(missing_docs)]
 
+use std::mem;
 use std::sync::Arc;
 
+use itertools::Itertools as _;
 use pollster::FutureExt;
 
 use crate::backend;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
+use crate::backend::CommitCopy;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
 use crate::backend::Signature;
 use crate::commit::Commit;
+use crate::merged_tree::MergedTree;
 use crate::repo::MutableRepo;
 use crate::repo::Repo;
 use crate::settings::JJRng;
@@ -73,6 +77,15 @@ impl CommitBuilder<'_> {
         self
     }
 
+    pub fn copies(&self) -> &[CommitCopy] {
+        self.inner.copies()
+    }
+
+    pub fn set_copies(mut self, copies: Vec<CommitCopy>) -> Self {
+        self.inner.set_copies(copies);
+        self
+    }
+
     pub fn change_id(&self) -> &ChangeId {
         self.inner.change_id()
     }
@@ -165,6 +178,7 @@ impl DetachedCommitBuilder {
             author: signature.clone(),
             committer: signature,
             secure_sig: None,
+            copies: vec![],
         };
         DetachedCommitBuilder {
             store,
@@ -254,6 +268,15 @@ impl DetachedCommitBuilder {
         self
     }
 
+    pub fn copies(&self) -> &[CommitCopy] {
+        &self.commit.copies
+    }
+
+    pub fn set_copies(&mut self, copies: Vec<CommitCopy>) -> &mut Self {
+        self.commit.copies = copies;
+        self
+    }
+
     pub fn change_id(&self) -> &ChangeId {
         &self.commit.change_id
     }
@@ -342,8 +365,38 @@ fn write_to_store(
     // assert, but sign_settings.should_sign check above will want to know
     // if we're rewriting a signed commit
     commit.secure_sig = None;
+    retain_valid_copies(store, &mut commit)?;
 
     store
         .write_commit(commit, should_sign.then_some(&mut &sign_fn))
         .block_on()
 }
+
+/// Drops the copies that no longer describe a file added by the commit from a
+/// file in one of its parents. Copies are recorded against the tree and the
+/// parents at the time, so they go stale when a rewrite changes either.
+fn retain_valid_copies(store: &Arc<Store>, commit: &mut backend::Commit) -> BackendResult<()> {
+    if commit.copies.is_empty() {
+        return Ok(());
+    }
+    let parent_trees: Vec<MergedTree> = commit
+        .parents
+        .iter()
+        .map(|id| store.get_commit(id)?.tree())
+        .try_collect()?;
+    let tree = store.get_root_tree(&commit.root_tree)?;
+    let mut copies = vec![];
+    for copy in mem::take(&mut commit.copies) {
+        let mut source_in_parents = false;
+        let mut target_in_parents = false;
+        for parent_tree in &parent_trees {
+            source_in_parents |= parent_tree.path_value(&copy.source)?.is_present();
+            target_in_parents |= parent_tree.path_value(&copy.target)?.is_present();
+        }
+        if source_in_parents && !target_in_parents && tree.path_value(&copy.target)?.is_present() {
+            copies.push(copy);
+        }
+    }
+    commit.copies = copies;
+    Ok(())
+}
@@ -53,11 +53,15 @@ const CONFLICT_END_LINE: &[u8] = b">>>>>>>";
 const CONFLICT_DIFF_LINE: &[u8] = b"%%%%%%%";
 const CONFLICT_MINUS_LINE: &[u8] = b"-------";
 const CONFLICT_PLUS_LINE: &[u8] = b"+++++++";
+const CONFLICT_GIT_ANCESTOR_LINE: &[u8] = b"|||||||";
+const CONFLICT_GIT_SEPARATOR_LINE: &[u8] = b"=======";
 const CONFLICT_START_LINE_CHAR: u8 = CONFLICT_START_LINE[0];
 const CONFLICT_END_LINE_CHAR: u8 = CONFLICT_END_LINE[0];
 const CONFLICT_DIFF_LINE_CHAR: u8 = CONFLICT_DIFF_LINE[0];
 const CONFLICT_MINUS_LINE_CHAR: u8 = CONFLICT_MINUS_LINE[0];
 const CONFLICT_PLUS_LINE_CHAR: u8 = CONFLICT_PLUS_LINE[0];
+const CONFLICT_GIT_ANCESTOR_LINE_CHAR: u8 = CONFLICT_GIT_ANCESTOR_LINE[0];
+const CONFLICT_GIT_SEPARATOR_LINE_CHAR: u8 = CONFLICT_GIT_SEPARATOR_LINE[0];
 
 /// A conflict marker is one of the separators, optionally followed by a space
 /// and some text.
@@ -65,7 +69,7 @@ const CONFLICT_PLUS_LINE_CHAR: u8 = CONFLICT_PLUS_LINE[0];
 // separators. This could be useful to make it possible to allow conflict
 // markers inside the text of the conflicts.
 static CONFLICT_MARKER_REGEX: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
-    RegexBuilder::new(r"^(<{7}|>{7}|%{7}|\-{7}|\+{7})( .*)?$")
+    RegexBuilder::new(r"^(<{7}|>{7}|%{7}|\-{7}|\+{7}|\|{7}|={7})( .*)?$")
         .multi_line(true)
         .build()
         .unwrap()
@@ -229,8 +233,31 @@ async fn materialize_tree_value_no_access_denied(
     }
 }
 
+/// Describes what style should be used when materializing conflicts.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictMarkerStyle {
+    /// Print the diff from a base to each side, or the contents of a side if
+    /// there's no base left to diff against.
+    #[default]
+    Diff,
+    /// Print the contents of every side and base in full.
+    Snapshot,
+    /// Use Git's "diff3" conflict markers for conflicts with two sides, and the
+    /// snapshot style for conflicts with more sides.
+    Git,
+}
+
+/// Writes the conflict markers for `single_hunk` to `output`.
+///
+/// Recorded resolutions aren't applied here. Files whose hunks all have
+/// resolutions are already resolved when the trees are merged, and applying
+/// only some of them would make the materialized file disagree with the
+/// conflict in the tree, so snapshotting it would record resolutions the user
+/// never made.
 pub fn materialize_merge_result(
     single_hunk: &Merge<BString>,
+    conflict_marker_style: ConflictMarkerStyle,
     output: &mut dyn Write,
 ) -> std::io::Result<()> {
     let merge_result = files::merge(single_hunk);
@@ -249,80 +276,18 @@ pub fn materialize_merge_result(
                     output.write_all(content)?;
                 } else {
                     conflict_index += 1;
-                    output.write_all(CONFLICT_START_LINE)?;
-                    output.write_all(
-                        format!(" Conflict {conflict_index} of {num_conflicts}\n").as_bytes(),
-                    )?;
-                    let mut add_index = 0;
-                    for (base_index, left) in hunk.removes().enumerate() {
-                        // The vast majority of conflicts one actually tries to
-                        // resolve manually have 1 base.
-                        let base_str = if hunk.removes().len() == 1 {
-                            "base".to_string()
-                        } else {
-                            format!("base #{}", base_index + 1)
-                        };
-
-                        let right1 = if let Some(right1) = hunk.get_add(add_index) {
-                            right1
-                        } else {
-                            // If we have no more positive terms, emit the remaining negative
-                            // terms as snapshots.
-                            output.write_all(CONFLICT_MINUS_LINE)?;
-                            output.write_all(format!(" Contents of {base_str}\n").as_bytes())?;
-                            output.write_all(left)?;
-                            continue;
-                        };
-                        let diff1 = Diff::by_line([&left, &right1]).hunks().collect_vec();
-                        // Check if the diff against the next positive term is better. Since
-                        // we want to preserve the order of the terms, we don't match against
-                        // any later positive terms.
-                        if let Some(right2) = hunk.get_add(add_index + 1) {
-                            let diff2 = Diff::by_line([&left, &right2]).hunks().collect_vec();
-                            if diff_size(&diff2) < diff_size(&diff1) {
-                                // If the next positive term is a better match, emit
-                                // the current positive term as a snapshot and the next
-                                // positive term as a diff.
-                                output.write_all(CONFLICT_PLUS_LINE)?;
-                                output.write_all(
-                                    format!(" Contents of side #{}\n", add_index + 1).as_bytes(),
-                                )?;
-                                output.write_all(right1)?;
-                                output.write_all(CONFLICT_DIFF_LINE)?;
-                                output.write_all(
-                                    format!(
-                                        " Changes from {base_str} to side #{}\n",
-                                        add_index + 2
-                                    )
-                                    .as_bytes(),
-                                )?;
-                                write_diff_hunks(&diff2, output)?;
-                                add_index += 2;
-                                continue;
-                            }
+                    let conflict_info = format!("Conflict {conflict_index} of {num_conflicts}");
+                    match conflict_marker_style {
+                        ConflictMarkerStyle::Git if hunk.num_sides() == 2 => {
+                            materialize_git_style_conflict(&hunk, &conflict_info, output)?;
+                        }
+                        ConflictMarkerStyle::Snapshot | ConflictMarkerStyle::Git => {
+                            materialize_jj_style_conflict(&hunk, &conflict_info, false, output)?;
+                        }
+                        ConflictMarkerStyle::Diff => {
+                            materialize_jj_style_conflict(&hunk, &conflict_info, true, output)?;
                         }
-
-                        output.write_all(CONFLICT_DIFF_LINE)?;
-                        output.write_all(
-                            format!(" Changes from {base_str} to side #{}\n", add_index + 1)
-                                .as_bytes(),
-                        )?;
-                        write_diff_hunks(&diff1, output)?;
-                        add_index += 1;
-                    }
-
-                    //  Emit the remaining positive terms as snapshots.
-                    for (add_index, slice) in hunk.adds().enumerate().skip(add_index) {
-                        output.write_all(CONFLICT_PLUS_LINE)?;
-                        output.write_all(
-                            format!(" Contents of side #{}\n", add_index + 1).as_bytes(),
-                        )?;
-                        output.write_all(slice)?;
                     }
-                    output.write_all(CONFLICT_END_LINE)?;
-                    output.write_all(
-                        format!(" Conflict {conflict_index} of {num_conflicts} ends\n").as_bytes(),
-                    )?;
                 }
             }
         }
@@ -330,6 +295,116 @@ pub fn materialize_merge_result(
     Ok(())
 }
 
+fn materialize_git_style_conflict(
+    hunk: &Merge<BString>,
+    conflict_info: &str,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    output.write_all(CONFLICT_START_LINE)?;
+    output.write_all(format!(" Side #1 ({conflict_info})\n").as_bytes())?;
+    output.write_all(hunk.get_add(0).unwrap())?;
+    output.write_all(CONFLICT_GIT_ANCESTOR_LINE)?;
+    output.write_all(b" Base\n")?;
+    output.write_all(hunk.get_remove(0).unwrap())?;
+    output.write_all(CONFLICT_GIT_SEPARATOR_LINE)?;
+    output.write_all(b"\n")?;
+    output.write_all(hunk.get_add(1).unwrap())?;
+    output.write_all(CONFLICT_END_LINE)?;
+    output.write_all(format!(" Side #2 ({conflict_info} ends)\n").as_bytes())?;
+    Ok(())
+}
+
+fn materialize_jj_style_conflict(
+    hunk: &Merge<BString>,
+    conflict_info: &str,
+    use_diffs: bool,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    // The vast majority of conflicts one actually tries to resolve manually have 1
+    // base.
+    let base_str = |base_index: usize| {
+        if hunk.removes().len() == 1 {
+            "base".to_string()
+        } else {
+            format!("base #{}", base_index + 1)
+        }
+    };
+    let write_side = |add_index: usize, output: &mut dyn Write| -> std::io::Result<()> {
+        output.write_all(CONFLICT_PLUS_LINE)?;
+        output.write_all(format!(" Contents of side #{}\n", add_index + 1).as_bytes())?;
+        output.write_all(hunk.get_add(add_index).unwrap())
+    };
+    let write_base = |base_index: usize, output: &mut dyn Write| -> std::io::Result<()> {
+        output.write_all(CONFLICT_MINUS_LINE)?;
+        output.write_all(format!(" Contents of {}\n", base_str(base_index)).as_bytes())?;
+        output.write_all(hunk.get_remove(base_index).unwrap())
+    };
+
+    output.write_all(CONFLICT_START_LINE)?;
+    output.write_all(format!(" {conflict_info}\n").as_bytes())?;
+    let mut add_index = 0;
+    for (base_index, left) in hunk.removes().enumerate() {
+        if !use_diffs {
+            write_side(add_index, output)?;
+            write_base(base_index, output)?;
+            add_index += 1;
+            continue;
+        }
+
+        let Some(right1) = hunk.get_add(add_index) else {
+            // If we have no more positive terms, emit the remaining negative
+            // terms as snapshots.
+            write_base(base_index, output)?;
+            continue;
+        };
+        let diff1 = Diff::by_line([&left, &right1]).hunks().collect_vec();
+        // Check if the diff against the next positive term is better. Since
+        // we want to preserve the order of the terms, we don't match against
+        // any later positive terms.
+        if let Some(right2) = hunk.get_add(add_index + 1) {
+            let diff2 = Diff::by_line([&left, &right2]).hunks().collect_vec();
+            if diff_size(&diff2) < diff_size(&diff1) {
+                // If the next positive term is a better match, emit
+                // the current positive term as a snapshot and the next
+                // positive term as a diff.
+                write_side(add_index, output)?;
+                output.write_all(CONFLICT_DIFF_LINE)?;
+                output.write_all(
+                    format!(
+                        " Changes from {} to side #{}\n",
+                        base_str(base_index),
+                        add_index + 2
+                    )
+                    .as_bytes(),
+                )?;
+                write_diff_hunks(&diff2, output)?;
+                add_index += 2;
+                continue;
+            }
+        }
+
+        output.write_all(CONFLICT_DIFF_LINE)?;
+        output.write_all(
+            format!(
+                " Changes from {} to side #{}\n",
+                base_str(base_index),
+                add_index + 1
+            )
+            .as_bytes(),
+        )?;
+        write_diff_hunks(&diff1, output)?;
+        add_index += 1;
+    }
+
+    //  Emit the remaining positive terms as snapshots.
+    for add_index in add_index..hunk.adds().len() {
+        write_side(add_index, output)?;
+    }
+    output.write_all(CONFLICT_END_LINE)?;
+    output.write_all(format!(" {conflict_info} ends\n").as_bytes())?;
+    Ok(())
+}
+
 fn diff_size(hunks: &[DiffHunk]) -> usize {
     hunks
         .iter()
@@ -415,6 +490,28 @@ pub fn parse_conflict(input: &[u8], num_sides: usize) -> Option<Vec<Merge<BStrin
 }
 
 fn parse_conflict_hunk(input: &[u8]) -> Merge<BString> {
+    // Only the Git style separates the sides with "|||||||" and "=======". The
+    // first line isn't enough to tell the styles apart, since the first side
+    // of a Git-style conflict may start with a line that looks like a jj
+    // marker. If the separators are only part of the contents of a jj-style
+    // conflict, parsing it as a Git-style conflict fails.
+    let has_git_separators = input
+        .split_inclusive(|b| *b == b'\n')
+        .filter(|line| CONFLICT_MARKER_REGEX.is_match_at(line, 0))
+        .any(|line| {
+            line[0] == CONFLICT_GIT_ANCESTOR_LINE_CHAR
+                || line[0] == CONFLICT_GIT_SEPARATOR_LINE_CHAR
+        });
+    if has_git_separators {
+        let hunk = parse_git_style_conflict_hunk(input);
+        if !hunk.is_resolved() {
+            return hunk;
+        }
+    }
+    parse_jj_style_conflict_hunk(input)
+}
+
+fn parse_jj_style_conflict_hunk(input: &[u8]) -> Merge<BString> {
     enum State {
         Diff,
         Minus,
@@ -481,6 +578,51 @@ fn parse_conflict_hunk(input: &[u8]) -> Merge<BString> {
     }
 }
 
+fn parse_git_style_conflict_hunk(input: &[u8]) -> Merge<BString> {
+    #[derive(PartialEq, Eq)]
+    enum State {
+        Left,
+        Base,
+        Right,
+    }
+    let mut state = State::Left;
+    let mut left = BString::new(vec![]);
+    let mut base = BString::new(vec![]);
+    let mut right = BString::new(vec![]);
+    for line in input.split_inclusive(|b| *b == b'\n') {
+        if CONFLICT_MARKER_REGEX.is_match_at(line, 0) {
+            match line[0] {
+                CONFLICT_GIT_ANCESTOR_LINE_CHAR if state == State::Left => {
+                    state = State::Base;
+                    continue;
+                }
+                CONFLICT_GIT_SEPARATOR_LINE_CHAR if state == State::Base => {
+                    state = State::Right;
+                    continue;
+                }
+                CONFLICT_GIT_ANCESTOR_LINE_CHAR | CONFLICT_GIT_SEPARATOR_LINE_CHAR => {
+                    // Doesn't look like a valid conflict
+                    return Merge::resolved(BString::new(vec![]));
+                }
+                _ => {}
+            }
+        }
+        match state {
+            State::Left => left.extend_from_slice(line),
+            State::Base => base.extend_from_slice(line),
+            State::Right => right.extend_from_slice(line),
+        }
+    }
+
+    if state == State::Right {
+        Merge::from_vec(vec![left, base, right])
+    } else {
+        // Doesn't look like a valid conflict, e.g. Git's "merge" style without
+        // the base
+        Merge::resolved(BString::new(vec![]))
+    }
+}
+
 /// Parses conflict markers in `content` and returns an updated version of
 /// `file_ids` with the new contents. If no (valid) conflict markers remain, a
 /// single resolves `FileId` will be returned.
@@ -489,6 +631,7 @@ pub async fn update_from_content(
     store: &Store,
     path: &RepoPath,
     content: &[u8],
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> BackendResult<Merge<Option<FileId>>> {
     let simplified_file_ids = file_ids.clone().simplify();
     let simplified_file_ids = &simplified_file_ids;
@@ -500,7 +643,7 @@ pub async fn update_from_content(
     // copy.
     let mut old_content = Vec::with_capacity(content.len());
     let merge_hunk = extract_as_single_hunk(simplified_file_ids, store, path).await?;
-    materialize_merge_result(&merge_hunk, &mut old_content).unwrap();
+    materialize_merge_result(&merge_hunk, conflict_marker_style, &mut old_content).unwrap();
     if content == old_content {
         return Ok(file_ids.clone());
     }
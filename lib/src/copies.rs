@@ -15,20 +15,37 @@
 //! Code for working with copies and renames.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Read as _;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
 
 use futures::Stream;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
 
+use crate::backend::BackendError;
 use crate::backend::BackendResult;
+use crate::backend::CommitCopy;
+use crate::backend::CommitId;
 use crate::backend::CopyRecord;
+use crate::backend::FileId;
+use crate::backend::TreeValue;
+use crate::index::Index;
+use crate::matchers::EverythingMatcher;
 use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
+use crate::merged_tree::TreeDiffEntry;
 use crate::merged_tree::TreeDiffStream;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
+use crate::revset::ResolvedExpression;
+use crate::revset::GENERATION_RANGE_FULL;
+use crate::store::Store;
 
 /// A collection of CopyRecords.
 #[derive(Default, Debug)]
@@ -214,3 +231,326 @@ impl Stream for CopiesTreeDiffStream<'_> {
         Poll::Ready(None)
     }
 }
+
+/// Minimum similarity of the contents of a deleted and an added file for them
+/// to be detected as a rename.
+const RENAME_SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// Maximum number of deleted and added file pairs to compare the contents of.
+/// Renames are only detected by identical contents above that.
+const MAX_SIMILARITY_COMPARISONS: usize = 1000;
+
+/// Detects files in `target_tree` that were copied or renamed from files in
+/// `source_tree`.
+///
+/// An added file is a copy of a deleted or modified file with the same
+/// contents. Otherwise, it's a rename of the deleted file with the most similar
+/// contents, if any is similar enough. Conflicted files are ignored.
+pub fn detect_copies(
+    source_tree: &MergedTree,
+    target_tree: &MergedTree,
+) -> BackendResult<Vec<CommitCopy>> {
+    let mut changes = ChangedFiles::default();
+    async {
+        let mut diff_stream = source_tree.diff_stream(target_tree, &EverythingMatcher);
+        while let Some(TreeDiffEntry { path, values }) = diff_stream.next().await {
+            let (source_value, target_value) = values?;
+            changes.add(path, &source_value, &target_value);
+        }
+        Ok::<(), BackendError>(())
+    }
+    .block_on()?;
+    changes.detect_copies(target_tree.store())
+}
+
+/// Like [`detect_copies()`], but only looks at the given `paths`, which
+/// should include all the paths that differ between the trees.
+///
+/// This avoids diffing the whole trees when the changed paths are already
+/// known, e.g. when snapshotting the working copy.
+pub fn detect_copies_in_paths<'a>(
+    source_tree: &MergedTree,
+    target_tree: &MergedTree,
+    paths: impl IntoIterator<Item = &'a RepoPath>,
+) -> BackendResult<Vec<CommitCopy>> {
+    let mut changes = ChangedFiles::default();
+    for path in paths {
+        let source_value = source_tree.path_value(path)?;
+        let target_value = target_tree.path_value(path)?;
+        if source_value != target_value {
+            changes.add(path.to_owned(), &source_value, &target_value);
+        }
+    }
+    changes.detect_copies(target_tree.store())
+}
+
+/// Combines the copies from a tree A to a tree B with the copies from B to a
+/// tree C into copies from A to C.
+///
+/// Copies whose source or target doesn't exist in A or C respectively aren't
+/// filtered out here.
+pub fn compose_copies(first: &[CommitCopy], second: &[CommitCopy]) -> Vec<CommitCopy> {
+    let sources_by_target: HashMap<&RepoPathBuf, &RepoPathBuf> = first
+        .iter()
+        .map(|copy| (&copy.target, &copy.source))
+        .collect();
+    let second_targets: HashSet<&RepoPathBuf> = second.iter().map(|copy| &copy.target).collect();
+    let kept = first
+        .iter()
+        .filter(|copy| !second_targets.contains(&copy.target))
+        .cloned();
+    let chained = second.iter().map(|copy| CommitCopy {
+        source: sources_by_target
+            .get(&copy.source)
+            .map_or_else(|| copy.source.clone(), |&source| source.clone()),
+        target: copy.target.clone(),
+    });
+    kept.chain(chained).collect()
+}
+
+/// Maximum number of commits to look for recorded copies in. Copies recorded
+/// in commits further away from the head are ignored.
+const MAX_RECORDED_COPIES_COMMITS: usize = 1000;
+
+/// Collects the copies recorded in the commits in the range `root..head`.
+/// Chains of renames (a->b->c) are composed into a single record from the
+/// path in the oldest commit. If `paths` is specified, only records targeting
+/// those paths are returned.
+///
+/// Only the [`MAX_RECORDED_COPIES_COMMITS`] commits closest to `head` are
+/// visited. The records are sorted in reverse-topological order. Nothing is
+/// returned if either commit isn't in the `index` yet.
+pub(crate) fn recorded_copy_records(
+    store: &Arc<Store>,
+    index: &dyn Index,
+    paths: Option<&[RepoPathBuf]>,
+    root: &CommitId,
+    head: &CommitId,
+) -> BackendResult<Vec<CopyRecord>> {
+    if !index.has_id(root) || !index.has_id(head) {
+        return Ok(vec![]);
+    }
+    let expression = ResolvedExpression::Range {
+        roots: Box::new(ResolvedExpression::Commits(vec![root.clone()])),
+        heads: Box::new(ResolvedExpression::Commits(vec![head.clone()])),
+        generation: GENERATION_RANGE_FULL,
+    };
+    let revset = index
+        .evaluate_revset(&expression, store)
+        .map_err(|err| BackendError::Other(err.into()))?;
+    // Children first
+    let commit_ids = revset
+        .iter()
+        .take(MAX_RECORDED_COPIES_COMMITS)
+        .collect_vec();
+
+    // Visit parents first so that chains of renames can be composed.
+    let mut records_by_target: HashMap<RepoPathBuf, CopyRecord> = HashMap::new();
+    for commit_id in commit_ids.iter().rev() {
+        let commit = store.get_commit(commit_id)?;
+        let copies = &commit.store_commit().copies;
+        if copies.is_empty() {
+            continue;
+        }
+        let tree = commit.tree()?;
+        let parent_trees: Vec<_> = commit
+            .parents()
+            .map(|parent| {
+                let parent = parent?;
+                let tree = parent.tree()?;
+                Ok((parent.id().clone(), tree))
+            })
+            .try_collect::<_, _, BackendError>()?;
+        for copy in copies {
+            // Records whose paths don't exist in the tree anymore (e.g. because
+            // the commit was rewritten) are stale.
+            if file_id(&tree.path_value(&copy.target)?).is_none() {
+                continue;
+            }
+            let record = if let Some(prev) = records_by_target.get(&copy.source) {
+                CopyRecord {
+                    target: copy.target.clone(),
+                    target_commit: commit_id.clone(),
+                    ..prev.clone()
+                }
+            } else {
+                let mut source = None;
+                for (parent_id, parent_tree) in &parent_trees {
+                    if let Some(file_id) = file_id(&parent_tree.path_value(&copy.source)?) {
+                        source = Some((parent_id, file_id));
+                        break;
+                    }
+                }
+                let Some((source_commit, source_file)) = source else {
+                    continue;
+                };
+                CopyRecord {
+                    target: copy.target.clone(),
+                    target_commit: commit_id.clone(),
+                    source: copy.source.clone(),
+                    source_file,
+                    source_commit: source_commit.clone(),
+                }
+            };
+            // The intermediate path of a renamed file doesn't exist anymore.
+            if tree.path_value(&copy.source)?.is_absent() {
+                records_by_target.remove(&copy.source);
+            }
+            records_by_target.insert(copy.target.clone(), record);
+        }
+    }
+
+    let commit_positions: HashMap<&CommitId, usize> = commit_ids
+        .iter()
+        .enumerate()
+        .map(|(pos, id)| (id, pos))
+        .collect();
+    let records = records_by_target
+        .into_values()
+        .filter(|record| paths.map_or(true, |paths| paths.contains(&record.target)))
+        .sorted_by(|a, b| {
+            let a_pos = commit_positions[&a.target_commit];
+            let b_pos = commit_positions[&b.target_commit];
+            a_pos.cmp(&b_pos).then_with(|| a.target.cmp(&b.target))
+        })
+        .collect();
+    Ok(records)
+}
+
+/// Files which differ between two trees, classified for copy detection.
+#[derive(Default)]
+struct ChangedFiles {
+    deleted: Vec<(RepoPathBuf, FileId)>,
+    modified: Vec<(RepoPathBuf, FileId)>,
+    added: Vec<(RepoPathBuf, FileId)>,
+}
+
+impl ChangedFiles {
+    fn add(
+        &mut self,
+        path: RepoPathBuf,
+        source_value: &MergedTreeValue,
+        target_value: &MergedTreeValue,
+    ) {
+        match (file_id(source_value), file_id(target_value)) {
+            (Some(id), None) if target_value.is_absent() => self.deleted.push((path, id)),
+            (Some(id), Some(_)) => self.modified.push((path, id)),
+            (None, Some(id)) if source_value.is_absent() => self.added.push((path, id)),
+            _ => {}
+        }
+    }
+
+    fn detect_copies(self, store: &Arc<Store>) -> BackendResult<Vec<CommitCopy>> {
+        let ChangedFiles {
+            deleted,
+            modified,
+            added,
+        } = self;
+        if added.is_empty() || (deleted.is_empty() && modified.is_empty()) {
+            return Ok(vec![]);
+        }
+
+        // Files with identical contents, preferring deleted sources
+        let sources_by_id: HashMap<&FileId, &RepoPathBuf> = modified
+            .iter()
+            .chain(&deleted)
+            .map(|(path, id)| (id, path))
+            .collect();
+        let mut copies = vec![];
+        let mut unmatched = vec![];
+        for (target, id) in &added {
+            match sources_by_id.get(id) {
+                Some(&source) => copies.push(CommitCopy {
+                    source: source.clone(),
+                    target: target.clone(),
+                }),
+                None => unmatched.push((target, id)),
+            }
+        }
+
+        // Deleted files with similar contents
+        let renamed: HashSet<&RepoPathBuf> = copies.iter().map(|copy| &copy.source).collect();
+        let candidates = deleted
+            .iter()
+            .filter(|(path, _)| !renamed.contains(path))
+            .collect::<Vec<_>>();
+        if candidates.is_empty() || unmatched.len() * candidates.len() > MAX_SIMILARITY_COMPARISONS
+        {
+            return Ok(copies);
+        }
+        // Files that can't be read are skipped.
+        let read_content = |path: &RepoPath, id: &FileId| -> BackendResult<Option<Vec<u8>>> {
+            let mut reader = match store.read_file(path, id) {
+                Ok(reader) => reader,
+                Err(BackendError::ReadAccessDenied { .. }) => return Ok(None),
+                Err(err) => return Err(err),
+            };
+            let mut content = vec![];
+            reader
+                .read_to_end(&mut content)
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.to_owned(),
+                    id: id.clone(),
+                    source: err.into(),
+                })?;
+            Ok(Some(content))
+        };
+        let candidate_contents: Vec<_> = candidates
+            .iter()
+            .map(|(path, id)| read_content(path, id))
+            .collect::<BackendResult<_>>()?;
+        let mut used = vec![false; candidates.len()];
+        for (target, id) in unmatched {
+            let Some(content) = read_content(target, id)? else {
+                continue;
+            };
+            let best = candidate_contents
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !used[*i])
+                .filter_map(|(i, source_content)| Some((i, source_content.as_ref()?)))
+                .map(|(i, source_content)| (i, similarity(source_content, &content)))
+                .filter(|(_, score)| *score >= RENAME_SIMILARITY_THRESHOLD)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b));
+            if let Some((i, _)) = best {
+                used[i] = true;
+                copies.push(CommitCopy {
+                    source: candidates[i].0.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+        Ok(copies)
+    }
+}
+
+fn file_id(value: &MergedTreeValue) -> Option<FileId> {
+    match value.as_resolved() {
+        Some(Some(TreeValue::File { id, .. })) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the fraction of lines the contents have in common.
+fn similarity(left: &[u8], right: &[u8]) -> f64 {
+    let split_lines =
+        |content| <[u8]>::split_inclusive(content, |&b| b == b'\n').filter(|line| !line.is_empty());
+    let mut left_lines: HashMap<&[u8], usize> = HashMap::new();
+    let mut num_lines = 0;
+    for line in split_lines(left) {
+        *left_lines.entry(line).or_default() += 1;
+        num_lines += 1;
+    }
+    let mut num_common = 0;
+    for line in split_lines(right) {
+        if let Some(count) = left_lines.get_mut(line).filter(|count| **count > 0) {
+            *count -= 1;
+            num_common += 1;
+        }
+        num_lines += 1;
+    }
+    if num_lines == 0 {
+        return 0.0;
+    }
+    (2 * num_common) as f64 / num_lines as f64
+}
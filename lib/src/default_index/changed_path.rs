@@ -0,0 +1,123 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Paths changed by each commit, stored alongside the commit graph.
+
+#![allow(missing_docs)]
+
+use std::collections::BTreeMap;
+
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+
+use super::composite::CompositeIndex;
+use crate::backend::BackendResult;
+use crate::commit::Commit;
+use crate::matchers::EverythingMatcher;
+use crate::merged_tree::resolve_file_values;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::rewrite;
+
+/// If set, changed paths of the entry weren't recorded.
+pub(super) const UNKNOWN_CHANGED_PATHS_FLAG: u32 = 0x8000_0000;
+
+/// Collects paths of the files that differ between the `commit` and its
+/// (auto-merged) parents.
+///
+/// The parent commits must have been indexed.
+pub(super) fn collect_changed_paths(
+    index: &CompositeIndex,
+    commit: &Commit,
+) -> BackendResult<Vec<RepoPathBuf>> {
+    let store = commit.store();
+    let parents: Vec<_> = commit.parents().try_collect()?;
+    if let [parent] = parents.as_slice() {
+        // Fast path: no need to load the root tree
+        if commit.tree_id() == parent.tree_id() {
+            return Ok(vec![]);
+        }
+    }
+    let from_tree = rewrite::merge_commit_trees_no_resolve_without_repo(store, &index, &parents)?;
+    let to_tree = commit.tree()?;
+    let mut tree_diff = from_tree.diff_stream(&to_tree, &EverythingMatcher);
+    async {
+        let mut paths = vec![];
+        while let Some(entry) = tree_diff.next().await {
+            let (from_value, to_value) = entry.values?;
+            // Conflicts in the merged parents might be resolved trivially, in
+            // which case the path shouldn't be considered changed.
+            let from_value = resolve_file_values(store, &entry.path, from_value).await?;
+            if from_value != to_value {
+                paths.push(entry.path);
+            }
+        }
+        Ok(paths)
+    }
+    .block_on()
+}
+
+/// Serialized form of the changed paths of local commit entries.
+#[derive(Debug, Default)]
+pub(super) struct ChangedPathTable {
+    /// For each entry, end position in `path_ids`, or'ed with
+    /// `UNKNOWN_CHANGED_PATHS_FLAG` if unknown.
+    pub entry_ends: Vec<u32>,
+    /// For each distinct path in sorted order, end offset in `path_strings`.
+    pub path_ends: Vec<u32>,
+    /// Concatenated changed path ids of all entries.
+    pub path_ids: Vec<u32>,
+    /// Concatenated path strings.
+    pub path_strings: Vec<u8>,
+}
+
+impl ChangedPathTable {
+    /// Builds table from the changed paths of each entry in graph order.
+    pub fn build<'a>(entries: impl IntoIterator<Item = Option<&'a [RepoPathBuf]>> + Clone) -> Self {
+        let path_id_map: BTreeMap<&RepoPath, u32> = entries
+            .clone()
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|path| path.as_ref())
+            .sorted()
+            .dedup()
+            .enumerate()
+            .map(|(i, path)| (path, u32::try_from(i).unwrap()))
+            .collect();
+        let mut table = ChangedPathTable::default();
+        for path in path_id_map.keys() {
+            table
+                .path_strings
+                .extend_from_slice(path.as_internal_file_string().as_bytes());
+            let end = u32::try_from(table.path_strings.len()).unwrap();
+            table.path_ends.push(end);
+        }
+        for paths in entries {
+            if let Some(paths) = paths {
+                table
+                    .path_ids
+                    .extend(paths.iter().map(|path| path_id_map[path.as_ref()]));
+                let end = u32::try_from(table.path_ids.len()).unwrap();
+                assert!(end < UNKNOWN_CHANGED_PATHS_FLAG);
+                table.entry_ends.push(end);
+            } else {
+                let end = u32::try_from(table.path_ids.len()).unwrap();
+                table.entry_ends.push(end | UNKNOWN_CHANGED_PATHS_FLAG);
+            }
+        }
+        table
+    }
+}
@@ -46,6 +46,7 @@ use crate::index::Index;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
 use crate::object_id::PrefixResolution;
+use crate::repo_path::RepoPath;
 use crate::revset::ResolvedExpression;
 use crate::revset::Revset;
 use crate::revset::RevsetEvaluationError;
@@ -90,6 +91,9 @@ pub(super) trait IndexSegment: Send + Sync {
     fn num_parents(&self, local_pos: LocalPosition) -> u32;
 
     fn parent_positions(&self, local_pos: LocalPosition) -> SmallIndexPositionsVec;
+
+    /// Returns paths changed by the commit, or `None` if unknown.
+    fn changed_paths(&self, local_pos: LocalPosition) -> Option<Vec<&RepoPath>>;
 }
 
 pub(super) type DynIndexSegment = dyn IndexSegment;
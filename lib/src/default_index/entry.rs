@@ -26,6 +26,7 @@ use super::composite::DynIndexSegment;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::object_id::ObjectId;
+use crate::repo_path::RepoPath;
 
 /// Global index position.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
@@ -120,6 +121,12 @@ impl<'a> IndexEntry<'a> {
             .into_iter()
             .map(move |pos| composite.entry_by_pos(pos))
     }
+
+    /// Returns paths of the files changed from the parents, or `None` if the
+    /// changed paths aren't indexed.
+    pub fn changed_paths(&self) -> Option<Vec<&'a RepoPath>> {
+        self.source.changed_paths(self.local_pos)
+    }
 }
 
 /// Wrapper to sort `IndexPosition` by its generation number.
@@ -31,6 +31,8 @@ use smallvec::smallvec;
 use smallvec::SmallVec;
 use tempfile::NamedTempFile;
 
+use super::changed_path::collect_changed_paths;
+use super::changed_path::ChangedPathTable;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CompositeIndex;
@@ -56,6 +58,8 @@ use crate::index::ReadonlyIndex;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
 use crate::object_id::PrefixResolution;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
 use crate::revset::ResolvedExpression;
 use crate::revset::Revset;
 use crate::revset::RevsetEvaluationError;
@@ -67,6 +71,7 @@ struct MutableGraphEntry {
     change_id: ChangeId,
     generation_number: u32,
     parent_positions: SmallIndexPositionsVec,
+    changed_paths: Option<Vec<RepoPathBuf>>,
 }
 
 pub(super) struct MutableIndexSegment {
@@ -74,6 +79,7 @@ pub(super) struct MutableIndexSegment {
     num_parent_commits: u32,
     commit_id_length: usize,
     change_id_length: usize,
+    changed_paths_indexed: bool,
     graph: Vec<MutableGraphEntry>,
     commit_lookup: BTreeMap<CommitId, LocalPosition>,
     change_lookup: BTreeMap<ChangeId, SmallLocalPositionsVec>,
@@ -86,6 +92,7 @@ impl MutableIndexSegment {
             num_parent_commits: 0,
             commit_id_length,
             change_id_length,
+            changed_paths_indexed: false,
             graph: vec![],
             commit_lookup: BTreeMap::new(),
             change_lookup: BTreeMap::new(),
@@ -96,11 +103,13 @@ impl MutableIndexSegment {
         let num_parent_commits = parent_file.as_composite().num_commits();
         let commit_id_length = parent_file.commit_id_length();
         let change_id_length = parent_file.change_id_length();
+        let changed_paths_indexed = parent_file.changed_paths_indexed();
         Self {
             parent_file: Some(parent_file),
             num_parent_commits,
             commit_id_length,
             change_id_length,
+            changed_paths_indexed,
             graph: vec![],
             commit_lookup: BTreeMap::new(),
             change_lookup: BTreeMap::new(),
@@ -112,18 +121,42 @@ impl MutableIndexSegment {
     }
 
     pub(super) fn add_commit(&mut self, commit: &Commit) {
-        self.add_commit_data(
+        if self.as_composite().has_id(commit.id()) {
+            return;
+        }
+        let changed_paths = if self.changed_paths_indexed {
+            // If the tree couldn't be read, the commit will be scanned at
+            // query time.
+            collect_changed_paths(self.as_composite(), commit)
+                .inspect_err(|err| tracing::warn!(?err, "failed to collect changed paths"))
+                .ok()
+        } else {
+            None
+        };
+        self.add_commit_data_with_changed_paths(
             commit.id().clone(),
             commit.change_id().clone(),
             commit.parent_ids(),
+            changed_paths,
         );
     }
 
+    #[cfg(test)]
     pub(super) fn add_commit_data(
         &mut self,
         commit_id: CommitId,
         change_id: ChangeId,
         parent_ids: &[CommitId],
+    ) {
+        self.add_commit_data_with_changed_paths(commit_id, change_id, parent_ids, None);
+    }
+
+    fn add_commit_data_with_changed_paths(
+        &mut self,
+        commit_id: CommitId,
+        change_id: ChangeId,
+        parent_ids: &[CommitId],
+        changed_paths: Option<Vec<RepoPathBuf>>,
     ) {
         if self.as_composite().has_id(&commit_id) {
             return;
@@ -133,6 +166,7 @@ impl MutableIndexSegment {
             change_id,
             generation_number: 0,
             parent_positions: SmallVec::new(),
+            changed_paths: changed_paths.filter(|_| self.changed_paths_indexed),
         };
         for parent_id in parent_ids {
             let parent_entry = self
@@ -161,7 +195,15 @@ impl MutableIndexSegment {
         for pos in other_segment.num_parent_commits()..other.num_commits() {
             let entry = other.entry_by_pos(IndexPosition(pos));
             let parent_ids = entry.parents().map(|entry| entry.commit_id()).collect_vec();
-            self.add_commit_data(entry.commit_id(), entry.change_id(), &parent_ids);
+            let changed_paths = entry
+                .changed_paths()
+                .map(|paths| paths.into_iter().map(ToOwned::to_owned).collect());
+            self.add_commit_data_with_changed_paths(
+                entry.commit_id(),
+                entry.change_id(),
+                &parent_ids,
+                changed_paths,
+            );
         }
     }
 
@@ -218,6 +260,16 @@ impl MutableIndexSegment {
             self.change_lookup.values().flatten().count()
         );
 
+        let changed_path_table = if self.changed_paths_indexed {
+            ChangedPathTable::build(
+                self.graph
+                    .iter()
+                    .map(|entry| entry.changed_paths.as_deref()),
+            )
+        } else {
+            ChangedPathTable::default()
+        };
+
         let num_commits = u32::try_from(self.graph.len()).unwrap();
         buf.extend(num_commits.to_le_bytes());
         let num_change_ids = u32::try_from(self.change_lookup.len()).unwrap();
@@ -227,6 +279,14 @@ impl MutableIndexSegment {
         buf.extend(0_u32.to_le_bytes());
         let change_overflow_offset = buf.len();
         buf.extend(0_u32.to_le_bytes());
+        buf.extend(u32::from(self.changed_paths_indexed).to_le_bytes());
+        let num_changed_paths = u32::try_from(changed_path_table.path_ends.len()).unwrap();
+        buf.extend(num_changed_paths.to_le_bytes());
+        let num_changed_path_ids = u32::try_from(changed_path_table.path_ids.len()).unwrap();
+        buf.extend(num_changed_path_ids.to_le_bytes());
+        let changed_path_strings_size =
+            u32::try_from(changed_path_table.path_strings.len()).unwrap();
+        buf.extend(changed_path_strings_size.to_le_bytes());
 
         // Positions of change ids in the sorted table
         let change_id_pos_map: HashMap<&ChangeId, u32> = self
@@ -311,6 +371,17 @@ impl MutableIndexSegment {
         for LocalPosition(pos) in change_overflow {
             buf.extend(pos.to_le_bytes());
         }
+
+        for end in changed_path_table.entry_ends {
+            buf.extend(end.to_le_bytes());
+        }
+        for end in changed_path_table.path_ends {
+            buf.extend(end.to_le_bytes());
+        }
+        for id in changed_path_table.path_ids {
+            buf.extend(id.to_le_bytes());
+        }
+        buf.extend(changed_path_table.path_strings);
     }
 
     /// If the MutableIndex has more than half the commits of its parent
@@ -340,6 +411,7 @@ impl MutableIndexSegment {
         } else {
             MutableIndexSegment::full(self.commit_id_length, self.change_id_length)
         };
+        squashed.changed_paths_indexed = self.changed_paths_indexed;
         for parent_file in files_to_squash.iter().rev() {
             squashed.add_commits_from(parent_file.as_ref());
         }
@@ -452,6 +524,11 @@ impl IndexSegment for MutableIndexSegment {
     fn parent_positions(&self, local_pos: LocalPosition) -> SmallIndexPositionsVec {
         self.graph[local_pos.0 as usize].parent_positions.clone()
     }
+
+    fn changed_paths(&self, local_pos: LocalPosition) -> Option<Vec<&RepoPath>> {
+        let paths = self.graph[local_pos.0 as usize].changed_paths.as_ref()?;
+        Some(paths.iter().map(AsRef::as_ref).collect())
+    }
 }
 
 /// In-memory mutable records for the on-disk commit index backend.
@@ -468,6 +545,13 @@ impl DefaultMutableIndex {
         DefaultMutableIndex(mutable_segment)
     }
 
+    /// Enables recording of paths changed by each commit to be added.
+    ///
+    /// Incremental index inherits the setting from the parent segment.
+    pub(super) fn enable_changed_paths(&mut self) {
+        self.0.changed_paths_indexed = true;
+    }
+
     #[cfg(test)]
     pub(crate) fn add_commit_data(
         &mut self,
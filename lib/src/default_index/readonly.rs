@@ -22,11 +22,13 @@ use std::fs::File;
 use std::io;
 use std::io::Read;
 use std::path::Path;
+use std::str;
 use std::sync::Arc;
 
 use smallvec::smallvec;
 use thiserror::Error;
 
+use super::changed_path::UNKNOWN_CHANGED_PATHS_FLAG;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CompositeIndex;
@@ -46,6 +48,8 @@ use crate::index::ReadonlyIndex;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
 use crate::object_id::PrefixResolution;
+use crate::repo_path::is_valid_repo_path_str;
+use crate::repo_path::RepoPath;
 use crate::revset::ResolvedExpression;
 use crate::revset::Revset;
 use crate::revset::RevsetEvaluationError;
@@ -103,7 +107,7 @@ impl ReadonlyIndexLoadError {
 }
 
 /// Current format version of the index segment file.
-pub(crate) const INDEX_SEGMENT_FILE_FORMAT_VERSION: u32 = 6;
+pub(crate) const INDEX_SEGMENT_FILE_FORMAT_VERSION: u32 = 7;
 
 /// If set, the value is stored in the overflow table.
 pub(crate) const OVERFLOW_FLAG: u32 = 0x8000_0000;
@@ -185,6 +189,10 @@ impl CommitGraphEntry<'_> {
 /// u32: number of local change ids
 /// u32: number of overflow parent entries
 /// u32: number of overflow change id positions
+/// u32: 1 if changed paths are indexed, 0 otherwise
+/// u32: number of distinct changed paths
+/// u32: number of changed path ids
+/// u32: size of changed path strings in bytes
 /// for each entry, in some topological order with parents first:
 ///   u32: generation number
 ///   if number of parents <= 2:
@@ -210,6 +218,14 @@ impl CommitGraphEntry<'_> {
 ///   u32: global index position
 /// for each overflow change id entry:
 ///   u32: local position in the graph entries table
+/// if changed paths are indexed, for each entry in graph order:
+///   u32: (< 0x8000_0000) end position in the changed path ids table
+///        (>=0x8000_0000) same, but changed paths of the entry are unknown
+/// for each distinct changed path, sorted:
+///   u32: end offset in the changed path strings
+/// for each entry's changed path, in graph order:
+///   u32: position in the sorted changed paths table
+/// <size number of bytes>: changed path strings
 /// ```
 ///
 /// Note that u32 fields are 4-byte aligned so long as the parent file name
@@ -227,12 +243,17 @@ pub(super) struct ReadonlyIndexSegment {
     num_local_commits: u32,
     num_local_change_ids: u32,
     num_change_overflow_entries: u32,
+    changed_paths_indexed: bool,
     // Base data offsets in bytes:
     commit_lookup_base: usize,
     change_id_table_base: usize,
     change_pos_table_base: usize,
     parent_overflow_base: usize,
     change_overflow_base: usize,
+    changed_path_entries_base: usize,
+    changed_path_ends_base: usize,
+    changed_path_ids_base: usize,
+    changed_path_strings_base: usize,
     data: Vec<u8>,
 }
 
@@ -328,6 +349,19 @@ impl ReadonlyIndexSegment {
         let num_local_change_ids = read_u32(file)?;
         let num_parent_overflow_entries = read_u32(file)?;
         let num_change_overflow_entries = read_u32(file)?;
+        let changed_paths_indexed = match read_u32(file)? {
+            0 => false,
+            1 => true,
+            _ => {
+                return Err(ReadonlyIndexLoadError::invalid_data(
+                    name,
+                    "invalid changed paths flag",
+                ))
+            }
+        };
+        let num_changed_paths = read_u32(file)?;
+        let num_changed_path_ids = read_u32(file)?;
+        let changed_path_strings_size = read_u32(file)?;
         let mut data = vec![];
         file.read_to_end(&mut data).map_err(from_io_err)?;
 
@@ -338,6 +372,13 @@ impl ReadonlyIndexSegment {
         let change_pos_table_size = (num_local_change_ids as usize) * 4;
         let parent_overflow_size = (num_parent_overflow_entries as usize) * 4;
         let change_overflow_size = (num_change_overflow_entries as usize) * 4;
+        let changed_path_entries_size = if changed_paths_indexed {
+            (num_local_commits as usize) * 4
+        } else {
+            0
+        };
+        let changed_path_ends_size = (num_changed_paths as usize) * 4;
+        let changed_path_ids_size = (num_changed_path_ids as usize) * 4;
 
         let graph_base = 0;
         let commit_lookup_base = graph_base + graph_size;
@@ -345,7 +386,11 @@ impl ReadonlyIndexSegment {
         let change_pos_table_base = change_id_table_base + change_id_table_size;
         let parent_overflow_base = change_pos_table_base + change_pos_table_size;
         let change_overflow_base = parent_overflow_base + parent_overflow_size;
-        let expected_size = change_overflow_base + change_overflow_size;
+        let changed_path_entries_base = change_overflow_base + change_overflow_size;
+        let changed_path_ends_base = changed_path_entries_base + changed_path_entries_size;
+        let changed_path_ids_base = changed_path_ends_base + changed_path_ends_size;
+        let changed_path_strings_base = changed_path_ids_base + changed_path_ids_size;
+        let expected_size = changed_path_strings_base + changed_path_strings_size as usize;
 
         if data.len() != expected_size {
             return Err(ReadonlyIndexLoadError::invalid_data(
@@ -354,6 +399,45 @@ impl ReadonlyIndexSegment {
             ));
         }
 
+        // Validate the changed-path tables up front so the lookups can't go out
+        // of bounds or produce invalid paths.
+        let read_u32_at = |base: usize, pos: u32| {
+            let offset = base + (pos as usize) * 4;
+            u32::from_le_bytes(data[offset..][..4].try_into().unwrap())
+        };
+        let changed_path_strings = &data[changed_path_strings_base..];
+        let mut path_start = 0;
+        for path_id in 0..num_changed_paths {
+            let path_end = read_u32_at(changed_path_ends_base, path_id) as usize;
+            let valid = changed_path_strings
+                .get(path_start..path_end)
+                .and_then(|bytes| str::from_utf8(bytes).ok())
+                .is_some_and(is_valid_repo_path_str);
+            if !valid {
+                return Err(ReadonlyIndexLoadError::invalid_data(
+                    name,
+                    "invalid changed path",
+                ));
+            }
+            path_start = path_end;
+        }
+        let path_ids_valid = (0..num_changed_path_ids)
+            .all(|pos| read_u32_at(changed_path_ids_base, pos) < num_changed_paths);
+        let mut prev_entry_end = 0;
+        let entry_ends_valid = changed_path_entries_size == 0
+            || (0..num_local_commits).all(|pos| {
+                let end = read_u32_at(changed_path_entries_base, pos) & !UNKNOWN_CHANGED_PATHS_FLAG;
+                let valid = prev_entry_end <= end && end <= num_changed_path_ids;
+                prev_entry_end = end;
+                valid
+            });
+        if !path_ids_valid || !entry_ends_valid {
+            return Err(ReadonlyIndexLoadError::invalid_data(
+                name,
+                "invalid changed path entry",
+            ));
+        }
+
         Ok(Arc::new(ReadonlyIndexSegment {
             parent_file,
             num_parent_commits,
@@ -363,11 +447,16 @@ impl ReadonlyIndexSegment {
             num_local_commits,
             num_local_change_ids,
             num_change_overflow_entries,
+            changed_paths_indexed,
             commit_lookup_base,
             change_id_table_base,
             change_pos_table_base,
             parent_overflow_base,
             change_overflow_base,
+            changed_path_entries_base,
+            changed_path_ends_base,
+            changed_path_ids_base,
+            changed_path_strings_base,
             data,
         }))
     }
@@ -388,6 +477,11 @@ impl ReadonlyIndexSegment {
         self.change_id_length
     }
 
+    /// Returns true if paths changed by commits are recorded in this segment.
+    pub(super) fn changed_paths_indexed(&self) -> bool {
+        self.changed_paths_indexed
+    }
+
     fn graph_entry(&self, local_pos: LocalPosition) -> CommitGraphEntry {
         let table = &self.data[..self.commit_lookup_base];
         let entry_size = CommitGraphEntry::size(self.commit_id_length);
@@ -430,6 +524,30 @@ impl ReadonlyIndexSegment {
             .collect()
     }
 
+    fn read_u32_at(&self, base: usize, pos: u32) -> u32 {
+        let offset = base + (pos as usize) * 4;
+        u32::from_le_bytes(self.data[offset..][..4].try_into().unwrap())
+    }
+
+    fn changed_path_entry_end(&self, local_pos: LocalPosition) -> u32 {
+        self.read_u32_at(self.changed_path_entries_base, local_pos.0)
+    }
+
+    /// Returns the changed path of the `path_id`. The paths are validated when
+    /// the segment is loaded, so this returns `None` only if that invariant is
+    /// broken.
+    fn changed_path(&self, path_id: u32) -> Option<&RepoPath> {
+        let start = match path_id.checked_sub(1) {
+            Some(prev_id) => self.read_u32_at(self.changed_path_ends_base, prev_id),
+            None => 0,
+        };
+        let end = self.read_u32_at(self.changed_path_ends_base, path_id);
+        let table = &self.data[self.changed_path_strings_base..];
+        let bytes = table.get(start as usize..end as usize)?;
+        let path = str::from_utf8(bytes).ok()?;
+        Some(RepoPath::from_internal_string(path))
+    }
+
     /// Scans graph entry positions stored in the overflow change ids table.
     fn overflow_changes_from(&self, overflow_pos: u32) -> impl Iterator<Item = LocalPosition> + '_ {
         let table = &self.data[self.change_overflow_base..];
@@ -583,6 +701,26 @@ impl IndexSegment for ReadonlyIndexSegment {
             self.overflow_parents(overflow_pos, num_parents)
         }
     }
+
+    fn changed_paths(&self, local_pos: LocalPosition) -> Option<Vec<&RepoPath>> {
+        if !self.changed_paths_indexed {
+            return None;
+        }
+        let end = self.changed_path_entry_end(local_pos);
+        if end & UNKNOWN_CHANGED_PATHS_FLAG != 0 {
+            return None;
+        }
+        let start = match local_pos.0.checked_sub(1) {
+            Some(prev_pos) => {
+                self.changed_path_entry_end(LocalPosition(prev_pos)) & !UNKNOWN_CHANGED_PATHS_FLAG
+            }
+            None => 0,
+        };
+        (start..end)
+            .map(|pos| self.read_u32_at(self.changed_path_ids_base, pos))
+            .map(|path_id| self.changed_path(path_id))
+            .collect()
+    }
 }
 
 /// Commit index backend which stores data on local disk.
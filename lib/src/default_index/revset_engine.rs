@@ -23,7 +23,6 @@ use std::collections::HashSet;
 use std::fmt;
 use std::iter;
 use std::ops::Range;
-use std::rc::Rc;
 use std::str;
 use std::sync::Arc;
 
@@ -44,22 +43,30 @@ use crate::backend::MillisSinceEpoch;
 use crate::commit::Commit;
 use crate::conflicts::materialize_merge_result;
 use crate::conflicts::materialize_tree_value;
+use crate::conflicts::ConflictMarkerStyle;
 use crate::conflicts::MaterializedTreeValue;
 use crate::default_index::AsCompositeIndex;
 use crate::default_index::CompositeIndex;
+use crate::default_index::IndexEntry;
 use crate::default_index::IndexPosition;
+use crate::diff::WhitespaceMode;
+use crate::fileset::FilesetExpression;
 use crate::graph::GraphEdge;
 use crate::matchers::Matcher;
 use crate::matchers::Visit;
 use crate::merged_tree::resolve_file_values;
+use crate::object_id::ObjectId as _;
 use crate::repo_path::RepoPath;
 use crate::revset::ResolvedExpression;
 use crate::revset::ResolvedPredicateExpression;
 use crate::revset::Revset;
 use crate::revset::RevsetEvaluationError;
 use crate::revset::RevsetFilterPredicate;
+use crate::revset::SignaturePredicate;
 use crate::revset::GENERATION_RANGE_FULL;
 use crate::rewrite;
+use crate::signing::SigStatus;
+use crate::signing::SignError;
 use crate::store::Store;
 use crate::str_util::StringPattern;
 use crate::union_find;
@@ -912,6 +919,24 @@ impl<'index> EvaluationContext<'index> {
                 });
                 Ok(Box::new(EagerRevset { positions }))
             }
+            ResolvedExpression::Bisect(candidates) => {
+                // Pick the candidate of the median generation number. This splits
+                // linear history exactly in half, and approximates the midpoint
+                // of non-linear history without having to count the ancestors
+                // of each candidate.
+                let mut positions = self
+                    .evaluate(candidates)?
+                    .positions()
+                    .attach(index)
+                    .collect_vec();
+                positions.sort_by_key(|&pos| (index.entry_by_pos(pos).generation_number(), pos));
+                let positions = positions
+                    .get(positions.len() / 2)
+                    .copied()
+                    .into_iter()
+                    .collect();
+                Ok(Box::new(EagerRevset { positions }))
+            }
             ResolvedExpression::Latest { candidates, count } => {
                 let candidate_set = self.evaluate(candidates)?;
                 Ok(Box::new(
@@ -1114,21 +1139,42 @@ fn build_predicate_fn(
             })
         }
         RevsetFilterPredicate::File(expr) => {
-            let matcher: Rc<dyn Matcher> = expr.to_matcher().into();
+            let expr = expr.clone();
+            let matcher = paths_matcher(&expr);
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
+                if let Some(matched) = matches_changed_paths(&entry, matcher.as_deref()) {
+                    return matched;
+                }
                 let commit = store.get_commit(&entry.commit_id()).unwrap();
-                has_diff_from_parent(&store, index, &commit, matcher.as_ref()).unwrap()
+                has_diff_from_parent(&store, index, &commit, &expr, matcher.as_deref()).unwrap()
             })
         }
-        RevsetFilterPredicate::DiffContains { text, files } => {
+        RevsetFilterPredicate::DiffContains {
+            text,
+            files,
+            whitespace,
+        } => {
             let text_pattern = text.clone();
-            let files_matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            let files = files.clone();
+            let whitespace = *whitespace;
+            let matcher = paths_matcher(&files);
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
+                if matches_changed_paths(&entry, matcher.as_deref()) == Some(false) {
+                    return false;
+                }
                 let commit = store.get_commit(&entry.commit_id()).unwrap();
-                matches_diff_from_parent(&store, index, &commit, &text_pattern, &*files_matcher)
-                    .unwrap()
+                matches_diff_from_parent(
+                    &store,
+                    index,
+                    &commit,
+                    &text_pattern,
+                    &files,
+                    matcher.as_deref(),
+                    whitespace,
+                )
+                .unwrap()
             })
         }
         RevsetFilterPredicate::HasConflict => box_pure_predicate_fn(move |index, pos| {
@@ -1136,6 +1182,14 @@ fn build_predicate_fn(
             let commit = store.get_commit(&entry.commit_id()).unwrap();
             commit.has_conflict().unwrap()
         }),
+        RevsetFilterPredicate::Signature(predicate) => {
+            let predicate = predicate.clone();
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id()).unwrap();
+                matches_signature(&commit, &predicate)
+            })
+        }
         RevsetFilterPredicate::Extension(ext) => {
             let ext = ext.clone();
             box_pure_predicate_fn(move |index, pos| {
@@ -1147,17 +1201,78 @@ fn build_predicate_fn(
     }
 }
 
+fn matches_signature(commit: &Commit, predicate: &SignaturePredicate) -> bool {
+    if !commit.is_signed() {
+        return false;
+    }
+    if let SignaturePredicate::Signed = predicate {
+        // Don't verify the signature, which may be slow.
+        return true;
+    }
+    let verification = match commit.verification() {
+        Ok(Some(verification)) => verification,
+        Ok(None) => return false,
+        // A signature which can't be parsed is never considered good.
+        Err(SignError::InvalidSignatureFormat) => return false,
+        // TODO: propagate SignError once revsets can fail during iteration.
+        // Until then, a signature which can't be verified (e.g. because the
+        // signing program is missing) isn't considered good either.
+        Err(err) => {
+            tracing::warn!(
+                ?err,
+                commit_id = commit.id().hex(),
+                "failed to verify signature"
+            );
+            return false;
+        }
+    };
+    if verification.status != SigStatus::Good {
+        return false;
+    }
+    match predicate {
+        SignaturePredicate::Signed | SignaturePredicate::Good => true,
+        SignaturePredicate::SignedBy(pattern) => {
+            let key_matches = verification
+                .key
+                .as_ref()
+                .is_some_and(|k| pattern.matches(k));
+            let display_matches = verification
+                .display
+                .as_ref()
+                .is_some_and(|d| pattern.matches(d));
+            key_matches || display_matches
+        }
+    }
+}
+
+/// Returns matcher which can be built once per query, or `None` if the
+/// `files` expression depends on the tree values of each commit.
+fn paths_matcher(files: &FilesetExpression) -> Option<Arc<dyn Matcher>> {
+    files.to_matcher().ok().map(Arc::from)
+}
+
+/// Tests if any of the paths changed by the `entry` commit matches the
+/// `matcher`. Returns `None` if that can't be determined from the index.
+fn matches_changed_paths(entry: &IndexEntry, matcher: Option<&dyn Matcher>) -> Option<bool> {
+    let matcher = matcher?;
+    let paths = entry.changed_paths()?;
+    Some(paths.into_iter().any(|path| matcher.matches(path)))
+}
+
 fn has_diff_from_parent(
     store: &Arc<Store>,
     index: &CompositeIndex,
     commit: &Commit,
-    matcher: &dyn Matcher,
+    files: &FilesetExpression,
+    paths_matcher: Option<&dyn Matcher>,
 ) -> BackendResult<bool> {
     let parents: Vec<_> = commit.parents().try_collect()?;
     if let [parent] = parents.as_slice() {
         // Fast path: no need to load the root tree
         let unchanged = commit.tree_id() == parent.tree_id();
-        if matcher.visit(RepoPath::root()) == Visit::AllRecursively {
+        if paths_matcher
+            .is_some_and(|matcher| matcher.visit(RepoPath::root()) == Visit::AllRecursively)
+        {
             return Ok(!unchanged);
         } else if unchanged {
             return Ok(false);
@@ -1167,12 +1282,23 @@ fn has_diff_from_parent(
     // Conflict resolution is expensive, try that only for matched files.
     let from_tree = rewrite::merge_commit_trees_no_resolve_without_repo(store, &index, &parents)?;
     let to_tree = commit.tree()?;
+    // File predicates are evaluated against the changed values.
+    let value_matcher = paths_matcher.is_none().then(|| files.to_value_matcher());
+    let matcher = match &value_matcher {
+        Some(value_matcher) => value_matcher.candidates(),
+        None => paths_matcher.expect("paths matcher should exist without predicates"),
+    };
     // TODO: handle copy tracking
     let mut tree_diff = from_tree.diff_stream(&to_tree, matcher);
     async {
         // TODO: Resolve values concurrently
         while let Some(entry) = tree_diff.next().await {
             let (from_value, to_value) = entry.values?;
+            if let Some(value_matcher) = &value_matcher {
+                if !value_matcher.matches_values(store, &entry.path, &[&from_value, &to_value])? {
+                    continue;
+                }
+            }
             let from_value = resolve_file_values(store, &entry.path, from_value).await?;
             if from_value == to_value {
                 continue;
@@ -1189,18 +1315,35 @@ fn matches_diff_from_parent(
     index: &CompositeIndex,
     commit: &Commit,
     text_pattern: &StringPattern,
-    files_matcher: &dyn Matcher,
+    files: &FilesetExpression,
+    paths_matcher: Option<&dyn Matcher>,
+    whitespace: WhitespaceMode,
 ) -> BackendResult<bool> {
     let parents: Vec<_> = commit.parents().try_collect()?;
     // Conflict resolution is expensive, try that only for matched files.
     let from_tree = rewrite::merge_commit_trees_no_resolve_without_repo(store, &index, &parents)?;
     let to_tree = commit.tree()?;
+    // File predicates are evaluated against the changed values.
+    let value_matcher = paths_matcher.is_none().then(|| files.to_value_matcher());
+    let files_matcher = match &value_matcher {
+        Some(value_matcher) => value_matcher.candidates(),
+        None => paths_matcher.expect("paths matcher should exist without predicates"),
+    };
     // TODO: handle copy tracking
     let mut tree_diff = from_tree.diff_stream(&to_tree, files_matcher);
     async {
         // TODO: Resolve values concurrently
         while let Some(entry) = tree_diff.next().await {
             let (left_value, right_value) = entry.values?;
+            if let Some(value_matcher) = &value_matcher {
+                if !value_matcher.matches_values(
+                    store,
+                    &entry.path,
+                    &[&left_value, &right_value],
+                )? {
+                    continue;
+                }
+            }
             let left_value = resolve_file_values(store, &entry.path, left_value).await?;
             if left_value == right_value {
                 continue;
@@ -1214,8 +1357,10 @@ fn matches_diff_from_parent(
             let right_content = to_file_content(&entry.path, right_value)?;
             // Filter lines prior to comparison. This might produce inferior
             // hunks due to lack of contexts, but is way faster than full diff.
-            let left_lines = match_lines(&left_content, text_pattern);
-            let right_lines = match_lines(&right_content, text_pattern);
+            let left_lines =
+                match_lines(&left_content, text_pattern).map(|line| whitespace.normalize(line));
+            let right_lines =
+                match_lines(&right_content, text_pattern).map(|line| whitespace.normalize(line));
             if left_lines.ne(right_lines) {
                 return Ok(true);
             }
@@ -1257,7 +1402,7 @@ fn to_file_content(path: &RepoPath, value: MaterializedTreeValue) -> BackendResu
         MaterializedTreeValue::GitSubmodule(_) => Ok(vec![]),
         MaterializedTreeValue::FileConflict { contents, .. } => {
             let mut content = vec![];
-            materialize_merge_result(&contents, &mut content)
+            materialize_merge_result(&contents, ConflictMarkerStyle::default(), &mut content)
                 .expect("Failed to materialize conflict to in-memory buffer");
             Ok(content)
         }
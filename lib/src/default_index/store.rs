@@ -50,6 +50,7 @@ use crate::object_id::ObjectId;
 use crate::op_store::OpStoreError;
 use crate::op_store::OperationId;
 use crate::operation::Operation;
+use crate::settings::UserSettings;
 use crate::store::Store;
 
 // BLAKE2b-512 hash length in hex string
@@ -94,6 +95,9 @@ pub enum DefaultIndexStoreError {
 #[derive(Debug)]
 pub struct DefaultIndexStore {
     dir: PathBuf,
+    /// Whether to record paths changed by each commit when the index is built
+    /// from scratch.
+    index_changed_paths: bool,
 }
 
 impl DefaultIndexStore {
@@ -101,17 +105,19 @@ impl DefaultIndexStore {
         "default"
     }
 
-    pub fn init(dir: &Path) -> Result<Self, DefaultIndexStoreInitError> {
+    pub fn init(settings: &UserSettings, dir: &Path) -> Result<Self, DefaultIndexStoreInitError> {
         let store = DefaultIndexStore {
             dir: dir.to_owned(),
+            index_changed_paths: settings.index_changed_paths(),
         };
         store.ensure_base_dirs()?;
         Ok(store)
     }
 
-    pub fn load(dir: &Path) -> DefaultIndexStore {
+    pub fn load(settings: &UserSettings, dir: &Path) -> DefaultIndexStore {
         DefaultIndexStore {
             dir: dir.to_owned(),
+            index_changed_paths: settings.index_changed_paths(),
         }
     }
 
@@ -225,6 +231,9 @@ impl DefaultIndexStore {
             None => {
                 maybe_parent_file = None;
                 mutable_index = DefaultMutableIndex::full(commit_id_length, change_id_length);
+                if self.index_changed_paths {
+                    mutable_index.enable_changed_paths();
+                }
             }
             Some(parent_op_id) => {
                 let parent_file = self.load_index_segments_at_operation(
@@ -14,14 +14,16 @@
 
 #![allow(missing_docs)]
 
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 
 use crate::submodule_store::SubmoduleStore;
+use crate::submodule_store::SubmoduleStoreError;
 
+/// Stores the repository of each submodule in a `repos/<name>` directory.
 #[derive(Debug)]
 pub struct DefaultSubmoduleStore {
-    #[allow(dead_code)]
     path: PathBuf,
 }
 
@@ -48,4 +50,18 @@ impl SubmoduleStore for DefaultSubmoduleStore {
     fn name(&self) -> &str {
         Self::name()
     }
+
+    fn repo_path(&self, submodule_name: &str) -> Result<PathBuf, SubmoduleStoreError> {
+        // Submodule names may contain slashes, but mustn't escape the store
+        // directory.
+        let name_path = Path::new(submodule_name);
+        let is_valid = !submodule_name.is_empty()
+            && name_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+        if !is_valid {
+            return Err(SubmoduleStoreError::InvalidName(submodule_name.to_owned()));
+        }
+        Ok(self.path.join("repos").join(name_path))
+    }
 }
@@ -14,6 +14,7 @@
 
 #![allow(missing_docs)]
 
+use std::borrow::Cow;
 use std::cmp::max;
 use std::cmp::min;
 use std::cmp::Ordering;
@@ -22,9 +23,12 @@ use std::collections::HashMap;
 use std::iter;
 use std::ops::Range;
 use std::slice;
+use std::str::FromStr;
 
 use bstr::BStr;
+use bstr::ByteSlice as _;
 use itertools::Itertools;
+use thiserror::Error;
 
 pub fn find_line_ranges(text: &[u8]) -> Vec<Range<usize>> {
     text.split_inclusive(|b| *b == b'\n')
@@ -73,20 +77,134 @@ pub fn find_nonword_ranges(text: &[u8]) -> Vec<Range<usize>> {
         .collect()
 }
 
+/// Like `find_nonword_ranges()`, but keeps each run of whitespace characters
+/// as a single token so it can be compared as a whole when whitespace is
+/// ignored.
+fn find_nonword_ranges_with_whitespace_runs(text: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for i in text.iter().positions(|b| !is_word_byte(*b)) {
+        match ranges.last_mut() {
+            Some(last)
+                if last.end == i
+                    && text[last.start].is_ascii_whitespace()
+                    && text[i].is_ascii_whitespace() =>
+            {
+                last.end = i + 1;
+            }
+            _ => ranges.push(i..i + 1),
+        }
+    }
+    ranges
+}
+
+/// Algorithm used to find the unchanged regions between inputs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DiffAlgorithm {
+    /// Anchors on the least frequent tokens shared by both sides.
+    #[default]
+    Histogram,
+    /// Anchors only on tokens that occur exactly once on both sides, and falls
+    /// back to Myers for regions without such tokens.
+    Patience,
+    /// Produces a minimal diff (longest common subsequence of tokens).
+    Myers,
+}
+
+impl FromStr for DiffAlgorithm {
+    type Err = DiffOptionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "histogram" => Ok(DiffAlgorithm::Histogram),
+            "patience" => Ok(DiffAlgorithm::Patience),
+            "myers" => Ok(DiffAlgorithm::Myers),
+            _ => Err(DiffOptionParseError(s.to_owned())),
+        }
+    }
+}
+
+/// How whitespace is taken into account when comparing tokens.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WhitespaceMode {
+    /// Tokens have to match byte by byte.
+    #[default]
+    Exact,
+    /// Ignores all whitespace characters.
+    IgnoreAllSpace,
+    /// Ignores trailing whitespace and treats runs of whitespace characters as
+    /// equivalent.
+    IgnoreSpaceChange,
+}
+
+impl WhitespaceMode {
+    /// Returns the form of `token` used for comparison.
+    pub fn normalize(self, token: &[u8]) -> Cow<'_, [u8]> {
+        match self {
+            WhitespaceMode::Exact => Cow::Borrowed(token),
+            WhitespaceMode::IgnoreAllSpace => {
+                if token.iter().any(u8::is_ascii_whitespace) {
+                    Cow::Owned(
+                        token
+                            .iter()
+                            .copied()
+                            .filter(|b| !b.is_ascii_whitespace())
+                            .collect(),
+                    )
+                } else {
+                    Cow::Borrowed(token)
+                }
+            }
+            WhitespaceMode::IgnoreSpaceChange => {
+                let token = token.trim_end_with(|c| c.is_ascii_whitespace());
+                let mut normalized = Vec::with_capacity(token.len());
+                for (is_space, group) in &token.iter().chunk_by(|b| b.is_ascii_whitespace()) {
+                    if is_space {
+                        normalized.push(b' ');
+                    } else {
+                        normalized.extend(group);
+                    }
+                }
+                Cow::Owned(normalized)
+            }
+        }
+    }
+}
+
+impl FromStr for WhitespaceMode {
+    type Err = DiffOptionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(WhitespaceMode::Exact),
+            "ignore-all-space" => Ok(WhitespaceMode::IgnoreAllSpace),
+            "ignore-space-change" => Ok(WhitespaceMode::IgnoreSpaceChange),
+            _ => Err(DiffOptionParseError(s.to_owned())),
+        }
+    }
+}
+
+/// Error occurred when parsing the name of a diff option.
+#[derive(Debug, Error)]
+#[error("Invalid diff option: {0}")]
+pub struct DiffOptionParseError(pub String);
+
+/// Options controlling how unchanged regions are found.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DiffOptions {
+    pub algorithm: DiffAlgorithm,
+    pub whitespace: WhitespaceMode,
+}
+
 struct Histogram<'a> {
     word_to_positions: HashMap<&'a [u8], Vec<usize>>,
     count_to_words: BTreeMap<usize, Vec<&'a [u8]>>,
 }
 
 impl Histogram<'_> {
-    fn calculate<'a>(
-        text: &'a [u8],
-        ranges: &[Range<usize>],
-        max_occurrences: usize,
-    ) -> Histogram<'a> {
+    fn calculate<'a>(words: &[&'a [u8]], max_occurrences: usize) -> Histogram<'a> {
         let mut word_to_positions: HashMap<&[u8], Vec<usize>> = HashMap::new();
-        for (i, range) in ranges.iter().enumerate() {
-            let positions = word_to_positions.entry(&text[range.clone()]).or_default();
+        for (i, word) in words.iter().enumerate() {
+            let positions = word_to_positions.entry(word).or_default();
             // Allow one more than max_occurrences, so we can later skip those with more
             // than max_occurrences
             if positions.len() <= max_occurrences {
@@ -162,68 +280,123 @@ fn find_lcs(input: &[usize]) -> Vec<(usize, usize)> {
 
 /// Finds unchanged ranges among the ones given as arguments. The data between
 /// those ranges is ignored.
+///
+/// If whitespace is ignored, the paired ranges may be of different lengths.
 pub(crate) fn unchanged_ranges(
     left: &[u8],
     right: &[u8],
     left_ranges: &[Range<usize>],
     right_ranges: &[Range<usize>],
+    options: &DiffOptions,
 ) -> Vec<(Range<usize>, Range<usize>)> {
-    if left_ranges.is_empty() || right_ranges.is_empty() {
+    let normalize = |text: &[u8], ranges: &[Range<usize>]| -> Vec<Vec<u8>> {
+        ranges
+            .iter()
+            .map(|range| {
+                options
+                    .whitespace
+                    .normalize(&text[range.clone()])
+                    .into_owned()
+            })
+            .collect()
+    };
+    let (left_words, right_words);
+    let (left_keys, right_keys): (Vec<&[u8]>, Vec<&[u8]>) = match options.whitespace {
+        WhitespaceMode::Exact => (
+            left_ranges
+                .iter()
+                .map(|range| &left[range.clone()])
+                .collect(),
+            right_ranges
+                .iter()
+                .map(|range| &right[range.clone()])
+                .collect(),
+        ),
+        WhitespaceMode::IgnoreAllSpace | WhitespaceMode::IgnoreSpaceChange => {
+            left_words = normalize(left, left_ranges);
+            right_words = normalize(right, right_ranges);
+            (
+                left_words.iter().map(Vec::as_slice).collect(),
+                right_words.iter().map(Vec::as_slice).collect(),
+            )
+        }
+    };
+    unchanged_words(&left_keys, &right_keys, options.algorithm)
+        .into_iter()
+        .map(|(l, r)| (left_ranges[l].clone(), right_ranges[r].clone()))
+        .collect()
+}
+
+/// Finds unchanged words. Returns pairs of indices into `left` and `right`.
+fn unchanged_words(
+    left: &[&[u8]],
+    right: &[&[u8]],
+    algorithm: DiffAlgorithm,
+) -> Vec<(usize, usize)> {
+    if left.is_empty() || right.is_empty() {
         return vec![];
     }
+    if algorithm == DiffAlgorithm::Myers {
+        let mut result = vec![];
+        collect_unchanged_words_myers(left, right, 0, 0, &mut result);
+        return result;
+    }
 
     // Prioritize LCS-based algorithm than leading/trailing matches
-    let result = unchanged_ranges_lcs(left, right, left_ranges, right_ranges);
+    let result = unchanged_words_lcs(left, right, algorithm);
     if !result.is_empty() {
         return result;
     }
 
-    // Trim leading common ranges (i.e. grow previous unchanged region)
-    let common_leading_len = iter::zip(left_ranges, right_ranges)
-        .take_while(|&(l, r)| left[l.clone()] == right[r.clone()])
-        .count();
-    let (left_leading_ranges, left_ranges) = left_ranges.split_at(common_leading_len);
-    let (right_leading_ranges, right_ranges) = right_ranges.split_at(common_leading_len);
+    // Trim leading common words (i.e. grow previous unchanged region)
+    let common_leading_len = iter::zip(left, right).take_while(|(l, r)| l == r).count();
+    let left_rest = &left[common_leading_len..];
+    let right_rest = &right[common_leading_len..];
 
-    // Trim trailing common ranges (i.e. grow next unchanged region)
-    let common_trailing_len = iter::zip(left_ranges.iter().rev(), right_ranges.iter().rev())
-        .take_while(|&(l, r)| left[l.clone()] == right[r.clone()])
+    // Trim trailing common words (i.e. grow next unchanged region)
+    let common_trailing_len = iter::zip(left_rest.iter().rev(), right_rest.iter().rev())
+        .take_while(|(l, r)| l == r)
         .count();
-    let left_trailing_ranges = &left_ranges[(left_ranges.len() - common_trailing_len)..];
-    let right_trailing_ranges = &right_ranges[(right_ranges.len() - common_trailing_len)..];
-
-    itertools::chain(
-        iter::zip(
-            left_leading_ranges.iter().cloned(),
-            right_leading_ranges.iter().cloned(),
-        ),
-        iter::zip(
-            left_trailing_ranges.iter().cloned(),
-            right_trailing_ranges.iter().cloned(),
-        ),
-    )
-    .collect()
+    let left_trailing_start = left.len() - common_trailing_len;
+    let right_trailing_start = right.len() - common_trailing_len;
+
+    let mut result = (0..common_leading_len).map(|i| (i, i)).collect_vec();
+    if algorithm == DiffAlgorithm::Patience {
+        // There are no unique words to anchor on. Find the minimal diff instead
+        // of giving up on the whole region.
+        collect_unchanged_words_myers(
+            &left[common_leading_len..left_trailing_start],
+            &right[common_leading_len..right_trailing_start],
+            common_leading_len,
+            common_leading_len,
+            &mut result,
+        );
+    }
+    result.extend(
+        (0..common_trailing_len).map(|i| (left_trailing_start + i, right_trailing_start + i)),
+    );
+    result
 }
 
-fn unchanged_ranges_lcs(
-    left: &[u8],
-    right: &[u8],
-    left_ranges: &[Range<usize>],
-    right_ranges: &[Range<usize>],
-) -> Vec<(Range<usize>, Range<usize>)> {
+fn unchanged_words_lcs(
+    left: &[&[u8]],
+    right: &[&[u8]],
+    algorithm: DiffAlgorithm,
+) -> Vec<(usize, usize)> {
     let max_occurrences = 100;
-    let left_histogram = Histogram::calculate(left, left_ranges, max_occurrences);
+    let left_histogram = Histogram::calculate(left, max_occurrences);
     if *left_histogram.count_to_words.keys().next().unwrap() > max_occurrences {
         // If there are very many occurrences of all words, then we just give up.
         return vec![];
     }
-    let right_histogram = Histogram::calculate(right, right_ranges, max_occurrences);
+    let right_histogram = Histogram::calculate(right, max_occurrences);
     // Look for words with few occurrences in `left` (could equally well have picked
     // `right`?). If any of them also occur in `right`, then we add the words to
-    // the LCS.
+    // the LCS. Patience diff only considers words occurring once.
     let Some(uncommon_shared_words) = left_histogram
         .count_to_words
         .iter()
+        .filter(|(&left_count, _)| algorithm != DiffAlgorithm::Patience || left_count == 1)
         .map(|(left_count, left_words)| -> Vec<&[u8]> {
             left_words
                 .iter()
@@ -242,7 +415,7 @@ fn unchanged_ranges_lcs(
         return vec![];
     };
 
-    // [(index into left_ranges, word, occurrence #)]
+    // [(index into left, word, occurrence #)]
     let mut left_positions = vec![];
     let mut right_positions = vec![];
     for uncommon_shared_word in uncommon_shared_words {
@@ -275,67 +448,195 @@ fn unchanged_ranges_lcs(
 
     let lcs = find_lcs(&left_index_by_right_index);
 
-    // Produce output ranges, recursing into the modified areas between the elements
+    // Produce output pairs, recursing into the modified areas between the elements
     // in the LCS.
     let mut result = vec![];
     let mut previous_left_position = 0;
     let mut previous_right_position = 0;
+    let recurse = |left_positions: Range<usize>, right_positions: Range<usize>| {
+        if !left_positions.is_empty() || !right_positions.is_empty() {
+            unchanged_words(
+                &left[left_positions.clone()],
+                &right[right_positions.clone()],
+                algorithm,
+            )
+            .into_iter()
+            .map(move |(l, r)| (l + left_positions.start, r + right_positions.start))
+            .collect_vec()
+        } else {
+            vec![]
+        }
+    };
     for (left_index, right_index) in lcs {
         let left_position = left_positions[left_index].0;
         let right_position = right_positions[right_index].0;
-        let skipped_left_positions = previous_left_position..left_position;
-        let skipped_right_positions = previous_right_position..right_position;
-        if !skipped_left_positions.is_empty() || !skipped_right_positions.is_empty() {
-            for unchanged_nested_range in unchanged_ranges(
-                left,
-                right,
-                &left_ranges[skipped_left_positions.clone()],
-                &right_ranges[skipped_right_positions.clone()],
-            ) {
-                result.push(unchanged_nested_range);
-            }
-        }
-        result.push((
-            left_ranges[left_position].clone(),
-            right_ranges[right_position].clone(),
+        result.extend(recurse(
+            previous_left_position..left_position,
+            previous_right_position..right_position,
         ));
+        result.push((left_position, right_position));
         previous_left_position = left_position + 1;
         previous_right_position = right_position + 1;
     }
     // Also recurse into range at end (after common ranges).
-    let skipped_left_positions = previous_left_position..left_ranges.len();
-    let skipped_right_positions = previous_right_position..right_ranges.len();
-    if !skipped_left_positions.is_empty() || !skipped_right_positions.is_empty() {
-        for unchanged_nested_range in unchanged_ranges(
-            left,
-            right,
-            &left_ranges[skipped_left_positions],
-            &right_ranges[skipped_right_positions],
-        ) {
-            result.push(unchanged_nested_range);
+    result.extend(recurse(
+        previous_left_position..left.len(),
+        previous_right_position..right.len(),
+    ));
+
+    result
+}
+
+/// Collects the longest common subsequence of `left` and `right` by using
+/// Myers' algorithm. The indices are shifted by the given offsets.
+fn collect_unchanged_words_myers(
+    left: &[&[u8]],
+    right: &[&[u8]],
+    left_offset: usize,
+    right_offset: usize,
+    result: &mut Vec<(usize, usize)>,
+) {
+    let common_leading_len = iter::zip(left, right).take_while(|(l, r)| l == r).count();
+    result.extend((0..common_leading_len).map(|i| (left_offset + i, right_offset + i)));
+    let left = &left[common_leading_len..];
+    let right = &right[common_leading_len..];
+    let left_offset = left_offset + common_leading_len;
+    let right_offset = right_offset + common_leading_len;
+
+    let common_trailing_len = iter::zip(left.iter().rev(), right.iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let left = &left[..left.len() - common_trailing_len];
+    let right = &right[..right.len() - common_trailing_len];
+
+    if !left.is_empty() && !right.is_empty() {
+        if let Some((x, y)) = find_middle_snake(left, right) {
+            collect_unchanged_words_myers(
+                &left[..x],
+                &right[..y],
+                left_offset,
+                right_offset,
+                result,
+            );
+            collect_unchanged_words_myers(
+                &left[x..],
+                &right[y..],
+                left_offset + x,
+                right_offset + y,
+                result,
+            );
         }
     }
 
-    result
+    result.extend(
+        (0..common_trailing_len)
+            .map(|i| (left_offset + left.len() + i, right_offset + right.len() + i)),
+    );
+}
+
+/// Finds a point on a shortest edit path by walking from both ends at once in
+/// linear space. Returns `None` if there's no point to split the inputs at.
+fn find_middle_snake(left: &[&[u8]], right: &[&[u8]]) -> Option<(usize, usize)> {
+    let n = left.len() as isize;
+    let m = right.len() as isize;
+    let max_d = (n + m + 1) / 2;
+    let v_offset = max_d;
+    let v_len = 2 * max_d + 2;
+    // Furthest reaching x for each diagonal, from the start and from the end.
+    let mut forward = vec![-1; v_len as usize];
+    let mut backward = vec![-1; v_len as usize];
+    forward[(v_offset + 1) as usize] = 0;
+    backward[(v_offset + 1) as usize] = 0;
+    let delta = n - m;
+    // Paths can only overlap after the forward walk if the delta is odd.
+    let check_forward = delta % 2 != 0;
+    let split = |x: isize, y: isize| {
+        let (x, y) = (x as usize, y as usize);
+        ((x, y) != (0, 0) && (x, y) != (left.len(), right.len())).then_some((x, y))
+    };
+    // Diagonals outside of the edit graph are skipped.
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+    for d in 0..max_d {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let k1_index = (v_offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && forward[k1_index - 1] < forward[k1_index + 1]) {
+                forward[k1_index + 1]
+            } else {
+                forward[k1_index - 1] + 1
+            };
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && left[x1 as usize] == right[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            forward[k1_index] = x1;
+            if x1 > n {
+                k1_end += 2;
+            } else if y1 > m {
+                k1_start += 2;
+            } else if check_forward {
+                let k2_index = v_offset + delta - k1;
+                if (0..v_len).contains(&k2_index) && backward[k2_index as usize] != -1 {
+                    let x2 = n - backward[k2_index as usize];
+                    if x1 >= x2 {
+                        return split(x1, y1);
+                    }
+                }
+            }
+            k1 += 2;
+        }
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let k2_index = (v_offset + k2) as usize;
+            let mut x2 = if k2 == -d || (k2 != d && backward[k2_index - 1] < backward[k2_index + 1])
+            {
+                backward[k2_index + 1]
+            } else {
+                backward[k2_index - 1] + 1
+            };
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && left[(n - x2 - 1) as usize] == right[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            backward[k2_index] = x2;
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !check_forward {
+                let k1_index = v_offset + delta - k2;
+                if (0..v_len).contains(&k1_index) && forward[k1_index as usize] != -1 {
+                    let x1 = forward[k1_index as usize];
+                    let y1 = v_offset + x1 - k1_index;
+                    if x1 >= n - x2 {
+                        return split(x1, y1);
+                    }
+                }
+            }
+            k2 += 2;
+        }
+    }
+    None
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 struct UnchangedRange {
     base_range: Range<usize>,
-    offsets: Vec<isize>,
+    // Corresponding range in each non-base input. These are of the same length
+    // as the base range unless whitespace is ignored.
+    other_ranges: Vec<Range<usize>>,
 }
 
 impl UnchangedRange {
     fn start(&self, side: usize) -> usize {
-        self.base_range
-            .start
-            .wrapping_add(self.offsets[side] as usize)
+        self.other_ranges[side].start
     }
 
     fn end(&self, side: usize) -> usize {
-        self.base_range
-            .end
-            .wrapping_add(self.offsets[side] as usize)
+        self.other_ranges[side].end
     }
 }
 
@@ -360,15 +661,34 @@ impl Ord for UnchangedRange {
 pub struct Diff<'input> {
     base_input: &'input BStr,
     other_inputs: Vec<&'input BStr>,
-    // The key is a range in the base input. The value is the start of each non-base region
-    // relative to the base region's start. By making them relative, they don't need to change
-    // when the base range changes.
+    options: DiffOptions,
+    // Sorted by the range in the base input. Each region also records the
+    // matching range in each non-base input.
     unchanged_regions: Vec<UnchangedRange>,
 }
 
+/// Maps `sub_range` of `base_range` to the corresponding part of `side_range`.
+///
+/// If the ranges only match when ignoring whitespace, their lengths may differ
+/// and there's no byte-wise correspondence. Such ranges can't be split.
+fn map_sub_range(
+    base_range: &Range<usize>,
+    side_range: &Range<usize>,
+    sub_range: &Range<usize>,
+) -> Option<Range<usize>> {
+    if base_range.len() == side_range.len() {
+        let start = side_range.start + (sub_range.start - base_range.start);
+        Some(start..start + sub_range.len())
+    } else if sub_range == base_range {
+        Some(side_range.clone())
+    } else {
+        None
+    }
+}
+
 /// Takes the current regions and intersects it with the new unchanged ranges
 /// from a 2-way diff. The result is a map of unchanged regions with one more
-/// offset in the map's values.
+/// range in the map's values.
 fn intersect_regions(
     current_ranges: Vec<UnchangedRange>,
     new_unchanged_ranges: &[(Range<usize>, Range<usize>)],
@@ -376,10 +696,9 @@ fn intersect_regions(
     let mut result = vec![];
     let mut current_ranges_iter = current_ranges.into_iter().peekable();
     for (new_base_range, other_range) in new_unchanged_ranges.iter() {
-        assert_eq!(new_base_range.len(), other_range.len());
         while let Some(UnchangedRange {
             base_range,
-            offsets,
+            other_ranges,
         }) = current_ranges_iter.peek()
         {
             // No need to look further if we're past the new range.
@@ -394,12 +713,18 @@ fn intersect_regions(
             }
             let new_start = max(base_range.start, new_base_range.start);
             let new_end = min(base_range.end, new_base_range.end);
-            let mut new_offsets = offsets.clone();
-            new_offsets.push(other_range.start.wrapping_sub(new_base_range.start) as isize);
-            result.push(UnchangedRange {
-                base_range: new_start..new_end,
-                offsets: new_offsets,
-            });
+            let sub_range = new_start..new_end;
+            let new_other_ranges: Option<Vec<_>> = other_ranges
+                .iter()
+                .map(|range| map_sub_range(base_range, range, &sub_range))
+                .chain([map_sub_range(new_base_range, other_range, &sub_range)])
+                .collect();
+            if let Some(new_other_ranges) = new_other_ranges {
+                result.push(UnchangedRange {
+                    base_range: sub_range,
+                    other_ranges: new_other_ranges,
+                });
+            }
             if base_range.end >= new_base_range.end {
                 // Break without consuming the item; there may be other new ranges that overlap
                 // with it.
@@ -415,6 +740,14 @@ impl<'input> Diff<'input> {
     pub fn for_tokenizer<T: AsRef<[u8]> + ?Sized + 'input>(
         inputs: impl IntoIterator<Item = &'input T>,
         tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
+    ) -> Self {
+        Diff::for_tokenizer_with_options(inputs, tokenizer, &DiffOptions::default())
+    }
+
+    pub fn for_tokenizer_with_options<T: AsRef<[u8]> + ?Sized + 'input>(
+        inputs: impl IntoIterator<Item = &'input T>,
+        tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>,
+        options: &DiffOptions,
     ) -> Self {
         let mut inputs = inputs.into_iter().map(BStr::new);
         let base_input = inputs.next().expect("inputs must not be empty");
@@ -439,6 +772,7 @@ impl<'input> Diff<'input> {
             other_inputs,
             &base_token_ranges,
             &other_token_ranges,
+            options,
         )
     }
 
@@ -447,6 +781,7 @@ impl<'input> Diff<'input> {
         other_inputs: Vec<&'input BStr>,
         base_token_ranges: &[Range<usize>],
         other_token_ranges: &[Vec<Range<usize>>],
+        options: &DiffOptions,
     ) -> Self {
         // Look for unchanged regions. Initially consider the whole range of the base
         // input as unchanged (compared to itself). Then diff each other input
@@ -454,7 +789,7 @@ impl<'input> Diff<'input> {
         // unchanged ranges in the diff.
         let mut unchanged_regions = vec![UnchangedRange {
             base_range: 0..base_input.len(),
-            offsets: vec![],
+            other_ranges: vec![],
         }];
         for (i, other_token_ranges) in other_token_ranges.iter().enumerate() {
             let unchanged_diff_ranges = unchanged_ranges(
@@ -462,22 +797,24 @@ impl<'input> Diff<'input> {
                 other_inputs[i],
                 base_token_ranges,
                 other_token_ranges,
+                options,
             );
             unchanged_regions = intersect_regions(unchanged_regions, &unchanged_diff_ranges);
         }
         // Add an empty range at the end to make life easier for hunks().
-        let offsets = other_inputs
+        let other_ranges = other_inputs
             .iter()
-            .map(|input| input.len().wrapping_sub(base_input.len()) as isize)
+            .map(|input| input.len()..input.len())
             .collect_vec();
         unchanged_regions.push(UnchangedRange {
             base_range: base_input.len()..base_input.len(),
-            offsets,
+            other_ranges,
         });
 
         let mut diff = Self {
             base_input,
             other_inputs,
+            options: *options,
             unchanged_regions,
         };
         diff.compact_unchanged_regions();
@@ -497,6 +834,18 @@ impl<'input> Diff<'input> {
         Diff::for_tokenizer(inputs, find_line_ranges)
     }
 
+    /// Compares `inputs` line by line with the given algorithm and whitespace
+    /// handling.
+    ///
+    /// Lines that only match when ignoring whitespace are reported as
+    /// `DiffHunk::Matching` with the content of the first input.
+    pub fn by_line_with_options<T: AsRef<[u8]> + ?Sized + 'input>(
+        inputs: impl IntoIterator<Item = &'input T>,
+        options: &DiffOptions,
+    ) -> Self {
+        Diff::for_tokenizer_with_options(inputs, find_line_ranges, options)
+    }
+
     /// Compares `inputs` word by word.
     ///
     /// The `inputs` is usually a changed hunk (e.g. a `DiffHunk::Different`)
@@ -504,18 +853,31 @@ impl<'input> Diff<'input> {
     pub fn by_word<T: AsRef<[u8]> + ?Sized + 'input>(
         inputs: impl IntoIterator<Item = &'input T>,
     ) -> Self {
-        let mut diff = Diff::for_tokenizer(inputs, find_word_ranges);
-        diff.refine_changed_regions(find_nonword_ranges);
+        Diff::by_word_with_options(inputs, &DiffOptions::default())
+    }
+
+    /// Compares `inputs` word by word with the given algorithm and whitespace
+    /// handling.
+    pub fn by_word_with_options<T: AsRef<[u8]> + ?Sized + 'input>(
+        inputs: impl IntoIterator<Item = &'input T>,
+        options: &DiffOptions,
+    ) -> Self {
+        let mut diff = Diff::for_tokenizer_with_options(inputs, find_word_ranges, options);
+        if options.whitespace == WhitespaceMode::Exact {
+            diff.refine_changed_regions(find_nonword_ranges);
+        } else {
+            diff.refine_changed_regions(find_nonword_ranges_with_whitespace_runs);
+        }
         diff
     }
 
     pub fn hunks<'diff>(&'diff self) -> DiffHunkIterator<'diff, 'input> {
-        let previous_offsets = vec![0; self.other_inputs.len()];
+        let previous_ranges = vec![0..0; self.other_inputs.len()];
         DiffHunkIterator {
             diff: self,
             previous: UnchangedRange {
                 base_range: 0..0,
-                offsets: previous_offsets,
+                other_ranges: previous_ranges,
             },
             unchanged_emitted: true,
             unchanged_iter: self.unchanged_regions.iter(),
@@ -527,38 +889,39 @@ impl<'input> Diff<'input> {
     pub fn refine_changed_regions(&mut self, tokenizer: impl Fn(&[u8]) -> Vec<Range<usize>>) {
         let mut previous = UnchangedRange {
             base_range: 0..0,
-            offsets: vec![0; self.other_inputs.len()],
+            other_ranges: vec![0..0; self.other_inputs.len()],
         };
         let mut new_unchanged_ranges = vec![];
         for current in self.unchanged_regions.iter() {
             // For the changed region between the previous region and the current one,
-            // create a new Diff instance. Then adjust the start positions and
-            // offsets to be valid in the context of the larger Diff instance
-            // (`self`).
+            // create a new Diff instance. Then adjust the ranges to be valid in the
+            // context of the larger Diff instance (`self`).
             let mut slices =
                 vec![&self.base_input[previous.base_range.end..current.base_range.start]];
-            for i in 0..current.offsets.len() {
+            for i in 0..current.other_ranges.len() {
                 let changed_range = previous.end(i)..current.start(i);
                 slices.push(&self.other_inputs[i][changed_range]);
             }
 
-            let refined_diff = Diff::for_tokenizer(slices, &tokenizer);
+            let refined_diff = Diff::for_tokenizer_with_options(slices, &tokenizer, &self.options);
 
             for UnchangedRange {
                 base_range,
-                offsets,
+                other_ranges,
             } in refined_diff.unchanged_regions
             {
-                let new_base_start = base_range.start + previous.base_range.end;
-                let new_base_end = base_range.end + previous.base_range.end;
-                let offsets = offsets
+                let base_start = previous.base_range.end;
+                let other_ranges = other_ranges
                     .into_iter()
                     .enumerate()
-                    .map(|(i, offset)| offset + previous.offsets[i])
+                    .map(|(i, range)| {
+                        let start = previous.end(i);
+                        range.start + start..range.end + start
+                    })
                     .collect_vec();
                 new_unchanged_ranges.push(UnchangedRange {
-                    base_range: new_base_start..new_base_end,
-                    offsets,
+                    base_range: base_range.start + base_start..base_range.end + base_start,
+                    other_ranges,
                 });
             }
             previous = current.clone();
@@ -578,11 +941,14 @@ impl<'input> Diff<'input> {
         for current in self.unchanged_regions.iter() {
             if let Some(previous) = maybe_previous {
                 if previous.base_range.end == current.base_range.start
-                    && previous.offsets == *current.offsets
+                    && iter::zip(&previous.other_ranges, &current.other_ranges)
+                        .all(|(previous, current)| previous.end == current.start)
                 {
                     maybe_previous = Some(UnchangedRange {
                         base_range: previous.base_range.start..current.base_range.end,
-                        offsets: current.offsets.clone(),
+                        other_ranges: iter::zip(&previous.other_ranges, &current.other_ranges)
+                            .map(|(previous, current)| previous.start..current.end)
+                            .collect(),
                     });
                     continue;
                 }
@@ -790,6 +1156,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_nonword_ranges_with_whitespace_runs() {
+        assert_eq!(
+            find_nonword_ranges_with_whitespace_runs(b"a  b\t(\n c) "),
+            vec![1..3, 4..5, 5..6, 6..8, 9..10, 10..11]
+        );
+    }
+
     #[test]
     fn test_unchanged_ranges_insert_in_middle() {
         assert_eq!(
@@ -798,6 +1172,7 @@ mod tests {
                 b"a b X b c",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7, 8..9],
+                &DiffOptions::default(),
             ),
             vec![(0..1, 0..1), (2..3, 2..3), (4..5, 6..7), (6..7, 8..9)]
         );
@@ -813,6 +1188,7 @@ mod tests {
                 b"a b a c",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7],
+                &DiffOptions::default(),
             ),
             vec![(0..1, 0..1)]
         );
@@ -822,6 +1198,7 @@ mod tests {
                 b"b a c a",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7],
+                &DiffOptions::default(),
             ),
             vec![(6..7, 6..7)]
         );
@@ -831,6 +1208,7 @@ mod tests {
                 b"b a a c",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7],
+                &DiffOptions::default(),
             ),
             vec![]
         );
@@ -840,6 +1218,7 @@ mod tests {
                 b"a b c a",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7],
+                &DiffOptions::default(),
             ),
             vec![(0..1, 0..1), (6..7, 6..7)]
         );
@@ -855,6 +1234,7 @@ mod tests {
                 b"a a a a",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7],
+                &DiffOptions::default(),
             ),
             vec![(0..1, 0..1)]
         );
@@ -864,6 +1244,7 @@ mod tests {
                 b"a a a a",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7],
+                &DiffOptions::default(),
             ),
             vec![(6..7, 6..7)]
         );
@@ -873,6 +1254,7 @@ mod tests {
                 b"a a a a",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7],
+                &DiffOptions::default(),
             ),
             vec![]
         );
@@ -882,6 +1264,7 @@ mod tests {
                 b"a a a a",
                 &[0..1, 2..3, 4..5, 6..7],
                 &[0..1, 2..3, 4..5, 6..7],
+                &DiffOptions::default(),
             ),
             vec![(0..1, 0..1), (6..7, 6..7)]
         );
@@ -899,18 +1282,18 @@ mod tests {
         let actual = intersect_regions(
             vec![UnchangedRange {
                 base_range: 20..70,
-                offsets: vec![3],
+                other_ranges: iter::once(23..73).collect(),
             }],
             &[(25..30, 35..40), (40..50, 40..50)],
         );
         let expected = vec![
             UnchangedRange {
                 base_range: 25..30,
-                offsets: vec![3, 10],
+                other_ranges: vec![28..33, 35..40],
             },
             UnchangedRange {
                 base_range: 40..50,
-                offsets: vec![3, 0],
+                other_ranges: vec![43..53, 40..50],
             },
         ];
         assert_eq!(actual, expected);
@@ -921,18 +1304,18 @@ mod tests {
         let actual = intersect_regions(
             vec![UnchangedRange {
                 base_range: 20..50,
-                offsets: vec![-3],
+                other_ranges: iter::once(17..47).collect(),
             }],
             &[(15..25, 5..15), (45..60, 55..70)],
         );
         let expected = vec![
             UnchangedRange {
                 base_range: 20..25,
-                offsets: vec![-3, -10],
+                other_ranges: vec![17..22, 10..15],
             },
             UnchangedRange {
                 base_range: 45..50,
-                offsets: vec![-3, 10],
+                other_ranges: vec![42..47, 55..60],
             },
         ];
         assert_eq!(actual, expected);
@@ -944,11 +1327,11 @@ mod tests {
             vec![
                 UnchangedRange {
                     base_range: 20..50,
-                    offsets: vec![3, -8],
+                    other_ranges: vec![23..53, 12..42],
                 },
                 UnchangedRange {
                     base_range: 70..80,
-                    offsets: vec![7, 1],
+                    other_ranges: vec![77..87, 71..81],
                 },
             ],
             &[(10..100, 5..95)],
@@ -956,11 +1339,11 @@ mod tests {
         let expected = vec![
             UnchangedRange {
                 base_range: 20..50,
-                offsets: vec![3, -8, -5],
+                other_ranges: vec![23..53, 12..42, 15..45],
             },
             UnchangedRange {
                 base_range: 70..80,
-                offsets: vec![7, 1, -5],
+                other_ranges: vec![77..87, 71..81, 65..75],
             },
         ];
         assert_eq!(actual, expected);
@@ -1301,4 +1684,157 @@ int main(int argc, char **argv)
             ]
         );
     }
+
+    #[test]
+    fn test_whitespace_mode_normalize() {
+        let normalize = |mode: WhitespaceMode, text: &str| -> String {
+            String::from_utf8(mode.normalize(text.as_bytes()).into_owned()).unwrap()
+        };
+        assert_eq!(normalize(WhitespaceMode::Exact, " a \tb \n"), " a \tb \n");
+        assert_eq!(normalize(WhitespaceMode::IgnoreAllSpace, " a \tb \n"), "ab");
+        assert_eq!(
+            normalize(WhitespaceMode::IgnoreSpaceChange, " a \tb \n"),
+            " a b"
+        );
+        assert_eq!(normalize(WhitespaceMode::IgnoreSpaceChange, "a b"), "a b");
+    }
+
+    #[test]
+    fn test_diff_by_line_ignore_all_space() {
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::IgnoreAllSpace,
+            ..DiffOptions::default()
+        };
+        let diff = Diff::by_line_with_options(["a\n b\nc\nd\n", "a\nb \nC\n\td\n"], &options);
+        assert_eq!(
+            diff.hunks().collect_vec(),
+            vec![
+                DiffHunk::matching("a\n b\n"),
+                DiffHunk::different(["c\n", "C\n"]),
+                DiffHunk::matching("d\n"),
+            ]
+        );
+        // Whitespace is significant by default
+        let diff = Diff::by_line(["a\n b\nc\nd\n", "a\nb \nC\n\td\n"]);
+        assert_eq!(
+            diff.hunks().collect_vec(),
+            vec![
+                DiffHunk::matching("a\n"),
+                DiffHunk::different([" b\nc\nd\n", "b \nC\n\td\n"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_by_line_ignore_space_change() {
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::IgnoreSpaceChange,
+            ..DiffOptions::default()
+        };
+        let diff = Diff::by_line_with_options(["a  b\nc\n x\n", "a b  \nc\nx\n"], &options);
+        assert_eq!(
+            diff.hunks().collect_vec(),
+            vec![
+                DiffHunk::matching("a  b\nc\n"),
+                DiffHunk::different([" x\n", "x\n"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_multiple_inputs_ignore_all_space() {
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::IgnoreAllSpace,
+            ..DiffOptions::default()
+        };
+        let diff =
+            Diff::by_line_with_options(["a\nb\nc\n", "a \n b\nX\n", "a\n\tb\nc \n"], &options);
+        assert_eq!(
+            diff.hunks().collect_vec(),
+            vec![
+                DiffHunk::matching("a\nb\n"),
+                DiffHunk::different(["c\n", "X\n", "c \n"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_by_line_patience() {
+        let options = DiffOptions {
+            algorithm: DiffAlgorithm::Patience,
+            ..DiffOptions::default()
+        };
+        // "b" is the only line unique to both sides. The regions around it have
+        // no unique lines, and are diffed minimally.
+        let diff = Diff::by_line_with_options(["a\na\nb\nc\nx\nc\n", "a\nb\nc\nc\n"], &options);
+        assert_eq!(
+            diff.hunks().collect_vec(),
+            vec![
+                DiffHunk::matching("a\n"),
+                DiffHunk::different(["a\n", ""]),
+                DiffHunk::matching("b\nc\n"),
+                DiffHunk::different(["x\n", ""]),
+                DiffHunk::matching("c\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_by_line_myers() {
+        let options = DiffOptions {
+            algorithm: DiffAlgorithm::Myers,
+            ..DiffOptions::default()
+        };
+        let diff =
+            Diff::by_line_with_options(["a\nb\nc\na\nb\nb\na\n", "c\nb\na\nb\na\nc\n"], &options);
+        // The classic example from Myers' paper. The edit script has length 5.
+        let hunks = diff.hunks().collect_vec();
+        let num_changed_lines: usize = hunks
+            .iter()
+            .map(|hunk| match hunk {
+                DiffHunk::Matching(_) => 0,
+                DiffHunk::Different(contents) => contents
+                    .iter()
+                    .map(|content| find_line_ranges(content).len())
+                    .sum(),
+            })
+            .sum();
+        assert_eq!(num_changed_lines, 5);
+    }
+
+    #[test]
+    fn test_unchanged_words_myers_is_minimal() {
+        fn lcs_len(left: &[&[u8]], right: &[&[u8]]) -> usize {
+            let mut table = vec![vec![0; right.len() + 1]; left.len() + 1];
+            for i in 0..left.len() {
+                for j in 0..right.len() {
+                    table[i + 1][j + 1] = if left[i] == right[j] {
+                        table[i][j] + 1
+                    } else {
+                        max(table[i][j + 1], table[i + 1][j])
+                    };
+                }
+            }
+            table[left.len()][right.len()]
+        }
+
+        let words: [&[u8]; 4] = [b"a", b"b", b"c", b"d"];
+        // Simple linear congruential generator to get reproducible inputs
+        let mut state: u32 = 1;
+        let mut next = |bound: usize| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as usize % bound
+        };
+        for _ in 0..500 {
+            let left = (0..next(12)).map(|_| words[next(4)]).collect_vec();
+            let right = (0..next(12)).map(|_| words[next(4)]).collect_vec();
+            let pairs = unchanged_words(&left, &right, DiffAlgorithm::Myers);
+            assert_eq!(pairs.len(), lcs_len(&left, &right), "{left:?} {right:?}");
+            assert!(pairs.iter().all(|&(l, r)| left[l] == right[r]));
+            assert!(pairs
+                .iter()
+                .tuple_windows()
+                .all(|(&(l1, r1), &(l2, r2))| l1 < l2 && r1 < r2));
+        }
+    }
 }
@@ -15,14 +15,22 @@
 //! Functional language for selecting a set of paths.
 
 use std::collections::HashMap;
+use std::io;
+use std::io::Read as _;
 use std::iter;
+use std::ops::Range;
 use std::path;
 use std::slice;
 
+use futures::executor::block_on_stream;
 use itertools::Itertools as _;
 use once_cell::sync::Lazy;
 use thiserror::Error;
 
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::FileId;
+use crate::backend::TreeValue;
 use crate::dsl_util::collect_similar;
 use crate::fileset_parser;
 use crate::fileset_parser::BinaryOp;
@@ -42,11 +50,16 @@ use crate::matchers::Matcher;
 use crate::matchers::NothingMatcher;
 use crate::matchers::PrefixMatcher;
 use crate::matchers::UnionMatcher;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::TreeDiffEntry;
 use crate::repo_path::RelativePathParseError;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathUiConverter;
 use crate::repo_path::UiPathParseError;
+use crate::settings::HumanByteSize;
+use crate::store::Store;
 
 /// Error occurred during file pattern parsing.
 #[derive(Debug, Error)]
@@ -65,6 +78,12 @@ pub enum FilePatternParseError {
     GlobPattern(#[from] glob::PatternError),
 }
 
+/// Error occurred when building a matcher which can't evaluate file
+/// predicates.
+#[derive(Debug, Error)]
+#[error("File predicates such as `binary()` can't be evaluated without trees")]
+pub struct FilePredicateUnsupportedError;
+
 /// Basic pattern to match `RepoPath`.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum FilePattern {
@@ -196,6 +215,106 @@ fn split_glob_path(input: &str) -> (&str, &str) {
     input.split_at(prefix_len)
 }
 
+/// Predicate to match files by their tree values.
+///
+/// Unlike `FilePattern`, a predicate can't be evaluated without looking up
+/// the file in a tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilePredicate {
+    /// Matches executable files.
+    Executable,
+    /// Matches symbolic links.
+    Symlink,
+    /// Matches files with unresolved conflicts.
+    Conflicted,
+    /// Matches Git submodules.
+    Submodule,
+    /// Matches files that look like binary files.
+    Binary,
+    /// Matches files whose size in bytes is in the range.
+    Size(Range<u64>),
+}
+
+impl FilePredicate {
+    /// Returns true if the tree `value` at `path` matches this predicate.
+    ///
+    /// Apart from `Conflicted`, predicates only match resolved values.
+    pub fn matches_value(
+        &self,
+        store: &Store,
+        path: &RepoPath,
+        value: &MergedTreeValue,
+    ) -> BackendResult<bool> {
+        if *self == FilePredicate::Conflicted {
+            return Ok(!value.is_resolved());
+        }
+        let Some(Some(value)) = value.as_resolved() else {
+            return Ok(false);
+        };
+        match (self, value) {
+            (FilePredicate::Executable, TreeValue::File { executable, .. }) => Ok(*executable),
+            (FilePredicate::Symlink, TreeValue::Symlink(_)) => Ok(true),
+            (FilePredicate::Submodule, TreeValue::GitSubmodule(_)) => Ok(true),
+            (FilePredicate::Binary, TreeValue::File { id, .. }) => {
+                // Same heuristic as git: a file is binary if the first 8000
+                // bytes contain a null character.
+                const PEEK_SIZE: u64 = 8000;
+                let mut start = vec![];
+                store
+                    .read_file(path, id)?
+                    .take(PEEK_SIZE)
+                    .read_to_end(&mut start)
+                    .map_err(|err| read_file_error(path, id, err))?;
+                Ok(start.contains(&b'\0'))
+            }
+            (FilePredicate::Size(range), TreeValue::File { id, .. }) => {
+                // Stop reading once the size is known to be out of the range,
+                // or above its lower bound if it has no upper bound.
+                let limit = if range.end == u64::MAX {
+                    range.start
+                } else {
+                    range.end
+                };
+                let size = io::copy(&mut store.read_file(path, id)?.take(limit), &mut io::sink())
+                    .map_err(|err| read_file_error(path, id, err))?;
+                if size < limit {
+                    Ok(range.contains(&size))
+                } else {
+                    Ok(range.end == u64::MAX)
+                }
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+fn read_file_error(path: &RepoPath, id: &FileId, err: io::Error) -> BackendError {
+    BackendError::ReadFile {
+        path: path.to_owned(),
+        id: id.clone(),
+        source: err.into(),
+    }
+}
+
+/// Parses size range such as `">1MiB"` or `"<=100"`. A bare size matches the
+/// exact size.
+fn parse_size_range(text: &str) -> Result<Range<u64>, String> {
+    let text = text.trim();
+    let (op, size) = [">=", "<=", ">", "<", "="]
+        .iter()
+        .find_map(|op| Some((*op, text.strip_prefix(op)?)))
+        .unwrap_or(("=", text));
+    let HumanByteSize(size) = HumanByteSize::try_from(size.trim().to_owned())?;
+    let range = match op {
+        ">=" => size..u64::MAX,
+        "<=" => 0..size.saturating_add(1),
+        ">" => size.saturating_add(1)..u64::MAX,
+        "<" => 0..size,
+        _ => size..size.saturating_add(1),
+    };
+    Ok(range)
+}
+
 /// AST-level representation of the fileset expression.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum FilesetExpression {
@@ -205,6 +324,8 @@ pub enum FilesetExpression {
     All,
     /// Matches basic pattern.
     Pattern(FilePattern),
+    /// Matches files whose tree values satisfy the predicate.
+    Predicate(FilePredicate),
     /// Matches any of the expressions.
     ///
     /// Use `FilesetExpression::union_all()` to construct a union expression.
@@ -277,7 +398,8 @@ impl FilesetExpression {
             match expr {
                 FilesetExpression::None
                 | FilesetExpression::All
-                | FilesetExpression::Pattern(_) => {}
+                | FilesetExpression::Pattern(_)
+                | FilesetExpression::Predicate(_) => {}
                 FilesetExpression::UnionAll(exprs) => stack.extend(exprs.iter().rev()),
                 FilesetExpression::Intersection(expr1, expr2)
                 | FilesetExpression::Difference(expr1, expr2) => {
@@ -302,9 +424,192 @@ impl FilesetExpression {
         })
     }
 
+    /// Returns true if this expression contains predicates that need to be
+    /// evaluated against trees.
+    pub fn has_predicates(&self) -> bool {
+        self.dfs_pre()
+            .any(|expr| matches!(expr, FilesetExpression::Predicate(_)))
+    }
+
     /// Transforms the expression tree to `Matcher` object.
-    pub fn to_matcher(&self) -> Box<dyn Matcher> {
-        build_union_matcher(self.as_union_all())
+    ///
+    /// File predicates such as `binary()` need tree values to be evaluated,
+    /// so an error is returned if the expression contains predicates. Use
+    /// `to_tree_matcher()` or `to_diff_matcher()` if the expression may
+    /// contain predicates.
+    pub fn to_matcher(&self) -> Result<Box<dyn Matcher>, FilePredicateUnsupportedError> {
+        if self.has_predicates() {
+            return Err(FilePredicateUnsupportedError);
+        }
+        Ok(build_union_matcher(self.as_union_all(), true))
+    }
+
+    /// Transforms the expression tree to `FilesetValueMatcher` object, which
+    /// can evaluate file predicates against tree values.
+    pub fn to_value_matcher(&self) -> FilesetValueMatcher {
+        FilesetValueMatcher {
+            candidates: build_union_matcher(self.as_union_all(), true),
+            evaluator: build_union_evaluator(self.as_union_all()),
+        }
+    }
+
+    /// Evaluates the expression against the files in `tree`, and returns
+    /// `Matcher` object which matches the resulting files.
+    pub fn to_tree_matcher(&self, tree: &MergedTree) -> BackendResult<Box<dyn Matcher>> {
+        if !self.has_predicates() {
+            return Ok(build_union_matcher(self.as_union_all(), true));
+        }
+        let value_matcher = self.to_value_matcher();
+        let mut paths = vec![];
+        for (path, value) in tree.entries_matching(value_matcher.candidates()) {
+            if value_matcher.matches_values(tree.store(), &path, &[&value?])? {
+                paths.push(path);
+            }
+        }
+        Ok(Box::new(FilesMatcher::new(paths)))
+    }
+
+    /// Evaluates the expression against the files changed from `from_tree` to
+    /// `to_tree`, and returns `Matcher` object which matches the resulting
+    /// files.
+    ///
+    /// A file predicate matches a changed file if it matches either side of
+    /// the change.
+    pub fn to_diff_matcher(
+        &self,
+        from_tree: &MergedTree,
+        to_tree: &MergedTree,
+    ) -> BackendResult<Box<dyn Matcher>> {
+        if !self.has_predicates() {
+            return Ok(build_union_matcher(self.as_union_all(), true));
+        }
+        let value_matcher = self.to_value_matcher();
+        let diff_stream = from_tree.diff_stream(to_tree, value_matcher.candidates());
+        let mut paths = vec![];
+        for TreeDiffEntry { path, values } in block_on_stream(diff_stream) {
+            let (from_value, to_value) = values?;
+            if value_matcher.matches_values(from_tree.store(), &path, &[&from_value, &to_value])? {
+                paths.push(path);
+            }
+        }
+        Ok(Box::new(FilesMatcher::new(paths)))
+    }
+}
+
+/// Matcher which evaluates fileset expression against tree values.
+///
+/// File predicates can't be evaluated against paths, so the caller should
+/// walk trees with the `candidates()` matcher, and then test each file with
+/// `matches_values()`.
+#[derive(Debug)]
+pub struct FilesetValueMatcher {
+    candidates: Box<dyn Matcher>,
+    evaluator: FilesetEvaluator,
+}
+
+impl FilesetValueMatcher {
+    /// Returns `Matcher` object which matches the files the expression may
+    /// match.
+    ///
+    /// File predicates are assumed to match every file (or no file if
+    /// negated), so it may match more files than the expression.
+    pub fn candidates(&self) -> &dyn Matcher {
+        self.candidates.as_ref()
+    }
+
+    /// Returns true if the file at `path` matches the expression. A file
+    /// predicate matches the file if it matches any of the tree `values`.
+    pub fn matches_values(
+        &self,
+        store: &Store,
+        path: &RepoPath,
+        values: &[&MergedTreeValue],
+    ) -> BackendResult<bool> {
+        self.evaluator.matches(store, path, values)
+    }
+}
+
+/// Fileset expression in which the parts without predicates are compiled to
+/// `Matcher` objects.
+#[derive(Debug)]
+enum FilesetEvaluator {
+    Paths(Box<dyn Matcher>),
+    Predicate(FilePredicate),
+    Union(Vec<FilesetEvaluator>),
+    Intersection(Box<FilesetEvaluator>, Box<FilesetEvaluator>),
+    Difference(Box<FilesetEvaluator>, Box<FilesetEvaluator>),
+}
+
+impl FilesetEvaluator {
+    fn matches(
+        &self,
+        store: &Store,
+        path: &RepoPath,
+        values: &[&MergedTreeValue],
+    ) -> BackendResult<bool> {
+        match self {
+            FilesetEvaluator::Paths(matcher) => Ok(matcher.matches(path)),
+            FilesetEvaluator::Predicate(predicate) => {
+                for value in values {
+                    if predicate.matches_value(store, path, value)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            FilesetEvaluator::Union(evaluators) => {
+                for evaluator in evaluators {
+                    if evaluator.matches(store, path, values)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            FilesetEvaluator::Intersection(evaluator1, evaluator2) => Ok(evaluator1
+                .matches(store, path, values)?
+                && evaluator2.matches(store, path, values)?),
+            FilesetEvaluator::Difference(evaluator1, evaluator2) => Ok(evaluator1
+                .matches(store, path, values)?
+                && !evaluator2.matches(store, path, values)?),
+        }
+    }
+}
+
+/// Transforms the union `expressions` to `FilesetEvaluator` object.
+///
+/// The expressions without predicates are combined into a single `Matcher`.
+fn build_union_evaluator(expressions: &[FilesetExpression]) -> FilesetEvaluator {
+    let (path_expressions, value_expressions): (Vec<_>, Vec<_>) =
+        expressions.iter().partition(|expr| !expr.has_predicates());
+    let mut evaluators = Vec::new();
+    if !path_expressions.is_empty() {
+        let matcher = build_union_matcher(path_expressions, true);
+        evaluators.push(FilesetEvaluator::Paths(matcher));
+    }
+    for expr in value_expressions {
+        let evaluator = match expr {
+            FilesetExpression::Predicate(predicate) => {
+                FilesetEvaluator::Predicate(predicate.clone())
+            }
+            FilesetExpression::UnionAll(exprs) => build_union_evaluator(exprs),
+            FilesetExpression::Intersection(expr1, expr2) => FilesetEvaluator::Intersection(
+                Box::new(build_union_evaluator(expr1.as_union_all())),
+                Box::new(build_union_evaluator(expr2.as_union_all())),
+            ),
+            FilesetExpression::Difference(expr1, expr2) => FilesetEvaluator::Difference(
+                Box::new(build_union_evaluator(expr1.as_union_all())),
+                Box::new(build_union_evaluator(expr2.as_union_all())),
+            ),
+            FilesetExpression::None | FilesetExpression::All | FilesetExpression::Pattern(_) => {
+                unreachable!("expression without predicates: {expr:?}")
+            }
+        };
+        evaluators.push(evaluator);
+    }
+    if evaluators.len() == 1 {
+        evaluators.pop().unwrap()
+    } else {
+        FilesetEvaluator::Union(evaluators)
     }
 }
 
@@ -312,7 +617,15 @@ impl FilesetExpression {
 ///
 /// Since `Matcher` typically accepts a set of patterns to be OR-ed, this
 /// function takes a list of union `expressions` as input.
-fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
+///
+/// File predicates can't be evaluated against paths. They're assumed to match
+/// everything if `predicates_match` is true, and nothing otherwise, so the
+/// matcher matches a superset of the files if predicates in the negated parts
+/// are assumed not to match.
+fn build_union_matcher<'a>(
+    expressions: impl IntoIterator<Item = &'a FilesetExpression>,
+    predicates_match: bool,
+) -> Box<dyn Matcher> {
     let mut file_paths = Vec::new();
     let mut prefix_paths = Vec::new();
     let mut file_globs = Vec::new();
@@ -332,16 +645,18 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
                 }
                 continue;
             }
+            FilesetExpression::Predicate(_) if predicates_match => Box::new(EverythingMatcher),
+            FilesetExpression::Predicate(_) => Box::new(NothingMatcher),
             // UnionAll is supposed to be flattened by caller.
-            FilesetExpression::UnionAll(exprs) => build_union_matcher(exprs),
+            FilesetExpression::UnionAll(exprs) => build_union_matcher(exprs, predicates_match),
             FilesetExpression::Intersection(expr1, expr2) => {
-                let m1 = build_union_matcher(expr1.as_union_all());
-                let m2 = build_union_matcher(expr2.as_union_all());
+                let m1 = build_union_matcher(expr1.as_union_all(), predicates_match);
+                let m2 = build_union_matcher(expr2.as_union_all(), predicates_match);
                 Box::new(IntersectionMatcher::new(m1, m2))
             }
             FilesetExpression::Difference(expr1, expr2) => {
-                let m1 = build_union_matcher(expr1.as_union_all());
-                let m2 = build_union_matcher(expr2.as_union_all());
+                let m1 = build_union_matcher(expr1.as_union_all(), predicates_match);
+                let m2 = build_union_matcher(expr2.as_union_all(), !predicates_match);
                 Box::new(DifferenceMatcher::new(m1, m2))
             }
         };
@@ -393,6 +708,43 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, FilesetFunction>> = Lazy
         function.expect_no_arguments()?;
         Ok(FilesetExpression::all())
     });
+    map.insert("executable", |_path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Executable))
+    });
+    map.insert("symlink", |_path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Symlink))
+    });
+    map.insert("conflicted", |_path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Conflicted))
+    });
+    map.insert("submodule", |_path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Submodule))
+    });
+    map.insert("binary", |_path_converter, function| {
+        function.expect_no_arguments()?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Binary))
+    });
+    map.insert("size", |_path_converter, function| {
+        let [arg] = function.expect_exact_arguments()?;
+        let text = match &arg.kind {
+            ExpressionKind::Identifier(name) => *name,
+            ExpressionKind::String(text) => text,
+            _ => {
+                return Err(FilesetParseError::expression(
+                    "Expected size range",
+                    arg.span,
+                ))
+            }
+        };
+        let range = parse_size_range(text).map_err(|message| {
+            FilesetParseError::expression(format!("Invalid size range: {message}"), arg.span)
+        })?;
+        Ok(FilesetExpression::Predicate(FilePredicate::Size(range)))
+    });
     map
 });
 
@@ -670,6 +1022,50 @@ mod tests {
             ],
         }
         "###);
+
+        assert_eq!(
+            parse("executable()").unwrap(),
+            FilesetExpression::Predicate(FilePredicate::Executable)
+        );
+        assert_eq!(
+            parse("conflicted()").unwrap(),
+            FilesetExpression::Predicate(FilePredicate::Conflicted)
+        );
+        assert_eq!(
+            parse(r#"size(">1MiB")"#).unwrap(),
+            FilesetExpression::Predicate(FilePredicate::Size(1024 * 1024 + 1..u64::MAX))
+        );
+        assert_eq!(
+            parse("size(100)").unwrap(),
+            FilesetExpression::Predicate(FilePredicate::Size(100..101))
+        );
+        insta::assert_debug_snapshot!(parse("size()").unwrap_err().kind(), @r###"
+        InvalidArguments {
+            name: "size",
+            message: "Expected 1 arguments",
+        }
+        "###);
+        insta::assert_debug_snapshot!(
+            parse(r#"size(">1LiB")"#).unwrap_err().kind(),
+            @r###"Expression("Invalid size range: unrecognized unit prefix")"###);
+        insta::assert_debug_snapshot!(
+            parse("size(x|y)").unwrap_err().kind(),
+            @r###"Expression("Expected size range")"###);
+        assert!(parse("binary(x)").is_err());
+        assert!(!parse("foo & ~bar").unwrap().has_predicates());
+        assert!(parse("foo & ~binary()").unwrap().has_predicates());
+    }
+
+    #[test]
+    fn test_parse_size_range() {
+        assert_eq!(parse_size_range("0"), Ok(0..1));
+        assert_eq!(parse_size_range("=1K"), Ok(1024..1025));
+        assert_eq!(parse_size_range(">1K"), Ok(1025..u64::MAX));
+        assert_eq!(parse_size_range(">= 1K"), Ok(1024..u64::MAX));
+        assert_eq!(parse_size_range("<1K"), Ok(0..1024));
+        assert_eq!(parse_size_range(" <=1 KiB "), Ok(0..1025));
+        assert!(parse_size_range(">").is_err());
+        assert!(parse_size_range("=>1").is_err());
     }
 
     #[test]
@@ -748,10 +1144,10 @@ mod tests {
         let settings = insta_settings();
         let _guard = settings.bind_to_scope();
 
-        insta::assert_debug_snapshot!(FilesetExpression::none().to_matcher(), @"NothingMatcher");
-        insta::assert_debug_snapshot!(FilesetExpression::all().to_matcher(), @"EverythingMatcher");
+        insta::assert_debug_snapshot!(FilesetExpression::none().to_matcher().unwrap(), @"NothingMatcher");
+        insta::assert_debug_snapshot!(FilesetExpression::all().to_matcher().unwrap(), @"EverythingMatcher");
         insta::assert_debug_snapshot!(
-            FilesetExpression::file_path(repo_path_buf("foo")).to_matcher(),
+            FilesetExpression::file_path(repo_path_buf("foo")).to_matcher().unwrap(),
             @r###"
         FilesMatcher {
             tree: Dir {
@@ -760,7 +1156,7 @@ mod tests {
         }
         "###);
         insta::assert_debug_snapshot!(
-            FilesetExpression::prefix_path(repo_path_buf("foo")).to_matcher(),
+            FilesetExpression::prefix_path(repo_path_buf("foo")).to_matcher().unwrap(),
             @r###"
         PrefixMatcher {
             tree: Dir {
@@ -781,7 +1177,7 @@ mod tests {
             })
         };
 
-        insta::assert_debug_snapshot!(glob_expr("", "*").to_matcher(), @r###"
+        insta::assert_debug_snapshot!(glob_expr("", "*").to_matcher().unwrap(), @r###"
         FileGlobsMatcher {
             tree: [
                 Pattern {
@@ -797,7 +1193,7 @@ mod tests {
 
         let expr =
             FilesetExpression::union_all(vec![glob_expr("foo", "*"), glob_expr("foo/bar", "*")]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         FileGlobsMatcher {
             tree: [] {
                 "foo": [
@@ -833,7 +1229,7 @@ mod tests {
             FilesetExpression::file_path(repo_path_buf("foo")),
             FilesetExpression::file_path(repo_path_buf("foo/bar")),
         ]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         FilesMatcher {
             tree: Dir {
                 "foo": File {
@@ -847,7 +1243,7 @@ mod tests {
             FilesetExpression::prefix_path(repo_path_buf("bar")),
             FilesetExpression::prefix_path(repo_path_buf("bar/baz")),
         ]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         PrefixMatcher {
             tree: Dir {
                 "bar": Prefix {
@@ -867,7 +1263,7 @@ mod tests {
             FilesetExpression::file_path(repo_path_buf("foo")),
             FilesetExpression::prefix_path(repo_path_buf("bar")),
         ]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         UnionMatcher {
             input1: FilesMatcher {
                 tree: Dir {
@@ -889,11 +1285,11 @@ mod tests {
         let _guard = settings.bind_to_scope();
 
         let expr = FilesetExpression::UnionAll(vec![]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @"NothingMatcher");
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @"NothingMatcher");
 
         let expr =
             FilesetExpression::UnionAll(vec![FilesetExpression::None, FilesetExpression::All]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         UnionMatcher {
             input1: NothingMatcher,
             input2: EverythingMatcher,
@@ -912,7 +1308,7 @@ mod tests {
             FilesetExpression::file_path(repo_path_buf("foo")),
             FilesetExpression::prefix_path(repo_path_buf("bar")),
         ]);
-        insta::assert_debug_snapshot!(expr.to_matcher(), @r###"
+        insta::assert_debug_snapshot!(expr.to_matcher().unwrap(), @r###"
         UnionMatcher {
             input1: UnionMatcher {
                 input1: IntersectionMatcher {
@@ -939,4 +1335,18 @@ mod tests {
         }
         "###);
     }
+
+    #[test]
+    fn test_build_matcher_predicate() {
+        let expr = FilesetExpression::difference(
+            FilesetExpression::prefix_path(repo_path_buf("foo")),
+            FilesetExpression::Predicate(FilePredicate::Binary),
+        );
+        assert!(expr.to_matcher().is_err());
+        let value_matcher = expr.to_value_matcher();
+        assert!(value_matcher
+            .candidates()
+            .matches(&repo_path_buf("foo/bar")));
+        assert!(!value_matcher.candidates().matches(&repo_path_buf("bar")));
+    }
 }
@@ -40,6 +40,9 @@ pub enum FsmonitorSettings {
     /// The Watchman filesystem monitor (<https://facebook.github.io/watchman/>).
     Watchman(WatchmanConfig),
 
+    /// The built-in filesystem monitor based on Linux's inotify API.
+    Inotify,
+
     /// Only used in tests.
     Test {
         /// The set of changed files to pretend that the filesystem monitor is
@@ -66,6 +69,7 @@ impl FsmonitorSettings {
                         .optional()?
                         .unwrap_or_default(),
                 })),
+                "inotify" => Ok(Self::Inotify),
                 "test" => Err(ConfigError::Message(
                     "cannot use test fsmonitor in real repository".to_string(),
                 )),
@@ -336,3 +340,857 @@ pub mod watchman {
         }
     }
 }
+
+/// Filesystem monitor integration using Linux's inotify API.
+///
+/// A daemon process watches the working copy with the `notify` crate and
+/// appends the paths of changed files and directories to an event log in the
+/// working copy state directory. A snapshot reads the paths appended since the
+/// previous snapshot.
+#[cfg(target_os = "linux")]
+pub mod inotify {
+    use std::collections::BTreeSet;
+    use std::ffi::OsStr;
+    use std::fs;
+    use std::fs::File;
+    use std::fs::OpenOptions;
+    use std::io;
+    use std::io::Read as _;
+    use std::io::Seek as _;
+    use std::io::SeekFrom;
+    use std::io::Write as _;
+    use std::os::unix::ffi::OsStrExt as _;
+    use std::os::unix::fs::MetadataExt as _;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use std::time::Instant;
+    use std::time::SystemTime;
+    use std::time::UNIX_EPOCH;
+
+    use notify::event::CreateKind;
+    use notify::event::ModifyKind;
+    use notify::event::RenameMode;
+    use notify::Event;
+    use notify::EventKind;
+    use notify::INotifyWatcher;
+    use notify::RecursiveMode;
+    use notify::Watcher as _;
+    use rustix::fs::FlockOperation;
+    use rustix::io::Errno;
+    use thiserror::Error;
+    use tracing::info;
+    use tracing::instrument;
+    use tracing::warn;
+
+    use crate::gitignore::GitIgnoreFile;
+
+    const LOCK_FILE_NAME: &str = "daemon.lock";
+    const EVENT_LOG_FILE_NAME: &str = "events";
+    const STOP_FILE_NAME: &str = "stop";
+    /// Lists the directories the daemon doesn't watch, such as ignored ones.
+    const UNWATCHED_FILE_NAME: &str = "unwatched";
+    /// Describes why the daemon failed to start.
+    const ERROR_FILE_NAME: &str = "error";
+    const COOKIE_PREFIX: &str = "cookie-";
+    /// The daemon starts a new event log once the current one exceeds this
+    /// size. The next snapshot will then crawl the whole working copy.
+    const MAX_EVENT_LOG_SIZE: u64 = 64 << 20;
+    const SYNC_TIMEOUT: Duration = Duration::from_secs(2);
+    const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+    // Kinds of records in the event log. Each record is terminated by a NUL
+    // byte, which can't occur in file names.
+    const INSTANCE_RECORD: u8 = b'I';
+    const PATH_RECORD: u8 = b'P';
+    const COOKIE_RECORD: u8 = b'C';
+    const OVERFLOW_RECORD: u8 = b'O';
+
+    /// Position in the event log of a daemon instance. Paths appended after
+    /// this position have changed since the clock was returned.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Clock {
+        instance_id: String,
+        offset: u64,
+    }
+
+    impl From<crate::protos::working_copy::InotifyClock> for Clock {
+        fn from(clock: crate::protos::working_copy::InotifyClock) -> Self {
+            Clock {
+                instance_id: clock.instance_id,
+                offset: clock.offset,
+            }
+        }
+    }
+
+    impl From<Clock> for crate::protos::working_copy::InotifyClock {
+        fn from(clock: Clock) -> Self {
+            crate::protos::working_copy::InotifyClock {
+                instance_id: clock.instance_id,
+                offset: clock.offset,
+            }
+        }
+    }
+
+    #[allow(missing_docs)]
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("The inotify daemon is already running")]
+        AlreadyRunning,
+
+        #[error("Failed to access the inotify daemon state in {path}")]
+        StateDir {
+            path: PathBuf,
+            #[source]
+            source: io::Error,
+        },
+
+        #[error("Failed to watch the working copy for changes")]
+        Watch(#[source] notify::Error),
+
+        #[error(
+            "Reached the limit on inotify watches while watching {path} (consider raising \
+             fs.inotify.max_user_watches)"
+        )]
+        WatchLimit { path: PathBuf },
+
+        #[error("Failed to start the inotify daemon")]
+        Spawn(#[source] io::Error),
+
+        #[error("The inotify daemon failed to start: {0}")]
+        DaemonFailed(String),
+
+        #[error("Timed out waiting for the inotify daemon")]
+        SyncTimeout,
+    }
+
+    /// Handle to the daemon watching a working copy.
+    pub struct Fsmonitor {
+        working_copy_path: PathBuf,
+        state_dir: PathBuf,
+    }
+
+    impl Fsmonitor {
+        /// Creates a handle for the daemon watching `working_copy_path`, which
+        /// keeps its state in `state_dir`.
+        pub fn new(working_copy_path: &Path, state_dir: &Path) -> Self {
+            Fsmonitor {
+                working_copy_path: working_copy_path.to_owned(),
+                state_dir: state_dir.to_owned(),
+            }
+        }
+
+        fn state_err(&self, source: impl Into<io::Error>) -> Error {
+            Error::StateDir {
+                path: self.state_dir.clone(),
+                source: source.into(),
+            }
+        }
+
+        /// Returns whether a daemon is running for the working copy.
+        pub fn is_daemon_running(&self) -> Result<bool, Error> {
+            let file = match File::open(self.state_dir.join(LOCK_FILE_NAME)) {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+                Err(err) => return Err(self.state_err(err)),
+            };
+            match rustix::fs::flock(&file, FlockOperation::NonBlockingLockShared) {
+                Ok(()) => Ok(false),
+                Err(Errno::WOULDBLOCK) => Ok(true),
+                Err(err) => Err(self.state_err(err)),
+            }
+        }
+
+        /// Waits until a daemon has started watching the working copy. Returns
+        /// false if that doesn't happen within `timeout`.
+        pub fn wait_for_daemon(&self, timeout: Duration) -> Result<bool, Error> {
+            let start = Instant::now();
+            loop {
+                if self.state_dir.join(EVENT_LOG_FILE_NAME).exists() && self.is_daemon_running()? {
+                    return Ok(true);
+                }
+                if start.elapsed() > timeout {
+                    return Ok(false);
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        /// Starts the daemon by running `command` in the background, and waits
+        /// until it has started watching the working copy. The `command` is
+        /// expected to call `run_daemon()`.
+        pub fn start_daemon(
+            &self,
+            mut command: process::Command,
+            timeout: Duration,
+        ) -> Result<(), Error> {
+            let error_path = self.state_dir.join(ERROR_FILE_NAME);
+            match fs::remove_file(&error_path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(self.state_err(err)),
+            }
+            let mut child = command.spawn().map_err(Error::Spawn)?;
+            let start = Instant::now();
+            loop {
+                if self.state_dir.join(EVENT_LOG_FILE_NAME).exists() && self.is_daemon_running()? {
+                    return Ok(());
+                }
+                if child.try_wait().map_err(Error::Spawn)?.is_some() {
+                    let message = fs::read_to_string(&error_path)
+                        .unwrap_or_else(|_| "the daemon exited unexpectedly".to_owned());
+                    return Err(Error::DaemonFailed(message));
+                }
+                if start.elapsed() > timeout {
+                    return Err(Error::SyncTimeout);
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        /// Asks the daemon to exit, and waits until it has exited.
+        pub fn stop_daemon(&self) -> Result<(), Error> {
+            if !self.is_daemon_running()? {
+                return Ok(());
+            }
+            File::create(self.state_dir.join(STOP_FILE_NAME)).map_err(|err| self.state_err(err))?;
+            let start = Instant::now();
+            while self.is_daemon_running()? {
+                if start.elapsed() > SYNC_TIMEOUT {
+                    return Err(Error::SyncTimeout);
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            Ok(())
+        }
+
+        /// Query for changed paths since the `previous_clock`.
+        ///
+        /// The returned paths are relative to the working copy path, and may
+        /// refer to directories whose contents have changed as a whole. If the
+        /// paths are `None`, then the caller must crawl the entire working copy
+        /// themselves. The returned clock is `None` if the daemon isn't
+        /// running.
+        #[instrument(skip(self))]
+        pub fn query_changed_files(
+            &self,
+            previous_clock: Option<Clock>,
+        ) -> Result<(Option<Clock>, Option<Vec<PathBuf>>), Error> {
+            if !self.is_daemon_running()? {
+                info!("The inotify daemon is not running");
+                return Ok((None, None));
+            }
+            info!("Querying the inotify daemon for changed files...");
+            let tail = self.sync(previous_clock.as_ref())?;
+            let records = parse_event_log(&tail.data);
+            let clock = Clock {
+                instance_id: tail.instance_id,
+                offset: tail.start + records.last().map_or(0, |record| record.end),
+            };
+            let changed_files = match tail.follows_previous_clock {
+                true => {
+                    let mut paths = vec![];
+                    let mut overflowed = false;
+                    for record in &records {
+                        match record.kind {
+                            PATH_RECORD => {
+                                paths.push(PathBuf::from(OsStr::from_bytes(record.value)));
+                            }
+                            OVERFLOW_RECORD => overflowed = true,
+                            _ => {}
+                        }
+                    }
+                    if overflowed {
+                        // Events were dropped if the kernel queue overflowed.
+                        None
+                    } else {
+                        // Tracked files in unwatched directories must be
+                        // checked by every snapshot.
+                        paths.extend(self.read_unwatched_dirs()?);
+                        Some(paths)
+                    }
+                }
+                // The daemon was restarted, so events may have been missed.
+                false => None,
+            };
+            Ok((Some(clock), changed_files))
+        }
+
+        fn read_unwatched_dirs(&self) -> Result<Vec<PathBuf>, Error> {
+            let data = match fs::read(self.state_dir.join(UNWATCHED_FILE_NAME)) {
+                Ok(data) => data,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+                Err(err) => return Err(self.state_err(err)),
+            };
+            Ok(data
+                .split(|&b| b == 0)
+                .filter(|path| !path.is_empty())
+                .map(|path| PathBuf::from(OsStr::from_bytes(path)))
+                .collect())
+        }
+
+        /// Creates a cookie file in the state directory, and waits until the
+        /// daemon has logged it. Since inotify events are queued in order, all
+        /// changes made before the cookie was created have been logged by
+        /// then. Returns the records after `previous_clock`, or all records if
+        /// the clock is from another daemon instance.
+        fn sync(&self, previous_clock: Option<&Clock>) -> Result<EventLogTail, Error> {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let cookie_name = format!("{COOKIE_PREFIX}{}-{nanos}", process::id());
+            let cookie_path = self.state_dir.join(&cookie_name);
+            File::create(&cookie_path).map_err(|err| self.state_err(err))?;
+            let mut cookie_record = vec![COOKIE_RECORD];
+            cookie_record.extend_from_slice(cookie_name.as_bytes());
+            cookie_record.push(0);
+            let log_path = self.state_dir.join(EVENT_LOG_FILE_NAME);
+            let mut log = None;
+            // Length of the data that was already searched for the cookie
+            let mut searched_len: usize = 0;
+            let start = Instant::now();
+            let result = loop {
+                match read_event_log(&log_path, previous_clock, &mut log) {
+                    Ok(true) => searched_len = 0,
+                    Ok(false) => {}
+                    Err(err) => break Err(self.state_err(err)),
+                }
+                if let Some((_, tail)) = &log {
+                    let search_start = searched_len.saturating_sub(cookie_record.len() - 1);
+                    if tail.data[search_start..]
+                        .windows(cookie_record.len())
+                        .any(|window| window == cookie_record)
+                    {
+                        break Ok(log.take().unwrap().1);
+                    }
+                    searched_len = tail.data.len();
+                }
+                if start.elapsed() > SYNC_TIMEOUT {
+                    break Err(Error::SyncTimeout);
+                }
+                thread::sleep(POLL_INTERVAL);
+            };
+            fs::remove_file(&cookie_path).ok();
+            result
+        }
+
+        /// Watches the working copy and logs changed paths until the working
+        /// copy is deleted or `stop_daemon()` is called. Fails if another
+        /// daemon is already running.
+        ///
+        /// Directories ignored by `base_ignores` or by `.gitignore` files
+        /// aren't watched.
+        pub fn run_daemon(&self, base_ignores: Arc<GitIgnoreFile>) -> Result<(), Error> {
+            fs::create_dir_all(&self.state_dir).map_err(|err| self.state_err(err))?;
+            let lock_file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(self.state_dir.join(LOCK_FILE_NAME))
+                .map_err(|err| self.state_err(err))?;
+            match rustix::fs::flock(&lock_file, FlockOperation::NonBlockingLockExclusive) {
+                Ok(()) => {}
+                Err(Errno::WOULDBLOCK) => return Err(Error::AlreadyRunning),
+                Err(err) => return Err(self.state_err(err)),
+            }
+            match fs::remove_file(self.state_dir.join(STOP_FILE_NAME)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(self.state_err(err)),
+            }
+            let mut daemon =
+                match Daemon::new(&self.working_copy_path, &self.state_dir, base_ignores) {
+                    Ok(daemon) => daemon,
+                    Err(err) => {
+                        // Let start_daemon() report why the daemon didn't
+                        // start.
+                        fs::write(self.state_dir.join(ERROR_FILE_NAME), err.to_string()).ok();
+                        return Err(err);
+                    }
+                };
+            let result = daemon.run();
+            fs::remove_file(self.state_dir.join(EVENT_LOG_FILE_NAME)).ok();
+            fs::remove_file(self.state_dir.join(UNWATCHED_FILE_NAME)).ok();
+            result
+        }
+    }
+
+    /// The records of the event log read by `Fsmonitor::sync()`.
+    struct EventLogTail {
+        instance_id: String,
+        /// Offset of `data` in the event log.
+        start: u64,
+        /// Whether `data` starts at the offset of the previous clock.
+        follows_previous_clock: bool,
+        data: Vec<u8>,
+    }
+
+    /// Reads the data appended to the event log since the previous call, so
+    /// polling doesn't read the whole log again. The log is (re)opened if the
+    /// daemon replaced it, in which case true is returned and the data read
+    /// before is discarded. `log` is `None` if there's no event log.
+    fn read_event_log(
+        path: &Path,
+        previous_clock: Option<&Clock>,
+        log: &mut Option<(File, EventLogTail)>,
+    ) -> io::Result<bool> {
+        let is_current = match (&*log, fs::metadata(path)) {
+            (Some((file, _)), Ok(metadata)) => {
+                let opened = file.metadata()?;
+                opened.dev() == metadata.dev() && opened.ino() == metadata.ino()
+            }
+            (None, Ok(_)) => false,
+            (_, Err(err)) if err.kind() == io::ErrorKind::NotFound => {
+                *log = None;
+                return Ok(false);
+            }
+            (_, Err(err)) => return Err(err),
+        };
+        if !is_current {
+            *log = open_event_log(path, previous_clock)?;
+        }
+        if let Some((file, tail)) = log {
+            file.read_to_end(&mut tail.data)?;
+        }
+        Ok(!is_current)
+    }
+
+    /// Opens the event log positioned after `previous_clock` if the clock is
+    /// from the same daemon instance, or else after the instance record.
+    fn open_event_log(
+        path: &Path,
+        previous_clock: Option<&Clock>,
+    ) -> io::Result<Option<(File, EventLogTail)>> {
+        const MAX_INSTANCE_RECORD_LEN: u64 = 256;
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        // The log is created with the instance record before it's moved into
+        // place, so the record is always complete.
+        let mut header = vec![];
+        (&mut file)
+            .take(MAX_INSTANCE_RECORD_LEN)
+            .read_to_end(&mut header)?;
+        let instance_record = parse_event_log(&header)
+            .into_iter()
+            .next()
+            .filter(|record| record.kind == INSTANCE_RECORD)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid event log"))?;
+        let instance_id = String::from_utf8_lossy(instance_record.value).into_owned();
+        let len = file.metadata()?.len();
+        let resume_offset = previous_clock
+            .filter(|clock| {
+                clock.instance_id == instance_id
+                    && (instance_record.end..=len).contains(&clock.offset)
+            })
+            .map(|clock| clock.offset);
+        let start = resume_offset.unwrap_or(instance_record.end);
+        file.seek(SeekFrom::Start(start))?;
+        let tail = EventLogTail {
+            instance_id,
+            start,
+            follows_previous_clock: resume_offset.is_some(),
+            data: vec![],
+        };
+        Ok(Some((file, tail)))
+    }
+
+    struct Record<'a> {
+        kind: u8,
+        value: &'a [u8],
+        /// Offset of the end of the record in the event log.
+        end: u64,
+    }
+
+    /// Parses the complete records in the event log. A record may be partially
+    /// written if the daemon is writing to the log concurrently.
+    fn parse_event_log(data: &[u8]) -> Vec<Record<'_>> {
+        let mut records = vec![];
+        let mut start = 0;
+        while let Some(len) = data[start..].iter().position(|&b| b == 0) {
+            if let Some((&kind, value)) = data[start..start + len].split_first() {
+                records.push(Record {
+                    kind,
+                    value,
+                    end: (start + len + 1) as u64,
+                });
+            }
+            start += len + 1;
+        }
+        records
+    }
+
+    struct Daemon<'a> {
+        working_copy_path: &'a Path,
+        state_dir: &'a Path,
+        base_ignores: Arc<GitIgnoreFile>,
+        /// Watches each directory separately so ignored directories can be
+        /// skipped. Watches of deleted and moved directories are removed by
+        /// the watcher itself.
+        watcher: INotifyWatcher,
+        events: mpsc::Receiver<notify::Result<Event>>,
+        /// Directories that aren't watched, relative to the working copy
+        /// path. Since changes in them aren't logged, queries report them as
+        /// changed.
+        unwatched_dirs: BTreeSet<PathBuf>,
+        unwatched_dirs_changed: bool,
+        event_log: File,
+        event_log_size: u64,
+        pending_records: Vec<u8>,
+    }
+
+    impl<'a> Daemon<'a> {
+        /// Watches the working copy, and starts a new event log once all
+        /// directories are watched.
+        fn new(
+            working_copy_path: &'a Path,
+            state_dir: &'a Path,
+            base_ignores: Arc<GitIgnoreFile>,
+        ) -> Result<Self, Error> {
+            info!(?working_copy_path, "Starting the inotify daemon...");
+            let (sender, events) = mpsc::channel();
+            let mut watcher =
+                INotifyWatcher::new(sender, notify::Config::default()).map_err(Error::Watch)?;
+            watcher
+                .watch(state_dir, RecursiveMode::NonRecursive)
+                .map_err(Error::Watch)?;
+            let mut unwatched_dirs = BTreeSet::new();
+            watch_dir_recursively(
+                &mut watcher,
+                working_copy_path,
+                &mut unwatched_dirs,
+                Path::new(""),
+                base_ignores.clone(),
+            )?;
+            write_unwatched_dirs(state_dir, &unwatched_dirs)?;
+            let (event_log, event_log_size) = create_event_log(state_dir)?;
+            Ok(Daemon {
+                working_copy_path,
+                state_dir,
+                base_ignores,
+                watcher,
+                events,
+                unwatched_dirs,
+                unwatched_dirs_changed: false,
+                event_log,
+                event_log_size,
+                pending_records: vec![],
+            })
+        }
+
+        fn run(&mut self) -> Result<(), Error> {
+            loop {
+                let event = match self.events.try_recv() {
+                    Ok(event) => event,
+                    Err(mpsc::TryRecvError::Empty) => {
+                        // Log the events received so far before waiting
+                        self.flush_records()?;
+                        self.events.recv().map_err(|_| {
+                            Error::Watch(notify::Error::generic("The watcher stopped"))
+                        })?
+                    }
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        return Err(Error::Watch(notify::Error::generic("The watcher stopped")));
+                    }
+                };
+                let event = event.map_err(Error::Watch)?;
+                if !self.handle_event(event)? {
+                    return Ok(());
+                }
+            }
+        }
+
+        /// Logs the paths of the event. Returns false if the daemon should
+        /// stop.
+        fn handle_event(&mut self, event: Event) -> Result<bool, Error> {
+            if event.need_rescan() {
+                self.push_record(OVERFLOW_RECORD, b"");
+                return Ok(true);
+            }
+            match event.kind {
+                // Files are opened and closed by snapshots, and by the daemon
+                // itself when reading .gitignore files
+                EventKind::Access(_) => return Ok(true),
+                // Both paths are also reported by separate events
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => return Ok(true),
+                _ => {}
+            }
+            for disk_path in &event.paths {
+                if disk_path == self.state_dir {
+                    if matches!(event.kind, EventKind::Remove(_)) {
+                        info!("The inotify state directory was deleted");
+                        return Ok(false);
+                    }
+                } else if disk_path.parent() == Some(self.state_dir) {
+                    if !matches!(event.kind, EventKind::Create(_)) {
+                        continue;
+                    }
+                    let name = disk_path.file_name().unwrap_or_default();
+                    if name == STOP_FILE_NAME {
+                        info!("Stopping the inotify daemon");
+                        fs::remove_file(disk_path).ok();
+                        return Ok(false);
+                    } else if name.as_bytes().starts_with(COOKIE_PREFIX.as_bytes()) {
+                        self.push_record(COOKIE_RECORD, name.as_bytes());
+                    }
+                } else if let Ok(path) = disk_path.strip_prefix(self.working_copy_path) {
+                    if path.as_os_str().is_empty() {
+                        if matches!(
+                            event.kind,
+                            EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+                        ) {
+                            info!("The working copy was deleted or moved");
+                            return Ok(false);
+                        }
+                        continue;
+                    }
+                    if is_excluded(path) {
+                        continue;
+                    }
+                    self.push_record(PATH_RECORD, path.as_os_str().as_bytes());
+                    if path.file_name() == Some(OsStr::new(".gitignore")) {
+                        self.rescan_dir(path.parent().unwrap())?;
+                    }
+                    self.update_watches(path, event.kind)?;
+                }
+            }
+            Ok(true)
+        }
+
+        /// Re-evaluates which directories to watch after the `.gitignore` file
+        /// in `dir` changed. The whole directory is reported as changed since
+        /// files in it may no longer be ignored.
+        fn rescan_dir(&mut self, dir: &Path) -> Result<(), Error> {
+            self.push_record(PATH_RECORD, dir.as_os_str().as_bytes());
+            self.forget_unwatched_dirs(dir);
+            self.watch_dir(dir)
+        }
+
+        /// Updates the watches after the path was created, deleted or moved.
+        fn update_watches(&mut self, path: &Path, kind: EventKind) -> Result<(), Error> {
+            match kind {
+                EventKind::Create(CreateKind::Folder) => self.watch_dir(path),
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                    // Rename events don't tell whether the path is a directory
+                    let is_dir = fs::symlink_metadata(self.working_copy_path.join(path))
+                        .is_ok_and(|metadata| metadata.is_dir());
+                    if is_dir {
+                        self.watch_dir(path)?;
+                    }
+                    Ok(())
+                }
+                EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                    self.forget_unwatched_dirs(path);
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        }
+
+        /// Forgets the unwatched directories at or below `path`.
+        fn forget_unwatched_dirs(&mut self, path: &Path) {
+            let old_len = self.unwatched_dirs.len();
+            self.unwatched_dirs
+                .retain(|unwatched_dir| !unwatched_dir.starts_with(path));
+            self.unwatched_dirs_changed |= self.unwatched_dirs.len() != old_len;
+        }
+
+        /// Watches the directory at `path` and its subdirectories. If the
+        /// watch limit is reached, the directory is reported as changed by
+        /// every query instead.
+        fn watch_dir(&mut self, path: &Path) -> Result<(), Error> {
+            let ignores = ignores_for_dir(self.working_copy_path, &self.base_ignores, path);
+            let old_len = self.unwatched_dirs.len();
+            match watch_dir_recursively(
+                &mut self.watcher,
+                self.working_copy_path,
+                &mut self.unwatched_dirs,
+                path,
+                ignores,
+            ) {
+                Ok(()) => {}
+                Err(err @ Error::WatchLimit { .. }) => {
+                    warn!(?err, "Failed to watch a new directory");
+                    self.unwatched_dirs.insert(path.to_owned());
+                }
+                Err(err) => return Err(err),
+            }
+            self.unwatched_dirs_changed |= self.unwatched_dirs.len() != old_len;
+            Ok(())
+        }
+
+        fn push_record(&mut self, kind: u8, value: &[u8]) {
+            self.pending_records.push(kind);
+            self.pending_records.extend_from_slice(value);
+            self.pending_records.push(0);
+        }
+
+        fn flush_records(&mut self) -> Result<(), Error> {
+            // Update the list of unwatched directories before logging any
+            // cookie, so that queries synced by the cookie see the new list.
+            if self.unwatched_dirs_changed {
+                write_unwatched_dirs(self.state_dir, &self.unwatched_dirs)?;
+                self.unwatched_dirs_changed = false;
+            }
+            if self.pending_records.is_empty() {
+                return Ok(());
+            }
+            self.event_log
+                .write_all(&self.pending_records)
+                .map_err(|err| Error::StateDir {
+                    path: self.state_dir.to_owned(),
+                    source: err,
+                })?;
+            self.event_log_size += self.pending_records.len() as u64;
+            self.pending_records.clear();
+            if self.event_log_size > MAX_EVENT_LOG_SIZE {
+                (self.event_log, self.event_log_size) = create_event_log(self.state_dir)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Watches `dir` (relative to `working_copy_path`) and its subdirectories,
+    /// except for the `.jj` and `.git` directories. Directories matched by
+    /// `ignores` or by the `.gitignore` files inside `dir` are added to
+    /// `unwatched_dirs` instead. The `ignores` should contain the patterns
+    /// that apply to `dir` itself.
+    fn watch_dir_recursively(
+        watcher: &mut INotifyWatcher,
+        working_copy_path: &Path,
+        unwatched_dirs: &mut BTreeSet<PathBuf>,
+        dir: &Path,
+        ignores: Arc<GitIgnoreFile>,
+    ) -> Result<(), Error> {
+        let mut dirs_to_visit = vec![(dir.to_owned(), ignores)];
+        while let Some((dir, ignores)) = dirs_to_visit.pop() {
+            if !dir.as_os_str().is_empty() && ignores.matches(&to_ignore_prefix(&dir)) {
+                unwatched_dirs.insert(dir);
+                continue;
+            }
+            let disk_dir = working_copy_path.join(&dir);
+            match watcher.watch(&disk_dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {}
+                Err(err) => match &err.kind {
+                    notify::ErrorKind::MaxFilesWatch => {
+                        return Err(Error::WatchLimit { path: disk_dir });
+                    }
+                    // The directory may have been removed in the meantime.
+                    notify::ErrorKind::PathNotFound => continue,
+                    notify::ErrorKind::Io(io_err)
+                        if matches!(
+                            Errno::from_io_error(io_err),
+                            Some(Errno::NOENT | Errno::NOTDIR)
+                        ) =>
+                    {
+                        continue;
+                    }
+                    _ => return Err(Error::Watch(err)),
+                },
+            }
+            let Ok(entries) = fs::read_dir(&disk_dir) else {
+                continue;
+            };
+            let ignores = chain_dir_ignores(working_copy_path, &ignores, &dir);
+            for entry in entries.flatten() {
+                if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                    let path = dir.join(entry.file_name());
+                    if !is_excluded(&path) {
+                        dirs_to_visit.push((path, ignores.clone()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the ignore patterns that apply to `dir`, i.e. the ones from
+    /// the `.gitignore` files in its parent directories.
+    fn ignores_for_dir(
+        working_copy_path: &Path,
+        base_ignores: &Arc<GitIgnoreFile>,
+        dir: &Path,
+    ) -> Arc<GitIgnoreFile> {
+        let parent_dirs = dir.ancestors().skip(1).collect::<Vec<_>>();
+        parent_dirs
+            .into_iter()
+            .rev()
+            .fold(base_ignores.clone(), |ignores, parent_dir| {
+                chain_dir_ignores(working_copy_path, &ignores, parent_dir)
+            })
+    }
+
+    /// Adds the patterns from the `.gitignore` file in `dir`. An unreadable
+    /// file is skipped, which at worst watches more directories than needed.
+    fn chain_dir_ignores(
+        working_copy_path: &Path,
+        ignores: &Arc<GitIgnoreFile>,
+        dir: &Path,
+    ) -> Arc<GitIgnoreFile> {
+        let file = working_copy_path.join(dir).join(".gitignore");
+        ignores
+            .chain_with_file(&to_ignore_prefix(dir), file)
+            .unwrap_or_else(|err| {
+                warn!(?err, "Failed to read .gitignore file");
+                ignores.clone()
+            })
+    }
+
+    /// Converts the relative directory path to the slash-separated form used
+    /// by `GitIgnoreFile`, e.g. `"dir/sub/"`.
+    fn to_ignore_prefix(dir: &Path) -> String {
+        dir.components()
+            .map(|component| component.as_os_str().to_string_lossy() + "/")
+            .collect()
+    }
+
+    fn is_excluded(path: &Path) -> bool {
+        path == Path::new(".jj") || path == Path::new(".git")
+    }
+
+    fn write_unwatched_dirs(state_dir: &Path, dirs: &BTreeSet<PathBuf>) -> Result<(), Error> {
+        let mut data = vec![];
+        for dir in dirs {
+            data.extend_from_slice(dir.as_os_str().as_bytes());
+            data.push(0);
+        }
+        let to_state_err = |source| Error::StateDir {
+            path: state_dir.to_owned(),
+            source,
+        };
+        let temp_path = state_dir.join(format!("{UNWATCHED_FILE_NAME}.tmp"));
+        fs::write(&temp_path, &data).map_err(to_state_err)?;
+        fs::rename(&temp_path, state_dir.join(UNWATCHED_FILE_NAME)).map_err(to_state_err)
+    }
+
+    /// Replaces the event log with an empty one for a new daemon instance.
+    fn create_event_log(state_dir: &Path) -> Result<(File, u64), Error> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let instance_id = format!("{}-{nanos}", process::id());
+        let mut header = vec![INSTANCE_RECORD];
+        header.extend_from_slice(instance_id.as_bytes());
+        header.push(0);
+        let to_state_err = |source| Error::StateDir {
+            path: state_dir.to_owned(),
+            source,
+        };
+        let temp_path = state_dir.join(format!("{EVENT_LOG_FILE_NAME}.tmp"));
+        fs::write(&temp_path, &header).map_err(to_state_err)?;
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&temp_path)
+            .map_err(to_state_err)?;
+        fs::rename(&temp_path, state_dir.join(EVENT_LOG_FILE_NAME)).map_err(to_state_err)?;
+        Ok((file, header.len() as u64))
+    }
+}
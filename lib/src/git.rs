@@ -21,6 +21,7 @@ use std::collections::HashSet;
 use std::default::Default;
 use std::fmt;
 use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
 use std::str;
 
@@ -31,6 +32,8 @@ use thiserror::Error;
 
 use crate::backend::BackendError;
 use crate::backend::CommitId;
+use crate::backend::Signature;
+use crate::backend::SigningFn;
 use crate::commit::Commit;
 use crate::git_backend::GitBackend;
 use crate::index::Index;
@@ -39,6 +42,7 @@ use crate::op_store::RefTarget;
 use crate::op_store::RefTargetOptionExt;
 use crate::op_store::RemoteRef;
 use crate::op_store::RemoteRefState;
+use crate::op_store::WorkspaceId;
 use crate::refs;
 use crate::refs::BranchPushUpdate;
 use crate::repo::MutableRepo;
@@ -47,6 +51,8 @@ use crate::revset::RevsetExpression;
 use crate::settings::GitSettings;
 use crate::store::Store;
 use crate::str_util::StringPattern;
+use crate::submodule_store::SubmoduleStore;
+use crate::submodule_store::SubmoduleStoreError;
 use crate::view::View;
 
 /// Reserved remote name for the backing Git repo.
@@ -650,9 +656,8 @@ struct RefsToExport {
 /// repo compared to our last remembered view of the Git repo). These will be
 /// marked conflicted by the next `jj git import`.
 ///
-/// We do not export tags and other refs at the moment, since these aren't
-/// supposed to be modified by JJ. For them, the Git state is considered
-/// authoritative.
+/// Tags are exported in the same way. Annotated tags which still point to the
+/// tag's target commit are left untouched. Other refs are not exported.
 pub fn export_refs(mut_repo: &mut MutableRepo) -> Result<Vec<FailedRefExport>, GitExportError> {
     export_some_refs(mut_repo, |_| true)
 }
@@ -771,6 +776,48 @@ fn copy_exportable_local_branches_to_remote_view(
     }
 }
 
+#[derive(Error, Debug)]
+pub enum GitTagError {
+    #[error("The repo is not backed by a Git repo")]
+    UnexpectedBackend,
+    #[error("Failed to write tag")]
+    Backend(#[from] BackendError),
+    #[error("Failed to update Git ref {0}")]
+    UpdateRef(String, #[source] Box<gix::reference::edit::Error>),
+}
+
+/// Writes an annotated tag object `name` pointing to the `target` commit to
+/// the backing Git repo, points the Git tag ref to it, and points the jj tag
+/// to the `target`. An existing tag of the same name is overwritten.
+///
+/// The Git ref is recorded as exported, so `export_refs()` leaves it pointing
+/// to the tag object as long as the jj tag isn't moved.
+pub fn set_annotated_tag(
+    mut_repo: &mut MutableRepo,
+    name: &str,
+    target: &CommitId,
+    tagger: &Signature,
+    message: &str,
+    sign_with: Option<&mut SigningFn>,
+) -> Result<(), GitTagError> {
+    let git_backend = get_git_backend(mut_repo.store()).ok_or(GitTagError::UnexpectedBackend)?;
+    let tag_oid = git_backend.write_tag(name, target, tagger, message, sign_with)?;
+    let git_ref_name = format!("refs/tags/{name}");
+    git_backend
+        .git_repo()
+        .reference(
+            git_ref_name.as_str(),
+            tag_oid,
+            gix::refs::transaction::PreviousValue::Any,
+            "tag from jj",
+        )
+        .map_err(|err| GitTagError::UpdateRef(git_ref_name.clone(), Box::new(err)))?;
+    let target = RefTarget::normal(target.clone());
+    mut_repo.set_tag_target(name, target.clone());
+    mut_repo.set_git_ref_target(&git_ref_name, target);
+    Ok(())
+}
+
 /// Calculates diff of branches to be exported.
 fn diff_refs_to_export(
     view: &View,
@@ -779,9 +826,12 @@ fn diff_refs_to_export(
 ) -> RefsToExport {
     // Local targets will be copied to the "git" remote if successfully exported. So
     // the local branches are considered to be the new "git" remote branches.
-    let mut all_branch_targets: HashMap<RefName, (&RefTarget, &RefTarget)> = itertools::chain(
+    let mut all_branch_targets: HashMap<RefName, (&RefTarget, &RefTarget)> = itertools::chain!(
         view.local_bookmarks()
             .map(|(branch, target)| (RefName::LocalBranch(branch.to_owned()), target)),
+        view.tags()
+            .iter()
+            .map(|(name, target)| (RefName::Tag(name.to_owned()), target)),
         view.all_remote_bookmarks()
             .filter(|&((_, remote), _)| remote != REMOTE_NAME_FOR_LOCAL_GIT_REPO)
             .map(|((branch, remote), remote_ref)| {
@@ -808,7 +858,7 @@ fn diff_refs_to_export(
             // 2. `jj op undo`/`restore` in colocated repo
             matches!(
                 ref_name,
-                RefName::LocalBranch(..) | RefName::RemoteBranch { .. }
+                RefName::LocalBranch(..) | RefName::RemoteBranch { .. } | RefName::Tag(..)
             )
         })
         .filter(|(ref_name, _)| git_ref_filter(ref_name));
@@ -868,7 +918,7 @@ fn delete_git_ref(
     old_oid: &gix::oid,
 ) -> Result<(), FailedRefExportReason> {
     if let Ok(git_ref) = git_repo.find_reference(git_ref_name) {
-        if git_ref.inner.target.try_id() == Some(old_oid) {
+        if git_ref_points_to(&git_ref, old_oid) {
             // The branch has not been updated by git, so go ahead and delete it
             git_ref
                 .delete()
@@ -894,7 +944,7 @@ fn update_git_ref(
             if let Ok(git_repo_ref) = git_repo.find_reference(git_ref_name) {
                 // The branch was added in jj and in git. We're good if and only if git
                 // pointed it to our desired target.
-                if git_repo_ref.inner.target.try_id() != Some(&new_oid) {
+                if !git_ref_points_to(&git_repo_ref, &new_oid) {
                     return Err(FailedRefExportReason::AddedInJjAddedInGit);
                 }
             } else {
@@ -910,17 +960,23 @@ fn update_git_ref(
             }
         }
         Some(old_oid) => {
+            // An annotated tag pointing to the old target should be replaced, so
+            // expect the tag object instead of the commit.
+            let expected_target = match git_repo.find_reference(git_ref_name) {
+                Ok(git_ref) if git_ref_points_to(&git_ref, &old_oid) => git_ref.inner.target,
+                _ => old_oid.into(),
+            };
             // The branch was modified in jj. We can use gix API for updating under a lock.
             if let Err(err) = git_repo.reference(
                 git_ref_name,
                 new_oid,
-                gix::refs::transaction::PreviousValue::MustExistAndMatch(old_oid.into()),
+                gix::refs::transaction::PreviousValue::MustExistAndMatch(expected_target),
                 "export from jj",
             ) {
                 // The reference was probably updated in git
                 if let Ok(git_repo_ref) = git_repo.find_reference(git_ref_name) {
                     // We still consider this a success if it was updated to our desired target
-                    if git_repo_ref.inner.target.try_id() != Some(&new_oid) {
+                    if !git_ref_points_to(&git_repo_ref, &new_oid) {
                         return Err(FailedRefExportReason::FailedToSet(err.into()));
                     }
                 } else {
@@ -936,6 +992,15 @@ fn update_git_ref(
     Ok(())
 }
 
+/// Checks if `git_ref` points to `oid` directly or through annotated tags.
+fn git_ref_points_to(git_ref: &gix::Reference, oid: &gix::oid) -> bool {
+    git_ref.inner.target.try_id() == Some(oid)
+        || git_ref
+            .clone()
+            .into_fully_peeled_id()
+            .is_ok_and(|peeled_id| peeled_id.detach() == oid)
+}
+
 /// Ensures `HEAD@git` is detached and pointing to the `new_oid`. If `new_oid`
 /// is `None` (meaning absent), dummy placeholder ref will be set.
 fn update_git_head(
@@ -1361,6 +1426,19 @@ pub fn push_branches(
     remote_name: &str,
     targets: &GitBranchPushTargets,
     callbacks: RemoteCallbacks<'_>,
+) -> Result<(), GitPushError> {
+    push_branches_and_tags(mut_repo, git_repo, remote_name, targets, &[], callbacks)
+}
+
+/// Pushes the specified branches and tags in a single push, and updates the
+/// repo view accordingly. See `push_tags()` for how the tags are pushed.
+pub fn push_branches_and_tags(
+    mut_repo: &mut MutableRepo,
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    targets: &GitBranchPushTargets,
+    tag_names: &[String],
+    callbacks: RemoteCallbacks<'_>,
 ) -> Result<(), GitPushError> {
     let ref_updates = targets
         .branch_updates
@@ -1371,7 +1449,14 @@ pub fn push_branches(
             new_target: update.new_target.clone(),
         })
         .collect_vec();
-    push_updates(mut_repo, git_repo, remote_name, &ref_updates, callbacks)?;
+    push_updates_and_tags(
+        mut_repo,
+        git_repo,
+        remote_name,
+        &ref_updates,
+        tag_names,
+        callbacks,
+    )?;
 
     // TODO: add support for partially pushed refs? we could update the view
     // excluding rejected refs, but the transaction would be aborted anyway
@@ -1389,6 +1474,19 @@ pub fn push_branches(
     Ok(())
 }
 
+/// Pushes the specified tags as they are in the backing Git repo, so the tags
+/// should have been exported beforehand. The tags are expected not to exist on
+/// the remote, but pushing a tag which is already there is a no-op.
+pub fn push_tags(
+    repo: &dyn Repo,
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    tag_names: &[String],
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<(), GitPushError> {
+    push_updates_and_tags(repo, git_repo, remote_name, &[], tag_names, callbacks)
+}
+
 /// Pushes the specified Git refs without updating the repo view.
 pub fn push_updates(
     repo: &dyn Repo,
@@ -1397,6 +1495,21 @@ pub fn push_updates(
     updates: &[GitRefUpdate],
     callbacks: RemoteCallbacks<'_>,
 ) -> Result<(), GitPushError> {
+    push_updates_and_tags(repo, git_repo, remote_name, updates, &[], callbacks)
+}
+
+fn push_updates_and_tags(
+    repo: &dyn Repo,
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    updates: &[GitRefUpdate],
+    tag_names: &[String],
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<(), GitPushError> {
+    let qualified_tag_names = tag_names
+        .iter()
+        .map(|name| format!("refs/tags/{name}"))
+        .collect_vec();
     let mut qualified_remote_refs_expected_locations = HashMap::new();
     let mut refspecs = vec![];
     for update in updates {
@@ -1416,6 +1529,12 @@ pub fn push_updates(
             refspecs.push(format!(":{}", update.qualified_name));
         }
     }
+    for name in &qualified_tag_names {
+        // We don't keep track of remote tags, so the tags are expected to be
+        // absent. Unlike branches, tags are not force-pushed.
+        qualified_remote_refs_expected_locations.insert(name.as_str(), None);
+        refspecs.push(format!("{name}:{name}"));
+    }
     // TODO(ilyagr): `push_refs`, or parts of it, should probably be inlined. This
     // requires adjusting some tests.
     push_refs(
@@ -1484,6 +1603,16 @@ fn push_refs(
                              {expected_remote_location:?}. We don't consider this an error.",
                         );
                     }
+                    Ok(PushAllowReason::ExceptionalFastforward)
+                        if dst_refname.starts_with("refs/tags/") =>
+                    {
+                        // Unlike branches, existing tags are never moved.
+                        tracing::info!(
+                            "Cannot push {dst_refname} to {local_location:?}; the tag already \
+                             exists at {actual_remote_location:?} on the server",
+                        );
+                        failed_push_negotiations.push(dst_refname.to_string());
+                    }
                     Ok(PushAllowReason::ExceptionalFastforward) => {
                         // TODO(ilyagr): We could consider printing a user-facing message at
                         // this point.
@@ -1571,6 +1700,11 @@ fn allow_push(
     if actual_remote_location == expected_remote_location {
         return Ok(PushAllowReason::NormalMatch);
     }
+    if actual_remote_location.is_some() && actual_remote_location == destination_location {
+        // The remote ref is already where we want it to be. This is checked
+        // before looking up the index since an annotated tag isn't a commit.
+        return Ok(PushAllowReason::UnexpectedNoop);
+    }
 
     // If the remote ref is in an unexpected location, we still allow some
     // pushes, based on whether `jj git fetch` would result in a conflicted ref.
@@ -1806,3 +1940,181 @@ pub fn parse_gitmodules(
         .collect();
     Ok(ret)
 }
+
+#[derive(Error, Debug)]
+pub enum GitSubmoduleError {
+    #[error(transparent)]
+    SubmoduleStore(#[from] SubmoduleStoreError),
+    #[error("Submodule {0} is not initialized")]
+    NotInitialized(String),
+    #[error("Commit {} of submodule {name} not found", commit_id.hex())]
+    CommitNotFound { name: String, commit_id: CommitId },
+    #[error("{} is not managed by jj, leaving it as is", path.display())]
+    UnmanagedGitDir { path: PathBuf },
+    #[error("Failed to create submodule directory {path}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    // TODO: I'm sure there are other errors possible, such as transport-level
+    // errors.
+    #[error("Unexpected git error when updating submodule")]
+    InternalGitError(#[from] git2::Error),
+}
+
+const SUBMODULE_REMOTE_NAME: &str = "origin";
+
+/// Fetches the submodule repository from the URL configured in `.gitmodules`,
+/// initializing the repository in the `submodule_store` if needed.
+pub fn fetch_submodule(
+    submodule_store: &dyn SubmoduleStore,
+    config: &SubmoduleConfig,
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<(), GitSubmoduleError> {
+    let repo_path = submodule_store.repo_path(&config.name)?;
+    let git_repo = if submodule_store.is_initialized(&config.name)? {
+        git2::Repository::open(&repo_path)?
+    } else {
+        let git_repo = git2::Repository::init_bare(&repo_path)?;
+        git_repo.remote(SUBMODULE_REMOTE_NAME, &config.url)?;
+        git_repo
+    };
+    let mut remote = git_repo.find_remote(SUBMODULE_REMOTE_NAME)?;
+    let mut fetch_options = git2::FetchOptions::new();
+    let mut proxy_options = git2::ProxyOptions::new();
+    proxy_options.auto();
+    fetch_options.proxy_options(proxy_options);
+    fetch_options.remote_callbacks(callbacks.into_git());
+    fetch_options.download_tags(git2::AutotagOption::All);
+    let refspec = format!("+refs/heads/*:refs/remotes/{SUBMODULE_REMOTE_NAME}/*");
+    tracing::debug!(name = config.name, "fetching submodule");
+    remote.fetch(&[refspec], Some(&mut fetch_options), None)?;
+    Ok(())
+}
+
+/// Checks out the `commit_id` of an initialized submodule into `work_dir`.
+///
+/// The submodule repository is shared by all workspaces, so each workspace
+/// gets its own linked worktree of it, with its own HEAD and index. The
+/// `work_dir` is linked to the worktree by a `.git` file as Git does. Local
+/// changes in the `work_dir` are preserved if they don't conflict with the
+/// update.
+pub fn checkout_submodule(
+    submodule_store: &dyn SubmoduleStore,
+    name: &str,
+    workspace_id: &WorkspaceId,
+    commit_id: &CommitId,
+    work_dir: &Path,
+) -> Result<(), GitSubmoduleError> {
+    if !submodule_store.is_initialized(name)? {
+        return Err(GitSubmoduleError::NotInitialized(name.to_owned()));
+    }
+    let repo_path = submodule_store.repo_path(name)?;
+    let git_repo = git2::Repository::open(&repo_path)?;
+    let oid = Oid::from_bytes(commit_id.as_bytes())?;
+    if git_repo.find_commit(oid).is_err() {
+        return Err(GitSubmoduleError::CommitNotFound {
+            name: name.to_owned(),
+            commit_id: commit_id.clone(),
+        });
+    }
+    // Workspace ids may contain any characters, so they're encoded to be
+    // usable as worktree names.
+    let worktree_dir = repo_path
+        .join("worktrees")
+        .join(hex::encode(workspace_id.as_str()));
+    let worktree_repo = link_submodule_worktree(&worktree_dir, work_dir, oid)?;
+    let commit = worktree_repo.find_commit(oid)?;
+    let mut checkout_builder = git2::build::CheckoutBuilder::new();
+    checkout_builder.safe().recreate_missing(true);
+    worktree_repo.checkout_tree(commit.as_object(), Some(&mut checkout_builder))?;
+    worktree_repo.set_head_detached(oid)?;
+    Ok(())
+}
+
+/// Creates the git dir of a linked worktree at `worktree_dir` if needed, and
+/// points `work_dir` to it. A new worktree starts with a detached HEAD at
+/// `oid`.
+///
+/// An existing `.git` in `work_dir` is only replaced if it already points to
+/// `worktree_dir`, so that checkouts made by other tools (e.g. `git submodule
+/// update`) aren't repointed.
+fn link_submodule_worktree(
+    worktree_dir: &Path,
+    work_dir: &Path,
+    oid: Oid,
+) -> Result<git2::Repository, GitSubmoduleError> {
+    let io_error = |path: &Path| {
+        let path = path.to_owned();
+        move |source| GitSubmoduleError::Io { path, source }
+    };
+    let dot_git_path = work_dir.join(".git");
+    if dot_git_path.symlink_metadata().is_ok() && !points_to_git_dir(&dot_git_path, worktree_dir) {
+        return Err(GitSubmoduleError::UnmanagedGitDir { path: dot_git_path });
+    }
+    std::fs::create_dir_all(work_dir).map_err(io_error(work_dir))?;
+    let work_dir = work_dir.canonicalize().map_err(io_error(work_dir))?;
+    if !worktree_dir.join("HEAD").exists() {
+        std::fs::create_dir_all(worktree_dir).map_err(io_error(worktree_dir))?;
+        let write = |file_name: &str, content: String| {
+            let path = worktree_dir.join(file_name);
+            std::fs::write(&path, content).map_err(io_error(&path))
+        };
+        write("commondir", "../..\n".to_owned())?;
+        write("HEAD", format!("{oid}\n"))?;
+    }
+    let worktree_dir = worktree_dir
+        .canonicalize()
+        .map_err(io_error(worktree_dir))?;
+    // The back link lets Git find (and prune) the worktree. It's rewritten in
+    // case the workspace has moved.
+    let gitdir_path = worktree_dir.join("gitdir");
+    std::fs::write(
+        &gitdir_path,
+        format!("{}\n", work_dir.join(".git").display()),
+    )
+    .map_err(io_error(&gitdir_path))?;
+    let dot_git_path = work_dir.join(".git");
+    std::fs::write(
+        &dot_git_path,
+        format!("gitdir: {}\n", worktree_dir.display()),
+    )
+    .map_err(io_error(&dot_git_path))?;
+    Ok(git2::Repository::open(&work_dir)?)
+}
+
+/// Returns true if the `.git` file at `dot_git_path` links to `git_dir`.
+fn points_to_git_dir(dot_git_path: &Path, git_dir: &Path) -> bool {
+    let Ok(content) = std::fs::read_to_string(dot_git_path) else {
+        // Not a file, e.g. a standalone repository
+        return false;
+    };
+    let Some(linked_dir) = content.trim_end().strip_prefix("gitdir: ") else {
+        return false;
+    };
+    let linked_dir = dot_git_path.parent().unwrap().join(linked_dir);
+    match (linked_dir.canonicalize(), git_dir.canonicalize()) {
+        (Ok(linked_dir), Ok(git_dir)) => linked_dir == git_dir,
+        _ => false,
+    }
+}
+
+/// Updates the remote URL of an initialized submodule to the one configured
+/// in `.gitmodules`. Returns false if the submodule isn't initialized or the
+/// URL is unchanged.
+pub fn sync_submodule(
+    submodule_store: &dyn SubmoduleStore,
+    config: &SubmoduleConfig,
+) -> Result<bool, GitSubmoduleError> {
+    if !submodule_store.is_initialized(&config.name)? {
+        return Ok(false);
+    }
+    let git_repo = git2::Repository::open(submodule_store.repo_path(&config.name)?)?;
+    let remote = git_repo.find_remote(SUBMODULE_REMOTE_NAME)?;
+    if remote.url() == Some(config.url.as_str()) {
+        return Ok(false);
+    }
+    git_repo.remote_set_url(SUBMODULE_REMOTE_NAME, &config.url)?;
+    Ok(true)
+}
@@ -79,6 +79,7 @@ use crate::object_id::ObjectId;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
+use crate::settings::GitSettings;
 use crate::settings::UserSettings;
 use crate::stacked_table::MutableTable;
 use crate::stacked_table::ReadonlyTable;
@@ -93,6 +94,8 @@ const NO_GC_REF_NAMESPACE: &str = "refs/jj/keep/";
 const CONFLICT_SUFFIX: &str = ".jjconflict";
 
 const JJ_TREES_COMMIT_HEADER: &[u8] = b"jj:trees";
+const JJ_CHANGE_ID_COMMIT_HEADER: &[u8] = b"jj:change-id";
+const JJ_PREDECESSORS_COMMIT_HEADER: &[u8] = b"jj:predecessors";
 
 #[derive(Debug, Error)]
 pub enum GitBackendInitError {
@@ -159,6 +162,9 @@ pub struct GitBackend {
     empty_tree_id: TreeId,
     extra_metadata_store: TableStore,
     cached_extra_metadata: Mutex<Option<Arc<ReadonlyTable>>>,
+    /// Whether to record the change id and predecessors in commit headers so
+    /// they survive a round trip through a Git remote.
+    write_jj_headers: bool,
 }
 
 impl GitBackend {
@@ -166,7 +172,11 @@ impl GitBackend {
         "git"
     }
 
-    fn new(base_repo: gix::ThreadSafeRepository, extra_metadata_store: TableStore) -> Self {
+    fn new(
+        base_repo: gix::ThreadSafeRepository,
+        extra_metadata_store: TableStore,
+        git_settings: &GitSettings,
+    ) -> Self {
         let repo = Mutex::new(base_repo.to_thread_local());
         let root_commit_id = CommitId::from_bytes(&[0; HASH_LENGTH]);
         let root_change_id = ChangeId::from_bytes(&[0; CHANGE_ID_LENGTH]);
@@ -179,6 +189,7 @@ impl GitBackend {
             empty_tree_id,
             extra_metadata_store,
             cached_extra_metadata: Mutex::new(None),
+            write_jj_headers: git_settings.write_jj_headers,
         }
     }
 
@@ -194,7 +205,7 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendInitError::InitRepository)?;
-        Self::init_with_repo(store_path, git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, git_repo_path, git_repo)
     }
 
     /// Initializes backend by creating a new Git repo at the specified
@@ -218,7 +229,7 @@ impl GitBackend {
         )
         .map_err(GitBackendInitError::InitRepository)?;
         let git_repo_path = workspace_root.join(".git");
-        Self::init_with_repo(store_path, &git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, &git_repo_path, git_repo)
     }
 
     /// Initializes backend with an existing Git repo at the specified path.
@@ -238,10 +249,11 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendInitError::OpenRepository)?;
-        Self::init_with_repo(store_path, git_repo_path, git_repo)
+        Self::init_with_repo(settings, store_path, git_repo_path, git_repo)
     }
 
     fn init_with_repo(
+        settings: &UserSettings,
         store_path: &Path,
         git_repo_path: &Path,
         git_repo: gix::ThreadSafeRepository,
@@ -271,7 +283,11 @@ impl GitBackend {
                 .map_err(GitBackendInitError::Path)?;
         };
         let extra_metadata_store = TableStore::init(extra_path, HASH_LENGTH);
-        Ok(GitBackend::new(git_repo, extra_metadata_store))
+        Ok(GitBackend::new(
+            git_repo,
+            extra_metadata_store,
+            &settings.git_settings(),
+        ))
     }
 
     pub fn load(
@@ -294,7 +310,11 @@ impl GitBackend {
         )
         .map_err(GitBackendLoadError::OpenRepository)?;
         let extra_metadata_store = TableStore::load(store_path.join("extra"), HASH_LENGTH);
-        Ok(GitBackend::new(repo, extra_metadata_store))
+        Ok(GitBackend::new(
+            repo,
+            extra_metadata_store,
+            &settings.git_settings(),
+        ))
     }
 
     fn lock_git_repo(&self) -> MutexGuard<'_, gix::Repository> {
@@ -321,6 +341,26 @@ impl GitBackend {
         self.base_repo.work_dir()
     }
 
+    /// Returns true if the commit carries its change id in a `jj:change-id`
+    /// header, so that jj can read it back from the Git repo as is.
+    pub fn has_change_id_header(&self, id: &CommitId) -> BackendResult<bool> {
+        if *id == self.root_commit_id {
+            return Ok(false);
+        }
+        let git_commit_id = validate_git_object_id(id)?;
+        let locked_repo = self.lock_git_repo();
+        let git_object = locked_repo
+            .find_object(git_commit_id)
+            .map_err(|err| map_not_found_err(err, id))?;
+        let commit = git_object
+            .try_to_commit_ref()
+            .map_err(|err| to_read_object_err(err, id))?;
+        Ok(commit
+            .extra_headers
+            .iter()
+            .any(|(key, _)| *key == JJ_CHANGE_ID_COMMIT_HEADER))
+    }
+
     fn cached_extra_metadata_table(&self) -> BackendResult<Arc<ReadonlyTable>> {
         let mut locked_head = self.cached_extra_metadata.lock().unwrap();
         match locked_head.as_ref() {
@@ -409,6 +449,57 @@ impl GitBackend {
         self.save_extra_metadata_table(mut_table, &table_lock)
     }
 
+    /// Writes an annotated tag object named `name` which points to the
+    /// `target` commit, and returns its id. The tag is signed if `sign_with`
+    /// is specified.
+    pub fn write_tag(
+        &self,
+        name: &str,
+        target: &CommitId,
+        tagger: &Signature,
+        message: &str,
+        sign_with: Option<&mut SigningFn>,
+    ) -> BackendResult<gix::ObjectId> {
+        if *target == self.root_commit_id {
+            return Err(BackendError::Unsupported(
+                "The Git backend does not support tagging the root commit.".to_owned(),
+            ));
+        }
+        let mut message = message.to_owned();
+        if !message.ends_with('\n') {
+            message.push('\n');
+        }
+        let mut tag = gix::objs::Tag {
+            target: validate_git_object_id(target)?,
+            target_kind: gix::object::Kind::Commit,
+            name: name.into(),
+            tagger: Some(signature_to_git(tagger).into()),
+            message: message.into(),
+            pgp_signature: None,
+        };
+        if let Some(sign) = sign_with {
+            let mut data = Vec::with_capacity(256);
+            tag.write_to(&mut data).unwrap();
+            let sig = sign(&data).map_err(|err| BackendError::WriteObject {
+                object_type: "tag",
+                source: Box::new(err),
+            })?;
+            // Git appends the signature right after the message. Since gix inserts
+            // a newline before the signature, strip the one terminating the message
+            // so the signed payload stays intact.
+            tag.message.pop();
+            tag.pgp_signature = Some(sig.into());
+        }
+        let locked_repo = self.lock_git_repo();
+        let id = locked_repo
+            .write_object(&tag)
+            .map_err(|err| BackendError::WriteObject {
+                object_type: "tag",
+                source: Box::new(err),
+            })?;
+        Ok(id.detach())
+    }
+
     fn read_file_sync(&self, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let git_blob_id = validate_git_object_id(id)?;
         let locked_repo = self.lock_git_repo();
@@ -515,6 +606,39 @@ fn root_tree_from_header(git_commit: &CommitRef) -> Result<Option<MergedTreeId>,
     Ok(None)
 }
 
+/// Reads the `jj:change-id` header from the commit.
+fn change_id_from_header(git_commit: &CommitRef) -> Result<Option<ChangeId>, ()> {
+    for (key, value) in &git_commit.extra_headers {
+        if *key == JJ_CHANGE_ID_COMMIT_HEADER {
+            let hex = str::from_utf8(value.as_ref()).or(Err(()))?;
+            let change_id = ChangeId::try_from_hex(hex).or(Err(()))?;
+            if change_id.as_bytes().len() != CHANGE_ID_LENGTH {
+                return Err(());
+            }
+            return Ok(Some(change_id));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads the `jj:predecessors` header from the commit.
+fn predecessors_from_header(git_commit: &CommitRef) -> Result<Vec<CommitId>, ()> {
+    for (key, value) in &git_commit.extra_headers {
+        if *key == JJ_PREDECESSORS_COMMIT_HEADER {
+            let mut predecessors = vec![];
+            for hex in str::from_utf8(value.as_ref()).or(Err(()))?.split(' ') {
+                let id = CommitId::try_from_hex(hex).or(Err(()))?;
+                if id.as_bytes().len() != HASH_LENGTH {
+                    return Err(());
+                }
+                predecessors.push(id);
+            }
+            return Ok(predecessors);
+        }
+    }
+    Ok(vec![])
+}
+
 fn commit_from_git_without_root_parent(
     id: &CommitId,
     git_object: &gix::Object,
@@ -530,13 +654,19 @@ fn commit_from_git_without_root_parent(
     // would have been enough to pick the last 16 bytes instead of the
     // leading 16 bytes to address that. We also reverse the bits to make it less
     // likely that users depend on any relationship between the two ids.
-    let change_id = ChangeId::new(
-        id.as_bytes()[4..HASH_LENGTH]
-            .iter()
-            .rev()
-            .map(|b| b.reverse_bits())
-            .collect(),
-    );
+    // Commits written by jj with `git.write-jj-headers` enabled carry their
+    // original change id in a header instead.
+    let change_id = change_id_from_header(&commit)
+        .map_err(|()| to_read_object_err("Invalid jj:change-id header", id))?;
+    let change_id = change_id.unwrap_or_else(|| {
+        ChangeId::new(
+            id.as_bytes()[4..HASH_LENGTH]
+                .iter()
+                .rev()
+                .map(|b| b.reverse_bits())
+                .collect(),
+        )
+    });
     let parents = commit
         .parents()
         .map(|oid| CommitId::from_bytes(oid.as_bytes()))
@@ -546,6 +676,8 @@ fn commit_from_git_without_root_parent(
     // the extra metadata.
     let root_tree = root_tree_from_header(&commit)
         .map_err(|()| to_read_object_err("Invalid jj:trees header", id))?;
+    let predecessors = predecessors_from_header(&commit)
+        .map_err(|()| to_read_object_err("Invalid jj:predecessors header", id))?;
     let root_tree = root_tree.unwrap_or_else(|| {
         if uses_tree_conflict_format {
             MergedTreeId::resolved(tree_id)
@@ -582,14 +714,15 @@ fn commit_from_git_without_root_parent(
 
     Ok(Commit {
         parents,
-        predecessors: vec![],
         // If this commit has associated extra metadata, we may reset this later.
+        predecessors,
         root_tree,
         change_id,
         description,
         author,
         committer,
         secure_sig,
+        copies: vec![],
     })
 }
 
@@ -687,9 +820,11 @@ fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
             commit.root_tree = MergedTreeId::resolved(legacy_tree_id.clone());
         }
     }
-    for predecessor in &proto.predecessors {
-        commit.predecessors.push(CommitId::from_bytes(predecessor));
-    }
+    commit.predecessors = proto
+        .predecessors
+        .iter()
+        .map(|id_bytes| CommitId::from_bytes(id_bytes))
+        .collect();
 }
 
 /// Returns `RefEdit` that will create a ref in `refs/jj/keep` if not exist.
@@ -871,8 +1006,13 @@ fn import_extra_metadata_entries_from_heads(
         // TODO(#1624): Should we read the root tree here and check if it has a
         // `.jjconflict-...` entries? That could happen if the user used `git` to e.g.
         // change the description of a commit with tree-level conflicts.
-        let commit =
+        let mut commit =
             commit_from_git_without_root_parent(&id, &git_object, uses_tree_conflict_format)?;
+        // Predecessors recorded in the jj:predecessors header may not have been
+        // fetched along with the commit.
+        commit.predecessors.retain(|predecessor_id| {
+            validate_git_object_id(predecessor_id).is_ok_and(|oid| git_repo.has_object(oid))
+        });
         mut_table.add_entry(id.to_bytes(), serialize_extras(&commit));
         work_ids.extend(
             commit
@@ -1171,6 +1311,8 @@ impl Backend for GitBackend {
         mut sign_with: Option<&mut SigningFn>,
     ) -> BackendResult<(CommitId, Commit)> {
         assert!(contents.secure_sig.is_none(), "commit.secure_sig was set");
+        // Copies are detected from the trees instead of being stored.
+        contents.copies.clear();
 
         let locked_repo = self.lock_git_repo();
         let git_tree_id = match &contents.root_tree {
@@ -1216,6 +1358,19 @@ impl Backend for GitBackend {
                 ));
             }
         }
+        if self.write_jj_headers {
+            extra_headers.push((
+                BString::new(JJ_CHANGE_ID_COMMIT_HEADER.to_vec()),
+                BString::new(contents.change_id.hex().into_bytes()),
+            ));
+            if !contents.predecessors.is_empty() {
+                let value = contents.predecessors.iter().map(|id| id.hex()).join(" ");
+                extra_headers.push((
+                    BString::new(JJ_PREDECESSORS_COMMIT_HEADER.to_vec()),
+                    BString::new(value.into_bytes()),
+                ));
+            }
+        }
         let extras = serialize_extras(&contents);
 
         // If two writers write commits of the same id with different metadata, they
@@ -1832,6 +1987,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -1914,6 +2070,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -2007,6 +2164,7 @@ mod tests {
             author: signature.clone(),
             committer: signature,
             secure_sig: None,
+            copies: vec![],
         };
         let commit_id = backend.write_commit(commit, None).block_on().unwrap().0;
         let git_refs: Vec<_> = git_repo
@@ -2084,6 +2242,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -2130,6 +2289,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let mut signer = |data: &_| {
@@ -2180,6 +2340,82 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn jj_headers_round_trip() {
+        let config = config::Config::builder()
+            .set_override("git.write-jj-headers", true)
+            .unwrap()
+            .build()
+            .unwrap();
+        let settings = UserSettings::from_config(config);
+        let temp_dir = testutils::new_temp_dir();
+        let git_repo_path = temp_dir.path().join("git");
+        git2::Repository::init_bare(&git_repo_path).unwrap();
+        let store_path1 = temp_dir.path().join("store1");
+        let store_path2 = temp_dir.path().join("store2");
+        fs::create_dir(&store_path1).unwrap();
+        fs::create_dir(&store_path2).unwrap();
+        let backend = GitBackend::init_external(&settings, &store_path1, &git_repo_path).unwrap();
+
+        let commit1 = Commit {
+            parents: vec![backend.root_commit_id().clone()],
+            predecessors: vec![],
+            root_tree: MergedTreeId::resolved(backend.empty_tree_id().clone()),
+            change_id: ChangeId::from_hex("0123456789abcdef0123456789abcdef"),
+            description: "initial".to_string(),
+            author: create_signature(),
+            committer: create_signature(),
+            secure_sig: None,
+            copies: vec![],
+        };
+        let (commit_id1, _) = backend.write_commit(commit1, None).block_on().unwrap();
+        let missing_id = CommitId::from_hex("1111111111111111111111111111111111111111");
+        let empty_tree_id = backend.empty_tree_id().clone();
+        let commit2 = Commit {
+            parents: vec![backend.root_commit_id().clone()],
+            predecessors: vec![commit_id1.clone(), missing_id],
+            root_tree: MergedTreeId::Merge(Merge::from_removes_adds(
+                vec![empty_tree_id.clone()],
+                vec![empty_tree_id.clone(), empty_tree_id],
+            )),
+            change_id: ChangeId::from_hex("0123456789abcdef0123456789abcdef"),
+            description: "rewritten".to_string(),
+            author: create_signature(),
+            committer: create_signature(),
+            secure_sig: None,
+            copies: vec![],
+        };
+        let (commit_id2, commit2) = backend.write_commit(commit2, None).block_on().unwrap();
+        let git_repo = backend.git_repo();
+        let obj = git_repo
+            .find_object(gix::ObjectId::try_from(commit_id2.as_bytes()).unwrap())
+            .unwrap();
+        insta::assert_snapshot!(std::str::from_utf8(&obj.data).unwrap(), @r###"
+        tree 8fa5a6a8f517f258ef52fd7a220483469754fbdc
+        author Someone <someone@example.com> 0 +0000
+        committer Someone <someone@example.com> 0 +0000
+        jj:trees 4b825dc642cb6eb9a060e54bf8d69288fbee4904 4b825dc642cb6eb9a060e54bf8d69288fbee4904 4b825dc642cb6eb9a060e54bf8d69288fbee4904
+        jj:change-id 0123456789abcdef0123456789abcdef
+        jj:predecessors 386f51de67d9564748362972c86883a719a41a6d 1111111111111111111111111111111111111111
+
+        rewritten
+        "###);
+
+        // Another repo sharing the Git objects reads the change id, the
+        // conflict and the predecessors back, except for the missing one.
+        let backend =
+            GitBackend::init_external(&user_settings(), &store_path2, &git_repo_path).unwrap();
+        backend.import_head_commits([&commit_id2]).unwrap();
+        let read_commit = backend.read_commit(&commit_id2).block_on().unwrap();
+        assert_eq!(
+            read_commit,
+            Commit {
+                predecessors: vec![commit_id1],
+                ..commit2
+            }
+        );
+    }
+
     fn git_id(commit_id: &CommitId) -> Oid {
         Oid::from_bytes(commit_id.as_bytes()).unwrap()
     }
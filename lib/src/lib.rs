@@ -28,7 +28,10 @@ extern crate self as jj_lib;
 #[macro_use]
 pub mod content_hash;
 
+pub mod absorb;
+pub mod annotate;
 pub mod backend;
+pub mod bisect;
 pub mod commit;
 pub mod commit_builder;
 pub mod conflicts;
@@ -61,16 +64,20 @@ pub mod lock;
 pub mod matchers;
 pub mod merge;
 pub mod merged_tree;
+pub mod native_sync;
 pub mod object_id;
+pub mod object_pack;
 pub mod op_heads_store;
 pub mod op_store;
 pub mod op_walk;
 pub mod operation;
+pub mod patch;
 #[allow(missing_docs)]
 pub mod protos;
 pub mod refs;
 pub mod repo;
 pub mod repo_path;
+pub mod rerere;
 pub mod revset;
 mod revset_parser;
 pub mod rewrite;
@@ -15,9 +15,12 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fs;
 use std::fs::File;
+use std::io;
+use std::io::Cursor;
 use std::io::Read;
 use std::io::Write;
 use std::path::Path;
@@ -29,6 +32,7 @@ use blake2::Blake2b512;
 use blake2::Digest;
 use futures::stream;
 use futures::stream::BoxStream;
+use itertools::Itertools as _;
 use pollster::FutureExt;
 use prost::Message;
 use tempfile::NamedTempFile;
@@ -39,6 +43,7 @@ use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::Commit;
+use crate::backend::CommitCopy;
 use crate::backend::CommitId;
 use crate::backend::Conflict;
 use crate::backend::ConflictId;
@@ -60,9 +65,14 @@ use crate::file_util::persist_content_addressed_temp_file;
 use crate::index::Index;
 use crate::merge::MergeBuilder;
 use crate::object_id::ObjectId;
+use crate::object_pack::gc_objects;
+use crate::object_pack::LooseObjectDir;
+use crate::object_pack::ObjectKey;
+use crate::object_pack::PackSet;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponentBuf;
+use crate::settings::UserSettings;
 
 const COMMIT_ID_LENGTH: usize = 64;
 const CHANGE_ID_LENGTH: usize = 16;
@@ -87,12 +97,49 @@ fn to_other_err(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Bac
     BackendError::Other(err.into())
 }
 
+/// Kinds of objects in the packs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ObjectKind {
+    Commit = 0,
+    Tree = 1,
+    File = 2,
+    Symlink = 3,
+    Conflict = 4,
+}
+
+impl ObjectKind {
+    const ALL: [ObjectKind; 5] = [
+        ObjectKind::Commit,
+        ObjectKind::Tree,
+        ObjectKind::File,
+        ObjectKind::Symlink,
+        ObjectKind::Conflict,
+    ];
+
+    /// Directory of the loose objects of this kind.
+    fn dir_name(self) -> &'static str {
+        match self {
+            ObjectKind::Commit => "commits",
+            ObjectKind::Tree => "trees",
+            ObjectKind::File => "files",
+            ObjectKind::Symlink => "symlinks",
+            ObjectKind::Conflict => "conflicts",
+        }
+    }
+
+    fn key(self, id: &impl ObjectId) -> ObjectKey {
+        (self as u8, id.to_bytes())
+    }
+}
+
 #[derive(Debug)]
 pub struct LocalBackend {
     path: PathBuf,
     root_commit_id: CommitId,
     root_change_id: ChangeId,
     empty_tree_id: TreeId,
+    packs: PackSet,
+    pack_objects: bool,
 }
 
 impl LocalBackend {
@@ -100,13 +147,11 @@ impl LocalBackend {
         "local"
     }
 
-    pub fn init(store_path: &Path) -> Self {
-        fs::create_dir(store_path.join("commits")).unwrap();
-        fs::create_dir(store_path.join("trees")).unwrap();
-        fs::create_dir(store_path.join("files")).unwrap();
-        fs::create_dir(store_path.join("symlinks")).unwrap();
-        fs::create_dir(store_path.join("conflicts")).unwrap();
-        let backend = Self::load(store_path);
+    pub fn init(settings: &UserSettings, store_path: &Path) -> Self {
+        for kind in ObjectKind::ALL {
+            fs::create_dir(store_path.join(kind.dir_name())).unwrap();
+        }
+        let backend = Self::load(settings, store_path);
         let empty_tree_id = backend
             .write_tree(RepoPath::root(), &Tree::default())
             .block_on()
@@ -115,7 +160,7 @@ impl LocalBackend {
         backend
     }
 
-    pub fn load(store_path: &Path) -> Self {
+    pub fn load(settings: &UserSettings, store_path: &Path) -> Self {
         let root_commit_id = CommitId::from_bytes(&[0; COMMIT_ID_LENGTH]);
         let root_change_id = ChangeId::from_bytes(&[0; CHANGE_ID_LENGTH]);
         let empty_tree_id = TreeId::from_hex(
@@ -126,6 +171,8 @@ impl LocalBackend {
             root_commit_id,
             root_change_id,
             empty_tree_id,
+            packs: PackSet::new(store_path.join("packs")),
+            pack_objects: settings.gc_pack_objects(),
         }
     }
 
@@ -148,6 +195,84 @@ impl LocalBackend {
     fn conflict_path(&self, id: &ConflictId) -> PathBuf {
         self.path.join("conflicts").join(id.hex())
     }
+
+    /// Reads the data of a loose object, or the packed object if there's no
+    /// loose file.
+    fn read_object_data(
+        &self,
+        kind: ObjectKind,
+        path: &Path,
+        id: &impl ObjectId,
+    ) -> BackendResult<Vec<u8>> {
+        match fs::read(path) {
+            Ok(buf) => Ok(buf),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => self
+                .read_packed_object(kind, id)?
+                .ok_or_else(|| map_not_found_err(err, id)),
+            Err(err) => Err(map_not_found_err(err, id)),
+        }
+    }
+
+    /// Reads and decompresses the whole packed object into memory.
+    fn read_packed_object(
+        &self,
+        kind: ObjectKind,
+        id: &impl ObjectId,
+    ) -> BackendResult<Option<Vec<u8>>> {
+        self.packs
+            .read(kind as u8, id.as_bytes())
+            .map_err(|err| BackendError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: Box::new(err),
+            })
+    }
+
+    /// Collects the objects reachable from the `heads`, which should include
+    /// all commits to be preserved.
+    fn collect_reachable_objects(
+        &self,
+        heads: impl IntoIterator<Item = CommitId>,
+    ) -> BackendResult<HashSet<ObjectKey>> {
+        let mut reachable = HashSet::new();
+        let mut pending_commits = heads.into_iter().collect_vec();
+        let mut pending_values = vec![TreeValue::Tree(self.empty_tree_id.clone())];
+        while let Some(id) = pending_commits.pop() {
+            if id == self.root_commit_id || !reachable.insert(ObjectKind::Commit.key(&id)) {
+                continue;
+            }
+            let commit = self.read_commit(&id).block_on()?;
+            pending_values.extend(commit.root_tree.to_merge().into_iter().map(TreeValue::Tree));
+            pending_commits.extend(commit.parents);
+        }
+        while let Some(value) = pending_values.pop() {
+            match value {
+                TreeValue::File { id, .. } => {
+                    reachable.insert(ObjectKind::File.key(&id));
+                }
+                TreeValue::Symlink(id) => {
+                    reachable.insert(ObjectKind::Symlink.key(&id));
+                }
+                TreeValue::Tree(id) => {
+                    if reachable.insert(ObjectKind::Tree.key(&id)) {
+                        let tree = self.read_tree(RepoPath::root(), &id).block_on()?;
+                        pending_values.extend(tree.entries().map(|entry| entry.value().clone()));
+                    }
+                }
+                TreeValue::Conflict(id) => {
+                    if reachable.insert(ObjectKind::Conflict.key(&id)) {
+                        let conflict = self.read_conflict(RepoPath::root(), &id)?;
+                        pending_values.extend(
+                            itertools::chain(conflict.removes, conflict.adds)
+                                .map(|term| term.value),
+                        );
+                    }
+                }
+                TreeValue::GitSubmodule(_) => {}
+            }
+        }
+        Ok(reachable)
+    }
 }
 
 #[async_trait]
@@ -184,9 +309,21 @@ impl Backend for LocalBackend {
         1
     }
 
+    /// Loose files are decompressed as they're read, but packed files are
+    /// loaded into memory in full.
+    // TODO: stream packed files from the pack
     async fn read_file(&self, _path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let path = self.file_path(id);
-        let file = File::open(path).map_err(|err| map_not_found_err(err, id))?;
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let data = self
+                    .read_packed_object(ObjectKind::File, id)?
+                    .ok_or_else(|| map_not_found_err(err, id))?;
+                return Ok(Box::new(Cursor::new(data)));
+            }
+            Err(err) => return Err(map_not_found_err(err, id)),
+        };
         Ok(Box::new(zstd::Decoder::new(file).map_err(to_other_err)?))
     }
 
@@ -218,7 +355,12 @@ impl Backend for LocalBackend {
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
         let path = self.symlink_path(id);
-        let target = fs::read_to_string(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object_data(ObjectKind::Symlink, &path, id)?;
+        let target = String::from_utf8(buf).map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: Box::new(err),
+        })?;
         Ok(target)
     }
 
@@ -238,7 +380,7 @@ impl Backend for LocalBackend {
 
     async fn read_tree(&self, _path: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
         let path = self.tree_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object_data(ObjectKind::Tree, &path, id)?;
 
         let proto = crate::protos::local_store::Tree::decode(&*buf).map_err(to_other_err)?;
         Ok(tree_from_proto(proto))
@@ -262,7 +404,7 @@ impl Backend for LocalBackend {
 
     fn read_conflict(&self, _path: &RepoPath, id: &ConflictId) -> BackendResult<Conflict> {
         let path = self.conflict_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object_data(ObjectKind::Conflict, &path, id)?;
 
         let proto = crate::protos::local_store::Conflict::decode(&*buf).map_err(to_other_err)?;
         Ok(conflict_from_proto(proto))
@@ -293,7 +435,7 @@ impl Backend for LocalBackend {
         }
 
         let path = self.commit_path(id);
-        let buf = fs::read(path).map_err(|err| map_not_found_err(err, id))?;
+        let buf = self.read_object_data(ObjectKind::Commit, &path, id)?;
 
         let proto = crate::protos::local_store::Commit::decode(&*buf).map_err(to_other_err)?;
         Ok(commit_from_proto(proto))
@@ -342,8 +484,36 @@ impl Backend for LocalBackend {
         Ok(Box::pin(stream::empty()))
     }
 
-    fn gc(&self, _index: &dyn Index, _keep_newer: SystemTime) -> BackendResult<()> {
-        Ok(())
+    #[tracing::instrument(skip(self, index))]
+    fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
+        let heads = index.all_heads_for_gc().map_err(to_other_err)?;
+        // Reachable objects are resolved without considering the keep_newer
+        // parameter. Objects written after that will be preserved by their
+        // file modification time.
+        let reachable = self.collect_reachable_objects(heads)?;
+        tracing::info!(
+            reachable_object_count = reachable.len(),
+            "collected reachable objects"
+        );
+        let read_plain = |path: &Path| fs::read(path);
+        let read_compressed = |path: &Path| zstd::decode_all(File::open(path)?);
+        let loose_dirs = ObjectKind::ALL.map(|kind| LooseObjectDir {
+            kind: kind as u8,
+            path: self.path.join(kind.dir_name()),
+            read: if kind == ObjectKind::File {
+                &read_compressed
+            } else {
+                &read_plain
+            },
+        });
+        gc_objects(
+            &self.packs,
+            &loose_dirs,
+            &reachable,
+            self.pack_objects,
+            keep_newer,
+        )
+        .map_err(to_other_err)
     }
 }
 
@@ -370,6 +540,14 @@ pub fn commit_to_proto(commit: &Commit) -> crate::protos::local_store::Commit {
     proto.description = commit.description.clone();
     proto.author = Some(signature_to_proto(&commit.author));
     proto.committer = Some(signature_to_proto(&commit.committer));
+    proto.copies = commit
+        .copies
+        .iter()
+        .map(|copy| crate::protos::local_store::commit::Copy {
+            source: copy.source.as_internal_file_string().to_owned(),
+            target: copy.target.as_internal_file_string().to_owned(),
+        })
+        .collect();
     proto
 }
 
@@ -391,6 +569,14 @@ fn commit_from_proto(mut proto: crate::protos::local_store::Commit) -> Commit {
         MergedTreeId::Legacy(TreeId::new(proto.root_tree[0].to_vec()))
     };
     let change_id = ChangeId::new(proto.change_id);
+    let copies = proto
+        .copies
+        .into_iter()
+        .map(|copy| CommitCopy {
+            source: RepoPathBuf::from_internal_string(copy.source),
+            target: RepoPathBuf::from_internal_string(copy.target),
+        })
+        .collect();
     Commit {
         parents,
         predecessors,
@@ -400,6 +586,7 @@ fn commit_from_proto(mut proto: crate::protos::local_store::Commit) -> Commit {
         author: signature_from_proto(proto.author.unwrap_or_default()),
         committer: signature_from_proto(proto.committer.unwrap_or_default()),
         secure_sig,
+        copies,
     }
 }
 
@@ -546,7 +733,10 @@ mod tests {
         let temp_dir = testutils::new_temp_dir();
         let store_path = temp_dir.path();
 
-        let backend = LocalBackend::init(store_path);
+        let backend = LocalBackend::init(
+            &UserSettings::from_config(config::Config::default()),
+            store_path,
+        );
         let mut commit = Commit {
             parents: vec![],
             predecessors: vec![],
@@ -556,6 +746,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
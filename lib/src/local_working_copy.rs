@@ -52,6 +52,8 @@ use tracing::trace_span;
 
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
+use crate::backend::CommitCopy;
+use crate::backend::CommitId;
 use crate::backend::FileId;
 use crate::backend::MergedTreeId;
 use crate::backend::MillisSinceEpoch;
@@ -62,9 +64,13 @@ use crate::commit::Commit;
 use crate::conflicts;
 use crate::conflicts::materialize_merge_result;
 use crate::conflicts::materialize_tree_value;
+use crate::conflicts::ConflictMarkerStyle;
 use crate::conflicts::MaterializedTreeValue;
+use crate::copies;
 use crate::file_util::check_symlink_support;
 use crate::file_util::try_symlink;
+#[cfg(target_os = "linux")]
+use crate::fsmonitor::inotify;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::watchman;
 use crate::fsmonitor::FsmonitorSettings;
@@ -94,6 +100,7 @@ use crate::settings::HumanByteSize;
 use crate::store::Store;
 use crate::tree::Tree;
 use crate::working_copy::CheckoutError;
+use crate::working_copy::CheckoutOptions;
 use crate::working_copy::CheckoutStats;
 use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::ResetError;
@@ -165,10 +172,12 @@ impl FileState {
         }
     }
 
-    fn for_gitsubmodule() -> Self {
+    /// The `head_mtime` is the last modification time of the submodule HEAD
+    /// seen by the working copy.
+    fn for_gitsubmodule(head_mtime: MillisSinceEpoch) -> Self {
         FileState {
             file_type: FileType::GitSubmodule,
-            mtime: MillisSinceEpoch(0),
+            mtime: head_mtime,
             size: 0,
         }
     }
@@ -339,6 +348,13 @@ pub struct TreeState {
     /// the repo is configured to use the Watchman filesystem monitor and
     /// Watchman has been queried at least once.
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    /// The most recent clock value returned by the inotify daemon. Will only
+    /// be set if the repo is configured to use the inotify filesystem monitor
+    /// and the daemon was running when the working copy was last snapshotted.
+    inotify_clock: Option<crate::protos::working_copy::InotifyClock>,
+    /// Files copied or renamed by the last snapshot, relative to the tree
+    /// before it. Not persisted.
+    snapshot_copies: Vec<CommitCopy>,
 }
 
 fn file_state_from_proto(proto: &crate::protos::working_copy::FileState) -> FileState {
@@ -511,6 +527,7 @@ fn file_state(metadata: &Metadata) -> Option<FileState> {
 struct FsmonitorMatcher {
     matcher: Option<Box<dyn Matcher>>,
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    inotify_clock: Option<crate::protos::working_copy::InotifyClock>,
 }
 
 struct DirectoryToVisit<'a> {
@@ -563,6 +580,12 @@ impl TreeState {
         &self.sparse_patterns
     }
 
+    /// Files detected as copied or renamed by the last call to `snapshot()`,
+    /// relative to the tree before that snapshot.
+    pub fn snapshot_copies(&self) -> &[CommitCopy] {
+        &self.snapshot_copies
+    }
+
     fn sparse_matcher(&self) -> Box<dyn Matcher> {
         Box::new(PrefixMatcher::new(&self.sparse_patterns))
     }
@@ -589,6 +612,8 @@ impl TreeState {
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
             watchman_clock: None,
+            inotify_clock: None,
+            snapshot_copies: vec![],
         }
     }
 
@@ -652,6 +677,7 @@ impl TreeState {
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
         self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
         self.watchman_clock = proto.watchman_clock;
+        self.inotify_clock = proto.inotify_clock;
         Ok(())
     }
 
@@ -679,6 +705,7 @@ impl TreeState {
         }
         proto.sparse_patterns = Some(sparse_patterns);
         proto.watchman_clock = self.watchman_clock.clone();
+        proto.inotify_clock = self.inotify_clock.clone();
 
         let mut temp_file = NamedTempFile::new_in(&self.state_path).unwrap();
         temp_file
@@ -773,6 +800,22 @@ impl TreeState {
         Ok(changed_files)
     }
 
+    #[cfg(target_os = "linux")]
+    fn inotify_fsmonitor(&self) -> inotify::Fsmonitor {
+        inotify::Fsmonitor::new(&self.working_copy_path, &self.state_path.join("inotify"))
+    }
+
+    #[cfg(target_os = "linux")]
+    #[instrument(skip(self))]
+    pub fn query_inotify(
+        &self,
+    ) -> Result<(Option<inotify::Clock>, Option<Vec<PathBuf>>), TreeStateError> {
+        let previous_clock = self.inotify_clock.clone().map(inotify::Clock::from);
+        self.inotify_fsmonitor()
+            .query_changed_files(previous_clock)
+            .map_err(|err| TreeStateError::Fsmonitor(Box::new(err)))
+    }
+
     #[cfg(feature = "watchman")]
     #[tokio::main(flavor = "current_thread")]
     #[instrument(skip(self))]
@@ -799,15 +842,18 @@ impl TreeState {
             progress,
             start_tracking_matcher,
             max_new_file_size,
+            conflict_marker_style,
         } = options;
 
         let sparse_matcher = self.sparse_matcher();
+        self.snapshot_copies.clear();
 
         let fsmonitor_clock_needs_save = *fsmonitor_settings != FsmonitorSettings::None;
         let mut is_dirty = fsmonitor_clock_needs_save;
         let FsmonitorMatcher {
             matcher: fsmonitor_matcher,
             watchman_clock,
+            inotify_clock,
         } = self.make_fsmonitor_matcher(fsmonitor_settings)?;
         let fsmonitor_matcher = match fsmonitor_matcher.as_ref() {
             None => &EverythingMatcher,
@@ -818,6 +864,7 @@ impl TreeState {
         if matcher.visit(RepoPath::root()).is_nothing() {
             // No need to iterate file states to build empty deleted_files.
             self.watchman_clock = watchman_clock;
+            self.inotify_clock = inotify_clock;
             return Ok(is_dirty);
         }
 
@@ -843,6 +890,7 @@ impl TreeState {
                 directory_to_visit,
                 *progress,
                 *max_new_file_size,
+                *conflict_marker_style,
             )
         })?;
 
@@ -860,8 +908,10 @@ impl TreeState {
                     .map(|(path, _state)| path.to_owned())
                     .collect()
             });
+        let mut changed_paths = vec![];
         trace_span!("process tree entries").in_scope(|| -> Result<(), SnapshotError> {
             while let Ok((path, tree_values)) = tree_entries_rx.recv() {
+                changed_paths.push(path.clone());
                 tree_builder.set_or_remove(path, tree_values);
             }
             Ok(())
@@ -886,11 +936,24 @@ impl TreeState {
             self.file_states
                 .merge_in(changed_file_states, &deleted_files);
         });
+        let old_tree_id = self.tree_id.clone();
         trace_span!("write tree").in_scope(|| {
             let new_tree_id = tree_builder.write_tree(&self.store).unwrap();
             is_dirty |= new_tree_id != self.tree_id;
             self.tree_id = new_tree_id;
         });
+        if self.tree_id != old_tree_id {
+            trace_span!("detect copies").in_scope(|| -> Result<(), SnapshotError> {
+                // Only the paths touched by this snapshot can be copies or
+                // copy sources, so there's no need to diff the whole trees.
+                let old_tree = self.store.get_root_tree(&old_tree_id)?;
+                let new_tree = self.current_tree()?;
+                let paths = changed_paths.iter().chain(&deleted_files);
+                self.snapshot_copies =
+                    copies::detect_copies_in_paths(&old_tree, &new_tree, paths.map(AsRef::as_ref))?;
+                Ok(())
+            })?;
+        }
         if cfg!(debug_assertions) {
             let tree = self.current_tree().unwrap();
             let tree_paths: HashSet<_> = tree
@@ -902,6 +965,7 @@ impl TreeState {
             assert_eq!(state_paths, tree_paths);
         }
         self.watchman_clock = watchman_clock;
+        self.inotify_clock = inotify_clock;
         Ok(is_dirty)
     }
 
@@ -917,6 +981,7 @@ impl TreeState {
         directory_to_visit: DirectoryToVisit,
         progress: Option<&SnapshotProgress>,
         max_new_file_size: u64,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<(), SnapshotError> {
         let DirectoryToVisit {
             dir,
@@ -960,12 +1025,31 @@ impl TreeState {
                 let maybe_current_file_state = file_states.get(&path);
                 if let Some(file_state) = &maybe_current_file_state {
                     if file_state.file_type == FileType::GitSubmodule {
+                        if file_type.is_dir() && matcher.matches(&path) {
+                            let (update, new_file_state) = self.get_updated_submodule_value(
+                                &path,
+                                &entry.path(),
+                                file_state,
+                                current_tree,
+                            )?;
+                            if let Some(tree_value) = update {
+                                tree_entries_tx.send((path.clone(), tree_value)).ok();
+                            }
+                            if let Some(new_file_state) = new_file_state {
+                                file_states_tx.send((path, new_file_state)).ok();
+                            }
+                        }
                         return Ok(());
                     }
                 }
 
                 if file_type.is_dir() {
                     let file_states = file_states.prefixed(&path);
+                    if file_states.is_empty() && entry.path().join(".git").is_file() {
+                        // Leave the working copy of a removed submodule
+                        // untracked.
+                        return Ok(());
+                    }
                     if git_ignore.matches(&path.to_internal_dir_string())
                         || start_tracking_matcher.visit(&path).is_nothing()
                     {
@@ -1002,6 +1086,7 @@ impl TreeState {
                                     Some(&current_file_state),
                                     current_tree,
                                     &new_file_state,
+                                    conflict_marker_style,
                                 )?;
                                 if let Some(tree_value) = update {
                                     tree_entries_tx
@@ -1032,6 +1117,7 @@ impl TreeState {
                             directory_to_visit,
                             progress,
                             max_new_file_size,
+                            conflict_marker_style,
                         )?;
                     }
                 } else if matcher.matches(&path) {
@@ -1070,6 +1156,7 @@ impl TreeState {
                                 maybe_current_file_state.as_ref(),
                                 current_tree,
                                 &new_file_state,
+                                conflict_marker_style,
                             )?;
                             if let Some(tree_value) = update {
                                 tree_entries_tx.send((path.clone(), tree_value)).ok();
@@ -1091,6 +1178,7 @@ impl TreeState {
         &self,
         fsmonitor_settings: &FsmonitorSettings,
     ) -> Result<FsmonitorMatcher, SnapshotError> {
+        let mut inotify_clock = None;
         let (watchman_clock, changed_files) = match fsmonitor_settings {
             FsmonitorSettings::None => (None, None),
             FsmonitorSettings::Test { changed_files } => (None, Some(changed_files.clone())),
@@ -1102,6 +1190,26 @@ impl TreeState {
                     (None, None)
                 }
             },
+            #[cfg(target_os = "linux")]
+            FsmonitorSettings::Inotify => match self.query_inotify() {
+                Ok((clock, changed_files)) => {
+                    inotify_clock = clock.map(Into::into);
+                    (None, changed_files)
+                }
+                Err(err) => {
+                    tracing::warn!(?err, "Failed to query filesystem monitor");
+                    (None, None)
+                }
+            },
+            #[cfg(not(target_os = "linux"))]
+            FsmonitorSettings::Inotify => {
+                return Err(SnapshotError::Other {
+                    message: "Failed to query the filesystem monitor".to_string(),
+                    err: "The inotify filesystem monitor is only supported on Linux (consider \
+                          disabling `core.fsmonitor`)"
+                        .into(),
+                });
+            }
             #[cfg(not(feature = "watchman"))]
             FsmonitorSettings::Watchman(_) => {
                 return Err(SnapshotError::Other {
@@ -1122,12 +1230,19 @@ impl TreeState {
                         .collect_vec()
                 });
 
-                Some(Box::new(FilesMatcher::new(repo_paths)))
+                if *fsmonitor_settings == FsmonitorSettings::Inotify {
+                    // Created or moved directories are reported without their
+                    // contents.
+                    Some(Box::new(PrefixMatcher::new(repo_paths)))
+                } else {
+                    Some(Box::new(FilesMatcher::new(repo_paths)))
+                }
             }
         };
         Ok(FsmonitorMatcher {
             matcher,
             watchman_clock,
+            inotify_clock,
         })
     }
 
@@ -1138,6 +1253,7 @@ impl TreeState {
         maybe_current_file_state: Option<&FileState>,
         current_tree: &MergedTree,
         new_file_state: &FileState,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<Option<MergedTreeValue>, SnapshotError> {
         let clean = match maybe_current_file_state {
             None => {
@@ -1167,7 +1283,13 @@ impl TreeState {
             };
             let new_tree_values = match new_file_type {
                 FileType::Normal { executable } => self
-                    .write_path_to_store(repo_path, &disk_path, &current_tree_values, executable)
+                    .write_path_to_store(
+                        repo_path,
+                        &disk_path,
+                        &current_tree_values,
+                        executable,
+                        conflict_marker_style,
+                    )
                     .block_on()?,
                 FileType::Symlink => {
                     let id = self
@@ -1185,12 +1307,46 @@ impl TreeState {
         }
     }
 
+    /// Returns the new tree value if the submodule HEAD was moved to a commit
+    /// other than the one in the current tree, and the new file state if the
+    /// HEAD was updated.
+    ///
+    /// The HEAD which hasn't been updated since the last checkout or snapshot
+    /// isn't recorded. It may point to an old commit if the submodule couldn't
+    /// be checked out.
+    fn get_updated_submodule_value(
+        &self,
+        repo_path: &RepoPath,
+        disk_path: &Path,
+        current_file_state: &FileState,
+        current_tree: &MergedTree,
+    ) -> Result<(Option<MergedTreeValue>, Option<FileState>), SnapshotError> {
+        let Some((head_id, head_mtime)) = read_submodule_head(disk_path) else {
+            // Not populated
+            return Ok((None, None));
+        };
+        if head_mtime == current_file_state.mtime {
+            return Ok((None, None));
+        }
+        let new_file_state = FileState::for_gitsubmodule(head_mtime);
+        let current_tree_values = current_tree.path_value(repo_path)?;
+        // Conflicted submodules have to be resolved explicitly
+        let update = match current_tree_values.as_normal() {
+            Some(TreeValue::GitSubmodule(id)) if *id != head_id => {
+                Some(Merge::normal(TreeValue::GitSubmodule(head_id)))
+            }
+            _ => None,
+        };
+        Ok((update, Some(new_file_state)))
+    }
+
     async fn write_path_to_store(
         &self,
         repo_path: &RepoPath,
         disk_path: &Path,
         current_tree_values: &MergedTreeValue,
         executable: FileExecutableFlag,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<MergedTreeValue, SnapshotError> {
         // If the file contained a conflict before and is now a normal file on disk, we
         // try to parse any conflict markers in the file into a conflict.
@@ -1219,6 +1375,7 @@ impl TreeState {
                 self.store.as_ref(),
                 repo_path,
                 &content,
+                conflict_marker_style,
             )
             .block_on()?;
             match new_file_ids.into_resolved() {
@@ -1296,6 +1453,29 @@ impl TreeState {
         Ok(FileState::for_symlink(&metadata))
     }
 
+    fn write_submodule_dir(&self, disk_path: &Path) -> Result<FileState, CheckoutError> {
+        // The submodule repository is checked out separately. Leave an empty
+        // directory as Git does for uninitialized submodules.
+        match fs::create_dir(disk_path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(err) => {
+                return Err(CheckoutError::Other {
+                    message: format!(
+                        "Failed to create submodule directory {}",
+                        disk_path.display()
+                    ),
+                    err: err.into(),
+                });
+            }
+        }
+        // The submodule may be populated already, but its HEAD isn't
+        // necessarily at the checked out commit.
+        let head_mtime =
+            read_submodule_head(disk_path).map_or(MillisSinceEpoch(0), |(_id, mtime)| mtime);
+        Ok(FileState::for_gitsubmodule(head_mtime))
+    }
+
     fn write_conflict(
         &self,
         disk_path: &Path,
@@ -1334,7 +1514,11 @@ impl TreeState {
         Ok(())
     }
 
-    pub fn check_out(&mut self, new_tree: &MergedTree) -> Result<CheckoutStats, CheckoutError> {
+    pub fn check_out(
+        &mut self,
+        new_tree: &MergedTree,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
                 source: Box::new(err),
@@ -1342,7 +1526,12 @@ impl TreeState {
             other => CheckoutError::InternalBackendError(other),
         })?;
         let stats = self
-            .update(&old_tree, new_tree, self.sparse_matcher().as_ref())
+            .update(
+                &old_tree,
+                new_tree,
+                self.sparse_matcher().as_ref(),
+                options.conflict_marker_style,
+            )
             .block_on()?;
         self.tree_id = new_tree.id();
         Ok(stats)
@@ -1351,6 +1540,7 @@ impl TreeState {
     pub fn set_sparse_patterns(
         &mut self,
         sparse_patterns: Vec<RepoPathBuf>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
@@ -1363,9 +1553,21 @@ impl TreeState {
         let added_matcher = DifferenceMatcher::new(&new_matcher, &old_matcher);
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
         let empty_tree = MergedTree::resolved(Tree::empty(self.store.clone(), RepoPathBuf::root()));
-        let added_stats = self.update(&empty_tree, &tree, &added_matcher).block_on()?;
+        let added_stats = self
+            .update(
+                &empty_tree,
+                &tree,
+                &added_matcher,
+                options.conflict_marker_style,
+            )
+            .block_on()?;
         let removed_stats = self
-            .update(&tree, &empty_tree, &removed_matcher)
+            .update(
+                &tree,
+                &empty_tree,
+                &removed_matcher,
+                options.conflict_marker_style,
+            )
             .block_on()?;
         self.sparse_patterns = sparse_patterns;
         assert_eq!(added_stats.updated_files, 0);
@@ -1386,6 +1588,7 @@ impl TreeState {
         old_tree: &MergedTree,
         new_tree: &MergedTree,
         matcher: &dyn Matcher,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: maybe it's better not include the skipped counts in the "intended"
         // counts
@@ -1422,8 +1625,14 @@ impl TreeState {
             }
             let disk_path = path.to_fs_path(&self.working_copy_path);
 
+            let is_submodule = matches!(after, MaterializedTreeValue::GitSubmodule(_));
             if present_before {
-                fs::remove_file(&disk_path).ok();
+                // An empty directory may be left by an uninitialized submodule
+                fs::remove_file(&disk_path)
+                    .or_else(|_| fs::remove_dir(&disk_path))
+                    .ok();
+            } else if is_submodule && disk_path.is_dir() {
+                // The submodule working copy may have been populated already
             } else if disk_path.exists() {
                 changed_file_states.push((path, FileState::placeholder()));
                 stats.skipped_files += 1;
@@ -1462,10 +1671,7 @@ impl TreeState {
                         self.write_file(&disk_path, &mut target.as_bytes(), false)?
                     }
                 }
-                MaterializedTreeValue::GitSubmodule(_) => {
-                    eprintln!("ignoring git submodule at {path:?}");
-                    FileState::for_gitsubmodule()
-                }
+                MaterializedTreeValue::GitSubmodule(_) => self.write_submodule_dir(&disk_path)?,
                 MaterializedTreeValue::Tree(_) => {
                     panic!("unexpected tree entry in diff at {path:?}");
                 }
@@ -1475,7 +1681,7 @@ impl TreeState {
                     executable,
                 } => {
                     let mut data = vec![];
-                    materialize_merge_result(&contents, &mut data)
+                    materialize_merge_result(&contents, conflict_marker_style, &mut data)
                         .expect("Failed to materialize conflict to in-memory buffer");
                     self.write_conflict(&disk_path, data, executable)?
                 }
@@ -1521,10 +1727,7 @@ impl TreeState {
                         TreeValue::Conflict(_id) => {
                             panic!("unexpected conflict entry in diff at {path:?}");
                         }
-                        TreeValue::GitSubmodule(_id) => {
-                            eprintln!("ignoring git submodule at {path:?}");
-                            FileType::GitSubmodule
-                        }
+                        TreeValue::GitSubmodule(_id) => FileType::GitSubmodule,
                         TreeValue::Tree(_id) => {
                             panic!("unexpected tree entry in diff at {path:?}");
                         }
@@ -1557,6 +1760,55 @@ impl TreeState {
     }
 }
 
+/// Reads the id of the commit checked out in the submodule working copy at
+/// `dir`, and the last modification time of the files the id was read from.
+/// Returns `None` if the submodule isn't populated.
+fn read_submodule_head(dir: &Path) -> Option<(CommitId, MillisSinceEpoch)> {
+    let dot_git = dir.join(".git");
+    let git_dir = if dot_git.is_dir() {
+        dot_git
+    } else {
+        // Submodule working copies usually point to the repository by a
+        // "gitdir: <path>" file.
+        let content = fs::read_to_string(&dot_git).ok()?;
+        dir.join(content.trim_end().strip_prefix("gitdir: ")?)
+    };
+    let head_path = git_dir.join("HEAD");
+    let head = fs::read_to_string(&head_path).ok()?;
+    let head_mtime = mtime_from_metadata(&head_path.metadata().ok()?);
+    let head = head.trim_end();
+    match head.strip_prefix("ref: ") {
+        Some(ref_name) => {
+            let (hex, ref_mtime) = read_git_ref(&git_dir, ref_name)?;
+            let id = CommitId::try_from_hex(&hex).ok()?;
+            Some((id, head_mtime.max(ref_mtime)))
+        }
+        None => Some((CommitId::try_from_hex(head).ok()?, head_mtime)),
+    }
+}
+
+fn read_git_ref(git_dir: &Path, ref_name: &str) -> Option<(String, MillisSinceEpoch)> {
+    // Linked worktrees share refs with the main repository.
+    let common_dir = match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(path) => git_dir.join(path.trim_end()),
+        Err(_) => git_dir.to_owned(),
+    };
+    for dir in [git_dir, &common_dir] {
+        let ref_path = dir.join(ref_name);
+        if let Ok(content) = fs::read_to_string(&ref_path) {
+            let mtime = mtime_from_metadata(&ref_path.metadata().ok()?);
+            return Some((content.trim_end().to_owned(), mtime));
+        }
+    }
+    let packed_refs_path = common_dir.join("packed-refs");
+    let packed_refs = fs::read_to_string(&packed_refs_path).ok()?;
+    let mtime = mtime_from_metadata(&packed_refs_path.metadata().ok()?);
+    packed_refs.lines().find_map(|line| {
+        let (hex, name) = line.split_once(' ')?;
+        (name == ref_name).then(|| (hex.to_owned(), mtime))
+    })
+}
+
 fn checkout_error_for_stat_error(err: std::io::Error, path: &Path) -> CheckoutError {
     CheckoutError::Other {
         message: format!("Failed to stat file {}", path.display()),
@@ -1626,6 +1878,7 @@ impl WorkingCopy for LocalWorkingCopy {
             old_operation_id,
             old_tree_id,
             tree_state_dirty: false,
+            copies: vec![],
         }))
     }
 }
@@ -1768,6 +2021,24 @@ impl LocalWorkingCopy {
             })
     }
 
+    /// Returns a handle to the inotify daemon watching this working copy.
+    #[cfg(target_os = "linux")]
+    pub fn inotify_fsmonitor(&self) -> inotify::Fsmonitor {
+        inotify::Fsmonitor::new(&self.working_copy_path, &self.state_path.join("inotify"))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn query_inotify(
+        &self,
+    ) -> Result<(Option<inotify::Clock>, Option<Vec<PathBuf>>), WorkingCopyStateError> {
+        self.tree_state()?
+            .query_inotify()
+            .map_err(|err| WorkingCopyStateError {
+                message: "Failed to query the inotify daemon".to_string(),
+                err: err.into(),
+            })
+    }
+
     #[cfg(feature = "watchman")]
     pub fn is_watchman_trigger_registered(
         &self,
@@ -1825,6 +2096,8 @@ pub struct LockedLocalWorkingCopy {
     old_operation_id: OperationId,
     old_tree_id: MergedTreeId,
     tree_state_dirty: bool,
+    /// Copies detected by the snapshots, relative to `old_tree_id`
+    copies: Vec<CommitCopy>,
 }
 
 impl LockedWorkingCopy for LockedLocalWorkingCopy {
@@ -1853,10 +2126,23 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
                 err: err.into(),
             })?;
         self.tree_state_dirty |= tree_state.snapshot(options)?;
+        let file_states = tree_state.file_states();
+        self.copies = copies::compose_copies(&self.copies, tree_state.snapshot_copies())
+            .into_iter()
+            .filter(|copy| file_states.contains_path(&copy.target))
+            .collect();
         Ok(tree_state.current_tree_id().clone())
     }
 
-    fn check_out(&mut self, commit: &Commit) -> Result<CheckoutStats, CheckoutError> {
+    fn snapshot_copies(&self) -> Vec<CommitCopy> {
+        self.copies.clone()
+    }
+
+    fn check_out(
+        &mut self,
+        commit: &Commit,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with the new TreeId so we can
         // continue an interrupted update if we find such a file.
         let new_tree = commit.tree()?;
@@ -1867,8 +2153,9 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
                 message: "Failed to load the working copy state".to_string(),
                 err: err.into(),
             })?
-            .check_out(&new_tree)?;
+            .check_out(&new_tree, options)?;
         self.tree_state_dirty = true;
+        self.copies.clear();
         Ok(stats)
     }
 
@@ -1883,6 +2170,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
             .reset(&new_tree)
             .block_on()?;
         self.tree_state_dirty = true;
+        self.copies.clear();
         Ok(())
     }
 
@@ -1897,6 +2185,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
             .recover(&new_tree)
             .block_on()?;
         self.tree_state_dirty = true;
+        self.copies.clear();
         Ok(())
     }
 
@@ -1907,6 +2196,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
     fn set_sparse_patterns(
         &mut self,
         new_sparse_patterns: Vec<RepoPathBuf>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with new sparse patterns so we can
         // continue an interrupted update if we find such a file.
@@ -1917,7 +2207,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
                 message: "Failed to load the working copy state".to_string(),
                 err: err.into(),
             })?
-            .set_sparse_patterns(new_sparse_patterns)?;
+            .set_sparse_patterns(new_sparse_patterns, options)?;
         self.tree_state_dirty = true;
         Ok(stats)
     }
@@ -18,7 +18,10 @@ use std::borrow::Borrow;
 use std::cmp::max;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::io::Read as _;
 use std::iter;
 use std::iter::zip;
 use std::pin::Pin;
@@ -38,6 +41,7 @@ use itertools::Itertools;
 use pollster::FutureExt;
 
 use crate::backend;
+use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::MergedTreeId;
 use crate::backend::TreeId;
@@ -325,6 +329,30 @@ impl MergedTree {
         self.merge_no_resolve(base, other).resolve()
     }
 
+    /// Like `merge()`, but carries changes across files renamed on either
+    /// side.
+    ///
+    /// The `copy_records` are copies from `base` to either side. A copy is a
+    /// rename on a side if the source is deleted and the target is added there.
+    /// Changes to the source on the other side are then merged into the target
+    /// instead.
+    pub fn merge_with_copies(
+        &self,
+        base: &MergedTree,
+        other: &MergedTree,
+        copy_records: &CopyRecords,
+    ) -> BackendResult<MergedTree> {
+        let self_renames = find_renames(base, self, other, copy_records)?;
+        let other_renames = find_renames(base, other, self, copy_records)?;
+        if self_renames.is_empty() && other_renames.is_empty() {
+            return self.merge(base, other);
+        }
+        let base = move_paths(base, self_renames.iter().chain(&other_renames))?;
+        let new_self = move_paths(self, &other_renames)?;
+        let new_other = move_paths(other, &self_renames)?;
+        new_self.merge(&base, &new_other)
+    }
+
     /// Merges this tree with `other`, using `base` as base, without attempting
     /// to resolve file conflicts.
     pub fn merge_no_resolve(&self, base: &MergedTree, other: &MergedTree) -> MergedTree {
@@ -339,6 +367,109 @@ impl MergedTree {
     }
 }
 
+/// Finds the files renamed from `base` to `side` whose source still exists on
+/// the `other` side, following chains of copies. Files renamed on both sides
+/// are left to the regular merge.
+fn find_renames(
+    base: &MergedTree,
+    side: &MergedTree,
+    other: &MergedTree,
+    copy_records: &CopyRecords,
+) -> BackendResult<Vec<(RepoPathBuf, RepoPathBuf)>> {
+    // Paths in `base` the copy targets were originally copied from
+    let mut origins: HashMap<&RepoPathBuf, &RepoPathBuf> = HashMap::new();
+    let mut renames: Vec<(RepoPathBuf, RepoPathBuf)> = vec![];
+    let mut seen_renames = HashSet::new();
+    for record in copy_records.iter() {
+        let target = &record.target;
+        // Follow the chain of copies back to a path that exists in `base`.
+        // Paths on the chain get the same origin, so each one is only
+        // followed once.
+        let mut chain = vec![];
+        let mut visited = HashSet::new();
+        let mut source = &record.source;
+        let source = loop {
+            if let Some(&origin) = origins.get(source) {
+                break origin;
+            }
+            if !base.path_value(source)?.is_absent() || !visited.insert(source) {
+                break source;
+            }
+            match copy_records.for_target(source) {
+                Some(record) => {
+                    chain.push(source);
+                    source = &record.source;
+                }
+                None => break source,
+            }
+        };
+        for path in chain {
+            origins.insert(path, source);
+        }
+        let is_rename = !base.path_value(source)?.is_absent()
+            && base.path_value(target)?.is_absent()
+            && side.path_value(source)?.is_absent()
+            && !side.path_value(target)?.is_absent()
+            && !other.path_value(source)?.is_absent()
+            && other.path_value(target)?.is_absent();
+        if is_rename && seen_renames.insert((source, target)) {
+            renames.push((source.clone(), target.clone()));
+        }
+    }
+    // A file copied to multiple targets can't be followed unambiguously.
+    let sources = renames.iter().map(|(source, _)| source).counts();
+    let ambiguous: HashSet<RepoPathBuf> = sources
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(source, _)| source.clone())
+        .collect();
+    renames.retain(|(source, _)| !ambiguous.contains(source));
+    Ok(renames)
+}
+
+/// Moves the files at the sources of `renames` to their targets.
+fn move_paths<'a>(
+    tree: &MergedTree,
+    renames: impl IntoIterator<Item = &'a (RepoPathBuf, RepoPathBuf)>,
+) -> BackendResult<MergedTree> {
+    let store = tree.store();
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for (source, target) in renames {
+        let value = tree.path_value(source)?;
+        if value.is_absent() || !tree.path_value(target)?.is_absent() {
+            continue;
+        }
+        // Backends are allowed to store file contents per path, so the contents
+        // are written again at the target path.
+        let value = value.try_map(|term| -> BackendResult<_> {
+            match term {
+                Some(TreeValue::File { id, executable }) => {
+                    let mut contents = vec![];
+                    store
+                        .read_file(source, id)?
+                        .read_to_end(&mut contents)
+                        .map_err(|err| BackendError::ReadFile {
+                            path: source.clone(),
+                            id: id.clone(),
+                            source: err.into(),
+                        })?;
+                    let id = store
+                        .write_file(target, &mut contents.as_slice())
+                        .block_on()?;
+                    Ok(Some(TreeValue::File {
+                        id,
+                        executable: *executable,
+                    }))
+                }
+                term => Ok(term.clone()),
+            }
+        })?;
+        tree_builder.set_or_remove(target.clone(), value);
+        tree_builder.set_or_remove(source.clone(), Merge::absent());
+    }
+    store.get_root_tree(&tree_builder.write_tree(store)?)
+}
+
 /// A single entry in a tree diff.
 pub struct TreeDiffEntry {
     /// The path.
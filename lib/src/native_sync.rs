@@ -0,0 +1,664 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exchanges commits and bookmarks with other jj repos without going through
+//! Git. Objects are copied as is, so change ids, predecessors and conflicts are
+//! preserved. Operations aren't exchanged. Only the view of the head operation
+//! of the remote repo is read to look up its bookmarks.
+
+#![allow(missing_docs)]
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::io::Read as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::backend;
+use crate::backend::BackendError;
+use crate::backend::CommitId;
+use crate::backend::ConflictId;
+use crate::backend::FileId;
+use crate::backend::SymlinkId;
+use crate::backend::TreeId;
+use crate::backend::TreeValue;
+use crate::dag_walk;
+use crate::merge::MergedTreeValue;
+use crate::object_id::ObjectId;
+use crate::op_store::RefTarget;
+use crate::op_store::RemoteRef;
+use crate::op_store::RemoteRefState;
+use crate::op_store::View;
+use crate::refs::BranchPushUpdate;
+use crate::repo::MutableRepo;
+use crate::repo::ReadonlyRepo;
+use crate::repo::Repo as _;
+use crate::repo::RepoLoader;
+use crate::repo::StoreFactories;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::settings::UserSettings;
+use crate::store::Store;
+
+#[derive(Debug, Error)]
+pub enum NativeSyncError {
+    #[error("The remote repo uses the {remote} backend, but this repo uses the {local} backend")]
+    IncompatibleBackend { local: String, remote: String },
+    #[error("Copied {object_type} {hash} was stored as {new_hash}")]
+    ObjectIdMismatch {
+        object_type: String,
+        hash: String,
+        new_hash: String,
+    },
+    #[error("The remote repo returned {actual} results for {expected} objects")]
+    ResultCountMismatch { expected: usize, actual: usize },
+    #[error("Bookmark {0} on the remote has been moved since it was last fetched")]
+    RemoteBookmarkMoved(String),
+    #[error("Failed to access the remote repo")]
+    Transport(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Id of an object which can be exchanged with a remote repo. Trees and the
+/// objects in them are identified along with their path, as the backends may
+/// use it to look them up.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ObjectRef {
+    Commit(CommitId),
+    Tree(RepoPathBuf, TreeId),
+    File(RepoPathBuf, FileId),
+    Symlink(RepoPathBuf, SymlinkId),
+    Conflict(RepoPathBuf, ConflictId),
+}
+
+/// Reads and writes the objects of a repo by id.
+///
+/// Written objects must get the same ids as in the repo they were read from,
+/// so both sides need to use the same backend.
+pub trait ObjectAccess {
+    /// Returns whether each of the `objects` exists.
+    fn has_objects(&self, objects: &[ObjectRef]) -> Result<Vec<bool>, NativeSyncError>;
+
+    fn read_commit(&self, id: &CommitId) -> Result<backend::Commit, NativeSyncError>;
+
+    /// Writes the commit, keeping its signature if any.
+    fn write_commit(&self, commit: backend::Commit) -> Result<CommitId, NativeSyncError>;
+
+    fn read_tree(&self, path: &RepoPath, id: &TreeId) -> Result<backend::Tree, NativeSyncError>;
+
+    fn write_tree(&self, path: &RepoPath, tree: backend::Tree) -> Result<TreeId, NativeSyncError>;
+
+    fn read_file(&self, path: &RepoPath, id: &FileId) -> Result<Vec<u8>, NativeSyncError>;
+
+    fn write_file(&self, path: &RepoPath, contents: &[u8]) -> Result<FileId, NativeSyncError>;
+
+    fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> Result<String, NativeSyncError>;
+
+    fn write_symlink(&self, path: &RepoPath, target: &str) -> Result<SymlinkId, NativeSyncError>;
+
+    fn read_conflict(
+        &self,
+        path: &RepoPath,
+        id: &ConflictId,
+    ) -> Result<MergedTreeValue, NativeSyncError>;
+
+    fn write_conflict(
+        &self,
+        path: &RepoPath,
+        conflict: &MergedTreeValue,
+    ) -> Result<ConflictId, NativeSyncError>;
+}
+
+/// Connection to a remote repo.
+pub trait Transport: ObjectAccess {
+    /// Returns the name of the backend of the remote repo.
+    fn backend_name(&self) -> Result<String, NativeSyncError>;
+
+    /// Returns the view of the operation the remote repo is at, which the
+    /// bookmarks of the remote repo are read from.
+    fn head_view(&self) -> Result<View, NativeSyncError>;
+
+    /// Returns whether the remote repo has each of the commits `ids` and
+    /// their ancestors.
+    fn has_commits(&self, ids: &[CommitId]) -> Result<Vec<bool>, NativeSyncError>;
+
+    /// Makes the `heads` visible, and updates the bookmarks of the remote repo
+    /// in a new operation. Fails without updating anything if a bookmark
+    /// doesn't point to the expected old target.
+    fn update_bookmarks(
+        &self,
+        heads: &[CommitId],
+        updates: &[(String, BranchPushUpdate)],
+    ) -> Result<(), NativeSyncError>;
+}
+
+impl ObjectAccess for Arc<Store> {
+    fn has_objects(&self, objects: &[ObjectRef]) -> Result<Vec<bool>, NativeSyncError> {
+        objects
+            .iter()
+            .map(|object| {
+                let result = match object {
+                    ObjectRef::Commit(id) => self.get_commit(id).map(|_| ()),
+                    ObjectRef::Tree(path, id) => self.get_tree(path, id).map(|_| ()),
+                    ObjectRef::File(path, id) => Store::read_file(self, path, id).map(|_| ()),
+                    ObjectRef::Symlink(path, id) => Store::read_symlink(self, path, id).map(|_| ()),
+                    ObjectRef::Conflict(path, id) => {
+                        Store::read_conflict(self, path, id).map(|_| ())
+                    }
+                };
+                match result {
+                    Ok(()) => Ok(true),
+                    Err(BackendError::ObjectNotFound { .. }) => Ok(false),
+                    Err(err) => Err(err.into()),
+                }
+            })
+            .collect()
+    }
+
+    fn read_commit(&self, id: &CommitId) -> Result<backend::Commit, NativeSyncError> {
+        Ok(self.get_commit(id)?.store_commit().clone())
+    }
+
+    fn write_commit(&self, mut commit: backend::Commit) -> Result<CommitId, NativeSyncError> {
+        let new_commit = if let Some(secure_sig) = commit.secure_sig.take() {
+            // Reuse the signature so the commit id stays the same.
+            let mut sign = |_: &[u8]| Ok(secure_sig.sig.clone());
+            Store::write_commit(self, commit, Some(&mut sign)).block_on()?
+        } else {
+            Store::write_commit(self, commit, None).block_on()?
+        };
+        Ok(new_commit.id().clone())
+    }
+
+    fn read_tree(&self, path: &RepoPath, id: &TreeId) -> Result<backend::Tree, NativeSyncError> {
+        Ok(self.get_tree(path, id)?.data().clone())
+    }
+
+    fn write_tree(&self, path: &RepoPath, tree: backend::Tree) -> Result<TreeId, NativeSyncError> {
+        Ok(Store::write_tree(self, path, tree).block_on()?.id().clone())
+    }
+
+    fn read_file(&self, path: &RepoPath, id: &FileId) -> Result<Vec<u8>, NativeSyncError> {
+        let mut contents = vec![];
+        Store::read_file(self, path, id)?
+            .read_to_end(&mut contents)
+            .map_err(|err| BackendError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: Box::new(err),
+            })?;
+        Ok(contents)
+    }
+
+    fn write_file(&self, path: &RepoPath, mut contents: &[u8]) -> Result<FileId, NativeSyncError> {
+        Ok(Store::write_file(self, path, &mut contents).block_on()?)
+    }
+
+    fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> Result<String, NativeSyncError> {
+        Ok(Store::read_symlink(self, path, id)?)
+    }
+
+    fn write_symlink(&self, path: &RepoPath, target: &str) -> Result<SymlinkId, NativeSyncError> {
+        Ok(Store::write_symlink(self, path, target).block_on()?)
+    }
+
+    fn read_conflict(
+        &self,
+        path: &RepoPath,
+        id: &ConflictId,
+    ) -> Result<MergedTreeValue, NativeSyncError> {
+        Ok(Store::read_conflict(self, path, id)?)
+    }
+
+    fn write_conflict(
+        &self,
+        path: &RepoPath,
+        conflict: &MergedTreeValue,
+    ) -> Result<ConflictId, NativeSyncError> {
+        Ok(Store::write_conflict(self, path, conflict)?)
+    }
+}
+
+/// Transport to a repo in the local file system.
+pub struct LocalRepoTransport {
+    settings: UserSettings,
+    repo: Arc<ReadonlyRepo>,
+}
+
+impl LocalRepoTransport {
+    /// Loads the repo at `repo_path` (the `.jj/repo` directory) at its head
+    /// operation.
+    pub fn load(
+        settings: &UserSettings,
+        repo_path: &Path,
+        store_factories: &StoreFactories,
+    ) -> Result<Self, NativeSyncError> {
+        let repo = RepoLoader::init_from_file_system(settings, repo_path, store_factories)
+            .map_err(|err| NativeSyncError::Transport(Box::new(err)))?
+            .load_at_head(settings)
+            .map_err(|err| NativeSyncError::Transport(Box::new(err)))?;
+        Ok(LocalRepoTransport {
+            settings: settings.clone(),
+            repo,
+        })
+    }
+}
+
+impl ObjectAccess for LocalRepoTransport {
+    fn has_objects(&self, objects: &[ObjectRef]) -> Result<Vec<bool>, NativeSyncError> {
+        self.repo.store().has_objects(objects)
+    }
+
+    fn read_commit(&self, id: &CommitId) -> Result<backend::Commit, NativeSyncError> {
+        self.repo.store().read_commit(id)
+    }
+
+    fn write_commit(&self, commit: backend::Commit) -> Result<CommitId, NativeSyncError> {
+        ObjectAccess::write_commit(self.repo.store(), commit)
+    }
+
+    fn read_tree(&self, path: &RepoPath, id: &TreeId) -> Result<backend::Tree, NativeSyncError> {
+        self.repo.store().read_tree(path, id)
+    }
+
+    fn write_tree(&self, path: &RepoPath, tree: backend::Tree) -> Result<TreeId, NativeSyncError> {
+        ObjectAccess::write_tree(self.repo.store(), path, tree)
+    }
+
+    fn read_file(&self, path: &RepoPath, id: &FileId) -> Result<Vec<u8>, NativeSyncError> {
+        ObjectAccess::read_file(self.repo.store(), path, id)
+    }
+
+    fn write_file(&self, path: &RepoPath, contents: &[u8]) -> Result<FileId, NativeSyncError> {
+        ObjectAccess::write_file(self.repo.store(), path, contents)
+    }
+
+    fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> Result<String, NativeSyncError> {
+        ObjectAccess::read_symlink(self.repo.store(), path, id)
+    }
+
+    fn write_symlink(&self, path: &RepoPath, target: &str) -> Result<SymlinkId, NativeSyncError> {
+        ObjectAccess::write_symlink(self.repo.store(), path, target)
+    }
+
+    fn read_conflict(
+        &self,
+        path: &RepoPath,
+        id: &ConflictId,
+    ) -> Result<MergedTreeValue, NativeSyncError> {
+        ObjectAccess::read_conflict(self.repo.store(), path, id)
+    }
+
+    fn write_conflict(
+        &self,
+        path: &RepoPath,
+        conflict: &MergedTreeValue,
+    ) -> Result<ConflictId, NativeSyncError> {
+        ObjectAccess::write_conflict(self.repo.store(), path, conflict)
+    }
+}
+
+impl Transport for LocalRepoTransport {
+    fn backend_name(&self) -> Result<String, NativeSyncError> {
+        Ok(self.repo.store().backend_name().to_owned())
+    }
+
+    fn head_view(&self) -> Result<View, NativeSyncError> {
+        Ok(self.repo.view().store_view().clone())
+    }
+
+    fn has_commits(&self, ids: &[CommitId]) -> Result<Vec<bool>, NativeSyncError> {
+        let index = self.repo.index();
+        Ok(ids.iter().map(|id| index.has_id(id)).collect())
+    }
+
+    fn update_bookmarks(
+        &self,
+        heads: &[CommitId],
+        updates: &[(String, BranchPushUpdate)],
+    ) -> Result<(), NativeSyncError> {
+        let mut tx = self.repo.start_transaction(&self.settings);
+        let head_commits: Vec<_> = heads
+            .iter()
+            .map(|id| self.repo.store().get_commit(id))
+            .try_collect()?;
+        tx.repo_mut().add_heads(&head_commits)?;
+        for (name, update) in updates {
+            let current_target = tx.repo().get_local_bookmark(name);
+            if current_target != RefTarget::resolved(update.old_target.clone()) {
+                return Err(NativeSyncError::RemoteBookmarkMoved(name.clone()));
+            }
+            tx.repo_mut()
+                .set_local_bookmark_target(name, RefTarget::resolved(update.new_target.clone()));
+        }
+        tx.commit(format!(
+            "push bookmarks {}",
+            updates.iter().map(|(name, _)| name).join(", ")
+        ));
+        Ok(())
+    }
+}
+
+/// Changes made by `fetch()`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FetchStats {
+    /// Number of commits copied from the remote repo.
+    pub copied_commit_count: usize,
+    /// Names of the remote bookmarks which were added, moved or deleted.
+    pub changed_bookmarks: Vec<String>,
+}
+
+/// Copies the commits pointed to by the bookmarks of the remote repo, and
+/// records the bookmarks as remote bookmarks of `remote_name`. Tracking local
+/// bookmarks are merged with the remote bookmarks.
+pub fn fetch(
+    mut_repo: &mut MutableRepo,
+    transport: &dyn Transport,
+    remote_name: &str,
+) -> Result<FetchStats, NativeSyncError> {
+    let store = mut_repo.store().clone();
+    check_compatible(&store, transport)?;
+    let remote_view = transport.head_view()?;
+    let remote_bookmarks: BTreeMap<String, RefTarget> = remote_view
+        .local_bookmarks
+        .into_iter()
+        .filter(|(_, target)| target.is_present())
+        .collect();
+    // All sides of conflicted bookmarks are copied, since the remote
+    // bookmarks refer to them.
+    let heads = remote_bookmarks
+        .values()
+        .flat_map(|target| target.as_merge().iter().flatten())
+        .unique()
+        .cloned()
+        .collect_vec();
+    let index = mut_repo.index();
+    let copied_commit_count = copy_commits(transport, &store, &heads, |ids| {
+        Ok(ids.iter().map(|id| index.has_id(id)).collect())
+    })?;
+    let head_commits: Vec<_> = heads.iter().map(|id| store.get_commit(id)).try_collect()?;
+    mut_repo.add_heads(&head_commits)?;
+
+    let old_names = mut_repo
+        .view()
+        .remote_bookmarks(remote_name)
+        .map(|(name, _)| name.to_owned())
+        .collect_vec();
+    let mut changed_bookmarks = vec![];
+    let names = itertools::chain(old_names, remote_bookmarks.keys().cloned())
+        .sorted()
+        .dedup();
+    for name in names {
+        let old_remote_ref = mut_repo.get_remote_bookmark(&name, remote_name);
+        let new_target = remote_bookmarks
+            .get(&name)
+            .cloned()
+            .unwrap_or_else(RefTarget::absent);
+        if old_remote_ref.target == new_target {
+            continue;
+        }
+        let new_remote_ref = RemoteRef {
+            target: new_target,
+            state: if old_remote_ref.is_present() {
+                old_remote_ref.state
+            } else {
+                RemoteRefState::New
+            },
+        };
+        if new_remote_ref.is_tracking() {
+            mut_repo.merge_local_bookmark(
+                &name,
+                old_remote_ref.tracking_target(),
+                &new_remote_ref.target,
+            );
+        }
+        mut_repo.set_remote_bookmark(&name, remote_name, new_remote_ref);
+        changed_bookmarks.push(name);
+    }
+    Ok(FetchStats {
+        copied_commit_count,
+        changed_bookmarks,
+    })
+}
+
+/// Copies the commits missing in the remote repo, and updates the bookmarks of
+/// the remote repo. The remote bookmarks of `remote_name` are updated
+/// accordingly, and deleted if the bookmarks were deleted.
+pub fn push_bookmarks(
+    mut_repo: &mut MutableRepo,
+    transport: &dyn Transport,
+    remote_name: &str,
+    updates: &[(String, BranchPushUpdate)],
+) -> Result<(), NativeSyncError> {
+    let store = mut_repo.store().clone();
+    check_compatible(&store, transport)?;
+    let heads = updates
+        .iter()
+        .filter_map(|(_, update)| update.new_target.clone())
+        .collect_vec();
+    copy_commits(&store, transport, &heads, |ids| transport.has_commits(ids))?;
+    transport.update_bookmarks(&heads, updates)?;
+    for (name, update) in updates {
+        let new_remote_ref = match &update.new_target {
+            Some(new_target) => RemoteRef {
+                target: RefTarget::normal(new_target.clone()),
+                state: RemoteRefState::Tracking,
+            },
+            None => RemoteRef::absent(),
+        };
+        mut_repo.set_remote_bookmark(name, remote_name, new_remote_ref);
+    }
+    Ok(())
+}
+
+fn check_compatible(local: &Store, transport: &dyn Transport) -> Result<(), NativeSyncError> {
+    let remote_backend_name = transport.backend_name()?;
+    if local.backend_name() != remote_backend_name {
+        return Err(NativeSyncError::IncompatibleBackend {
+            local: local.backend_name().to_owned(),
+            remote: remote_backend_name,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that there's one of the `results` for each of the `expected` number
+/// of objects.
+fn check_result_count<T>(results: Vec<T>, expected: usize) -> Result<Vec<T>, NativeSyncError> {
+    if results.len() != expected {
+        return Err(NativeSyncError::ResultCountMismatch {
+            expected,
+            actual: results.len(),
+        });
+    }
+    Ok(results)
+}
+
+fn check_copied_id<T: ObjectId + Eq>(id: &T, new_id: &T) -> Result<(), NativeSyncError> {
+    if id != new_id {
+        return Err(NativeSyncError::ObjectIdMismatch {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            new_hash: new_id.hex(),
+        });
+    }
+    Ok(())
+}
+
+/// Copies the `heads` and their ancestors which `has_commits()` returns false
+/// for from the `source` to the `dest`, parents first. Returns the number of
+/// copied commits.
+///
+/// Predecessors of the copied commits are copied too, so the evolution log
+/// is preserved.
+fn copy_commits<S, D>(
+    source: &S,
+    dest: &D,
+    heads: &[CommitId],
+    has_commits: impl Fn(&[CommitId]) -> Result<Vec<bool>, NativeSyncError>,
+) -> Result<usize, NativeSyncError>
+where
+    S: ObjectAccess + ?Sized,
+    D: ObjectAccess + ?Sized,
+{
+    let read_commits = |ids: Vec<CommitId>| -> Result<Vec<_>, NativeSyncError> {
+        ids.into_iter()
+            .map(|id| Ok((source.read_commit(&id)?, id)))
+            .collect()
+    };
+    let read_missing = |ids: &[CommitId]| -> Result<Vec<_>, NativeSyncError> {
+        let exists = check_result_count(has_commits(ids)?, ids.len())?;
+        let missing_ids = itertools::zip_eq(ids, exists)
+            .filter(|(_, exists)| !exists)
+            .map(|(id, _)| id.clone())
+            .collect_vec();
+        read_commits(missing_ids)
+    };
+    // Predecessors aren't necessarily indexed, so look them up in the store.
+    // Ones missing in the source are left dangling as before.
+    let read_missing_predecessors = |ids: &[CommitId]| -> Result<Vec<_>, NativeSyncError> {
+        let refs = ids.iter().cloned().map(ObjectRef::Commit).collect_vec();
+        let in_dest = check_result_count(dest.has_objects(&refs)?, refs.len())?;
+        let in_source = check_result_count(source.has_objects(&refs)?, refs.len())?;
+        let missing_ids = itertools::izip!(ids, in_dest, in_source)
+            .filter(|(_, in_dest, in_source)| !in_dest && *in_source)
+            .map(|(id, _, _)| id.clone())
+            .collect_vec();
+        read_commits(missing_ids)
+    };
+    let missing_commits = dag_walk::topo_order_reverse_ok(
+        read_missing(heads)?.into_iter().map(Ok),
+        |(_, id): &(backend::Commit, CommitId)| id.clone(),
+        |(commit, _): &(backend::Commit, CommitId)| {
+            let parents = read_missing(&commit.parents);
+            let predecessors = read_missing_predecessors(&commit.predecessors);
+            match (parents, predecessors) {
+                (Ok(parents), Ok(predecessors)) => itertools::chain(parents, predecessors)
+                    .map(Ok)
+                    .collect_vec(),
+                (Err(err), _) | (_, Err(err)) => vec![Err(err)],
+            }
+        },
+    )?;
+
+    let mut copier = ObjectCopier {
+        source,
+        dest,
+        copied_trees: HashSet::new(),
+    };
+    let copied_count = missing_commits.len();
+    for (commit, id) in missing_commits.into_iter().rev() {
+        for tree_id in commit.root_tree.to_merge().iter() {
+            copier.copy_tree(RepoPath::root(), tree_id)?;
+        }
+        let new_id = dest.write_commit(commit)?;
+        check_copied_id(&id, &new_id)?;
+    }
+    Ok(copied_count)
+}
+
+struct ObjectCopier<'a, S: ?Sized, D: ?Sized> {
+    source: &'a S,
+    dest: &'a D,
+    copied_trees: HashSet<TreeId>,
+}
+
+impl<S: ObjectAccess + ?Sized, D: ObjectAccess + ?Sized> ObjectCopier<'_, S, D> {
+    /// Copies the tree and the objects it refers to, unless the destination
+    /// already has the tree.
+    fn copy_tree(&mut self, path: &RepoPath, id: &TreeId) -> Result<(), NativeSyncError> {
+        if self.copied_trees.contains(id) {
+            return Ok(());
+        }
+        let [exists] = self.has_objects([ObjectRef::Tree(path.to_owned(), id.clone())])?;
+        if !exists {
+            let tree = self.source.read_tree(path, id)?;
+            self.copy_values(
+                tree.entries()
+                    .map(|entry| (path.join(entry.name()), entry.value().clone()))
+                    .collect(),
+            )?;
+            check_copied_id(id, &self.dest.write_tree(path, tree)?)?;
+        }
+        self.copied_trees.insert(id.clone());
+        Ok(())
+    }
+
+    /// Copies the values the destination doesn't have yet. The existence of
+    /// the values is checked at once.
+    fn copy_values(
+        &mut self,
+        values: Vec<(RepoPathBuf, TreeValue)>,
+    ) -> Result<(), NativeSyncError> {
+        let (refs, values): (Vec<_>, Vec<_>) = values
+            .into_iter()
+            .filter_map(|(path, value)| {
+                let object = match &value {
+                    TreeValue::File { id, .. } => ObjectRef::File(path.clone(), id.clone()),
+                    TreeValue::Symlink(id) => ObjectRef::Symlink(path.clone(), id.clone()),
+                    TreeValue::Tree(id) => ObjectRef::Tree(path.clone(), id.clone()),
+                    TreeValue::Conflict(id) => ObjectRef::Conflict(path.clone(), id.clone()),
+                    TreeValue::GitSubmodule(_) => return None,
+                };
+                Some((object, (path, value)))
+            })
+            .unzip();
+        let exists = check_result_count(self.dest.has_objects(&refs)?, refs.len())?;
+        for ((path, value), exists) in itertools::zip_eq(values, exists) {
+            match value {
+                TreeValue::Tree(id) => self.copy_tree(&path, &id)?,
+                _ if exists => {}
+                TreeValue::File { id, .. } => {
+                    let contents = self.source.read_file(&path, &id)?;
+                    check_copied_id(&id, &self.dest.write_file(&path, &contents)?)?;
+                }
+                TreeValue::Symlink(id) => {
+                    let target = self.source.read_symlink(&path, &id)?;
+                    check_copied_id(&id, &self.dest.write_symlink(&path, &target)?)?;
+                }
+                TreeValue::Conflict(id) => {
+                    let conflict = self.source.read_conflict(&path, &id)?;
+                    self.copy_values(
+                        conflict
+                            .iter()
+                            .flatten()
+                            .map(|value| (path.clone(), value.clone()))
+                            .collect(),
+                    )?;
+                    check_copied_id(&id, &self.dest.write_conflict(&path, &conflict)?)?;
+                }
+                TreeValue::GitSubmodule(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn has_objects<const N: usize>(
+        &self,
+        objects: [ObjectRef; N],
+    ) -> Result<[bool; N], NativeSyncError> {
+        let exists = self.dest.has_objects(&objects)?;
+        exists
+            .try_into()
+            .map_err(|exists: Vec<bool>| NativeSyncError::ResultCountMismatch {
+                expected: N,
+                actual: exists.len(),
+            })
+    }
+}
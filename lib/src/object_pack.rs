@@ -0,0 +1,468 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Packs of compressed objects for the local stores. A pack consists of a
+//! `<name>.pack` file, which is the concatenation of the zstd-compressed
+//! objects, and a `<name>.idx` file, which maps the object keys to their
+//! positions in the pack file. The keys are stored in sorted order so an
+//! object can be looked up by binary search. The name is the hash of the index
+//! file, so packs are never modified once written.
+//!
+//! An object key is a kind (e.g. commit or tree) and the object id. Loose
+//! objects stored by the backends take precedence over the packed objects.
+
+#![allow(missing_docs)]
+
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use blake2::Blake2b512;
+use blake2::Digest;
+use itertools::Itertools as _;
+use tempfile::NamedTempFile;
+
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+
+const PACK_MAGIC: &[u8; 8] = b"JJPACK01";
+const INDEX_MAGIC: &[u8; 8] = b"JJPIDX01";
+const PACK_EXTENSION: &str = "pack";
+const INDEX_EXTENSION: &str = "idx";
+
+/// Kind and id of a packed object.
+pub type ObjectKey = (u8, Vec<u8>);
+
+#[derive(Debug)]
+struct PackEntry {
+    kind: u8,
+    id: Vec<u8>,
+    offset: u64,
+    size: u64,
+}
+
+#[derive(Debug)]
+struct Pack {
+    name: String,
+    mtime: SystemTime,
+    // Sorted by (kind, id)
+    entries: Vec<PackEntry>,
+    file: Mutex<File>,
+}
+
+impl Pack {
+    /// Loads the index of the pack, and opens the pack file. Returns `None` if
+    /// the pack has been removed concurrently.
+    fn load(dir: &Path, name: &str) -> Result<Option<Self>, PathError> {
+        let index_path = dir.join(name).with_extension(INDEX_EXTENSION);
+        let pack_path = dir.join(name).with_extension(PACK_EXTENSION);
+        let (index_buf, mut file) = match (fs::read(&index_path), File::open(&pack_path)) {
+            (Ok(buf), Ok(file)) => (buf, file),
+            (Err(err), _) | (_, Err(err)) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(None);
+            }
+            (Err(err), _) => return Err(err).context(&index_path),
+            (_, Err(err)) => return Err(err).context(&pack_path),
+        };
+        let entries = parse_index(&index_buf).context(&index_path)?;
+        let mut magic = [0; PACK_MAGIC.len()];
+        file.read_exact(&mut magic).context(&pack_path)?;
+        if magic != *PACK_MAGIC {
+            return Err(invalid_data("unknown pack file format")).context(&pack_path);
+        }
+        let metadata = file.metadata().context(&pack_path)?;
+        let mtime = metadata.modified().context(&pack_path)?;
+        // Check the entries up front so a corrupt index can't make us read
+        // past the end of the pack file, or allocate a huge buffer.
+        let pack_len = metadata.len();
+        let in_bounds = |entry: &PackEntry| {
+            entry.offset >= PACK_MAGIC.len() as u64
+                && entry
+                    .offset
+                    .checked_add(entry.size)
+                    .is_some_and(|end| end <= pack_len)
+        };
+        if !entries.iter().all(in_bounds) {
+            return Err(invalid_data("pack index entry out of bounds")).context(&index_path);
+        }
+        Ok(Some(Pack {
+            name: name.to_owned(),
+            mtime,
+            entries,
+            file: Mutex::new(file),
+        }))
+    }
+
+    fn find(&self, kind: u8, id: &[u8]) -> Option<usize> {
+        self.entries
+            .binary_search_by(|entry| (entry.kind, entry.id.as_slice()).cmp(&(kind, id)))
+            .ok()
+    }
+
+    fn read(&self, entry: &PackEntry) -> io::Result<Vec<u8>> {
+        let size = usize::try_from(entry.size).map_err(|_| invalid_data("pack entry too large"))?;
+        let mut compressed = vec![0; size];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(entry.offset))?;
+            file.read_exact(&mut compressed)?;
+        }
+        zstd::decode_all(compressed.as_slice())
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn parse_index(buf: &[u8]) -> io::Result<Vec<PackEntry>> {
+    let header_size = INDEX_MAGIC.len() + 4 + 4;
+    if buf.len() < header_size {
+        return Err(invalid_data("truncated pack index"));
+    }
+    if buf[..INDEX_MAGIC.len()] != *INDEX_MAGIC {
+        return Err(invalid_data("unknown pack index format"));
+    }
+    let read_u32 = |pos: usize| u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap());
+    let id_length = read_u32(INDEX_MAGIC.len()) as usize;
+    let num_entries = read_u32(INDEX_MAGIC.len() + 4) as usize;
+    let rest = &buf[header_size..];
+    let entry_size = 1 + id_length + 8 + 8;
+    if num_entries.checked_mul(entry_size) != Some(rest.len()) {
+        return Err(invalid_data("pack index size mismatch"));
+    }
+    let entries = rest
+        .chunks_exact(entry_size)
+        .map(|chunk| {
+            let (id, tail) = chunk[1..].split_at(id_length);
+            let (offset, size) = tail.split_at(8);
+            PackEntry {
+                kind: chunk[0],
+                id: id.to_vec(),
+                offset: u64::from_le_bytes(offset.try_into().unwrap()),
+                size: u64::from_le_bytes(size.try_into().unwrap()),
+            }
+        })
+        .collect_vec();
+    if !entries
+        .iter()
+        .tuple_windows()
+        .all(|(a, b)| (a.kind, &a.id) < (b.kind, &b.id))
+    {
+        return Err(invalid_data("pack index entries are not sorted"));
+    }
+    Ok(entries)
+}
+
+/// Set of packs stored in a directory.
+///
+/// The packs are loaded lazily, and reloaded if an object can't be found, in
+/// case new packs were written by another process.
+#[derive(Debug)]
+pub struct PackSet {
+    dir: PathBuf,
+    packs: Mutex<Option<Vec<Arc<Pack>>>>,
+}
+
+impl PackSet {
+    /// Creates a set of packs stored in `dir`. The directory will be created
+    /// when the first pack is written.
+    pub fn new(dir: PathBuf) -> Self {
+        PackSet {
+            dir,
+            packs: Mutex::new(None),
+        }
+    }
+
+    /// Loads the packs which aren't loaded yet, and drops the removed ones.
+    fn reload(&self, loaded: &[Arc<Pack>]) -> Result<Vec<Arc<Pack>>, PathError> {
+        let dir_entries = match self.dir.read_dir() {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err).context(&self.dir),
+        };
+        let mut packs = vec![];
+        for entry in dir_entries {
+            let path = entry.context(&self.dir)?.path();
+            if path.extension() != Some(INDEX_EXTENSION.as_ref()) {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if let Some(pack) = loaded.iter().find(|pack| pack.name == name) {
+                packs.push(pack.clone());
+            } else if let Some(pack) = Pack::load(&self.dir, name)? {
+                packs.push(Arc::new(pack));
+            }
+        }
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(packs)
+    }
+
+    fn packs(&self) -> Result<Vec<Arc<Pack>>, PathError> {
+        let mut locked = self.packs.lock().unwrap();
+        if locked.is_none() {
+            *locked = Some(self.reload(&[])?);
+        }
+        Ok(locked.clone().unwrap())
+    }
+
+    fn find(&self, kind: u8, id: &[u8]) -> Result<Option<(Arc<Pack>, usize)>, PathError> {
+        let find_in = |packs: &[Arc<Pack>]| {
+            packs
+                .iter()
+                .find_map(|pack| Some((pack.clone(), pack.find(kind, id)?)))
+        };
+        let mut locked = self.packs.lock().unwrap();
+        if let Some(packs) = locked.as_deref() {
+            if let Some(found) = find_in(packs) {
+                return Ok(Some(found));
+            }
+        }
+        let packs = self.reload(locked.as_deref().unwrap_or_default())?;
+        let found = find_in(&packs);
+        *locked = Some(packs);
+        Ok(found)
+    }
+
+    /// Reads and decompresses the object. Returns `None` if no pack contains
+    /// the object.
+    pub fn read(&self, kind: u8, id: &[u8]) -> Result<Option<Vec<u8>>, PathError> {
+        let Some((pack, index)) = self.find(kind, id)? else {
+            return Ok(None);
+        };
+        let data = pack
+            .read(&pack.entries[index])
+            .context(self.dir.join(&pack.name).with_extension(PACK_EXTENSION))?;
+        Ok(Some(data))
+    }
+
+    /// Returns true if a pack contains the object.
+    pub fn contains(&self, kind: u8, id: &[u8]) -> Result<bool, PathError> {
+        Ok(self.find(kind, id)?.is_some())
+    }
+
+    /// Returns the ids of the packed objects of the given kind, in sorted
+    /// order.
+    pub fn ids(&self, kind: u8) -> Result<Vec<Vec<u8>>, PathError> {
+        let ids = self
+            .packs()?
+            .iter()
+            .flat_map(|pack| {
+                pack.entries
+                    .iter()
+                    .filter(|entry| entry.kind == kind)
+                    .map(|entry| entry.id.clone())
+                    .collect_vec()
+            })
+            .sorted()
+            .dedup()
+            .collect();
+        Ok(ids)
+    }
+
+    /// Writes a new pack containing the given (uncompressed) objects, and
+    /// returns its name. The ids of all objects must have the same length.
+    pub fn write_pack(
+        &self,
+        objects: impl IntoIterator<Item = (ObjectKey, Vec<u8>)>,
+    ) -> Result<String, PathError> {
+        fs::create_dir_all(&self.dir).context(&self.dir)?;
+        let objects = objects
+            .into_iter()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .dedup_by(|(a, _), (b, _)| a == b)
+            .collect_vec();
+        let id_length = objects.first().map_or(0, |((_, id), _)| id.len());
+
+        let mut pack_file = NamedTempFile::new_in(&self.dir).context(&self.dir)?;
+        let mut index_buf = INDEX_MAGIC.to_vec();
+        index_buf.extend(u32::try_from(id_length).unwrap().to_le_bytes());
+        index_buf.extend(u32::try_from(objects.len()).unwrap().to_le_bytes());
+        let mut offset = PACK_MAGIC.len() as u64;
+        let mut write_objects = || -> io::Result<()> {
+            pack_file.write_all(PACK_MAGIC)?;
+            for ((kind, id), data) in &objects {
+                assert_eq!(id.len(), id_length, "object ids must have the same length");
+                let compressed = zstd::encode_all(data.as_slice(), 0)?;
+                pack_file.write_all(&compressed)?;
+                index_buf.push(*kind);
+                index_buf.extend_from_slice(id);
+                index_buf.extend(offset.to_le_bytes());
+                index_buf.extend((compressed.len() as u64).to_le_bytes());
+                offset += compressed.len() as u64;
+            }
+            pack_file.as_file().sync_data()
+        };
+        write_objects().context(pack_file.path())?;
+
+        let name = hex::encode(Blake2b512::digest(&index_buf));
+        let pack_path = self.dir.join(&name).with_extension(PACK_EXTENSION);
+        let index_path = self.dir.join(&name).with_extension(INDEX_EXTENSION);
+        // The pack file must be in place before the index makes it visible.
+        persist_content_addressed_temp_file(pack_file, &pack_path).context(&pack_path)?;
+        let mut index_file = NamedTempFile::new_in(&self.dir).context(&self.dir)?;
+        index_file
+            .write_all(&index_buf)
+            .context(index_file.path())?;
+        persist_content_addressed_temp_file(index_file, &index_path).context(&index_path)?;
+        Ok(name)
+    }
+
+    /// Removes the pack. Readers which already loaded the pack can still read
+    /// objects from it on Unix.
+    fn remove_pack(&self, name: &str) -> Result<(), PathError> {
+        let index_path = self.dir.join(name).with_extension(INDEX_EXTENSION);
+        let pack_path = self.dir.join(name).with_extension(PACK_EXTENSION);
+        fs::remove_file(&index_path).context(&index_path)?;
+        fs::remove_file(&pack_path).context(&pack_path)?;
+        Ok(())
+    }
+}
+
+/// Directory of loose objects of a certain kind. The file names are the hex
+/// ids of the objects.
+pub struct LooseObjectDir<'a> {
+    pub kind: u8,
+    pub path: PathBuf,
+    /// Reads the uncompressed object data from a loose file.
+    pub read: &'a dyn Fn(&Path) -> io::Result<Vec<u8>>,
+}
+
+/// Removes the unreachable loose and packed objects, and optionally moves the
+/// reachable loose objects to a new pack.
+///
+/// Objects and packs modified after `keep_newer` are preserved. Since objects
+/// may be written concurrently, the reachable objects should be determined
+/// without considering the `keep_newer` parameter.
+pub fn gc_objects(
+    packs: &PackSet,
+    loose_dirs: &[LooseObjectDir],
+    reachable: &HashSet<ObjectKey>,
+    pack_objects: bool,
+    keep_newer: SystemTime,
+) -> Result<(), PathError> {
+    let mut packed_loose_paths = vec![];
+    let mut new_pack_objects = vec![];
+    for dir in loose_dirs {
+        for entry in dir.path.read_dir().context(&dir.path)? {
+            let entry = entry.context(&dir.path)?;
+            let path = entry.path();
+            let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| hex::decode(name).ok())
+            else {
+                tracing::trace!(?path, "skipping invalid file name");
+                continue;
+            };
+            let key = (dir.kind, id);
+            if reachable.contains(&key) {
+                if pack_objects {
+                    let data = (dir.read)(&path).context(&path)?;
+                    new_pack_objects.push((key, data));
+                    packed_loose_paths.push(path);
+                }
+                continue;
+            }
+            // Check timestamp, but there's still TOCTOU problem if an existing
+            // file is renewed.
+            let metadata = entry.metadata().context(&path)?;
+            let mtime = metadata.modified().expect("unsupported platform?");
+            if mtime > keep_newer {
+                tracing::trace!(?path, "not removing");
+            } else {
+                tracing::trace!(?path, "removing");
+                fs::remove_file(&path).context(&path)?;
+            }
+        }
+    }
+
+    let old_packs = packs.packs()?;
+    let mut removed_pack_names = vec![];
+    let mut new_packs = vec![];
+    for pack in &old_packs {
+        if pack.mtime > keep_newer {
+            continue;
+        }
+        let has_unreachable = pack
+            .entries
+            .iter()
+            .any(|entry| !reachable.contains(&(entry.kind, entry.id.clone())));
+        if !has_unreachable && !pack_objects {
+            continue;
+        }
+        let pack_path = packs.dir.join(&pack.name).with_extension(PACK_EXTENSION);
+        let mut objects = vec![];
+        for entry in &pack.entries {
+            let key = (entry.kind, entry.id.clone());
+            if reachable.contains(&key) {
+                let data = pack.read(entry).context(&pack_path)?;
+                objects.push((key, data));
+            }
+        }
+        removed_pack_names.push(pack.name.clone());
+        if pack_objects {
+            new_pack_objects.extend(objects);
+        } else if !objects.is_empty() {
+            new_packs.push(objects);
+        }
+    }
+    if pack_objects {
+        // Don't rewrite the only pack if it wouldn't change.
+        let is_unchanged = packed_loose_paths.is_empty()
+            && old_packs.len() == 1
+            && removed_pack_names.len() == 1
+            && new_pack_objects.len() == old_packs[0].entries.len();
+        if is_unchanged {
+            return Ok(());
+        }
+        if !new_pack_objects.is_empty() {
+            new_packs.push(new_pack_objects);
+        }
+    }
+
+    let mut new_pack_names = vec![];
+    for objects in new_packs {
+        new_pack_names.push(packs.write_pack(objects)?);
+    }
+    tracing::info!(
+        packed_loose_count = packed_loose_paths.len(),
+        removed_pack_count = removed_pack_names.len(),
+        new_pack_count = new_pack_names.len(),
+        "rewrote packs"
+    );
+    for path in packed_loose_paths {
+        fs::remove_file(&path).context(&path)?;
+    }
+    for name in removed_pack_names {
+        if !new_pack_names.contains(&name) {
+            packs.remove_pack(&name)?;
+        }
+    }
+    *packs.packs.lock().unwrap() = None;
+    Ok(())
+}
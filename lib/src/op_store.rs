@@ -280,6 +280,19 @@ pub struct View {
     // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
     // precise: the commit to which we most recently completed an update to).
     pub wc_commit_ids: HashMap<WorkspaceId, CommitId>,
+    /// The state of the bisection in progress, if any.
+    pub bisect: Option<BisectState>,
+}
+
+/// Commits marked while bisecting the history to find the first bad commit.
+#[derive(ContentHash, Clone, Debug, Default, Eq, PartialEq)]
+pub struct BisectState {
+    /// Commits known to be good.
+    pub good: Vec<CommitId>,
+    /// Commits known to be bad.
+    pub bad: Vec<CommitId>,
+    /// Commits that couldn't be tested.
+    pub skipped: Vec<CommitId>,
 }
 
 /// Represents the state of the remote repo.
@@ -0,0 +1,1144 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and applying of patches in the unified diff format, including the
+//! extended headers of `git diff` and the mailbox format of `git
+//! format-patch`.
+
+use std::io::Read as _;
+use std::sync::Arc;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::CommitId;
+use crate::backend::MergedTreeId;
+use crate::backend::Signature;
+use crate::backend::Timestamp;
+use crate::backend::TreeValue;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
+use crate::object_id::ObjectId as _;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
+
+/// A change to a set of files, with optional commit metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Patch {
+    /// The author from the `From:` and `Date:` headers of a mail, if any. The
+    /// current time is used if there's no `Date:` header.
+    pub author: Option<Signature>,
+    /// The description from the `Subject:` header and the body of a mail.
+    /// Empty if the patch isn't a mail.
+    pub description: String,
+    /// The changes to each file.
+    pub files: Vec<FilePatch>,
+}
+
+/// The type of a file as recorded in the mode lines of a git diff.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileMode {
+    /// A regular file (`100644`).
+    Normal,
+    /// An executable file (`100755`).
+    Executable,
+    /// A symbolic link (`120000`).
+    Symlink,
+    /// A Git submodule (`160000`).
+    GitSubmodule,
+}
+
+impl FileMode {
+    fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "100644" | "100664" => Some(FileMode::Normal),
+            "100755" => Some(FileMode::Executable),
+            "120000" => Some(FileMode::Symlink),
+            "160000" => Some(FileMode::GitSubmodule),
+            _ => None,
+        }
+    }
+}
+
+/// The change to a single file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilePatch {
+    /// The path before the change, or `None` if the file is added.
+    pub old_path: Option<RepoPathBuf>,
+    /// The path after the change, or `None` if the file is deleted.
+    pub new_path: Option<RepoPathBuf>,
+    /// The mode before the change, if recorded in the patch.
+    pub old_mode: Option<FileMode>,
+    /// The mode after the change, if recorded in the patch.
+    pub new_mode: Option<FileMode>,
+    /// Whether the file at `new_path` is a copy of the file at `old_path`
+    /// rather than a rename.
+    pub is_copy: bool,
+    /// The change to the contents.
+    pub content: FilePatchContent,
+}
+
+/// The change to the contents of a file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilePatchContent {
+    /// Line-based changes. Empty if only the path or the mode changed.
+    Text(Vec<Hunk>),
+    /// The full new contents of a binary file.
+    BinaryLiteral(Vec<u8>),
+}
+
+/// A hunk of a unified diff.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hunk {
+    /// The 1-based line number where the hunk starts in the old file. If the
+    /// hunk doesn't contain any old lines, this is the line after which the
+    /// new lines are inserted.
+    pub old_start: usize,
+    /// The lines of the hunk, including their line terminators.
+    pub lines: Vec<HunkLine>,
+}
+
+/// A line of a [`Hunk`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HunkLine {
+    /// A line that's in both the old and the new file.
+    Context(BString),
+    /// A line that's only in the old file.
+    Removed(BString),
+    /// A line that's only in the new file.
+    Added(BString),
+}
+
+impl Hunk {
+    /// The lines of the hunk as found in the old file.
+    pub fn old_lines(&self) -> impl Iterator<Item = &BString> {
+        self.lines.iter().filter_map(|line| match line {
+            HunkLine::Context(text) | HunkLine::Removed(text) => Some(text),
+            HunkLine::Added(_) => None,
+        })
+    }
+
+    /// The lines of the hunk as found in the new file.
+    pub fn new_lines(&self) -> impl Iterator<Item = &BString> {
+        self.lines.iter().filter_map(|line| match line {
+            HunkLine::Context(text) | HunkLine::Added(text) => Some(text),
+            HunkLine::Removed(_) => None,
+        })
+    }
+}
+
+/// Error from parsing a patch.
+#[derive(Debug, Error)]
+#[error("Line {line_number}: {message}")]
+pub struct PatchParseError {
+    /// The 1-based line number in the input.
+    pub line_number: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+/// Error from applying a patch.
+#[derive(Debug, Error)]
+pub enum PatchApplyError {
+    /// The file to change doesn't exist.
+    #[error("Path '{}' doesn't exist", .0.as_internal_file_string())]
+    MissingFile(RepoPathBuf),
+    /// The path exists but isn't a resolved file, symlink, or submodule.
+    #[error("Path '{}' is not a resolved file", .0.as_internal_file_string())]
+    NotAFile(RepoPathBuf),
+    /// The patched contents of a submodule aren't a `Subproject commit` line.
+    #[error("Path '{}' has an invalid submodule commit", .0.as_internal_file_string())]
+    InvalidSubmoduleCommit(RepoPathBuf),
+    /// Error from the backend.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Parses the patches in `data`.
+///
+/// If `data` is a mailbox, as written by `git format-patch`, each mail is
+/// parsed as a patch with the author and description from its headers and
+/// body. Otherwise, `data` is parsed as a single patch without metadata.
+pub fn parse_patches(data: &[u8]) -> Result<Vec<Patch>, PatchParseError> {
+    let lines = data.lines_with_terminator().collect::<Vec<_>>();
+    let mut parser = Parser { lines, pos: 0 };
+    if !parser.is_mail_start() {
+        let files = parser.parse_files()?;
+        return Ok(vec![Patch {
+            author: None,
+            description: String::new(),
+            files,
+        }]);
+    }
+    let mut patches = vec![];
+    while parser.pos < parser.lines.len() {
+        patches.push(parser.parse_mail()?);
+    }
+    Ok(patches)
+}
+
+struct Parser<'a> {
+    lines: Vec<&'a [u8]>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a [u8]> {
+        self.lines.get(self.pos).copied()
+    }
+
+    fn peek_str(&self) -> Option<&'a str> {
+        self.peek().map(line_str)
+    }
+
+    fn error(&self, message: impl Into<String>) -> PatchParseError {
+        PatchParseError {
+            line_number: self.pos + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Whether the current line is the `From ` line that starts a mail in a
+    /// mailbox.
+    fn is_mail_start(&self) -> bool {
+        let Some(line) = self.peek() else {
+            return false;
+        };
+        let next_is_header = self
+            .lines
+            .get(self.pos + 1)
+            .is_some_and(|next| parse_header(next).is_some());
+        line.starts_with(b"From ") && next_is_header
+    }
+
+    fn parse_mail(&mut self) -> Result<Patch, PatchParseError> {
+        self.pos += 1; // The "From " line
+        let mut headers: Vec<(String, String)> = vec![];
+        while let Some(line) = self.peek() {
+            self.pos += 1;
+            let line = trim_newline(line).to_str_lossy();
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = parse_header(line.as_bytes()) {
+                headers.push((name.to_ascii_lowercase(), value.to_owned()));
+            }
+        }
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(header_name, _)| header_name == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        let author = match (header("from"), header("date")) {
+            (Some(from), Some(date)) => {
+                let (name, email) = parse_address(from);
+                let datetime = chrono::DateTime::parse_from_rfc2822(date)
+                    .map_err(|err| self.error(format!("Invalid date '{date}': {err}")))?;
+                Some(Signature {
+                    name,
+                    email,
+                    timestamp: Timestamp::from_datetime(datetime),
+                })
+            }
+            (Some(from), None) => {
+                let (name, email) = parse_address(from);
+                Some(Signature {
+                    name,
+                    email,
+                    timestamp: Timestamp::now(),
+                })
+            }
+            _ => None,
+        };
+
+        let subject = decode_header_value(header("subject").unwrap_or_default());
+        let mut description = strip_subject_prefix(&subject).to_owned();
+        let mut body = String::new();
+        while let Some(line) = self.peek() {
+            if line_str(line) == "---" || self.is_file_start() || self.is_mail_start() {
+                break;
+            }
+            body.push_str(unescape_body_line(&trim_newline(line).to_str_lossy()));
+            body.push('\n');
+            self.pos += 1;
+        }
+        let body = body.trim();
+        if !body.is_empty() {
+            description.push_str("\n\n");
+            description.push_str(body);
+        }
+        if !description.is_empty() {
+            description.push('\n');
+        }
+
+        let files = self.parse_files()?;
+        Ok(Patch {
+            author,
+            description,
+            files,
+        })
+    }
+
+    /// Whether the current line starts the changes to a file.
+    fn is_file_start(&self) -> bool {
+        let Some(line) = self.peek() else {
+            return false;
+        };
+        line.starts_with(b"diff --git ")
+            || (line.starts_with(b"--- ")
+                && self
+                    .lines
+                    .get(self.pos + 1)
+                    .is_some_and(|next| next.starts_with(b"+++ ")))
+    }
+
+    /// Parses file changes until the end of the input or the start of the next
+    /// mail. Other lines between the files, such as a diffstat, are skipped.
+    fn parse_files(&mut self) -> Result<Vec<FilePatch>, PatchParseError> {
+        let mut files = vec![];
+        while self.pos < self.lines.len() && !self.is_mail_start() {
+            if !self.is_file_start() {
+                self.pos += 1;
+                continue;
+            }
+            let line = self.peek_str().unwrap();
+            let file = if let Some(paths) = line.strip_prefix("diff --git ") {
+                self.parse_git_file(paths)?
+            } else {
+                self.parse_unified_file()?
+            };
+            files.push(file);
+        }
+        Ok(files)
+    }
+
+    fn parse_git_file(&mut self, paths: &str) -> Result<FilePatch, PatchParseError> {
+        let Some((old_path, new_path)) = split_git_header_paths(paths) else {
+            return Err(self.error("Invalid diff header"));
+        };
+        self.pos += 1;
+        let mut file = FilePatch {
+            old_path: Some(self.parse_path(&old_path)?),
+            new_path: Some(self.parse_path(&new_path)?),
+            old_mode: None,
+            new_mode: None,
+            is_copy: false,
+            content: FilePatchContent::Text(vec![]),
+        };
+        while let Some(line) = self.peek_str() {
+            if line.starts_with("diff --git ") || line.starts_with("@@ ") || self.is_mail_start() {
+                break;
+            }
+            if let Some(mode) = line.strip_prefix("old mode ") {
+                file.old_mode = Some(self.parse_mode(mode)?);
+            } else if let Some(mode) = line.strip_prefix("new mode ") {
+                file.new_mode = Some(self.parse_mode(mode)?);
+            } else if let Some(mode) = line.strip_prefix("new file mode ") {
+                file.old_path = None;
+                file.new_mode = Some(self.parse_mode(mode)?);
+            } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+                file.new_path = None;
+                file.old_mode = Some(self.parse_mode(mode)?);
+            } else if let Some(path) = line
+                .strip_prefix("rename from ")
+                .or_else(|| line.strip_prefix("copy from "))
+            {
+                file.old_path = Some(self.parse_quoted_path(path)?);
+            } else if let Some(path) = line.strip_prefix("rename to ") {
+                file.new_path = Some(self.parse_quoted_path(path)?);
+            } else if let Some(path) = line.strip_prefix("copy to ") {
+                file.new_path = Some(self.parse_quoted_path(path)?);
+                file.is_copy = true;
+            } else if line.starts_with("--- ") {
+                // The paths were already taken from the extended headers
+                self.pos += 2;
+                continue;
+            } else if line == "GIT binary patch" {
+                self.pos += 1;
+                file.content = FilePatchContent::BinaryLiteral(self.parse_binary_literal()?);
+                break;
+            } else if line.starts_with("Binary files ") {
+                return Err(self.error("Binary patch without data can't be applied"));
+            }
+            self.pos += 1;
+        }
+        if let FilePatchContent::Text(hunks) = &mut file.content {
+            *hunks = self.parse_hunks()?;
+        }
+        Ok(file)
+    }
+
+    fn parse_unified_file(&mut self) -> Result<FilePatch, PatchParseError> {
+        let old_path = self.parse_unified_path("--- ", "a/")?;
+        self.pos += 1;
+        let new_path = self.parse_unified_path("+++ ", "b/")?;
+        self.pos += 1;
+        let hunks = self.parse_hunks()?;
+        Ok(FilePatch {
+            old_path,
+            new_path,
+            old_mode: None,
+            new_mode: None,
+            is_copy: false,
+            content: FilePatchContent::Text(hunks),
+        })
+    }
+
+    /// Parses the path of a `---` or `+++` line. Returns `None` for
+    /// `/dev/null`.
+    fn parse_unified_path(
+        &self,
+        marker: &str,
+        prefix: &str,
+    ) -> Result<Option<RepoPathBuf>, PatchParseError> {
+        let line = self.peek_str().unwrap();
+        let path = line.strip_prefix(marker).unwrap();
+        let path = if path.starts_with('"') {
+            // Anything after the closing quote is a timestamp
+            let (path, _) = unquote_path(path).ok_or_else(|| self.error("Invalid quoted path"))?;
+            path
+        } else {
+            // Strip the timestamp that diff(1) puts after the path
+            path.split('\t').next().unwrap().to_owned()
+        };
+        if path == "/dev/null" {
+            return Ok(None);
+        }
+        let path = path.strip_prefix(prefix).unwrap_or(&path);
+        self.parse_path(path).map(Some)
+    }
+
+    fn parse_path(&self, path: &str) -> Result<RepoPathBuf, PatchParseError> {
+        RepoPathBuf::from_relative_path(path)
+            .map_err(|_| self.error(format!("Invalid path '{path}'")))
+    }
+
+    /// Parses a path of an extended header, which git quotes if it contains
+    /// special characters.
+    fn parse_quoted_path(&self, path: &str) -> Result<RepoPathBuf, PatchParseError> {
+        if !path.starts_with('"') {
+            return self.parse_path(path);
+        }
+        match unquote_path(path) {
+            Some((path, "")) => self.parse_path(&path),
+            _ => Err(self.error("Invalid quoted path")),
+        }
+    }
+
+    fn parse_mode(&self, mode: &str) -> Result<FileMode, PatchParseError> {
+        FileMode::parse(mode).ok_or_else(|| self.error(format!("Unsupported file mode {mode}")))
+    }
+
+    fn parse_hunks(&mut self) -> Result<Vec<Hunk>, PatchParseError> {
+        let mut hunks = vec![];
+        while let Some(line) = self.peek_str() {
+            if !line.starts_with("@@ ") {
+                break;
+            }
+            let Some((old_start, mut old_count, mut new_count)) = parse_hunk_header(line) else {
+                return Err(self.error("Invalid hunk header"));
+            };
+            self.pos += 1;
+            let mut lines = vec![];
+            while old_count > 0 || new_count > 0 {
+                let Some(line) = self.peek() else {
+                    return Err(self.error("Unexpected end of hunk"));
+                };
+                let (kind, text) = match line.split_first() {
+                    // Some tools strip the space of empty context lines
+                    None | Some((b'\n', _)) | Some((b'\r', _)) => (b' ', line),
+                    Some((kind, text)) => (*kind, text),
+                };
+                let text = BString::from(text);
+                match kind {
+                    b' ' if old_count > 0 && new_count > 0 => {
+                        old_count -= 1;
+                        new_count -= 1;
+                        lines.push(HunkLine::Context(text));
+                    }
+                    b'-' if old_count > 0 => {
+                        old_count -= 1;
+                        lines.push(HunkLine::Removed(text));
+                    }
+                    b'+' if new_count > 0 => {
+                        new_count -= 1;
+                        lines.push(HunkLine::Added(text));
+                    }
+                    b'\\' => {}
+                    _ => return Err(self.error("Unexpected line in hunk")),
+                }
+                self.pos += 1;
+                self.strip_missing_newline(&mut lines);
+            }
+            hunks.push(Hunk { old_start, lines });
+        }
+        Ok(hunks)
+    }
+
+    /// Removes the line terminator of the last line if it's followed by a "\ No
+    /// newline at end of file" line.
+    fn strip_missing_newline(&mut self, lines: &mut [HunkLine]) {
+        if !self.peek().is_some_and(|line| line.starts_with(b"\\")) {
+            return;
+        }
+        self.pos += 1;
+        if let Some(HunkLine::Context(text) | HunkLine::Removed(text) | HunkLine::Added(text)) =
+            lines.last_mut()
+        {
+            if text.ends_with(b"\n") {
+                text.pop();
+            }
+            if text.ends_with(b"\r") {
+                text.pop();
+            }
+        }
+    }
+
+    /// Parses the forward hunk of a git binary patch and skips the reverse
+    /// hunk.
+    fn parse_binary_literal(&mut self) -> Result<Vec<u8>, PatchParseError> {
+        let line = self.peek_str().unwrap_or_default();
+        let size = if let Some(size) = line.strip_prefix("literal ") {
+            size.parse::<usize>()
+                .map_err(|_| self.error("Invalid binary literal size"))?
+        } else if line.starts_with("delta ") {
+            return Err(self.error("Binary delta patches are not supported"));
+        } else {
+            return Err(self.error("Expected a binary literal"));
+        };
+        self.pos += 1;
+        let mut compressed = vec![];
+        while let Some(line) = self.peek_str() {
+            self.pos += 1;
+            if line.is_empty() {
+                break;
+            }
+            let data = decode_base85_line(line)
+                .ok_or_else(|| self.error("Invalid base85 data in binary patch"))?;
+            compressed.extend(data);
+        }
+        let contents = zlib_decompress(&compressed, size)
+            .map_err(|err| self.error(format!("Invalid compressed data in binary patch: {err}")))?;
+        if contents.len() != size {
+            return Err(self.error("Binary literal has the wrong size"));
+        }
+        // Skip the reverse hunk
+        if self
+            .peek()
+            .is_some_and(|line| line.starts_with(b"literal ") || line.starts_with(b"delta "))
+        {
+            self.pos += 1;
+            while let Some(line) = self.peek_str() {
+                self.pos += 1;
+                if line.is_empty() {
+                    break;
+                }
+            }
+        }
+        Ok(contents)
+    }
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Returns the line without its terminator as a string. Lines that aren't
+/// valid UTF-8 never match the syntax of a patch, so they're replaced.
+fn line_str(line: &[u8]) -> &str {
+    trim_newline(line).to_str().unwrap_or("\u{fffd}")
+}
+
+/// Parses a `Name: value` mail header line.
+fn parse_header(line: &[u8]) -> Option<(&str, &str)> {
+    let line = trim_newline(line).to_str().ok()?;
+    let (name, value) = line.split_once(':')?;
+    let is_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    is_name.then(|| (name, value.trim()))
+}
+
+/// Splits `Name <email>` into its parts.
+fn parse_address(address: &str) -> (String, String) {
+    match address.rsplit_once('<') {
+        Some((name, email)) => {
+            let name = decode_header_value(name.trim().trim_matches('"'));
+            let email = email.trim_end().trim_end_matches('>');
+            (name, email.to_owned())
+        }
+        None => (String::new(), address.trim().to_owned()),
+    }
+}
+
+/// Decodes the RFC 2047 encoded-words in a header value, such as
+/// `=?UTF-8?q?caf=C3=A9?=`. Whitespace between adjacent encoded-words is
+/// dropped. Encoded-words in unsupported charsets are kept as is.
+fn decode_header_value(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut follows_encoded_word = false;
+    while let Some(start) = rest.find("=?") {
+        let Some((text, len)) = decode_encoded_word(&rest[start..]) else {
+            decoded.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            follows_encoded_word = false;
+            continue;
+        };
+        let between = &rest[..start];
+        if !follows_encoded_word || !between.trim().is_empty() {
+            decoded.push_str(between);
+        }
+        decoded.push_str(&text);
+        rest = &rest[start + len..];
+        follows_encoded_word = true;
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Decodes the `=?charset?encoding?text?=` encoded-word at the start of
+/// `word`. Returns the decoded text and the length of the encoded-word.
+fn decode_encoded_word(word: &str) -> Option<(String, usize)> {
+    let inner = word.strip_prefix("=?")?;
+    let (charset, inner) = inner.split_once('?')?;
+    let (encoding, inner) = inner.split_once('?')?;
+    let (text, _) = inner.split_once("?=")?;
+    if text.contains(char::is_whitespace) {
+        return None;
+    }
+    let len = word.len() - inner.len() + text.len() + 2;
+    let bytes = match encoding {
+        "B" | "b" => decode_base64(text)?,
+        "Q" | "q" => decode_q_encoding(text)?,
+        _ => return None,
+    };
+    // RFC 2231 allows a language after the charset, as in "UTF-8*en"
+    let charset = charset.split('*').next().unwrap().to_ascii_lowercase();
+    let text = match charset.as_str() {
+        "utf-8" | "us-ascii" => String::from_utf8(bytes).ok()?,
+        "iso-8859-1" | "latin1" => bytes.iter().map(|&b| char::from(b)).collect(),
+        _ => return None,
+    };
+    Some((text, len))
+}
+
+fn decode_q_encoding(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut iter = text.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [iter.next()?, iter.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => bytes.push(b),
+        }
+    }
+    Some(bytes)
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut bytes = vec![];
+    let mut value: u32 = 0;
+    let mut bits = 0;
+    for c in text.trim_end_matches('=').bytes() {
+        let digit = ALPHABET.iter().position(|&a| a == c)?;
+        value = (value << 6 | digit as u32) & 0xffff;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((value >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Removes the `>` that was added to a description line starting with `From `
+/// or `---` when the mail was written, so the line isn't mistaken for the
+/// start of a mail or of the diff.
+fn unescape_body_line(line: &str) -> &str {
+    match line.strip_prefix('>') {
+        Some(rest) if rest.trim_start_matches('>').starts_with("From ") => rest,
+        Some(rest) if rest.trim_start_matches('>').starts_with("---") => rest,
+        _ => line,
+    }
+}
+
+/// Removes a `[PATCH n/m]` prefix from a subject.
+fn strip_subject_prefix(subject: &str) -> &str {
+    let subject = subject.trim();
+    if subject.starts_with('[') {
+        if let Some((_, rest)) = subject.split_once(']') {
+            return rest.trim_start();
+        }
+    }
+    subject
+}
+
+/// Splits the `a/old b/new` paths of a `diff --git` line. Either path may be
+/// quoted. Unquoted paths containing " b/" are split so that both sides are
+/// equal if possible.
+fn split_git_header_paths(paths: &str) -> Option<(String, String)> {
+    let (old_path, new_path) = if let Some((old_path, rest)) = unquote_path(paths) {
+        let rest = rest.strip_prefix(' ')?;
+        let new_path = match unquote_path(rest) {
+            Some((new_path, "")) => new_path,
+            Some(_) => return None,
+            None => rest.to_owned(),
+        };
+        (old_path, new_path)
+    } else if paths.ends_with('"') {
+        paths.match_indices(" \"").find_map(|(index, _)| {
+            match unquote_path(&paths[index + 1..]) {
+                Some((new_path, "")) => Some((paths[..index].to_owned(), new_path)),
+                _ => None,
+            }
+        })?
+    } else {
+        let paths = paths.strip_prefix("a/")?;
+        let candidates = paths.match_indices(" b/").map(|(index, _)| index);
+        let mut first = None;
+        for index in candidates {
+            let old_path = &paths[..index];
+            let new_path = &paths[index + 3..];
+            if old_path == new_path {
+                return Some((old_path.to_owned(), new_path.to_owned()));
+            }
+            first.get_or_insert((old_path, new_path));
+        }
+        let (old_path, new_path) = first?;
+        return Some((old_path.to_owned(), new_path.to_owned()));
+    };
+    let old_path = old_path.strip_prefix("a/")?.to_owned();
+    let new_path = new_path.strip_prefix("b/")?.to_owned();
+    Some((old_path, new_path))
+}
+
+/// Parses a path that git quoted because it contains special characters, such
+/// as `"a/caf\303\251"`. Returns the unquoted path and the rest of the input
+/// after the closing quote, or `None` if `quoted` doesn't start with a valid
+/// quoted path.
+fn unquote_path(quoted: &str) -> Option<(String, &str)> {
+    let inner = quoted.strip_prefix('"')?;
+    let mut bytes = vec![];
+    let mut iter = inner.bytes().enumerate();
+    while let Some((index, b)) = iter.next() {
+        match b {
+            b'"' => {
+                let path = String::from_utf8(bytes).ok()?;
+                return Some((path, &inner[index + 1..]));
+            }
+            b'\\' => {
+                let (_, escaped) = iter.next()?;
+                bytes.push(match escaped {
+                    b'a' => b'\x07',
+                    b'b' => b'\x08',
+                    b't' => b'\t',
+                    b'n' => b'\n',
+                    b'v' => b'\x0b',
+                    b'f' => b'\x0c',
+                    b'r' => b'\r',
+                    b'0'..=b'7' => {
+                        let digits = [escaped, iter.next()?.1, iter.next()?.1];
+                        u8::from_str_radix(std::str::from_utf8(&digits).ok()?, 8).ok()?
+                    }
+                    // `\"` and `\\`
+                    _ => escaped,
+                });
+            }
+            _ => bytes.push(b),
+        }
+    }
+    None
+}
+
+/// Parses `@@ -start,count +start,count @@`.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let mut words = line.split(' ');
+    let _ = words.next()?;
+    let old_range = words.next()?.strip_prefix('-')?;
+    let new_range = words.next()?.strip_prefix('+')?;
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse_range(old_range)?;
+    let (_, new_count) = parse_range(new_range)?;
+    Some((old_start, old_count, new_count))
+}
+
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// Decodes a line of base85 data as found in git binary patches. The first
+/// character encodes the number of decoded bytes.
+fn decode_base85_line(line: &str) -> Option<Vec<u8>> {
+    let (&len_char, data) = line.as_bytes().split_first()?;
+    let len = match len_char {
+        b'A'..=b'Z' => len_char - b'A' + 1,
+        b'a'..=b'z' => len_char - b'a' + 27,
+        _ => return None,
+    } as usize;
+    if data.len() % 5 != 0 || data.len() / 5 * 4 < len {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(data.len() / 5 * 4);
+    for chunk in data.chunks(5) {
+        let mut value: u64 = 0;
+        for &c in chunk {
+            let digit = BASE85_ALPHABET.iter().position(|&a| a == c)?;
+            value = value * 85 + digit as u64;
+        }
+        let value = u32::try_from(value).ok()?;
+        decoded.extend_from_slice(&value.to_be_bytes());
+    }
+    decoded.truncate(len);
+    Some(decoded)
+}
+
+/// Decompresses a zlib stream which is expected to expand to `size` bytes. At
+/// most one byte more than that is returned if the stream is larger.
+#[cfg(feature = "git")]
+fn zlib_decompress(compressed: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    use gix::features::zlib;
+
+    let mut contents = vec![0; size + 1];
+    let mut reader = compressed;
+    let mut state = zlib::Decompress::new(true);
+    let len = zlib::stream::inflate::read(&mut reader, &mut state, &mut contents)
+        .map_err(|err| err.to_string())?;
+    contents.truncate(len);
+    Ok(contents)
+}
+
+#[cfg(not(feature = "git"))]
+fn zlib_decompress(_compressed: &[u8], _size: usize) -> Result<Vec<u8>, String> {
+    Err("zlib support requires the git feature".to_owned())
+}
+
+#[cfg(feature = "git")]
+fn encode_binary_literal(contents: &[u8], out: &mut String) {
+    use std::io::Write as _;
+
+    let mut encoder = gix::features::zlib::stream::deflate::Write::new(vec![]);
+    encoder
+        .write_all(contents)
+        .and_then(|()| encoder.flush())
+        .expect("writing to an in-memory buffer should never fail");
+    let compressed = encoder.into_inner();
+    out.push_str(&format!("literal {}\n", contents.len()));
+    for line in compressed.chunks(52) {
+        let len = line.len() as u8;
+        out.push(if len <= 26 {
+            (b'A' + len - 1) as char
+        } else {
+            (b'a' + len - 27) as char
+        });
+        for chunk in line.chunks(4) {
+            let mut bytes = [0; 4];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            let mut value = u32::from_be_bytes(bytes);
+            let mut encoded = [0; 5];
+            for c in encoded.iter_mut().rev() {
+                *c = BASE85_ALPHABET[(value % 85) as usize];
+                value /= 85;
+            }
+            out.push_str(std::str::from_utf8(&encoded).unwrap());
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+/// Formats the change from `old` to `new` as a git binary patch, starting with
+/// the `GIT binary patch` line. Both the forward and the reverse literals are
+/// included so the patch can also be applied by git.
+#[cfg(feature = "git")]
+pub fn format_binary_patch(old: &[u8], new: &[u8]) -> String {
+    let mut out = "GIT binary patch\n".to_owned();
+    encode_binary_literal(new, &mut out);
+    encode_binary_literal(old, &mut out);
+    out
+}
+
+/// Applies `patch` to `tree` and writes the resulting tree.
+///
+/// Hunks are located by their context, so they still apply if lines were added
+/// or removed elsewhere in the file. A hunk that can't be located is recorded
+/// as a conflict between the current contents of the file and the patch,
+/// instead of being rejected.
+pub fn apply_patch(
+    store: &Arc<Store>,
+    tree: &MergedTree,
+    patch: &Patch,
+) -> Result<MergedTreeId, PatchApplyError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for file in &patch.files {
+        apply_file_patch(store, tree, file, &mut tree_builder)?;
+    }
+    Ok(tree_builder.write_tree(store)?)
+}
+
+fn apply_file_patch(
+    store: &Store,
+    tree: &MergedTree,
+    file: &FilePatch,
+    tree_builder: &mut MergedTreeBuilder,
+) -> Result<(), PatchApplyError> {
+    let current = match (&file.old_path, &file.new_path) {
+        (Some(old_path), _) => Some(
+            read_file(store, tree, old_path)?
+                .ok_or_else(|| PatchApplyError::MissingFile(old_path.clone()))?,
+        ),
+        (None, Some(new_path)) => read_file(store, tree, new_path)?,
+        (None, None) => return Ok(()),
+    };
+    let (current_text, current_mode) = match &current {
+        Some((text, mode)) => (text.as_slice(), Some(*mode)),
+        None => (&b""[..], None),
+    };
+    let mode = file.new_mode.or(current_mode).unwrap_or(FileMode::Normal);
+    let applied = match &file.content {
+        FilePatchContent::Text(hunks) if file.old_path.is_none() && current.is_some() => {
+            // The file to add already exists
+            let new_text: Vec<u8> = hunks
+                .iter()
+                .flat_map(|hunk| hunk.new_lines())
+                .flat_map(|line| line.iter().copied())
+                .collect();
+            AppliedText::Conflict {
+                base: None,
+                ours: current_text.to_vec(),
+                theirs: new_text,
+            }
+        }
+        FilePatchContent::Text(hunks) => apply_hunks(current_text, hunks),
+        FilePatchContent::BinaryLiteral(contents) => AppliedText::Clean(contents.clone()),
+    };
+
+    if let Some(old_path) = &file.old_path {
+        if file.new_path.as_ref() != Some(old_path) && !file.is_copy {
+            tree_builder.set_or_remove(old_path.clone(), Merge::absent());
+        }
+    }
+    let (path, is_deleted) = match (&file.new_path, &file.old_path) {
+        (Some(new_path), _) => (new_path, false),
+        (None, Some(old_path)) => (old_path, true),
+        (None, None) => unreachable!(),
+    };
+    let value = match applied {
+        AppliedText::Clean(text) if is_deleted && text.is_empty() => Merge::absent(),
+        AppliedText::Clean(text) => Merge::normal(write_value(store, path, &text, mode)?),
+        AppliedText::Conflict { base, ours, theirs } => {
+            let base = match base {
+                Some(base) => Some(write_value(store, path, &base, mode)?),
+                None => None,
+            };
+            let ours = write_value(store, path, &ours, current_mode.unwrap_or(mode))?;
+            let theirs = if is_deleted {
+                None
+            } else {
+                Some(write_value(store, path, &theirs, mode)?)
+            };
+            Merge::from_removes_adds([base], [Some(ours), theirs])
+        }
+    };
+    tree_builder.set_or_remove(path.clone(), value);
+    Ok(())
+}
+
+/// Reads the contents of a resolved file or symlink. Returns `None` if the
+/// path doesn't exist.
+fn read_file(
+    store: &Store,
+    tree: &MergedTree,
+    path: &RepoPath,
+) -> Result<Option<(Vec<u8>, FileMode)>, PatchApplyError> {
+    let value = tree.path_value(path)?;
+    let Some(value) = value.as_resolved() else {
+        return Err(PatchApplyError::NotAFile(path.to_owned()));
+    };
+    match value {
+        None => Ok(None),
+        Some(TreeValue::File { id, executable }) => {
+            let mut contents = vec![];
+            store
+                .read_file(path, id)?
+                .read_to_end(&mut contents)
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.to_owned(),
+                    id: id.clone(),
+                    source: err.into(),
+                })?;
+            let mode = if *executable {
+                FileMode::Executable
+            } else {
+                FileMode::Normal
+            };
+            Ok(Some((contents, mode)))
+        }
+        Some(TreeValue::Symlink(id)) => {
+            let target = store.read_symlink(path, id)?;
+            Ok(Some((target.into_bytes(), FileMode::Symlink)))
+        }
+        Some(TreeValue::GitSubmodule(id)) => {
+            // Git diffs a submodule as a single line naming the commit
+            let contents = format!("Subproject commit {}\n", id.hex());
+            Ok(Some((contents.into_bytes(), FileMode::GitSubmodule)))
+        }
+        Some(_) => Err(PatchApplyError::NotAFile(path.to_owned())),
+    }
+}
+
+fn write_value(
+    store: &Store,
+    path: &RepoPath,
+    contents: &[u8],
+    mode: FileMode,
+) -> Result<TreeValue, PatchApplyError> {
+    match mode {
+        FileMode::Normal | FileMode::Executable => {
+            let id = store.write_file(path, &mut &contents[..]).block_on()?;
+            Ok(TreeValue::File {
+                id,
+                executable: mode == FileMode::Executable,
+            })
+        }
+        FileMode::Symlink => {
+            let target = String::from_utf8_lossy(contents);
+            let id = store.write_symlink(path, &target).block_on()?;
+            Ok(TreeValue::Symlink(id))
+        }
+        FileMode::GitSubmodule => {
+            let id = contents
+                .strip_prefix(b"Subproject commit ")
+                .and_then(|hex| hex.trim().to_str().ok())
+                .and_then(|hex| CommitId::try_from_hex(hex).ok())
+                .ok_or_else(|| PatchApplyError::InvalidSubmoduleCommit(path.to_owned()))?;
+            Ok(TreeValue::GitSubmodule(id))
+        }
+    }
+}
+
+enum AppliedText {
+    Clean(Vec<u8>),
+    /// The hunks that couldn't be located are in `base` as the old lines and in
+    /// `theirs` as the new lines. The other hunks are applied to all sides.
+    Conflict {
+        base: Option<Vec<u8>>,
+        ours: Vec<u8>,
+        theirs: Vec<u8>,
+    },
+}
+
+fn apply_hunks(text: &[u8], hunks: &[Hunk]) -> AppliedText {
+    let lines = text.lines_with_terminator().collect::<Vec<_>>();
+    let mut base = vec![];
+    let mut ours = vec![];
+    let mut theirs = vec![];
+    let mut is_conflicted = false;
+    let mut cursor = 0;
+    // How far the hunks were found from where they were expected
+    let mut offset: isize = 0;
+    for hunk in hunks {
+        let old_lines = hunk
+            .old_lines()
+            .map(|line| line.as_slice())
+            .collect::<Vec<_>>();
+        let new_text = hunk
+            .new_lines()
+            .flat_map(|line| line.iter().copied())
+            .collect::<Vec<_>>();
+        let expected = if old_lines.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = expected
+            .saturating_add_signed(offset)
+            .clamp(cursor, lines.len());
+        let found = find_lines(&lines, &old_lines, cursor, expected);
+        let (start, end) = match found {
+            Some(start) => (start, start + old_lines.len()),
+            None => (expected, (expected + old_lines.len()).min(lines.len())),
+        };
+        for side in [&mut base, &mut ours, &mut theirs] {
+            side.extend(lines[cursor..start].concat());
+        }
+        if found.is_some() {
+            offset += start as isize - expected as isize;
+            for side in [&mut base, &mut ours, &mut theirs] {
+                side.extend_from_slice(&new_text);
+            }
+        } else {
+            is_conflicted = true;
+            base.extend(old_lines.concat());
+            ours.extend(lines[start..end].concat());
+            theirs.extend_from_slice(&new_text);
+        }
+        cursor = end;
+    }
+    for side in [&mut base, &mut ours, &mut theirs] {
+        side.extend(lines[cursor..].concat());
+    }
+    if is_conflicted {
+        AppliedText::Conflict {
+            base: Some(base),
+            ours,
+            theirs,
+        }
+    } else {
+        AppliedText::Clean(theirs)
+    }
+}
+
+/// Finds `needle` in `lines` at or after `min_start`, as close to `expected`
+/// as possible.
+fn find_lines(
+    lines: &[&[u8]],
+    needle: &[&[u8]],
+    min_start: usize,
+    expected: usize,
+) -> Option<usize> {
+    let matches_at = |start: usize| {
+        start >= min_start
+            && start + needle.len() <= lines.len()
+            && lines[start..start + needle.len()] == *needle
+    };
+    (0..=lines.len()).find_map(|distance| {
+        [
+            expected.checked_add(distance),
+            expected.checked_sub(distance),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|&start| matches_at(start))
+    })
+}
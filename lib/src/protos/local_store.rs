@@ -68,6 +68,9 @@ pub struct Commit {
     pub committer: ::core::option::Option<commit::Signature>,
     #[prost(bytes = "vec", optional, tag = "9")]
     pub secure_sig: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// Copies and renames from the parents' trees
+    #[prost(message, repeated, tag = "10")]
+    pub copies: ::prost::alloc::vec::Vec<commit::Copy>,
 }
 /// Nested message and enum types in `Commit`.
 pub mod commit {
@@ -89,6 +92,14 @@ pub mod commit {
         #[prost(message, optional, tag = "3")]
         pub timestamp: ::core::option::Option<Timestamp>,
     }
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Copy {
+        #[prost(string, tag = "1")]
+        pub source: ::prost::alloc::string::String,
+        #[prost(string, tag = "2")]
+        pub target: ::prost::alloc::string::String,
+    }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
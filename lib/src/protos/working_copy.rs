@@ -44,6 +44,8 @@ pub struct TreeState {
     pub sparse_patterns: ::core::option::Option<SparsePatterns>,
     #[prost(message, optional, tag = "4")]
     pub watchman_clock: ::core::option::Option<WatchmanClock>,
+    #[prost(message, optional, tag = "7")]
+    pub inotify_clock: ::core::option::Option<InotifyClock>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -64,6 +66,16 @@ pub mod watchman_clock {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InotifyClock {
+    /// Identifies the daemon instance which wrote the event log.
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+    /// Length of the event log which has been consumed.
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Checkout {
     /// The operation at which the working copy was updated.
     #[prost(bytes = "vec", tag = "2")]
@@ -20,6 +20,7 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fs;
+use std::mem;
 use std::path::Path;
 use std::slice;
 use std::sync::Arc;
@@ -63,6 +64,7 @@ use crate::op_heads_store;
 use crate::op_heads_store::OpHeadResolutionError;
 use crate::op_heads_store::OpHeadsStore;
 use crate::op_store;
+use crate::op_store::BisectState;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OperationId;
@@ -75,6 +77,7 @@ use crate::refs::diff_named_ref_targets;
 use crate::refs::diff_named_remote_refs;
 use crate::refs::merge_ref_targets;
 use crate::refs::merge_remote_refs;
+use crate::rerere::ResolutionStore;
 use crate::revset;
 use crate::revset::RevsetEvaluationError;
 use crate::revset::RevsetExpression;
@@ -95,6 +98,10 @@ use crate::transaction::Transaction;
 use crate::view::View;
 
 pub trait Repo {
+    /// Base repository that contains all committed data. Returns `self` if this
+    /// is a `ReadonlyRepo`.
+    fn base_repo(&self) -> &ReadonlyRepo;
+
     fn store(&self) -> &Arc<Store>;
 
     fn op_store(&self) -> &Arc<dyn OpStore>;
@@ -146,11 +153,13 @@ pub enum RepoInitError {
     Backend(#[from] BackendInitError),
     #[error(transparent)]
     Path(#[from] PathError),
+    #[error(transparent)]
+    Config(#[from] config::ConfigError),
 }
 
 impl ReadonlyRepo {
     pub fn default_op_store_initializer() -> &'static OpStoreInitializer<'static> {
-        &|_settings, store_path| Box::new(SimpleOpStore::init(store_path))
+        &|settings, store_path| Box::new(SimpleOpStore::init(settings, store_path))
     }
 
     pub fn default_op_heads_store_initializer() -> &'static OpHeadsStoreInitializer<'static> {
@@ -161,7 +170,7 @@ impl ReadonlyRepo {
     }
 
     pub fn default_index_store_initializer() -> &'static IndexStoreInitializer<'static> {
-        &|_settings, store_path| Ok(Box::new(DefaultIndexStore::init(store_path)?))
+        &|settings, store_path| Ok(Box::new(DefaultIndexStore::init(settings, store_path)?))
     }
 
     pub fn default_submodule_store_initializer() -> &'static SubmoduleStoreInitializer<'static> {
@@ -186,8 +195,11 @@ impl ReadonlyRepo {
         let backend = backend_initializer(user_settings, &store_path)?;
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
-        let store = Store::new(backend, signer);
         let repo_settings = user_settings.with_repo(&repo_path).unwrap();
+        let resolution_store = repo_settings
+            .merge_reuse_resolutions()?
+            .then(|| ResolutionStore::load(&repo_path.join("rerere")));
+        let store = Store::new(backend, signer, resolution_store);
 
         let op_store_path = repo_path.join("op_store");
         fs::create_dir(&op_store_path).context(&op_store_path)?;
@@ -322,6 +334,10 @@ impl ReadonlyRepo {
 }
 
 impl Repo for ReadonlyRepo {
+    fn base_repo(&self) -> &ReadonlyRepo {
+        self
+    }
+
     fn store(&self) -> &Arc<Store> {
         &self.store
     }
@@ -396,7 +412,7 @@ impl Default for StoreFactories {
         // Backends
         factories.add_backend(
             LocalBackend::name(),
-            Box::new(|_settings, store_path| Ok(Box::new(LocalBackend::load(store_path)))),
+            Box::new(|settings, store_path| Ok(Box::new(LocalBackend::load(settings, store_path)))),
         );
         #[cfg(feature = "git")]
         factories.add_backend(
@@ -420,7 +436,7 @@ impl Default for StoreFactories {
         // OpStores
         factories.add_op_store(
             SimpleOpStore::name(),
-            Box::new(|_settings, store_path| Box::new(SimpleOpStore::load(store_path))),
+            Box::new(|settings, store_path| Box::new(SimpleOpStore::load(settings, store_path))),
         );
 
         // OpHeadsStores
@@ -432,7 +448,9 @@ impl Default for StoreFactories {
         // Index
         factories.add_index_store(
             DefaultIndexStore::name(),
-            Box::new(|_settings, store_path| Ok(Box::new(DefaultIndexStore::load(store_path)))),
+            Box::new(|settings, store_path| {
+                Ok(Box::new(DefaultIndexStore::load(settings, store_path)))
+            }),
         );
 
         // SubmoduleStores
@@ -461,6 +479,8 @@ pub enum StoreLoadError {
     Backend(#[from] BackendLoadError),
     #[error(transparent)]
     Signing(#[from] SignInitError),
+    #[error(transparent)]
+    Config(#[from] config::ConfigError),
 }
 
 impl StoreFactories {
@@ -656,11 +676,15 @@ impl RepoLoader {
         repo_path: &Path,
         store_factories: &StoreFactories,
     ) -> Result<Self, StoreLoadError> {
+        let repo_settings = user_settings.with_repo(repo_path).unwrap();
+        let resolution_store = repo_settings
+            .merge_reuse_resolutions()?
+            .then(|| ResolutionStore::load(&repo_path.join("rerere")));
         let store = Store::new(
             store_factories.load_backend(user_settings, &repo_path.join("store"))?,
             Signer::from_settings(user_settings)?,
+            resolution_store,
         );
-        let repo_settings = user_settings.with_repo(repo_path).unwrap();
         let op_store =
             Arc::from(store_factories.load_op_store(user_settings, &repo_path.join("op_store"))?);
         let op_heads_store = Arc::from(
@@ -844,6 +868,10 @@ pub struct MutableRepo {
     //   commits. However, if the type is `Abandoned`, a new working-copy commit should be created
     //   on top of all of the new commits instead.
     parent_mapping: HashMap<CommitId, Rewrite>,
+    // Pairs of conflicted commits and their rewrites that resolved some of the
+    // conflicts. The resolutions are recorded when the transaction is
+    // committed.
+    resolved_conflicts: Vec<(Commit, Commit)>,
 }
 
 impl MutableRepo {
@@ -859,6 +887,7 @@ impl MutableRepo {
             index: mut_index,
             view: DirtyCell::with_clean(mut_view),
             parent_mapping: Default::default(),
+            resolved_conflicts: vec![],
         }
     }
 
@@ -878,6 +907,35 @@ impl MutableRepo {
         !(self.parent_mapping.is_empty() && self.view() == &self.base_repo.view)
     }
 
+    /// Remembers the conflicted commits which were rewritten in this
+    /// transaction along with their rewrites, so the resolutions of the
+    /// conflicts can be recorded once the transaction is committed. This needs
+    /// to be called before the rewrites are cleared.
+    fn add_resolved_conflicts(&mut self) -> BackendResult<()> {
+        let store = self.store().clone();
+        if store.resolution_store().is_none() {
+            return Ok(());
+        }
+        for (old_id, rewrite) in &self.parent_mapping {
+            let Rewrite::Rewritten(new_id) = rewrite else {
+                continue;
+            };
+            let old_commit = store.get_commit(old_id)?;
+            if !old_commit.has_conflict()? {
+                continue;
+            }
+            let new_commit = store.get_commit(new_id)?;
+            if new_commit.tree_id() != old_commit.tree_id() {
+                self.resolved_conflicts.push((old_commit, new_commit));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn take_resolved_conflicts(&mut self) -> Vec<(Commit, Commit)> {
+        mem::take(&mut self.resolved_conflicts)
+    }
+
     pub(crate) fn consume(self) -> (Box<dyn MutableIndex>, View) {
         self.view.ensure_clean(|v| self.enforce_view_invariants(v));
         (self.index, self.view.into_inner())
@@ -1280,6 +1338,7 @@ impl MutableRepo {
         let result = self
             .rebase_descendants_return_rebaser(settings, options)?
             .map_or(0, |rebaser| rebaser.into_map().len());
+        self.add_resolved_conflicts()?;
         self.parent_mapping.clear();
         Ok(result)
     }
@@ -1309,6 +1368,7 @@ impl MutableRepo {
             // abandoned
             .rebase_descendants_return_rebaser(settings, options)?
             .map_or(HashMap::new(), |rebaser| rebaser.into_map()));
+        self.add_resolved_conflicts()?;
         self.parent_mapping.clear();
         result
     }
@@ -1324,6 +1384,7 @@ impl MutableRepo {
             }
             Ok(())
         })?;
+        self.add_resolved_conflicts()?;
         self.parent_mapping.clear();
         Ok(num_rebased)
     }
@@ -1615,6 +1676,10 @@ impl MutableRepo {
         self.view_mut().set_git_head_target(target);
     }
 
+    pub fn set_bisect_state(&mut self, state: Option<BisectState>) {
+        self.view_mut().set_bisect_state(state);
+    }
+
     pub fn set_view(&mut self, data: op_store::View) {
         self.view_mut().set_view(data);
         self.view.mark_dirty();
@@ -1718,6 +1783,13 @@ impl MutableRepo {
             other.git_head(),
         );
         self.set_git_head_target(new_git_head_target);
+
+        // If both sides changed the bisection state, we keep the self side.
+        if other.bisect_state() != base.bisect_state()
+            && self.view().bisect_state() == base.bisect_state()
+        {
+            self.set_bisect_state(other.bisect_state().cloned());
+        }
     }
 
     /// Finds and records commits that were rewritten or abandoned between
@@ -1775,6 +1847,10 @@ impl MutableRepo {
 }
 
 impl Repo for MutableRepo {
+    fn base_repo(&self) -> &ReadonlyRepo {
+        &self.base_repo
+    }
+
     fn store(&self) -> &Arc<Store> {
         self.base_repo.store()
     }
@@ -0,0 +1,264 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reuse of recorded conflict resolutions ("rerere").
+//!
+//! When a conflict in a file is resolved in a successor or a child of the
+//! conflicted commit, the resolution of each conflicted hunk is recorded along
+//! with the sides of the hunk. When the same hunk is encountered again while merging
+//! trees, the recorded resolution is used instead of leaving a conflict.
+//!
+//! The resolutions are stored outside of the operation log, so undoing the
+//! operation that recorded a resolution doesn't forget it. The paths of files
+//! resolved with recorded resolutions are kept until they're taken with
+//! [`ResolutionStore::take_applied_paths()`] so that they can be reported.
+
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::mem;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use blake2::Blake2b512;
+use blake2::Digest as _;
+use bstr::BString;
+use bstr::ByteSlice as _;
+use pollster::FutureExt as _;
+use tempfile::NamedTempFile;
+use tempfile::PersistError;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
+use crate::conflicts::extract_as_single_hunk;
+use crate::content_hash::ContentHash as _;
+use crate::file_util::create_or_reuse_dir;
+use crate::files;
+use crate::files::MergeResult;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
+
+/// Stores the recorded resolution of each conflicted hunk in a file named
+/// after the hash of the sides of the hunk.
+#[derive(Debug)]
+pub struct ResolutionStore {
+    dir: PathBuf,
+    applied_paths: Mutex<Vec<RepoPathBuf>>,
+}
+
+impl ResolutionStore {
+    /// Creates a store for the resolutions in `dir`. The directory is created
+    /// when the first resolution is recorded.
+    pub fn load(dir: &Path) -> Self {
+        ResolutionStore {
+            dir: dir.to_owned(),
+            applied_paths: Mutex::new(vec![]),
+        }
+    }
+
+    /// Records `resolution` as the resolution of the conflicted `hunk`.
+    pub fn record(&self, hunk: &Merge<BString>, resolution: &[u8]) -> io::Result<()> {
+        create_or_reuse_dir(&self.dir)?;
+        let mut temp_file = NamedTempFile::new_in(&self.dir)?;
+        temp_file.write_all(resolution)?;
+        temp_file
+            .persist(self.hunk_path(hunk))
+            .map_err(|PersistError { error, file: _ }| error)?;
+        Ok(())
+    }
+
+    /// Returns the recorded resolution of the conflicted `hunk`, if any.
+    pub fn get(&self, hunk: &Merge<BString>) -> io::Result<Option<BString>> {
+        match fs::read(self.hunk_path(hunk)) {
+            Ok(resolution) => Ok(Some(BString::from(resolution))),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Removes the recorded resolution of the conflicted `hunk`. Returns
+    /// whether there was one.
+    pub fn forget(&self, hunk: &Merge<BString>) -> io::Result<bool> {
+        match fs::remove_file(self.hunk_path(hunk)) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Resolves the conflicted hunks of the merge of the file at `path` with
+    /// the recorded resolutions. Returns `None` unless all conflicted hunks
+    /// have a recorded resolution.
+    pub fn resolve(
+        &self,
+        path: &RepoPath,
+        hunks: &[Merge<BString>],
+    ) -> io::Result<Option<BString>> {
+        let mut content = BString::default();
+        for hunk in hunks {
+            if let Some(text) = hunk.as_resolved() {
+                content.extend_from_slice(text);
+            } else if let Some(resolution) = self.get(hunk)? {
+                content.extend_from_slice(&resolution);
+            } else {
+                return Ok(None);
+            }
+        }
+        self.applied_paths.lock().unwrap().push(path.to_owned());
+        Ok(Some(content))
+    }
+
+    /// Returns the paths of the files resolved by [`Self::resolve()`] since
+    /// the last call. A path may be listed more than once if the same
+    /// conflict was resolved in multiple trees.
+    pub fn take_applied_paths(&self) -> Vec<RepoPathBuf> {
+        mem::take(&mut *self.applied_paths.lock().unwrap())
+    }
+
+    fn hunk_path(&self, hunk: &Merge<BString>) -> PathBuf {
+        let mut hasher = Blake2b512::new();
+        (hunk.num_sides() as u32).hash(&mut hasher);
+        for term in hunk.iter() {
+            term.as_slice().hash(&mut hasher);
+        }
+        self.dir.join(hex::encode(hasher.finalize()))
+    }
+}
+
+/// Returns the conflicted hunks of a file conflict, or `None` if the value at
+/// the path isn't a file conflict.
+fn file_conflict_hunks(
+    store: &Store,
+    path: &RepoPath,
+    tree: &MergedTree,
+) -> BackendResult<Option<Vec<Merge<BString>>>> {
+    let value = tree.path_value(path)?;
+    let Some(file_id_conflict) = value.to_file_merge() else {
+        return Ok(None);
+    };
+    let contents = extract_as_single_hunk(&file_id_conflict.simplify(), store, path).block_on()?;
+    match files::merge(&contents) {
+        MergeResult::Resolved(_) => Ok(None),
+        MergeResult::Conflict(hunks) => Ok(Some(hunks)),
+    }
+}
+
+/// Records the resolutions of the conflicts in `conflicted_tree` that were
+/// resolved in `resolved_tree`. Returns the number of recorded resolutions.
+///
+/// The resolution of each conflicted hunk is located in the resolved file by
+/// the resolved hunks around it. Files where the text around the conflicts was
+/// also changed are skipped.
+pub fn record_resolutions(
+    store: &Store,
+    resolutions: &ResolutionStore,
+    conflicted_tree: &MergedTree,
+    resolved_tree: &MergedTree,
+) -> BackendResult<usize> {
+    let mut num_recorded = 0;
+    for (path, _) in conflicted_tree.conflicts() {
+        let Ok(Some(TreeValue::File { id, .. })) = resolved_tree.path_value(&path)?.into_resolved()
+        else {
+            continue;
+        };
+        let Some(hunks) = file_conflict_hunks(store, &path, conflicted_tree)? else {
+            continue;
+        };
+        let mut resolved_content = vec![];
+        store
+            .read_file(&path, &id)?
+            .read_to_end(&mut resolved_content)
+            .map_err(|err| BackendError::ReadFile {
+                path: path.clone(),
+                id: id.clone(),
+                source: err.into(),
+            })?;
+        for (hunk, resolution) in locate_resolutions(&hunks, &resolved_content) {
+            resolutions
+                .record(hunk, resolution)
+                .map_err(|err| BackendError::Other(err.into()))?;
+            num_recorded += 1;
+        }
+    }
+    Ok(num_recorded)
+}
+
+/// Forgets the recorded resolutions of the conflicts in `tree`. Returns the
+/// number of forgotten resolutions.
+pub fn forget_resolutions(
+    store: &Store,
+    resolutions: &ResolutionStore,
+    tree: &MergedTree,
+    paths: &[&RepoPath],
+) -> BackendResult<usize> {
+    let mut num_forgotten = 0;
+    for path in paths {
+        let Some(hunks) = file_conflict_hunks(store, path, tree)? else {
+            continue;
+        };
+        for hunk in hunks.iter().filter(|hunk| !hunk.is_resolved()) {
+            if resolutions
+                .forget(hunk)
+                .map_err(|err| BackendError::Other(err.into()))?
+            {
+                num_forgotten += 1;
+            }
+        }
+    }
+    Ok(num_forgotten)
+}
+
+/// Pairs each conflicted hunk with the text that replaced it in
+/// `resolved_content`. Returns nothing if the resolved hunks can't be found in
+/// order.
+fn locate_resolutions<'a>(
+    hunks: &'a [Merge<BString>],
+    resolved_content: &'a [u8],
+) -> Vec<(&'a Merge<BString>, &'a [u8])> {
+    let mut located = vec![];
+    let mut pos = 0;
+    let mut pending_conflict = None;
+    for hunk in hunks {
+        if let Some(text) = hunk.as_resolved() {
+            let remainder = &resolved_content[pos..];
+            if let Some(conflict) = pending_conflict.take() {
+                let Some(offset) = remainder.find(text) else {
+                    return vec![];
+                };
+                located.push((conflict, &remainder[..offset]));
+                pos += offset;
+            } else if !remainder.starts_with(text) {
+                return vec![];
+            }
+            pos += text.len();
+        } else if pending_conflict.replace(hunk).is_some() {
+            // Adjacent conflicted hunks can't be told apart
+            return vec![];
+        }
+    }
+    if let Some(conflict) = pending_conflict {
+        located.push((conflict, &resolved_content[pos..]));
+    } else if pos != resolved_content.len() {
+        return vec![];
+    }
+    located
+}
@@ -32,6 +32,7 @@ use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::commit::Commit;
+use crate::diff::WhitespaceMode;
 use crate::dsl_util;
 use crate::dsl_util::collect_similar;
 use crate::dsl_util::AliasExpandError as _;
@@ -44,7 +45,10 @@ use crate::object_id::HexPrefix;
 use crate::object_id::PrefixResolution;
 use crate::op_store::RemoteRefState;
 use crate::op_store::WorkspaceId;
+use crate::op_walk;
+use crate::repo::ReadonlyRepo;
 use crate::repo::Repo;
+use crate::repo::RepoLoaderError;
 use crate::repo_path::RepoPathUiConverter;
 use crate::revset_parser;
 pub use crate::revset_parser::expect_literal;
@@ -138,6 +142,19 @@ pub trait RevsetFilterExtension: std::fmt::Debug + Any {
     fn matches_commit(&self, commit: &Commit) -> bool;
 }
 
+/// Condition on the cryptographic signature of a commit.
+#[derive(Clone, Debug)]
+pub enum SignaturePredicate {
+    /// Commits carrying a signature, regardless of whether it verifies.
+    Signed,
+    /// Commits with a good signature made by a key whose id or display string
+    /// matches the pattern. The pattern matches exactly unless it has an
+    /// explicit kind.
+    SignedBy(StringPattern),
+    /// Commits with a signature that verifies as good.
+    Good,
+}
+
 #[derive(Clone, Debug)]
 pub enum RevsetFilterPredicate {
     /// Commits with number of parents in the range.
@@ -158,9 +175,13 @@ pub enum RevsetFilterPredicate {
     DiffContains {
         text: StringPattern,
         files: FilesetExpression,
+        /// How whitespace is compared between the matched lines.
+        whitespace: WhitespaceMode,
     },
     /// Commits with conflicts
     HasConflict,
+    /// Commits with a cryptographic signature matching the predicate.
+    Signature(SignaturePredicate),
     /// Custom predicates provided by extensions
     Extension(Rc<dyn RevsetFilterExtension>),
 }
@@ -202,9 +223,22 @@ pub enum RevsetExpression {
         candidates: Rc<RevsetExpression>,
         count: usize,
     },
+    /// Commit in `candidates` that splits them roughly in half.
+    Bisect(Rc<RevsetExpression>),
     Filter(RevsetFilterPredicate),
     /// Marker for subtree that should be intersected as filter.
     AsFilter(Rc<RevsetExpression>),
+    /// Resolves symbols and visibility at the specified operation.
+    AtOperation {
+        operation: String,
+        candidates: Rc<RevsetExpression>,
+    },
+    /// Resolves visibility within the specified repo state.
+    WithinVisibility {
+        candidates: Rc<RevsetExpression>,
+        /// Copy of `repo.view().heads()` at the operation.
+        visible_heads: Vec<CommitId>,
+    },
     Present(Rc<RevsetExpression>),
     NotIn(Rc<RevsetExpression>),
     Union(Rc<RevsetExpression>, Rc<RevsetExpression>),
@@ -295,6 +329,12 @@ impl RevsetExpression {
         })
     }
 
+    /// Commit in `self` that splits `self` roughly in half, i.e. about half of
+    /// the commits in `self` are its ancestors.
+    pub fn bisect(self: &Rc<RevsetExpression>) -> Rc<RevsetExpression> {
+        Rc::new(RevsetExpression::Bisect(self.clone()))
+    }
+
     pub fn filter(predicate: RevsetFilterPredicate) -> Rc<RevsetExpression> {
         Rc::new(RevsetExpression::Filter(predicate))
     }
@@ -544,6 +584,7 @@ pub enum ResolvedExpression {
         candidates: Box<ResolvedExpression>,
         count: usize,
     },
+    Bisect(Box<ResolvedExpression>),
     Union(Box<ResolvedExpression>, Box<ResolvedExpression>),
     /// Intersects `candidates` with `predicate` by filtering.
     FilterWithin {
@@ -694,6 +735,11 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         };
         Ok(candidates.latest(count))
     });
+    map.insert("bisect", |function, context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let candidates = lower_expression(arg, context)?;
+        Ok(candidates.bisect())
+    });
     map.insert("merges", |function, _context| {
         function.expect_no_arguments()?;
         Ok(RevsetExpression::filter(
@@ -764,7 +810,8 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         Ok(RevsetExpression::filter(RevsetFilterPredicate::File(expr)))
     });
     map.insert("diff_contains", |function, context| {
-        let ([text_arg], [files_opt_arg]) = function.expect_arguments()?;
+        let ([text_arg], [files_opt_arg, whitespace_opt_arg]) =
+            function.expect_named_arguments(&["", "files", "whitespace"])?;
         let text = expect_string_pattern(text_arg)?;
         let files = if let Some(files_arg) = files_opt_arg {
             let ctx = context.workspace.as_ref().ok_or_else(|| {
@@ -779,14 +826,60 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
             // https://github.com/martinvonz/jj/issues/2933#issuecomment-1925870731
             FilesetExpression::all()
         };
+        let whitespace = if let Some(whitespace_arg) = whitespace_opt_arg {
+            expect_literal("whitespace mode", whitespace_arg)?
+        } else {
+            WhitespaceMode::Exact
+        };
         Ok(RevsetExpression::filter(
-            RevsetFilterPredicate::DiffContains { text, files },
+            RevsetFilterPredicate::DiffContains {
+                text,
+                files,
+                whitespace,
+            },
         ))
     });
     map.insert("conflict", |function, _context| {
         function.expect_no_arguments()?;
         Ok(RevsetExpression::filter(RevsetFilterPredicate::HasConflict))
     });
+    map.insert("signed", |function, _context| {
+        function.expect_no_arguments()?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::Signature(
+            SignaturePredicate::Signed,
+        )))
+    });
+    map.insert("signed_by", |function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        // Unlike the other string patterns, a bare key must match exactly, so
+        // that e.g. "alice" doesn't accept a key of "malice".
+        let pattern =
+            revset_parser::expect_pattern_with("string pattern", arg, |value, kind| match kind {
+                Some(kind) => StringPattern::from_str_kind(value, kind),
+                None => Ok(StringPattern::exact(value)),
+            })?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::Signature(
+            SignaturePredicate::SignedBy(pattern),
+        )))
+    });
+    map.insert("good_signature", |function, _context| {
+        function.expect_no_arguments()?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::Signature(
+            SignaturePredicate::Good,
+        )))
+    });
+    map.insert("at_operation", |function, context| {
+        let [op_arg, cand_arg] = function.expect_exact_arguments()?;
+        // TODO: Parse "opset" here if we add proper language support.
+        let operation = revset_parser::expect_expression_with(op_arg, |node| {
+            Ok(node.span.as_str().to_owned())
+        })?;
+        let candidates = lower_expression(cand_arg, context)?;
+        Ok(Rc::new(RevsetExpression::AtOperation {
+            operation,
+            candidates,
+        }))
+    });
     map.insert("present", |function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let expression = lower_expression(arg, context)?;
@@ -1063,10 +1156,31 @@ fn try_transform_expression<E>(
                     candidates,
                     count: *count,
                 }),
+            RevsetExpression::Bisect(candidates) => {
+                transform_rec(candidates, pre, post)?.map(RevsetExpression::Bisect)
+            }
             RevsetExpression::Filter(_) => None,
             RevsetExpression::AsFilter(candidates) => {
                 transform_rec(candidates, pre, post)?.map(RevsetExpression::AsFilter)
             }
+            RevsetExpression::AtOperation {
+                operation,
+                candidates,
+            } => transform_rec(candidates, pre, post)?.map(|candidates| {
+                RevsetExpression::AtOperation {
+                    operation: operation.clone(),
+                    candidates,
+                }
+            }),
+            RevsetExpression::WithinVisibility {
+                candidates,
+                visible_heads,
+            } => transform_rec(candidates, pre, post)?.map(|candidates| {
+                RevsetExpression::WithinVisibility {
+                    candidates,
+                    visible_heads: visible_heads.clone(),
+                }
+            }),
             RevsetExpression::Present(candidates) => {
                 transform_rec(candidates, pre, post)?.map(RevsetExpression::Present)
             }
@@ -1466,14 +1580,23 @@ fn make_no_such_symbol_error(repo: &dyn Repo, name: impl Into<String>) -> Revset
 }
 
 pub trait SymbolResolver {
-    fn resolve_symbol(&self, symbol: &str) -> Result<Vec<CommitId>, RevsetResolutionError>;
+    /// Looks up `symbol` in the given `repo`.
+    fn resolve_symbol(
+        &self,
+        repo: &dyn Repo,
+        symbol: &str,
+    ) -> Result<Vec<CommitId>, RevsetResolutionError>;
 }
 
 /// Fails on any attempt to resolve a symbol.
 pub struct FailingSymbolResolver;
 
 impl SymbolResolver for FailingSymbolResolver {
-    fn resolve_symbol(&self, symbol: &str) -> Result<Vec<CommitId>, RevsetResolutionError> {
+    fn resolve_symbol(
+        &self,
+        _repo: &dyn Repo,
+        symbol: &str,
+    ) -> Result<Vec<CommitId>, RevsetResolutionError> {
         Err(RevsetResolutionError::NoSuchRevision {
             name: format!(
                 "Won't resolve symbol {symbol:?}. When creating revsets programmatically, avoid \
@@ -1624,7 +1747,6 @@ pub trait SymbolResolverExtension {
 /// Resolves bookmarks, remote bookmarks, tags, git refs, and full and
 /// abbreviated commit and change ids.
 pub struct DefaultSymbolResolver<'a> {
-    repo: &'a dyn Repo,
     commit_id_resolver: CommitPrefixResolver<'a>,
     change_id_resolver: ChangePrefixResolver<'a>,
     extensions: Vec<Box<dyn PartialSymbolResolver + 'a>>,
@@ -1633,7 +1755,6 @@ pub struct DefaultSymbolResolver<'a> {
 impl<'a> DefaultSymbolResolver<'a> {
     pub fn new(repo: &'a dyn Repo, extensions: &[impl AsRef<dyn SymbolResolverExtension>]) -> Self {
         DefaultSymbolResolver {
-            repo,
             commit_id_resolver: Default::default(),
             change_id_resolver: Default::default(),
             extensions: extensions
@@ -1661,18 +1782,22 @@ impl<'a> DefaultSymbolResolver<'a> {
 }
 
 impl SymbolResolver for DefaultSymbolResolver<'_> {
-    fn resolve_symbol(&self, symbol: &str) -> Result<Vec<CommitId>, RevsetResolutionError> {
+    fn resolve_symbol(
+        &self,
+        repo: &dyn Repo,
+        symbol: &str,
+    ) -> Result<Vec<CommitId>, RevsetResolutionError> {
         if symbol.is_empty() {
             return Err(RevsetResolutionError::EmptyString);
         }
 
         for partial_resolver in self.partial_resolvers() {
-            if let Some(ids) = partial_resolver.resolve_symbol(self.repo, symbol)? {
+            if let Some(ids) = partial_resolver.resolve_symbol(repo, symbol)? {
                 return Ok(ids);
             }
         }
 
-        Err(make_no_such_symbol_error(self.repo, symbol))
+        Err(make_no_such_symbol_error(repo, symbol))
     }
 }
 
@@ -1682,7 +1807,7 @@ fn resolve_commit_ref(
     symbol_resolver: &dyn SymbolResolver,
 ) -> Result<Vec<CommitId>, RevsetResolutionError> {
     match commit_ref {
-        RevsetCommitRef::Symbol(symbol) => symbol_resolver.resolve_symbol(symbol),
+        RevsetCommitRef::Symbol(symbol) => symbol_resolver.resolve_symbol(repo, symbol),
         RevsetCommitRef::RemoteSymbol { name, remote } => {
             resolve_remote_bookmark(repo, name, remote)
                 .ok_or_else(|| make_no_such_symbol_error(repo, format!("{name}@{remote}")))
@@ -1765,6 +1890,20 @@ fn resolve_symbols(
     Ok(try_transform_expression(
         &expression,
         |expression| match expression.as_ref() {
+            // 'at_operation(op, x)' switches symbol resolution contexts.
+            RevsetExpression::AtOperation {
+                operation,
+                candidates,
+            } => {
+                let repo = reload_repo_at_operation(repo, operation)?;
+                let candidates =
+                    resolve_symbols(repo.as_ref(), candidates.clone(), symbol_resolver)?;
+                let visible_heads = repo.view().heads().iter().cloned().collect();
+                Ok(Some(Rc::new(RevsetExpression::WithinVisibility {
+                    candidates,
+                    visible_heads,
+                })))
+            }
             // 'present(x)' opens new symbol resolution scope to map error to 'none()'.
             RevsetExpression::Present(candidates) => {
                 resolve_symbols(repo, candidates.clone(), symbol_resolver)
@@ -1795,6 +1934,24 @@ fn resolve_symbols(
     .unwrap_or(expression))
 }
 
+fn reload_repo_at_operation(
+    repo: &dyn Repo,
+    op_str: &str,
+) -> Result<Arc<ReadonlyRepo>, RevsetResolutionError> {
+    // TODO: Maybe we should ensure that the resolved operation is an ancestor
+    // of the current operation. If it weren't, there might be commits unknown
+    // to the outer repo.
+    let base_repo = repo.base_repo();
+    let operation = op_walk::resolve_op_with_repo(base_repo, op_str)
+        .map_err(|err| RevsetResolutionError::Other(err.into()))?;
+    base_repo.reload_at(&operation).map_err(|err| match err {
+        RepoLoaderError::Backend(err) => RevsetResolutionError::StoreError(err),
+        RepoLoaderError::IndexRead(_)
+        | RepoLoaderError::OpHeadResolution(_)
+        | RepoLoaderError::OpStore(_) => RevsetResolutionError::Other(err.into()),
+    })
+}
+
 /// Inserts implicit `all()` and `visible_heads()` nodes to the `expression`.
 ///
 /// Symbols and commit refs in the `expression` should have been resolved.
@@ -1805,9 +1962,6 @@ fn resolve_symbols(
 /// return type `ResolvedExpression` is stricter than `RevsetExpression`,
 /// and isn't designed for such transformation.
 fn resolve_visibility(repo: &dyn Repo, expression: &RevsetExpression) -> ResolvedExpression {
-    // If we add "operation" scope (#1283), visible_heads might be translated to
-    // `RevsetExpression::WithinOperation(visible_heads, expression)` node to
-    // evaluate filter predicates and "all()" against that scope.
     let context = VisibilityResolutionContext {
         visible_heads: &repo.view().heads().iter().cloned().collect_vec(),
     };
@@ -1868,6 +2022,9 @@ impl VisibilityResolutionContext<'_> {
                 candidates: self.resolve(candidates).into(),
                 count: *count,
             },
+            RevsetExpression::Bisect(candidates) => {
+                ResolvedExpression::Bisect(self.resolve(candidates).into())
+            }
             RevsetExpression::Filter(_) | RevsetExpression::AsFilter(_) => {
                 // Top-level filter without intersection: e.g. "~author(_)" is represented as
                 // `AsFilter(NotIn(Filter(Author(_))))`.
@@ -1876,6 +2033,16 @@ impl VisibilityResolutionContext<'_> {
                     predicate: self.resolve_predicate(expression),
                 }
             }
+            RevsetExpression::AtOperation { .. } => {
+                panic!("Expression '{expression:?}' should have been resolved by caller");
+            }
+            RevsetExpression::WithinVisibility {
+                candidates,
+                visible_heads,
+            } => {
+                let context = VisibilityResolutionContext { visible_heads };
+                context.resolve(candidates)
+            }
             RevsetExpression::Present(_) => {
                 panic!("Expression '{expression:?}' should have been resolved by caller");
             }
@@ -1945,9 +2112,14 @@ impl VisibilityResolutionContext<'_> {
             | RevsetExpression::Reachable { .. }
             | RevsetExpression::Heads(_)
             | RevsetExpression::Roots(_)
-            | RevsetExpression::Latest { .. } => {
+            | RevsetExpression::Latest { .. }
+            | RevsetExpression::Bisect(_)
+            | RevsetExpression::WithinVisibility { .. } => {
                 ResolvedPredicateExpression::Set(self.resolve(expression).into())
             }
+            RevsetExpression::AtOperation { .. } => {
+                panic!("Expression '{expression:?}' should have been resolved by caller");
+            }
             RevsetExpression::Filter(predicate) => {
                 ResolvedPredicateExpression::Filter(predicate.clone())
             }
@@ -2642,6 +2814,24 @@ mod tests {
             parse("root()").unwrap(),
             @"CommitRef(Root)");
         assert!(parse("root(a)").is_err());
+        insta::assert_debug_snapshot!(
+            parse("at_operation(@-, foo)").unwrap(), @r###"
+        AtOperation {
+            operation: "@-",
+            candidates: CommitRef(Symbol("foo")),
+        }
+        "###);
+        insta::assert_debug_snapshot!(
+            parse("at_operation(abc123, ::foo)").unwrap(), @r###"
+        AtOperation {
+            operation: "abc123",
+            candidates: Ancestors {
+                heads: CommitRef(Symbol("foo")),
+                generation: 0..18446744073709551615,
+            },
+        }
+        "###);
+        assert!(parse("at_operation(@-)").is_err());
         insta::assert_debug_snapshot!(
             parse(r#"description("")"#).unwrap(),
             @r###"Filter(Description(Substring("")))"###);
@@ -2658,6 +2848,20 @@ mod tests {
         insta::assert_debug_snapshot!(
             parse("mine()").unwrap(),
             @r###"Filter(Author(ExactI("test.user@example.com")))"###);
+        insta::assert_debug_snapshot!(
+            parse("signed()").unwrap(),
+            @"Filter(Signature(Signed))");
+        insta::assert_debug_snapshot!(
+            parse("signed_by(foo)").unwrap(),
+            @r###"Filter(Signature(SignedBy(Exact("foo"))))"###);
+        insta::assert_debug_snapshot!(
+            parse("signed_by(substring:foo)").unwrap(),
+            @r###"Filter(Signature(SignedBy(Substring("foo"))))"###);
+        insta::assert_debug_snapshot!(
+            parse("good_signature()").unwrap(),
+            @"Filter(Signature(Good))");
+        assert!(parse("signed(foo)").is_err());
+        assert!(parse("signed_by()").is_err());
         insta::assert_debug_snapshot!(
             parse_with_workspace("empty()", &WorkspaceId::default()).unwrap(),
             @"NotIn(Filter(File(All)))");
@@ -16,8 +16,10 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::iter;
 use std::sync::Arc;
 
+use futures::executor::block_on_stream;
 use futures::StreamExt;
 use indexmap::IndexMap;
 use indexmap::IndexSet;
@@ -32,6 +34,7 @@ use crate::backend::MergedTreeId;
 use crate::commit::Commit;
 use crate::commit::CommitIteratorExt;
 use crate::commit_builder::CommitBuilder;
+use crate::copies::CopyRecords;
 use crate::dag_walk;
 use crate::index::Index;
 use crate::matchers::Matcher;
@@ -48,14 +51,138 @@ use crate::revset::RevsetIteratorExt;
 use crate::settings::UserSettings;
 use crate::store::Store;
 
-/// Merges `commits` and tries to resolve any conflicts recursively.
+/// Merges `commits` and tries to resolve any conflicts recursively. Changes
+/// are carried across renames when merging two commits.
 #[instrument(skip(repo))]
 pub fn merge_commit_trees(repo: &dyn Repo, commits: &[Commit]) -> BackendResult<MergedTree> {
     if let [commit] = commits {
-        commit.tree()
-    } else {
-        merge_commit_trees_no_resolve_without_repo(repo.store(), repo.index(), commits)?.resolve()
+        return commit.tree();
+    }
+    let merged_tree =
+        merge_commit_trees_no_resolve_without_repo(repo.store(), repo.index(), commits)?
+            .resolve()?;
+    let [commit1, commit2] = commits else {
+        return Ok(merged_tree);
+    };
+    if !merged_tree.has_conflict() || !detect_renames(repo)? {
+        return Ok(merged_tree);
     }
+    let ancestor_ids = repo
+        .index()
+        .common_ancestors(&[commit1.id().clone()], &[commit2.id().clone()]);
+    let ancestors: Vec<_> = ancestor_ids
+        .iter()
+        .map(|id| repo.store().get_commit(id))
+        .try_collect()?;
+    let ancestor_tree =
+        merge_commit_trees_no_resolve_without_repo(repo.store(), repo.index(), &ancestors)?;
+    merge_conflicted_trees_across_renames(
+        repo,
+        merged_tree,
+        &commit1.tree()?,
+        &ancestor_tree,
+        &commit2.tree()?,
+        &ancestors,
+        &[commit1, commit2],
+    )
+}
+
+/// Merges `tree` with `other_tree` like `MergedTree::merge()`, but carries
+/// changes across files renamed on either side. The `base` and `sides` are the
+/// commits the trees come from, which are used for finding the renames.
+///
+/// Since detecting copies can be expensive, it's only done if the plain merge
+/// has conflicts that renames could resolve.
+pub fn merge_trees_across_renames(
+    repo: &dyn Repo,
+    tree: &MergedTree,
+    base_tree: &MergedTree,
+    other_tree: &MergedTree,
+    base: &[Commit],
+    sides: &[&Commit],
+) -> BackendResult<MergedTree> {
+    let merged_tree = tree.merge(base_tree, other_tree)?;
+    merge_conflicted_trees_across_renames(
+        repo,
+        merged_tree,
+        tree,
+        base_tree,
+        other_tree,
+        base,
+        sides,
+    )
+}
+
+/// Merges the trees again across renames if the `merged_tree` resulting from
+/// the plain merge has conflicts that renames could resolve. Otherwise returns
+/// the `merged_tree` as is.
+fn merge_conflicted_trees_across_renames(
+    repo: &dyn Repo,
+    merged_tree: MergedTree,
+    tree: &MergedTree,
+    base_tree: &MergedTree,
+    other_tree: &MergedTree,
+    base: &[Commit],
+    sides: &[&Commit],
+) -> BackendResult<MergedTree> {
+    if !merged_tree.has_conflict() || !has_rename_conflicts(&merged_tree) {
+        return Ok(merged_tree);
+    }
+    let copy_records = merge_copy_records(repo, base, sides)?;
+    if copy_records.iter().next().is_none() {
+        return Ok(merged_tree);
+    }
+    tree.merge_with_copies(base_tree, other_tree, &copy_records)
+}
+
+/// Whether the tree has conflicts where a file is absent on some side, i.e.
+/// modify/delete or delete/add conflicts. Changes are carried across a rename
+/// only if the renamed file was modified on the other side, which results in
+/// such a conflict when the rename isn't detected.
+fn has_rename_conflicts(tree: &MergedTree) -> bool {
+    tree.conflicts()
+        .any(|(_, value)| value.iter().any(|term| term.is_none()))
+}
+
+/// Whether renames should be detected when merging, according to the
+/// `merge.detect-renames` setting.
+fn detect_renames(repo: &dyn Repo) -> BackendResult<bool> {
+    repo.base_repo()
+        .settings()
+        .merge_detect_renames()
+        .map_err(|err| BackendError::Other(err.into()))
+}
+
+/// Collects the copies from the `base` commits to the `sides` for carrying
+/// changes across renames when merging their trees. Returns no copies if
+/// that's disabled by the `merge.detect-renames` setting.
+pub fn merge_copy_records(
+    repo: &dyn Repo,
+    base: &[Commit],
+    sides: &[&Commit],
+) -> BackendResult<CopyRecords> {
+    let mut copy_records = CopyRecords::default();
+    if !detect_renames(repo)? {
+        return Ok(copy_records);
+    }
+    let base_ids = base.iter().ids().cloned().collect_vec();
+    for side in sides {
+        // Start from the common ancestors in case the side isn't a descendant
+        // of the base.
+        for root_id in repo
+            .index()
+            .common_ancestors(&base_ids, &[side.id().clone()])
+        {
+            if root_id == *side.id() {
+                continue;
+            }
+            let stream = repo
+                .store()
+                .get_copy_records(None, &root_id, side.id(), repo.index())?;
+            copy_records.add_records(block_on_stream(stream))?;
+        }
+    }
+    Ok(copy_records)
 }
 
 /// Merges `commits` without attempting to resolve file conflicts.
@@ -252,9 +379,21 @@ impl<'repo> CommitRewriter<'repo> {
             let old_base_tree = merge_commit_trees(self.mut_repo, &old_parents)?;
             let new_base_tree = merge_commit_trees(self.mut_repo, &new_parents)?;
             let old_tree = self.old_commit.tree()?;
+            let sides = new_parents
+                .iter()
+                .chain(iter::once(&self.old_commit))
+                .collect_vec();
             (
                 old_base_tree.id() == *self.old_commit.tree_id(),
-                new_base_tree.merge(&old_base_tree, &old_tree)?.id(),
+                merge_trees_across_renames(
+                    self.mut_repo,
+                    &new_base_tree,
+                    &old_base_tree,
+                    &old_tree,
+                    &old_parents,
+                    &sides,
+                )?
+                .id(),
             )
         };
         // Ensure we don't abandon commits with multiple parents (merge commits), even
@@ -26,6 +26,7 @@ use crate::backend::ChangeId;
 use crate::backend::Commit;
 use crate::backend::Signature;
 use crate::backend::Timestamp;
+use crate::conflicts::ConflictMarkerStyle;
 use crate::fmt_util::binary_prefix;
 use crate::fsmonitor::FsmonitorSettings;
 use crate::signing::SignBehavior;
@@ -39,13 +40,33 @@ pub struct UserSettings {
 
 #[derive(Debug, Clone)]
 pub struct RepoSettings {
-    _config: config::Config,
+    config: config::Config,
+}
+
+impl RepoSettings {
+    /// Whether merging trees should carry changes across renamed files.
+    pub fn merge_detect_renames(&self) -> Result<bool, config::ConfigError> {
+        self.config
+            .get_bool("merge.detect-renames")
+            .optional()
+            .map(|value| value.unwrap_or(true))
+    }
+
+    /// Whether recorded conflict resolutions should be reused when merging
+    /// trees.
+    pub fn merge_reuse_resolutions(&self) -> Result<bool, config::ConfigError> {
+        self.config
+            .get_bool("merge.reuse-resolutions")
+            .optional()
+            .map(|value| value.unwrap_or(false))
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct GitSettings {
     pub auto_local_bookmark: bool,
     pub abandon_unreachable_commits: bool,
+    pub write_jj_headers: bool,
 }
 
 impl GitSettings {
@@ -55,6 +76,7 @@ impl GitSettings {
             abandon_unreachable_commits: config
                 .get_bool("git.abandon-unreachable-commits")
                 .unwrap_or(true),
+            write_jj_headers: config.get_bool("git.write-jj-headers").unwrap_or(false),
         }
     }
 }
@@ -64,6 +86,7 @@ impl Default for GitSettings {
         GitSettings {
             auto_local_bookmark: false,
             abandon_unreachable_commits: true,
+            write_jj_headers: false,
         }
     }
 }
@@ -144,7 +167,7 @@ impl UserSettings {
     // https://github.com/martinvonz/jj/issues/616#issuecomment-1345170699
     pub fn with_repo(&self, _repo_path: &Path) -> Result<RepoSettings, config::ConfigError> {
         let config = self.config.clone();
-        Ok(RepoSettings { _config: config })
+        Ok(RepoSettings { config })
     }
 
     pub fn get_rng(&self) -> Arc<JJRng> {
@@ -221,6 +244,18 @@ impl UserSettings {
             .unwrap_or(false)
     }
 
+    /// Whether the default index should record paths changed by each commit.
+    /// This takes effect when the index is rebuilt from scratch.
+    pub fn index_changed_paths(&self) -> bool {
+        self.config.get_bool("index.changed-paths").unwrap_or(false)
+    }
+
+    /// Whether garbage collection of the local backend and operation store
+    /// should move the remaining objects into compressed packs.
+    pub fn gc_pack_objects(&self) -> bool {
+        self.config.get_bool("gc.pack-objects").unwrap_or(false)
+    }
+
     pub fn config(&self) -> &config::Config {
         &self.config
     }
@@ -242,6 +277,13 @@ impl UserSettings {
         }
     }
 
+    pub fn conflict_marker_style(&self) -> Result<ConflictMarkerStyle, config::ConfigError> {
+        self.config
+            .get("ui.conflict-marker-style")
+            .optional()
+            .map(Option::unwrap_or_default)
+    }
+
     // separate from sign_settings as those two are needed in pretty different
     // places
     pub fn signing_backend(&self) -> Option<String> {
@@ -39,12 +39,15 @@ use crate::content_hash::blake2b_hash;
 use crate::dag_walk;
 use crate::file_util::persist_content_addressed_temp_file;
 use crate::file_util::IoResultExt as _;
-use crate::file_util::PathError;
 use crate::merge::Merge;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
 use crate::object_id::PrefixResolution;
+use crate::object_pack::gc_objects;
+use crate::object_pack::LooseObjectDir;
+use crate::object_pack::PackSet;
 use crate::op_store;
+use crate::op_store::BisectState;
 use crate::op_store::OpStore;
 use crate::op_store::OpStoreError;
 use crate::op_store::OpStoreResult;
@@ -58,11 +61,16 @@ use crate::op_store::RemoteView;
 use crate::op_store::View;
 use crate::op_store::ViewId;
 use crate::op_store::WorkspaceId;
+use crate::settings::UserSettings;
 
 // BLAKE2b-512 hash length in bytes
 const OPERATION_ID_LENGTH: usize = 64;
 const VIEW_ID_LENGTH: usize = 64;
 
+// Kinds of objects in the packs
+const OPERATION_KIND: u8 = 0;
+const VIEW_KIND: u8 = 1;
+
 #[derive(Debug, Error)]
 #[error("Failed to read {kind} with ID {id}")]
 struct DecodeError {
@@ -83,6 +91,8 @@ pub struct SimpleOpStore {
     path: PathBuf,
     empty_view_id: ViewId,
     root_operation_id: OperationId,
+    packs: PackSet,
+    pack_objects: bool,
 }
 
 impl SimpleOpStore {
@@ -91,18 +101,20 @@ impl SimpleOpStore {
     }
 
     /// Creates an empty OpStore, panics if it already exists
-    pub fn init(store_path: &Path) -> Self {
+    pub fn init(settings: &UserSettings, store_path: &Path) -> Self {
         fs::create_dir(store_path.join("views")).unwrap();
         fs::create_dir(store_path.join("operations")).unwrap();
-        Self::load(store_path)
+        Self::load(settings, store_path)
     }
 
     /// Load an existing OpStore
-    pub fn load(store_path: &Path) -> Self {
+    pub fn load(settings: &UserSettings, store_path: &Path) -> Self {
         SimpleOpStore {
             path: store_path.to_path_buf(),
             empty_view_id: ViewId::from_bytes(&[0; VIEW_ID_LENGTH]),
             root_operation_id: OperationId::from_bytes(&[0; OPERATION_ID_LENGTH]),
+            packs: PackSet::new(store_path.join("packs")),
+            pack_objects: settings.gc_pack_objects(),
         }
     }
 
@@ -113,6 +125,30 @@ impl SimpleOpStore {
     fn operation_path(&self, id: &OperationId) -> PathBuf {
         self.path.join("operations").join(id.hex())
     }
+
+    /// Reads the data of a loose object, or the packed object if there's no
+    /// loose file.
+    fn read_object_data(
+        &self,
+        kind: u8,
+        path: &Path,
+        id: &impl ObjectId,
+    ) -> OpStoreResult<Vec<u8>> {
+        match fs::read(path) {
+            Ok(buf) => Ok(buf),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let packed = self.packs.read(kind, id.as_bytes()).map_err(|err| {
+                    OpStoreError::ReadObject {
+                        object_type: id.object_type(),
+                        hash: id.hex(),
+                        source: Box::new(err),
+                    }
+                })?;
+                packed.ok_or_else(|| io_to_read_error(err, id))
+            }
+            Err(err) => Err(io_to_read_error(err, id)),
+        }
+    }
 }
 
 impl OpStore for SimpleOpStore {
@@ -134,7 +170,7 @@ impl OpStore for SimpleOpStore {
         }
 
         let path = self.view_path(id);
-        let buf = fs::read(path).map_err(|err| io_to_read_error(err, id))?;
+        let buf = self.read_object_data(VIEW_KIND, &path, id)?;
 
         let proto = crate::protos::op_store::View::decode(&*buf).map_err(|err| DecodeError {
             kind: "view",
@@ -167,7 +203,7 @@ impl OpStore for SimpleOpStore {
         }
 
         let path = self.operation_path(id);
-        let buf = fs::read(path).map_err(|err| io_to_read_error(err, id))?;
+        let buf = self.read_object_data(OPERATION_KIND, &path, id)?;
 
         let proto =
             crate::protos::op_store::Operation::decode(&*buf).map_err(|err| DecodeError {
@@ -212,7 +248,14 @@ impl OpStore for SimpleOpStore {
             let hex_prefix = prefix.hex();
             if hex_prefix.len() == OPERATION_ID_LENGTH * 2 {
                 // Fast path for full-length ID
-                if matches_root || op_dir.join(hex_prefix).try_exists()? {
+                let id_bytes = prefix.as_full_bytes().unwrap();
+                if matches_root
+                    || op_dir.join(hex_prefix).try_exists()?
+                    || self
+                        .packs
+                        .contains(OPERATION_KIND, id_bytes)
+                        .map_err(io::Error::other)?
+                {
                     let id = OperationId::from_bytes(prefix.as_full_bytes().unwrap());
                     return Ok(PrefixResolution::SingleMatch(id));
                 } else {
@@ -236,6 +279,16 @@ impl OpStore for SimpleOpStore {
                 }
                 matched = Some(id);
             }
+            for id_bytes in self.packs.ids(OPERATION_KIND).map_err(io::Error::other)? {
+                let id = OperationId::new(id_bytes);
+                if !prefix.matches(&id) || matched.as_ref() == Some(&id) {
+                    continue;
+                }
+                if matched.is_some() {
+                    return Ok(PrefixResolution::AmbiguousMatch);
+                }
+                matched = Some(id);
+            }
             if let Some(id) = matched {
                 Ok(PrefixResolution::SingleMatch(id))
             } else {
@@ -249,29 +302,6 @@ impl OpStore for SimpleOpStore {
 
     #[tracing::instrument(skip(self))]
     fn gc(&self, head_ids: &[OperationId], keep_newer: SystemTime) -> OpStoreResult<()> {
-        let to_op_id = |entry: &fs::DirEntry| -> Option<OperationId> {
-            let name = entry.file_name().into_string().ok()?;
-            OperationId::try_from_hex(&name).ok()
-        };
-        let to_view_id = |entry: &fs::DirEntry| -> Option<ViewId> {
-            let name = entry.file_name().into_string().ok()?;
-            ViewId::try_from_hex(&name).ok()
-        };
-        let remove_file_if_not_new = |entry: &fs::DirEntry| -> Result<(), PathError> {
-            let path = entry.path();
-            // Check timestamp, but there's still TOCTOU problem if an existing
-            // file is renewed.
-            let metadata = entry.metadata().context(&path)?;
-            let mtime = metadata.modified().expect("unsupported platform?");
-            if mtime > keep_newer {
-                tracing::trace!(?path, "not removing");
-                Ok(())
-            } else {
-                tracing::trace!(?path, "removing");
-                fs::remove_file(&path).context(&path)
-            }
-        };
-
         // Reachable objects are resolved without considering the keep_newer
         // parameter. We could collect ancestors of the "new" operations here,
         // but more files can be added anyway after that.
@@ -290,44 +320,37 @@ impl OpStore for SimpleOpStore {
             "collected reachable objects"
         );
 
-        let prune_ops = || -> Result<(), PathError> {
-            let op_dir = self.path.join("operations");
-            for entry in op_dir.read_dir().context(&op_dir)? {
-                let entry = entry.context(&op_dir)?;
-                let Some(id) = to_op_id(&entry) else {
-                    tracing::trace!(?entry, "skipping invalid file name");
-                    continue;
-                };
-                if reachable_ops.contains_key(&id) {
-                    continue;
-                }
-                // If the operation was added after collecting reachable_views,
-                // its view mtime would also be renewed. So there's no need to
-                // update the reachable_views set to preserve the view.
-                remove_file_if_not_new(&entry)?;
-            }
-            Ok(())
-        };
-        prune_ops().map_err(|err| OpStoreError::Other(err.into()))?;
-
-        let prune_views = || -> Result<(), PathError> {
-            let view_dir = self.path.join("views");
-            for entry in view_dir.read_dir().context(&view_dir)? {
-                let entry = entry.context(&view_dir)?;
-                let Some(id) = to_view_id(&entry) else {
-                    tracing::trace!(?entry, "skipping invalid file name");
-                    continue;
-                };
-                if reachable_views.contains(&id) {
-                    continue;
-                }
-                remove_file_if_not_new(&entry)?;
-            }
-            Ok(())
-        };
-        prune_views().map_err(|err| OpStoreError::Other(err.into()))?;
-
-        Ok(())
+        // If an operation was added after collecting reachable_views, its view
+        // mtime would also be renewed. So there's no need to update the
+        // reachable_views set to preserve the view.
+        let reachable = itertools::chain(
+            reachable_ops
+                .keys()
+                .map(|id| (OPERATION_KIND, id.to_bytes())),
+            reachable_views.iter().map(|id| (VIEW_KIND, id.to_bytes())),
+        )
+        .collect();
+        let read_plain = |path: &Path| fs::read(path);
+        let loose_dirs = [
+            LooseObjectDir {
+                kind: OPERATION_KIND,
+                path: self.path.join("operations"),
+                read: &read_plain,
+            },
+            LooseObjectDir {
+                kind: VIEW_KIND,
+                path: self.path.join("views"),
+                read: &read_plain,
+            },
+        ];
+        gc_objects(
+            &self.packs,
+            &loose_dirs,
+            &reachable,
+            self.pack_objects,
+            keep_newer,
+        )
+        .map_err(|err| OpStoreError::Other(err.into()))
     }
 }
 
@@ -455,6 +478,8 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
 
     proto.git_head = ref_target_to_proto(&view.git_head);
 
+    proto.bisect = view.bisect.as_ref().map(bisect_state_to_proto);
+
     proto
 }
 
@@ -505,9 +530,29 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
         migrate_git_refs_to_remote(&mut view);
     }
 
+    view.bisect = proto.bisect.map(bisect_state_from_proto);
+
     view
 }
 
+fn bisect_state_to_proto(state: &BisectState) -> crate::protos::op_store::BisectState {
+    let to_bytes = |ids: &[CommitId]| ids.iter().map(|id| id.to_bytes()).collect();
+    crate::protos::op_store::BisectState {
+        good: to_bytes(&state.good),
+        bad: to_bytes(&state.bad),
+        skipped: to_bytes(&state.skipped),
+    }
+}
+
+fn bisect_state_from_proto(proto: crate::protos::op_store::BisectState) -> BisectState {
+    let from_bytes = |ids: Vec<Vec<u8>>| ids.into_iter().map(CommitId::new).collect();
+    BisectState {
+        good: from_bytes(proto.good),
+        bad: from_bytes(proto.bad),
+        skipped: from_bytes(proto.skipped),
+    }
+}
+
 fn bookmark_views_to_proto_legacy(
     local_bookmarks: &BTreeMap<String, RefTarget>,
     remote_views: &BTreeMap<String, RemoteView>,
@@ -768,6 +813,7 @@ mod tests {
                 WorkspaceId::default() => default_wc_commit_id,
                 WorkspaceId::new("test".to_string()) => test_wc_commit_id,
             },
+            bisect: None,
         }
     }
 
@@ -804,7 +850,7 @@ mod tests {
         // Test exact output so we detect regressions in compatibility
         assert_snapshot!(
             ViewId::new(blake2b_hash(&create_view()).to_vec()).hex(),
-            @"f426676b3a2f7c6b9ec8677cb05ed249d0d244ab7e86a7c51117e2d8a4829db65e55970c761231e2107d303bf3d33a1f2afdd4ed2181f223e99753674b20a35e"
+            @"797913c2593c8d9ab5acb398c5ab7d1f22ce68350c4a2ef4d473944aa4e3547cf723717ce1971239f7f6734fdfdff50865f8ae4b2b8f405424a09297f5dddc1c"
         );
     }
 
@@ -820,17 +866,44 @@ mod tests {
     #[test]
     fn test_read_write_view() {
         let temp_dir = testutils::new_temp_dir();
-        let store = SimpleOpStore::init(temp_dir.path());
+        let store = SimpleOpStore::init(
+            &UserSettings::from_config(config::Config::default()),
+            temp_dir.path(),
+        );
         let view = create_view();
         let view_id = store.write_view(&view).unwrap();
         let read_view = store.read_view(&view_id).unwrap();
         assert_eq!(read_view, view);
     }
 
+    #[test]
+    fn test_read_write_view_with_bisect() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = SimpleOpStore::init(
+            &UserSettings::from_config(config::Config::default()),
+            temp_dir.path(),
+        );
+        let view = View {
+            bisect: Some(BisectState {
+                good: vec![CommitId::from_hex("aaa111")],
+                bad: vec![CommitId::from_hex("bbb111"), CommitId::from_hex("bbb222")],
+                skipped: vec![CommitId::from_hex("ccc111")],
+            }),
+            ..create_view()
+        };
+        let view_id = store.write_view(&view).unwrap();
+        assert_ne!(view_id, store.write_view(&create_view()).unwrap());
+        let read_view = store.read_view(&view_id).unwrap();
+        assert_eq!(read_view, view);
+    }
+
     #[test]
     fn test_read_write_operation() {
         let temp_dir = testutils::new_temp_dir();
-        let store = SimpleOpStore::init(temp_dir.path());
+        let store = SimpleOpStore::init(
+            &UserSettings::from_config(config::Config::default()),
+            temp_dir.path(),
+        );
         let operation = create_operation();
         let op_id = store.write_operation(&operation).unwrap();
         let read_operation = store.read_operation(&op_id).unwrap();
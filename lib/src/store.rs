@@ -23,7 +23,9 @@ use std::sync::Mutex;
 use std::time::SystemTime;
 
 use clru::CLruCache;
+use futures::stream;
 use futures::stream::BoxStream;
+use futures::StreamExt as _;
 use pollster::FutureExt;
 
 use crate::backend;
@@ -39,12 +41,14 @@ use crate::backend::SigningFn;
 use crate::backend::SymlinkId;
 use crate::backend::TreeId;
 use crate::commit::Commit;
+use crate::copies;
 use crate::index::Index;
 use crate::merge::Merge;
 use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
+use crate::rerere::ResolutionStore;
 use crate::signing::Signer;
 use crate::tree::Tree;
 use crate::tree_builder::TreeBuilder;
@@ -59,6 +63,7 @@ const TREE_CACHE_CAPACITY: usize = 1000;
 pub struct Store {
     backend: Box<dyn Backend>,
     signer: Signer,
+    resolution_store: Option<ResolutionStore>,
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
 }
@@ -72,10 +77,15 @@ impl Debug for Store {
 }
 
 impl Store {
-    pub fn new(backend: Box<dyn Backend>, signer: Signer) -> Arc<Self> {
+    pub fn new(
+        backend: Box<dyn Backend>,
+        signer: Signer,
+        resolution_store: Option<ResolutionStore>,
+    ) -> Arc<Self> {
         Arc::new(Store {
             backend,
             signer,
+            resolution_store,
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
         })
@@ -85,17 +95,38 @@ impl Store {
         self.backend.as_any()
     }
 
+    /// Name of the commit backend.
+    pub fn backend_name(&self) -> &str {
+        self.backend.name()
+    }
+
     pub fn signer(&self) -> &Signer {
         &self.signer
     }
 
+    /// The recorded conflict resolutions, if they're enabled for this store.
+    pub fn resolution_store(&self) -> Option<&ResolutionStore> {
+        self.resolution_store.as_ref()
+    }
+
+    /// Get copy records for the dag range `root..head` like
+    /// [`Backend::get_copy_records()`], including the copies recorded in the
+    /// commits, which are looked up in the `index`.
+    ///
+    /// Backends which detect copies don't record them in commits, so only
+    /// one kind of records is expected to be returned.
     pub fn get_copy_records(
-        &self,
+        self: &Arc<Self>,
         paths: Option<&[RepoPathBuf]>,
         root: &CommitId,
         head: &CommitId,
+        index: &dyn Index,
     ) -> BackendResult<BoxStream<BackendResult<CopyRecord>>> {
-        self.backend.get_copy_records(paths, root, head)
+        let recorded = copies::recorded_copy_records(self, index, paths, root, head)?;
+        let detected = self.backend.get_copy_records(paths, root, head)?;
+        Ok(Box::pin(
+            stream::iter(recorded.into_iter().map(Ok)).chain(detected),
+        ))
     }
 
     pub fn commit_id_length(&self) -> usize {
@@ -15,7 +15,26 @@
 #![allow(missing_docs)]
 
 use std::fmt::Debug;
+use std::path::PathBuf;
 
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SubmoduleStoreError {
+    #[error("Invalid submodule name: {0}")]
+    InvalidName(String),
+}
+
+/// Stores the repositories backing the submodules of a repo.
 pub trait SubmoduleStore: Send + Sync + Debug {
     fn name(&self) -> &str;
+
+    /// Returns the path to the repository of the named submodule. The
+    /// repository doesn't exist until the submodule is initialized.
+    fn repo_path(&self, submodule_name: &str) -> Result<PathBuf, SubmoduleStoreError>;
+
+    /// Returns true if the repository of the named submodule exists.
+    fn is_initialized(&self, submodule_name: &str) -> Result<bool, SubmoduleStoreError> {
+        Ok(self.repo_path(submodule_name)?.exists())
+    }
 }
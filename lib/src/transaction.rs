@@ -30,6 +30,7 @@ use crate::repo::ReadonlyRepo;
 use crate::repo::Repo;
 use crate::repo::RepoLoader;
 use crate::repo::RepoLoaderError;
+use crate::rerere::record_resolutions;
 use crate::settings::UserSettings;
 use crate::view::View;
 
@@ -110,13 +111,26 @@ impl Transaction {
     /// That means that a repo can be loaded at the operation, but the
     /// operation will not be seen when loading the repo at head.
     pub fn write(mut self, description: impl Into<String>) -> UnpublishedOperation {
-        let mut_repo = self.mut_repo;
+        let mut mut_repo = self.mut_repo;
         // TODO: Should we instead just do the rebasing here if necessary?
         assert!(
             !mut_repo.has_rewrites(),
             "BUG: Descendants have not been rebased after the last rewrites."
         );
         let base_repo = mut_repo.base_repo().clone();
+        // Resolutions are only recorded here so that discarded transactions
+        // don't leave any behind.
+        let store = base_repo.store();
+        if let Some(resolution_store) = store.resolution_store() {
+            for (conflicted, resolved) in mut_repo.take_resolved_conflicts() {
+                let result = conflicted.tree().and_then(|conflicted_tree| {
+                    record_resolutions(store, resolution_store, &conflicted_tree, &resolved.tree()?)
+                });
+                if let Err(err) = result {
+                    tracing::warn!(?err, "failed to record conflict resolutions");
+                }
+            }
+        }
         let (mut_index, view) = mut_repo.consume();
 
         let view_id = base_repo.op_store().write_view(view.store_view()).unwrap();
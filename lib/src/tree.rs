@@ -471,14 +471,22 @@ pub async fn try_resolve_file_conflict(
         BackendResult::Ok(content)
     });
     let contents = Merge::from_vec(try_join_all(content_futures).await?);
-    let merge_result = files::merge(&contents);
-    match merge_result {
-        MergeResult::Resolved(merged_content) => {
-            let id = store
-                .write_file(filename, &mut merged_content.as_slice())
-                .await?;
-            Ok(Some(TreeValue::File { id, executable }))
+    let merged_content = match files::merge(&contents) {
+        MergeResult::Resolved(merged_content) => merged_content,
+        MergeResult::Conflict(hunks) => {
+            // Reuse the recorded resolutions if all the conflicted hunks have one
+            let Some(resolution_store) = store.resolution_store() else {
+                return Ok(None);
+            };
+            match resolution_store.resolve(filename, &hunks) {
+                Ok(Some(merged_content)) => merged_content,
+                Ok(None) => return Ok(None),
+                Err(err) => return Err(BackendError::Other(err.into())),
+            }
         }
-        MergeResult::Conflict(_) => Ok(None),
-    }
+    };
+    let id = store
+        .write_file(filename, &mut merged_content.as_slice())
+        .await?;
+    Ok(Some(TreeValue::File { id, executable }))
 }
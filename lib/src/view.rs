@@ -22,6 +22,7 @@ use itertools::Itertools;
 
 use crate::backend::CommitId;
 use crate::op_store;
+use crate::op_store::BisectState;
 use crate::op_store::BranchTarget;
 use crate::op_store::RefTarget;
 use crate::op_store::RefTargetOptionExt as _;
@@ -87,6 +88,11 @@ impl View {
         &self.data.git_head
     }
 
+    /// Returns the state of the bisection in progress, if any.
+    pub fn bisect_state(&self) -> Option<&BisectState> {
+        self.data.bisect.as_ref()
+    }
+
     pub fn set_wc_commit(&mut self, workspace_id: WorkspaceId, commit_id: CommitId) {
         self.data.wc_commit_ids.insert(workspace_id, commit_id);
     }
@@ -285,6 +291,17 @@ impl View {
         self.data.tags.get(name).flatten()
     }
 
+    /// Iterates tag `(name, target)`s matching the given pattern. Entries are
+    /// sorted by `name`.
+    pub fn tags_matching<'a: 'b, 'b>(
+        &'a self,
+        pattern: &'b StringPattern,
+    ) -> impl Iterator<Item = (&'a str, &'a RefTarget)> + 'b {
+        pattern
+            .filter_btree_map(&self.data.tags)
+            .map(|(name, target)| (name.as_ref(), target))
+    }
+
     /// Sets tag to point to the given target. If the target is absent, the tag
     /// will be removed.
     pub fn set_tag_target(&mut self, name: &str, target: RefTarget) {
@@ -315,6 +332,11 @@ impl View {
         self.data.git_head = target;
     }
 
+    /// Sets the state of the bisection in progress. `None` ends the bisection.
+    pub fn set_bisect_state(&mut self, state: Option<BisectState>) {
+        self.data.bisect = state;
+    }
+
     /// Iterates all commit ids referenced by this view.
     ///
     /// This can include hidden commits referenced by remote bookmarks, previous
@@ -340,6 +362,7 @@ impl View {
             git_refs,
             git_head,
             wc_commit_ids,
+            bisect,
         } = &self.data;
         itertools::chain!(
             head_ids,
@@ -353,7 +376,11 @@ impl View {
             }),
             git_refs.values().flat_map(ref_target_ids),
             ref_target_ids(git_head),
-            wc_commit_ids.values()
+            wc_commit_ids.values(),
+            bisect.iter().flat_map(|state| {
+                let op_store::BisectState { good, bad, skipped } = state;
+                itertools::chain!(good, bad, skipped)
+            })
         )
     }
 
@@ -23,8 +23,10 @@ use std::sync::Arc;
 use thiserror::Error;
 
 use crate::backend::BackendError;
+use crate::backend::CommitCopy;
 use crate::backend::MergedTreeId;
 use crate::commit::Commit;
+use crate::conflicts::ConflictMarkerStyle;
 use crate::fsmonitor::FsmonitorSettings;
 use crate::gitignore::GitIgnoreError;
 use crate::gitignore::GitIgnoreFile;
@@ -104,8 +106,19 @@ pub trait LockedWorkingCopy {
     /// Snapshot the working copy and return the tree id.
     fn snapshot(&mut self, options: &SnapshotOptions) -> Result<MergedTreeId, SnapshotError>;
 
+    /// Files detected as copied or renamed by the snapshots taken since the
+    /// lock was taken, relative to `old_tree_id()`. Working copies that don't
+    /// detect copies return nothing.
+    fn snapshot_copies(&self) -> Vec<CommitCopy> {
+        vec![]
+    }
+
     /// Check out the specified commit in the working copy.
-    fn check_out(&mut self, commit: &Commit) -> Result<CheckoutStats, CheckoutError>;
+    fn check_out(
+        &mut self,
+        commit: &Commit,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError>;
 
     /// Update to another commit without touching the files in the working copy.
     fn reset(&mut self, commit: &Commit) -> Result<(), ResetError>;
@@ -126,6 +139,7 @@ pub trait LockedWorkingCopy {
     fn set_sparse_patterns(
         &mut self,
         new_sparse_patterns: Vec<RepoPathBuf>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError>;
 
     /// Finish the modifications to the working copy by writing the updated
@@ -205,6 +219,10 @@ pub struct SnapshotOptions<'a> {
     /// (depending on implementation)
     /// return `SnapshotError::NewFileTooLarge`.
     pub max_new_file_size: u64,
+    /// The style of the conflict markers that were written to the working
+    /// copy. Conflict markers in any style are parsed, but files that match
+    /// the materialized conflict in this style are known to be unchanged.
+    pub conflict_marker_style: ConflictMarkerStyle,
 }
 
 impl SnapshotOptions<'_> {
@@ -216,6 +234,23 @@ impl SnapshotOptions<'_> {
             progress: None,
             start_tracking_matcher: &EverythingMatcher,
             max_new_file_size: u64::MAX,
+            conflict_marker_style: ConflictMarkerStyle::default(),
+        }
+    }
+}
+
+/// Options used when checking out a tree in the working copy.
+#[derive(Clone)]
+pub struct CheckoutOptions {
+    /// The style of the conflict markers to write for conflicted files.
+    pub conflict_marker_style: ConflictMarkerStyle,
+}
+
+impl CheckoutOptions {
+    /// Create an instance for use in tests.
+    pub fn empty_for_test() -> Self {
+        CheckoutOptions {
+            conflict_marker_style: ConflictMarkerStyle::default(),
         }
     }
 }
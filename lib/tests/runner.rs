@@ -6,7 +6,9 @@ fn test_no_forgotten_test_files() {
     testutils::assert_no_forgotten_test_files(&test_dir);
 }
 
+mod test_annotate;
 mod test_bad_locking;
+mod test_bisect;
 mod test_commit_builder;
 mod test_commit_concurrent;
 mod test_conflicts;
@@ -18,14 +20,18 @@ mod test_id_prefix;
 mod test_index;
 mod test_init;
 mod test_load_repo;
+mod test_local_backend;
 mod test_local_working_copy;
 mod test_local_working_copy_concurrent;
 mod test_local_working_copy_sparse;
 mod test_merge_trees;
 mod test_merged_tree;
 mod test_mut_repo;
+mod test_native_sync;
 mod test_operations;
+mod test_patch;
 mod test_refs;
+mod test_rerere;
 mod test_revset;
 mod test_rewrite;
 mod test_rewrite_transform;
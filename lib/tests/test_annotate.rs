@@ -0,0 +1,222 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::annotate::get_annotation_for_file;
+use jj_lib::annotate::get_annotation_with_file_content;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::repo::MutableRepo;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::settings::UserSettings;
+use testutils::create_tree;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+
+fn create_commit(
+    mut_repo: &mut MutableRepo,
+    settings: &UserSettings,
+    parents: &[&Commit],
+    path_contents: &[(&RepoPath, &str)],
+) -> Commit {
+    let tree = create_tree(mut_repo.base_repo(), path_contents);
+    let parent_ids = if parents.is_empty() {
+        vec![mut_repo.store().root_commit_id().clone()]
+    } else {
+        parents.iter().map(|commit| commit.id().clone()).collect()
+    };
+    mut_repo
+        .new_commit(settings, parent_ids, tree.id())
+        .write()
+        .unwrap()
+}
+
+fn annotate(
+    repo: &dyn Repo,
+    commit: &Commit,
+    path: &RepoPath,
+    follow_renames: bool,
+) -> Option<Vec<(CommitId, String)>> {
+    let results = get_annotation_for_file(repo, commit, path, follow_renames).unwrap()?;
+    let lines = results
+        .file_annotations
+        .into_iter()
+        .map(|(commit_id, line)| (commit_id, line.to_string()))
+        .collect();
+    Some(lines)
+}
+
+fn line(commit: &Commit, text: &str) -> (CommitId, String) {
+    (commit.id().clone(), text.to_owned())
+}
+
+#[test]
+fn test_annotate_linear() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let file_path = RepoPath::from_internal_string("file");
+    let other_path = RepoPath::from_internal_string("other");
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit1 = create_commit(mut_repo, &settings, &[], &[(file_path, "1\n2\n3\n")]);
+    let commit2 = create_commit(
+        mut_repo,
+        &settings,
+        &[&commit1],
+        &[(file_path, "1\n2b\n3\n4")],
+    );
+    // A commit that doesn't touch the file
+    let commit3 = create_commit(
+        mut_repo,
+        &settings,
+        &[&commit2],
+        &[(file_path, "1\n2b\n3\n4"), (other_path, "other\n")],
+    );
+
+    assert_eq!(
+        annotate(mut_repo, &commit3, file_path, false),
+        Some(vec![
+            line(&commit1, "1\n"),
+            line(&commit2, "2b\n"),
+            line(&commit1, "3\n"),
+            line(&commit2, "4"),
+        ])
+    );
+    assert_eq!(
+        annotate(mut_repo, &commit1, file_path, false),
+        Some(vec![
+            line(&commit1, "1\n"),
+            line(&commit1, "2\n"),
+            line(&commit1, "3\n"),
+        ])
+    );
+
+    // Not a file in the commit
+    assert_eq!(annotate(mut_repo, &commit1, other_path, false), None);
+    assert_eq!(annotate(mut_repo, &commit3, RepoPath::root(), false), None);
+}
+
+#[test]
+fn test_annotate_merge() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let file_path = RepoPath::from_internal_string("file");
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit1 = create_commit(mut_repo, &settings, &[], &[(file_path, "1\n2\n")]);
+    let commit2 = create_commit(
+        mut_repo,
+        &settings,
+        &[&commit1],
+        &[(file_path, "0\n1\n2\n")],
+    );
+    let commit3 = create_commit(
+        mut_repo,
+        &settings,
+        &[&commit1],
+        &[(file_path, "1\n2\n3\n")],
+    );
+    let commit4 = create_commit(
+        mut_repo,
+        &settings,
+        &[&commit2, &commit3],
+        &[(file_path, "0\n1\n2\n3\nmerge\n")],
+    );
+
+    // Lines are attributed to the side that introduced them
+    assert_eq!(
+        annotate(mut_repo, &commit4, file_path, false),
+        Some(vec![
+            line(&commit2, "0\n"),
+            line(&commit1, "1\n"),
+            line(&commit1, "2\n"),
+            line(&commit3, "3\n"),
+            line(&commit4, "merge\n"),
+        ])
+    );
+}
+
+#[test]
+fn test_annotate_renamed() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let old_path = RepoPath::from_internal_string("old");
+    let new_path = RepoPath::from_internal_string("new");
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit1 = create_commit(mut_repo, &settings, &[], &[(old_path, "1\n2\n3\n")]);
+    let commit2 = create_commit(
+        mut_repo,
+        &settings,
+        &[&commit1],
+        &[(new_path, "1\n2\n3\n4\n")],
+    );
+
+    // The file is followed across the rename only if requested
+    assert_eq!(
+        annotate(mut_repo, &commit2, new_path, true),
+        Some(vec![
+            line(&commit1, "1\n"),
+            line(&commit1, "2\n"),
+            line(&commit1, "3\n"),
+            line(&commit2, "4\n"),
+        ])
+    );
+    assert_eq!(
+        annotate(mut_repo, &commit2, new_path, false),
+        Some(vec![
+            line(&commit2, "1\n"),
+            line(&commit2, "2\n"),
+            line(&commit2, "3\n"),
+            line(&commit2, "4\n"),
+        ])
+    );
+}
+
+#[test]
+fn test_annotate_with_file_content() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let file_path = RepoPath::from_internal_string("file");
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit1 = create_commit(mut_repo, &settings, &[], &[(file_path, "1\n")]);
+    let commit2 = create_commit(mut_repo, &settings, &[&commit1], &[(file_path, "1\n2\n")]);
+    let commit3 = create_commit(
+        mut_repo,
+        &settings,
+        &[&commit2],
+        &[(file_path, "1\n2\n3\n")],
+    );
+
+    // The given contents are annotated as if they were in commit3. Lines from
+    // outside of the domain aren't attributed.
+    let domain = RevsetExpression::commit(commit2.id().clone());
+    let annotations =
+        get_annotation_with_file_content(mut_repo, &commit3, &domain, file_path, b"1\n2\nnew\n")
+            .unwrap();
+    assert_eq!(
+        annotations,
+        vec![None, Some(commit2.id().clone()), Some(commit3.id().clone())]
+    );
+}
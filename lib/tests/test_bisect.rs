@@ -0,0 +1,140 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectionStep;
+use jj_lib::commit::Commit;
+use jj_lib::op_store::BisectState;
+use testutils::CommitGraphBuilder;
+use testutils::TestRepo;
+
+fn ids(commits: &[&Commit]) -> Vec<CommitId> {
+    commits.iter().map(|commit| commit.id().clone()).collect()
+}
+
+fn test_step(commit: &Commit, num_untested: usize) -> BisectionStep {
+    BisectionStep::Test {
+        commit_id: commit.id().clone(),
+        num_untested,
+    }
+}
+
+#[test]
+fn test_bisect_linear() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.repo_mut());
+    let commit1 = graph_builder.initial_commit();
+    let commit2 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit3 = graph_builder.commit_with_parents(&[&commit2]);
+    let commit4 = graph_builder.commit_with_parents(&[&commit3]);
+    let commit5 = graph_builder.commit_with_parents(&[&commit4]);
+    let commit6 = graph_builder.commit_with_parents(&[&commit5]);
+    let repo = tx.repo();
+
+    // commit4 is the first bad commit
+    let mut state = BisectState::new(ids(&[&commit1]), ids(&[&commit6]));
+    assert_eq!(state.next_step(repo).unwrap(), test_step(&commit4, 4));
+    state.bad.push(commit4.id().clone());
+    assert_eq!(state.next_step(repo).unwrap(), test_step(&commit3, 2));
+    state.good.push(commit3.id().clone());
+    assert_eq!(
+        state.next_step(repo).unwrap(),
+        BisectionStep::Done(ids(&[&commit4]))
+    );
+}
+
+#[test]
+fn test_bisect_skipped() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.repo_mut());
+    let commit1 = graph_builder.initial_commit();
+    let commit2 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit3 = graph_builder.commit_with_parents(&[&commit2]);
+    let commit4 = graph_builder.commit_with_parents(&[&commit3]);
+    let repo = tx.repo();
+
+    // If the untested commits were skipped, they may be the first bad commit
+    let mut state = BisectState::new(ids(&[&commit1]), ids(&[&commit4]));
+    state.skipped = ids(&[&commit2, &commit3]);
+    assert_eq!(
+        state.next_step(repo).unwrap(),
+        BisectionStep::Done(ids(&[&commit4, &commit3, &commit2]))
+    );
+
+    // Marking an ancestor as bad narrows the range down
+    state.skipped = ids(&[&commit3]);
+    state.bad.push(commit2.id().clone());
+    assert_eq!(
+        state.next_step(repo).unwrap(),
+        BisectionStep::Done(ids(&[&commit2]))
+    );
+}
+
+#[test]
+fn test_bisect_non_linear() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // 6
+    // |\
+    // 4 5
+    // | |
+    // 2 3
+    // |/
+    // 1
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.repo_mut());
+    let commit1 = graph_builder.initial_commit();
+    let commit2 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit3 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit4 = graph_builder.commit_with_parents(&[&commit2]);
+    let commit5 = graph_builder.commit_with_parents(&[&commit3]);
+    let commit6 = graph_builder.commit_with_parents(&[&commit4, &commit5]);
+    let repo = tx.repo();
+
+    // Marks commits until the bisection is done, with commit3 as the first bad
+    // commit.
+    let bad_commits = [&commit3, &commit5, &commit6];
+    let mut state = BisectState::new(ids(&[&commit1]), ids(&[&commit6]));
+    let mut num_steps = 0;
+    let candidates = loop {
+        match state.next_step(repo).unwrap() {
+            BisectionStep::Test { commit_id, .. } => {
+                if bad_commits.iter().any(|commit| commit.id() == &commit_id) {
+                    state.bad.push(commit_id);
+                } else {
+                    state.good.push(commit_id);
+                }
+                num_steps += 1;
+            }
+            BisectionStep::Done(candidates) => break candidates,
+        }
+    };
+    assert_eq!(candidates, ids(&[&commit3]));
+    assert!(num_steps <= 3, "took {num_steps} steps");
+
+    // Multiple bad commits on different branches narrow the range down to
+    // their common ancestors
+    let state = BisectState::new(vec![], ids(&[&commit4, &commit5]));
+    assert_eq!(state.next_step(repo).unwrap(), test_step(&commit1, 2));
+}
@@ -13,11 +13,18 @@
 // limitations under the License.
 
 use futures::StreamExt as _;
+use futures::TryStreamExt as _;
 use itertools::Itertools;
+use jj_lib::backend::Backend as _;
 use jj_lib::backend::ChangeId;
+use jj_lib::backend::CommitCopy;
+use jj_lib::backend::CopyRecord;
 use jj_lib::backend::MillisSinceEpoch;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
+use jj_lib::backend::TreeValue;
+use jj_lib::commit::Commit;
+use jj_lib::local_backend::LocalBackend;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::Repo;
@@ -199,6 +206,152 @@ fn test_rewrite(backend: TestRepoBackend) {
     );
 }
 
+#[test]
+fn test_copies() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+
+    let source_path = RepoPath::from_internal_string("source");
+    let target_path = RepoPath::from_internal_string("dir/target");
+    let initial_tree = create_tree(repo, &[(source_path, "contents")]);
+    let renamed_tree = create_tree(repo, &[(target_path, "contents")]);
+    let copies = vec![CommitCopy {
+        source: source_path.to_owned(),
+        target: target_path.to_owned(),
+    }];
+
+    let mut tx = repo.start_transaction(&settings);
+    let initial_commit = tx
+        .repo_mut()
+        .new_commit(
+            &settings,
+            vec![store.root_commit_id().clone()],
+            initial_tree.id(),
+        )
+        .write()
+        .unwrap();
+    let builder = tx
+        .repo_mut()
+        .new_commit(
+            &settings,
+            vec![initial_commit.id().clone()],
+            renamed_tree.id(),
+        )
+        .set_copies(copies.clone());
+    assert_eq!(builder.copies(), copies);
+    let renamed_commit = builder.write().unwrap();
+    // The copies are persisted with the commit
+    let stored_commit = store
+        .backend_impl()
+        .downcast_ref::<LocalBackend>()
+        .unwrap()
+        .read_commit(renamed_commit.id())
+        .block_on()
+        .unwrap();
+    assert_eq!(stored_commit.copies, copies);
+
+    let get_copy_records = |repo: &dyn Repo, root: &Commit, head: &Commit| -> Vec<CopyRecord> {
+        let stream = store
+            .get_copy_records(None, root.id(), head.id(), repo.index())
+            .unwrap();
+        stream.try_collect().block_on().unwrap()
+    };
+    let source_file = match initial_tree
+        .path_value(source_path)
+        .unwrap()
+        .into_resolved()
+    {
+        Ok(Some(TreeValue::File { id, .. })) => id,
+        value => panic!("unexpected value: {value:?}"),
+    };
+    assert_eq!(
+        get_copy_records(tx.repo(), &initial_commit, &renamed_commit),
+        vec![CopyRecord {
+            target: target_path.to_owned(),
+            target_commit: renamed_commit.id().clone(),
+            source: source_path.to_owned(),
+            source_file: source_file.clone(),
+            source_commit: initial_commit.id().clone(),
+        }]
+    );
+    assert_eq!(
+        get_copy_records(tx.repo(), &renamed_commit, &renamed_commit),
+        vec![]
+    );
+
+    // Chained renames are composed into a single record, but only the part
+    // within the range is reported
+    let final_path = RepoPath::from_internal_string("final");
+    let final_tree = create_tree(repo, &[(final_path, "contents")]);
+    let final_commit = tx
+        .repo_mut()
+        .new_commit(
+            &settings,
+            vec![renamed_commit.id().clone()],
+            final_tree.id(),
+        )
+        .set_copies(vec![CommitCopy {
+            source: target_path.to_owned(),
+            target: final_path.to_owned(),
+        }])
+        .write()
+        .unwrap();
+    assert_eq!(
+        get_copy_records(tx.repo(), &initial_commit, &final_commit),
+        vec![CopyRecord {
+            target: final_path.to_owned(),
+            target_commit: final_commit.id().clone(),
+            source: source_path.to_owned(),
+            source_file: source_file.clone(),
+            source_commit: initial_commit.id().clone(),
+        }]
+    );
+    assert_eq!(
+        get_copy_records(tx.repo(), &renamed_commit, &final_commit),
+        vec![CopyRecord {
+            target: final_path.to_owned(),
+            target_commit: final_commit.id().clone(),
+            source: target_path.to_owned(),
+            source_file,
+            source_commit: renamed_commit.id().clone(),
+        }]
+    );
+
+    // The copies are kept when the commit is rewritten without changing the
+    // tree or the parents
+    let described_commit = tx
+        .repo_mut()
+        .rewrite_commit(&settings, &renamed_commit)
+        .set_description("renamed")
+        .write()
+        .unwrap();
+    assert_eq!(described_commit.store_commit().copies, copies);
+
+    // They're dropped once the target doesn't exist anymore
+    let rewritten_commit = tx
+        .repo_mut()
+        .rewrite_commit(&settings, &described_commit)
+        .set_tree_id(initial_tree.id())
+        .write()
+        .unwrap();
+    assert_eq!(rewritten_commit.store_commit().copies, vec![]);
+    assert_eq!(
+        get_copy_records(tx.repo(), &initial_commit, &rewritten_commit),
+        vec![]
+    );
+
+    // Or when the source doesn't exist in the new parents
+    let rebased_commit = tx
+        .repo_mut()
+        .rewrite_commit(&settings, &described_commit)
+        .set_parents(vec![store.root_commit_id().clone()])
+        .write()
+        .unwrap();
+    assert_eq!(rebased_commit.store_commit().copies, vec![]);
+}
+
 // An author field with an empty name/email should get filled in on rewrite
 #[test_case(TestRepoBackend::Local ; "local backend")]
 #[test_case(TestRepoBackend::Git ; "git backend")]
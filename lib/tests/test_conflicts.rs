@@ -18,6 +18,7 @@ use jj_lib::conflicts::extract_as_single_hunk;
 use jj_lib::conflicts::materialize_merge_result;
 use jj_lib::conflicts::parse_conflict;
 use jj_lib::conflicts::update_from_content;
+use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::merge::Merge;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
@@ -74,7 +75,7 @@ fn test_materialize_conflict_basic() {
         vec![Some(left_id.clone()), Some(right_id.clone())],
     );
     insta::assert_snapshot!(
-        &materialize_conflict_string(store, path, &conflict),
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
         @r###"
     line 1
     line 2
@@ -98,7 +99,7 @@ fn test_materialize_conflict_basic() {
         vec![Some(right_id.clone()), Some(left_id.clone())],
     );
     insta::assert_snapshot!(
-        &materialize_conflict_string(store, path, &conflict),
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
         @r###"
     line 1
     line 2
@@ -171,7 +172,7 @@ fn test_materialize_conflict_multi_rebase_conflicts() {
         vec![Some(a_id.clone()), Some(b_id.clone()), Some(c_id.clone())],
     );
     insta::assert_snapshot!(
-        &materialize_conflict_string(store, path, &conflict),
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
         @r###"
     line 1
     <<<<<<< Conflict 1 of 1
@@ -195,7 +196,7 @@ fn test_materialize_conflict_multi_rebase_conflicts() {
         vec![Some(c_id.clone()), Some(b_id.clone()), Some(a_id.clone())],
     );
     insta::assert_snapshot!(
-        &materialize_conflict_string(store, path, &conflict),
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
         @r###"
     line 1
     <<<<<<< Conflict 1 of 1
@@ -219,7 +220,7 @@ fn test_materialize_conflict_multi_rebase_conflicts() {
         vec![Some(c_id.clone()), Some(a_id.clone()), Some(b_id.clone())],
     );
     insta::assert_snapshot!(
-        &materialize_conflict_string(store, path, &conflict),
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
         @r###"
     line 1
     <<<<<<< Conflict 1 of 1
@@ -285,7 +286,8 @@ fn test_materialize_parse_roundtrip() {
         vec![Some(base_id.clone())],
         vec![Some(left_id.clone()), Some(right_id.clone())],
     );
-    let materialized = materialize_conflict_string(store, path, &conflict);
+    let materialized =
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff);
     insta::assert_snapshot!(
         materialized,
         @r###"
@@ -353,7 +355,8 @@ fn test_materialize_conflict_no_newlines_at_eof() {
         vec![Some(base_id.clone())],
         vec![Some(left_empty_id.clone()), Some(right_id.clone())],
     );
-    let materialized = &materialize_conflict_string(store, path, &conflict);
+    let materialized =
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff);
     insta::assert_snapshot!(materialized,
         @r###"
     <<<<<<< Conflict 1 of 1
@@ -413,7 +416,9 @@ fn test_materialize_conflict_modify_delete() {
         vec![Some(base_id.clone())],
         vec![Some(modified_id.clone()), Some(deleted_id.clone())],
     );
-    insta::assert_snapshot!(&materialize_conflict_string(store, path, &conflict), @r###"
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
+        @r###"
     line 1
     line 2
     <<<<<<< Conflict 1 of 1
@@ -432,7 +437,9 @@ fn test_materialize_conflict_modify_delete() {
         vec![Some(base_id.clone())],
         vec![Some(deleted_id.clone()), Some(modified_id.clone())],
     );
-    insta::assert_snapshot!(&materialize_conflict_string(store, path, &conflict), @r###"
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
+        @r###"
     line 1
     line 2
     <<<<<<< Conflict 1 of 1
@@ -451,7 +458,9 @@ fn test_materialize_conflict_modify_delete() {
         vec![Some(base_id.clone())],
         vec![Some(modified_id.clone()), None],
     );
-    insta::assert_snapshot!(&materialize_conflict_string(store, path, &conflict), @r###"
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
+        @r###"
     <<<<<<< Conflict 1 of 1
     %%%%%%% Changes from base to side #1
      line 1
@@ -503,7 +512,7 @@ fn test_materialize_conflict_two_forward_diffs() {
         ],
     );
     insta::assert_snapshot!(
-        &materialize_conflict_string(store, path, &conflict),
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff),
         @r###"
     <<<<<<< Conflict 1 of 1
     +++++++ Contents of side #1
@@ -521,6 +530,82 @@ fn test_materialize_conflict_two_forward_diffs() {
     );
 }
 
+#[test]
+fn test_materialize_conflict_snapshot_and_git_styles() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let base_id = testutils::write_file(store, path, "line 1\nline 2\nline 3\n");
+    let left_id = testutils::write_file(store, path, "line 1\nleft 2\nline 3\n");
+    let right_id = testutils::write_file(store, path, "line 1\nright 2\nline 3\n");
+    let other_id = testutils::write_file(store, path, "line 1\nother 2\nline 3\n");
+
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Snapshot),
+        @r###"
+    line 1
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    left 2
+    ------- Contents of base
+    line 2
+    +++++++ Contents of side #2
+    right 2
+    >>>>>>> Conflict 1 of 1 ends
+    line 3
+    "###
+    );
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Git),
+        @r###"
+    line 1
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    left 2
+    ||||||| Base
+    line 2
+    =======
+    right 2
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    line 3
+    "###
+    );
+
+    // Git-style markers can't represent more than two sides, so the snapshot
+    // style is used instead
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone()), Some(base_id.clone())],
+        vec![
+            Some(left_id.clone()),
+            Some(right_id.clone()),
+            Some(other_id.clone()),
+        ],
+    );
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Git),
+        @r###"
+    line 1
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    left 2
+    ------- Contents of base #1
+    line 2
+    +++++++ Contents of side #2
+    right 2
+    ------- Contents of base #2
+    line 2
+    +++++++ Contents of side #3
+    other 2
+    >>>>>>> Conflict 1 of 1 ends
+    line 3
+    "###
+    );
+}
+
 #[test]
 fn test_parse_conflict_resolved() {
     assert_eq!(
@@ -725,6 +810,129 @@ fn test_parse_conflict_multi_way() {
     );
 }
 
+#[test]
+fn test_parse_conflict_git_style() {
+    assert_eq!(
+        parse_conflict(
+            indoc! {b"
+            line 1
+            <<<<<<< Side #1
+            left
+            ||||||| Base
+            line 2
+            =======
+            right
+            >>>>>>> Side #2
+            line 3
+            "},
+            2
+        ),
+        Some(vec![
+            Merge::resolved("line 1\n".into()),
+            Merge::from_removes_adds(
+                vec!["line 2\n".into()],
+                vec!["left\n".into(), "right\n".into()]
+            ),
+            Merge::resolved("line 3\n".into()),
+        ])
+    );
+    // An empty first side
+    assert_eq!(
+        parse_conflict(
+            indoc! {b"
+            <<<<<<<
+            |||||||
+            base
+            =======
+            right
+            >>>>>>>
+            "},
+            2
+        ),
+        Some(vec![Merge::from_removes_adds(
+            vec!["base\n".into()],
+            vec!["".into(), "right\n".into()]
+        )])
+    );
+    // Git's "merge" style without the base can't be parsed
+    assert_eq!(
+        parse_conflict(
+            indoc! {b"
+            <<<<<<<
+            left
+            =======
+            right
+            >>>>>>>
+            "},
+            2
+        ),
+        None
+    );
+    // The first side starts with a line that looks like a jj-style marker
+    assert_eq!(
+        parse_conflict(
+            indoc! {b"
+            <<<<<<< Side #1
+            +++++++ left
+            ||||||| Base
+            base
+            =======
+            right
+            >>>>>>> Side #2
+            "},
+            2
+        ),
+        Some(vec![Merge::from_removes_adds(
+            vec!["base\n".into()],
+            vec!["+++++++ left\n".into(), "right\n".into()]
+        )])
+    );
+}
+
+#[test]
+fn test_parse_conflict_snapshot_style() {
+    assert_eq!(
+        parse_conflict(
+            indoc! {b"
+            <<<<<<< Conflict 1 of 1
+            +++++++ Contents of side #1
+            left
+            ------- Contents of base
+            base
+            +++++++ Contents of side #2
+            right
+            >>>>>>> Conflict 1 of 1 ends
+            "},
+            2
+        ),
+        Some(vec![Merge::from_removes_adds(
+            vec!["base\n".into()],
+            vec!["left\n".into(), "right\n".into()]
+        )])
+    );
+    // A side contains a line that looks like a Git-style separator
+    assert_eq!(
+        parse_conflict(
+            indoc! {b"
+            <<<<<<< Conflict 1 of 1
+            +++++++ Contents of side #1
+            left
+            =======
+            ------- Contents of base
+            base
+            +++++++ Contents of side #2
+            right
+            >>>>>>> Conflict 1 of 1 ends
+            "},
+            2
+        ),
+        Some(vec![Merge::from_removes_adds(
+            vec!["base\n".into()],
+            vec!["left\n=======\n".into(), "right\n".into()]
+        )])
+    );
+}
+
 #[test]
 fn test_parse_conflict_wrong_arity() {
     // Valid conflict marker but it has fewer sides than the caller expected
@@ -833,9 +1041,10 @@ fn test_update_conflict_from_content() {
 
     // If the content is unchanged compared to the materialized value, we get the
     // old conflict id back.
-    let materialized = materialize_conflict_string(store, path, &conflict);
+    let materialized =
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -869,6 +1078,83 @@ fn test_update_conflict_from_content() {
     );
 }
 
+#[test]
+fn test_update_conflict_from_content_with_marker_styles() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("dir/file");
+    let base_file_id = testutils::write_file(store, path, "line 1\nline 2\nline 3\n");
+    let left_file_id = testutils::write_file(store, path, "left 1\nline 2\nleft 3\n");
+    let right_file_id = testutils::write_file(store, path, "right 1\nline 2\nright 3\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_file_id.clone())],
+        vec![Some(left_file_id.clone()), Some(right_file_id.clone())],
+    );
+
+    for style in [
+        ConflictMarkerStyle::Diff,
+        ConflictMarkerStyle::Snapshot,
+        ConflictMarkerStyle::Git,
+    ] {
+        let parse = |content| {
+            update_from_content(&conflict, store, path, content, style)
+                .block_on()
+                .unwrap()
+        };
+        // The unchanged materialized conflict gives back the old conflict
+        let materialized = materialize_conflict_string(store, path, &conflict, style);
+        assert_eq!(parse(materialized.as_bytes()), conflict);
+
+        // Conflicts in any style can be parsed regardless of the configured
+        // style
+        let new_base_file_id = testutils::write_file(store, path, "resolved 1\nline 2\nline 3\n");
+        let new_left_file_id = testutils::write_file(store, path, "resolved 1\nline 2\nleft 3\n");
+        let new_right_file_id = testutils::write_file(store, path, "resolved 1\nline 2\nright 3\n");
+        let expected_conflict = Merge::from_removes_adds(
+            vec![Some(new_base_file_id)],
+            vec![Some(new_left_file_id), Some(new_right_file_id)],
+        );
+        let diff_style = indoc! {b"
+            resolved 1
+            line 2
+            <<<<<<<
+            %%%%%%%
+            -line 3
+            +left 3
+            +++++++
+            right 3
+            >>>>>>>
+        "};
+        let snapshot_style = indoc! {b"
+            resolved 1
+            line 2
+            <<<<<<<
+            +++++++
+            left 3
+            -------
+            line 3
+            +++++++
+            right 3
+            >>>>>>>
+        "};
+        let git_style = indoc! {b"
+            resolved 1
+            line 2
+            <<<<<<<
+            left 3
+            |||||||
+            line 3
+            =======
+            right 3
+            >>>>>>>
+        "};
+        assert_eq!(parse(diff_style), expected_conflict);
+        assert_eq!(parse(snapshot_style), expected_conflict);
+        assert_eq!(parse(git_style), expected_conflict);
+    }
+}
+
 #[test]
 fn test_update_conflict_from_content_modify_delete() {
     let test_repo = TestRepo::init();
@@ -882,9 +1168,10 @@ fn test_update_conflict_from_content_modify_delete() {
 
     // If the content is unchanged compared to the materialized value, we get the
     // old conflict id back.
-    let materialized = materialize_conflict_string(store, path, &conflict);
+    let materialized =
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -935,10 +1222,12 @@ fn test_update_conflict_from_content_simplified_conflict() {
     // If the content is unchanged compared to the materialized value, we get the
     // old conflict id back. Both the simplified and unsimplified materialized
     // conflicts should return the old conflict id.
-    let materialized = materialize_conflict_string(store, path, &conflict);
-    let materialized_simplified = materialize_conflict_string(store, path, &simplified_conflict);
+    let materialized =
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff);
+    let materialized_simplified =
+        materialize_conflict_string(store, path, &simplified_conflict, ConflictMarkerStyle::Diff);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -1062,11 +1351,12 @@ fn materialize_conflict_string(
     store: &Store,
     path: &RepoPath,
     conflict: &Merge<Option<FileId>>,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> String {
     let mut result: Vec<u8> = vec![];
     let contents = extract_as_single_hunk(conflict, store, path)
         .block_on()
         .unwrap();
-    materialize_merge_result(&contents, &mut result).unwrap();
+    materialize_merge_result(&contents, conflict_marker_style, &mut result).unwrap();
     String::from_utf8(result).unwrap()
 }
@@ -41,6 +41,7 @@ use jj_lib::git::GitFetchError;
 use jj_lib::git::GitImportError;
 use jj_lib::git::GitPushError;
 use jj_lib::git::GitRefUpdate;
+use jj_lib::git::GitTagError;
 use jj_lib::git::RefName;
 use jj_lib::git::SubmoduleConfig;
 use jj_lib::git_backend::GitBackend;
@@ -57,6 +58,7 @@ use jj_lib::repo_path::RepoPath;
 use jj_lib::settings::GitSettings;
 use jj_lib::settings::UserSettings;
 use jj_lib::signing::Signer;
+use jj_lib::signing::SigningBackend as _;
 use jj_lib::str_util::StringPattern;
 use jj_lib::workspace::Workspace;
 use maplit::btreemap;
@@ -66,6 +68,7 @@ use test_case::test_case;
 use testutils::commit_transactions;
 use testutils::create_random_commit;
 use testutils::load_repo_at_head;
+use testutils::test_signing_backend::TestSigningBackend;
 use testutils::write_random_commit;
 use testutils::TestRepo;
 use testutils::TestRepoBackend;
@@ -2123,6 +2126,192 @@ fn test_export_undo_reexport() {
     );
 }
 
+#[test]
+fn test_export_tags() {
+    let test_data = GitRepoData::create();
+    let git_repo = test_data.git_repo;
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    let mut_repo = tx.repo_mut();
+    let commit1 = write_random_commit(mut_repo, &test_data.settings);
+    let commit2 = write_random_commit(mut_repo, &test_data.settings);
+
+    // Create tag
+    mut_repo.set_tag_target("v1", RefTarget::normal(commit1.id().clone()));
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v1").unwrap().target(),
+        Some(git_id(&commit1))
+    );
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v1"),
+        RefTarget::normal(commit1.id().clone())
+    );
+
+    // Move tag
+    mut_repo.set_tag_target("v1", RefTarget::normal(commit2.id().clone()));
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v1").unwrap().target(),
+        Some(git_id(&commit2))
+    );
+
+    // Delete tag
+    mut_repo.set_tag_target("v1", RefTarget::absent());
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert!(git_repo.find_reference("refs/tags/v1").is_err());
+    assert!(mut_repo.get_git_ref("refs/tags/v1").is_absent());
+}
+
+#[test]
+fn test_export_annotated_tag() {
+    // Annotated tags which still point to the same commit shouldn't be replaced
+    // by lightweight tags
+    let test_data = GitRepoData::create();
+    let git_settings = GitSettings::default();
+    let git_repo = test_data.git_repo;
+    let commit1 = empty_git_commit(&git_repo, "refs/heads/main", &[]);
+    let commit2 = empty_git_commit(&git_repo, "refs/heads/main", &[&commit1]);
+    let git_signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+    let tag_oid = git_repo
+        .tag(
+            "v1",
+            commit1.as_object(),
+            &git_signature,
+            "Release\n",
+            false,
+        )
+        .unwrap();
+
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    let mut_repo = tx.repo_mut();
+    git::import_refs(mut_repo, &git_settings).unwrap();
+    assert_eq!(
+        *mut_repo.view().get_tag("v1"),
+        RefTarget::normal(jj_id(&commit1))
+    );
+
+    // Re-export is no-op
+    mut_repo.set_git_ref_target("refs/tags/v1", RefTarget::absent());
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v1").unwrap().target(),
+        Some(tag_oid)
+    );
+
+    // Moving the tag replaces the annotated tag
+    mut_repo.set_tag_target("v1", RefTarget::normal(jj_id(&commit2)));
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v1").unwrap().target(),
+        Some(commit2.id())
+    );
+
+    // Deleting the tag deletes the lightweight tag
+    let tag_oid = git_repo
+        .tag(
+            "v2",
+            commit1.as_object(),
+            &git_signature,
+            "Release\n",
+            false,
+        )
+        .unwrap();
+    git::import_refs(mut_repo, &git_settings).unwrap();
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v2"),
+        RefTarget::normal(jj_id(&commit1))
+    );
+    assert!(git_repo.find_tag(tag_oid).is_ok());
+    mut_repo.set_tag_target("v2", RefTarget::absent());
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert!(git_repo.find_reference("refs/tags/v2").is_err());
+}
+
+#[test]
+fn test_set_annotated_tag() {
+    let test_data = GitRepoData::create();
+    let git_repo = test_data.git_repo;
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    let mut_repo = tx.repo_mut();
+    let commit = write_random_commit(mut_repo, &test_data.settings);
+    let tagger = test_data.settings.signature();
+
+    git::set_annotated_tag(mut_repo, "v1", commit.id(), &tagger, "Release 1", None).unwrap();
+    assert_eq!(
+        *mut_repo.view().get_tag("v1"),
+        RefTarget::normal(commit.id().clone())
+    );
+
+    // The Git ref is written immediately, and recorded as exported
+    assert_eq!(
+        mut_repo.get_git_ref("refs/tags/v1"),
+        RefTarget::normal(commit.id().clone())
+    );
+    let git_tag = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    assert_eq!(git_tag.name(), Some("v1"));
+    assert_eq!(git_tag.target_id(), git_id(&commit));
+    assert_eq!(git_tag.message(), Some("Release 1\n"));
+    assert_eq!(git_tag.tagger().unwrap().name(), Some(tagger.name.as_str()));
+    assert_eq!(
+        git_tag.tagger().unwrap().email(),
+        Some(tagger.email.as_str())
+    );
+
+    // Export leaves the annotated tag alone
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v1").unwrap().target(),
+        Some(git_tag.id())
+    );
+
+    // Signed tag overwriting the existing tag on the same commit
+    let mut signed_data = vec![];
+    let mut sign_fn = |data: &[u8]| {
+        signed_data = data.to_vec();
+        TestSigningBackend.sign(data, Some("key"))
+    };
+    git::set_annotated_tag(
+        mut_repo,
+        "v1",
+        commit.id(),
+        &tagger,
+        "Release 1\n\nSigned.\n",
+        Some(&mut sign_fn),
+    )
+    .unwrap();
+    let signature = TestSigningBackend.sign(&signed_data, Some("key")).unwrap();
+    let git_tag = git_repo
+        .find_reference("refs/tags/v1")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    let odb = git_repo.odb().unwrap();
+    let raw_tag = odb.read(git_tag.id()).unwrap();
+    assert_eq!(raw_tag.data(), [signed_data, signature].concat());
+    assert!(String::from_utf8_lossy(raw_tag.data()).contains("\nSigned.\n--- JJ-TEST-SIGNATURE"));
+
+    // Moving the tag replaces it with a lightweight tag on export
+    let commit2 = write_random_commit(mut_repo, &test_data.settings);
+    git::set_annotated_tag(mut_repo, "v2", commit.id(), &tagger, "Release 2", None).unwrap();
+    mut_repo.set_tag_target("v2", RefTarget::normal(commit2.id().clone()));
+    assert!(git::export_refs(mut_repo).unwrap().is_empty());
+    assert_eq!(
+        git_repo.find_reference("refs/tags/v2").unwrap().target(),
+        Some(git_id(&commit2))
+    );
+
+    // Tags on the root commit can't be created
+    let root_commit_id = mut_repo.store().root_commit_id().clone();
+    assert_matches!(
+        git::set_annotated_tag(mut_repo, "root", &root_commit_id, &tagger, "Root", None),
+        Err(GitTagError::Backend(BackendError::Unsupported(_)))
+    );
+}
+
 #[test]
 fn test_reset_head_to_root() {
     // Create colocated workspace
@@ -3035,6 +3224,171 @@ fn test_push_updates_unexpectedly_exists_on_remote() {
     );
 }
 
+#[test]
+fn test_push_tags() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let clone_repo = get_git_repo(&setup.jj_repo);
+    let source_repo = git2::Repository::open(&setup.source_repo_dir).unwrap();
+    let mut tx = setup.jj_repo.start_transaction(&settings);
+    let tagger = settings.signature();
+    tx.repo_mut().set_tag_target(
+        "lightweight",
+        RefTarget::normal(setup.main_commit.id().clone()),
+    );
+    git::set_annotated_tag(
+        tx.repo_mut(),
+        "annotated",
+        setup.child_of_main_commit.id(),
+        &tagger,
+        "Message",
+        None,
+    )
+    .unwrap();
+    assert!(git::export_refs(tx.repo_mut()).unwrap().is_empty());
+    let tag_names = ["annotated".to_owned(), "lightweight".to_owned()];
+    let push = |tag_names: &[String]| {
+        git::push_tags(
+            tx.repo(),
+            &clone_repo,
+            "origin",
+            tag_names,
+            git::RemoteCallbacks::default(),
+        )
+    };
+
+    assert_eq!(push(&tag_names), Ok(()));
+    assert_eq!(
+        source_repo
+            .find_reference("refs/tags/lightweight")
+            .unwrap()
+            .target(),
+        Some(git_id(&setup.main_commit))
+    );
+    let annotated_ref = source_repo.find_reference("refs/tags/annotated").unwrap();
+    assert_eq!(
+        annotated_ref.target(),
+        clone_repo
+            .find_reference("refs/tags/annotated")
+            .unwrap()
+            .target()
+    );
+    assert_eq!(
+        annotated_ref.peel_to_commit().unwrap().id(),
+        git_id(&setup.child_of_main_commit)
+    );
+
+    // Pushing the same tags again is no-op
+    assert_eq!(push(&tag_names), Ok(()));
+
+    // Tags pointing elsewhere on the remote are rejected
+    source_repo
+        .reference(
+            "refs/tags/lightweight",
+            git_id(&setup.child_of_main_commit),
+            true,
+            "test",
+        )
+        .unwrap();
+    assert_eq!(
+        push(&tag_names),
+        Err(GitPushError::RefInUnexpectedLocation(vec![
+            "refs/tags/lightweight".to_owned()
+        ]))
+    );
+}
+
+#[test]
+fn test_push_bookmarks_and_tags() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let clone_repo = get_git_repo(&setup.jj_repo);
+    let source_repo = git2::Repository::open(&setup.source_repo_dir).unwrap();
+    let mut tx = setup.jj_repo.start_transaction(&settings);
+    tx.repo_mut()
+        .set_tag_target("tag", RefTarget::normal(setup.main_commit.id().clone()));
+    assert!(git::export_refs(tx.repo_mut()).unwrap().is_empty());
+    let targets = GitBranchPushTargets {
+        branch_updates: vec![(
+            "main".to_owned(),
+            BranchPushUpdate {
+                old_target: Some(setup.main_commit.id().clone()),
+                new_target: Some(setup.child_of_main_commit.id().clone()),
+            },
+        )],
+    };
+    let tag_names = ["tag".to_owned()];
+
+    // If the tag is rejected, the bookmark isn't pushed either
+    source_repo
+        .reference(
+            "refs/tags/tag",
+            git_id(&setup.parent_of_main_commit),
+            true,
+            "test",
+        )
+        .unwrap();
+    let result = git::push_branches_and_tags(
+        tx.repo_mut(),
+        &clone_repo,
+        "origin",
+        &targets,
+        &tag_names,
+        git::RemoteCallbacks::default(),
+    );
+    assert_eq!(
+        result,
+        Err(GitPushError::RefInUnexpectedLocation(vec![
+            "refs/tags/tag".to_owned()
+        ]))
+    );
+    assert_eq!(
+        source_repo
+            .find_reference("refs/heads/main")
+            .unwrap()
+            .target(),
+        Some(git_id(&setup.main_commit))
+    );
+
+    source_repo
+        .find_reference("refs/tags/tag")
+        .unwrap()
+        .delete()
+        .unwrap();
+    let result = git::push_branches_and_tags(
+        tx.repo_mut(),
+        &clone_repo,
+        "origin",
+        &targets,
+        &tag_names,
+        git::RemoteCallbacks::default(),
+    );
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        source_repo
+            .find_reference("refs/heads/main")
+            .unwrap()
+            .target(),
+        Some(git_id(&setup.child_of_main_commit))
+    );
+    assert_eq!(
+        source_repo
+            .find_reference("refs/tags/tag")
+            .unwrap()
+            .target(),
+        Some(git_id(&setup.main_commit))
+    );
+    assert_eq!(
+        *tx.repo_mut().view().get_remote_bookmark("main", "origin"),
+        RemoteRef {
+            target: RefTarget::normal(setup.child_of_main_commit.id().clone()),
+            state: RemoteRefState::Tracking,
+        },
+    );
+}
+
 #[test]
 fn test_push_updates_success() {
     let settings = testutils::user_settings();
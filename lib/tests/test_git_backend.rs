@@ -30,7 +30,6 @@ use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::settings::UserSettings;
-use jj_lib::store::Store;
 use jj_lib::transaction::Transaction;
 use maplit::hashset;
 use testutils::create_random_commit;
@@ -59,12 +58,15 @@ fn collect_no_gc_refs(git_repo_path: &Path) -> HashSet<CommitId> {
 }
 
 fn get_copy_records(
-    store: &Store,
+    repo: &dyn Repo,
     paths: Option<&[RepoPathBuf]>,
     a: &Commit,
     b: &Commit,
 ) -> HashMap<String, String> {
-    let stream = store.get_copy_records(paths, a.id(), b.id()).unwrap();
+    let stream = repo
+        .store()
+        .get_copy_records(paths, a.id(), b.id(), repo.index())
+        .unwrap();
     let mut res: HashMap<String, String> = HashMap::new();
     for CopyRecord { target, source, .. } in block_on_stream(stream).filter_map(|r| r.ok()) {
         res.insert(
@@ -269,29 +271,29 @@ fn test_copy_detection() {
         &[(&paths[2], "content")],
     );
 
-    let store = repo.store();
+    let repo = tx.repo();
     assert_eq!(
-        get_copy_records(store, Some(paths), &commit_a, &commit_b),
+        get_copy_records(repo, Some(paths), &commit_a, &commit_b),
         HashMap::from([("file1".to_string(), "file0".to_string())])
     );
     assert_eq!(
-        get_copy_records(store, Some(paths), &commit_b, &commit_c),
+        get_copy_records(repo, Some(paths), &commit_b, &commit_c),
         HashMap::from([("file2".to_string(), "file1".to_string())])
     );
     assert_eq!(
-        get_copy_records(store, Some(paths), &commit_a, &commit_c),
+        get_copy_records(repo, Some(paths), &commit_a, &commit_c),
         HashMap::from([("file2".to_string(), "file0".to_string())])
     );
     assert_eq!(
-        get_copy_records(store, None, &commit_a, &commit_c),
+        get_copy_records(repo, None, &commit_a, &commit_c),
         HashMap::from([("file2".to_string(), "file0".to_string())])
     );
     assert_eq!(
-        get_copy_records(store, Some(&[paths[1].clone()]), &commit_a, &commit_c),
+        get_copy_records(repo, Some(&[paths[1].clone()]), &commit_a, &commit_c),
         HashMap::default(),
     );
     assert_eq!(
-        get_copy_records(store, Some(paths), &commit_c, &commit_c),
+        get_copy_records(repo, Some(paths), &commit_c, &commit_c),
         HashMap::default(),
     );
 }
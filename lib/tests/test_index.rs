@@ -17,6 +17,7 @@ use std::fs;
 use std::sync::Arc;
 
 use assert_matches::assert_matches;
+use itertools::Itertools as _;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
@@ -36,12 +37,14 @@ use jj_lib::op_store::RemoteRef;
 use jj_lib::repo::MutableRepo;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
 use jj_lib::revset::ResolvedExpression;
 use jj_lib::revset::GENERATION_RANGE_FULL;
 use jj_lib::settings::UserSettings;
 use maplit::hashset;
 use testutils::commit_transactions;
 use testutils::create_random_commit;
+use testutils::create_tree;
 use testutils::load_repo_at_head;
 use testutils::test_backend::TestBackend;
 use testutils::write_random_commit;
@@ -738,6 +741,170 @@ fn test_reindex_missing_commit() {
     assert_matches!(err, DefaultIndexStoreError::IndexCommits { op_id, .. } if op_id == *bad_op_id);
 }
 
+#[test]
+fn test_index_changed_paths() {
+    let config = testutils::base_config()
+        .set_override("index.changed-paths", true)
+        .unwrap()
+        .build()
+        .unwrap();
+    let settings = UserSettings::from_config(config);
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+
+    let file1 = RepoPath::from_internal_string("file1");
+    let file2 = RepoPath::from_internal_string("dir/file2");
+    let tree1 = create_tree(repo, &[(file1, "1")]);
+    let tree2 = create_tree(repo, &[(file2, "1")]);
+    let tree3 = create_tree(repo, &[(file1, "1"), (file2, "1")]);
+    let tree4 = create_tree(repo, &[(file1, "1"), (file2, "2")]);
+
+    // 4 merges 2 and 3 with no conflicting changes
+    // |\
+    // 2 3 (3 is empty)
+    // |/
+    // 1
+    let mut tx = repo.start_transaction(&settings);
+    let root_id = repo.store().root_commit_id().clone();
+    let commit1 = tx
+        .repo_mut()
+        .new_commit(&settings, vec![root_id], tree1.id())
+        .write()
+        .unwrap();
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(&settings, vec![commit1.id().clone()], tree3.id())
+        .write()
+        .unwrap();
+    let commit3 = tx
+        .repo_mut()
+        .new_commit(&settings, vec![commit1.id().clone()], tree1.id())
+        .write()
+        .unwrap();
+    let commit4 = tx
+        .repo_mut()
+        .new_commit(
+            &settings,
+            vec![commit2.id().clone(), commit3.id().clone()],
+            tree4.id(),
+        )
+        .write()
+        .unwrap();
+    let commit5 = tx
+        .repo_mut()
+        .new_commit(&settings, vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let repo = tx.commit("test");
+
+    let changed_paths = |repo: &Arc<ReadonlyRepo>, id: &CommitId| {
+        let index = as_readonly_composite(repo);
+        let entry = index.entry_by_id(id).unwrap();
+        entry
+            .changed_paths()
+            .map(|paths| paths.into_iter().map(|path| path.to_owned()).collect_vec())
+    };
+    let check = |repo: &Arc<ReadonlyRepo>| {
+        assert_eq!(
+            changed_paths(repo, repo.store().root_commit_id()),
+            Some(vec![])
+        );
+        assert_eq!(
+            changed_paths(repo, commit1.id()),
+            Some(vec![file1.to_owned()])
+        );
+        assert_eq!(
+            changed_paths(repo, commit2.id()),
+            Some(vec![file2.to_owned()])
+        );
+        assert_eq!(changed_paths(repo, commit3.id()), Some(vec![]));
+        assert_eq!(
+            changed_paths(repo, commit4.id()),
+            Some(vec![file2.to_owned()])
+        );
+        assert_eq!(
+            changed_paths(repo, commit5.id()),
+            Some(vec![file2.to_owned(), file1.to_owned()])
+        );
+    };
+    check(&repo);
+
+    // Changed paths should be persisted in the index segment files
+    let repo = load_repo_at_head(&settings, test_repo.repo_path());
+    check(&repo);
+
+    // Incremental segments inherit the setting from the parent segment
+    let default_settings = testutils::user_settings();
+    let repo = load_repo_at_head(&default_settings, test_repo.repo_path());
+    let mut tx = repo.start_transaction(&default_settings);
+    let commit6 = tx
+        .repo_mut()
+        .new_commit(&default_settings, vec![commit5.id().clone()], tree4.id())
+        .write()
+        .unwrap();
+    let repo = tx.commit("test");
+    check(&repo);
+    assert_eq!(
+        changed_paths(&repo, commit6.id()),
+        Some(vec![file2.to_owned(), file1.to_owned()])
+    );
+
+    // Reindexing without the setting drops the changed paths
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    default_index_store.reinit().unwrap();
+    let repo = load_repo_at_head(&default_settings, test_repo.repo_path());
+    assert_eq!(changed_paths(&repo, commit1.id()), None);
+    assert_eq!(changed_paths(&repo, commit6.id()), None);
+}
+
+#[test]
+fn test_reindex_corrupt_changed_paths() {
+    let config = testutils::base_config()
+        .set_override("index.changed-paths", true)
+        .unwrap()
+        .build()
+        .unwrap();
+    let settings = UserSettings::from_config(config);
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+
+    let file = RepoPath::from_internal_string("file");
+    let tree = create_tree(repo, &[(file, "1")]);
+    let mut tx = repo.start_transaction(&settings);
+    let commit = tx
+        .repo_mut()
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            tree.id(),
+        )
+        .write()
+        .unwrap();
+    tx.commit("test");
+
+    // Replace the stored path with invalid UTF-8
+    let segments_dir = test_repo.repo_path().join("index").join("segments");
+    let mut corrupted = false;
+    for entry in segments_dir.read_dir().unwrap() {
+        let path = entry.unwrap().path();
+        let mut data = fs::read(&path).unwrap();
+        if data.ends_with(b"file") {
+            let len = data.len();
+            data[len - 1] = 0xff;
+            fs::write(&path, data).unwrap();
+            corrupted = true;
+        }
+    }
+    assert!(corrupted);
+
+    // The corrupt segment is rejected and the index is rebuilt
+    let repo = load_repo_at_head(&settings, test_repo.repo_path());
+    let index = as_readonly_composite(&repo);
+    let entry = index.entry_by_id(commit.id()).unwrap();
+    assert_eq!(entry.changed_paths(), Some(vec![file]));
+}
+
 /// Test that .jj/repo/index/type is created when the repo is created.
 #[test]
 fn test_index_store_type() {
@@ -0,0 +1,260 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::FileId;
+use jj_lib::backend::TreeValue;
+use jj_lib::commit::Commit;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::settings::UserSettings;
+use jj_lib::transaction::Transaction;
+use testutils::create_tree;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+
+fn list_dir(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_str().unwrap().to_owned())
+        .sorted()
+        .collect()
+}
+
+fn make_commit(
+    tx: &mut Transaction,
+    settings: &UserSettings,
+    parents: &[&Commit],
+    content: &[(&RepoPath, &str)],
+) -> Commit {
+    let tree = create_tree(tx.base_repo(), content);
+    let parents = parents.iter().map(|commit| commit.id().clone()).collect();
+    tx.repo_mut()
+        .new_commit(settings, parents, tree.id())
+        .write()
+        .unwrap()
+}
+
+fn file_id(commit: &Commit, path: &RepoPath) -> FileId {
+    match commit
+        .tree()
+        .unwrap()
+        .path_value(path)
+        .unwrap()
+        .into_resolved()
+    {
+        Ok(Some(TreeValue::File { id, .. })) => id,
+        value => panic!("unexpected value at {path:?}: {value:?}"),
+    }
+}
+
+fn pack_objects_settings() -> UserSettings {
+    let config = testutils::base_config()
+        .set_override("gc.pack-objects", true)
+        .unwrap()
+        .build()
+        .unwrap();
+    UserSettings::from_config(config)
+}
+
+#[test]
+fn test_gc() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let store_path = test_repo.repo_path().join("store");
+    let repo_path = test_repo.repo_path().to_owned();
+    let repo = test_repo.repo;
+    let base_index = repo.readonly_index();
+    let file1 = RepoPath::from_internal_string("file1");
+    let file2 = RepoPath::from_internal_string("file2");
+    let file3 = RepoPath::from_internal_string("file3");
+
+    // Set up commits:
+    //
+    // B C
+    // |/
+    // A
+    let root_commit = repo.store().root_commit();
+    let mut tx = repo.start_transaction(&settings);
+    let commit_a = make_commit(&mut tx, &settings, &[&root_commit], &[(file1, "a")]);
+    let commit_b = make_commit(&mut tx, &settings, &[&commit_a], &[(file2, "b")]);
+    let commit_c = make_commit(&mut tx, &settings, &[&commit_a], &[(file3, "c")]);
+    let repo = tx.commit("test");
+    let file_b_id = file_id(&commit_b, file2);
+    let file_c_id = file_id(&commit_c, file3);
+    let commit_entries = list_dir(&store_path.join("commits"));
+    let file_entries = list_dir(&store_path.join("files"));
+    assert_eq!(commit_entries.len(), 3);
+    assert_eq!(file_entries.len(), 3);
+
+    // Empty index, but all kept by file modification time
+    repo.store()
+        .gc(base_index.as_index(), SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(list_dir(&store_path.join("commits")), commit_entries);
+    assert_eq!(list_dir(&store_path.join("files")), file_entries);
+
+    // Don't rely on the exact system time because file modification time might
+    // have lower precision for example.
+    let now = || SystemTime::now() + Duration::from_secs(1);
+
+    // All reachable
+    repo.store().gc(repo.index(), now()).unwrap();
+    assert_eq!(list_dir(&store_path.join("commits")), commit_entries);
+    assert_eq!(list_dir(&store_path.join("files")), file_entries);
+
+    // C is no longer reachable
+    let mut mut_index = base_index.start_modification();
+    mut_index.add_commit(&commit_a);
+    mut_index.add_commit(&commit_b);
+    repo.store().gc(mut_index.as_index(), now()).unwrap();
+    assert_eq!(
+        list_dir(&store_path.join("commits")),
+        [commit_a.id().hex(), commit_b.id().hex()]
+            .into_iter()
+            .sorted()
+            .collect_vec()
+    );
+    assert_eq!(list_dir(&store_path.join("files")).len(), 2);
+    let repo = testutils::load_repo_at_head(&settings, &repo_path);
+    let store = repo.store();
+    assert_eq!(store.get_commit(commit_b.id()).unwrap(), commit_b);
+    assert_eq!(testutils::read_file(store, file2, &file_b_id), b"b");
+    assert!(matches!(
+        store.get_commit(commit_c.id()),
+        Err(BackendError::ObjectNotFound { .. })
+    ));
+    assert!(store.read_file(file3, &file_c_id).is_err());
+}
+
+#[test]
+fn test_gc_pack_objects() {
+    let settings = pack_objects_settings();
+    let test_repo = TestRepo::init_with_backend_and_settings(TestRepoBackend::Local, &settings);
+    let store_path = test_repo.repo_path().join("store");
+    let repo_path = test_repo.repo_path().to_owned();
+    let repo = test_repo.repo;
+    let base_index = repo.readonly_index();
+    let file1 = RepoPath::from_internal_string("file1");
+    let file2 = RepoPath::from_internal_string("dir/file2");
+
+    // Set up commits:
+    //
+    // B C
+    // |/
+    // A
+    let root_commit = repo.store().root_commit();
+    let mut tx = repo.start_transaction(&settings);
+    let commit_a = make_commit(&mut tx, &settings, &[&root_commit], &[(file1, "a")]);
+    let commit_b = make_commit(&mut tx, &settings, &[&commit_a], &[(file2, "b")]);
+    let commit_c = make_commit(&mut tx, &settings, &[&commit_a], &[(file2, "c")]);
+    let repo = tx.commit("test");
+    let file_b_id = file_id(&commit_b, file2);
+    let file_c_id = file_id(&commit_c, file2);
+
+    // Empty index, but all kept by file modification time
+    repo.store()
+        .gc(base_index.as_index(), SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(list_dir(&store_path.join("commits")).len(), 3);
+    assert_eq!(list_dir(&store_path.join("packs")).len(), 2);
+
+    let now = || SystemTime::now() + Duration::from_secs(1);
+
+    // All reachable objects are moved to a new pack
+    repo.store().gc(repo.index(), now()).unwrap();
+    assert_eq!(list_dir(&store_path.join("commits")), [] as [String; 0]);
+    assert_eq!(list_dir(&store_path.join("trees")), [] as [String; 0]);
+    assert_eq!(list_dir(&store_path.join("files")), [] as [String; 0]);
+    assert_eq!(list_dir(&store_path.join("packs")).len(), 2);
+    let repo = testutils::load_repo_at_head(&settings, &repo_path);
+    let store = repo.store();
+    for commit in [&commit_a, &commit_b, &commit_c] {
+        assert_eq!(store.get_commit(commit.id()).unwrap(), *commit);
+    }
+    assert_eq!(testutils::read_file(store, file2, &file_c_id), b"c");
+
+    // Running gc again doesn't change the pack
+    let pack_entries = list_dir(&store_path.join("packs"));
+    repo.store().gc(repo.index(), now()).unwrap();
+    assert_eq!(list_dir(&store_path.join("packs")), pack_entries);
+
+    // New objects are written as loose files, and packed together with the
+    // existing objects
+    let mut tx = repo.start_transaction(&settings);
+    let commit_d = make_commit(&mut tx, &settings, &[&commit_b], &[(file1, "d")]);
+    let repo = tx.commit("test");
+    assert_eq!(list_dir(&store_path.join("commits")), [commit_d.id().hex()]);
+    repo.store().gc(repo.index(), now()).unwrap();
+    assert_eq!(list_dir(&store_path.join("commits")), [] as [String; 0]);
+    assert_eq!(list_dir(&store_path.join("packs")).len(), 2);
+    assert_ne!(list_dir(&store_path.join("packs")), pack_entries);
+
+    // C is no longer reachable
+    let mut mut_index = base_index.start_modification();
+    for commit in [&commit_a, &commit_b, &commit_d] {
+        mut_index.add_commit(commit);
+    }
+    repo.store().gc(mut_index.as_index(), now()).unwrap();
+    let repo = testutils::load_repo_at_head(&settings, &repo_path);
+    let store = repo.store();
+    for commit in [&commit_a, &commit_b, &commit_d] {
+        assert_eq!(store.get_commit(commit.id()).unwrap(), *commit);
+    }
+    assert_eq!(testutils::read_file(store, file2, &file_b_id), b"b");
+    assert!(matches!(
+        store.get_commit(commit_c.id()),
+        Err(BackendError::ObjectNotFound { .. })
+    ));
+    assert!(store.read_file(file2, &file_c_id).is_err());
+}
+
+#[test]
+fn test_corrupt_pack_index() {
+    let settings = pack_objects_settings();
+    let test_repo = TestRepo::init_with_backend_and_settings(TestRepoBackend::Local, &settings);
+    let packs_path = test_repo.repo_path().join("store").join("packs");
+    let repo_path = test_repo.repo_path().to_owned();
+    let repo = test_repo.repo;
+    let file1 = RepoPath::from_internal_string("file1");
+
+    let root_commit = repo.store().root_commit();
+    let mut tx = repo.start_transaction(&settings);
+    let commit_a = make_commit(&mut tx, &settings, &[&root_commit], &[(file1, "a")]);
+    let repo = tx.commit("test");
+    let now = SystemTime::now() + Duration::from_secs(1);
+    repo.store().gc(repo.index(), now).unwrap();
+
+    // The size of the last entry is out of the bounds of the pack file
+    for name in list_dir(&packs_path) {
+        if name.ends_with(".idx") {
+            let path = packs_path.join(name);
+            let mut data = std::fs::read(&path).unwrap();
+            let len = data.len();
+            data[len - 8..].copy_from_slice(&u64::MAX.to_le_bytes());
+            std::fs::write(&path, data).unwrap();
+        }
+    }
+    let repo = testutils::load_repo_at_head(&settings, &repo_path);
+    assert!(matches!(
+        repo.store().get_commit(commit_a.id()),
+        Err(BackendError::ReadObject { .. })
+    ));
+}
@@ -18,20 +18,25 @@ use std::os::unix::fs::PermissionsExt;
 use std::os::unix::net::UnixListener;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use indoc::indoc;
 use itertools::Itertools;
+use jj_lib::backend::CommitCopy;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeId;
 use jj_lib::backend::TreeValue;
 use jj_lib::file_util::check_symlink_support;
 use jj_lib::file_util::try_symlink;
 use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::merge::Merge;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::object_id::ObjectId as _;
 use jj_lib::op_store::OperationId;
 use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::ReadonlyRepo;
@@ -41,6 +46,7 @@ use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::repo_path::RepoPathComponent;
 use jj_lib::secret_backend::SecretBackend;
 use jj_lib::settings::UserSettings;
+use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
 use jj_lib::working_copy::SnapshotError;
 use jj_lib::working_copy::SnapshotOptions;
@@ -243,10 +249,20 @@ fn test_checkout_file_transitions(backend: TestRepoBackend) {
     let right_commit = commit_with_tree(&store, right_tree_id.clone());
 
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &left_commit)
-        .unwrap();
-    ws.check_out(repo.op_id().clone(), None, &right_commit)
-        .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &left_commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &right_commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Check that the working copy is clean.
     let new_tree = test_workspace.snapshot().unwrap();
@@ -319,8 +335,9 @@ fn test_checkout_file_transitions(backend: TestRepoBackend) {
                 assert!(metadata.is_dir(), "{path:?} should be a directory");
             }
             Kind::GitSubmodule => {
-                // Not supported for now
-                assert!(maybe_metadata.is_err(), "{path:?} should not exist");
+                assert!(maybe_metadata.is_ok(), "{path:?} should exist");
+                let metadata = maybe_metadata.unwrap();
+                assert!(metadata.is_dir(), "{path:?} should be a directory");
             }
         };
     }
@@ -342,9 +359,20 @@ fn test_conflict_subdirectory() {
     let merged_commit = commit_with_tree(repo.store(), merged_tree.id());
     let repo = &test_workspace.repo;
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &commit1).unwrap();
-    ws.check_out(repo.op_id().clone(), None, &merged_commit)
-        .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &merged_commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 }
 
 #[test]
@@ -393,13 +421,25 @@ fn test_acl() {
     let commit1 = repo.store().get_commit(commit1.id()).unwrap();
     let commit2 = repo.store().get_commit(commit2.id()).unwrap();
 
-    ws.check_out(repo.op_id().clone(), None, &commit1).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
     assert!(!secret_modified_path.to_fs_path(&workspace_root).is_file());
     assert!(!secret_added_path.to_fs_path(&workspace_root).is_file());
     assert!(!secret_deleted_path.to_fs_path(&workspace_root).is_file());
     assert!(became_secret_path.to_fs_path(&workspace_root).is_file());
     assert!(!became_public_path.to_fs_path(&workspace_root).is_file());
-    ws.check_out(repo.op_id().clone(), None, &commit2).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit2,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
     assert!(!secret_modified_path.to_fs_path(&workspace_root).is_file());
     assert!(!secret_added_path.to_fs_path(&workspace_root).is_file());
     assert!(!secret_deleted_path.to_fs_path(&workspace_root).is_file());
@@ -418,7 +458,13 @@ fn test_tree_builder_file_directory_transition() {
     let mut check_out_tree = |tree_id: &TreeId| {
         let tree = repo.store().get_tree(RepoPath::root(), tree_id).unwrap();
         let commit = commit_with_tree(repo.store(), MergedTreeId::Legacy(tree.id().clone()));
-        ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+        ws.check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
     };
 
     let parent_path = RepoPath::from_internal_string("foo/bar");
@@ -495,7 +541,14 @@ fn test_conflicting_changes_on_disk() {
     )
     .unwrap();
 
-    let stats = ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
     assert_eq!(
         stats,
         CheckoutStats {
@@ -541,7 +594,13 @@ fn test_reset() {
 
     let ws = &mut test_workspace.workspace;
     let commit = commit_with_tree(repo.store(), tree_with_file.id());
-    ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Test the setup: the file should exist on disk and in the tree state.
     assert!(ignored_path.to_fs_path(&workspace_root).is_file());
@@ -593,7 +652,13 @@ fn test_checkout_discard() {
     let commit2 = commit_with_tree(repo.store(), tree2.id());
 
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &commit1).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
     let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
     let state_path = wc.state_path().to_path_buf();
 
@@ -604,7 +669,10 @@ fn test_checkout_discard() {
 
     // Start a checkout
     let mut locked_ws = ws.start_working_copy_mutation().unwrap();
-    locked_ws.locked_wc().check_out(&commit2).unwrap();
+    locked_ws
+        .locked_wc()
+        .check_out(&commit2, &CheckoutOptions::empty_for_test())
+        .unwrap();
     // The change should be reflected in the working copy but not saved
     assert!(!file1_path.to_fs_path(&workspace_root).is_file());
     assert!(file2_path.to_fs_path(&workspace_root).is_file());
@@ -651,7 +719,14 @@ fn test_materialize_snapshot_conflicted_files() {
         .unwrap();
     let commit = commit_with_tree(repo.store(), merged_tree.id());
 
-    let stats = ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    let stats = ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
     assert_eq!(
         stats,
         CheckoutStats {
@@ -898,7 +973,13 @@ fn test_gitignores_in_ignored_dir() {
     let tree1 = create_tree(&test_workspace.repo, &[(gitignore_path, "ignored\n")]);
     let commit1 = commit_with_tree(test_workspace.repo.store(), tree1.id());
     let ws = &mut test_workspace.workspace;
-    ws.check_out(op_id.clone(), None, &commit1).unwrap();
+    ws.check_out(
+        op_id.clone(),
+        None,
+        &commit1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     testutils::write_working_copy_file(&workspace_root, nested_gitignore_path, "!file\n");
     testutils::write_working_copy_file(&workspace_root, ignored_path, "contents");
@@ -950,7 +1031,14 @@ fn test_gitignores_checkout_never_overwrites_ignored() {
     // "contents". The exiting contents ("garbage") shouldn't be replaced in the
     // working copy.
     let ws = &mut test_workspace.workspace;
-    assert!(ws.check_out(repo.op_id().clone(), None, &commit).is_ok());
+    assert!(ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test()
+        )
+        .is_ok());
 
     // Check that the old contents are in the working copy
     let path = workspace_root.join("modified");
@@ -1020,7 +1108,13 @@ fn test_gitignores_ignored_directory_already_tracked() {
 
     // Check out the tree with the files in `ignored/`
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Make some changes inside the ignored directory and check that they are
     // detected when we snapshot. The files that are still there should not be
@@ -1125,9 +1219,16 @@ fn test_gitsubmodule() {
     let tree = store.get_root_tree(&tree_id).unwrap();
     let commit = commit_with_tree(repo.store(), tree.id());
     let ws = &mut test_workspace.workspace;
-    ws.check_out(repo.op_id().clone(), None, &commit).unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
-    std::fs::create_dir(submodule_path.to_fs_path(&workspace_root)).unwrap();
+    // An empty directory is created for the submodule
+    assert!(submodule_path.to_fs_path(&workspace_root).is_dir());
 
     testutils::write_working_copy_file(
         &workspace_root,
@@ -1148,6 +1249,79 @@ fn test_gitsubmodule() {
     );
 }
 
+#[test]
+fn test_gitsubmodule_snapshot_head() {
+    // Tests that the commit checked out in a submodule is snapshotted.
+
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init_with_backend(&settings, TestRepoBackend::Git);
+    let repo = &test_workspace.repo;
+    let store = repo.store().clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let submodule_path = RepoPath::from_internal_string("submodule");
+    let mut tx = repo.start_transaction(&settings);
+    let submodule_id1 = write_random_commit(tx.repo_mut(), &settings).id().clone();
+    let submodule_id2 = write_random_commit(tx.repo_mut(), &settings).id().clone();
+    tx.commit("create submodule commits");
+
+    let mut tree_builder = store.tree_builder(store.empty_tree_id().clone());
+    tree_builder.set(
+        submodule_path.to_owned(),
+        TreeValue::GitSubmodule(submodule_id1.clone()),
+    );
+    let tree_id = MergedTreeId::Legacy(tree_builder.write_tree().unwrap());
+    let tree = store.get_root_tree(&tree_id).unwrap();
+    let commit = commit_with_tree(repo.store(), tree.id());
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+
+    // Uninitialized submodule is left unchanged
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree_id);
+
+    // Populate the submodule working copy with a detached HEAD
+    let submodule_git_dir = workspace_root.join("submodule").join(".git");
+    std::fs::create_dir(&submodule_git_dir).unwrap();
+    std::fs::write(submodule_git_dir.join("HEAD"), submodule_id1.hex()).unwrap();
+    // Make sure the HEAD updated below will have a different mtime
+    std::fs::File::options()
+        .write(true)
+        .open(submodule_git_dir.join("HEAD"))
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+        .unwrap();
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree_id);
+
+    // Move HEAD through a branch in a separate git dir
+    std::fs::remove_dir_all(&submodule_git_dir).unwrap();
+    let separate_git_dir = workspace_root.parent().unwrap().join("sub.git");
+    std::fs::create_dir_all(separate_git_dir.join("refs").join("heads")).unwrap();
+    std::fs::write(separate_git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+    std::fs::write(
+        separate_git_dir.join("refs").join("heads").join("main"),
+        format!("{}\n", submodule_id2.hex()),
+    )
+    .unwrap();
+    std::fs::write(
+        &submodule_git_dir,
+        format!("gitdir: {}\n", separate_git_dir.display()),
+    )
+    .unwrap();
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(
+        new_tree.path_value(submodule_path).unwrap(),
+        Merge::normal(TreeValue::GitSubmodule(submodule_id2))
+    );
+}
+
 #[test]
 fn test_existing_directory_symlink() {
     let settings = testutils::user_settings();
@@ -1169,7 +1343,14 @@ fn test_existing_directory_symlink() {
 
     // Checkout should fail because "parent" already exists and is a symlink.
     let ws = &mut test_workspace.workspace;
-    assert!(ws.check_out(repo.op_id().clone(), None, &commit).is_err());
+    assert!(ws
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test()
+        )
+        .is_err());
 
     // Therefore, "../escaped" shouldn't be created.
     assert!(!workspace_root.parent().unwrap().join("escaped").exists());
@@ -1269,6 +1450,172 @@ fn test_fsmonitor() {
     }
 }
 
+#[cfg(target_os = "linux")]
+#[test]
+fn test_fsmonitor_inotify() {
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let ws = &mut test_workspace.workspace;
+
+    let foo_path = RepoPath::from_internal_string("foo");
+    let bar_path = RepoPath::from_internal_string("bar");
+    let nested_path = RepoPath::from_internal_string("dir/nested");
+    let gitignore_path = RepoPath::from_internal_string(".gitignore");
+    let ignored_tracked_path = RepoPath::from_internal_string("ignored/tracked");
+    let ignored_new_path = RepoPath::from_internal_string("ignored/new");
+    let snapshot = |ws: &mut Workspace| {
+        let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+        let tree_id = locked_ws
+            .locked_wc()
+            .snapshot(&SnapshotOptions {
+                fsmonitor_settings: FsmonitorSettings::Inotify,
+                ..SnapshotOptions::empty_for_test()
+            })
+            .unwrap();
+        locked_ws.finish(repo.op_id().clone()).unwrap();
+        tree_id
+    };
+
+    // Track a file before its directory gets ignored
+    testutils::write_working_copy_file(&workspace_root, ignored_tracked_path, "tracked\n");
+    snapshot(ws);
+    testutils::write_working_copy_file(&workspace_root, gitignore_path, "ignored/\n");
+
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let fsmonitor = wc.inotify_fsmonitor();
+    assert!(!fsmonitor.is_daemon_running().unwrap());
+    let daemon = {
+        let fsmonitor = wc.inotify_fsmonitor();
+        std::thread::spawn(move || fsmonitor.run_daemon(GitIgnoreFile::empty()))
+    };
+    assert!(fsmonitor.wait_for_daemon(Duration::from_secs(10)).unwrap());
+
+    // The first snapshot crawls the working copy
+    testutils::write_working_copy_file(&workspace_root, foo_path, "foo\n");
+    let tree_id = snapshot(ws);
+    insta::assert_snapshot!(testutils::dump_tree(repo.store(), &tree_id), @r###"
+    tree 11d61374241dd658c23e
+      file ".gitignore" (db22bcd2a37d8ee3fddc): "ignored/\n"
+      file "foo" (e99c2057c15160add351): "foo\n"
+      file "ignored/tracked" (f2f1052ce882ee0a2390): "tracked\n"
+    "###);
+
+    // Then only the paths reported by the daemon are checked
+    testutils::write_working_copy_file(&workspace_root, bar_path, "bar\n");
+    testutils::write_working_copy_file(&workspace_root, nested_path, "nested\n");
+    std::fs::remove_file(foo_path.to_fs_path(&workspace_root)).unwrap();
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let (_clock, changed_files) = wc.query_inotify().unwrap();
+    let changed_files = changed_files.unwrap();
+    assert!(changed_files.contains(&"foo".into()));
+    assert!(changed_files.contains(&"bar".into()));
+    assert!(changed_files.contains(&"dir".into()));
+    let tree_id = snapshot(ws);
+    insta::assert_snapshot!(testutils::dump_tree(repo.store(), &tree_id), @r###"
+    tree fb8ee86fa4ec1f8e066f
+      file ".gitignore" (db22bcd2a37d8ee3fddc): "ignored/\n"
+      file "bar" (94cc973e7e1aefb7eff6): "bar\n"
+      file "dir/nested" (6209060941cd770c8d46): "nested\n"
+      file "ignored/tracked" (f2f1052ce882ee0a2390): "tracked\n"
+    "###);
+
+    // The ignored directory isn't watched, so it's always reported in case
+    // tracked files in it changed
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let (_clock, changed_files) = wc.query_inotify().unwrap();
+    assert_eq!(changed_files, Some(vec!["ignored".into()]));
+    testutils::write_working_copy_file(&workspace_root, ignored_tracked_path, "modified\n");
+    let tree_id = snapshot(ws);
+    insta::assert_snapshot!(testutils::dump_tree(repo.store(), &tree_id), @r###"
+    tree 5c14255e44160929a51f
+      file ".gitignore" (db22bcd2a37d8ee3fddc): "ignored/\n"
+      file "bar" (94cc973e7e1aefb7eff6): "bar\n"
+      file "dir/nested" (6209060941cd770c8d46): "nested\n"
+      file "ignored/tracked" (9fc795c07576dde300b4): "modified\n"
+    "###);
+
+    // Once the directory is no longer ignored, it's watched and the files in
+    // it are picked up
+    testutils::write_working_copy_file(&workspace_root, gitignore_path, "");
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let (_clock, changed_files) = wc.query_inotify().unwrap();
+    assert!(changed_files.unwrap().contains(&"".into()));
+    snapshot(ws);
+    testutils::write_working_copy_file(&workspace_root, ignored_new_path, "new\n");
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let (_clock, changed_files) = wc.query_inotify().unwrap();
+    let changed_files = changed_files.unwrap();
+    assert!(changed_files.contains(&"ignored/new".into()));
+    assert!(!changed_files.contains(&"ignored".into()));
+
+    fsmonitor.stop_daemon().unwrap();
+    daemon.join().unwrap().unwrap();
+    assert!(!fsmonitor.is_daemon_running().unwrap());
+}
+
+#[test]
+fn test_snapshot_copies() {
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo;
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let op_id = repo.op_id().clone();
+
+    let file1_path = RepoPath::from_internal_string("file1");
+    let file2_path = RepoPath::from_internal_string("file2");
+    let file3_path = RepoPath::from_internal_string("file3");
+    let unchanged_path = RepoPath::from_internal_string("unchanged");
+    let copy_path = RepoPath::from_internal_string("copy");
+    std::fs::write(file1_path.to_fs_path(&workspace_root), "contents").unwrap();
+    std::fs::write(unchanged_path.to_fs_path(&workspace_root), "unchanged").unwrap();
+    test_workspace.snapshot().unwrap();
+
+    let ws = &mut test_workspace.workspace;
+    let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+    assert_eq!(locked_ws.locked_wc().snapshot_copies(), vec![]);
+
+    // Renames are composed across snapshots
+    std::fs::rename(
+        file1_path.to_fs_path(&workspace_root),
+        file2_path.to_fs_path(&workspace_root),
+    )
+    .unwrap();
+    locked_ws
+        .locked_wc()
+        .snapshot(&SnapshotOptions::empty_for_test())
+        .unwrap();
+    std::fs::rename(
+        file2_path.to_fs_path(&workspace_root),
+        file3_path.to_fs_path(&workspace_root),
+    )
+    .unwrap();
+    // Copies of files that weren't changed by the snapshot aren't detected
+    std::fs::write(copy_path.to_fs_path(&workspace_root), "unchanged").unwrap();
+    locked_ws
+        .locked_wc()
+        .snapshot(&SnapshotOptions::empty_for_test())
+        .unwrap();
+    assert_eq!(
+        locked_ws.locked_wc().snapshot_copies(),
+        vec![CommitCopy {
+            source: file1_path.to_owned(),
+            target: file3_path.to_owned(),
+        }]
+    );
+    locked_ws.finish(op_id.clone()).unwrap();
+
+    // A new lock starts from the snapshotted tree
+    let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+    locked_ws
+        .locked_wc()
+        .snapshot(&SnapshotOptions::empty_for_test())
+        .unwrap();
+    assert_eq!(locked_ws.locked_wc().snapshot_copies(), vec![]);
+    locked_ws.finish(op_id).unwrap();
+}
+
 #[test]
 fn test_snapshot_max_new_file_size() {
     let settings = testutils::user_settings();
@@ -12,17 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use assert_matches::assert_matches;
 use futures::StreamExt;
 use itertools::Itertools;
+use jj_lib::backend::BackendError;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::CopyRecord;
 use jj_lib::backend::FileId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::copies;
 use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
 use jj_lib::files::MergeResult;
+use jj_lib::fileset::FilePredicate;
+use jj_lib::fileset::FilesetExpression;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::matchers::FilesMatcher;
 use jj_lib::matchers::Matcher;
@@ -35,6 +40,7 @@ use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::merged_tree::TreeDiffEntry;
 use jj_lib::merged_tree::TreeDiffIterator;
 use jj_lib::merged_tree::TreeDiffStreamImpl;
+use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
@@ -915,6 +921,91 @@ fn test_diff_copy_tracing() {
     diff_stream_equals_iter(&before_merged, &after_merged, &EverythingMatcher);
 }
 
+#[test]
+fn test_detect_copies() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let modified_path = RepoPath::from_internal_string("modified");
+    let copied_path = RepoPath::from_internal_string("copied");
+    let renamed_from_path = RepoPath::from_internal_string("dir/renamed");
+    let renamed_to_path = RepoPath::from_internal_string("renamed");
+    let edited_from_path = RepoPath::from_internal_string("edited");
+    let edited_to_path = RepoPath::from_internal_string("dir/edited");
+    let removed_path = RepoPath::from_internal_string("removed");
+    let added_path = RepoPath::from_internal_string("added");
+    let before = MergedTree::resolved(create_single_tree(
+        repo,
+        &[
+            (modified_path, "before\n"),
+            (renamed_from_path, "renamed\n"),
+            (edited_from_path, "1\n2\n3\n4\n"),
+            (removed_path, "a\nb\nc\n"),
+        ],
+    ));
+    let after = MergedTree::resolved(create_single_tree(
+        repo,
+        &[
+            (modified_path, "after\n"),
+            (copied_path, "before\n"),
+            (renamed_to_path, "renamed\n"),
+            (edited_to_path, "1\n2\n3\n5\n"),
+            (added_path, "a\nx\ny\n"),
+        ],
+    ));
+
+    let copies = copies::detect_copies(&before, &after).unwrap();
+    let copies = copies
+        .iter()
+        .map(|copy| (copy.source.as_ref(), copy.target.as_ref()))
+        .collect_vec();
+    assert_eq!(
+        copies,
+        vec![
+            (modified_path, copied_path),
+            (renamed_from_path, renamed_to_path),
+            (edited_from_path, edited_to_path),
+        ]
+    );
+
+    // Nothing can be copied if no file was deleted or modified
+    let added = MergedTree::resolved(create_single_tree(
+        repo,
+        &[
+            (modified_path, "before\n"),
+            (renamed_from_path, "renamed\n"),
+            (edited_from_path, "1\n2\n3\n4\n"),
+            (removed_path, "a\nb\nc\n"),
+            (copied_path, "before\n"),
+        ],
+    ));
+    assert_eq!(copies::detect_copies(&before, &added).unwrap(), vec![]);
+
+    // Only the given paths are looked at if they're known
+    let copies = copies::detect_copies_in_paths(
+        &before,
+        &after,
+        [
+            modified_path,
+            copied_path,
+            renamed_from_path,
+            renamed_to_path,
+        ],
+    )
+    .unwrap();
+    let copies = copies
+        .iter()
+        .map(|copy| (copy.source.as_ref(), copy.target.as_ref()))
+        .collect_vec();
+    assert_eq!(
+        copies,
+        vec![
+            (modified_path, copied_path),
+            (renamed_from_path, renamed_to_path),
+        ]
+    );
+}
+
 /// Diff two conflicted trees
 #[test]
 fn test_diff_conflicted() {
@@ -1527,6 +1618,77 @@ fn test_merge_simplify_file_conflict() {
     ));
 }
 
+#[test]
+fn test_merge_with_copies() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let old_path = RepoPath::from_internal_string("old/file");
+    let new_path = RepoPath::from_internal_string("new/file");
+    let other_path = RepoPath::from_internal_string("other");
+    let base = MergedTree::resolved(create_single_tree(
+        repo,
+        &[(old_path, "1\n2\n3\n"), (other_path, "base\n")],
+    ));
+    let renamed = MergedTree::resolved(create_single_tree(
+        repo,
+        &[(new_path, "1\n2\n3\n4\n"), (other_path, "base\n")],
+    ));
+    let edited = MergedTree::resolved(create_single_tree(
+        repo,
+        &[(old_path, "0\n1\n2\n3\n"), (other_path, "edited\n")],
+    ));
+    let expected = MergedTree::resolved(create_single_tree(
+        repo,
+        &[(new_path, "0\n1\n2\n3\n4\n"), (other_path, "edited\n")],
+    ));
+    let copy_records = create_copy_records(&[(old_path, new_path)]);
+
+    // The edit is carried to the new path whichever side renamed the file
+    let merged = renamed
+        .merge_with_copies(&base, &edited, &copy_records)
+        .unwrap();
+    assert_eq!(merged, expected);
+    let merged = edited
+        .merge_with_copies(&base, &renamed, &copy_records)
+        .unwrap();
+    assert_eq!(merged, expected);
+
+    // Without copy records, it's a modify/delete conflict
+    let merged = renamed
+        .merge_with_copies(&base, &edited, &CopyRecords::default())
+        .unwrap();
+    assert!(!merged.path_value(old_path).unwrap().is_resolved());
+    assert_eq!(merged, renamed.merge(&base, &edited).unwrap());
+
+    // If the other side deleted the file, the renamed file is kept as is
+    let deleted = MergedTree::resolved(create_single_tree(repo, &[(other_path, "base\n")]));
+    let merged = renamed
+        .merge_with_copies(&base, &deleted, &copy_records)
+        .unwrap();
+    assert_eq!(merged, renamed);
+
+    // If both sides renamed the file to different paths, both paths are kept
+    let other_new_path = RepoPath::from_internal_string("other/new/file");
+    let renamed_elsewhere = MergedTree::resolved(create_single_tree(
+        repo,
+        &[(other_new_path, "1\n2\n3\n"), (other_path, "base\n")],
+    ));
+    let copy_records = create_copy_records(&[(old_path, new_path), (old_path, other_new_path)]);
+    let merged = renamed
+        .merge_with_copies(&base, &renamed_elsewhere, &copy_records)
+        .unwrap();
+    let expected = MergedTree::resolved(create_single_tree(
+        repo,
+        &[
+            (new_path, "1\n2\n3\n4\n"),
+            (other_new_path, "1\n2\n3\n"),
+            (other_path, "base\n"),
+        ],
+    ));
+    assert_eq!(merged, expected);
+}
+
 /// Like `test_merge_simplify_file_conflict()`, but some of the conflicts are
 /// absent.
 #[test]
@@ -1557,3 +1719,52 @@ fn test_merge_simplify_file_conflict_with_absent() {
     let merged = child1_merged.merge(&parent_merged, &child2_merged).unwrap();
     assert_eq!(merged, expected_merged);
 }
+
+#[test]
+fn test_file_predicate_errors() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let store = repo.store();
+
+    let present_path = RepoPath::from_internal_string("present");
+    let missing_path = RepoPath::from_internal_string("missing");
+    let present_id = write_file(store, present_path, "a");
+    // Not written to the store, so the contents can't be read
+    let missing_id = FileId::new(vec![0; present_id.as_bytes().len()]);
+    let mut tree_builder = MergedTreeBuilder::new(store.empty_merged_tree_id());
+    tree_builder.set_or_remove(
+        present_path.to_owned(),
+        Merge::normal(file_value(&present_id)),
+    );
+    tree_builder.set_or_remove(
+        missing_path.to_owned(),
+        Merge::normal(file_value(&missing_id)),
+    );
+    let tree = store
+        .get_root_tree(&tree_builder.write_tree(store).unwrap())
+        .unwrap();
+
+    // Evaluating the predicate for a file which can't be read fails
+    let expression = FilesetExpression::Predicate(FilePredicate::Size(1..2));
+    let value_matcher = expression.to_value_matcher();
+    let present_value = tree.path_value(present_path).unwrap();
+    let missing_value = tree.path_value(missing_path).unwrap();
+    assert!(value_matcher
+        .matches_values(store, present_path, &[&present_value])
+        .unwrap());
+    assert_matches!(
+        value_matcher.matches_values(store, missing_path, &[&missing_value]),
+        Err(BackendError::ObjectNotFound { .. })
+    );
+    assert_matches!(
+        expression.to_tree_matcher(&tree),
+        Err(BackendError::ObjectNotFound { .. })
+    );
+
+    // Files which don't match the paths aren't read
+    let expression = FilesetExpression::prefix_path(present_path.to_owned())
+        .intersection(FilesetExpression::Predicate(FilePredicate::Size(1..2)));
+    let matcher = expression.to_tree_matcher(&tree).unwrap();
+    assert!(matcher.matches(present_path));
+    assert!(!matcher.matches(missing_path));
+}
@@ -0,0 +1,423 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use assert_matches::assert_matches;
+use jj_lib::backend;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::ConflictId;
+use jj_lib::backend::FileId;
+use jj_lib::backend::SymlinkId;
+use jj_lib::backend::TreeId;
+use jj_lib::commit::Commit;
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::native_sync;
+use jj_lib::native_sync::FetchStats;
+use jj_lib::native_sync::LocalRepoTransport;
+use jj_lib::native_sync::NativeSyncError;
+use jj_lib::native_sync::ObjectAccess;
+use jj_lib::native_sync::ObjectRef;
+use jj_lib::native_sync::Transport;
+use jj_lib::op_store::OpStoreError;
+use jj_lib::op_store::RefTarget;
+use jj_lib::op_store::RemoteRef;
+use jj_lib::op_store::RemoteRefState;
+use jj_lib::op_store::View;
+use jj_lib::refs::BranchPushUpdate;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::settings::UserSettings;
+use testutils::create_tree;
+use testutils::write_random_commit;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+
+fn load_transport(settings: &UserSettings, test_repo: &TestRepo) -> LocalRepoTransport {
+    LocalRepoTransport::load(
+        settings,
+        test_repo.repo_path(),
+        &TestRepo::default_store_factories(),
+    )
+    .unwrap()
+}
+
+/// Transport which returns one result less than requested when looking up
+/// objects in the remote repo.
+struct MissingResultTransport(LocalRepoTransport);
+
+impl ObjectAccess for MissingResultTransport {
+    fn has_objects(&self, objects: &[ObjectRef]) -> Result<Vec<bool>, NativeSyncError> {
+        let mut exists = self.0.has_objects(objects)?;
+        exists.pop();
+        Ok(exists)
+    }
+
+    fn read_commit(&self, id: &CommitId) -> Result<backend::Commit, NativeSyncError> {
+        self.0.read_commit(id)
+    }
+
+    fn write_commit(&self, commit: backend::Commit) -> Result<CommitId, NativeSyncError> {
+        self.0.write_commit(commit)
+    }
+
+    fn read_tree(&self, path: &RepoPath, id: &TreeId) -> Result<backend::Tree, NativeSyncError> {
+        self.0.read_tree(path, id)
+    }
+
+    fn write_tree(&self, path: &RepoPath, tree: backend::Tree) -> Result<TreeId, NativeSyncError> {
+        self.0.write_tree(path, tree)
+    }
+
+    fn read_file(&self, path: &RepoPath, id: &FileId) -> Result<Vec<u8>, NativeSyncError> {
+        self.0.read_file(path, id)
+    }
+
+    fn write_file(&self, path: &RepoPath, contents: &[u8]) -> Result<FileId, NativeSyncError> {
+        self.0.write_file(path, contents)
+    }
+
+    fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> Result<String, NativeSyncError> {
+        self.0.read_symlink(path, id)
+    }
+
+    fn write_symlink(&self, path: &RepoPath, target: &str) -> Result<SymlinkId, NativeSyncError> {
+        self.0.write_symlink(path, target)
+    }
+
+    fn read_conflict(
+        &self,
+        path: &RepoPath,
+        id: &ConflictId,
+    ) -> Result<MergedTreeValue, NativeSyncError> {
+        self.0.read_conflict(path, id)
+    }
+
+    fn write_conflict(
+        &self,
+        path: &RepoPath,
+        conflict: &MergedTreeValue,
+    ) -> Result<ConflictId, NativeSyncError> {
+        self.0.write_conflict(path, conflict)
+    }
+}
+
+impl Transport for MissingResultTransport {
+    fn backend_name(&self) -> Result<String, NativeSyncError> {
+        self.0.backend_name()
+    }
+
+    fn head_view(&self) -> Result<View, NativeSyncError> {
+        self.0.head_view()
+    }
+
+    fn has_commits(&self, ids: &[CommitId]) -> Result<Vec<bool>, NativeSyncError> {
+        self.0.has_commits(ids)
+    }
+
+    fn update_bookmarks(
+        &self,
+        heads: &[CommitId],
+        updates: &[(String, BranchPushUpdate)],
+    ) -> Result<(), NativeSyncError> {
+        self.0.update_bookmarks(heads, updates)
+    }
+}
+
+/// Creates a commit with a conflicted tree in the remote repo, rewritten from
+/// another commit, and points bookmark "main" to it.
+fn create_conflicted_commit(settings: &UserSettings, test_repo: &TestRepo) -> (Commit, Commit) {
+    let repo = &test_repo.repo;
+    let path = RepoPath::from_internal_string("file");
+    let base_tree = create_tree(repo, &[(path, "base")]);
+    let left_tree = create_tree(repo, &[(path, "left")]);
+    let right_tree = create_tree(repo, &[(path, "right")]);
+    let conflicted_tree = left_tree.merge(&base_tree, &right_tree).unwrap();
+
+    let mut tx = repo.start_transaction(settings);
+    let root_commit_id = repo.store().root_commit_id().clone();
+    let commit_a = tx
+        .repo_mut()
+        .new_commit(settings, vec![root_commit_id], left_tree.id())
+        .write()
+        .unwrap();
+    let commit_b = tx
+        .repo_mut()
+        .rewrite_commit(settings, &commit_a)
+        .set_tree_id(conflicted_tree.id())
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants(settings).unwrap();
+    tx.repo_mut()
+        .set_local_bookmark_target("main", RefTarget::normal(commit_b.id().clone()));
+    tx.commit("test");
+    (commit_a, commit_b)
+}
+
+#[test]
+fn test_fetch() {
+    let settings = testutils::user_settings();
+    let remote_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let local_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let (commit_a, commit_b) = create_conflicted_commit(&settings, &remote_test_repo);
+    let remote_repo = testutils::load_repo_at_head(&settings, remote_test_repo.repo_path());
+
+    let transport = load_transport(&settings, &remote_test_repo);
+    let mut tx = local_test_repo.repo.start_transaction(&settings);
+    let stats = native_sync::fetch(tx.repo_mut(), &transport, "origin").unwrap();
+    assert_eq!(
+        stats,
+        FetchStats {
+            copied_commit_count: 2,
+            changed_bookmarks: vec!["main".to_owned()],
+        }
+    );
+    let repo = tx.commit("fetch");
+
+    // Only the bookmarks are read from the remote repo. Its operations aren't
+    // copied.
+    assert_matches!(
+        repo.op_store().read_operation(remote_repo.op_id()),
+        Err(OpStoreError::ObjectNotFound { .. })
+    );
+    assert_eq!(repo.operation().parent_ids().len(), 1);
+
+    // The commit is copied as is, including the change id, the predecessors
+    // and the conflict.
+    let fetched_commit = repo.store().get_commit(commit_b.id()).unwrap();
+    assert_eq!(fetched_commit.store_commit(), commit_b.store_commit());
+    assert!(fetched_commit.has_conflict().unwrap());
+    assert_eq!(
+        fetched_commit
+            .predecessors()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>(),
+        vec![commit_a.clone()]
+    );
+    assert!(repo.view().heads().contains(commit_b.id()));
+    assert_eq!(
+        repo.view().get_remote_bookmark("main", "origin"),
+        &RemoteRef {
+            target: RefTarget::normal(commit_b.id().clone()),
+            state: RemoteRefState::New,
+        }
+    );
+    assert!(repo.view().get_local_bookmark("main").is_absent());
+
+    // Fetching again doesn't copy or change anything
+    let mut tx = repo.start_transaction(&settings);
+    let stats = native_sync::fetch(tx.repo_mut(), &transport, "origin").unwrap();
+    assert_eq!(stats, FetchStats::default());
+    assert!(!tx.repo().has_changes());
+}
+
+#[test]
+fn test_fetch_tracked_bookmark() {
+    let settings = testutils::user_settings();
+    let remote_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let local_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let (_, commit_b) = create_conflicted_commit(&settings, &remote_test_repo);
+
+    let transport = load_transport(&settings, &remote_test_repo);
+    let mut tx = local_test_repo.repo.start_transaction(&settings);
+    native_sync::fetch(tx.repo_mut(), &transport, "origin").unwrap();
+    tx.repo_mut().track_remote_bookmark("main", "origin");
+    let repo = tx.commit("fetch");
+    assert_eq!(
+        repo.view().get_local_bookmark("main"),
+        &RefTarget::normal(commit_b.id().clone())
+    );
+
+    // Deleted remote bookmark is propagated to the tracking local bookmark
+    let remote_repo = testutils::load_repo_at_head(&settings, remote_test_repo.repo_path());
+    let mut tx = remote_repo.start_transaction(&settings);
+    tx.repo_mut()
+        .set_local_bookmark_target("main", RefTarget::absent());
+    tx.commit("delete");
+    let transport = load_transport(&settings, &remote_test_repo);
+    let mut tx = repo.start_transaction(&settings);
+    let stats = native_sync::fetch(tx.repo_mut(), &transport, "origin").unwrap();
+    assert_eq!(stats.changed_bookmarks, vec!["main".to_owned()]);
+    assert!(tx.repo().get_local_bookmark("main").is_absent());
+    assert!(tx.repo().get_remote_bookmark("main", "origin").is_absent());
+}
+
+#[test]
+fn test_fetch_conflicted_bookmark() {
+    let settings = testutils::user_settings();
+    let remote_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let local_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+
+    let mut tx = remote_test_repo.repo.start_transaction(&settings);
+    let commit1 = write_random_commit(tx.repo_mut(), &settings);
+    let commit2 = write_random_commit(tx.repo_mut(), &settings);
+    let commit3 = write_random_commit(tx.repo_mut(), &settings);
+    let target = RefTarget::from_legacy_form(
+        [commit1.id().clone()],
+        [commit2.id().clone(), commit3.id().clone()],
+    );
+    tx.repo_mut()
+        .set_local_bookmark_target("main", target.clone());
+    tx.commit("test");
+
+    // The removed side of the conflict is copied as well
+    let transport = load_transport(&settings, &remote_test_repo);
+    let mut tx = local_test_repo.repo.start_transaction(&settings);
+    let stats = native_sync::fetch(tx.repo_mut(), &transport, "origin").unwrap();
+    assert_eq!(stats.copied_commit_count, 3);
+    let repo = tx.commit("fetch");
+    for commit in [&commit1, &commit2, &commit3] {
+        assert_eq!(&repo.store().get_commit(commit.id()).unwrap(), commit);
+        assert!(repo.index().has_id(commit.id()));
+    }
+    assert_eq!(
+        repo.view().get_remote_bookmark("main", "origin").target,
+        target
+    );
+}
+
+#[test]
+fn test_push_bookmarks() {
+    let settings = testutils::user_settings();
+    let remote_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let local_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+
+    let mut tx = local_test_repo.repo.start_transaction(&settings);
+    let commit1 = write_random_commit(tx.repo_mut(), &settings);
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(
+            &settings,
+            vec![commit1.id().clone()],
+            commit1.tree_id().clone(),
+        )
+        .write()
+        .unwrap();
+    let transport = load_transport(&settings, &remote_test_repo);
+    let updates = [(
+        "feature".to_owned(),
+        BranchPushUpdate {
+            old_target: None,
+            new_target: Some(commit2.id().clone()),
+        },
+    )];
+    native_sync::push_bookmarks(tx.repo_mut(), &transport, "origin", &updates).unwrap();
+    assert_eq!(
+        tx.repo().get_remote_bookmark("feature", "origin"),
+        RemoteRef {
+            target: RefTarget::normal(commit2.id().clone()),
+            state: RemoteRefState::Tracking,
+        }
+    );
+
+    let remote_repo = testutils::load_repo_at_head(&settings, remote_test_repo.repo_path());
+    assert_eq!(
+        remote_repo.view().get_local_bookmark("feature"),
+        &RefTarget::normal(commit2.id().clone())
+    );
+    assert_eq!(
+        remote_repo.store().get_commit(commit2.id()).unwrap(),
+        commit2
+    );
+    assert!(remote_repo.index().has_id(commit1.id()));
+    assert_eq!(
+        remote_repo.operation().metadata().description,
+        "push bookmarks feature"
+    );
+    // The operations of the local repo aren't copied
+    assert_matches!(
+        remote_repo
+            .op_store()
+            .read_operation(tx.base_repo().op_id()),
+        Err(OpStoreError::ObjectNotFound { .. })
+    );
+
+    // Pushing based on an outdated target fails without updating the remote
+    let transport = load_transport(&settings, &remote_test_repo);
+    let updates = [(
+        "feature".to_owned(),
+        BranchPushUpdate {
+            old_target: None,
+            new_target: Some(commit1.id().clone()),
+        },
+    )];
+    let result = native_sync::push_bookmarks(tx.repo_mut(), &transport, "origin", &updates);
+    assert!(matches!(
+        result,
+        Err(NativeSyncError::RemoteBookmarkMoved(name)) if name == "feature"
+    ));
+    let remote_repo = testutils::load_repo_at_head(&settings, remote_test_repo.repo_path());
+    assert_eq!(
+        remote_repo.view().get_local_bookmark("feature"),
+        &RefTarget::normal(commit2.id().clone())
+    );
+
+    // Pushing a deleted bookmark deletes the remote bookmark
+    let transport = load_transport(&settings, &remote_test_repo);
+    let updates = [(
+        "feature".to_owned(),
+        BranchPushUpdate {
+            old_target: Some(commit2.id().clone()),
+            new_target: None,
+        },
+    )];
+    native_sync::push_bookmarks(tx.repo_mut(), &transport, "origin", &updates).unwrap();
+    assert!(tx
+        .repo()
+        .view()
+        .remote_bookmarks("origin")
+        .all(|(name, _)| name != "feature"));
+    let remote_repo = testutils::load_repo_at_head(&settings, remote_test_repo.repo_path());
+    assert!(remote_repo.view().get_local_bookmark("feature").is_absent());
+}
+
+#[test]
+fn test_incompatible_backend() {
+    let settings = testutils::user_settings();
+    let remote_test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let local_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+
+    let transport = load_transport(&settings, &remote_test_repo);
+    let mut tx = local_test_repo.repo.start_transaction(&settings);
+    let result = native_sync::fetch(tx.repo_mut(), &transport, "origin");
+    assert!(matches!(
+        result,
+        Err(NativeSyncError::IncompatibleBackend { local, remote })
+            if local == "local" && remote == "git"
+    ));
+}
+
+#[test]
+fn test_push_missing_results() {
+    let settings = testutils::user_settings();
+    let remote_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+    let local_test_repo = TestRepo::init_with_backend(TestRepoBackend::Local);
+
+    let mut tx = local_test_repo.repo.start_transaction(&settings);
+    let commit = write_random_commit(tx.repo_mut(), &settings);
+    let transport = MissingResultTransport(load_transport(&settings, &remote_test_repo));
+    let updates = [(
+        "feature".to_owned(),
+        BranchPushUpdate {
+            old_target: None,
+            new_target: Some(commit.id().clone()),
+        },
+    )];
+    let result = native_sync::push_bookmarks(tx.repo_mut(), &transport, "origin", &updates);
+    assert_matches!(
+        result,
+        Err(NativeSyncError::ResultCountMismatch {
+            expected: 1,
+            actual: 0
+        })
+    );
+}
@@ -426,7 +426,7 @@ fn test_resolve_op_id() {
     let mut operations = Vec::new();
     // The actual value of `i` doesn't matter, we just need to make sure we end
     // up with hashes with ambiguous prefixes.
-    for i in (1..7).chain([16]) {
+    for i in (1..6).chain([17, 740]) {
         let tx = repo.start_transaction(&settings);
         let repo = tx.commit(format!("transaction {i}"));
         operations.push(repo.operation().clone());
@@ -434,13 +434,13 @@ fn test_resolve_op_id() {
     // "2" and "0" are ambiguous
     insta::assert_debug_snapshot!(operations.iter().map(|op| op.id().hex()).collect_vec(), @r###"
     [
-        "5aebb24d08d6f5282d9e06bded4b51febbb4ff4bc822cdd4db9043961339955d6af912e783c3864245867d0cf6d609cb004c5ff0cef5e914c15a415ba92e38a2",
-        "ad1cda629b220f2651d972475fba75e47c30cd57862c51a727e57b7b6e2fbaa937d0bcb881cf6fcff30ba1a088a4ad588cf880765b9d9680b551cdc446f3489e",
-        "2feaf9eb61232582d35f1cb0203b69425f7a7d07140a5f8ff0d0bf98dd9f433b941e4c9e0882f65a007266affee275e235ae1614b159ceadbf9fe6879077a5da",
-        "0b183be5767c3ff9945c8a9e3ac7639d249ff9873985a551f4c2070782aea8b5018ebff906036d1658038708ab9de0d867be385b1181aa7a81669e2ef6852355",
-        "d1c3031dff7b1db4db1bfb592a9aa0ea6faae9300033ef20cb6da488e4b90524c22af8e2541cdb99ca0ee2be3299c213c0f8d48390cd5f11462c4f80c5790f68",
-        "2369ba98e2596715606978a72608970287aaa064ec890f87c29b7e7df64fdf02b7f24b8b03ee845e132f3e19e3929de359b6cfe1328b42946c45ac5ff80705ca",
-        "00271842a189d274a2c97ac28f937584a47b84463d5b408c6f998089131e0e1329a287667b2ac5d63f8c576e95323bcf992c99caa4ef4612a1c3798fe8a3f74f",
+        "6397997b87af7b3451cb163e12a5298b76fabc43a8268149e5c82669f72206da89339395d0feb78adfcf17b6238d4619dfdd740291b57bf32f59f06bff3317fe",
+        "f785a7c5cd650f97086a320dfb05e3675eb3268bc485baa30c36b9bd8e01b10814e68ceeff3f20e211daa0bf7760fc0ba958409afb96ab1d775f4d889f4b81db",
+        "818d6b89678b68f97a548da47d4f363c1ec5f5d74db77b87f6e90e1b6c3644d692c1d22ec49474d5aee052ae54afc402b40a5143c6e40f18343d0b8e2a6fdc22",
+        "ff302181c30e54a27e1c0677e5f3f0956a54c9ddb8bcb00fb989c620df96d9f9c492a4a0f205051f24a4bf5288b6993391ddc08eb66af683c71598e4ab5ae6b8",
+        "2e15b2078957c806948fa0ee3c5d6447f29fca8f314bfe93fa53e9e32a1a87b2a88b60a98f884d58e87af01add2aebea4ce8260d394b1eb8ec52a154d4f2db6a",
+        "2c31d030204fcf4a37d68a302bb52d97f321ec9c42aada43a1642ecad68203530c0183cab8a740925131fde3c90552908f668ea831571792adf9e34125155a96",
+        "00a72e53854b2545799c48129252a2bd552242e2d4d124b256c28537c115428c936ed08c28005b553498660cadeef886d1e5ebd1b9f0bcbfadc8c9672ff58700",
     ]
     "###);
 
@@ -488,7 +488,7 @@ fn test_resolve_op_id() {
     };
     assert_eq!(resolve(&root_operation.id().hex()).unwrap(), root_operation);
     assert_eq!(resolve("000").unwrap(), root_operation);
-    assert_eq!(resolve("002").unwrap(), operations[6]);
+    assert_eq!(resolve("00a").unwrap(), operations[6]);
     assert_matches!(
         resolve("0"),
         Err(OpsetEvaluationError::OpsetResolution(
@@ -674,3 +674,60 @@ fn test_gc() {
     assert_eq!(expected_op_entries.len(), 2);
     assert_eq!(expected_view_entries.len(), 2);
 }
+
+#[test]
+fn test_gc_pack_objects() {
+    let settings = UserSettings::from_config(
+        testutils::base_config()
+            .set_override("gc.pack-objects", true)
+            .unwrap()
+            .build()
+            .unwrap(),
+    );
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let op_dir = test_repo.repo_path().join("op_store").join("operations");
+    let view_dir = test_repo.repo_path().join("op_store").join("views");
+    let pack_dir = test_repo.repo_path().join("op_store").join("packs");
+    let repo_path = test_repo.repo_path().to_owned();
+    let repo_0 = test_repo.repo;
+    let op_store = repo_0.op_store();
+
+    // Set up operation graph:
+    //
+    // B C
+    // |/
+    // A
+    // 0 (initial)
+    let random_tx = |repo: &Arc<ReadonlyRepo>| {
+        let mut tx = repo.start_transaction(&settings);
+        write_random_commit(tx.repo_mut(), &settings);
+        tx
+    };
+    let repo_a = random_tx(&repo_0).commit("op A");
+    let repo_b = random_tx(&repo_a).commit("op B");
+    let repo_c = random_tx(&repo_a).commit("op C");
+
+    // All reachable operations and views are moved to a pack
+    let now = SystemTime::now();
+    let head_ids = [repo_b.op_id().clone(), repo_c.op_id().clone()];
+    op_store.gc(&head_ids, now).unwrap();
+    assert!(list_dir(&op_dir).is_empty());
+    assert!(list_dir(&view_dir).is_empty());
+    assert_eq!(list_dir(&pack_dir).len(), 2);
+    let repo = testutils::load_repo_at_head(&settings, &repo_path);
+    for op in [repo_a.operation(), repo_b.operation(), repo_c.operation()] {
+        let op_id_hex = op.id().hex();
+        assert_eq!(
+            op_walk::resolve_op_with_repo(&repo, &op_id_hex[..12]).unwrap(),
+            *op
+        );
+        assert!(repo.op_store().read_view(op.view_id()).is_ok());
+    }
+
+    // C is no longer reachable
+    op_store.gc(slice::from_ref(repo_b.op_id()), now).unwrap();
+    assert_eq!(list_dir(&pack_dir).len(), 2);
+    assert!(op_store.read_operation(repo_b.op_id()).is_ok());
+    assert!(op_store.read_operation(repo_c.op_id()).is_err());
+    assert!(op_store.read_view(repo_c.operation().view_id()).is_err());
+}
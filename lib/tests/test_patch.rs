@@ -0,0 +1,411 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use indoc::indoc;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::TreeValue;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::patch::apply_patch;
+use jj_lib::patch::format_binary_patch;
+use jj_lib::patch::parse_patches;
+use jj_lib::patch::FileMode;
+use jj_lib::patch::FilePatchContent;
+use jj_lib::patch::HunkLine;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+use testutils::create_tree;
+use testutils::read_file;
+use testutils::TestRepo;
+
+fn read_text(tree: &MergedTree, path: &RepoPath) -> Option<String> {
+    match tree.path_value(path).unwrap().into_resolved().unwrap() {
+        Some(TreeValue::File { id, .. }) => {
+            let contents = read_file(tree.store(), path, &id);
+            Some(String::from_utf8(contents).unwrap())
+        }
+        None => None,
+        Some(value) => panic!("unexpected value {value:?}"),
+    }
+}
+
+#[test]
+fn test_parse_mbox() {
+    let mbox = indoc! {"
+        From 0123456789abcdef0123456789abcdef01234567 Mon Sep 17 00:00:00 2001
+        From: Some One <some.one@example.com>
+        Date: Sat, 3 Feb 2001 04:05:06 +0700
+        Subject: [PATCH 1/2] Rename the file
+         and change it
+
+        With a body.
+        ---
+         file => renamed | 2 +-
+         1 file changed, 1 insertion(+), 1 deletion(-)
+
+        diff --git a/file b/renamed
+        rename from file
+        rename to renamed
+        index 1111111..2222222 100644
+        --- a/file
+        +++ b/renamed
+        @@ -1,2 +1,2 @@
+         a
+        -b
+        +c
+        From 0123456789abcdef0123456789abcdef01234568 Mon Sep 17 00:00:00 2001
+        From: Some One <some.one@example.com>
+        Date: Sat, 3 Feb 2001 04:05:07 +0700
+        Subject: [PATCH 2/2] Make it executable
+
+        ---
+        diff --git a/renamed b/renamed
+        old mode 100644
+        new mode 100755
+        --
+        2.46.0
+    "};
+    let patches = parse_patches(mbox.as_bytes()).unwrap();
+    assert_eq!(patches.len(), 2);
+
+    let author = patches[0].author.as_ref().unwrap();
+    assert_eq!(author.name, "Some One");
+    assert_eq!(author.email, "some.one@example.com");
+    assert_eq!(author.timestamp.tz_offset, 7 * 60);
+    assert_eq!(
+        patches[0].description,
+        "Rename the file and change it\n\nWith a body.\n"
+    );
+    let [file] = &patches[0].files[..] else {
+        panic!("expected a single file");
+    };
+    assert_eq!(
+        file.old_path,
+        Some(RepoPathBuf::from_internal_string("file"))
+    );
+    assert_eq!(
+        file.new_path,
+        Some(RepoPathBuf::from_internal_string("renamed"))
+    );
+    assert!(!file.is_copy);
+    let FilePatchContent::Text(hunks) = &file.content else {
+        panic!("expected a text patch");
+    };
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(hunks[0].old_start, 1);
+    assert_eq!(
+        hunks[0].lines,
+        vec![
+            HunkLine::Context("a\n".into()),
+            HunkLine::Removed("b\n".into()),
+            HunkLine::Added("c\n".into()),
+        ]
+    );
+
+    assert_eq!(patches[1].description, "Make it executable\n");
+    let [file] = &patches[1].files[..] else {
+        panic!("expected a single file");
+    };
+    assert_eq!(file.old_mode, Some(FileMode::Normal));
+    assert_eq!(file.new_mode, Some(FileMode::Executable));
+    assert_eq!(file.content, FilePatchContent::Text(vec![]));
+}
+
+#[test]
+fn test_parse_mbox_encoded_headers() {
+    let mbox = indoc! {"
+        From 0123456789abcdef0123456789abcdef01234567 Mon Sep 17 00:00:00 2001
+        From: =?UTF-8?q?J=C3=B6rg?= =?UTF-8?b?IE3DvGxsZXI=?= <jorg@example.com>
+        Date: Sat, 3 Feb 2001 04:05:06 +0700
+        Subject: [PATCH] =?UTF-8?q?Caf=C3=A9_menu?=
+         =?ISO-8859-1?Q?_=E9t=E9?= and =?X-UNKNOWN?q?more?=
+
+        >From the body
+        >>---
+        ---
+    "};
+    let patches = parse_patches(mbox.as_bytes()).unwrap();
+    let author = patches[0].author.as_ref().unwrap();
+    assert_eq!(author.name, "Jörg Müller");
+    assert_eq!(author.email, "jorg@example.com");
+    assert_eq!(
+        patches[0].description,
+        "Café menu été and =?X-UNKNOWN?q?more?=\n\nFrom the body\n>---\n"
+    );
+}
+
+#[test]
+fn test_parse_quoted_paths() {
+    let diff = indoc! {r#"
+        diff --git "a/caf\303\251 \"x\"" "b/caf\303\251 \"x\""
+        --- "a/caf\303\251 \"x\""
+        +++ "b/caf\303\251 \"x\""
+        @@ -1 +1 @@
+        -a
+        +b
+        diff --git a/old "b/new\tname"
+        rename from old
+        rename to "new\tname"
+    "#};
+    let patches = parse_patches(diff.as_bytes()).unwrap();
+    let files = &patches[0].files;
+    assert_eq!(files.len(), 2);
+    let quoted_path = RepoPathBuf::from_internal_string("café \"x\"");
+    assert_eq!(files[0].old_path.as_ref(), Some(&quoted_path));
+    assert_eq!(files[0].new_path.as_ref(), Some(&quoted_path));
+    assert_eq!(
+        files[1].old_path,
+        Some(RepoPathBuf::from_internal_string("old"))
+    );
+    assert_eq!(
+        files[1].new_path,
+        Some(RepoPathBuf::from_internal_string("new\tname"))
+    );
+
+    let diff = indoc! {r#"
+        --- "a/tab\there"
+        +++ "b/tab\there"	2001-02-03 04:05:06
+        @@ -1 +1 @@
+        -a
+        +b
+    "#};
+    let patches = parse_patches(diff.as_bytes()).unwrap();
+    assert_eq!(
+        patches[0].files[0].new_path,
+        Some(RepoPathBuf::from_internal_string("tab\there"))
+    );
+}
+
+#[test]
+fn test_parse_unified_diff() {
+    let diff = indoc! {"
+        --- a/file\t2001-02-03 04:05:06
+        +++ b/file\t2001-02-03 04:05:07
+        @@ -1 +1 @@
+        -old
+        \\ No newline at end of file
+        +new
+        --- /dev/null
+        +++ b/added
+        @@ -0,0 +1 @@
+        +added
+    "};
+    let patches = parse_patches(diff.as_bytes()).unwrap();
+    let [patch] = &patches[..] else {
+        panic!("expected a single patch");
+    };
+    assert_eq!(patch.author, None);
+    assert_eq!(patch.description, "");
+    assert_eq!(patch.files.len(), 2);
+    assert_eq!(
+        patch.files[0].content,
+        FilePatchContent::Text(vec![jj_lib::patch::Hunk {
+            old_start: 1,
+            lines: vec![
+                HunkLine::Removed("old".into()),
+                HunkLine::Added("new\n".into()),
+            ],
+        }])
+    );
+    assert_eq!(patch.files[1].old_path, None);
+    assert_eq!(
+        patch.files[1].new_path,
+        Some(RepoPathBuf::from_internal_string("added"))
+    );
+
+    let err = parse_patches(b"--- a/file\n+++ b/file\n@@ -1,2 +1,2 @@\n x\n").unwrap_err();
+    assert_eq!(err.to_string(), "Line 5: Unexpected end of hunk");
+}
+
+#[test]
+fn test_parse_binary_literal() {
+    let old = b"\0old binary contents\n".to_vec();
+    let new = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+    let diff = format!(
+        "diff --git a/file b/file\nindex 1111111..2222222 100644\n{}",
+        format_binary_patch(&old, &new)
+    );
+    let patches = parse_patches(diff.as_bytes()).unwrap();
+    assert_eq!(
+        patches[0].files[0].content,
+        FilePatchContent::BinaryLiteral(new)
+    );
+
+    let diff = "diff --git a/file b/file\nGIT binary patch\ndelta 5\nAAAAA\n\n";
+    let err = parse_patches(diff.as_bytes()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 3: Binary delta patches are not supported"
+    );
+}
+
+#[test]
+fn test_apply_patch() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let store = repo.store();
+    let file_path = RepoPath::from_internal_string("file");
+    let renamed_path = RepoPath::from_internal_string("renamed");
+    let deleted_path = RepoPath::from_internal_string("deleted");
+    let added_path = RepoPath::from_internal_string("added");
+
+    // Lines were added before the hunk since the patch was made
+    let tree = create_tree(
+        repo,
+        &[(file_path, "0\n1\n2\n3\n4\n"), (deleted_path, "deleted\n")],
+    );
+    let diff = indoc! {"
+        diff --git a/file b/renamed
+        rename from file
+        rename to renamed
+        --- a/file
+        +++ b/renamed
+        @@ -1,3 +1,3 @@
+         1
+        -2
+        +two
+         3
+        diff --git a/deleted b/deleted
+        deleted file mode 100644
+        --- a/deleted
+        +++ /dev/null
+        @@ -1 +0,0 @@
+        -deleted
+        diff --git a/added b/added
+        new file mode 100755
+        --- /dev/null
+        +++ b/added
+        @@ -0,0 +1 @@
+        +added
+    "};
+    let patches = parse_patches(diff.as_bytes()).unwrap();
+    let tree_id = apply_patch(store, &tree, &patches[0]).unwrap();
+    let new_tree = store.get_root_tree(&tree_id).unwrap();
+    assert_eq!(read_text(&new_tree, file_path), None);
+    assert_eq!(
+        read_text(&new_tree, renamed_path).as_deref(),
+        Some("0\n1\ntwo\n3\n4\n")
+    );
+    assert_eq!(read_text(&new_tree, deleted_path), None);
+    assert_eq!(read_text(&new_tree, added_path).as_deref(), Some("added\n"));
+    assert!(matches!(
+        new_tree.path_value(added_path).unwrap().into_resolved(),
+        Ok(Some(TreeValue::File {
+            executable: true,
+            ..
+        }))
+    ));
+
+    // The file to change doesn't exist
+    let err = apply_patch(store, &new_tree, &patches[0]).unwrap_err();
+    assert_eq!(err.to_string(), "Path 'file' doesn't exist");
+}
+
+#[test]
+fn test_apply_patch_conflict() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let store = repo.store();
+    let file_path = RepoPath::from_internal_string("file");
+
+    // The first hunk applies, but the lines of the second hunk were changed
+    let tree = create_tree(repo, &[(file_path, "a\nb\nc\nd\ne\nf\ng\nchanged\ni\n")]);
+    let diff = indoc! {"
+        --- a/file
+        +++ b/file
+        @@ -1,2 +1,2 @@
+        -a
+        +A
+         b
+        @@ -7,3 +7,3 @@
+         g
+        -h
+        +H
+         i
+    "};
+    let patches = parse_patches(diff.as_bytes()).unwrap();
+    let tree_id = apply_patch(store, &tree, &patches[0]).unwrap();
+    let new_tree = store.get_root_tree(&tree_id).unwrap();
+    let value = new_tree.path_value(file_path).unwrap();
+    assert!(!value.is_resolved());
+    let texts = value
+        .iter()
+        .map(|term| match term {
+            Some(TreeValue::File { id, .. }) => {
+                String::from_utf8(read_file(store, file_path, id)).unwrap()
+            }
+            _ => panic!("unexpected value {term:?}"),
+        })
+        .collect::<Vec<_>>();
+    // The sides are the current contents and the patch, with the hunk that
+    // applied included in all of them
+    assert_eq!(
+        texts,
+        vec![
+            "A\nb\nc\nd\ne\nf\ng\nchanged\ni\n",
+            "A\nb\nc\nd\ne\nf\ng\nh\ni\n",
+            "A\nb\nc\nd\ne\nf\ng\nH\ni\n",
+        ]
+    );
+}
+
+#[test]
+fn test_apply_patch_submodule() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let store = repo.store();
+    let submodule_path = RepoPath::from_internal_string("sub");
+    let added_path = RepoPath::from_internal_string("added");
+    let old_id = CommitId::from_hex("1111111111111111111111111111111111111111");
+    let new_id = CommitId::from_hex("2222222222222222222222222222222222222222");
+
+    let mut tree_builder = MergedTreeBuilder::new(store.empty_merged_tree_id());
+    tree_builder.set_or_remove(
+        submodule_path.to_owned(),
+        Merge::normal(TreeValue::GitSubmodule(old_id.clone())),
+    );
+    let tree = store
+        .get_root_tree(&tree_builder.write_tree(store).unwrap())
+        .unwrap();
+    let diff = indoc! {"
+        diff --git a/sub b/sub
+        index 1111111..2222222 160000
+        --- a/sub
+        +++ b/sub
+        @@ -1 +1 @@
+        -Subproject commit 1111111111111111111111111111111111111111
+        +Subproject commit 2222222222222222222222222222222222222222
+        diff --git a/added b/added
+        new file mode 160000
+        index 0000000..1111111
+        --- /dev/null
+        +++ b/added
+        @@ -0,0 +1 @@
+        +Subproject commit 1111111111111111111111111111111111111111
+    "};
+    let patches = parse_patches(diff.as_bytes()).unwrap();
+    assert_eq!(patches[0].files[1].new_mode, Some(FileMode::GitSubmodule));
+    let tree_id = apply_patch(store, &tree, &patches[0]).unwrap();
+    let new_tree = store.get_root_tree(&tree_id).unwrap();
+    assert_eq!(
+        new_tree.path_value(submodule_path).unwrap(),
+        Merge::normal(TreeValue::GitSubmodule(new_id))
+    );
+    assert_eq!(
+        new_tree.path_value(added_path).unwrap(),
+        Merge::normal(TreeValue::GitSubmodule(old_id))
+    );
+}
@@ -0,0 +1,252 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::backend::TreeValue;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::rerere::forget_resolutions;
+use jj_lib::settings::UserSettings;
+use testutils::create_random_commit;
+use testutils::create_tree;
+use testutils::read_file;
+use testutils::TestRepo;
+
+fn read_resolved(tree: &MergedTree, path: &RepoPath) -> Option<String> {
+    match tree.path_value(path).unwrap().into_resolved() {
+        Ok(Some(TreeValue::File { id, .. })) => {
+            Some(String::from_utf8(read_file(tree.store(), path, &id)).unwrap())
+        }
+        _ => None,
+    }
+}
+
+fn settings_with_reuse() -> UserSettings {
+    let config = testutils::base_config()
+        .set_override("merge.reuse-resolutions", true)
+        .unwrap()
+        .build()
+        .unwrap();
+    UserSettings::from_config(config)
+}
+
+#[test]
+fn test_reuse_recorded_resolution() {
+    let settings = settings_with_reuse();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    let store = repo.store();
+    let path = RepoPath::from_internal_string("file");
+
+    let base = create_tree(repo, &[(path, "1\n2\n3\n4\n5\n")]);
+    let side1 = create_tree(repo, &[(path, "1\nA\n3\n4\n5\n")]);
+    let side2 = create_tree(repo, &[(path, "1\nB\n3\n4\n5\n")]);
+    let conflicted = side1.merge(&base, &side2).unwrap();
+    assert_eq!(read_resolved(&conflicted, path), None);
+
+    // Resolving the conflict in a successor records the resolution, even if
+    // other lines were changed along with it
+    let mut tx = repo.start_transaction(&settings);
+    let commit = create_random_commit(tx.repo_mut(), &settings)
+        .set_tree_id(conflicted.id())
+        .write()
+        .unwrap();
+    let resolved = create_tree(repo, &[(path, "1\nA and B\n3\n4\n5\n")]);
+    tx.repo_mut()
+        .rewrite_commit(&settings, &commit)
+        .set_tree_id(resolved.id())
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants(&settings).unwrap();
+
+    // The resolution isn't recorded until the transaction is committed
+    let merged = side1.merge(&base, &side2).unwrap();
+    assert_eq!(read_resolved(&merged, path), None);
+    tx.commit("resolve conflict");
+
+    // The same conflict is resolved when merging again, including when the
+    // conflict appears among other changes
+    let merged = side1.merge(&base, &side2).unwrap();
+    assert_eq!(
+        read_resolved(&merged, path).as_deref(),
+        Some("1\nA and B\n3\n4\n5\n")
+    );
+    let resolution_store = store.resolution_store().unwrap();
+    assert_eq!(resolution_store.take_applied_paths(), vec![path.to_owned()]);
+    assert_eq!(resolution_store.take_applied_paths(), vec![]);
+    let side2_with_more = create_tree(repo, &[(path, "1\nB\n3\n4\nmore\n")]);
+    let merged = side1.merge(&base, &side2_with_more).unwrap();
+    assert_eq!(
+        read_resolved(&merged, path).as_deref(),
+        Some("1\nA and B\n3\n4\nmore\n")
+    );
+
+    // Other conflicts aren't resolved
+    let side3 = create_tree(repo, &[(path, "1\nC\n3\n4\n5\n")]);
+    let merged = side1.merge(&base, &side3).unwrap();
+    assert_eq!(read_resolved(&merged, path), None);
+
+    // Forgotten resolutions aren't reused
+    assert_eq!(
+        forget_resolutions(store, resolution_store, &conflicted, &[path]).unwrap(),
+        1
+    );
+    let merged = side1.merge(&base, &side2).unwrap();
+    assert_eq!(read_resolved(&merged, path), None);
+}
+
+#[test]
+fn test_record_resolution_in_child() {
+    let settings = settings_with_reuse();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    let path = RepoPath::from_internal_string("file");
+
+    let base = create_tree(repo, &[(path, "1\n2\n3\n")]);
+    let side1 = create_tree(repo, &[(path, "1\nA\n3\n")]);
+    let side2 = create_tree(repo, &[(path, "1\nB\n3\n")]);
+    let conflicted = side1.merge(&base, &side2).unwrap();
+
+    // The conflict is resolved in a new child of the conflicted commit, like
+    // in a working-copy commit created on top of it
+    let mut tx = repo.start_transaction(&settings);
+    let commit = create_random_commit(tx.repo_mut(), &settings)
+        .set_tree_id(conflicted.id())
+        .write()
+        .unwrap();
+    let child = tx
+        .repo_mut()
+        .new_commit(&settings, vec![commit.id().clone()], conflicted.id())
+        .write()
+        .unwrap();
+    let resolved = create_tree(repo, &[(path, "1\nA and B\n3\n")]);
+    tx.repo_mut()
+        .rewrite_commit(&settings, &child)
+        .set_tree_id(resolved.id())
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants(&settings).unwrap();
+    tx.commit("resolve conflict in child");
+    let merged = side1.merge(&base, &side2).unwrap();
+    assert_eq!(
+        read_resolved(&merged, path).as_deref(),
+        Some("1\nA and B\n3\n")
+    );
+}
+
+#[test]
+fn test_record_resolution_in_rebased_commit() {
+    let settings = settings_with_reuse();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    let path = RepoPath::from_internal_string("file");
+
+    let base = create_tree(repo, &[(path, "1\n2\n3\n")]);
+    let side1 = create_tree(repo, &[(path, "1\nA\n3\n")]);
+    let side2 = create_tree(repo, &[(path, "1\nB\n3\n")]);
+    let conflicted = side1.merge(&base, &side2).unwrap();
+
+    // The conflict disappears while the commit is moved to other parents
+    let mut tx = repo.start_transaction(&settings);
+    let commit = create_random_commit(tx.repo_mut(), &settings)
+        .set_tree_id(conflicted.id())
+        .write()
+        .unwrap();
+    let new_parent = create_random_commit(tx.repo_mut(), &settings)
+        .write()
+        .unwrap();
+    let resolved = create_tree(repo, &[(path, "1\nA and B\n3\n")]);
+    tx.repo_mut()
+        .rewrite_commit(&settings, &commit)
+        .set_parents(vec![new_parent.id().clone()])
+        .set_tree_id(resolved.id())
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants(&settings).unwrap();
+    tx.commit("rebase and resolve conflict");
+    let merged = side1.merge(&base, &side2).unwrap();
+    assert_eq!(
+        read_resolved(&merged, path).as_deref(),
+        Some("1\nA and B\n3\n")
+    );
+}
+
+#[test]
+fn test_record_resolution_with_changed_context() {
+    let settings = settings_with_reuse();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    let path = RepoPath::from_internal_string("file");
+
+    let base = create_tree(repo, &[(path, "1\n2\n3\n")]);
+    let side1 = create_tree(repo, &[(path, "1\nA\n3\n")]);
+    let side2 = create_tree(repo, &[(path, "1\nB\n3\n")]);
+    let conflicted = side1.merge(&base, &side2).unwrap();
+
+    // The lines around the conflict were changed too, so the resolution of the
+    // conflict can't be told apart from the other changes
+    let mut tx = repo.start_transaction(&settings);
+    let commit = create_random_commit(tx.repo_mut(), &settings)
+        .set_tree_id(conflicted.id())
+        .write()
+        .unwrap();
+    let resolved = create_tree(repo, &[(path, "one\nA and B\n3\n")]);
+    tx.repo_mut()
+        .rewrite_commit(&settings, &commit)
+        .set_tree_id(resolved.id())
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants(&settings).unwrap();
+    tx.commit("resolve conflict");
+    let merged = side1.merge(&base, &side2).unwrap();
+    assert_eq!(read_resolved(&merged, path), None);
+}
+
+#[test]
+fn test_discarded_resolution() {
+    let settings = settings_with_reuse();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    let path = RepoPath::from_internal_string("file");
+
+    let base = create_tree(repo, &[(path, "1\n2\n3\n")]);
+    let side1 = create_tree(repo, &[(path, "1\nA\n3\n")]);
+    let side2 = create_tree(repo, &[(path, "1\nB\n3\n")]);
+    let conflicted = side1.merge(&base, &side2).unwrap();
+
+    // Resolutions made in a transaction that is never committed aren't
+    // recorded
+    let mut tx = repo.start_transaction(&settings);
+    let commit = create_random_commit(tx.repo_mut(), &settings)
+        .set_tree_id(conflicted.id())
+        .write()
+        .unwrap();
+    let resolved = create_tree(repo, &[(path, "1\nA and B\n3\n")]);
+    tx.repo_mut()
+        .rewrite_commit(&settings, &commit)
+        .set_tree_id(resolved.id())
+        .write()
+        .unwrap();
+    drop(tx);
+    let merged = side1.merge(&base, &side2).unwrap();
+    assert_eq!(read_resolved(&merged, path), None);
+}
+
+#[test]
+fn test_reuse_resolutions_disabled() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    assert!(repo.store().resolution_store().is_none());
+}
@@ -19,6 +19,7 @@ use assert_matches::assert_matches;
 use chrono::DateTime;
 use itertools::Itertools;
 use jj_lib::backend::CommitId;
+use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::MillisSinceEpoch;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
@@ -51,11 +52,16 @@ use jj_lib::revset::RevsetResolutionError;
 use jj_lib::revset::RevsetWorkspaceContext;
 use jj_lib::revset::SymbolResolverExtension;
 use jj_lib::settings::GitSettings;
+use jj_lib::settings::UserSettings;
+use jj_lib::tree_builder::TreeBuilder;
 use jj_lib::workspace::Workspace;
 use test_case::test_case;
 use testutils::create_random_commit;
 use testutils::create_tree;
+use testutils::write_executable_file;
+use testutils::write_normal_file;
 use testutils::write_random_commit;
+use testutils::write_symlink;
 use testutils::CommitGraphBuilder;
 use testutils::TestRepo;
 use testutils::TestRepoBackend;
@@ -2011,6 +2017,104 @@ fn test_evaluate_expression_git_head() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_at_operation() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo0 = &test_repo.repo;
+    let root_commit_id = repo0.store().root_commit_id().clone();
+
+    let mut tx = repo0.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit1 = write_random_commit(mut_repo, &settings);
+    let commit2 = write_random_commit(mut_repo, &settings);
+    mut_repo.set_local_bookmark_target("bookmark1", RefTarget::normal(commit1.id().clone()));
+    let repo1 = tx.commit("test");
+
+    let mut tx = repo1.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit3 = create_random_commit(mut_repo, &settings)
+        .set_parents(vec![commit1.id().clone()])
+        .write()
+        .unwrap();
+    mut_repo.record_abandoned_commit(commit2.id().clone());
+    mut_repo.rebase_descendants(&settings).unwrap();
+    mut_repo.set_local_bookmark_target("bookmark1", RefTarget::normal(commit3.id().clone()));
+    let repo2 = tx.commit("test");
+
+    // Symbols and visible heads are resolved at the given operation
+    assert_eq!(
+        resolve_commit_ids(repo2.as_ref(), "at_operation(@-, bookmark1)"),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(repo2.as_ref(), "at_operation(@-, visible_heads())"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(repo2.as_ref(), "at_operation(@-, all())"),
+        vec![
+            commit2.id().clone(),
+            commit1.id().clone(),
+            root_commit_id.clone(),
+        ]
+    );
+    assert_eq!(
+        resolve_commit_ids(repo2.as_ref(), "at_operation(@-, ~bookmark1)"),
+        vec![commit2.id().clone(), root_commit_id.clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(repo2.as_ref(), "at_operation(@--, all())"),
+        vec![root_commit_id.clone()]
+    );
+    let op_id_hex = repo1.op_id().hex();
+    assert_eq!(
+        resolve_commit_ids(
+            repo2.as_ref(),
+            &format!("at_operation({op_id_hex}, bookmarks())")
+        ),
+        vec![commit1.id().clone()]
+    );
+
+    // Can be mixed with expressions evaluated at the current operation
+    assert_eq!(
+        resolve_commit_ids(
+            repo2.as_ref(),
+            "bookmarks() | at_operation(@-, bookmarks())"
+        ),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(repo2.as_ref(), "all() ~ at_operation(@-, all())"),
+        vec![commit3.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            repo2.as_ref(),
+            "at_operation(@-, at_operation(@, bookmark1) | visible_heads())"
+        ),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+
+    // Unknown operation
+    let aliases_map = RevsetAliasesMap::default();
+    let revset_extensions = RevsetExtensions::default();
+    let context = RevsetParseContext::new(
+        &aliases_map,
+        settings.user_email(),
+        chrono::Utc::now().fixed_offset().into(),
+        &revset_extensions,
+        None,
+    );
+    let expression = parse("at_operation(@----, all())", &context).unwrap();
+    let symbol_resolver =
+        DefaultSymbolResolver::new(repo2.as_ref(), revset_extensions.symbol_resolvers());
+    assert_matches!(
+        expression.resolve_user_expression(repo2.as_ref(), &symbol_resolver),
+        Err(RevsetResolutionError::Other(_))
+    );
+}
+
 #[test]
 fn test_evaluate_expression_bookmarks() {
     let settings = testutils::user_settings();
@@ -2367,6 +2471,58 @@ fn test_evaluate_expression_latest() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_bisect() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let mut graph_builder = CommitGraphBuilder::new(&settings, mut_repo);
+    let commit1 = graph_builder.initial_commit();
+    let commit2 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit3 = graph_builder.commit_with_parents(&[&commit2]);
+    let commit4 = graph_builder.commit_with_parents(&[&commit3]);
+    let commit5 = graph_builder.commit_with_parents(&[&commit4]);
+    let commit6 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit7 = graph_builder.commit_with_parents(&[&commit5, &commit6]);
+
+    // Bisection of an empty set is an empty set
+    assert_eq!(resolve_commit_ids(mut_repo, "bisect(none())"), vec![]);
+
+    // Bisection of a single commit is that commit
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("bisect({})", commit3.id().hex())),
+        vec![commit3.id().clone()]
+    );
+
+    // Linear history is split in half
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("bisect({}::{})", commit1.id().hex(), commit5.id().hex())
+        ),
+        vec![commit3.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("bisect({}::{})", commit2.id().hex(), commit5.id().hex())
+        ),
+        vec![commit4.id().clone()]
+    );
+
+    // Non-linear history picks the commit of the median generation
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("bisect({}::{})", commit1.id().hex(), commit7.id().hex())
+        ),
+        vec![commit3.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_merges() {
     let settings = testutils::user_settings();
@@ -3201,6 +3357,185 @@ fn test_evaluate_expression_file() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_file_with_changed_path_index() {
+    let config = testutils::base_config()
+        .set_override("index.changed-paths", true)
+        .unwrap()
+        .build()
+        .unwrap();
+    let settings = UserSettings::from_config(config);
+    let test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+
+    let file1 = RepoPath::from_internal_string("dir/file1");
+    let file2 = RepoPath::from_internal_string("file2");
+    let tree1 = create_tree(repo, &[(file1, "1\n")]);
+    let tree2 = create_tree(repo, &[(file1, "1\n"), (file2, "2\n")]);
+    let tree3 = create_tree(repo, &[(file1, "1\n3\n"), (file2, "2\n")]);
+    let commit1 = mut_repo
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            tree1.id(),
+        )
+        .write()
+        .unwrap();
+    let commit2 = mut_repo
+        .new_commit(&settings, vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let commit3 = mut_repo
+        .new_commit(&settings, vec![commit2.id().clone()], tree3.id())
+        .write()
+        .unwrap();
+    let commit4 = mut_repo
+        .new_commit(&settings, vec![commit3.id().clone()], tree3.id())
+        .write()
+        .unwrap();
+    let repo = tx.commit("test");
+
+    let query = |revset_str: &str| {
+        resolve_commit_ids_in_workspace(
+            repo.as_ref(),
+            revset_str,
+            &test_workspace.workspace,
+            Some(test_workspace.workspace.workspace_root()),
+        )
+    };
+
+    assert_eq!(
+        query("file(dir)"),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(query("file(file2)"), vec![commit2.id().clone()]);
+    assert_eq!(
+        query("file(all())"),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+    assert_eq!(query("file(none())"), vec![]);
+    assert_eq!(
+        query("~empty()"),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+    assert_eq!(
+        query("empty() & descendants(file(file2))"),
+        vec![commit4.id().clone()]
+    );
+    // File predicates can't be tested against the index
+    assert_eq!(
+        query("file(dir | executable())"),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+
+    assert_eq!(query("diff_contains(3)"), vec![commit3.id().clone()]);
+    assert_eq!(query("diff_contains(2, dir)"), vec![]);
+    assert_eq!(query("diff_contains(2, file2)"), vec![commit2.id().clone()]);
+}
+
+#[test]
+fn test_evaluate_expression_file_predicates() {
+    let settings = testutils::user_settings();
+    let test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo;
+    let store = repo.store();
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+
+    let text_path = RepoPath::from_internal_string("text");
+    let binary_path = RepoPath::from_internal_string("binary");
+    let executable_path = RepoPath::from_internal_string("executable");
+    let symlink_path = RepoPath::from_internal_string("symlink");
+    let large_path = RepoPath::from_internal_string("large");
+    let mut write_commit = |parent: &Commit, write: &dyn Fn(&mut TreeBuilder)| {
+        let parent_tree = parent.tree().unwrap();
+        let parent_tree_id = parent_tree.as_merge().as_resolved().unwrap().id();
+        let mut tree_builder = store.tree_builder(parent_tree_id.clone());
+        write(&mut tree_builder);
+        let tree_id = tree_builder.write_tree().unwrap();
+        mut_repo
+            .new_commit(
+                &settings,
+                vec![parent.id().clone()],
+                MergedTreeId::resolved(tree_id),
+            )
+            .write()
+            .unwrap()
+    };
+    let root_commit = store.root_commit();
+    let commit_text = write_commit(&root_commit, &|builder| {
+        write_normal_file(builder, text_path, "text\n");
+    });
+    let commit_binary = write_commit(&root_commit, &|builder| {
+        write_normal_file(builder, binary_path, "bin\0ary");
+    });
+    let commit_removed_binary = write_commit(&commit_binary, &|builder| {
+        builder.remove(binary_path.to_owned());
+    });
+    let commit_executable = write_commit(&root_commit, &|builder| {
+        write_executable_file(builder, executable_path, "#!/bin/sh\n");
+    });
+    let commit_symlink = write_commit(&root_commit, &|builder| {
+        write_symlink(builder, symlink_path, "text");
+    });
+    let commit_large = write_commit(&root_commit, &|builder| {
+        write_normal_file(builder, large_path, &"x".repeat(2000));
+    });
+
+    let resolve = |revset_str: &str| {
+        resolve_commit_ids_in_workspace(mut_repo, revset_str, &test_workspace.workspace, None)
+    };
+
+    // Predicates match files on either side of the diff
+    assert_eq!(
+        resolve("file(binary())"),
+        vec![
+            commit_removed_binary.id().clone(),
+            commit_binary.id().clone()
+        ]
+    );
+    assert_eq!(
+        resolve("file(executable())"),
+        vec![commit_executable.id().clone()]
+    );
+    assert_eq!(
+        resolve("file(symlink())"),
+        vec![commit_symlink.id().clone()]
+    );
+    assert_eq!(resolve("file(submodule())"), vec![]);
+    assert_eq!(resolve("file(conflicted())"), vec![]);
+    assert_eq!(
+        resolve(r#"file(size(">1KiB"))"#),
+        vec![commit_large.id().clone()]
+    );
+    assert_eq!(
+        resolve(r#"file(size("<=5"))"#),
+        vec![commit_text.id().clone()]
+    );
+
+    // Predicates can be combined with patterns
+    assert_eq!(
+        resolve("file(~binary() ~ symlink() ~ large)"),
+        vec![commit_executable.id().clone(), commit_text.id().clone()]
+    );
+    assert_eq!(
+        resolve("file(root:text & ~binary())"),
+        vec![commit_text.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_diff_contains() {
     let settings = testutils::user_settings();
@@ -3347,6 +3682,67 @@ fn test_evaluate_expression_diff_contains() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_diff_contains_ignore_whitespace() {
+    let settings = testutils::user_settings();
+    let test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+
+    let file_path = RepoPath::from_internal_string("file");
+    let tree1 = create_tree(repo, &[(file_path, "foo(a, b)\n")]);
+    let tree2 = create_tree(repo, &[(file_path, "foo(a,  b)  \n")]);
+    let tree3 = create_tree(repo, &[(file_path, "    foo(a,b)\n")]);
+    let commit1 = mut_repo
+        .new_commit(
+            &settings,
+            vec![repo.store().root_commit_id().clone()],
+            tree1.id(),
+        )
+        .write()
+        .unwrap();
+    let commit2 = mut_repo
+        .new_commit(&settings, vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let commit3 = mut_repo
+        .new_commit(&settings, vec![commit2.id().clone()], tree3.id())
+        .write()
+        .unwrap();
+
+    let query = |revset_str: &str| {
+        resolve_commit_ids_in_workspace(
+            mut_repo,
+            revset_str,
+            &test_workspace.workspace,
+            Some(test_workspace.workspace.workspace_root()),
+        )
+    };
+
+    assert_eq!(
+        query("diff_contains('foo')"),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone(),
+        ]
+    );
+    assert_eq!(
+        query("diff_contains('foo', whitespace=ignore-space-change)"),
+        vec![commit3.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        query("diff_contains('foo', 'file', 'ignore-all-space')"),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        query("diff_contains('foo', whitespace='exact')"),
+        query("diff_contains('foo')")
+    );
+}
+
 #[test]
 fn test_evaluate_expression_file_merged_parents() {
     let settings = testutils::user_settings();
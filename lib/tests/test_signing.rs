@@ -2,11 +2,15 @@ use jj_lib::backend::MillisSinceEpoch;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
 use jj_lib::repo::Repo;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::RevsetFilterPredicate;
+use jj_lib::revset::SignaturePredicate;
 use jj_lib::settings::UserSettings;
 use jj_lib::signing::SigStatus;
 use jj_lib::signing::SignBehavior;
 use jj_lib::signing::Signer;
 use jj_lib::signing::Verification;
+use jj_lib::str_util::StringPattern;
 use test_case::test_case;
 use testutils::create_random_commit;
 use testutils::test_signing_backend::TestSigningBackend;
@@ -177,3 +181,61 @@ fn configured(backend: TestRepoBackend) {
     let commit = repo.store().get_commit(commit.id()).unwrap();
     assert_eq!(commit.verification().unwrap(), good_verification());
 }
+
+#[test_case(TestRepoBackend::Local ; "local backend")]
+#[test_case(TestRepoBackend::Git ; "git backend")]
+fn revset_predicates(backend: TestRepoBackend) {
+    let settings = user_settings(false);
+
+    let signer = Signer::new(Some(Box::new(TestSigningBackend)), vec![]);
+    let test_workspace = TestWorkspace::init_with_backend_and_signer(&settings, backend, signer);
+
+    let repo = &test_workspace.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let signed_commit = create_random_commit(mut_repo, &settings)
+        .set_sign_behavior(SignBehavior::Own)
+        .write()
+        .unwrap();
+    // Unsigned commits never match.
+    write_random_commit(mut_repo, &settings);
+
+    let evaluate = |predicate: SignaturePredicate| {
+        RevsetExpression::filter(RevsetFilterPredicate::Signature(predicate))
+            .evaluate_programmatic(mut_repo)
+            .unwrap()
+            .iter()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        evaluate(SignaturePredicate::Signed),
+        vec![signed_commit.id().clone()]
+    );
+    assert_eq!(
+        evaluate(SignaturePredicate::Good),
+        vec![signed_commit.id().clone()]
+    );
+    assert_eq!(
+        evaluate(SignaturePredicate::SignedBy(StringPattern::exact(
+            "impeccable"
+        ))),
+        vec![signed_commit.id().clone()]
+    );
+    assert_eq!(
+        evaluate(SignaturePredicate::SignedBy(StringPattern::exact("other"))),
+        vec![]
+    );
+    assert_eq!(
+        evaluate(SignaturePredicate::SignedBy(StringPattern::exact(
+            "peccable"
+        ))),
+        vec![]
+    );
+    assert_eq!(
+        evaluate(SignaturePredicate::SignedBy(StringPattern::substring(
+            "peccable"
+        ))),
+        vec![signed_commit.id().clone()]
+    );
+}
@@ -140,7 +140,7 @@ impl TestRepoBackend {
     ) -> Result<Box<dyn Backend>, BackendInitError> {
         match self {
             TestRepoBackend::Git => Ok(Box::new(GitBackend::init_internal(settings, store_path)?)),
-            TestRepoBackend::Local => Ok(Box::new(LocalBackend::init(store_path))),
+            TestRepoBackend::Local => Ok(Box::new(LocalBackend::init(settings, store_path))),
             TestRepoBackend::Test => Ok(Box::new(TestBackend::init(store_path))),
         }
     }
@@ -407,6 +407,7 @@ pub fn commit_with_tree(store: &Arc<Store>, tree_id: MergedTreeId) -> Commit {
         author: signature.clone(),
         committer: signature,
         secure_sig: None,
+        copies: vec![],
     };
     store.write_commit(commit, None).block_on().unwrap()
 }